@@ -1,8 +1,18 @@
 import bpy
 from bpy.types import Context, Menu
 
-from . import preferences, importer, tools, benchmark
-from .importer import ImportMdl, ImportVmf, ImportVmt, ImportVtf
+from . import preferences, importer, exporter, tools, benchmark
+from .exporter import ExportVmf
+from .importer import (
+    ImportAin,
+    ImportGoldSrcBsp,
+    ImportMdl,
+    ImportNav,
+    ImportVmap,
+    ImportVmf,
+    ImportVmt,
+    ImportVtf,
+)
 from .tools import IMPORT_MT_plumber_browse
 
 
@@ -14,6 +24,14 @@ class IMPORT_MT_plumber(Menu):
         self.layout.operator(
             ImportVmf.bl_idname, text="Valve Map Format (.vmf)"
         ).from_game_fs = False
+        quake_map = self.layout.operator(
+            ImportVmf.bl_idname, text="Quake / Half-Life Map (.map)"
+        )
+        quake_map.from_game_fs = False
+        quake_map.filter_glob = "*.map"
+        rmf = self.layout.operator(ImportVmf.bl_idname, text="Hammer Map (.rmf)")
+        rmf.from_game_fs = False
+        rmf.filter_glob = "*.rmf"
         self.layout.operator(
             ImportMdl.bl_idname, text="Source Model (.mdl)"
         ).from_game_fs = False
@@ -23,6 +41,18 @@ class IMPORT_MT_plumber(Menu):
         self.layout.operator(
             ImportVtf.bl_idname, text="Valve Texture Format (.vtf)"
         ).from_game_fs = False
+        self.layout.operator(
+            ImportNav.bl_idname, text="Navigation Mesh (.nav)"
+        ).from_game_fs = False
+        self.layout.operator(
+            ImportAin.bl_idname, text="AI Node Graph (.ain)"
+        ).from_game_fs = False
+        self.layout.operator(
+            ImportGoldSrcBsp.bl_idname, text="GoldSrc Map (.bsp)"
+        ).from_game_fs = False
+        self.layout.operator(
+            ImportVmap.bl_idname, text="Source 2 Map (.vmap)"
+        ).from_game_fs = False
 
         self.layout.menu(IMPORT_MT_plumber_browse.bl_idname)
 
@@ -31,21 +61,29 @@ def menu_func_import(self: Menu, context: Context):
     self.layout.menu(IMPORT_MT_plumber.bl_idname)
 
 
+def menu_func_export(self: Menu, context: Context):
+    self.layout.operator(ExportVmf.bl_idname, text="Plumber Valve Map Format (.vmf)")
+
+
 def register():
     preferences.register()
     importer.register()
+    exporter.register()
     tools.register()
     benchmark.register()
 
     bpy.utils.register_class(IMPORT_MT_plumber)
     bpy.types.TOPBAR_MT_file_import.append(menu_func_import)
+    bpy.types.TOPBAR_MT_file_export.append(menu_func_export)
 
 
 def unregister():
+    bpy.types.TOPBAR_MT_file_export.remove(menu_func_export)
     bpy.types.TOPBAR_MT_file_import.remove(menu_func_import)
     bpy.utils.unregister_class(IMPORT_MT_plumber)
 
     benchmark.unregister()
     tools.unregister()
+    exporter.unregister()
     importer.unregister()
     preferences.unregister()
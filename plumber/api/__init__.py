@@ -7,7 +7,7 @@ import functionality from external scripts and addons.
 Main components:
 - Games: Access game definitions from Plumber preferences
 - GameFileSystem: Interface for browsing and reading game files
-- Import functions: Import individual assets or batches of assets
+- Import functions: Import individual assets or batches of assets, or a skybox by name
 - ParallelImportBuilder: Build custom parallel import processes
 """
 
@@ -15,9 +15,12 @@ from .games import Games, Game
 from .filesystem import GameFileSystem, FileBrowserEntry
 from .importer import (
     import_vmf,
+    import_vmf_diff,
+    import_vmf_data,
     import_mdl,
     import_vmt,
     import_vtf,
+    import_sky,
     ParallelImportBuilder,
 )
 from .exceptions import (
@@ -25,6 +28,7 @@ from .exceptions import (
     GameNotFoundError,
     FileSystemError,
     AssetImportError,
+    StrictAssetImportError,
 )
 
 __all__ = [
@@ -35,13 +39,17 @@ __all__ = [
     "FileBrowserEntry",
     # Import functions
     "import_vmf",
+    "import_vmf_diff",
+    "import_vmf_data",
     "import_mdl",
     "import_vmt",
     "import_vtf",
+    "import_sky",
     "ParallelImportBuilder",
     # Exceptions
     "PlumberAPIError",
     "GameNotFoundError",
     "FileSystemError",
     "AssetImportError",
+    "StrictAssetImportError",
 ]
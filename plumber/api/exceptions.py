@@ -25,3 +25,16 @@ class AssetImportError(PlumberAPIError):
     """Raised when there's an error during asset import."""
 
     pass
+
+
+class StrictAssetImportError(AssetImportError):
+    """Raised when an asset fails to import with strict mode enabled.
+
+    The failed asset is described by ``asset_kind``, ``asset_id`` and ``text``.
+    """
+
+    def __init__(self, message: str, diagnostic) -> None:
+        super().__init__(message)
+        self.asset_kind: str = diagnostic.asset_kind()
+        self.asset_id = diagnostic.asset_id()
+        self.text: str = diagnostic.text()
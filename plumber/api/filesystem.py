@@ -2,7 +2,9 @@
 Game file system interface for browsing and reading files.
 """
 
-from typing import List, Tuple
+import json
+import os
+from typing import Any, Dict, List, Optional, Tuple
 
 from .exceptions import FileSystemError
 
@@ -138,6 +140,119 @@ class GameFileSystem:
         """Get the search paths as (kind, path) tuples."""
         return self._fs.search_paths()
 
+    @property
+    def resolved_search_paths(self) -> List[Tuple[str, str]]:
+        """
+        Get the search paths in priority order as (kind, path) tuples,
+        with wildcard search paths expanded into the directories and VPKs they match.
+        """
+        return self._fs.resolved_search_paths()
+
+    def find_file(self, path: str) -> List[Tuple[str, str]]:
+        """
+        Find every search path containing a file, to see which one is used.
+
+        Args:
+            path: Game path of the file, for example materials/dev/dev_floor.vmt
+
+        Returns:
+            (kind, path) tuples of the search paths containing the file in priority order.
+            The first one is used when importing, the others are overridden by it.
+        """
+        return self._fs.find_file(path)
+
+    def mount_vpk(self, vpk_path: str) -> None:
+        """
+        Append a .vpk archive to the search paths of this file system.
+
+        Files in the archive have the lowest priority, so they don't replace
+        files already in the game. Imports started after this see the archive.
+
+        Args:
+            vpk_path: Path to the .vpk archive, for multi-part archives the _dir.vpk file
+
+        Raises:
+            FileSystemError: If the archive doesn't exist
+        """
+        if not os.path.isfile(vpk_path):
+            raise FileSystemError(f"Failed to mount vpk '{vpk_path}': file not found")
+
+        self._mount(("VPK", vpk_path))
+
+    def mount_gma(self, gma_path: str) -> None:
+        """
+        Append a Garry's Mod .gma addon to the search paths of this file system.
+
+        The addon is extracted into a cache directory the first time it's mounted.
+        Files in it have the lowest priority, so they don't replace files already
+        in the game. Imports started after this see the addon.
+
+        Args:
+            gma_path: Path to the .gma addon
+
+        Raises:
+            FileSystemError: If the addon doesn't exist or can't be extracted
+        """
+        if not os.path.isfile(gma_path):
+            raise FileSystemError(f"Failed to mount gma '{gma_path}': file not found")
+
+        self._mount(("GMA", gma_path))
+
+    def mount_directory(self, directory: str) -> None:
+        """
+        Append a loose directory to the search paths of this file system.
+
+        The directory should have the layout of a game directory, for example
+        containing materials/ and models/ directories. Files in it have the lowest
+        priority, so they don't replace files already in the game.
+        Imports started after this see the directory.
+
+        Args:
+            directory: Path to the directory
+
+        Raises:
+            FileSystemError: If the directory doesn't exist
+        """
+        if not os.path.isdir(directory):
+            raise FileSystemError(
+                f"Failed to mount directory '{directory}': directory not found"
+            )
+
+        self._mount(("DIR", directory))
+
+    def mount_workshop(self, directory: str) -> None:
+        """
+        Append the subscribed items of a workshop content directory to the search paths
+        of this file system, for example steamapps/workshop/content/4000 for Garry's Mod.
+
+        Each item directory is mounted, along with the .gma addons inside them.
+
+        Args:
+            directory: Path to the workshop content directory of a game
+
+        Raises:
+            FileSystemError: If the directory can't be read
+        """
+        try:
+            self._fs = self._fs.with_workshop(directory)
+        except Exception as e:
+            raise FileSystemError(
+                f"Failed to mount workshop content '{directory}': {e}"
+            ) from e
+
+        self._browser = None
+
+    def _mount(self, search_path: Tuple[str, str]) -> None:
+        try:
+            from ..plumber import FileSystem
+
+            self._fs = FileSystem(self.name, self.search_paths + [search_path])
+        except Exception as e:
+            raise FileSystemError(f"Failed to mount '{search_path[1]}': {e}") from e
+
+        # the browser was opened with the previous search paths
+        self._browser = None
+
     def browse_directory(self, directory: str = "") -> List[FileBrowserEntry]:
         """
         Browse a directory in the game file system.
@@ -152,10 +267,7 @@ class GameFileSystem:
             FileSystemError: If directory browsing fails
         """
         try:
-            if self._browser is None:
-                self._browser = self._fs.browse()
-
-            entries_internal = self._browser.read_dir(directory)
+            entries_internal = self._get_browser().read_dir(directory)
             entries = []
 
             for entry in entries_internal:
@@ -216,10 +328,81 @@ class GameFileSystem:
             True if file exists, False otherwise
         """
         try:
-            return self._fs.file_exists(filepath)
-        except FileSystemError:
+            return self._get_browser().file_exists(filepath)
+        except Exception:
+            return False
+
+    def directory_exists(self, directory: str) -> bool:
+        """
+        Check if a directory exists in the game file system.
+
+        Args:
+            directory: Path to check
+
+        Returns:
+            True if the directory exists and isn't empty, False otherwise
+        """
+        try:
+            return self._get_browser().dir_exists(directory)
+        except Exception:
             return False
 
+    def list_files(
+        self,
+        directory: str = "",
+        extensions: Optional[List[str]] = None,
+        recursive: bool = False,
+    ) -> List[str]:
+        """
+        List the files in a directory of the game file system.
+
+        Args:
+            directory: Directory path to list (empty for root)
+            extensions: Only list files with these extensions, without the dot.
+                All files are listed if not given
+            recursive: Whether to also list the files in subdirectories
+
+        Returns:
+            Sorted game paths of the files
+
+        Raises:
+            FileSystemError: If listing the directory fails
+        """
+        try:
+            return self._get_browser().list_files(
+                directory, extensions or [], recursive
+            )
+        except Exception as e:
+            raise FileSystemError(
+                f"Failed to list files in '{directory}': {e}"
+            ) from e
+
+    def _get_browser(self):
+        # opening the file system reads all vpk directories, so it's kept open
+        if self._browser is None:
+            self._browser = self._fs.browse()
+        return self._browser
+
+    def vmf_manifest(self, filepath: str, from_game: bool = True) -> Dict[str, Any]:
+        """
+        Scan a VMF map for the files it references without importing anything.
+
+        Args:
+            filepath: Path to the VMF file
+            from_game: Whether the path is in the game file system or the OS file system
+
+        Returns:
+            Dictionary with "materials", "textures", "models" and "sounds" lists,
+            each entry being a dictionary with "path" and "found" keys
+
+        Raises:
+            FileSystemError: If the VMF can't be read
+        """
+        try:
+            return json.loads(self._fs.vmf_manifest(filepath, from_game))
+        except Exception as e:
+            raise FileSystemError(f"Failed to scan map '{filepath}': {e}") from e
+
     def __repr__(self) -> str:
         return (
             f"GameFileSystem(name='{self.name}', search_paths={len(self.search_paths)})"
@@ -2,7 +2,7 @@
 Game definitions and access to Plumber preferences.
 """
 
-from typing import List, Tuple
+from typing import List, Optional, Tuple
 
 from .exceptions import GameNotFoundError
 from .filesystem import GameFileSystem
@@ -15,16 +15,23 @@ class Game:
     Provides read-only access to game name and file system configuration.
     """
 
-    def __init__(self, name: str, search_paths: List[Tuple[str, str]]):
+    def __init__(
+        self,
+        name: str,
+        search_paths: List[Tuple[str, str]],
+        workshop_path: Optional[str] = None,
+    ):
         """
         Initialize a Game instance.
 
         Args:
             name: Name of the game
             search_paths: List of (kind, path) tuples for search paths
+            workshop_path: Workshop content directory to mount, if any
         """
         self._name = name
         self._search_paths = search_paths
+        self._workshop_path = workshop_path
 
     @property
     def name(self) -> str:
@@ -36,6 +43,11 @@ class Game:
         """Get the game's search paths as (kind, path) tuples."""
         return self._search_paths.copy()
 
+    @property
+    def workshop_path(self) -> Optional[str]:
+        """Get the mounted workshop content directory, if any."""
+        return self._workshop_path
+
     def get_file_system(self) -> GameFileSystem:
         """
         Create a GameFileSystem for this game.
@@ -43,7 +55,10 @@ class Game:
         Returns:
             GameFileSystem instance for browsing and reading game files
         """
-        return GameFileSystem.from_search_paths(self._name, self._search_paths)
+        file_system = GameFileSystem.from_search_paths(self._name, self._search_paths)
+        if self._workshop_path:
+            file_system.mount_workshop(self._workshop_path)
+        return file_system
 
     def __repr__(self) -> str:
         return f"Game(name='{self._name}', search_paths={len(self._search_paths)})"
@@ -76,7 +91,8 @@ class Games:
             games = []
             for game in preferences.games:
                 search_paths = [(path.kind, path.path) for path in game.search_paths]
-                games.append(Game(game.name, search_paths))
+                workshop_path = game.workshop_path if game.mount_workshop else None
+                games.append(Game(game.name, search_paths, workshop_path))
 
             return games
         except (KeyError, AttributeError) as e:
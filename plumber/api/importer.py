@@ -2,10 +2,10 @@
 Asset import functionality for individual and batch imports.
 """
 
-from typing import Any, List, Optional
+from typing import Any, List, Optional, Sequence, Tuple, Union
 from enum import Enum
 
-from .exceptions import AssetImportError
+from .exceptions import AssetImportError, StrictAssetImportError
 from .filesystem import GameFileSystem
 
 
@@ -46,6 +46,8 @@ def _map_api_to_rust_params(api_params: dict) -> dict:
         # Material settings
         "material_import_materials": "import_materials",
         "material_simple_materials": "simple_materials",
+        "material_pbr_estimation": "pbr_estimation",
+        "material_envmap_mask_roughness": "envmap_mask_roughness",
         "material_texture_format": "texture_format",
         "material_texture_interpolation": "texture_interpolation",
         "material_allow_culling": "allow_culling",
@@ -64,11 +66,23 @@ def _map_api_to_rust_params(api_params: dict) -> dict:
         "vmf_import_overlays": "import_overlays",
         "vmf_epsilon": "epsilon",
         "vmf_cut_threshold": "cut_threshold",
+        "vmf_repair_solids": "repair_solids",
+        "vmf_bounds": "bounds",
         "vmf_merge_solids": "merge_solids",
         "vmf_invisible_solids": "invisible_solids",
         "vmf_import_props": "import_props",
         "vmf_import_entities": "import_entities",
         "vmf_import_sky": "import_sky",
+        "vmf_import_radar": "import_radar",
+        "vmf_seam_tolerance": "seam_tolerance",
+        "vmf_lightmap_uvs": "lightmap_uvs",
+        "vmf_exact_vertices": "exact_vertices",
+        "vmf_editor_groups": "editor_groups",
+        "vmf_clean_faces": "clean_faces",
+        "vmf_weld_distance": "weld_distance",
+        "vmf_overlay_offset": "overlay_offset",
+        "vmf_max_displacement_power": "max_displacement_power",
+        "vmf_displacement_tolerance": "displacement_tolerance",
         # MDL-specific settings
         "mdl_import_animations": "import_animations",
         "mdl_target_fps": "target_fps",
@@ -103,6 +117,8 @@ class ParallelImportBuilder:
         # Material settings
         material_import_materials: bool = True,
         material_simple_materials: bool = False,
+        material_pbr_estimation: bool = False,
+        material_envmap_mask_roughness: bool = False,
         material_texture_format: str = "Png",
         material_texture_interpolation: str = "Linear",
         material_allow_culling: bool = False,
@@ -119,11 +135,23 @@ class ParallelImportBuilder:
         vmf_import_overlays: bool = True,
         vmf_epsilon: float = 0.01,
         vmf_cut_threshold: float = 0.1,
+        vmf_repair_solids: bool = False,
+        vmf_bounds: Optional[Tuple[Sequence[float], Sequence[float]]] = None,
         vmf_merge_solids: str = "MERGE",
         vmf_invisible_solids: str = "SKIP",
         vmf_import_props: bool = True,
         vmf_import_entities: bool = True,
         vmf_import_sky: bool = True,
+        vmf_import_radar: bool = False,
+        vmf_seam_tolerance: float = 1.0,
+        vmf_lightmap_uvs: bool = False,
+        vmf_exact_vertices: bool = True,
+        vmf_editor_groups: bool = True,
+        vmf_clean_faces: bool = False,
+        vmf_weld_distance: float = 0.0,
+        vmf_overlay_offset: float = 0.1,
+        vmf_max_displacement_power: int = 4,
+        vmf_displacement_tolerance: float = 0.0,
         vmf_scale: float = 1.0,
         # VMF collections
         vmf_brush_collection=None,
@@ -140,6 +168,7 @@ class ParallelImportBuilder:
         mdl_apply_armatures: bool = False,
         # General collections
         main_collection=None,
+        strict: bool = False,
     ):
         """
         Initialize the builder with all import settings.
@@ -150,7 +179,9 @@ class ParallelImportBuilder:
             # Material settings
             material_import_materials: Import materials
             material_simple_materials: Import simple, exporter-friendly materials
-            material_texture_format: Texture format ("Png", "Tga")
+            material_pbr_estimation: Estimate metallic and roughness values for PBR
+            material_envmap_mask_roughness: Use inverted envmap masks as roughness
+            material_texture_format: Texture format ("Png", "Tga", "Exr", "Raw")
             material_texture_interpolation: Texture interpolation ("Linear", "Closest", "Cubic", "Smart")
             material_allow_culling: Enable backface culling
             material_editor_materials: Import editor materials instead of invisible ones
@@ -167,11 +198,26 @@ class ParallelImportBuilder:
             vmf_import_overlays: Import overlay geometry
             vmf_epsilon: Geometry epsilon for calculations
             vmf_cut_threshold: Cut threshold for geometry
-            vmf_merge_solids: How to merge solids ("MERGE", "SEPARATE")
+            vmf_repair_solids: Import invalid solids without their sides with no face
+            vmf_bounds: Only import the solids and entities within the box between these
+                two corners in Source units, None imports the whole map
+            vmf_merge_solids: How to merge solids ("MERGE", "MERGE_WORLD", "SEPARATE")
             vmf_invisible_solids: How to handle invisible solids ("IMPORT", "SKIP")
             vmf_import_props: Import props
             vmf_import_entities: Import entities
             vmf_import_sky: Import skybox
+            vmf_import_radar: Import the CS:S/CS:GO radar overview as a plane
+            vmf_seam_tolerance: Sew displacement edges within this distance, 0 disables
+            vmf_lightmap_uvs: Add a "Lightmap" UV layer to brushes for baking
+            vmf_exact_vertices: Snap brush vertices to the vertices Hammer++ stores
+            vmf_editor_groups: Store the visgroups and groups of objects as properties
+            vmf_clean_faces: Remove faces without area and collinear vertices
+            vmf_weld_distance: Weld brush vertices within this distance, 0 disables
+            vmf_overlay_offset: Move overlays this distance off their surface per render
+                order step, 0 disables
+            vmf_max_displacement_power: Downsample displacements above this power (2-4)
+            vmf_displacement_tolerance: Downsample displacements further while no vertex
+                moves more than this distance, 0 disables
             vmf_scale: VMF-specific scale factor
 
             # Asset search settings
@@ -194,6 +240,8 @@ class ParallelImportBuilder:
 
             # General collections
             main_collection: Main collection for imports
+
+            strict: Abort the import on the first asset error instead of skipping the asset
         """
         self._file_system = file_system
         self._jobs: List[ImportJob] = []
@@ -203,6 +251,8 @@ class ParallelImportBuilder:
             # Material settings
             "material_import_materials": material_import_materials,
             "material_simple_materials": material_simple_materials,
+            "material_pbr_estimation": material_pbr_estimation,
+            "material_envmap_mask_roughness": material_envmap_mask_roughness,
             "material_texture_format": material_texture_format,
             "material_texture_interpolation": material_texture_interpolation,
             "material_allow_culling": material_allow_culling,
@@ -219,11 +269,23 @@ class ParallelImportBuilder:
             "vmf_import_overlays": vmf_import_overlays,
             "vmf_epsilon": vmf_epsilon,
             "vmf_cut_threshold": vmf_cut_threshold,
+            "vmf_repair_solids": vmf_repair_solids,
+            "vmf_bounds": vmf_bounds,
             "vmf_merge_solids": vmf_merge_solids,
             "vmf_invisible_solids": vmf_invisible_solids,
             "vmf_import_props": vmf_import_props,
             "vmf_import_entities": vmf_import_entities,
             "vmf_import_sky": vmf_import_sky,
+            "vmf_import_radar": vmf_import_radar,
+            "vmf_seam_tolerance": vmf_seam_tolerance,
+            "vmf_lightmap_uvs": vmf_lightmap_uvs,
+            "vmf_exact_vertices": vmf_exact_vertices,
+            "vmf_editor_groups": vmf_editor_groups,
+            "vmf_clean_faces": vmf_clean_faces,
+            "vmf_weld_distance": vmf_weld_distance,
+            "vmf_overlay_offset": vmf_overlay_offset,
+            "vmf_max_displacement_power": vmf_max_displacement_power,
+            "vmf_displacement_tolerance": vmf_displacement_tolerance,
             "vmf_scale": vmf_scale,
             # Asset search settings
             "asset_search_path": asset_search_path,
@@ -231,6 +293,7 @@ class ParallelImportBuilder:
             "mdl_target_fps": mdl_target_fps,
             "mdl_remove_animations": mdl_remove_animations,
             "mdl_import_animations": mdl_import_animations,
+            "strict": strict,
         }
 
         # Store AssetCallbacks settings
@@ -332,6 +395,7 @@ class ParallelImportBuilder:
                 self._file_system._fs,
                 callbacks,
                 threads,
+                channel_capacity=_get_channel_capacity(context),
                 **rust_settings,
             )
 
@@ -353,7 +417,7 @@ class ParallelImportBuilder:
             self._jobs.clear()
 
         except Exception as e:
-            raise AssetImportError(f"Parallel import execution failed: {e}") from e
+            raise _import_error("Parallel import execution failed", e) from e
 
     def clear(self) -> "ParallelImportBuilder":
         """Clear all import jobs."""
@@ -366,6 +430,37 @@ class ParallelImportBuilder:
         return len(self._jobs)
 
 
+def _import_error(message: str, error: Exception) -> AssetImportError:
+    """Wrap an import error, keeping the failed asset of strict mode errors."""
+    from .. import plumber
+
+    if isinstance(error, plumber.StrictImportError):
+        return StrictAssetImportError(str(error.args[0]), error.args[1])
+
+    return AssetImportError(f"{message}: {error}")
+
+
+def _remove_replaced_objects(collection, diff) -> None:
+    """
+    Delete the objects of an earlier import of the map whose solids or entities
+    changed or were removed, so they are replaced instead of duplicated.
+    """
+    import bpy
+
+    solids = set(diff.changed_solids()) | set(diff.removed_solids())
+    entities = set(diff.changed_entities()) | set(diff.removed_entities())
+
+    for obj in list(collection.all_objects):
+        if obj.get("vmf_class_name") == "worldspawn":
+            # merged world solids are imported again together
+            replaced = diff.world_solids_replaced() or obj.get("vmf_solid_id") in solids
+        else:
+            replaced = obj.get("vmf_id") in entities
+
+        if replaced:
+            bpy.data.objects.remove(obj)
+
+
 def _create_asset_callbacks(context, **options) -> Any:
     from ..asset import AssetCallbacks
 
@@ -395,6 +490,12 @@ def _create_asset_callbacks(context, **options) -> Any:
         entity_collection = main_collection
 
     apply_armatures = options.get("apply_armatures", False)
+    placeholder_scale = options.get("placeholder_scale")
+    instance_props = options.get("instance_props", False)
+    smooth_angle = options.get("smooth_angle", 0.0)
+    recenter = options.get("recenter")
+    atlas_size = options.get("atlas_size")
+    bake_size = options.get("bake_size")
 
     return AssetCallbacks(
         context,
@@ -405,16 +506,33 @@ def _create_asset_callbacks(context, **options) -> Any:
         light_collection=light_collection,
         entity_collection=entity_collection,
         apply_armatures=apply_armatures,
+        placeholder_scale=placeholder_scale,
+        instance_props=instance_props,
+        smooth_angle=smooth_angle,
+        recenter=recenter,
+        atlas_size=atlas_size,
+        bake_size=bake_size,
     )
 
 
+def _get_channel_capacity(context) -> Optional[int]:
+    """Get the asset queue size from preferences, or None for the default."""
+    try:
+        from .. import __package__ as ADDON_NAME
+
+        preferences = context.preferences.addons[ADDON_NAME].preferences
+        return preferences.channel_capacity
+    except (KeyError, AttributeError):
+        return None
+
+
 def _get_threads_suggestion(context) -> int:
     """Get thread count suggestion from preferences."""
     try:
         from .. import __package__ as ADDON_NAME
 
         preferences = context.preferences.addons[ADDON_NAME].preferences
-        return max(1, preferences.threads - 1)  # Leave room for Blender's thread
+        return preferences.get_importer_threads()
     except (KeyError, AttributeError):
         return 1
 
@@ -424,9 +542,12 @@ def import_vmf(
     path: str,
     from_game: bool = True,
     context=None,
+    strict: bool = False,
     # Material settings
     material_import_materials: bool = True,
     material_simple_materials: bool = False,
+    material_pbr_estimation: bool = False,
+    material_envmap_mask_roughness: bool = False,
     material_texture_format: str = "Png",
     material_texture_interpolation: str = "Linear",
     material_allow_culling: bool = False,
@@ -443,11 +564,23 @@ def import_vmf(
     vmf_import_overlays: bool = True,
     vmf_epsilon: float = 0.01,
     vmf_cut_threshold: float = 0.1,
+    vmf_repair_solids: bool = False,
+    vmf_bounds: Optional[Tuple[Sequence[float], Sequence[float]]] = None,
     vmf_merge_solids: str = "MERGE",
     vmf_invisible_solids: str = "SKIP",
     vmf_import_props: bool = True,
     vmf_import_entities: bool = True,
     vmf_import_sky: bool = True,
+    vmf_import_radar: bool = False,
+    vmf_seam_tolerance: float = 1.0,
+    vmf_lightmap_uvs: bool = False,
+    vmf_exact_vertices: bool = True,
+    vmf_editor_groups: bool = True,
+    vmf_clean_faces: bool = False,
+    vmf_weld_distance: float = 0.0,
+    vmf_overlay_offset: float = 0.1,
+    vmf_max_displacement_power: int = 4,
+    vmf_displacement_tolerance: float = 0.0,
     vmf_scale: float = 1.0,
     # Asset search settings
     asset_search_path: Optional[str] = None,
@@ -456,9 +589,13 @@ def import_vmf(
     mdl_remove_animations: bool = False,
     mdl_import_animations: bool = True,
     mdl_apply_armatures: bool = False,
+    # Diff settings
+    previous_path: Optional[str] = None,
+    # In-memory settings
+    data: Optional[Union[str, bytes]] = None,
     # Collection options
     **options,
-) -> None:
+) -> Optional[Any]:
     """
     Import a VMF (Valve Map Format) file.
 
@@ -467,11 +604,14 @@ def import_vmf(
         path: Path to VMF file
         from_game: Whether to load from game file system or OS file system
         context: Blender context (uses bpy.context if None)
+        strict: Abort the import on the first asset error instead of skipping the asset
 
         # Material settings
         material_import_materials: Import materials
         material_simple_materials: Import simple, exporter-friendly materials
-        material_texture_format: Texture format ("Png", "Tga")
+        material_pbr_estimation: Estimate metallic and roughness values for PBR
+        material_envmap_mask_roughness: Use inverted envmap masks as roughness
+        material_texture_format: Texture format ("Png", "Tga", "Exr", "Raw")
         material_texture_interpolation: Texture interpolation ("Linear", "Closest", "Cubic", "Smart")
         material_allow_culling: Enable backface culling
         material_editor_materials: Import editor materials instead of invisible ones
@@ -488,11 +628,26 @@ def import_vmf(
         vmf_import_overlays: Import overlay geometry
         vmf_epsilon: Geometry epsilon for calculations
         vmf_cut_threshold: Cut threshold for geometry
-        vmf_merge_solids: How to merge solids ("MERGE", "SEPARATE")
+        vmf_repair_solids: Import invalid solids without their sides with no face
+        vmf_bounds: Only import the solids and entities within the box between these
+            two corners in Source units, None imports the whole map
+        vmf_merge_solids: How to merge solids ("MERGE", "MERGE_WORLD", "SEPARATE")
         vmf_invisible_solids: How to handle invisible solids ("IMPORT", "SKIP")
         vmf_import_props: Import props
         vmf_import_entities: Import entities
         vmf_import_sky: Import skybox
+        vmf_import_radar: Import the CS:S/CS:GO radar overview as a plane
+        vmf_seam_tolerance: Sew displacement edges within this distance, 0 disables
+        vmf_lightmap_uvs: Add a "Lightmap" UV layer to brushes for baking
+        vmf_exact_vertices: Snap brush vertices to the vertices Hammer++ stores
+        vmf_editor_groups: Store the visgroups and groups of objects as properties
+        vmf_clean_faces: Remove faces without area and collinear vertices
+        vmf_weld_distance: Weld brush vertices within this distance, 0 disables
+        vmf_overlay_offset: Move overlays this distance off their surface per render
+            order step, 0 disables
+        vmf_max_displacement_power: Downsample displacements above this power (2-4)
+        vmf_displacement_tolerance: Downsample displacements further while no vertex
+            moves more than this distance, 0 disables
         vmf_scale: VMF-specific scale factor
 
         # Asset search settings
@@ -504,6 +659,14 @@ def import_vmf(
         mdl_import_animations: Import model animations
         mdl_apply_armatures: Apply armatures to models
 
+        # Diff settings
+        previous_path: Earlier version of the VMF, only solids and entities added or
+            changed since then are imported
+
+        # In-memory settings
+        data: Content of the VMF to import instead of reading path,
+            which then only names the VMF in the log
+
         # Collection options
         main_collection: Main collection for imports
         vmf_brush_collection: Collection for brushes (VMF imports)
@@ -511,9 +674,24 @@ def import_vmf(
         vmf_prop_collection: Collection for props (VMF imports)
         vmf_light_collection: Collection for lights (VMF imports)
         vmf_entity_collection: Collection for entities (VMF imports)
+        placeholder_scale: Replace props with missing models by error cubes of this scale
+        instance_props: Import props sharing a model, skin and bodygroup as instances
+        smooth_angle: Shade brushes smooth up to this angle between faces in radians,
+            0 shades them flat
+        recenter: "BOUNDS" or "SPAWN" to move the center of the map's bounds or its
+            player spawn to the world origin, None keeps the map in place
+        atlas_size: Pack the textures of brushes into atlases of this size and move
+            the faces which don't repeat their texture to them, None disables
+        bake_size: Bake the materials blended by displacement alpha into a texture of
+            this size per displacement, None disables
+
+    Returns:
+        The plumber.VmfDiff between previous_path and path if previous_path is given,
+        which also lists the removed solid and entity ids
 
     Raises:
         AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
     """
     if context is None:
         import bpy
@@ -530,9 +708,12 @@ def import_vmf(
 
         # Collect all API parameters
         api_params = {
+            "strict": strict,
             # Material settings
             "material_import_materials": material_import_materials,
             "material_simple_materials": material_simple_materials,
+            "material_pbr_estimation": material_pbr_estimation,
+            "material_envmap_mask_roughness": material_envmap_mask_roughness,
             "material_texture_format": material_texture_format,
             "material_texture_interpolation": material_texture_interpolation,
             "material_allow_culling": material_allow_culling,
@@ -549,11 +730,23 @@ def import_vmf(
             "vmf_import_overlays": vmf_import_overlays,
             "vmf_epsilon": vmf_epsilon,
             "vmf_cut_threshold": vmf_cut_threshold,
+            "vmf_repair_solids": vmf_repair_solids,
+            "vmf_bounds": vmf_bounds,
             "vmf_merge_solids": vmf_merge_solids,
             "vmf_invisible_solids": vmf_invisible_solids,
             "vmf_import_props": vmf_import_props,
             "vmf_import_entities": vmf_import_entities,
             "vmf_import_sky": vmf_import_sky,
+            "vmf_import_radar": vmf_import_radar,
+            "vmf_seam_tolerance": vmf_seam_tolerance,
+            "vmf_lightmap_uvs": vmf_lightmap_uvs,
+            "vmf_exact_vertices": vmf_exact_vertices,
+            "vmf_editor_groups": vmf_editor_groups,
+            "vmf_clean_faces": vmf_clean_faces,
+            "vmf_weld_distance": vmf_weld_distance,
+            "vmf_overlay_offset": vmf_overlay_offset,
+            "vmf_max_displacement_power": vmf_max_displacement_power,
+            "vmf_displacement_tolerance": vmf_displacement_tolerance,
             "vmf_scale": vmf_scale,
             # Asset search settings
             "asset_search_path": asset_search_path,
@@ -570,14 +763,90 @@ def import_vmf(
             file_system._fs,
             callbacks,
             threads,
+            channel_capacity=_get_channel_capacity(context),
             **rust_params,
         )
 
-        api_importer.add_vmf_job(path, from_game)
+        diff = None
+        if data is not None:
+            if isinstance(data, str):
+                data = data.encode("utf-8")
+            api_importer.add_vmf_data_job(path, data)
+        elif previous_path is None:
+            api_importer.add_vmf_job(path, from_game)
+        else:
+            diff = api_importer.add_vmf_diff_job(previous_path, path, from_game)
+            _remove_replaced_objects(callbacks.main_collection, diff)
+
         api_importer.execute_jobs()
+        return diff
 
     except Exception as e:
-        raise AssetImportError(f"VMF import failed: {e}") from e
+        raise _import_error("VMF import failed", e) from e
+
+
+def import_vmf_diff(
+    file_system: GameFileSystem,
+    old_path: str,
+    new_path: str,
+    from_game: bool = True,
+    context=None,
+    **kwargs,
+) -> Any:
+    """
+    Import only the solids and entities of a VMF added or changed since an older one.
+
+    Solids and entities are matched by their id. The objects of the main collection
+    imported from changed or removed ids are deleted, so they are replaced.
+
+    Args:
+        file_system: GameFileSystem to use for asset loading
+        old_path: Path to the previously imported version of the VMF
+        new_path: Path to the new version of the VMF
+        from_game: Whether to load from game file system or OS file system
+        context: Blender context (uses bpy.context if None)
+        **kwargs: Any other settings of import_vmf
+
+    Returns:
+        plumber.VmfDiff with the added, changed and removed solid and entity ids
+
+    Raises:
+        AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
+    """
+    return import_vmf(
+        file_system,
+        new_path,
+        from_game,
+        context,
+        previous_path=old_path,
+        **kwargs,
+    )
+
+
+def import_vmf_data(
+    file_system: GameFileSystem,
+    data: Union[str, bytes],
+    name: str = "memory",
+    context=None,
+    **kwargs,
+) -> None:
+    """
+    Import a VMF from its content instead of a file, such as a generated VMF
+    or one fetched from a network service, without writing a temporary file.
+
+    Args:
+        file_system: GameFileSystem to use for asset loading
+        data: Content of the VMF
+        name: Name of the VMF in the log
+        context: Blender context (uses bpy.context if None)
+        **kwargs: Any other settings of import_vmf
+
+    Raises:
+        AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
+    """
+    import_vmf(file_system, name, False, context, data=data, **kwargs)
 
 
 def import_mdl(
@@ -585,9 +854,12 @@ def import_mdl(
     path: str,
     from_game: bool = True,
     context=None,
+    strict: bool = False,
     # Material settings
     material_import_materials: bool = True,
     material_simple_materials: bool = False,
+    material_pbr_estimation: bool = False,
+    material_envmap_mask_roughness: bool = False,
     material_texture_format: str = "Png",
     material_texture_interpolation: str = "Linear",
     material_allow_culling: bool = False,
@@ -599,6 +871,7 @@ def import_mdl(
     mdl_remove_animations: bool = False,
     mdl_import_animations: bool = True,
     mdl_apply_armatures: bool = False,
+    mdl_static_prop_rotation: str = "MAP",
     # Collection options
     main_collection=None,
 ) -> None:
@@ -610,11 +883,14 @@ def import_mdl(
         path: Path to MDL file
         from_game: Whether to load from game file system or OS file system
         context: Blender context (uses bpy.context if None)
+        strict: Abort the import on the first asset error instead of skipping the asset
 
         # Material settings
         material_import_materials: Import materials
         material_simple_materials: Import simple, exporter-friendly materials
-        material_texture_format: Texture format ("Png", "Tga")
+        material_pbr_estimation: Estimate metallic and roughness values for PBR
+        material_envmap_mask_roughness: Use inverted envmap masks as roughness
+        material_texture_format: Texture format ("Png", "Tga", "Exr", "Raw")
         material_texture_interpolation: Texture interpolation ("Linear", "Closest", "Cubic", "Smart")
         material_allow_culling: Enable backface culling
         material_editor_materials: Import editor materials instead of invisible ones
@@ -627,12 +903,14 @@ def import_mdl(
         mdl_remove_animations: Remove animations from imported models
         mdl_import_animations: Import model animations
         mdl_apply_armatures: Apply armatures to models
+        mdl_static_prop_rotation: Orientation of $staticprop models ("MAP", "MODELED")
 
         # Collection options
         main_collection: Main collection for imports
 
     Raises:
         AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
     """
     if context is None:
         import bpy
@@ -653,9 +931,12 @@ def import_mdl(
 
         # Collect all API parameters
         api_params = {
+            "strict": strict,
             # Material settings
             "material_import_materials": material_import_materials,
             "material_simple_materials": material_simple_materials,
+            "material_pbr_estimation": material_pbr_estimation,
+            "material_envmap_mask_roughness": material_envmap_mask_roughness,
             "material_texture_format": material_texture_format,
             "material_texture_interpolation": material_texture_interpolation,
             "material_allow_culling": material_allow_culling,
@@ -675,14 +956,18 @@ def import_mdl(
             file_system._fs,
             callbacks,
             threads,
+            channel_capacity=_get_channel_capacity(context),
             **rust_params,
         )
 
         api_importer.add_mdl_job(path, from_game)
         api_importer.execute_jobs()
 
+        if mdl_static_prop_rotation == "MODELED":
+            callbacks.model_tracker.rotate_static_props()
+
     except Exception as e:
-        raise AssetImportError(f"MDL import failed: {e}") from e
+        raise _import_error("MDL import failed", e) from e
 
 
 def import_vmt(
@@ -690,8 +975,11 @@ def import_vmt(
     path: str,
     from_game: bool = True,
     context=None,
+    strict: bool = False,
     # Material settings
     material_simple_materials: bool = False,
+    material_pbr_estimation: bool = False,
+    material_envmap_mask_roughness: bool = False,
     material_texture_format: str = "Png",
     material_texture_interpolation: str = "Linear",
     material_allow_culling: bool = False,
@@ -707,10 +995,13 @@ def import_vmt(
         path: Path to VMT file
         from_game: Whether to load from game file system or OS file system
         context: Blender context (uses bpy.context if None)
+        strict: Abort the import on the first asset error instead of skipping the asset
 
         # Material settings
         material_simple_materials: Import simple, exporter-friendly materials
-        material_texture_format: Texture format ("Png", "Tga")
+        material_pbr_estimation: Estimate metallic and roughness values for PBR
+        material_envmap_mask_roughness: Use inverted envmap masks as roughness
+        material_texture_format: Texture format ("Png", "Tga", "Exr", "Raw")
         material_texture_interpolation: Texture interpolation ("Linear", "Closest", "Cubic", "Smart")
         material_allow_culling: Enable backface culling
         material_editor_materials: Import editor materials instead of invisible ones
@@ -720,6 +1011,7 @@ def import_vmt(
 
     Raises:
         AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
     """
     if context is None:
         import bpy
@@ -734,9 +1026,12 @@ def import_vmt(
 
         # Collect all API parameters
         api_params = {
+            "strict": strict,
             # Material settings
             "material_import_materials": True,
             "material_simple_materials": material_simple_materials,
+            "material_pbr_estimation": material_pbr_estimation,
+            "material_envmap_mask_roughness": material_envmap_mask_roughness,
             "material_texture_format": material_texture_format,
             "material_texture_interpolation": material_texture_interpolation,
             "material_allow_culling": material_allow_culling,
@@ -752,6 +1047,7 @@ def import_vmt(
             file_system._fs,
             callbacks,
             threads,
+            channel_capacity=_get_channel_capacity(context),
             **rust_params,
         )
 
@@ -759,7 +1055,7 @@ def import_vmt(
         api_importer.execute_jobs()
 
     except Exception as e:
-        raise AssetImportError(f"VMT import failed: {e}") from e
+        raise _import_error("VMT import failed", e) from e
 
 
 def import_vtf(
@@ -767,6 +1063,7 @@ def import_vtf(
     path: str,
     from_game: bool = True,
     context=None,
+    strict: bool = False,
     # Material settings
     material_texture_format: str = "Png",
     material_texture_interpolation: str = "Linear",
@@ -781,9 +1078,10 @@ def import_vtf(
         path: Path to VTF file
         from_game: Whether to load from game file system or OS file system
         context: Blender context (uses bpy.context if None)
+        strict: Abort the import on the first asset error instead of skipping the asset
 
         # Material settings
-        material_texture_format: Texture format ("Png", "Tga")
+        material_texture_format: Texture format ("Png", "Tga", "Exr", "Raw")
         material_texture_interpolation: Texture interpolation ("Linear", "Closest", "Cubic", "Smart")
 
         # Asset search settings
@@ -791,6 +1089,7 @@ def import_vtf(
 
     Raises:
         AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
     """
     if context is None:
         import bpy
@@ -805,6 +1104,7 @@ def import_vtf(
 
         # Collect all API parameters
         api_params = {
+            "strict": strict,
             # Material settings
             "material_texture_format": material_texture_format,
             "material_texture_interpolation": material_texture_interpolation,
@@ -819,6 +1119,7 @@ def import_vtf(
             file_system._fs,
             callbacks,
             threads,
+            channel_capacity=_get_channel_capacity(context),
             **rust_params,
         )
 
@@ -826,4 +1127,73 @@ def import_vtf(
         api_importer.execute_jobs()
 
     except Exception as e:
-        raise AssetImportError(f"VTF import failed: {e}") from e
+        raise _import_error("VTF import failed", e) from e
+
+
+def import_sky(
+    file_system: GameFileSystem,
+    sky_name: str,
+    context=None,
+    strict: bool = False,
+    # Sky settings
+    sky_equi_height: Optional[int] = None,
+    # Asset search settings
+    asset_search_path: Optional[str] = None,
+) -> None:
+    """
+    Import a skybox by its name as the equirectangular world background,
+    without a VMF.
+
+    Args:
+        file_system: GameFileSystem to use for asset loading
+        sky_name: Name of the skybox, like the skyname of a map (e.g. "sky_day01_01")
+        context: Blender context (uses bpy.context if None)
+        strict: Abort the import on the first asset error instead of skipping the asset
+
+        # Sky settings
+        sky_equi_height: Height of the equirectangular texture, None keeps the
+            resolution of the skybox
+
+        # Asset search settings
+        asset_search_path: Additional search path for assets
+
+    Raises:
+        AssetImportError: If import fails
+        StrictAssetImportError: If an asset fails to import in strict mode
+    """
+    if context is None:
+        import bpy
+
+        context = bpy.context
+
+    try:
+        from .. import plumber
+
+        callbacks = _create_asset_callbacks(context)
+        threads = _get_threads_suggestion(context)
+
+        # Collect all API parameters
+        api_params = {
+            "strict": strict,
+            # Sky settings
+            "vmf_sky_equi_height": sky_equi_height,
+            # Asset search settings
+            "asset_search_path": asset_search_path,
+        }
+
+        # Map API parameter names to Rust parameter names
+        rust_params = _map_api_to_rust_params(api_params)
+
+        api_importer = plumber.ApiImporter(
+            file_system._fs,
+            callbacks,
+            threads,
+            channel_capacity=_get_channel_capacity(context),
+            **rust_params,
+        )
+
+        api_importer.add_sky_job(sky_name)
+        api_importer.execute_jobs()
+
+    except Exception as e:
+        raise _import_error("Sky import failed", e) from e
@@ -1,28 +1,79 @@
-from typing import Optional
-from bpy.types import Context, Collection
+from typing import Dict, List, Optional, Tuple
+from bpy.types import Context, Collection, Object
 
 from ..plumber import (
+    BrushMotion,
+    BrushOrigin,
+    BrushRender,
+    BrushRotation,
     BuiltBrushEntity,
     BuiltOverlay,
+    ColorCorrection,
+    Decal,
+    Dependency,
+    Diagnostic,
+    GoldSrcModel,
     LoadedProp,
     Material,
     Model,
+    NavMesh,
+    NodeGraph,
+    PathChain,
+    Radar,
     Light,
     EnvLight,
+    Sun,
+    LightGlow,
+    LightingOrigin,
+    PlayerSpawn,
     SkyCamera,
+    Smoke,
     SpotLight,
+    PointSpotlight,
+    ProjectedTexture,
     SkyEqui,
+    Summary,
     Texture,
     UnknownEntity,
+    log_info,
 )
-from .material import import_material, import_texture
+from .dependency import DependencyGraph
+from .material import import_material, import_texture, reset_texture_aliases
 from .model import ModelTracker
-from .brush import import_brush
-from .overlay import import_overlay
-from .prop import apply_armatures, import_prop
-from .light import import_light, import_spot_light, import_env_light
+from .brush import (
+    PORTAL_CLASS_NAMES,
+    apply_brush_motions,
+    apply_brush_origins,
+    apply_brush_renders,
+    apply_brush_rotations,
+    import_brush,
+)
+from .overlay import import_decals, import_overlay
+from .parents import apply_entity_parents
+from .placeholder import apply_placeholder_materials
+from .prop import apply_armatures, apply_lighting_origins, import_prop
+from .light import (
+    import_light,
+    import_spot_light,
+    import_point_spotlight,
+    import_projected_texture,
+    apply_projected_textures,
+    import_env_light,
+    import_sun,
+    import_light_glow,
+)
+from .smoke import import_smoke
 from .sky_camera import import_sky_camera
+from .color_correction import import_color_correction
+from .path_chain import import_path_chain
 from .sky_equi import import_sky_equi
+from .nav import import_nav_mesh
+from .goldsrc import import_goldsrc_model
+from .ain import import_node_graph
+from .radar import import_radar
+from .recenter import recenter_map
+from .atlas import pack_brush_atlases
+from .bake import bake_blend_materials
 from .unknown_entity import import_unknown_entity
 
 
@@ -36,11 +87,36 @@ class AssetCallbacks:
         prop_collection: Optional[Collection] = None,
         light_collection: Optional[Collection] = None,
         entity_collection: Optional[Collection] = None,
+        portal_collection: Optional[Collection] = None,
+        skip_portals: bool = False,
+        smooth_angle: float = 0.0,
         apply_armatures: bool = False,
+        dependency_graph: Optional[DependencyGraph] = None,
+        placeholder_scale: Optional[float] = None,
+        instance_props: bool = False,
+        reuse_existing_models: bool = False,
+        recenter: Optional[str] = None,
+        atlas_size: Optional[int] = None,
+        bake_size: Optional[int] = None,
     ) -> None:
+        # the duplicate textures of an earlier import may not exist anymore
+        reset_texture_aliases()
+
         self.context = context
-        self.model_tracker = ModelTracker()
+        self.model_tracker = ModelTracker(reuse_existing_models)
         self.armatures_to_apply = []
+        self.projected_textures = []
+        self.lit_props = []
+        self.brush_objects = {}
+        self.brush_renders: List[BrushRender] = []
+        self.brush_origins: List[BrushOrigin] = []
+        self.brush_motions: List[BrushMotion] = []
+        self.brush_rotations: List[BrushRotation] = []
+        self.decals: List[Decal] = []
+        self.entity_objects: Dict[int, List[Object]] = {}
+        self.entity_parents: List[Tuple[List[Object], int]] = []
+        self.lighting_origins = {}
+        self.player_spawns: List[PlayerSpawn] = []
 
         self.main_collection = main_collection or context.collection
         self.brush_collection = brush_collection or self.main_collection
@@ -48,9 +124,39 @@ class AssetCallbacks:
         self.prop_collection = prop_collection or self.main_collection
         self.light_collection = light_collection or self.main_collection
         self.entity_collection = entity_collection or self.main_collection
+        self.portal_collection = portal_collection or self.brush_collection
+
+        self.skip_portals = skip_portals
+
+        # brushes are shaded smooth up to this angle between faces, 0 shades them flat
+        self.smooth_angle = smooth_angle
 
         self.apply_armatures = apply_armatures
 
+        self.dependency_graph = dependency_graph
+
+        # missing content is replaced with placeholders of this scale, None disables them
+        self.placeholder_scale = placeholder_scale
+
+        # props sharing a model, skin and bodygroup instance the same collection
+        self.instance_props = instance_props
+
+        # "BOUNDS" or "SPAWN" moves that point of the map to the origin
+        self.recenter = recenter
+        # only the objects of this import are moved, not existing ones in the collection
+        self.existing_objects = (
+            set(self.main_collection.all_objects) if recenter is not None else set()
+        )
+
+        # the textures of the brushes are packed into atlases of this size, None disables
+        self.atlas_size = atlas_size
+        # blend materials of displacements are baked into textures of this size,
+        # None disables
+        self.bake_size = bake_size
+
+        self.diagnostics: List[Diagnostic] = []
+        self.import_summary: Optional[Summary] = None
+
     def material(self, material: Material) -> None:
         import_material(material)
 
@@ -61,37 +167,200 @@ class AssetCallbacks:
         self.model_tracker.import_model(model, self.prop_collection)
 
     def brush(self, brush: BuiltBrushEntity) -> None:
-        import_brush(brush, self.brush_collection)
+        if brush.class_name() in PORTAL_CLASS_NAMES:
+            if not self.skip_portals:
+                import_brush(brush, self.portal_collection, self.smooth_angle)
+            return
+
+        objects = import_brush(brush, self.brush_collection, self.smooth_angle)
+        self.brush_objects[brush.id()] = objects
+        self.add_entity_objects(brush.id(), brush.parent_id(), objects)
+
+        # applied once all brushes are imported, since they are pivoted and animated
+        render = brush.render()
+        if render is not None:
+            self.brush_renders.append(render)
+        origin = brush.origin()
+        if origin is not None:
+            self.brush_origins.append(origin)
+        motion = brush.motion()
+        if motion is not None:
+            self.brush_motions.append(motion)
+        rotation = brush.rotation()
+        if rotation is not None:
+            self.brush_rotations.append(rotation)
 
     def overlay(self, overlay: BuiltOverlay) -> None:
         import_overlay(overlay, self.overlay_collection)
 
+    def decal(self, decal: Decal) -> None:
+        self.decals.append(decal)
+
     def prop(self, prop: LoadedProp) -> None:
-        import_prop(
+        obj = import_prop(
             prop,
             self.prop_collection,
             self.model_tracker,
             self.apply_armatures,
             self.armatures_to_apply,
+            self.placeholder_scale,
+            self.instance_props,
         )
+        self.add_entity_objects(prop.id(), prop.parent_id(), [obj])
+
+        if "lighting_origin" in obj:
+            self.lit_props.append(obj)
 
     def light(self, light: Light) -> None:
-        import_light(light, self.light_collection)
+        obj = import_light(light, self.light_collection)
+        self.add_entity_objects(light.id(), light.parent_id(), [obj])
 
     def spot_light(self, light: SpotLight) -> None:
-        import_spot_light(light, self.light_collection)
+        obj = import_spot_light(light, self.light_collection)
+        self.add_entity_objects(light.id(), light.parent_id(), [obj])
+
+    def point_spotlight(self, light: PointSpotlight) -> None:
+        obj = import_point_spotlight(light, self.light_collection)
+        self.add_entity_objects(light.id(), light.parent_id(), [obj])
+
+    def projected_texture(self, light: ProjectedTexture) -> None:
+        obj = import_projected_texture(light, self.light_collection)
+        self.projected_textures.append(obj)
+        self.add_entity_objects(light.id(), light.parent_id(), [obj])
 
     def env_light(self, light: EnvLight) -> None:
         import_env_light(light, self.context, self.light_collection)
 
+    def sun(self, sun: Sun) -> None:
+        import_sun(sun, self.light_collection)
+
+    def light_glow(self, glow: LightGlow) -> None:
+        obj = import_light_glow(glow, self.light_collection)
+        self.add_entity_objects(glow.id(), glow.parent_id(), [obj])
+
+    def lighting_origin(self, origin: LightingOrigin) -> None:
+        self.lighting_origins[origin.name()] = tuple(origin.position())
+
+    def smoke(self, smoke: Smoke) -> None:
+        obj = import_smoke(smoke, self.entity_collection)
+        self.add_entity_objects(smoke.id(), smoke.parent_id(), [obj])
+
+    def player_spawn(self, spawn: PlayerSpawn) -> None:
+        self.player_spawns.append(spawn)
+
     def sky_camera(self, sky_camera: SkyCamera) -> None:
         import_sky_camera(sky_camera, self.context, self.main_collection)
 
+    def color_correction(self, color_correction: ColorCorrection) -> None:
+        import_color_correction(color_correction, self.main_collection)
+
+    def path_chain(self, chain: PathChain) -> None:
+        import_path_chain(chain, self.entity_collection)
+
     def sky_equi(self, sky_equi: SkyEqui) -> None:
         import_sky_equi(sky_equi, self.context)
 
+    def nav_mesh(self, nav: NavMesh) -> None:
+        import_nav_mesh(nav, self.main_collection)
+
+    def goldsrc_model(self, model: GoldSrcModel) -> None:
+        import_goldsrc_model(model, self.brush_collection)
+
+    def node_graph(self, graph: NodeGraph) -> None:
+        import_node_graph(graph, self.main_collection)
+
+    def radar(self, radar: Radar) -> None:
+        import_radar(radar, self.main_collection)
+
     def unknown_entity(self, entity: UnknownEntity) -> None:
-        import_unknown_entity(entity, self.entity_collection)
+        obj = import_unknown_entity(entity, self.entity_collection)
+        self.add_entity_objects(entity.id(), entity.parent_id(), [obj])
+
+    def add_entity_objects(
+        self, entity_id: int, parent_id: Optional[int], objects: List[Object]
+    ) -> None:
+        # parented once every entity is imported, since parents can come after children
+        self.entity_objects[entity_id] = objects
+        if parent_id is not None:
+            self.entity_parents.append((objects, parent_id))
+
+    def dependency(self, dependency: Dependency) -> None:
+        if self.dependency_graph is not None:
+            self.dependency_graph.add(dependency)
+
+    def diagnostic(self, diagnostic: Diagnostic) -> None:
+        # the problem is already logged, just keep it for the completion report
+        self.diagnostics.append(diagnostic)
+
+    def summary(self, summary: Summary) -> None:
+        self.import_summary = summary
+
+        for kind, count in summary.asset_counts().items():
+            log_info(f"imported {count} {kind} assets")
+
+        stages = ", ".join(
+            f"{name} {time:.2f} s" for name, time in summary.stage_times()
+        )
+        log_info(f"import stages: {stages}")
+
+    def summary_report(self) -> Optional[str]:
+        """Get a short completion report of the import, if the summary was received."""
+        summary = self.import_summary
+        if summary is None:
+            return None
+
+        assets = sum(summary.asset_counts().values())
+        return (
+            f"Imported {assets} assets in {summary.total_time():.2f} s "
+            f"({summary.errors()} errors, {summary.warnings()} warnings)"
+        )
 
     def finish(self) -> None:
         apply_armatures(self.armatures_to_apply)
+        apply_projected_textures(self.projected_textures)
+        apply_lighting_origins(self.lit_props, self.lighting_origins)
+        apply_brush_renders(self.brush_renders, self.brush_objects)
+        apply_brush_origins(self.brush_origins, self.brush_objects)
+        apply_brush_motions(self.brush_motions, self.brush_objects)
+        apply_brush_rotations(
+            self.brush_rotations,
+            self.brush_objects,
+            self.context.scene.render.fps / self.context.scene.render.fps_base,
+        )
+        if self.entity_parents:
+            self.context.view_layer.update()
+            apply_entity_parents(self.entity_parents, self.entity_objects)
+        if self.decals:
+            import_decals(self.decals, self.context, self.overlay_collection)
+
+        if self.placeholder_scale is not None:
+            apply_placeholder_materials(self.diagnostics)
+
+        brush_objects = [
+            obj for objects in self.brush_objects.values() for obj in objects
+        ]
+
+        if self.bake_size is not None:
+            bake_blend_materials(self.context, brush_objects, self.bake_size)
+
+        if self.atlas_size is not None:
+            pack_brush_atlases(
+                self.main_collection.name,
+                brush_objects,
+                self.atlas_size,
+            )
+
+        if self.recenter is not None:
+            # the world matrices of the new objects are needed for their bounds
+            self.context.view_layer.update()
+            objects = [
+                obj
+                for obj in self.main_collection.all_objects
+                if obj not in self.existing_objects
+            ]
+            recenter_map(
+                self.main_collection, objects, self.recenter, self.player_spawns
+            )
+
+        if self.dependency_graph is not None:
+            self.dependency_graph.write()
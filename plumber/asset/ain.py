@@ -0,0 +1,39 @@
+import bpy
+from bpy.types import Collection
+
+from ..plumber import NodeGraph
+
+
+def import_node_graph(graph: NodeGraph, collection: Collection) -> None:
+    name = bpy.path.display_name_from_filepath(graph.name())
+    mesh = bpy.data.meshes.new(name)
+
+    vertices = graph.vertices()
+    edge_vertices = graph.edge_vertices()
+
+    mesh.vertices.add(len(vertices) // 3)
+    mesh.edges.add(len(edge_vertices) // 2)
+    mesh.vertices.foreach_set("co", vertices)
+    mesh.edges.foreach_set("vertices", edge_vertices)
+    mesh.update()
+
+    obj = bpy.data.objects.new(name, object_data=mesh)
+    obj.scale = graph.scale()
+    collection.objects.link(obj)
+
+    yaws = graph.yaws()
+    node_types = graph.node_types()
+    zones = graph.zones()
+    hammer_ids = graph.hammer_ids()
+
+    for i, vertex in enumerate(mesh.vertices):
+        node = bpy.data.objects.new(f"{name}_node_{i}", object_data=None)
+        node.empty_display_type = "SINGLE_ARROW"
+        node.parent = obj
+        node.location = vertex.co
+        # single arrow empties point along z, rotate them to point along the node's yaw
+        node.rotation_euler = (0, 1.5707964, yaws[i])
+        node["node_type"] = node_types[i]
+        node["zone"] = zones[i]
+        node["hammer_id"] = hammer_ids[i]
+        collection.objects.link(node)
@@ -0,0 +1,246 @@
+from math import floor
+from typing import Dict, Iterable, List, Optional, Tuple
+
+import bpy
+import numpy as np
+from bpy.types import Image, Material, Mesh, Object
+
+from .utils import truncate_name
+from ..plumber import log_info
+
+# pixels around each texture repeating its edges,
+# so filtering and mipmaps don't bleed its neighbours into it
+ATLAS_PADDING = 4
+
+# principled inputs which only the base color is packed for, materials using them keep
+# their own textures
+UNPACKED_INPUTS = {"Alpha", "Normal", "Transmission", "Subsurface"}
+
+
+class AtlasRegion:
+    def __init__(self, image: Image, atlas: int, x: int, y: int) -> None:
+        self.image = image
+        self.atlas = atlas
+        self.x = x
+        self.y = y
+
+
+def base_color_image(material: Material) -> Optional[Image]:
+    """
+    Find the image texture linked directly to the base color of the material,
+    if it's mapped by the uvs of the face as they are.
+    """
+    if not material.use_nodes:
+        return None
+
+    for link in material.node_tree.links:
+        if (
+            link.to_socket.name == "Base Color"
+            and link.from_node.type == "TEX_IMAGE"
+            and link.from_node.image is not None
+            and not link.from_node.inputs["Vector"].is_linked
+        ):
+            return link.from_node.image
+
+    return None
+
+
+def atlas_image(material: Material) -> Optional[Image]:
+    """
+    Find the base color image of the material if it can be packed into an atlas,
+    which only carries the base color, so transparent or normal mapped materials can't.
+    """
+    if material.blend_method != "OPAQUE":
+        return None
+
+    if material.use_nodes and any(
+        link.to_socket.name in UNPACKED_INPUTS or link.to_node.type == "NORMAL_MAP"
+        for link in material.node_tree.links
+    ):
+        return None
+
+    return base_color_image(material)
+
+
+def pack_regions(
+    images: List[Image], atlas_size: int
+) -> Tuple[Dict[str, AtlasRegion], int]:
+    """
+    Place the images on shelves of atlases, tallest first,
+    starting a new atlas when one is full.
+    """
+    regions = {}
+    atlas = 0
+    x = y = shelf_height = 0
+
+    for image in sorted(images, key=lambda i: (i.size[1], i.size[0]), reverse=True):
+        width = image.size[0] + 2 * ATLAS_PADDING
+        height = image.size[1] + 2 * ATLAS_PADDING
+
+        if x + width > atlas_size:
+            x = 0
+            y += shelf_height
+            shelf_height = 0
+
+        if y + height > atlas_size:
+            atlas += 1
+            x = y = shelf_height = 0
+
+        regions[image.name] = AtlasRegion(
+            image, atlas, x + ATLAS_PADDING, y + ATLAS_PADDING
+        )
+        x += width
+        shelf_height = max(shelf_height, height)
+
+    return regions, atlas + 1 if regions else 0
+
+
+def build_atlas_image(
+    name: str, atlas_size: int, regions: Iterable[AtlasRegion]
+) -> Image:
+    pixels = np.zeros((atlas_size, atlas_size, 4), dtype=np.float32)
+
+    for region in regions:
+        width, height = region.image.size
+        image_pixels = np.empty(width * height * 4, dtype=np.float32)
+        region.image.pixels.foreach_get(image_pixels)
+        padded = np.pad(
+            image_pixels.reshape(height, width, 4),
+            ((ATLAS_PADDING, ATLAS_PADDING), (ATLAS_PADDING, ATLAS_PADDING), (0, 0)),
+            mode="edge",
+        )
+        pixels[
+            region.y - ATLAS_PADDING : region.y + height + ATLAS_PADDING,
+            region.x - ATLAS_PADDING : region.x + width + ATLAS_PADDING,
+        ] = padded
+
+    image = bpy.data.images.new(name, atlas_size, atlas_size, alpha=True)
+    image.pixels.foreach_set(pixels.ravel())
+    image.pack()
+    return image
+
+
+def build_atlas_material(name: str, image: Image) -> Material:
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    nt = material.node_tree
+
+    shader_node = nt.nodes.get("Principled BSDF")
+    tex_node = nt.nodes.new("ShaderNodeTexImage")
+    tex_node.image = image
+    tex_node.location = (-400, 300)
+    nt.links.new(tex_node.outputs["Color"], shader_node.inputs["Base Color"])
+
+    material["atlas"] = True
+    return material
+
+
+def face_uv_offset(uvs: np.ndarray) -> Optional[Tuple[float, float]]:
+    """
+    Get the whole tile offset which moves the uvs of a face inside one tile,
+    or None if the face repeats its texture.
+    """
+    minimum = uvs.min(axis=0)
+    maximum = uvs.max(axis=0)
+
+    offset = (floor(minimum[0]), floor(minimum[1]))
+    if maximum[0] - offset[0] > 1.0 or maximum[1] - offset[1] > 1.0:
+        return None
+
+    return offset
+
+
+def remap_mesh(
+    mesh: Mesh,
+    image_regions: Dict[str, AtlasRegion],
+    atlas_materials: List[Material],
+    atlas_size: int,
+) -> None:
+    """Move the faces whose texture is in an atlas to the atlas, within its region."""
+    if not mesh.uv_layers:
+        return
+
+    slot_regions = []
+    for material in mesh.materials:
+        image = atlas_image(material) if material is not None else None
+        slot_regions.append(image_regions.get(image.name) if image else None)
+
+    if not any(slot_regions):
+        return
+
+    uv_layer = mesh.uv_layers[0]
+    uvs = np.empty(len(uv_layer.data) * 2, dtype=np.float32)
+    uv_layer.data.foreach_get("uv", uvs)
+    uvs = uvs.reshape(-1, 2)
+
+    atlas_slots: Dict[int, int] = {}
+
+    for polygon in mesh.polygons:
+        if polygon.material_index >= len(slot_regions):
+            continue
+
+        region = slot_regions[polygon.material_index]
+        if region is None:
+            continue
+
+        loops = slice(polygon.loop_start, polygon.loop_start + polygon.loop_total)
+        offset = face_uv_offset(uvs[loops])
+        if offset is None:
+            continue
+
+        width, height = region.image.size
+        uvs[loops, 0] = ((uvs[loops, 0] - offset[0]) * width + region.x) / atlas_size
+        uvs[loops, 1] = ((uvs[loops, 1] - offset[1]) * height + region.y) / atlas_size
+
+        if region.atlas not in atlas_slots:
+            atlas_slots[region.atlas] = len(mesh.materials)
+            mesh.materials.append(atlas_materials[region.atlas])
+
+        polygon.material_index = atlas_slots[region.atlas]
+
+    uv_layer.data.foreach_set("uv", uvs.ravel())
+
+
+def pack_brush_atlases(
+    map_name: str, objects: Iterable[Object], atlas_size: int
+) -> None:
+    """
+    Pack the base textures of the brushes into atlases of the given size,
+    and move the faces using them to the atlases.
+    Faces which repeat their texture or whose material is transparent or normal mapped
+    keep their own material.
+    """
+    meshes = {obj.data for obj in objects if obj.type == "MESH"}
+
+    images = {}
+    for mesh in meshes:
+        for material in mesh.materials:
+            if material is None:
+                continue
+
+            image = atlas_image(material)
+            if (
+                image is not None
+                and 0 < image.size[0] <= atlas_size - 2 * ATLAS_PADDING
+                and 0 < image.size[1] <= atlas_size - 2 * ATLAS_PADDING
+            ):
+                images[image.name] = image
+
+    image_regions, atlas_count = pack_regions(list(images.values()), atlas_size)
+    if atlas_count == 0:
+        return
+
+    atlas_materials = []
+    for atlas in range(atlas_count):
+        name = truncate_name(f"{map_name}_atlas_{atlas}")
+        image = build_atlas_image(
+            name,
+            atlas_size,
+            (region for region in image_regions.values() if region.atlas == atlas),
+        )
+        atlas_materials.append(build_atlas_material(name, image))
+
+    for mesh in meshes:
+        remap_mesh(mesh, image_regions, atlas_materials, atlas_size)
+
+    log_info(f"packed {len(image_regions)} brush textures into {atlas_count} atlases")
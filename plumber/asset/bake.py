@@ -0,0 +1,243 @@
+from typing import Dict, Iterable, List, Optional, Set
+
+import bmesh
+import bpy
+from bpy.types import Context, Image, Material, Node, Object
+
+from .utils import truncate_name
+from ..plumber import log_error, log_info
+
+BAKED_UV_LAYER = "Baked"
+
+
+def is_blend_material(material: Optional[Material]) -> bool:
+    """
+    Check whether the vertex colors of the material mix two textures,
+    like the alpha of displacements blends WorldVertexTransition materials.
+    """
+    if material is None or not material.use_nodes:
+        return False
+
+    links = material.node_tree.links
+    nodes: List[Node] = [
+        node for node in material.node_tree.nodes if node.type == "VERTEX_COLOR"
+    ]
+    visited: Set[str] = set()
+
+    while nodes:
+        node = nodes.pop()
+        if node.name in visited:
+            continue
+        visited.add(node.name)
+
+        for link in links:
+            if link.from_node != node:
+                continue
+
+            if link.to_node.type == "MIX_RGB" and link.to_socket.name == "Fac":
+                return True
+
+            nodes.append(link.to_node)
+
+    return False
+
+
+def blend_face_groups(obj: Object) -> List[List[int]]:
+    """
+    Group the faces using blend materials by the vertices they share.
+    Displacements are built separately, so each group is one displacement,
+    unless welding joined it with its neighbors.
+    """
+    mesh = obj.data
+    blend_slots = [is_blend_material(material) for material in mesh.materials]
+    faces = [
+        polygon
+        for polygon in mesh.polygons
+        if polygon.material_index < len(blend_slots)
+        and blend_slots[polygon.material_index]
+    ]
+
+    parent = list(range(len(mesh.vertices)))
+
+    def find(i: int) -> int:
+        while parent[i] != i:
+            parent[i] = parent[parent[i]]
+            i = parent[i]
+        return i
+
+    for polygon in faces:
+        first = find(polygon.vertices[0])
+        for vertex in polygon.vertices[1:]:
+            parent[find(vertex)] = first
+
+    groups: Dict[int, List[int]] = {}
+    for polygon in faces:
+        groups.setdefault(find(polygon.vertices[0]), []).append(polygon.index)
+
+    return list(groups.values())
+
+
+def group_object(context: Context, obj: Object, group: List[int]) -> Object:
+    """Copy the faces of the group into a temporary object, in the same order."""
+    bm = bmesh.new()
+    bm.from_mesh(obj.data)
+    kept = set(group)
+    bmesh.ops.delete(
+        bm, geom=[face for face in bm.faces if face.index not in kept], context="FACES"
+    )
+
+    mesh = bpy.data.meshes.new(f"{obj.name}_bake")
+    bm.to_mesh(mesh)
+    bm.free()
+    for material in obj.data.materials:
+        mesh.materials.append(material)
+
+    group_obj = bpy.data.objects.new(mesh.name, mesh)
+    group_obj.matrix_world = obj.matrix_world
+    context.scene.collection.objects.link(group_obj)
+    return group_obj
+
+
+def unwrap(context: Context, obj: Object) -> None:
+    """Lay out every face of the object without overlaps in a new uv layer."""
+    mesh = obj.data
+    mesh.uv_layers.active = mesh.uv_layers.new(name=BAKED_UV_LAYER)
+
+    context.view_layer.objects.active = obj
+    bpy.ops.object.mode_set(mode="EDIT")
+    bpy.ops.mesh.select_all(action="SELECT")
+    bpy.ops.uv.smart_project(island_margin=0.01)
+    bpy.ops.object.mode_set(mode="OBJECT")
+
+
+def build_baked_material(name: str, image: Image) -> Material:
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    nt = material.node_tree
+
+    shader_node = nt.nodes.get("Principled BSDF")
+    uv_node = nt.nodes.new("ShaderNodeUVMap")
+    uv_node.uv_map = BAKED_UV_LAYER
+    uv_node.location = (-700, 300)
+    tex_node = nt.nodes.new("ShaderNodeTexImage")
+    tex_node.image = image
+    tex_node.location = (-400, 300)
+    nt.links.new(uv_node.outputs["UV"], tex_node.inputs["Vector"])
+    nt.links.new(tex_node.outputs["Color"], shader_node.inputs["Base Color"])
+
+    material["baked_blend"] = True
+    return material
+
+
+def bake_group(context: Context, obj: Object, group: List[int], size: int) -> None:
+    """Bake the faces of the group into their own texture used as their material."""
+    mesh = obj.data
+    name = truncate_name(f"{obj.name}_baked_{group[0]}")
+    image = bpy.data.images.new(name, size, size)
+
+    group_obj = group_object(context, obj, group)
+    group_mesh = group_obj.data
+
+    # every material of the group is baked into the active image node
+    bake_nodes = []
+    for material in mesh.materials:
+        tex_node = material.node_tree.nodes.new("ShaderNodeTexImage")
+        tex_node.image = image
+        material.node_tree.nodes.active = tex_node
+        bake_nodes.append((material, tex_node))
+
+    try:
+        unwrap(context, group_obj)
+
+        group_obj.select_set(True)
+        bpy.ops.object.bake(
+            type="DIFFUSE",
+            pass_filter={"COLOR"},
+            uv_layer=BAKED_UV_LAYER,
+            margin=4,
+        )
+
+        # the faces of the group object are in the order of the group
+        group_uvs = group_mesh.uv_layers[BAKED_UV_LAYER].data
+        baked_uvs = mesh.uv_layers[BAKED_UV_LAYER].data
+        for polygon_index, group_polygon in zip(group, group_mesh.polygons):
+            polygon = mesh.polygons[polygon_index]
+            for loop, group_loop in zip(
+                polygon.loop_indices, group_polygon.loop_indices
+            ):
+                baked_uvs[loop].uv = group_uvs[group_loop].uv
+    finally:
+        for material, tex_node in bake_nodes:
+            material.node_tree.nodes.remove(tex_node)
+        bpy.data.objects.remove(group_obj)
+        bpy.data.meshes.remove(group_mesh)
+
+    image.pack()
+
+    baked_slot = len(mesh.materials)
+    mesh.materials.append(build_baked_material(name, image))
+
+    for polygon_index in group:
+        mesh.polygons[polygon_index].material_index = baked_slot
+
+
+def bake_object(context: Context, obj: Object, size: int) -> None:
+    mesh = obj.data
+    # the faces of each displacement are laid out in the layer by their own bake
+    if BAKED_UV_LAYER not in mesh.uv_layers:
+        render_layer = mesh.uv_layers.active_index
+        mesh.uv_layers.new(name=BAKED_UV_LAYER)
+        mesh.uv_layers.active_index = render_layer
+
+    for group in blend_face_groups(obj):
+        bake_group(context, obj, group, size)
+
+
+def bake_blend_materials(
+    context: Context, objects: Iterable[Object], size: int
+) -> None:
+    """
+    Bake the blend materials of each displacement of the brush objects into a texture
+    of the given size, and move its faces to a material of the baked texture.
+    """
+    objects = [
+        obj
+        for obj in objects
+        if obj.type == "MESH"
+        and obj.data.polygons
+        and any(is_blend_material(material) for material in obj.data.materials)
+        and all(
+            material is not None and material.use_nodes
+            for material in obj.data.materials
+        )
+    ]
+    if not objects:
+        return
+
+    log_info(f"baking blend materials of {len(objects)} brushes...")
+
+    scene = context.scene
+    engine = scene.render.engine
+    selected_objects = context.selected_objects
+    active_object = context.view_layer.objects.active
+
+    for selected_obj in selected_objects:
+        selected_obj.select_set(False)
+
+    scene.render.engine = "CYCLES"
+
+    try:
+        for obj in objects:
+            try:
+                bake_object(context, obj, size)
+            except RuntimeError as err:
+                log_error(f"baking blend materials of `{obj.name}` failed: {err}")
+    finally:
+        scene.render.engine = engine
+
+        for selected_obj in selected_objects:
+            selected_obj.select_set(True)
+
+        context.view_layer.objects.active = active_object
+
+    log_info("blend materials baked")
@@ -1,24 +1,222 @@
+from typing import Dict, List, Optional, Union
+
 import bpy
-from bpy.types import Collection
+from bpy.types import Collection, Material, Mesh, Object
+from mathutils import Matrix, Vector
+
+from .utils import store_editor_groups, truncate_name
+from ..plumber import (
+    BrushMotion,
+    BrushOrigin,
+    BrushRender,
+    BrushRotation,
+    BuiltBrushEntity,
+    BuiltSolid,
+    MergedSolids,
+)
+
+# rendermode 10 doesn't draw the entity in game
+RENDER_MODE_NONE = 10
 
-from .utils import truncate_name
-from ..plumber import BuiltBrushEntity, BuiltSolid, MergedSolids
+# brush entities which only affect visibility and use tool materials not meant to be seen
+PORTAL_CLASS_NAMES = {"func_areaportal", "func_areaportalwindow", "func_occluder"}
 
 
-def import_brush(brush: BuiltBrushEntity, collection: Collection) -> None:
+def import_brush(
+    brush: BuiltBrushEntity, collection: Collection, smooth_angle: float = 0.0
+) -> List[Object]:
     id = brush.id()
     class_name = brush.class_name()
     brush_name = f"{class_name}_{id}"
 
+    objects = []
+
+    # read before the solids are taken out of the brush
+    origin_brush = brush.origin_brush()
+    groups = brush.editor_groups()
+    keyvalues = brush.keyvalues()
+
     merged_solids = brush.merged_solids()
     if merged_solids is not None:
-        import_merged_solids(collection, brush_name, merged_solids)
+        obj = import_merged_solids(collection, brush_name, merged_solids, smooth_angle)
+        store_editor_groups(obj, groups)
+        objects.append(obj)
 
     for solid in brush.solids():
-        import_solid(collection, brush_name, solid)
+        obj = import_solid(collection, brush_name, solid, smooth_angle)
+        # the solids of the world are grouped on their own, those of entities with them
+        if groups is None or groups.world():
+            store_editor_groups(obj, solid.editor_groups())
+        else:
+            store_editor_groups(obj, groups)
+        # separate solids are replaced by their own id when a changed map is imported
+        obj["vmf_solid_id"] = solid.id()
+        objects.append(obj)
+
+    # used for exporting the solids back into the right entity
+    for obj in objects:
+        obj["vmf_class_name"] = class_name
+        obj["vmf_id"] = id
+        if keyvalues is not None:
+            obj["props"] = dict(keyvalues)
+
+        if origin_brush is not None:
+            set_pivot(obj, Vector(origin_brush))
+            obj["origin_brush"] = True
+
+    return objects
+
+
+def apply_brush_renders(
+    renders: List[BrushRender], brush_objects: Dict[int, List[Object]]
+) -> None:
+    # the render settings may arrive before the brushes, so they are applied at the end
+    for render in renders:
+        for obj in brush_objects.get(render.id(), []):
+            obj.color = render.color()
+            obj["rendermode"] = render.render_mode()
+
+            alpha = render.color()[3]
+            if render.render_mode() == RENDER_MODE_NONE:
+                obj.hide_render = True
+            elif alpha < 1.0:
+                fade_materials(obj, alpha)
+
+
+def fade_materials(obj: Object, alpha: float) -> None:
+    # the materials are shared with other brushes, so faded copies replace them
+    materials = obj.data.materials
+    for index, material in enumerate(materials):
+        if material is not None and material.use_nodes:
+            materials[index] = faded_material(material, alpha)
+
+
+def faded_material(material: Material, alpha: float) -> Material:
+    """Get a copy of the material mixed with transparency by the render amount."""
+    name = truncate_name(f"{material.name}_renderamt{round(alpha * 255)}")
+    faded = bpy.data.materials.get(name)
+    if faded is not None:
+        return faded
+
+    faded = material.copy()
+    faded.name = name
+    faded.blend_method = "BLEND"
+
+    nt = faded.node_tree
+    out_node = next(
+        (
+            node
+            for node in nt.nodes
+            if node.type == "OUTPUT_MATERIAL" and node.is_active_output
+        ),
+        None,
+    )
+    if out_node is None or not out_node.inputs["Surface"].is_linked:
+        return faded
+
+    surface = out_node.inputs["Surface"].links[0].from_socket
+    transparent_node = nt.nodes.new("ShaderNodeBsdfTransparent")
+    transparent_node.location = (out_node.location.x, out_node.location.y - 300)
+    mix_node = nt.nodes.new("ShaderNodeMixShader")
+    mix_node.location = (out_node.location.x, out_node.location.y + 200)
+    mix_node.inputs["Fac"].default_value = alpha
+
+    nt.links.new(transparent_node.outputs["BSDF"], mix_node.inputs[1])
+    nt.links.new(surface, mix_node.inputs[2])
+    nt.links.new(mix_node.outputs["Shader"], out_node.inputs["Surface"])
+
+    return faded
+
+
+def apply_brush_origins(
+    origins: List[BrushOrigin], brush_objects: Dict[int, List[Object]]
+) -> None:
+    # rotating brush entities turn around their origin, so it's used as the pivot.
+    # legacy origin brushes take precedence, like when compiling the map
+    for origin in origins:
+        for obj in brush_objects.get(origin.id(), []):
+            if not obj.get("origin_brush", False):
+                set_pivot(obj, Vector(origin.origin()))
+
+
+def apply_brush_motions(
+    motions: List[BrushMotion], brush_objects: Dict[int, List[Object]]
+) -> None:
+    for motion in motions:
+        objects = brush_objects.get(motion.id(), [])
+        if not objects:
+            continue
+
+        direction = Vector(motion.direction())
+        distance = motion.distance()
+        if distance is None:
+            # doors travel their own size along the direction
+            size = bounds_size(objects)
+            distance = sum(abs(d) * s for d, s in zip(direction, size)) - motion.lip()
+
+        travel = direction * max(distance, 0.0)
+        # relative to the placed location, so they stay valid when the map is moved
+        closed = -travel * motion.start_position()
+
+        for obj in objects:
+            obj["closed_offset"] = tuple(closed)
+            obj["open_offset"] = tuple(closed + travel)
+            obj["move_speed"] = motion.speed()
+
+
+def apply_brush_rotations(
+    rotations: List[BrushRotation],
+    brush_objects: Dict[int, List[Object]],
+    fps: float,
+) -> None:
+    # spins around the pivot set from the origin forever, like in game
+    for rotation in rotations:
+        for obj in brush_objects.get(rotation.id(), []):
+            action = bpy.data.actions.new(f"{obj.name}_rotation")
+            obj.animation_data_create()
+            obj.animation_data.action = action
 
+            for index, axis in enumerate(rotation.axis()):
+                if axis == 0:
+                    continue
 
-def import_solid(collection: Collection, brush_name: str, solid: BuiltSolid) -> None:
+                fcurve = action.fcurves.new("rotation_euler", index=index)
+                # one second of rotation, continued by the extrapolation
+                fcurve.keyframe_points.add(2)
+                fcurve.keyframe_points.foreach_set(
+                    "co", (0, 0, fps, axis * rotation.speed())
+                )
+                for keyframe in fcurve.keyframe_points:
+                    keyframe.interpolation = "LINEAR"
+                fcurve.extrapolation = "LINEAR"
+                # entities which aren't on from the start only spin once triggered
+                fcurve.mute = not rotation.starts_on()
+
+
+def bounds_size(objects: List[Object]) -> Vector:
+    # the world matrices aren't updated yet, brushes are only moved and scaled
+    points = [
+        obj.matrix_basis @ vertex.co for obj in objects for vertex in obj.data.vertices
+    ]
+    if not points:
+        return Vector((0.0, 0.0, 0.0))
+
+    return Vector(
+        max(p[axis] for p in points) - min(p[axis] for p in points) for axis in range(3)
+    )
+
+
+def set_pivot(obj: Object, pivot: Vector) -> None:
+    # the mesh is moved the opposite way, so it stays in place
+    offset = pivot - obj.location
+    local_offset = Vector(o / s for o, s in zip(offset, obj.scale))
+    obj.data.transform(Matrix.Translation(-local_offset))
+    obj.location = pivot
+
+
+def import_solid(
+    collection: Collection, brush_name: str, solid: BuiltSolid, smooth_angle: float
+) -> Object:
     id = solid.id()
     solid_name = f"{brush_name}_{id}"
     mesh = bpy.data.meshes.new(solid_name)
@@ -33,17 +231,23 @@ def import_solid(collection: Collection, brush_name: str, solid: BuiltSolid) ->
     mesh.polygons.foreach_set("vertices", solid.polygon_vertices())
     mesh.polygons.foreach_set("material_index", solid.polygon_material_indices())
 
-    mesh.shade_flat()
-
     mesh.update()
 
+    shade_brush(mesh, smooth_angle)
+
     uv_layer = mesh.uv_layers.new()
     uv_layer.data.foreach_set("uv", solid.loop_uvs())
 
     color_layer = mesh.vertex_colors.new(name="Col", do_init=False)
     color_layer.data.foreach_set("color", solid.loop_colors())
 
-    for material in solid.materials():
+    lightmap_resolution = import_lightmap_uvs(mesh, solid)
+
+    materials = solid.materials()
+    store_material_paths(mesh, materials, solid.polygon_material_indices())
+    store_texture_axes(mesh, solid)
+
+    for material in materials:
         material_data = bpy.data.materials.get(truncate_name(material))
         if material_data is None:
             material_data = bpy.data.materials.new(material)
@@ -52,12 +256,19 @@ def import_solid(collection: Collection, brush_name: str, solid: BuiltSolid) ->
     obj = bpy.data.objects.new(solid_name, object_data=mesh)
     obj.location = solid.position()
     obj.scale = solid.scale()
+    if lightmap_resolution is not None:
+        obj["lightmap_resolution"] = lightmap_resolution
     collection.objects.link(obj)
 
+    return obj
+
 
 def import_merged_solids(
-    collection: Collection, brush_name: str, merged_solids: MergedSolids
-) -> None:
+    collection: Collection,
+    brush_name: str,
+    merged_solids: MergedSolids,
+    smooth_angle: float,
+) -> Object:
     mesh = bpy.data.meshes.new(brush_name)
 
     vertices = merged_solids.vertices()
@@ -72,17 +283,23 @@ def import_merged_solids(
         "material_index", merged_solids.polygon_material_indices()
     )
 
-    mesh.shade_flat()
-
     mesh.update()
 
+    shade_brush(mesh, smooth_angle)
+
     uv_layer = mesh.uv_layers.new()
     uv_layer.data.foreach_set("uv", merged_solids.loop_uvs())
 
     color_layer = mesh.vertex_colors.new(name="Col", do_init=False)
     color_layer.data.foreach_set("color", merged_solids.loop_colors())
 
-    for material in merged_solids.materials():
+    lightmap_resolution = import_lightmap_uvs(mesh, merged_solids)
+
+    materials = merged_solids.materials()
+    store_material_paths(mesh, materials, merged_solids.polygon_material_indices())
+    store_texture_axes(mesh, merged_solids)
+
+    for material in materials:
         material_data = bpy.data.materials.get(truncate_name(material))
         if material_data is None:
             material_data = bpy.data.materials.new(material)
@@ -91,4 +308,61 @@ def import_merged_solids(
     obj = bpy.data.objects.new(brush_name, object_data=mesh)
     obj.location = merged_solids.position()
     obj.scale = merged_solids.scale()
+    if lightmap_resolution is not None:
+        obj["lightmap_resolution"] = lightmap_resolution
     collection.objects.link(obj)
+
+    return obj
+
+
+def shade_brush(mesh: Mesh, smooth_angle: float) -> None:
+    # edges between faces at a larger angle stay sharp, so brushes keep their hard edges
+    # while displacements and curved brushes are shaded smooth
+    if smooth_angle > 0:
+        mesh.shade_smooth()
+        mesh.set_sharp_from_angle(angle=smooth_angle)
+    else:
+        mesh.shade_flat()
+
+
+def import_lightmap_uvs(
+    mesh: Mesh, solids: Union[BuiltSolid, MergedSolids]
+) -> Optional[int]:
+    # generated only when enabled, at the resolution which matches the in game density
+    resolution = solids.lightmap_resolution()
+    if resolution is None:
+        return None
+
+    lightmap_layer = mesh.uv_layers.new(name="Lightmap")
+    lightmap_layer.data.foreach_set("uv", solids.lightmap_uvs())
+
+    # the compiler's texel density of each face, for baking tools which replicate it
+    attribute = mesh.attributes.new("vmf_lightmap_scale", "FLOAT", "FACE")
+    attribute.data.foreach_set("value", solids.lightmap_scales())
+
+    return resolution
+
+
+def store_material_paths(
+    mesh: Mesh, materials: List[str], material_indices: memoryview
+) -> None:
+    # material slots are renamed, merged or reordered by users and exporters,
+    # so each face keeps the index of its original material path separately
+    mesh["vmf_material_paths"] = [f"materials/{material}.vmt" for material in materials]
+
+    attribute = mesh.attributes.new("vmf_material", "INT", "FACE")
+    attribute.data.foreach_set("value", material_indices)
+
+
+def store_texture_axes(mesh: Mesh, solids: Union[BuiltSolid, MergedSolids]) -> None:
+    # the original texture axes of the sides, exported instead of world aligned ones.
+    # like the material paths, each face keeps an index into them
+    axes = solids.texture_axes()
+    if not axes:
+        return
+
+    mesh["vmf_uaxes"] = [u_axis for u_axis, _ in axes]
+    mesh["vmf_vaxes"] = [v_axis for _, v_axis in axes]
+
+    attribute = mesh.attributes.new("vmf_texture_axes", "INT", "FACE")
+    attribute.data.foreach_set("value", solids.face_texture_axes())
@@ -0,0 +1,25 @@
+import bpy
+from bpy.types import Collection
+
+from .utils import store_editor_groups, store_vmf_id
+from ..plumber import ColorCorrection
+
+
+def import_color_correction(
+    color_correction: ColorCorrection, collection: Collection
+) -> None:
+    name = f"color_correction_{color_correction.id()}"
+
+    obj = bpy.data.objects.new(name, object_data=None)
+    obj.location = color_correction.position()
+    obj["props"] = color_correction.properties()
+    store_editor_groups(obj, color_correction.editor_groups())
+    store_vmf_id(obj, color_correction.id())
+    obj["lut_path"] = color_correction.lut_path()
+    obj["cube_path"] = color_correction.cube_path()
+    obj["weight"] = color_correction.weight()
+    obj["max_weight"] = color_correction.max_weight()
+    obj["min_falloff"] = color_correction.min_falloff()
+    obj["max_falloff"] = color_correction.max_falloff()
+
+    collection.objects.link(obj)
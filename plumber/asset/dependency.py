@@ -0,0 +1,37 @@
+import json
+from typing import Dict, Set
+
+from ..plumber import Dependency
+
+# assets placed directly by the map, the roots of the dependency graph
+MAP_KINDS = {"brush", "overlay", "prop"}
+
+
+class DependencyGraph:
+    def __init__(self, map_name: str, path: str) -> None:
+        self.path = path
+        self.root = f"map:{map_name}"
+        self.nodes: Dict[str, Set[str]] = {self.root: set()}
+
+    def add(self, dependency: Dependency) -> None:
+        node = f"{dependency.kind()}:{dependency.name()}"
+        dependency_kind = dependency.dependency_kind()
+
+        self.nodes.setdefault(node, set()).update(
+            f"{dependency_kind}:{name}" for name in dependency.dependencies()
+        )
+
+        if dependency.kind() in MAP_KINDS:
+            self.nodes[self.root].add(node)
+
+    def write(self) -> None:
+        graph = {
+            "root": self.root,
+            "nodes": {
+                node: sorted(dependencies)
+                for node, dependencies in sorted(self.nodes.items())
+            },
+        }
+
+        with open(self.path, "w", encoding="utf-8") as f:
+            json.dump(graph, f, indent=2)
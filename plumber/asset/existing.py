@@ -0,0 +1,44 @@
+from typing import List
+
+import bpy
+
+
+def collect_existing_assets() -> List[str]:
+    """
+    Get the names of the materials, textures and models already in the blend file,
+    so re-importing a map can skip them.
+    """
+    names = set()
+
+    for material in bpy.data.materials:
+        # materials referenced by models are created empty before they are imported
+        if material.use_nodes and not material.get("placeholder", False):
+            _add_path_id(names, material)
+
+    for image in bpy.data.images:
+        _add_path_id(names, image)
+
+    for obj in bpy.data.objects:
+        if is_existing_model(obj):
+            _add_path_id(names, obj)
+
+    return sorted(names)
+
+
+def is_existing_model(obj) -> bool:
+    """Whether a prop object from an earlier import has its model."""
+    if obj.get("placeholder", False):
+        return False
+
+    # props whose model failed to load are empties without children
+    return (
+        obj.type != "EMPTY"
+        or len(obj.children) > 0
+        or obj.instance_collection is not None
+    )
+
+
+def _add_path_id(names: set, datablock) -> None:
+    path_id = datablock.get("path_id")
+    if isinstance(path_id, str):
+        names.add(path_id)
@@ -0,0 +1,65 @@
+import bpy
+from bpy.types import Collection, Material, Object
+
+from .utils import truncate_name
+from ..plumber import GoldSrcModel
+
+
+def import_goldsrc_model(model: GoldSrcModel, collection: Collection) -> Object:
+    name = model.name()
+    mesh = bpy.data.meshes.new(name)
+
+    vertices = model.vertices()
+    mesh.vertices.add(len(vertices) // 3)
+    mesh.loops.add(model.loops_len())
+    mesh.polygons.add(model.polygons_len())
+    mesh.vertices.foreach_set("co", vertices)
+    mesh.polygons.foreach_set("loop_total", model.polygon_loop_totals())
+    mesh.polygons.foreach_set("loop_start", model.polygon_loop_starts())
+    mesh.polygons.foreach_set("vertices", model.polygon_vertices())
+    mesh.polygons.foreach_set("material_index", model.polygon_material_indices())
+
+    mesh.shade_flat()
+
+    mesh.update()
+
+    uv_layer = mesh.uv_layers.new()
+    uv_layer.data.foreach_set("uv", model.loop_uvs())
+
+    texture_ext = model.texture_ext()
+    for material in model.materials():
+        mesh.materials.append(get_goldsrc_material(material, texture_ext))
+
+    obj = bpy.data.objects.new(name, object_data=mesh)
+    obj.location = model.position()
+    obj.scale = model.scale()
+    collection.objects.link(obj)
+
+    return obj
+
+
+def get_goldsrc_material(texture: str, texture_ext: str) -> Material:
+    material_name = truncate_name(texture)
+
+    material_data = bpy.data.materials.get(material_name)
+    if material_data is not None:
+        return material_data
+
+    material_data = bpy.data.materials.new(material_name)
+    material_data.use_nodes = True
+    nt = material_data.node_tree
+    bsdf = nt.nodes["Principled BSDF"]
+
+    image_data = bpy.data.images.get(truncate_name(texture + texture_ext))
+    if image_data is not None:
+        image_node = nt.nodes.new("ShaderNodeTexImage")
+        image_node.image = image_data
+        image_node.location = (-300, 300)
+        nt.links.new(image_node.outputs["Color"], bsdf.inputs["Base Color"])
+
+        # textures starting with { use palette transparency
+        if texture.startswith("{"):
+            nt.links.new(image_node.outputs["Alpha"], bsdf.inputs["Alpha"])
+            material_data.blend_method = "CLIP"
+
+    return material_data
@@ -1,25 +1,37 @@
+import math
+from typing import List, Optional
+
 import bpy
 from bpy.types import Context, Collection
 
-from ..plumber import Light, SpotLight, EnvLight
+from .material import get_texture_image
+from .utils import store_editor_groups, store_vmf_id, truncate_name
+from ..plumber import Light, SpotLight, PointSpotlight, ProjectedTexture, EnvLight, Sun, LightGlow
 
 
-def import_light(light: Light, collection: Collection) -> None:
+def import_light(light: Light, collection: Collection) -> bpy.types.Object:
     name = f"light_{light.id()}"
 
     light_data = bpy.data.lights.new(name, "POINT")
     light_data.cycles.use_multiple_importance_sampling = False
     light_data.color = light.color()
     light_data.energy = light.energy()
+    import_cutoff_distance(light_data, light.cutoff_distance())
 
     obj = bpy.data.objects.new(name, object_data=light_data)
     collection.objects.link(obj)
 
     obj.location = light.position()
     obj["props"] = light.properties()
+    store_editor_groups(obj, light.editor_groups())
+    store_vmf_id(obj, light.id())
+
+    import_energy_keyframes(light_data, light.energy_keyframes())
+
+    return obj
 
 
-def import_spot_light(light: SpotLight, collection: Collection) -> None:
+def import_spot_light(light: SpotLight, collection: Collection) -> bpy.types.Object:
     name = f"light_spot_{light.id()}"
 
     light_data = bpy.data.lights.new(name, "SPOT")
@@ -28,6 +40,63 @@ def import_spot_light(light: SpotLight, collection: Collection) -> None:
     light_data.energy = light.energy()
     light_data.spot_size = light.spot_size()
     light_data.spot_blend = light.spot_blend()
+    import_cutoff_distance(light_data, light.cutoff_distance())
+
+    obj = bpy.data.objects.new(name, object_data=light_data)
+    collection.objects.link(obj)
+
+    obj.location = light.position()
+    obj.rotation_euler = light.rotation()
+    obj["props"] = light.properties()
+    store_editor_groups(obj, light.editor_groups())
+    store_vmf_id(obj, light.id())
+
+    import_energy_keyframes(light_data, light.energy_keyframes())
+
+    return obj
+
+
+def import_cutoff_distance(
+    light_data: bpy.types.Light, cutoff_distance: Optional[float]
+) -> None:
+    if cutoff_distance is None:
+        return
+
+    light_data.use_custom_distance = True
+    light_data.cutoff_distance = cutoff_distance
+
+
+def import_energy_keyframes(light_data: bpy.types.Light, keyframes: List[float]) -> None:
+    if not keyframes:
+        return
+
+    light_data.animation_data_create()
+    action = bpy.data.actions.new(f"{light_data.name}_style")
+    light_data.animation_data.action = action
+
+    curve = action.fcurves.new("energy")
+    curve.keyframe_points.add(len(keyframes) // 2)
+    curve.keyframe_points.foreach_set("co", keyframes)
+
+    for point in curve.keyframe_points:
+        point.interpolation = "CONSTANT"
+
+    curve.modifiers.new("CYCLES")
+
+
+def import_point_spotlight(
+    light: PointSpotlight, collection: Collection
+) -> bpy.types.Object:
+    name = f"point_spotlight_{light.id()}"
+
+    light_data = bpy.data.lights.new(name, "SPOT")
+    light_data.cycles.use_multiple_importance_sampling = False
+    light_data.color = light.color()
+    light_data.energy = light.energy()
+    light_data.spot_size = light.spot_size()
+    light_data.spot_blend = 1.0
+    light_data.use_custom_distance = True
+    light_data.cutoff_distance = light.beam_length()
 
     obj = bpy.data.objects.new(name, object_data=light_data)
     collection.objects.link(obj)
@@ -35,6 +104,92 @@ def import_spot_light(light: SpotLight, collection: Collection) -> None:
     obj.location = light.position()
     obj.rotation_euler = light.rotation()
     obj["props"] = light.properties()
+    store_editor_groups(obj, light.editor_groups())
+    store_vmf_id(obj, light.id())
+    obj["beam_length"] = light.beam_length()
+    obj["beam_width"] = light.beam_width()
+    obj["hdr_scale"] = light.hdr_scale()
+
+    return obj
+
+
+def import_projected_texture(
+    light: ProjectedTexture, collection: Collection
+) -> bpy.types.Object:
+    name = f"env_projectedtexture_{light.id()}"
+
+    light_data = bpy.data.lights.new(name, "SPOT")
+    light_data.cycles.use_multiple_importance_sampling = False
+    light_data.color = light.color()
+    light_data.energy = light.energy()
+    light_data.spot_size = light.fov()
+    light_data.spot_blend = 0.0
+
+    obj = bpy.data.objects.new(name, object_data=light_data)
+    collection.objects.link(obj)
+
+    obj.location = light.position()
+    obj.rotation_euler = light.rotation()
+    obj["props"] = light.properties()
+    store_editor_groups(obj, light.editor_groups())
+    store_vmf_id(obj, light.id())
+
+    texture = light.texture()
+    if texture is not None:
+        obj["texture"] = truncate_name(texture + light.texture_ext())
+
+    return obj
+
+
+def apply_projected_textures(objects: List[bpy.types.Object]) -> None:
+    # the projected textures may arrive after the lights, so they are linked at the end
+    for obj in objects:
+        image = get_texture_image(obj.get("texture", ""))
+        if image is None:
+            continue
+
+        light_data = obj.data
+        light_data.use_nodes = True
+        nt = light_data.node_tree
+
+        coord_node = nt.nodes.new("ShaderNodeTexCoord")
+        coord_node.location = (-1100, 0)
+
+        # project the normalized direction onto the texture plane, where the edges of
+        # the field of view are at 0 and 1: 0.5 + x / z * 0.5 / tan(fov / 2)
+        separate_node = nt.nodes.new("ShaderNodeSeparateXYZ")
+        separate_node.location = (-900, 0)
+        nt.links.new(coord_node.outputs["Normal"], separate_node.inputs["Vector"])
+
+        scale = 0.5 / math.tan(light_data.spot_size / 2)
+
+        combine_node = nt.nodes.new("ShaderNodeCombineXYZ")
+        combine_node.location = (-300, 0)
+
+        for i, axis in enumerate(["X", "Y"]):
+            divide_node = nt.nodes.new("ShaderNodeMath")
+            divide_node.operation = "DIVIDE"
+            divide_node.location = (-700, 100 - 200 * i)
+            nt.links.new(separate_node.outputs[axis], divide_node.inputs[0])
+            nt.links.new(separate_node.outputs["Z"], divide_node.inputs[1])
+
+            offset_node = nt.nodes.new("ShaderNodeMath")
+            offset_node.operation = "MULTIPLY_ADD"
+            offset_node.location = (-500, 100 - 200 * i)
+            nt.links.new(divide_node.outputs["Value"], offset_node.inputs[0])
+            offset_node.inputs[1].default_value = scale
+            offset_node.inputs[2].default_value = 0.5
+            nt.links.new(offset_node.outputs["Value"], combine_node.inputs[axis])
+
+        tex_node = nt.nodes.new("ShaderNodeTexImage")
+        tex_node.image = image
+        tex_node.extension = "CLIP"
+        tex_node.location = (-100, 0)
+        nt.links.new(combine_node.outputs["Vector"], tex_node.inputs["Vector"])
+
+        emission_node = nt.nodes.get("Emission")
+        if emission_node is not None:
+            nt.links.new(tex_node.outputs["Color"], emission_node.inputs["Color"])
 
 
 def import_env_light(light: EnvLight, context: Context, collection: Collection) -> None:
@@ -49,6 +204,8 @@ def import_env_light(light: EnvLight, context: Context, collection: Collection)
     obj = bpy.data.objects.new(name, object_data=light_data)
     collection.objects.link(obj)
     obj["props"] = light.properties()
+    store_editor_groups(obj, light.editor_groups())
+    store_vmf_id(obj, light.id())
 
     obj.location = light.position()
     obj.rotation_euler = light.rotation()
@@ -72,3 +229,76 @@ def import_env_light(light: EnvLight, context: Context, collection: Collection)
 
     bg_node.inputs["Color"].default_value = light.ambient_color()
     bg_node.inputs["Strength"].default_value = light.ambient_strength()
+
+
+def import_sun(sun: Sun, collection: Collection) -> None:
+    name = f"env_sun_{sun.id()}"
+
+    # env_sun only draws the sun sprite in game, so the light is created without energy
+    # to mark the direction without affecting lighting from light_environment
+    light_data = bpy.data.lights.new(name, "SUN")
+    light_data.color = sun.color()
+    light_data.energy = 0.0
+
+    obj = bpy.data.objects.new(name, object_data=light_data)
+    collection.objects.link(obj)
+
+    obj.location = sun.position()
+    obj.rotation_euler = sun.rotation()
+    obj["props"] = sun.properties()
+    store_editor_groups(obj, sun.editor_groups())
+    store_vmf_id(obj, sun.id())
+    obj["size"] = sun.size()
+    obj["overlay_size"] = sun.overlay_size()
+    obj["overlay_color"] = sun.overlay_color()
+    obj["hdr_scale"] = sun.hdr_scale()
+
+
+def import_light_glow(glow: LightGlow, collection: Collection) -> bpy.types.Object:
+    name = f"{glow.class_name()}_{glow.id()}"
+    width, height = glow.size()
+
+    mesh_data = bpy.data.meshes.new(name)
+    mesh_data.from_pydata(
+        [
+            (-width / 2, 0, -height / 2),
+            (width / 2, 0, -height / 2),
+            (width / 2, 0, height / 2),
+            (-width / 2, 0, height / 2),
+        ],
+        [],
+        [(0, 1, 2, 3)],
+    )
+
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    material.blend_method = "BLEND"
+    nt = material.node_tree
+    nt.nodes.clear()
+
+    out_node = nt.nodes.new("ShaderNodeOutputMaterial")
+    out_node.location = (300, 0)
+
+    emission_node = nt.nodes.new("ShaderNodeEmission")
+    emission_node.location = (0, 0)
+    *color, alpha = glow.color()
+    emission_node.inputs["Color"].default_value = (*color, 1.0)
+    emission_node.inputs["Strength"].default_value = glow.hdr_scale() * alpha
+
+    nt.links.new(emission_node.outputs["Emission"], out_node.inputs["Surface"])
+    mesh_data.materials.append(material)
+
+    obj = bpy.data.objects.new(name, object_data=mesh_data)
+    collection.objects.link(obj)
+
+    obj.location = glow.position()
+    obj.rotation_euler = glow.rotation()
+    obj["props"] = glow.properties()
+    store_editor_groups(obj, glow.editor_groups())
+    store_vmf_id(obj, glow.id())
+
+    sprite = glow.material()
+    if sprite is not None:
+        obj["material"] = sprite
+
+    return obj
@@ -1,7 +1,7 @@
-from typing import List
+from typing import Dict, List, Optional, Tuple
 
 import bpy
-from bpy.types import ShaderNode
+from bpy.types import Image, ShaderNode
 
 from .utils import truncate_name
 from ..plumber import Material, Texture, TextureRef
@@ -10,6 +10,7 @@ from ..plumber import Material, Texture, TextureRef
 FORMAT_MAP = {
     ".tga": "TARGA_RAW",
     ".png": "PNG",
+    ".exr": "OPEN_EXR",
 }
 
 NODE_INPUT_SOCKET_MAP = {
@@ -17,21 +18,84 @@ NODE_INPUT_SOCKET_MAP = {
     "Emission": "Emission Color",
 }
 
+# image names of textures with the same contents as an earlier texture, by their own name
+texture_aliases: Dict[str, str] = {}
+# color spaces of the images of this import, set by the first material using them
+image_color_spaces: Dict[str, str] = {}
+# copies of shared images for uses in another color space, by image name and color space
+color_space_copies: Dict[Tuple[str, str], str] = {}
+
+
+def reset_texture_aliases() -> None:
+    """Forget the duplicate textures of the previous import."""
+    texture_aliases.clear()
+    image_color_spaces.clear()
+    color_space_copies.clear()
+
+
+def get_texture_image(
+    image_name: str, color_space: Optional[str] = None
+) -> Optional[Image]:
+    """Get the image of a texture, following duplicates to the image of the original.
+    Duplicates used in another color space than the original get a copy of its image."""
+    original_name = texture_aliases.get(image_name, image_name)
+    image = bpy.data.images.get(original_name)
+    if image is None or color_space is None:
+        return image
+
+    claimed = image_color_spaces.setdefault(original_name, color_space)
+    is_shared = original_name in texture_aliases.values()
+    if claimed == color_space or not is_shared:
+        return image
+
+    copy_name = color_space_copies.get((original_name, color_space))
+    if copy_name is None:
+        copy = image.copy()
+        copy.name = truncate_name(f"{original_name} {color_space}")
+        copy.colorspace_settings.name = color_space
+        copy_name = copy.name
+        color_space_copies[(original_name, color_space)] = copy_name
+
+    return bpy.data.images.get(copy_name)
+
 
 def import_texture(texture: Texture) -> None:
     format_ext = texture.format_ext()
     texture_name = truncate_name(texture.name() + format_ext)
 
+    original = texture.duplicate_of()
+    if original is not None:
+        texture_aliases[texture_name] = truncate_name(original + format_ext)
+        return
+
+    # an earlier import may have found this texture to be a duplicate
+    texture_aliases.pop(texture_name, None)
+
     image_data = bpy.data.images.get(texture_name)
     if image_data is None:
         width = texture.width()
         height = texture.height()
-        image_data = bpy.data.images.new(texture_name, width, height, alpha=True)
-        image_data.file_format = FORMAT_MAP[format_ext]
-        image_data.source = "FILE"
-        bytes = texture.bytes()
-        image_data.pack(data=bytes, data_len=len(bytes))
+
+        if format_ext == "":
+            # raw pixels are copied into the image directly, without decoding a file
+            image_data = bpy.data.images.new(
+                texture_name,
+                width,
+                height,
+                alpha=True,
+                float_buffer=texture.float_buffer(),
+            )
+            image_data.pixels.foreach_set(texture.pixels())
+            image_data.pack()
+        else:
+            image_data = bpy.data.images.new(texture_name, width, height, alpha=True)
+            image_data.file_format = FORMAT_MAP[texture.encoding_ext()]
+            image_data.source = "FILE"
+            bytes = texture.bytes()
+            image_data.pack(data=bytes, data_len=len(bytes))
+
         image_data.alpha_mode = "CHANNEL_PACKED"
+        image_data["path_id"] = texture.name()
 
 
 def import_material(material: Material) -> None:
@@ -51,9 +115,16 @@ def import_material(material: Material) -> None:
 
     built_data = material.data()
     texture_ext = material.texture_ext()
+    color_spaces = {
+        truncate_name(texture_name + texture_ext): color_space
+        for texture_name, color_space in built_data.texture_color_spaces().items()
+    }
+
+    def resolve(value):
+        return resolve_value(value, texture_ext, color_spaces)
 
     for property, value in built_data.properties().items():
-        setattr(material_data, property, resolve_value(value, texture_ext))
+        setattr(material_data, property, resolve(value))
 
     built_nodes: List[ShaderNode] = []
 
@@ -62,11 +133,11 @@ def import_material(material: Material) -> None:
         built_node.location = node.position()
 
         for property, value in node.properties().items():
-            setattr(built_node, property, resolve_value(value, texture_ext))
+            setattr(built_node, property, resolve(value))
 
         for socket, value in node.socket_values().items():
             socket = resolve_input_socket(socket)
-            built_node.inputs[socket].default_value = resolve_value(value, texture_ext)
+            built_node.inputs[socket].default_value = resolve(value)
 
         for socket, link in node.socket_links().items():
             target_node: ShaderNode = built_nodes[link.node_index()]
@@ -81,16 +152,16 @@ def import_material(material: Material) -> None:
 
     nt.links.new(shader_node.outputs["BSDF"], out_node.inputs["Surface"])
 
-    for texture_name, color_space in built_data.texture_color_spaces().items():
-        image_name = truncate_name(texture_name + texture_ext)
-        image = bpy.data.images[image_name]
-        image.colorspace_settings.name = color_space
+    for image_name, color_space in color_spaces.items():
+        image = get_texture_image(image_name, color_space)
+        if image is not None:
+            image.colorspace_settings.name = color_space
 
 
-def resolve_value(value, texture_ext: str):
+def resolve_value(value, texture_ext: str, color_spaces: Dict[str, str]):
     if isinstance(value, TextureRef):
         texture_name = truncate_name(value.path() + texture_ext)
-        return bpy.data.images.get(texture_name)
+        return get_texture_image(texture_name, color_spaces.get(texture_name))
 
     return value
 
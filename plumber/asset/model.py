@@ -1,3 +1,4 @@
+from math import radians
 from typing import Dict, List, Optional
 
 import bpy
@@ -12,33 +13,48 @@ from bpy.types import (
 )
 from mathutils import Euler, Vector, Matrix
 
+from .existing import is_existing_model
 from .utils import find_armature_modifier, get_unknown_material, truncate_name
 from ..plumber import (
+    AnimationEvent,
     BoneRestData,
+    IkChain,
+    JiggleBone,
     LoadedAnimation,
     LoadedBone,
     LoadedMesh,
     Model,
     QuaternionData,
+    RagdollJoint,
     VectorData,
 )
 
 
 class ModelState:
     def __init__(
-        self, model_obj: Object, children: List[Object], collection: Collection
+        self,
+        model_obj: Object,
+        children: List[Object],
+        collection: Collection,
+        static_prop: bool = False,
     ) -> None:
         self.object = model_obj
         self.children = children
         self.used = False
         self.collection = collection
+        self.static_prop = static_prop
 
 
 class ModelTracker:
     imported_objects: Dict[str, ModelState]
+    instance_collections: Dict[str, Collection]
 
-    def __init__(self) -> None:
+    def __init__(self, reuse_existing: bool = False) -> None:
         self.imported_objects = {}
+        self.instance_collections = {}
+        # props of models skipped by the importer copy the props of earlier imports
+        self.reuse_existing = reuse_existing
+        self.existing_objects: Optional[Dict[str, Object]] = None
 
     def import_model(self, model: Model, collection: Collection) -> None:
         original_name = model.name()
@@ -55,9 +71,17 @@ class ModelTracker:
                 collection, model_name, bones, rest_positions, bone_names
             )
 
-            animations = model.animations()
-            for animation in animations:
-                import_animation(parent_obj, bone_names, animation)
+            actions = {}
+            for animation in model.animations():
+                actions[animation.name()] = import_animation(
+                    parent_obj, bone_names, animation
+                )
+
+            import_animation_events(actions, model.animation_events())
+
+            import_ragdoll(parent_obj, bone_names, model.ragdoll())
+            import_jiggle_bones(parent_obj, bone_names, model.jiggle_bones())
+            import_ik_chains(parent_obj, bone_names, model.ik_chains())
 
         bl_materials = []
         for material in model.materials():
@@ -94,7 +118,12 @@ class ModelTracker:
                 # this only gets called if there is 1 mesh
                 parent_obj = mesh_obj
 
-        model_state = ModelState(parent_obj, children, collection)
+        if parent_obj is not None:
+            import_bounds(parent_obj, model)
+
+        model_state = ModelState(
+            parent_obj, children, collection, model.static_prop()
+        )
         self.imported_objects[original_name.lower()] = model_state
 
     def apply_scale_to_batch(self, scale: float) -> None:
@@ -103,11 +132,23 @@ class ModelTracker:
             if model_state.object is not None:
                 model_state.object.scale = (scale, scale, scale)
 
+    def rotate_static_props(self) -> None:
+        """
+        Undo the 90 degree yaw studiomdl bakes into $staticprop models,
+        so they face the way they were modelled instead of the way they face in maps.
+        """
+        for model_state in self.imported_objects.values():
+            if model_state.static_prop and model_state.object is not None:
+                model_state.object.rotation_euler.z -= radians(90)
+
     def get_model_copy(
         self, model_name: str, collection: Collection
     ) -> Optional[Object]:
         model_state = self.imported_objects.get(model_name.lower())
 
+        if model_state is None and self.reuse_existing:
+            model_state = self.find_existing(model_name)
+
         if model_state is None:
             return None
 
@@ -141,6 +182,62 @@ class ModelTracker:
 
         return parent_copy
 
+    def find_existing(self, model_name: str) -> Optional[ModelState]:
+        """Find a prop of an earlier import using the model, to be copied for new props."""
+        if self.existing_objects is None:
+            self.existing_objects = {}
+
+            for obj in bpy.data.objects:
+                path_id = obj.get("path_id")
+                if (
+                    isinstance(path_id, str)
+                    and obj.parent is None
+                    and is_existing_model(obj)
+                ):
+                    self.existing_objects.setdefault(path_id.lower(), obj)
+
+        obj = self.existing_objects.get(model_name.lower())
+        if obj is None:
+            return None
+
+        collection = obj.users_collection[0] if obj.users_collection else None
+        model_state = ModelState(obj, list(obj.children), collection)
+        # the existing prop stays where it is
+        model_state.used = True
+        self.imported_objects[model_name.lower()] = model_state
+        return model_state
+
+    def get_instance_collection(
+        self, instance_key: str, model_name: str
+    ) -> Optional[Collection]:
+        """
+        Get the collection instanced by the props sharing an instance key,
+        or None if the model can't be instanced.
+        """
+        instance_collection = self.instance_collections.get(instance_key)
+        if instance_collection is not None:
+            return instance_collection
+
+        model_state = self.imported_objects.get(model_name.lower())
+
+        # armatures are posed and applied separately for each prop
+        if (
+            model_state is None
+            or model_state.object is None
+            or model_state.object.type == "ARMATURE"
+        ):
+            return None
+
+        # the collection isn't linked to the scene, it's only visible through the props
+        instance_collection = bpy.data.collections.new(truncate_name(instance_key))
+        obj = self.get_model_copy(model_name, instance_collection)
+        obj.location = (0.0, 0.0, 0.0)
+        obj.rotation_euler = (0.0, 0.0, 0.0)
+        obj.scale = (1.0, 1.0, 1.0)
+
+        self.instance_collections[instance_key] = instance_collection
+        return instance_collection
+
     def get_last_imported(self) -> Optional[Object]:
         last = next(reversed(self.imported_objects.values()), None)
 
@@ -182,7 +279,10 @@ def import_mesh(
     mesh_data.polygons.foreach_set("use_smooth", [True] * polygons_len)
     mesh_data.update(calc_edges=True)
 
-    mesh_data.normals_split_custom_set_from_vertices(mesh.normals())
+    normals = mesh.normals()
+    mesh_data.normals_split_custom_set_from_vertices(
+        tuple(zip(normals[0::3], normals[1::3], normals[2::3]))
+    )
 
     uv_layer = mesh_data.uv_layers.new()
     uv_layer.data.foreach_set("uv", mesh.loop_uvs())
@@ -203,6 +303,92 @@ def import_mesh(
     return mesh_obj
 
 
+def import_bounds(model_obj: Object, model: Model) -> None:
+    """
+    Store the illumination position and the bounding boxes of the model
+    as custom properties, in the space of the model.
+    """
+    illum_position = model.illum_position()
+    if illum_position is None:
+        return
+
+    model_obj["illum_position"] = illum_position
+    model_obj["eye_position"] = model.eye_position()
+    model_obj["hull_min"], model_obj["hull_max"] = model.hull_bounds()
+    model_obj["view_min"], model_obj["view_max"] = model.view_bounds()
+
+
+def import_ragdoll(
+    armature: Object, bone_names: List[str], joints: List[RagdollJoint]
+) -> None:
+    """
+    Store the ragdoll joints as muted rotation limits of the bones,
+    so they don't affect the animations but can be used to set up physics.
+    """
+    for joint in joints:
+        pose_bone = armature.pose.bones[bone_names[joint.bone_index()]]
+
+        constraint = pose_bone.constraints.new("LIMIT_ROTATION")
+        constraint.name = "Ragdoll"
+        constraint.owner_space = "LOCAL"
+        constraint.mute = True
+
+        for axis, (min_angle, max_angle) in zip("xyz", joint.limits()):
+            setattr(constraint, f"use_limit_{axis}", True)
+            setattr(constraint, f"min_{axis}", min_angle)
+            setattr(constraint, f"max_{axis}", max_angle)
+
+        pose_bone["ragdoll_friction"] = joint.friction()
+
+        parent_index = joint.parent_bone_index()
+        if parent_index is not None:
+            pose_bone["ragdoll_parent"] = bone_names[parent_index]
+
+
+def import_jiggle_bones(
+    armature: Object, bone_names: List[str], jiggle_bones: List[JiggleBone]
+) -> None:
+    """Store the parameters of jiggle bones as custom properties of the bones."""
+    for jiggle in jiggle_bones:
+        pose_bone = armature.pose.bones[bone_names[jiggle.bone_index()]]
+
+        pose_bone["jiggle_rigid"] = jiggle.rigid()
+        pose_bone["jiggle_length"] = jiggle.length()
+        pose_bone["jiggle_tip_mass"] = jiggle.tip_mass()
+
+        for axis, (stiffness, damping) in zip(
+            ("yaw", "pitch", "along"), jiggle.tip_springs()
+        ):
+            pose_bone[f"jiggle_{axis}_stiffness"] = stiffness
+            pose_bone[f"jiggle_{axis}_damping"] = damping
+
+        base_spring = jiggle.base_spring()
+        if base_spring is not None:
+            mass, stiffness, damping = base_spring
+            pose_bone["jiggle_base_mass"] = mass
+            pose_bone["jiggle_base_stiffness"] = stiffness
+            pose_bone["jiggle_base_damping"] = damping
+
+
+def import_ik_chains(
+    armature: Object, bone_names: List[str], ik_chains: List[IkChain]
+) -> None:
+    """
+    Add an ik constraint without a target to the end effector of each chain,
+    which reaches the end effector to the target once one is set.
+    """
+    for chain in ik_chains:
+        pose_bone = armature.pose.bones[bone_names[chain.end_effector_index()]]
+
+        constraint = pose_bone.constraints.new("IK")
+        constraint.name = truncate_name(chain.name())
+        constraint.use_tail = False
+        # the end effector itself isn't rotated, 0 would rotate every parent
+        constraint.chain_count = max(len(chain.bone_indices()) - 1, 1)
+
+        pose_bone["ik_knee_direction"] = chain.knee_direction()
+
+
 def import_armature(
     collection: Collection,
     model_name: str,
@@ -261,7 +447,7 @@ def import_animation(
     armature_obj: Object,
     bone_names: List[str],
     animation: LoadedAnimation,
-) -> None:
+) -> Action:
     animation_data = armature_obj.animation_data_create()
 
     name = truncate_name(f"{armature_obj.name}/{animation.name()}")
@@ -296,6 +482,24 @@ def import_animation(
             curve_name = f"{curve_basename}.location"
             import_vector(action, position, curve_name)
 
+    return action
+
+
+def import_animation_events(
+    actions: Dict[str, Action], events: List[AnimationEvent]
+) -> None:
+    """
+    Add a pose marker named after each event to the action of the animation
+    the sequence of the event plays.
+    """
+    for event in events:
+        action = actions.get(event.animation())
+        if action is None:
+            continue
+
+        marker = action.pose_markers.new(truncate_name(event.name()))
+        marker.frame = round(event.frame())
+
 
 def import_quaternions(
     action: Action, data: QuaternionData, curve_name: str, looping: bool
@@ -0,0 +1,33 @@
+import bpy
+from bpy.types import Collection
+
+from ..plumber import NavMesh
+
+
+def import_nav_mesh(nav: NavMesh, collection: Collection) -> None:
+    name = bpy.path.display_name_from_filepath(nav.name())
+    mesh = bpy.data.meshes.new(name)
+
+    vertices = nav.vertices()
+    polygon_vertices = nav.polygon_vertices()
+    edge_vertices = nav.edge_vertices()
+    polygons_len = len(polygon_vertices) // 4
+
+    mesh.vertices.add(len(vertices) // 3)
+    mesh.edges.add(len(edge_vertices) // 2)
+    mesh.loops.add(len(polygon_vertices))
+    mesh.polygons.add(polygons_len)
+    mesh.vertices.foreach_set("co", vertices)
+    mesh.edges.foreach_set("vertices", edge_vertices)
+    mesh.polygons.foreach_set("loop_total", [4] * polygons_len)
+    mesh.polygons.foreach_set("loop_start", range(0, len(polygon_vertices), 4))
+    mesh.polygons.foreach_set("vertices", polygon_vertices)
+    mesh.update(calc_edges=True)
+
+    area_ids = mesh.attributes.new("area_id", "INT", "FACE")
+    area_ids.data.foreach_set("value", nav.area_ids())
+
+    obj = bpy.data.objects.new(name, object_data=mesh)
+    obj.scale = nav.scale()
+    obj.display_type = "WIRE"
+    collection.objects.link(obj)
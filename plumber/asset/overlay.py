@@ -1,8 +1,14 @@
+from typing import List, Optional, Tuple
+
 import bpy
-from bpy.types import Collection
+from bpy.types import Collection, Context, Depsgraph
+from mathutils import Vector
+
+from .utils import store_editor_groups, store_vmf_id, truncate_name
+from ..plumber import BuiltOverlay, Decal
 
-from .utils import truncate_name
-from ..plumber import BuiltOverlay
+# distance decals are lifted off their surface, relative to their size
+DECAL_OFFSET = 0.001
 
 
 def import_overlay(overlay: BuiltOverlay, collection: Collection) -> None:
@@ -31,4 +37,86 @@ def import_overlay(overlay: BuiltOverlay, collection: Collection) -> None:
     obj = bpy.data.objects.new(name, object_data=mesh)
     obj.location = overlay.position()
     obj.scale = overlay.scale()
+    store_editor_groups(obj, overlay.editor_groups())
+    store_vmf_id(obj, overlay.id())
+    collection.objects.link(obj)
+
+
+# directions decals are projected in, the closest surface is used
+PROJECTION_AXES = [
+    Vector((1, 0, 0)),
+    Vector((-1, 0, 0)),
+    Vector((0, 1, 0)),
+    Vector((0, -1, 0)),
+    Vector((0, 0, 1)),
+    Vector((0, 0, -1)),
+]
+
+
+def import_decals(
+    decals: List[Decal], context: Context, collection: Collection
+) -> None:
+    # decals are placed on the surfaces around them, so the brushes must be evaluated
+    context.view_layer.update()
+    depsgraph = context.evaluated_depsgraph_get()
+
+    # all surfaces are found first, so decals aren't placed onto each other
+    surfaces = [closest_surface(context, depsgraph, decal) for decal in decals]
+
+    for decal, surface in zip(decals, surfaces):
+        import_decal(decal, surface, collection)
+
+
+def closest_surface(
+    context: Context, depsgraph: Depsgraph, decal: Decal
+) -> Optional[Tuple[Vector, Vector]]:
+    origin = Vector(decal.position())
+    closest = None
+    closest_distance = max(decal.size())
+
+    for axis in PROJECTION_AXES:
+        hit, location, normal, *_ = context.scene.ray_cast(
+            depsgraph, origin, axis, distance=closest_distance
+        )
+        if hit:
+            closest = (location, normal)
+            closest_distance = (location - origin).length
+
+    return closest
+
+
+def import_decal(
+    decal: Decal, surface: Optional[Tuple[Vector, Vector]], collection: Collection
+) -> None:
+    name = f"infodecal_{decal.id()}"
+    width, height = decal.size()
+
+    if surface is None:
+        location, normal = Vector(decal.position()), Vector((0, 0, 1))
+    else:
+        location, normal = surface
+
+    # the texture is upright on walls and points along y on floors and ceilings
+    up = Vector((0, 1, 0)) if abs(normal.z) > 0.99 else Vector((0, 0, 1))
+    right = up.cross(normal).normalized() * width / 2
+    up = normal.cross(right).normalized() * height / 2
+
+    mesh = bpy.data.meshes.new(name)
+    mesh.from_pydata(
+        [-right - up, right - up, right + up, -right + up], [], [(0, 1, 2, 3)]
+    )
+    uv_layer = mesh.uv_layers.new()
+    uv_layer.data.foreach_set("uv", (0, 0, 1, 0, 1, 1, 0, 1))
+
+    material = truncate_name(decal.material())
+    material_data = bpy.data.materials.get(material)
+    if material_data is None:
+        material_data = bpy.data.materials.new(material)
+    mesh.materials.append(material_data)
+
+    obj = bpy.data.objects.new(name, object_data=mesh)
+    # lifted slightly, so it doesn't flicker with the surface
+    obj.location = location + normal * DECAL_OFFSET * max(width, height)
+    store_editor_groups(obj, decal.editor_groups())
+    store_vmf_id(obj, decal.id())
     collection.objects.link(obj)
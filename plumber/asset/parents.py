@@ -0,0 +1,23 @@
+from typing import Dict, List, Tuple
+
+from bpy.types import Object
+
+
+def apply_entity_parents(
+    parents: List[Tuple[List[Object], int]], entity_objects: Dict[int, List[Object]]
+) -> None:
+    # children keep their place, the world matrices need to be up to date for this.
+    # brush entities built from several solids are parented to their first object
+    for children, parent_id in parents:
+        parent_objects = entity_objects.get(parent_id)
+        if not parent_objects:
+            # the parent wasn't imported
+            continue
+
+        parent = parent_objects[0]
+        inverse = parent.matrix_world.inverted()
+
+        for child in children:
+            if child is not parent:
+                child.parent = parent
+                child.matrix_parent_inverse = inverse
@@ -0,0 +1,25 @@
+import bpy
+from bpy.types import Collection
+
+from .utils import store_editor_groups, store_vmf_id
+from ..plumber import PathChain
+
+
+def import_path_chain(chain: PathChain, collection: Collection) -> None:
+    name = chain.name() or f"{chain.class_name()}_{chain.id()}"
+    curve = bpy.data.curves.new(name, type="CURVE")
+    curve.dimensions = "3D"
+
+    points = chain.points()
+    spline = curve.splines.new("POLY")
+    # new splines already have one point
+    spline.points.add(len(points) // 3 - 1)
+    for i, point in enumerate(spline.points):
+        point.co = (*points[i * 3 : i * 3 + 3], 1.0)
+    spline.use_cyclic_u = chain.closed()
+
+    obj = bpy.data.objects.new(name, object_data=curve)
+    obj["hammer_id"] = chain.id()
+    store_editor_groups(obj, chain.editor_groups())
+    store_vmf_id(obj, chain.id())
+    collection.objects.link(obj)
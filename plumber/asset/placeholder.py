@@ -0,0 +1,83 @@
+from typing import List
+import bpy
+from bpy.types import Material, Mesh
+
+from .utils import truncate_name
+from ..plumber import Diagnostic
+
+PLACEHOLDER_MATERIAL_NAME = "plumber_missing"
+PLACEHOLDER_MESH_NAME = "plumber_error_cube"
+# half of the error model size in source units
+_CUBE_EXTENT = 16.0
+
+
+def build_placeholder_nodes(material: Material) -> None:
+    """Replace the nodes of a material with a magenta and black checker pattern."""
+    material.use_nodes = True
+    nt = material.node_tree
+    nt.nodes.clear()
+
+    out_node = nt.nodes.new("ShaderNodeOutputMaterial")
+    out_node.location = (300, 0)
+
+    shader_node = nt.nodes.new("ShaderNodeEmission")
+    shader_node.location = (100, 0)
+
+    checker_node = nt.nodes.new("ShaderNodeTexChecker")
+    checker_node.location = (-100, 0)
+    checker_node.inputs["Color1"].default_value = (1.0, 0.0, 1.0, 1.0)
+    checker_node.inputs["Color2"].default_value = (0.0, 0.0, 0.0, 1.0)
+    checker_node.inputs["Scale"].default_value = 8.0
+
+    nt.links.new(checker_node.outputs["Color"], shader_node.inputs["Color"])
+    nt.links.new(shader_node.outputs["Emission"], out_node.inputs["Surface"])
+
+    material.diffuse_color = (1.0, 0.0, 1.0, 1.0)
+    material["placeholder"] = True
+
+
+def get_placeholder_material() -> Material:
+    material = bpy.data.materials.get(PLACEHOLDER_MATERIAL_NAME)
+    if material is None:
+        material = bpy.data.materials.new(PLACEHOLDER_MATERIAL_NAME)
+        build_placeholder_nodes(material)
+    return material
+
+
+def get_placeholder_mesh(scale: float) -> Mesh:
+    name = f"{PLACEHOLDER_MESH_NAME}_{scale:g}"
+    mesh = bpy.data.meshes.get(name)
+    if mesh is not None:
+        return mesh
+
+    e = _CUBE_EXTENT * scale
+    vertices = [(x, y, z) for x in (-e, e) for y in (-e, e) for z in (0.0, e * 2)]
+    faces = [
+        (0, 1, 3, 2),
+        (4, 6, 7, 5),
+        (0, 4, 5, 1),
+        (2, 3, 7, 6),
+        (0, 2, 6, 4),
+        (1, 5, 7, 3),
+    ]
+
+    mesh = bpy.data.meshes.new(name)
+    mesh.from_pydata(vertices, [], faces)
+    mesh.materials.append(get_placeholder_material())
+    mesh.update()
+    return mesh
+
+
+def apply_placeholder_materials(diagnostics: List[Diagnostic]) -> None:
+    """Mark the materials which failed to load with the placeholder pattern."""
+    for diagnostic in diagnostics:
+        if diagnostic.severity() != "ERROR" or diagnostic.asset_kind() != "material":
+            continue
+
+        material_name = diagnostic.asset_id()
+        if material_name is None:
+            continue
+
+        material = bpy.data.materials.get(truncate_name(material_name))
+        if material is not None:
+            build_placeholder_nodes(material)
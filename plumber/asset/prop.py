@@ -1,9 +1,10 @@
-from typing import List
+from typing import Dict, List, Optional, Tuple
 import bpy
 from bpy.types import Collection, Object
 
-from .utils import find_armature_modifier
+from .utils import find_armature_modifier, store_editor_groups, store_vmf_id
 from .model import ModelTracker
+from .placeholder import get_placeholder_mesh
 from ..plumber import LoadedProp, log_info
 
 
@@ -13,28 +14,77 @@ def import_prop(
     model_tracker: ModelTracker,
     apply_armatures: bool,
     armatures_to_apply: List[Object],
-) -> None:
+    placeholder_scale: Optional[float] = None,
+    instance_props: bool = False,
+) -> Object:
     model_name = prop.model()
-    obj = model_tracker.get_model_copy(model_name, collection)
-    obj["path_id"] = model_name
-    obj["props"] = prop.properties()
-
     name = f"{prop.class_name()}_{prop.id()}"
 
-    if obj is None:
+    instance_collection = None
+    if instance_props:
+        instance_collection = model_tracker.get_instance_collection(
+            prop.instance_key(), model_name
+        )
+
+    if instance_collection is not None:
         obj = bpy.data.objects.new(name, object_data=None)
+        obj.instance_type = "COLLECTION"
+        obj.instance_collection = instance_collection
+        collection.objects.link(obj)
+    else:
+        obj = model_tracker.get_model_copy(model_name, collection)
+
+    if obj is None:
+        # the model failed to load
+        if placeholder_scale is not None:
+            obj = bpy.data.objects.new(name, get_placeholder_mesh(placeholder_scale))
+            obj["placeholder"] = True
+        else:
+            obj = bpy.data.objects.new(name, object_data=None)
         collection.objects.link(obj)
     else:
         obj.name = name
 
+    obj["path_id"] = model_name
+    obj["props"] = prop.properties()
+    store_editor_groups(obj, prop.editor_groups())
+    store_vmf_id(obj, prop.id())
+
     obj.location = prop.position()
     obj.rotation_euler = prop.rotation()
     obj.scale = prop.scale()
     obj.color = prop.color()
 
+    lighting_origin = prop.lighting_origin()
+    if lighting_origin is not None:
+        obj["lighting_origin"] = lighting_origin
+    elif "lighting_origin" in obj:
+        # copied from a prop of an earlier import
+        del obj["lighting_origin"]
+
+    fade_distances = prop.fade_distances()
+    if fade_distances is not None:
+        obj["fade_distances"] = fade_distances
+    elif "fade_distances" in obj:
+        del obj["fade_distances"]
+    obj["fade_scale"] = prop.fade_scale()
+    obj["screen_space_fade"] = prop.screen_space_fade()
+
     if apply_armatures and obj.type == "ARMATURE":
         armatures_to_apply.append(obj)
 
+    return obj
+
+
+def apply_lighting_origins(
+    props: List[Object], lighting_origins: Dict[str, Tuple[float, float, float]]
+) -> None:
+    # the info_lighting entities may arrive after the props, so they are resolved at the end
+    for obj in props:
+        position = lighting_origins.get(obj["lighting_origin"])
+        if position is not None:
+            obj["lighting_origin_position"] = position
+
 
 def apply_armatures(armatures_to_apply: List[Object]):
     if not armatures_to_apply:
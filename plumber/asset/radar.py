@@ -0,0 +1,62 @@
+import bpy
+from bpy.types import Collection
+
+from ..plumber import Radar
+
+
+def import_radar(radar: Radar, collection: Collection) -> None:
+    name = f"{radar.name()}_radar"
+    width, height = radar.size()
+
+    mesh_data = bpy.data.meshes.new(name)
+    mesh_data.from_pydata(
+        [
+            (-width / 2, -height / 2, 0),
+            (width / 2, -height / 2, 0),
+            (width / 2, height / 2, 0),
+            (-width / 2, height / 2, 0),
+        ],
+        [],
+        [(0, 1, 2, 3)],
+    )
+
+    uv_layer = mesh_data.uv_layers.new()
+    for loop, uv in zip(uv_layer.data, ((0, 0), (1, 0), (1, 1), (0, 1))):
+        loop.uv = uv
+
+    image = bpy.data.images.load(radar.image_path())
+    image.pack()
+
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    material.blend_method = "BLEND"
+    nt = material.node_tree
+    nt.nodes.clear()
+
+    out_node = nt.nodes.new("ShaderNodeOutputMaterial")
+    out_node.location = (600, 0)
+
+    texture_node = nt.nodes.new("ShaderNodeTexImage")
+    texture_node.location = (-300, 0)
+    texture_node.image = image
+
+    emission_node = nt.nodes.new("ShaderNodeEmission")
+    emission_node.location = (0, -100)
+
+    transparent_node = nt.nodes.new("ShaderNodeBsdfTransparent")
+    transparent_node.location = (0, 100)
+
+    mix_node = nt.nodes.new("ShaderNodeMixShader")
+    mix_node.location = (300, 0)
+
+    nt.links.new(texture_node.outputs["Color"], emission_node.inputs["Color"])
+    nt.links.new(texture_node.outputs["Alpha"], mix_node.inputs["Fac"])
+    nt.links.new(transparent_node.outputs["BSDF"], mix_node.inputs[1])
+    nt.links.new(emission_node.outputs["Emission"], mix_node.inputs[2])
+    nt.links.new(mix_node.outputs["Shader"], out_node.inputs["Surface"])
+    mesh_data.materials.append(material)
+
+    obj = bpy.data.objects.new(name, object_data=mesh_data)
+    obj.location = radar.position()
+    obj.hide_render = True
+    collection.objects.link(obj)
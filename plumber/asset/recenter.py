@@ -0,0 +1,65 @@
+from typing import Iterable, List, Optional
+
+from bpy.types import Collection, Object
+from mathutils import Vector
+
+from ..plumber import PlayerSpawn, log_info
+
+
+def spawn_center(spawns: List[PlayerSpawn]) -> Optional[Vector]:
+    if not spawns:
+        return None
+
+    # spawns of the generic class are preferred over the ones of specific teams
+    spawn = min(spawns, key=lambda spawn: (spawn.priority(), spawn.id()))
+    return Vector(spawn.position())
+
+
+def bounds_center(objects: Iterable[Object]) -> Optional[Vector]:
+    # the meshes span the map, empties and lights only have their location
+    points = [
+        obj.matrix_world @ Vector(corner)
+        for obj in objects
+        if obj.type == "MESH"
+        for corner in obj.bound_box
+    ]
+    if not points:
+        points = [obj.matrix_world.translation for obj in objects]
+    if not points:
+        return None
+
+    minimum = Vector(min(point[i] for point in points) for i in range(3))
+    maximum = Vector(max(point[i] for point in points) for i in range(3))
+    return (minimum + maximum) / 2
+
+
+def recenter_map(
+    collection: Collection,
+    objects: List[Object],
+    mode: str,
+    spawns: List[PlayerSpawn],
+) -> None:
+    """
+    Move the imported objects so the center of the map's bounds or the player spawn
+    is at the world origin. Maps far from the origin suffer from float precision issues.
+    """
+    center = None
+    if mode == "SPAWN":
+        center = spawn_center(spawns)
+        if center is None:
+            log_info("the map has no player spawn, recentering it around its bounds")
+
+    if center is None:
+        center = bounds_center(objects)
+    if center is None:
+        return
+
+    # children move with their parents
+    for obj in objects:
+        if obj.parent is None:
+            obj.location -= center
+
+    # kept for moving the map back into place
+    collection["recenter_offset"] = tuple(-center)
+
+    log_info(f"recentered the map by {tuple(round(c, 2) for c in -center)}")
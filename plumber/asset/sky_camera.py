@@ -1,6 +1,7 @@
 import bpy
 from bpy.types import Context, Collection
 
+from .utils import store_editor_groups, store_vmf_id
 from ..plumber import SkyCamera
 
 
@@ -14,5 +15,16 @@ def import_sky_camera(
     obj.scale = sky_camera.scale()
     collection.objects.link(obj)
 
+    obj["fog_enabled"] = sky_camera.fog_enabled()
+    obj["fog_blend"] = sky_camera.fog_blend()
+    obj["fog_color"] = sky_camera.fog_color()
+    obj["fog_color2"] = sky_camera.fog_color2()
+    obj["fog_direction"] = sky_camera.fog_direction()
+    obj["fog_start"] = sky_camera.fog_start()
+    obj["fog_end"] = sky_camera.fog_end()
+    obj["fog_max_density"] = sky_camera.fog_max_density()
+    store_editor_groups(obj, sky_camera.editor_groups())
+    store_vmf_id(obj, sky_camera.id())
+
     obj.select_set(True)
     context.view_layer.objects.active = obj
@@ -0,0 +1,64 @@
+from math import cos, pi, sin
+
+import bpy
+from bpy.types import Collection, Object
+
+from .utils import store_editor_groups, store_vmf_id
+from ..plumber import Smoke
+
+CONE_SEGMENTS = 12
+
+
+def import_smoke(smoke: Smoke, collection: Collection) -> Object:
+    name = f"{smoke.class_name()}_{smoke.id()}"
+
+    # a cone along the x axis from the emitted to the final size of the particles
+    start_radius = smoke.start_size() / 2
+    end_radius = smoke.end_size() / 2
+    length = smoke.length()
+
+    vertices = []
+    for x, radius in ((0.0, start_radius), (length, end_radius)):
+        for i in range(CONE_SEGMENTS):
+            angle = 2 * pi * i / CONE_SEGMENTS
+            vertices.append((x, radius * cos(angle), radius * sin(angle)))
+
+    faces = []
+    for i in range(CONE_SEGMENTS):
+        j = (i + 1) % CONE_SEGMENTS
+        faces.append((i, j, j + CONE_SEGMENTS, i + CONE_SEGMENTS))
+    faces.append(tuple(reversed(range(CONE_SEGMENTS))))
+    faces.append(tuple(range(CONE_SEGMENTS, 2 * CONE_SEGMENTS)))
+
+    mesh_data = bpy.data.meshes.new(name)
+    mesh_data.from_pydata(vertices, [], faces)
+
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    nt = material.node_tree
+    nt.nodes.clear()
+
+    out_node = nt.nodes.new("ShaderNodeOutputMaterial")
+    out_node.location = (300, 0)
+
+    volume_node = nt.nodes.new("ShaderNodeVolumePrincipled")
+    volume_node.location = (0, 0)
+    *color, alpha = smoke.color()
+    volume_node.inputs["Color"].default_value = (*color, 1.0)
+    volume_node.inputs["Density"].default_value = alpha
+
+    nt.links.new(volume_node.outputs["Volume"], out_node.inputs["Volume"])
+    mesh_data.materials.append(material)
+
+    obj = bpy.data.objects.new(name, object_data=mesh_data)
+    collection.objects.link(obj)
+
+    obj.location = smoke.position()
+    obj.rotation_euler = smoke.rotation()
+    obj["props"] = smoke.properties()
+    store_editor_groups(obj, smoke.editor_groups())
+    store_vmf_id(obj, smoke.id())
+    obj["speed"] = smoke.speed()
+    obj["rate"] = smoke.rate()
+
+    return obj
@@ -1,10 +1,11 @@
 import bpy
-from bpy.types import Collection
+from bpy.types import Collection, Object
 
+from .utils import store_editor_groups, store_vmf_id
 from ..plumber import UnknownEntity
 
 
-def import_unknown_entity(entity: UnknownEntity, collection: Collection) -> None:
+def import_unknown_entity(entity: UnknownEntity, collection: Collection) -> Object:
     name = f"{entity.class_name()}_{entity.id()}"
 
     obj = bpy.data.objects.new(name, object_data=None)
@@ -12,5 +13,9 @@ def import_unknown_entity(entity: UnknownEntity, collection: Collection) -> None
     obj.rotation_euler = entity.rotation()
     obj.scale = entity.scale()
     obj["props"] = entity.properties()
+    store_editor_groups(obj, entity.editor_groups())
+    store_vmf_id(obj, entity.id())
 
     collection.objects.link(obj)
+
+    return obj
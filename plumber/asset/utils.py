@@ -4,6 +4,8 @@ from posixpath import split, splitext
 from typing import Optional
 import bpy
 
+from ..plumber import EditorGroups
+
 _HASH_LEN = 6
 _B64_LEN = 8
 
@@ -34,6 +36,46 @@ def truncate_name(name: str, maxlen: int = 59) -> str:
     return f"~{_hashed(final_discard)}/{final_keep}{basename}"
 
 
+def store_editor_groups(obj: bpy.types.Object, groups: Optional[EditorGroups]) -> None:
+    # Hammer visgroups and groups of the object, for mirroring them in collections.
+    # objects copied from earlier imports have the groups of the copied object,
+    # and brushes of the copied object are marked for exporting with their class
+    for key in (
+        "vmf_world",
+        "vmf_visgroups",
+        "vmf_groups",
+        "vmf_editor_class_name",
+        "vmf_class_name",
+        "vmf_id",
+        "vmf_solid_id",
+    ):
+        if key in obj:
+            del obj[key]
+
+    if groups is None:
+        return
+
+    obj["vmf_world"] = groups.world()
+
+    visgroups = groups.visgroups()
+    if visgroups:
+        obj["vmf_visgroups"] = ["/".join(path) for path in visgroups]
+
+    hammer_groups = groups.groups()
+    if hammer_groups:
+        obj["vmf_groups"] = hammer_groups
+
+    class_name = groups.class_name()
+    if class_name is not None:
+        obj["vmf_editor_class_name"] = class_name
+
+
+def store_vmf_id(obj: bpy.types.Object, id: int) -> None:
+    # the entity the object was imported from, which is replaced by importing
+    # a changed version of the map
+    obj["vmf_id"] = id
+
+
 def get_unknown_material() -> bpy.types.Material:
     material = bpy.data.materials.get("?.vmt")
     if material is None:
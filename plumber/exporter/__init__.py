@@ -0,0 +1,18 @@
+import bpy
+
+from .vmf import ExportVmf
+
+
+CLASSES = [
+    ExportVmf,
+]
+
+
+def register():
+    for cls in CLASSES:
+        bpy.utils.register_class(cls)
+
+
+def unregister():
+    for cls in reversed(CLASSES):
+        bpy.utils.unregister_class(cls)
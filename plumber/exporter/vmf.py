@@ -0,0 +1,365 @@
+from typing import Dict, Iterable, List, Optional, Set, Tuple
+from math import degrees
+import re
+
+from bpy.props import BoolProperty, StringProperty
+from bpy.types import Context, Object, Operator
+from bpy_extras.io_utils import ExportHelper
+from mathutils import Vector
+
+from ..importer import ScaleOperatorProps
+
+# imported entities are named `<class name>_<entity id>`, maybe with a blender suffix
+_ENTITY_NAME_RE = re.compile(r"^(?P<class_name>.+?)_(?P<id>\d+)(?:\.\d+)?$")
+
+# keyvalues that are written separately instead of from the stored properties
+_SKIPPED_KEYS = {"classname", "id", "origin", "angles"}
+_SKIPPED_BRUSH_KEYS = {"classname", "id"}
+
+# distance vertices can be in front of a face plane of a convex solid, in map units
+_CONVEX_EPSILON = 0.01
+
+
+class VmfWriter:
+    """Writes KeyValues formatted text with tab indentation, like Hammer does."""
+
+    def __init__(self) -> None:
+        self.lines: List[str] = []
+        self.depth = 0
+
+    def begin(self, name: str) -> None:
+        self.lines.append("\t" * self.depth + name)
+        self.lines.append("\t" * self.depth + "{")
+        self.depth += 1
+
+    def end(self) -> None:
+        self.depth -= 1
+        self.lines.append("\t" * self.depth + "}")
+
+    def key_value(self, key: str, value) -> None:
+        value = str(value).replace('"', "'")
+        self.lines.append("\t" * self.depth + f'"{key}" "{value}"')
+
+    def text(self) -> str:
+        return "\n".join(self.lines) + "\n"
+
+
+class IdAllocator:
+    def __init__(self, used: Iterable[int] = ()) -> None:
+        self.next = max(used, default=0) + 1
+
+    def allocate(self) -> int:
+        id = self.next
+        self.next += 1
+        return id
+
+
+def format_number(value: float) -> str:
+    value = round(value, 4)
+    if value == int(value):
+        return str(int(value))
+    return f"{value:g}"
+
+
+def format_vector(vector: Iterable[float]) -> str:
+    return " ".join(format_number(c) for c in vector)
+
+
+def parse_entity_name(obj: Object) -> Optional[Tuple[str, int]]:
+    class_name = obj.get("vmf_class_name")
+    entity_id = obj.get("vmf_id")
+    if class_name is not None and entity_id is not None:
+        return class_name, int(entity_id)
+
+    match = _ENTITY_NAME_RE.match(obj.name)
+    if match is None:
+        return None
+
+    return match["class_name"], int(match["id"])
+
+
+def material_name(obj: Object, index: int) -> str:
+    materials = obj.data.materials
+    if index >= len(materials) or materials[index] is None:
+        return "TOOLS/TOOLSNODRAW"
+
+    name = materials[index].name.replace("\\", "/")
+    if name.lower().startswith("materials/"):
+        name = name[len("materials/") :]
+    if name.lower().endswith(".vmt"):
+        name = name[: -len(".vmt")]
+
+    return name.upper()
+
+
+def texture_axes(normal: Vector) -> Tuple[str, str]:
+    # world aligned texture axes, as Hammer chooses them for a new face.
+    # used for faces without stored axes, such as ones added in blender
+    x, y, z = (abs(c) for c in normal)
+    if z >= x and z >= y:
+        return "[1 0 0 0] 0.25", "[0 -1 0 0] 0.25"
+    if x >= y:
+        return "[0 1 0 0] 0.25", "[0 0 -1 0] 0.25"
+    return "[1 0 0 0] 0.25", "[0 0 -1 0] 0.25"
+
+
+def polygon_islands(mesh) -> List[List[int]]:
+    """Groups mesh polygons by connectivity to export merged solids separately."""
+    parent = list(range(len(mesh.vertices)))
+
+    def find(i: int) -> int:
+        while parent[i] != i:
+            parent[i] = parent[parent[i]]
+            i = parent[i]
+        return i
+
+    for polygon in mesh.polygons:
+        first = find(polygon.vertices[0])
+        for vertex in polygon.vertices[1:]:
+            parent[find(vertex)] = first
+
+    islands: Dict[int, List[int]] = {}
+    for polygon in mesh.polygons:
+        islands.setdefault(find(polygon.vertices[0]), []).append(polygon.index)
+
+    return list(islands.values())
+
+
+def stored_texture_axes(mesh) -> List[Optional[Tuple[str, str]]]:
+    """Returns the texture axes the importer stored for each polygon, if any."""
+    attribute = mesh.attributes.get("vmf_texture_axes")
+    u_axes = mesh.get("vmf_uaxes")
+    v_axes = mesh.get("vmf_vaxes")
+    if attribute is None or u_axes is None or v_axes is None:
+        return [None] * len(mesh.polygons)
+
+    axes = []
+    for data in attribute.data:
+        if 0 <= data.value < min(len(u_axes), len(v_axes)):
+            axes.append((u_axes[data.value], v_axes[data.value]))
+        else:
+            axes.append(None)
+
+    return axes
+
+
+def stored_lightmap_scales(mesh) -> List[float]:
+    attribute = mesh.attributes.get("vmf_lightmap_scale")
+    if attribute is None:
+        return [16.0] * len(mesh.polygons)
+
+    return [data.value for data in attribute.data]
+
+
+def is_convex(mesh, island: List[int], positions: List[Vector], normal_matrix) -> bool:
+    # hammer rejects concave solids, so every vertex needs to be behind every face
+    vertices = {v for index in island for v in mesh.polygons[index].vertices}
+
+    for polygon_index in island:
+        polygon = mesh.polygons[polygon_index]
+        normal = (normal_matrix @ polygon.normal).normalized()
+        origin = positions[polygon.vertices[0]]
+
+        if any(normal.dot(positions[v] - origin) > _CONVEX_EPSILON for v in vertices):
+            return False
+
+    return True
+
+
+def write_solids(
+    writer: VmfWriter, obj: Object, scale: float, ids: IdAllocator
+) -> int:
+    """Writes the connected parts of the mesh as solids.
+    Returns the amount of parts which were skipped because they aren't convex."""
+    mesh = obj.data
+    matrix = obj.matrix_world
+    normal_matrix = matrix.to_3x3().inverted_safe().transposed()
+
+    positions = [(matrix @ vertex.co) / scale for vertex in mesh.vertices]
+    axes = stored_texture_axes(mesh)
+    lightmap_scales = stored_lightmap_scales(mesh)
+    skipped = 0
+
+    for island in polygon_islands(mesh):
+        if not is_convex(mesh, island, positions, normal_matrix):
+            skipped += 1
+            continue
+
+        writer.begin("solid")
+        writer.key_value("id", ids.allocate())
+
+        for polygon_index in island:
+            polygon = mesh.polygons[polygon_index]
+            if len(polygon.vertices) < 3:
+                continue
+
+            points = [positions[v] for v in polygon.vertices[:3]]
+            # blender winds faces counter-clockwise, vmf planes are clockwise
+            plane = " ".join(f"({format_vector(p)})" for p in reversed(points))
+            u_axis, v_axis = axes[polygon_index] or texture_axes(
+                normal_matrix @ polygon.normal
+            )
+
+            writer.begin("side")
+            writer.key_value("id", ids.allocate())
+            writer.key_value("plane", plane)
+            writer.key_value("material", material_name(obj, polygon.material_index))
+            writer.key_value("uaxis", u_axis)
+            writer.key_value("vaxis", v_axis)
+            writer.key_value("rotation", 0)
+            writer.key_value(
+                "lightmapscale", format_number(lightmap_scales[polygon_index])
+            )
+            writer.key_value("smoothing_groups", 0)
+            writer.end()
+
+        writer.end()
+
+    return skipped
+
+
+def write_entity_properties(writer: VmfWriter, obj: Object, scale: float) -> None:
+    props = obj.get("props")
+    if props is not None:
+        for key, value in props.items():
+            if key.lower() not in _SKIPPED_KEYS:
+                writer.key_value(key, value)
+
+    writer.key_value("origin", format_vector(obj.matrix_world.translation / scale))
+
+    # lights store their direction differently, keep their original angles
+    if obj.type != "LIGHT":
+        roll, pitch, yaw = obj.matrix_world.to_euler("XYZ")
+        writer.key_value(
+            "angles", format_vector((degrees(pitch), degrees(yaw), degrees(roll)))
+        )
+    elif props is not None and "angles" in props:
+        writer.key_value("angles", props["angles"])
+
+
+def write_brush_properties(writer: VmfWriter, brush_objects: List[Object]) -> bool:
+    """Writes the stored keyvalues of a brush entity.
+    Returns whether any were stored."""
+    for obj in brush_objects:
+        props = obj.get("props")
+        if props is not None:
+            for key, value in props.items():
+                if key.lower() not in _SKIPPED_BRUSH_KEYS:
+                    writer.key_value(key, value)
+            return True
+
+    return False
+
+
+def export_vmf(objects: List[Object], scale: float) -> Tuple[str, int]:
+    """Returns the vmf text, and the amount of solids which were skipped
+    because they aren't convex."""
+    brushes: Dict[Tuple[str, int], List[Object]] = {}
+    entities: List[Tuple[str, int, Object]] = []
+
+    for obj in objects:
+        parsed = parse_entity_name(obj)
+        if parsed is None:
+            # not imported from a vmf, so there's no entity to export it into
+            continue
+
+        # only brushes are marked with their class, other entities have it in `props`
+        if obj.type == "MESH" and obj.get("vmf_class_name") is not None:
+            brushes.setdefault(parsed, []).append(obj)
+        elif obj.get("props") is not None:
+            class_name, entity_id = parsed
+            entities.append((obj["props"].get("classname", class_name), entity_id, obj))
+
+    used_ids: Set[int] = {id for _, id in brushes} | {id for _, id, _ in entities}
+    ids = IdAllocator(used_ids)
+
+    writer = VmfWriter()
+    skipped = 0
+
+    writer.begin("versioninfo")
+    writer.key_value("editorversion", 400)
+    writer.key_value("editorbuild", 8000)
+    writer.key_value("mapversion", 1)
+    writer.key_value("formatversion", 100)
+    writer.key_value("prefab", 0)
+    writer.end()
+
+    world_objects = [
+        obj
+        for (class_name, _), brush_objects in brushes.items()
+        if class_name == "worldspawn"
+        for obj in brush_objects
+    ]
+
+    writer.begin("world")
+    writer.key_value("id", ids.allocate())
+    writer.key_value("classname", "worldspawn")
+    if not write_brush_properties(writer, world_objects):
+        writer.key_value("mapversion", 1)
+        writer.key_value("skyname", "sky_day01_01")
+    for obj in world_objects:
+        skipped += write_solids(writer, obj, scale, ids)
+    writer.end()
+
+    for (class_name, entity_id), brush_objects in brushes.items():
+        if class_name == "worldspawn":
+            continue
+
+        writer.begin("entity")
+        writer.key_value("id", entity_id)
+        writer.key_value("classname", class_name)
+        write_brush_properties(writer, brush_objects)
+        for obj in brush_objects:
+            skipped += write_solids(writer, obj, scale, ids)
+        writer.end()
+
+    for class_name, entity_id, obj in entities:
+        writer.begin("entity")
+        writer.key_value("id", entity_id)
+        writer.key_value("classname", class_name)
+        write_entity_properties(writer, obj, scale)
+        writer.end()
+
+    return writer.text(), skipped
+
+
+class ExportVmf(Operator, ExportHelper, ScaleOperatorProps):
+    """Export imported brushes, props and entities back into a Source Engine VMF map"""
+
+    bl_idname = "export_scene.plumber_vmf"
+    bl_label = "Export VMF"
+    bl_options = {"REGISTER"}
+
+    filename_ext = ".vmf"
+
+    filter_glob: StringProperty(
+        default="*.vmf",
+        options={"HIDDEN"},
+        maxlen=255,
+    )
+
+    selected_only: BoolProperty(
+        name="Selected only",
+        description="Only export selected objects",
+        default=False,
+    )
+
+    def execute(self, context: Context) -> Set[str]:
+        if self.selected_only:
+            objects = list(context.selected_objects)
+        else:
+            objects = list(context.scene.objects)
+
+        # the scale the map was imported at
+        text, skipped = export_vmf(objects, self.get_scale())
+        if skipped:
+            self.report({"WARNING"}, f"skipped {skipped} solids which aren't convex")
+
+        try:
+            with open(self.filepath, "w", encoding="utf-8") as f:
+                f.write(text)
+        except OSError as err:
+            self.report({"ERROR"}, f"could not write vmf: {err}")
+            return {"CANCELLED"}
+
+        return {"FINISHED"}
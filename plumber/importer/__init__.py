@@ -3,7 +3,13 @@ from os.path import basename, dirname
 import os
 
 import bpy
-from bpy.props import EnumProperty, BoolProperty, StringProperty, CollectionProperty
+from bpy.props import (
+    BoolProperty,
+    CollectionProperty,
+    EnumProperty,
+    FloatProperty,
+    StringProperty,
+)
 from bpy.types import Context, Operator, Panel, UILayout, PropertyGroup
 
 from ..plumber import FileSystem
@@ -11,6 +17,9 @@ from ..preferences import AddonPreferences
 
 from .. import __package__ as ADDON_NAME
 
+# more problems than this are only shown in the console
+MAX_REPORTED_DIAGNOSTICS = 10
+
 
 class ImporterOperatorProps:
     game: EnumProperty(
@@ -51,8 +60,28 @@ class ImporterOperator(Operator, ImporterOperatorProps):
 
     def get_threads_suggestion(self, context: Context) -> int:
         preferences = context.preferences.addons[ADDON_NAME].preferences
-        # leave room for blender's thread
-        return preferences.threads - 1
+        return preferences.get_importer_threads()
+
+    def get_channel_capacity(self, context: Context) -> int:
+        preferences = context.preferences.addons[ADDON_NAME].preferences
+        return preferences.channel_capacity
+
+    def report_import_results(self, asset_callbacks) -> None:
+        """Report the problems of individual assets and the import summary to the user."""
+        diagnostics = asset_callbacks.diagnostics
+
+        for diagnostic in diagnostics[:MAX_REPORTED_DIAGNOSTICS]:
+            self.report({diagnostic.severity()}, diagnostic.text())
+
+        if len(diagnostics) > MAX_REPORTED_DIAGNOSTICS:
+            hidden = len(diagnostics) - MAX_REPORTED_DIAGNOSTICS
+            self.report(
+                {"WARNING"}, f"{hidden} more problems, see the console for details"
+            )
+
+        report = asset_callbacks.summary_report()
+        if report is not None:
+            self.report({"INFO"}, report)
 
     def get_target_fps(self, context: Context) -> float:
         scene = context.scene
@@ -155,6 +184,23 @@ class PLUMBER_PT_importer_common(Panel):
         layout.prop(operator, "game")
 
 
+TEXTURE_FORMAT_ITEMS = [
+    ("Tga", "TGA", "Truevision TGA"),
+    ("Png", "PNG", "Portable Network Graphigs"),
+    (
+        "Exr",
+        "OpenEXR",
+        "Keeps the values of HDR textures above 1.0, at the cost of larger images",
+    ),
+    (
+        "Raw",
+        "Raw",
+        "Copy decoded pixels straight into the images, skipping image encoding. "
+        "Faster, but uses more memory during the import",
+    ),
+]
+
+
 class MaterialImporterOperatorProps:
     simple_materials: BoolProperty(
         name="Simple materials",
@@ -162,13 +208,23 @@ class MaterialImporterOperatorProps:
         default=False,
     )
 
+    pbr_estimation: BoolProperty(
+        name="PBR estimation",
+        description="Estimate metallic and roughness values from the Source shading "
+        "parameters of materials, for physically based rendering pipelines",
+        default=False,
+    )
+
+    envmap_mask_roughness: BoolProperty(
+        name="Envmap mask roughness",
+        description="Use the inverted envmap masks of materials as their roughness",
+        default=False,
+    )
+
     texture_format: EnumProperty(
         name="Texture format",
         description="Format to use for imported image textures",
-        items=[
-            ("Tga", "TGA", "Truevision TGA"),
-            ("Png", "PNG", "Portable Network Graphigs"),
-        ],
+        items=TEXTURE_FORMAT_ITEMS,
         default="Png",
     )
 
@@ -202,6 +258,8 @@ class MaterialImporterOperatorProps:
     ):
         layout.use_property_split = True
         layout.prop(operator, "simple_materials")
+        layout.prop(operator, "pbr_estimation")
+        layout.prop(operator, "envmap_mask_roughness")
         layout.prop(operator, "texture_format")
         layout.prop(operator, "texture_interpolation")
         layout.prop(operator, "allow_culling")
@@ -250,14 +308,82 @@ class PLUMBER_PT_importer_materials(Panel):
         MaterialImporterOperatorProps.draw_props(layout, operator, context)
 
 
+# Blender units per Source unit of the scale presets
+SCALE_PRESETS = {
+    "CENTIMETERS": 0.01,
+    # Valve's scale for world geometry is 1 unit = 1 inch
+    "METERS": 0.0254,
+    # and 1 unit = 0.75 inch for characters, which are oversized in world scale
+    "CHARACTER": 0.01905,
+}
+
+
+class ScaleOperatorProps:
+    scale_preset: EnumProperty(
+        name="Scale",
+        description="How Source units are converted into Blender units",
+        items=[
+            ("CENTIMETERS", "Default", "1 unit = 1 cm, a convenient size in Blender"),
+            (
+                "METERS",
+                "Real world",
+                "1 unit = 1 inch, the real world size of maps and props",
+            ),
+            (
+                "CHARACTER",
+                "Character",
+                "1 unit = 0.75 inch, the real world size of characters",
+            ),
+            ("CUSTOM", "Custom", "Multiply Source units by the custom scale"),
+        ],
+        default="CENTIMETERS",
+    )
+
+    scale: FloatProperty(
+        name="Custom scale",
+        default=0.01,
+        min=1e-6,
+        max=1e6,
+        soft_min=0.001,
+        soft_max=1.0,
+    )
+
+    def get_scale(self) -> float:
+        if self.scale_preset == "CUSTOM":
+            return self.scale
+        return SCALE_PRESETS[self.scale_preset]
+
+    @staticmethod
+    def draw_props(layout: UILayout, operator: "ScaleOperatorProps", context: Context):
+        layout.prop(operator, "scale_preset")
+        row = layout.row()
+        row.enabled = operator.scale_preset == "CUSTOM"
+        row.prop(operator, "scale")
+
+
 class ModelImporterOperatorProps:
     import_animations: BoolProperty(name="Import animations", default=True)
 
+    static_prop_rotation: EnumProperty(
+        name="Static prop rotation",
+        description="Orientation of models compiled with $staticprop",
+        items=[
+            ("MAP", "As in maps", "Face the way the model faces in maps"),
+            (
+                "MODELED",
+                "As modeled",
+                "Undo the 90 degree yaw studiomdl bakes into static props",
+            ),
+        ],
+        default="MAP",
+    )
+
     @staticmethod
     def draw_props(
         layout: UILayout, operator: "ModelImporterOperatorProps", context: Context
     ):
         layout.prop(operator, "import_animations")
+        layout.prop(operator, "static_prop_rotation")
 
 
 from .vmf import (
@@ -271,7 +397,11 @@ from .vmf import (
 )
 from .mdl import ImportMdl, PLUMBER_PT_mdl_main
 from .vmt import ImportVmt, PLUMBER_PT_vmt_main
-from .vtf import ImportVtf
+from .vtf import ImportVtf, PLUMBER_PT_vtf_main
+from .nav import ImportNav
+from .ain import ImportAin
+from .goldsrc import ImportGoldSrcBsp
+from .vmap import ImportVmap
 
 
 CLASSES = [
@@ -284,11 +414,16 @@ CLASSES = [
     PLUMBER_PT_importer_materials,
     PLUMBER_PT_vmf_main,
     PLUMBER_PT_vmt_main,
+    PLUMBER_PT_vtf_main,
     PLUMBER_PT_mdl_main,
     ImportVmf,
     ImportMdl,
     ImportVmt,
     ImportVtf,
+    ImportNav,
+    ImportAin,
+    ImportGoldSrcBsp,
+    ImportVmap,
 ]
 
 
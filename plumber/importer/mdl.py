@@ -1,7 +1,7 @@
 from typing import Set
 
 from bpy.types import Context, Panel
-from bpy.props import StringProperty, FloatProperty
+from bpy.props import StringProperty
 
 from . import (
     GameFileImporterOperator,
@@ -9,6 +9,7 @@ from . import (
     ImporterOperatorProps,
     MaterialToggleOperatorProps,
     ModelImporterOperatorProps,
+    ScaleOperatorProps,
 )
 from ..asset import AssetCallbacks
 from ..plumber import Importer
@@ -20,6 +21,7 @@ class ImportMdl(
     GameFileImporterOperatorProps,
     ModelImporterOperatorProps,
     MaterialToggleOperatorProps,
+    ScaleOperatorProps,
 ):
     """Import Source Engine MDL model"""
 
@@ -35,15 +37,6 @@ class ImportMdl(
         maxlen=255,
     )
 
-    scale: FloatProperty(
-        name="Scale",
-        default=0.01,
-        min=1e-6,
-        max=1e6,
-        soft_min=0.001,
-        soft_max=1.0,
-    )
-
     def execute(self, context: Context) -> Set[str]:
         fs = self.get_game_fs(context)
         asset_callbacks = AssetCallbacks(context)
@@ -62,9 +55,12 @@ class ImportMdl(
                 fs,
                 asset_callbacks,
                 self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
                 import_materials=self.import_materials,
                 target_fps=self.get_target_fps(context),
                 simple_materials=self.simple_materials,
+                pbr_estimation=self.pbr_estimation,
+                envmap_mask_roughness=self.envmap_mask_roughness,
                 allow_culling=self.allow_culling,
                 editor_materials=self.editor_materials,
                 texture_format=self.texture_format,
@@ -84,7 +80,7 @@ class ImportMdl(
                 )
 
                 # Apply scale to all models in the batch
-                asset_callbacks.model_tracker.apply_scale_to_batch(self.scale)
+                asset_callbacks.model_tracker.apply_scale_to_batch(self.get_scale())
             else:
                 importer.import_mdl(
                     self.filepath,
@@ -95,12 +91,18 @@ class ImportMdl(
                 # Apply scale to the single imported model
                 imported_obj = asset_callbacks.model_tracker.get_last_imported()
                 if imported_obj is not None:
-                    imported_obj.scale = (self.scale, self.scale, self.scale)
+                    scale = self.get_scale()
+                    imported_obj.scale = (scale, scale, scale)
+
+            if self.static_prop_rotation == "MODELED":
+                asset_callbacks.model_tracker.rotate_static_props()
 
         except OSError as err:
             self.report({"ERROR"}, f"could not import mdl: {err}")
             return {"CANCELLED"}
 
+        self.report_import_results(asset_callbacks)
+
         return {"FINISHED"}
 
     def draw(self, context: Context):
@@ -108,7 +110,7 @@ class ImportMdl(
             ModelImporterOperatorProps.draw_props(self.layout, self, context)
             MaterialToggleOperatorProps.draw_props(self.layout, self, context)
 
-            self.layout.prop(self, "scale")
+            ScaleOperatorProps.draw_props(self.layout, self, context)
 
 
 class PLUMBER_PT_mdl_main(Panel):
@@ -127,4 +129,4 @@ class PLUMBER_PT_mdl_main(Panel):
         operator = context.space_data.active_operator
         ModelImporterOperatorProps.draw_props(self.layout, operator, context)
 
-        self.layout.prop(operator, "scale")
+        ScaleOperatorProps.draw_props(self.layout, operator, context)
@@ -0,0 +1,54 @@
+from typing import Set
+
+from bpy.types import Context
+from bpy.props import StringProperty
+
+from . import (
+    GameFileImporterOperator,
+    GameFileImporterOperatorProps,
+    ImporterOperatorProps,
+)
+from ..asset import AssetCallbacks
+from ..plumber import Importer
+
+
+class ImportNav(
+    GameFileImporterOperator,
+    ImporterOperatorProps,
+    GameFileImporterOperatorProps,
+):
+    """Import Source Engine navigation mesh"""
+
+    bl_idname = "import_scene.plumber_nav"
+    bl_label = "Import NAV"
+    bl_options = {"REGISTER", "UNDO"}
+
+    filename_ext = ".nav"
+
+    filter_glob: StringProperty(
+        default="*.nav",
+        options={"HIDDEN"},
+        maxlen=255,
+    )
+
+    def execute(self, context: Context) -> Set[str]:
+        fs = self.get_game_fs(context)
+
+        try:
+            importer = Importer(
+                fs,
+                AssetCallbacks(context),
+                self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
+            )
+        except OSError as err:
+            self.report({"ERROR"}, f"could not open file system: {err}")
+            return {"CANCELLED"}
+
+        try:
+            importer.import_nav(self.filepath, self.from_game_fs)
+        except OSError as err:
+            self.report({"ERROR"}, f"could not import nav: {err}")
+            return {"CANCELLED"}
+
+        return {"FINISHED"}
@@ -0,0 +1,116 @@
+from typing import Set
+from os.path import basename, splitext
+
+from bpy.props import BoolProperty, FloatProperty, StringProperty
+from bpy.types import Context
+import bpy
+
+from . import (
+    GameFileImporterOperator,
+    GameFileImporterOperatorProps,
+    ImporterOperatorProps,
+    ScaleOperatorProps,
+)
+from ..asset import AssetCallbacks
+from ..plumber import Importer
+
+
+class ImportVmap(
+    GameFileImporterOperator,
+    ImporterOperatorProps,
+    GameFileImporterOperatorProps,
+    ScaleOperatorProps,
+):
+    """Import Source 2 map saved in the text DMX format"""
+
+    bl_idname = "import_scene.plumber_vmap"
+    bl_label = "Import VMAP"
+    bl_options = {"REGISTER", "UNDO"}
+
+    filename_ext = ".vmap"
+
+    filter_glob: StringProperty(
+        default="*.vmap",
+        options={"HIDDEN"},
+        maxlen=255,
+    )
+
+    import_lights: BoolProperty(
+        name="Lights",
+        default=True,
+    )
+
+    light_factor: FloatProperty(
+        name="Light brightness factor",
+        description="Factor for converting light brightness into Blender",
+        min=0,
+        max=100.0,
+        soft_min=0.0001,
+        soft_max=1.0,
+        default=0.1,
+        precision=4,
+    )
+
+    import_unknown_entities: BoolProperty(
+        name="Other entities",
+        description="Import all other entities, including props, as empties",
+        default=True,
+    )
+
+    def execute(self, context: Context) -> Set[str]:
+        fs = self.get_game_fs(context)
+
+        map_collection = bpy.data.collections.new(
+            splitext(basename(self.filepath))[0]
+        )
+        context.collection.children.link(map_collection)
+
+        brush_collection = bpy.data.collections.new("brushes")
+        map_collection.children.link(brush_collection)
+
+        if self.import_lights:
+            light_collection = bpy.data.collections.new("lights")
+            map_collection.children.link(light_collection)
+        else:
+            light_collection = None
+
+        if self.import_unknown_entities:
+            entity_collection = bpy.data.collections.new("entities")
+            map_collection.children.link(entity_collection)
+        else:
+            entity_collection = None
+
+        asset_callbacks = AssetCallbacks(
+            context,
+            main_collection=map_collection,
+            brush_collection=brush_collection,
+            light_collection=light_collection,
+            entity_collection=entity_collection,
+        )
+
+        try:
+            importer = Importer(
+                fs,
+                asset_callbacks,
+                self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
+                import_lights=self.import_lights,
+                light_factor=self.light_factor,
+                import_unknown_entities=self.import_unknown_entities,
+                scale=self.get_scale(),
+            )
+        except OSError as err:
+            self.report({"ERROR"}, f"Could not open file system: {err}")
+            return {"CANCELLED"}
+
+        try:
+            importer.import_vmap(self.filepath, self.from_game_fs)
+        except OSError as err:
+            self.report({"ERROR"}, f"Could not import vmap: {err}")
+            return {"CANCELLED"}
+
+        asset_callbacks.finish()
+
+        self.report_import_results(asset_callbacks)
+
+        return {"FINISHED"}
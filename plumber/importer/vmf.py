@@ -1,3 +1,4 @@
+import math
 from typing import Set
 from os.path import basename, splitext, isdir, isabs, dirname, join
 
@@ -5,6 +6,7 @@ from bpy.props import (
     BoolProperty,
     EnumProperty,
     FloatProperty,
+    FloatVectorProperty,
     StringProperty,
     IntProperty,
 )
@@ -16,8 +18,11 @@ from . import (
     GameFileImporterOperatorProps,
     ImporterOperatorProps,
     MaterialToggleOperatorProps,
+    ScaleOperatorProps,
 )
 from ..asset import AssetCallbacks
+from ..asset.dependency import DependencyGraph
+from ..asset.existing import collect_existing_assets
 from ..plumber import Importer
 
 
@@ -26,8 +31,9 @@ class ImportVmf(
     ImporterOperatorProps,
     GameFileImporterOperatorProps,
     MaterialToggleOperatorProps,
+    ScaleOperatorProps,
 ):
-    """Import Source Engine VMF map"""
+    """Import Source Engine VMF, Hammer RMF or Quake / Half-Life MAP map"""
 
     bl_idname = "import_scene.plumber_vmf"
     bl_label = "Import VMF"
@@ -35,9 +41,10 @@ class ImportVmf(
 
     filename_ext = ".vmf"
 
+    # the .map and .rmf menu entries set this, so it's not remembered for the .vmf entry
     filter_glob: StringProperty(
         default="*.vmf",
-        options={"HIDDEN"},
+        options={"HIDDEN", "SKIP_SAVE"},
         maxlen=255,
     )
 
@@ -56,9 +63,24 @@ class ImportVmf(
         default=True,
     )
 
+    overlay_offset: FloatProperty(
+        name="Overlay offset",
+        description=(
+            "Distance in Source units overlays are moved off their surface for each "
+            "step of their render order, so they don't flicker with the brushes "
+            "or overlays below them. 0 disables"
+        ),
+        min=0,
+        soft_max=1.0,
+        default=0.1,
+    )
+
     epsilon: FloatProperty(
         name="Epsilon",
-        description="Equality threshold for building geometry",
+        description=(
+            "Distance within which points are considered to be on a plane "
+            "when building brushes. Raise it if faces of off-grid brushes are missing"
+        ),
         min=0,
         max=1.0,
         soft_min=0.0001,
@@ -69,7 +91,10 @@ class ImportVmf(
 
     cut_threshold: FloatProperty(
         name="Cut threshold",
-        description="Threshold for cutting geometry",
+        description=(
+            "Minimum distance a face must extend past a plane to be cut by it. "
+            "Raise it if off-grid brushes have sliver polygons"
+        ),
         min=0,
         max=1.0,
         soft_min=0.0001,
@@ -78,10 +103,138 @@ class ImportVmf(
         precision=6,
     )
 
+    repair_solids: BoolProperty(
+        name="Repair invalid solids",
+        description=(
+            "Import invalid solids without the sides which have no face, "
+            "instead of skipping the whole solid"
+        ),
+        default=False,
+    )
+
+    seam_tolerance: FloatProperty(
+        name="Sew seams",
+        description=(
+            "Move displacement edges within this distance together, "
+            "so there are no cracks between them. 0 disables"
+        ),
+        min=0,
+        soft_max=4.0,
+        default=1.0,
+    )
+
+    lightmap_uvs: BoolProperty(
+        name="Lightmap UVs",
+        description=(
+            "Add a second UV layer to brushes for baking lighting, "
+            "at the lightmap scale of their faces"
+        ),
+        default=False,
+    )
+
+    exact_vertices: BoolProperty(
+        name="Hammer++ vertices",
+        description=(
+            "Snap brush vertices to the exact vertices Hammer++ stores in the map, "
+            "instead of the vertices derived from the brush planes"
+        ),
+        default=True,
+    )
+
+    texture_atlas: EnumProperty(
+        name="Texture atlas",
+        description=(
+            "Pack the textures of brushes into atlases of this size, "
+            "for game engines with draw call limits"
+        ),
+        items=[
+            ("NONE", "None", "Keep the materials of the brushes"),
+            ("2048", "2048", "Pack the textures into 2048x2048 atlases"),
+            ("4096", "4096", "Pack the textures into 4096x4096 atlases"),
+            ("8192", "8192", "Pack the textures into 8192x8192 atlases"),
+        ],
+        default="NONE",
+    )
+
+    bake_blend_materials: EnumProperty(
+        name="Bake blend materials",
+        description=(
+            "Bake the materials blended by displacement alpha into one texture "
+            "of this size per displacement, at the cost of texture memory"
+        ),
+        items=[
+            ("NONE", "None", "Keep the blend materials"),
+            ("1024", "1024", "Bake into 1024x1024 textures"),
+            ("2048", "2048", "Bake into 2048x2048 textures"),
+            ("4096", "4096", "Bake into 4096x4096 textures"),
+        ],
+        default="NONE",
+    )
+
+    weld_distance: FloatProperty(
+        name="Weld distance",
+        description=(
+            "Merge brush vertices within this distance of each other, "
+            "so solids which touch are connected. 0 disables"
+        ),
+        min=0,
+        soft_max=1.0,
+        default=0.0,
+    )
+
+    smooth_angle: FloatProperty(
+        name="Smooth angle",
+        description=(
+            "Shade brushes and displacements smooth between faces at a smaller angle, "
+            "and sharp at larger angles. 0 shades them flat"
+        ),
+        subtype="ANGLE",
+        min=0,
+        max=math.pi,
+        default=0.0,
+    )
+
+    clean_faces: BoolProperty(
+        name="Clean up faces",
+        description=(
+            "Remove faces without area and collinear vertices from brushes, "
+            "which cause shading artifacts"
+        ),
+        default=False,
+    )
+
+    max_displacement_power: IntProperty(
+        name="Max displacement power",
+        description=(
+            "Import displacements with a higher power at this power, "
+            "with fewer vertices"
+        ),
+        min=2,
+        max=4,
+        default=4,
+    )
+
+    displacement_tolerance: FloatProperty(
+        name="Displacement decimation",
+        description=(
+            "Lower the power of displacements as long as no vertex moves "
+            "further than this distance. 0 disables"
+        ),
+        min=0,
+        soft_max=16.0,
+        default=0.0,
+    )
+
     merge_solids: EnumProperty(
         name="Merge solids",
         items=[
             ("MERGE", "Merge", "Solids are merged into one mesh object per brush"),
+            (
+                "MERGE_WORLD",
+                "Merge world",
+                "Merge the world solids, with a separate mesh object for each solid "
+                "of brush entities, so they can be moved individually",
+            ),
             (
                 "SEPARATE",
                 "Separate",
@@ -100,6 +253,16 @@ class ImportVmf(
         default="SKIP",
     )
 
+    portal_brushes: EnumProperty(
+        name="Portals and occluders",
+        description="Import settings for func_areaportal and func_occluder brushes",
+        items=[
+            ("HIDE", "Hide", "Import into a hidden collection"),
+            ("SKIP", "Skip", "Don't import portal and occluder brushes"),
+        ],
+        default="HIDE",
+    )
+
     import_props: BoolProperty(
         name="Props",
         default=True,
@@ -125,6 +288,16 @@ class ImportVmf(
         default="NORMAL",
     )
 
+    instance_props: BoolProperty(
+        name="Instance props",
+        description=(
+            "Import props sharing a model, skin and bodygroup as instances of "
+            "the same collection, which uses much less memory in large maps. "
+            "Props with armatures are not instanced"
+        ),
+        default=False,
+    )
+
     import_lights: BoolProperty(
         name="Lights",
         default=True,
@@ -184,19 +357,95 @@ class ImportVmf(
         subtype="PIXEL",
     )
 
+    import_radar: BoolProperty(
+        name="Radar overview",
+        description="Import the CS:S/CS:GO radar image of the map as a plane",
+        default=False,
+    )
+
     import_unknown_entities: BoolProperty(
         name="Unknown entities",
         description="Import all entities not imported elsewhere as empties",
         default=False,
     )
 
-    scale: FloatProperty(
-        name="Scale",
-        default=0.01,
-        min=1e-6,
-        max=1e6,
-        soft_min=0.001,
-        soft_max=1.0,
+    editor_groups: BoolProperty(
+        name="Editor groups",
+        description=(
+            "Store the visgroups and groups of solids and entities, and the keyvalues "
+            "exporting brushes back into a VMF needs, as custom properties"
+        ),
+        default=True,
+    )
+
+    filter_bounds: BoolProperty(
+        name="Only import within bounds",
+        description=(
+            "Only import the solids and entities within a box, "
+            "such as one building of a huge map with everything inside it"
+        ),
+        default=False,
+    )
+
+    bounds_min: FloatVectorProperty(
+        name="Bounds min",
+        description="Corner of the box with the lowest coordinates, in Hammer units",
+        size=3,
+        default=(-1024.0, -1024.0, -1024.0),
+    )
+
+    bounds_max: FloatVectorProperty(
+        name="Bounds max",
+        description="Corner of the box with the highest coordinates, in Hammer units",
+        size=3,
+        default=(1024.0, 1024.0, 1024.0),
+    )
+
+    recenter: EnumProperty(
+        name="Recenter",
+        description=(
+            "Move the map so this point is at the world origin. "
+            "Maps far from the origin suffer from float precision issues"
+        ),
+        items=[
+            ("NONE", "None", "Keep the map where it is in Hammer"),
+            ("BOUNDS", "Bounds", "Move the center of the map's bounds to the origin"),
+            (
+                "SPAWN",
+                "Player spawn",
+                "Move the player spawn to the origin, "
+                "or the center of the bounds if there is none",
+            ),
+        ],
+        default="NONE",
+    )
+
+    placeholders: BoolProperty(
+        name="Placeholders for missing content",
+        description=(
+            "Replace models which failed to load with error cubes "
+            "and materials which failed to load with a magenta checker pattern"
+        ),
+        default=False,
+    )
+
+    skip_existing: BoolProperty(
+        name="Skip existing assets",
+        description=(
+            "Don't convert materials, textures and models already in the blend file "
+            "again, so re-importing an edited map only imports what changed"
+        ),
+        default=False,
+    )
+
+    dependency_graph_path: StringProperty(
+        name="Dependency graph",
+        description=(
+            "Write a JSON graph of the imported assets and their dependencies "
+            "to this file. Leave empty to disable"
+        ),
+        default="",
+        subtype="FILE_PATH",
     )
 
     def execute(self, context: Context) -> Set[str]:
@@ -217,7 +466,15 @@ class ImportVmf(
                 )
                 return {"CANCELLED"}
 
-        map_name = splitext(basename(self.filepath))[0]
+        file_paths = self.get_file_paths()
+        is_batch = self.is_batch_import() and not self.from_game_fs
+
+        if is_batch:
+            # the maps of a batch share one session, so their assets are only
+            # imported once
+            map_name = " + ".join(splitext(basename(path))[0] for path in file_paths)
+        else:
+            map_name = splitext(basename(self.filepath))[0]
 
         map_collection = bpy.data.collections.new(map_name)
         context.collection.children.link(map_collection)
@@ -228,6 +485,14 @@ class ImportVmf(
         else:
             brush_collection = None
 
+        if self.import_brushes and self.portal_brushes == "HIDE":
+            portal_collection = bpy.data.collections.new("portals")
+            map_collection.children.link(portal_collection)
+            portal_collection.hide_viewport = True
+            portal_collection.hide_render = True
+        else:
+            portal_collection = None
+
         if self.import_brushes and self.import_overlays:
             overlay_collection = bpy.data.collections.new("overlays")
             map_collection.children.link(overlay_collection)
@@ -252,6 +517,13 @@ class ImportVmf(
         else:
             entity_collection = None
 
+        if self.dependency_graph_path == "":
+            dependency_graph = None
+        else:
+            dependency_graph = DependencyGraph(
+                map_name, bpy.path.abspath(self.dependency_graph_path)
+            )
+
         asset_callbacks = AssetCallbacks(
             context,
             main_collection=map_collection,
@@ -260,7 +532,23 @@ class ImportVmf(
             prop_collection=prop_collection,
             light_collection=light_collection,
             entity_collection=entity_collection,
+            portal_collection=portal_collection,
+            skip_portals=self.portal_brushes == "SKIP",
+            smooth_angle=self.smooth_angle,
             apply_armatures=self.dynamic_props == "REMOVE_ARM",
+            dependency_graph=dependency_graph,
+            placeholder_scale=self.get_scale() if self.placeholders else None,
+            instance_props=self.instance_props,
+            reuse_existing_models=self.skip_existing,
+            recenter=None if self.recenter == "NONE" else self.recenter,
+            atlas_size=(
+                None if self.texture_atlas == "NONE" else int(self.texture_atlas)
+            ),
+            bake_size=(
+                None
+                if self.bake_blend_materials == "NONE"
+                else int(self.bake_blend_materials)
+            ),
         )
 
         try:
@@ -268,6 +556,7 @@ class ImportVmf(
                 fs,
                 asset_callbacks,
                 self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
                 import_materials=self.import_materials,
                 import_lights=self.import_lights,
                 light_factor=self.light_factor,
@@ -278,10 +567,23 @@ class ImportVmf(
                     self.sky_equi_height if self.sky_equi_height != 0 else None
                 ),
                 import_unknown_entities=self.import_unknown_entities,
-                scale=self.scale,
+                dependency_graph=dependency_graph is not None,
+                existing_assets=(
+                    collect_existing_assets() if self.skip_existing else None
+                ),
+                seam_tolerance=self.seam_tolerance,
+                lightmap_uvs=self.lightmap_uvs,
+                exact_vertices=self.exact_vertices,
+                editor_groups=self.editor_groups,
+                clean_faces=self.clean_faces,
+                weld_distance=self.weld_distance,
+                overlay_offset=self.overlay_offset,
+                scale=self.get_scale(),
                 target_fps=self.get_target_fps(context),
                 remove_animations=self.dynamic_props in ("REMOVE_ANIM", "REMOVE_ARM"),
                 simple_materials=self.simple_materials,
+                pbr_estimation=self.pbr_estimation,
+                envmap_mask_roughness=self.envmap_mask_roughness,
                 allow_culling=self.allow_culling,
                 editor_materials=self.editor_materials,
                 texture_interpolation=self.texture_interpolation,
@@ -294,27 +596,41 @@ class ImportVmf(
             self.report({"ERROR"}, f"Could not open file system: {err}")
             return {"CANCELLED"}
 
+        vmf_settings = dict(
+            import_brushes=self.import_brushes,
+            import_overlays=self.import_overlays,
+            epsilon=self.epsilon,
+            cut_threshold=self.cut_threshold,
+            repair_solids=self.repair_solids,
+            bounds=(
+                (tuple(self.bounds_min), tuple(self.bounds_max))
+                if self.filter_bounds
+                else None
+            ),
+            max_displacement_power=self.max_displacement_power,
+            displacement_tolerance=self.displacement_tolerance,
+            merge_solids=self.merge_solids,
+            invisible_solids=self.invisible_solids,
+            import_props=self.import_props,
+            import_entities=self.import_lights or self.import_sky_camera,
+            import_sky=self.import_sky,
+            import_radar=self.import_radar,
+            scale=self.get_scale(),
+        )
+
         try:
-            importer.import_vmf(
-                self.filepath,
-                self.from_game_fs,
-                import_brushes=self.import_brushes,
-                import_overlays=self.import_overlays,
-                epsilon=self.epsilon,
-                cut_threshold=self.cut_threshold,
-                merge_solids=self.merge_solids,
-                invisible_solids=self.invisible_solids,
-                import_props=self.import_props,
-                import_entities=self.import_lights or self.import_sky_camera,
-                import_sky=self.import_sky,
-                scale=self.scale,
-            )
+            if is_batch:
+                importer.import_vmf_batch(file_paths, self.from_game_fs, **vmf_settings)
+            else:
+                importer.import_vmf(self.filepath, self.from_game_fs, **vmf_settings)
         except OSError as err:
             self.report({"ERROR"}, f"Could not parse vmf: {err}")
             return {"CANCELLED"}
 
         asset_callbacks.finish()
 
+        self.report_import_results(asset_callbacks)
+
         return {"FINISHED"}
 
     def draw(self, context: Context):
@@ -364,10 +680,25 @@ def draw_geometry_props(layout: UILayout, operator: ImportVmf, context: Context)
     layout.use_property_split = True
     layout.enabled = operator.import_brushes
     layout.prop(operator, "import_overlays")
+    row = layout.row()
+    row.enabled = operator.import_overlays
+    row.prop(operator, "overlay_offset")
     layout.prop(operator, "epsilon")
     layout.prop(operator, "cut_threshold")
+    layout.prop(operator, "repair_solids")
+    layout.prop(operator, "seam_tolerance")
+    layout.prop(operator, "lightmap_uvs")
+    layout.prop(operator, "exact_vertices")
+    layout.prop(operator, "texture_atlas")
+    layout.prop(operator, "bake_blend_materials")
+    layout.prop(operator, "weld_distance")
+    layout.prop(operator, "smooth_angle")
+    layout.prop(operator, "clean_faces")
+    layout.prop(operator, "max_displacement_power")
+    layout.prop(operator, "displacement_tolerance")
     layout.prop(operator, "merge_solids", expand=True)
     layout.prop(operator, "invisible_solids", expand=True)
+    layout.prop(operator, "portal_brushes", expand=True)
 
 
 class PLUMBER_PT_vmf_geometry(Panel):
@@ -451,6 +782,7 @@ def draw_props_props(layout: UILayout, operator: ImportVmf, context: Context):
     layout.use_property_split = True
     layout.enabled = operator.import_props
     layout.prop(operator, "dynamic_props")
+    layout.prop(operator, "instance_props")
 
 
 class PLUMBER_PT_vmf_props(Panel):
@@ -477,8 +809,19 @@ class PLUMBER_PT_vmf_props(Panel):
 def draw_main_props(layout: UILayout, operator: ImportVmf, context: Context):
     layout.use_property_split = True
     layout.prop(operator, "import_sky_camera")
+    layout.prop(operator, "import_radar")
     layout.prop(operator, "import_unknown_entities")
-    layout.prop(operator, "scale")
+    layout.prop(operator, "editor_groups")
+    layout.prop(operator, "filter_bounds")
+    col = layout.column()
+    col.enabled = operator.filter_bounds
+    col.prop(operator, "bounds_min")
+    col.prop(operator, "bounds_max")
+    layout.prop(operator, "recenter")
+    layout.prop(operator, "placeholders")
+    layout.prop(operator, "skip_existing")
+    layout.prop(operator, "dependency_graph_path")
+    ScaleOperatorProps.draw_props(layout, operator, context)
 
 
 class PLUMBER_PT_vmf_main(Panel):
@@ -50,8 +50,11 @@ class ImportVmt(
                 fs,
                 AssetCallbacks(context),
                 self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
                 import_materials=True,
                 simple_materials=self.simple_materials,
+                pbr_estimation=self.pbr_estimation,
+                envmap_mask_roughness=self.envmap_mask_roughness,
                 allow_culling=self.allow_culling,
                 editor_materials=self.editor_materials,
                 texture_interpolation=self.texture_interpolation,
@@ -1,12 +1,13 @@
 from typing import Set
 
-from bpy.types import Context
-from bpy.props import StringProperty
+from bpy.types import Context, Panel, UILayout
+from bpy.props import EnumProperty, StringProperty
 
 from . import (
     GameFileImporterOperator,
     GameFileImporterOperatorProps,
     ImporterOperatorProps,
+    TEXTURE_FORMAT_ITEMS,
 )
 from ..asset import AssetCallbacks
 from ..plumber import Importer
@@ -31,6 +32,13 @@ class ImportVtf(
         maxlen=255,
     )
 
+    texture_format: EnumProperty(
+        name="Texture format",
+        description="Format to use for imported image textures",
+        items=TEXTURE_FORMAT_ITEMS,
+        default="Png",
+    )
+
     def execute(self, context: Context) -> Set[str]:
         fs = self.get_game_fs(context)
 
@@ -42,6 +50,8 @@ class ImportVtf(
                 fs,
                 AssetCallbacks(context),
                 self.get_threads_suggestion(context),
+                channel_capacity=self.get_channel_capacity(context),
+                texture_format=self.texture_format,
             )
         except OSError as err:
             self.report({"ERROR"}, f"could not open file system: {err}")
@@ -57,3 +67,28 @@ class ImportVtf(
             return {"CANCELLED"}
 
         return {"FINISHED"}
+
+    def draw(self, context: Context):
+        if self.from_game_fs:
+            draw_vtf_props(self.layout, self)
+
+
+def draw_vtf_props(layout: UILayout, operator: ImportVtf) -> None:
+    layout.use_property_split = True
+    layout.prop(operator, "texture_format")
+
+
+class PLUMBER_PT_vtf_main(Panel):
+    bl_space_type = "FILE_BROWSER"
+    bl_region_type = "TOOL_PROPS"
+    bl_label = ""
+    bl_parent_id = "FILE_PT_operator"
+    bl_options = {"HIDE_HEADER"}
+
+    @classmethod
+    def poll(cls, context: Context) -> bool:
+        operator = context.space_data.active_operator
+        return operator.bl_idname == "IMPORT_SCENE_OT_plumber_vtf"
+
+    def draw(self, context: Context) -> None:
+        draw_vtf_props(self.layout, context.space_data.active_operator)
@@ -1,4 +1,5 @@
-from typing import Any, Dict, List, Optional, Tuple, Union
+import logging
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
 
 class FileSystem:
     def __init__(self, name: str, search_paths: List[Tuple[str, str]]) -> None: ...
@@ -6,14 +7,33 @@ class FileSystem:
     def empty() -> "FileSystem": ...
     def name(self) -> str: ...
     def search_paths(self) -> List[Tuple[str, str]]: ...
+    def resolved_search_paths(self) -> List[Tuple[str, str]]: ...
+    def find_file(self, path: str) -> List[Tuple[str, str]]: ...
     def with_search_path(self, search_path: Tuple[str, str]) -> "FileSystem": ...
+    def mount(self, other: "FileSystem") -> "FileSystem": ...
+    def with_file_priority(self, priority: str) -> "FileSystem": ...
+    def with_workshop(self, directory: str) -> "FileSystem": ...
     def browse(self) -> "FileBrowser": ...
     def extract(self, path: str, is_dir: bool, target_dir: str): ...
+    def vmf_manifest(self, path: str, from_game: bool) -> str: ...
+
+class Game:
+    def app_id(self) -> int: ...
+    def name(self) -> str: ...
+    def game_info_path(self) -> Optional[str]: ...
+    def workshop_path(self) -> Optional[str]: ...
+    def search_paths(self) -> List[Tuple[str, str]]: ...
 
 def discover_filesystems() -> List[FileSystem]: ...
+def discover_games() -> List[Game]: ...
 def filesystem_from_gameinfo(path: str) -> FileSystem: ...
+def filesystem_from_gameinfos(paths: List[str]) -> FileSystem: ...
 def log_error(error: str) -> None: ...
 def log_info(info: str) -> None: ...
+def set_log_target(
+    target: Optional[Union[logging.Logger, Callable[[int, str], Any]]]
+) -> None: ...
+def set_profiling(enabled: bool) -> None: ...
 def version() -> str: ...
 
 class FileBrowser:
@@ -21,6 +41,10 @@ class FileBrowser:
     def read_file_text(self, path: str) -> str: ...
     def read_file_bytes(self, path: str) -> bytes: ...
     def file_exists(self, path: str) -> bool: ...
+    def dir_exists(self, path: str) -> bool: ...
+    def list_files(
+        self, dir: str, extensions: List[str], recursive: bool
+    ) -> List[str]: ...
 
 class FileBrowserEntry:
     def name(self) -> str: ...
@@ -34,12 +58,55 @@ class SkyEqui:
     def format(self) -> str: ...
     def bytes(self) -> bytes: ...
 
+class NavMesh:
+    def name(self) -> str: ...
+    def scale(self) -> Tuple[float, float, float]: ...
+    def area_ids(self) -> List[int]: ...
+    def vertices(self) -> List[float]: ...
+    def polygon_vertices(self) -> List[int]: ...
+    def edge_vertices(self) -> List[int]: ...
+
+class GoldSrcModel:
+    def name(self) -> str: ...
+    def position(self) -> Tuple[float, float, float]: ...
+    def scale(self) -> Tuple[float, float, float]: ...
+    def texture_ext(self) -> str: ...
+    def vertices(self) -> memoryview: ...
+    def loops_len(self) -> int: ...
+    def polygons_len(self) -> int: ...
+    def polygon_loop_totals(self) -> memoryview: ...
+    def polygon_loop_starts(self) -> memoryview: ...
+    def polygon_vertices(self) -> memoryview: ...
+    def polygon_material_indices(self) -> memoryview: ...
+    def loop_uvs(self) -> memoryview: ...
+    def materials(self) -> List[str]: ...
+
+class NodeGraph:
+    def name(self) -> str: ...
+    def scale(self) -> Tuple[float, float, float]: ...
+    def vertices(self) -> List[float]: ...
+    def edge_vertices(self) -> List[int]: ...
+    def yaws(self) -> List[float]: ...
+    def node_types(self) -> List[str]: ...
+    def zones(self) -> List[int]: ...
+    def hammer_ids(self) -> List[int]: ...
+
+class Radar:
+    def name(self) -> str: ...
+    def image_path(self) -> str: ...
+    def position(self) -> Tuple[float, float, float]: ...
+    def size(self) -> Tuple[float, float]: ...
+
 class Texture:
     def name(self) -> str: ...
     def width(self) -> int: ...
     def height(self) -> int: ...
     def format_ext(self) -> str: ...
+    def encoding_ext(self) -> str: ...
     def bytes(self) -> bytes: ...
+    def float_buffer(self) -> bool: ...
+    def pixels(self) -> memoryview: ...
+    def duplicate_of(self) -> Optional[str]: ...
 
 class Material:
     def name(self) -> str: ...
@@ -79,10 +146,19 @@ class LoadedProp:
     def model(self) -> str: ...
     def class_name(self) -> str: ...
     def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def position(self) -> List[float]: ...
     def rotation(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
     def color(self) -> List[float]: ...
+    def lighting_origin(self) -> Optional[str]: ...
+    def skin(self) -> int: ...
+    def body(self) -> int: ...
+    def fade_distances(self) -> Optional[List[float]]: ...
+    def fade_scale(self) -> float: ...
+    def screen_space_fade(self) -> bool: ...
+    def instance_key(self) -> str: ...
     def properties(self) -> Dict[str, str]: ...
 
 class QuaternionData:
@@ -117,97 +193,219 @@ class LoadedBone:
     def position(self) -> List[float]: ...
     def rotation(self) -> List[float]: ...
 
+class RagdollJoint:
+    def bone_index(self) -> int: ...
+    def parent_bone_index(self) -> Optional[int]: ...
+    def limits(self) -> List[List[float]]: ...
+    def friction(self) -> List[float]: ...
+
+class JiggleBone:
+    def bone_index(self) -> int: ...
+    def rigid(self) -> bool: ...
+    def length(self) -> float: ...
+    def tip_mass(self) -> float: ...
+    def tip_springs(self) -> List[List[float]]: ...
+    def base_spring(self) -> Optional[List[float]]: ...
+
+class IkChain:
+    def name(self) -> str: ...
+    def bone_indices(self) -> List[int]: ...
+    def end_effector_index(self) -> int: ...
+    def knee_direction(self) -> List[float]: ...
+
+class AnimationEvent:
+    def sequence(self) -> str: ...
+    def animation(self) -> str: ...
+    def frame(self) -> float: ...
+    def name(self) -> str: ...
+    def options(self) -> str: ...
+
 class LoadedMesh:
     def name(self) -> str: ...
-    def vertices(self) -> List[float]: ...
+    def vertices(self) -> memoryview: ...
     def loops_len(self) -> int: ...
     def polygons_len(self) -> int: ...
-    def polygon_loop_totals(self) -> List[int]: ...
-    def polygon_loop_starts(self) -> List[int]: ...
-    def polygon_vertices(self) -> List[int]: ...
-    def polygon_material_indices(self) -> List[int]: ...
-    def loop_uvs(self) -> List[float]: ...
-    def normals(self) -> List[List[float]]: ...
+    def polygon_loop_totals(self) -> memoryview: ...
+    def polygon_loop_starts(self) -> memoryview: ...
+    def polygon_vertices(self) -> memoryview: ...
+    def polygon_material_indices(self) -> memoryview: ...
+    def loop_uvs(self) -> memoryview: ...
+    def normals(self) -> memoryview: ...
     def weight_groups(self) -> Dict[int, Dict[int, float]]: ...
 
 class Model:
     def name(self) -> str: ...
+    def static_prop(self) -> bool: ...
     def meshes(self) -> List[LoadedMesh]: ...
     def materials(self) -> List[Optional[str]]: ...
     def bones(self) -> List[LoadedBone]: ...
     def animations(self) -> List[LoadedAnimation]: ...
     def rest_positions(self) -> Dict[int, BoneRestData]: ...
+    def ragdoll(self) -> List[RagdollJoint]: ...
+    def jiggle_bones(self) -> List[JiggleBone]: ...
+    def ik_chains(self) -> List[IkChain]: ...
+    def animation_events(self) -> List[AnimationEvent]: ...
+    def illum_position(self) -> Optional[List[float]]: ...
+    def eye_position(self) -> Optional[List[float]]: ...
+    def hull_bounds(self) -> Optional[List[List[float]]]: ...
+    def view_bounds(self) -> Optional[List[List[float]]]: ...
 
 class MergedSolids:
     def no_draw(self) -> bool: ...
     def position(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
-    def vertices(self) -> List[float]: ...
+    def vertices(self) -> memoryview: ...
     def loops_len(self) -> int: ...
     def polygons_len(self) -> int: ...
-    def polygon_loop_totals(self) -> List[int]: ...
-    def polygon_loop_starts(self) -> List[int]: ...
-    def polygon_vertices(self) -> List[int]: ...
-    def polygon_material_indices(self) -> List[int]: ...
-    def loop_uvs(self) -> List[float]: ...
-    def loop_colors(self) -> List[float]: ...
+    def polygon_loop_totals(self) -> memoryview: ...
+    def polygon_loop_starts(self) -> memoryview: ...
+    def polygon_vertices(self) -> memoryview: ...
+    def polygon_material_indices(self) -> memoryview: ...
+    def loop_uvs(self) -> memoryview: ...
+    def loop_colors(self) -> memoryview: ...
+    def lightmap_resolution(self) -> Optional[int]: ...
+    def lightmap_uvs(self) -> memoryview: ...
+    def lightmap_scales(self) -> memoryview: ...
     def materials(self) -> List[str]: ...
+    def texture_axes(self) -> List[List[str]]: ...
+    def face_texture_axes(self) -> memoryview: ...
 
 class BuiltSolid:
     def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def no_draw(self) -> bool: ...
     def position(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
-    def vertices(self) -> List[float]: ...
+    def vertices(self) -> memoryview: ...
     def loops_len(self) -> int: ...
     def polygons_len(self) -> int: ...
-    def polygon_loop_totals(self) -> List[int]: ...
-    def polygon_loop_starts(self) -> List[int]: ...
-    def polygon_vertices(self) -> List[int]: ...
-    def polygon_material_indices(self) -> List[int]: ...
-    def loop_uvs(self) -> List[float]: ...
-    def loop_colors(self) -> List[float]: ...
+    def polygon_loop_totals(self) -> memoryview: ...
+    def polygon_loop_starts(self) -> memoryview: ...
+    def polygon_vertices(self) -> memoryview: ...
+    def polygon_material_indices(self) -> memoryview: ...
+    def loop_uvs(self) -> memoryview: ...
+    def loop_colors(self) -> memoryview: ...
+    def lightmap_resolution(self) -> Optional[int]: ...
+    def lightmap_uvs(self) -> memoryview: ...
+    def lightmap_scales(self) -> memoryview: ...
     def materials(self) -> List[str]: ...
+    def texture_axes(self) -> List[List[str]]: ...
+    def face_texture_axes(self) -> memoryview: ...
+
+class BrushRender:
+    def id(self) -> int: ...
+    def render_mode(self) -> int: ...
+    def color(self) -> List[float]: ...
+
+class BrushOrigin:
+    def id(self) -> int: ...
+    def origin(self) -> List[float]: ...
+
+class BrushMotion:
+    def id(self) -> int: ...
+    def direction(self) -> List[float]: ...
+    def distance(self) -> Optional[float]: ...
+    def lip(self) -> float: ...
+    def start_position(self) -> float: ...
+    def speed(self) -> float: ...
+
+class BrushRotation:
+    def id(self) -> int: ...
+    def axis(self) -> List[float]: ...
+    def speed(self) -> float: ...
+    def starts_on(self) -> bool: ...
 
 class BuiltBrushEntity:
     def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def class_name(self) -> str: ...
     def merged_solids(self) -> Optional[MergedSolids]: ...
     def solids(self) -> List[BuiltSolid]: ...
+    def origin_brush(self) -> Optional[List[float]]: ...
+    def keyvalues(self) -> Optional[List[Tuple[str, str]]]: ...
+    def render(self) -> Optional[BrushRender]: ...
+    def origin(self) -> Optional[BrushOrigin]: ...
+    def motion(self) -> Optional[BrushMotion]: ...
+    def rotation(self) -> Optional[BrushRotation]: ...
+
+class Decal:
+    def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def material(self) -> str: ...
+    def size(self) -> List[float]: ...
 
 class BuiltOverlay:
     def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def position(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
-    def vertices(self) -> List[float]: ...
+    def vertices(self) -> memoryview: ...
     def loops_len(self) -> int: ...
     def polygons_len(self) -> int: ...
-    def polygon_loop_totals(self) -> List[int]: ...
-    def polygon_loop_starts(self) -> List[int]: ...
-    def polygon_vertices(self) -> List[int]: ...
+    def polygon_loop_totals(self) -> memoryview: ...
+    def polygon_loop_starts(self) -> memoryview: ...
+    def polygon_vertices(self) -> memoryview: ...
     def polygon_material_indices(self) -> List[int]: ...
-    def loop_uvs(self) -> List[float]: ...
+    def loop_uvs(self) -> memoryview: ...
     def material(self) -> str: ...
 
 class Light:
     def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def energy_keyframes(self) -> List[float]: ...
     def position(self) -> List[float]: ...
     def color(self) -> List[float]: ...
     def energy(self) -> float: ...
+    def cutoff_distance(self) -> Optional[float]: ...
     def properties(self) -> Dict[str, str]: ...
 
 class SpotLight:
     def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def energy_keyframes(self) -> List[float]: ...
     def position(self) -> List[float]: ...
     def rotation(self) -> List[float]: ...
     def color(self) -> List[float]: ...
     def energy(self) -> float: ...
+    def cutoff_distance(self) -> Optional[float]: ...
     def spot_size(self) -> float: ...
     def spot_blend(self) -> float: ...
     def properties(self) -> Dict[str, str]: ...
 
+class PointSpotlight:
+    def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def rotation(self) -> List[float]: ...
+    def color(self) -> List[float]: ...
+    def energy(self) -> float: ...
+    def spot_size(self) -> float: ...
+    def beam_length(self) -> float: ...
+    def beam_width(self) -> float: ...
+    def hdr_scale(self) -> float: ...
+    def properties(self) -> Dict[str, str]: ...
+
+class ProjectedTexture:
+    def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def rotation(self) -> List[float]: ...
+    def color(self) -> List[float]: ...
+    def energy(self) -> float: ...
+    def fov(self) -> float: ...
+    def texture(self) -> Optional[str]: ...
+    def texture_ext(self) -> str: ...
+    def properties(self) -> Dict[str, str]: ...
+
 class EnvLight:
     def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def position(self) -> List[float]: ...
     def rotation(self) -> List[float]: ...
     def sun_color(self) -> List[float]: ...
@@ -217,19 +415,141 @@ class EnvLight:
     def angle(self) -> float: ...
     def properties(self) -> Dict[str, str]: ...
 
+class Sun:
+    def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def rotation(self) -> List[float]: ...
+    def color(self) -> List[float]: ...
+    def overlay_color(self) -> List[float]: ...
+    def size(self) -> float: ...
+    def overlay_size(self) -> float: ...
+    def hdr_scale(self) -> float: ...
+    def material(self) -> Optional[str]: ...
+    def properties(self) -> Dict[str, str]: ...
+
+class LightGlow:
+    def class_name(self) -> str: ...
+    def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def rotation(self) -> List[float]: ...
+    def color(self) -> List[float]: ...
+    def size(self) -> List[float]: ...
+    def hdr_scale(self) -> float: ...
+    def material(self) -> Optional[str]: ...
+    def properties(self) -> Dict[str, str]: ...
+
+class Smoke:
+    def class_name(self) -> str: ...
+    def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def rotation(self) -> List[float]: ...
+    def color(self) -> List[float]: ...
+    def start_size(self) -> float: ...
+    def end_size(self) -> float: ...
+    def length(self) -> float: ...
+    def speed(self) -> float: ...
+    def rate(self) -> float: ...
+    def properties(self) -> Dict[str, str]: ...
+
+class LightingOrigin:
+    def name(self) -> str: ...
+    def id(self) -> int: ...
+    def position(self) -> List[float]: ...
+
+class PlayerSpawn:
+    def class_name(self) -> str: ...
+    def id(self) -> int: ...
+    def position(self) -> List[float]: ...
+    def priority(self) -> int: ...
+
 class SkyCamera:
     def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def position(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
+    def fog_enabled(self) -> bool: ...
+    def fog_blend(self) -> bool: ...
+    def fog_color(self) -> List[float]: ...
+    def fog_color2(self) -> List[float]: ...
+    def fog_direction(self) -> List[float]: ...
+    def fog_start(self) -> float: ...
+    def fog_end(self) -> float: ...
+    def fog_max_density(self) -> float: ...
+
+class ColorCorrection:
+    def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def position(self) -> List[float]: ...
+    def lut_path(self) -> str: ...
+    def cube_path(self) -> str: ...
+    def weight(self) -> float: ...
+    def max_weight(self) -> float: ...
+    def min_falloff(self) -> float: ...
+    def max_falloff(self) -> float: ...
+    def properties(self) -> Dict[str, str]: ...
+
+class PathChain:
+    def id(self) -> int: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
+    def class_name(self) -> str: ...
+    def name(self) -> str: ...
+    def points(self) -> List[float]: ...
+    def closed(self) -> bool: ...
 
 class UnknownEntity:
     def class_name(self) -> str: ...
     def id(self) -> int: ...
+    def parent_id(self) -> Optional[int]: ...
+    def editor_groups(self) -> Optional[EditorGroups]: ...
     def position(self) -> List[float]: ...
     def rotation(self) -> List[float]: ...
     def scale(self) -> List[float]: ...
     def properties(self) -> Dict[str, str]: ...
 
+class Dependency:
+    def kind(self) -> str: ...
+    def name(self) -> str: ...
+    def dependency_kind(self) -> str: ...
+    def dependencies(self) -> List[str]: ...
+
+class Diagnostic:
+    def severity(self) -> str: ...
+    def asset_kind(self) -> str: ...
+    def asset_id(self) -> Optional[str]: ...
+    def text(self) -> str: ...
+
+class EditorGroups:
+    def class_name(self) -> Optional[str]: ...
+    def world(self) -> bool: ...
+    def visgroups(self) -> List[List[str]]: ...
+    def groups(self) -> List[int]: ...
+
+class Summary:
+    def asset_counts(self) -> Dict[str, int]: ...
+    def errors(self) -> int: ...
+    def warnings(self) -> int: ...
+    def cache_hits(self) -> int: ...
+    def stage_times(self) -> List[Tuple[str, float]]: ...
+    def total_time(self) -> float: ...
+
+class VmfDiff:
+    def added_solids(self) -> List[int]: ...
+    def changed_solids(self) -> List[int]: ...
+    def removed_solids(self) -> List[int]: ...
+    def added_entities(self) -> List[int]: ...
+    def changed_entities(self) -> List[int]: ...
+    def removed_entities(self) -> List[int]: ...
+    def world_changed(self) -> bool: ...
+    def world_solids_replaced(self) -> bool: ...
+    def is_empty(self) -> bool: ...
+
+class StrictImportError(RuntimeError): ...
+
 class Importer:
     def __init__(
         self,
@@ -242,9 +562,14 @@ class Importer:
     def import_mdl(self, path: str, from_game: bool, **kwargs) -> None: ...
     def import_vmt(self, path: str, from_game: bool) -> None: ...
     def import_vtf(self, path: str, from_game: bool) -> None: ...
+    def import_vmf_batch(self, paths: List[str], from_game: bool, **kwargs) -> None: ...
     def import_mdl_batch(self, paths: List[str], from_game: bool, **kwargs) -> None: ...
     def import_vmt_batch(self, paths: List[str], from_game: bool) -> None: ...
     def import_vtf_batch(self, paths: List[str], from_game: bool) -> None: ...
+    def import_nav(self, path: str, from_game: bool) -> None: ...
+    def import_ain(self, path: str, from_game: bool) -> None: ...
+    def import_goldsrc_bsp(self, path: str, from_game: bool) -> None: ...
+    def import_vmap(self, path: str, from_game: bool) -> None: ...
     def stage_mdl(self, path: str, **kwargs) -> None: ...
     def import_assets(self) -> None: ...
 
@@ -257,9 +582,14 @@ class ApiImporter:
         **kwargs
     ) -> None: ...
     def add_vmf_job(self, path: str, from_game: bool) -> None: ...
+    def add_vmf_diff_job(
+        self, old_path: str, new_path: str, from_game: bool
+    ) -> VmfDiff: ...
+    def add_vmf_data_job(self, name: str, data: bytes) -> None: ...
     def add_mdl_job(self, path: str, from_game: bool) -> None: ...
     def add_vmt_job(self, path: str, from_game: bool) -> None: ...
     def add_vtf_job(self, path: str, from_game: bool) -> None: ...
+    def add_sky_job(self, name: str) -> None: ...
     def execute_jobs(self) -> None: ...
     @property
     def job_count(self) -> int: ...
@@ -1,7 +1,14 @@
-from .plumber import discover_filesystems, FileSystem, filesystem_from_gameinfo
+from .plumber import (
+    discover_games,
+    FileSystem,
+    filesystem_from_gameinfo,
+    set_log_target,
+    set_profiling,
+)
 
 from typing import List, Set, Tuple
 from os.path import isdir
+import logging
 import os
 
 from bpy.types import (
@@ -33,6 +40,8 @@ class GameSearchPath(PropertyGroup):
             self.kind = "DIR"
         elif value.lower().endswith(".vpk"):
             self.kind = "VPK"
+        elif value.lower().endswith(".gma"):
+            self.kind = "GMA"
 
     path: StringProperty(
         subtype="FILE_PATH",
@@ -46,6 +55,7 @@ class GameSearchPath(PropertyGroup):
             ("DIR", "Directory", "", "FILE_FOLDER", 0),
             ("VPK", "VPK Archive", "", "PACKAGE", 1),
             ("WILDCARD", "Wildcard Directory", "", "FOLDER_REDIRECT", 2),
+            ("GMA", "Garry's Mod Addon", "", "FILE_ARCHIVE", 3),
         ),
         name="Type",
     )
@@ -177,9 +187,12 @@ class Game(PropertyGroup):
         self["name"] = value
 
     def get_file_system(self) -> FileSystem:
-        return FileSystem(
+        file_system = FileSystem(
             self.name, [(path.kind, path.path) for path in self.search_paths]
-        )
+        ).with_file_priority(self.file_priority)
+        if self.mount_workshop and self.workshop_path:
+            file_system = file_system.with_workshop(self.workshop_path)
+        return file_system
 
     name: StringProperty(
         name="Name",
@@ -191,6 +204,30 @@ class Game(PropertyGroup):
     search_paths: CollectionProperty(type=GameSearchPath)
     search_path_index: IntProperty(name="Search Path")
 
+    workshop_path: StringProperty(
+        name="Workshop content",
+        description="Directory containing the subscribed workshop items of the game",
+        subtype="DIR_PATH",
+        default="",
+    )
+
+    mount_workshop: BoolProperty(
+        name="Mount workshop content",
+        description="Make the files of subscribed workshop items available when importing",
+        default=False,
+    )
+
+    file_priority: EnumProperty(
+        name="File priority",
+        description="Which files are used when a file exists both as a loose file and inside a VPK",
+        items=[
+            ("DEFINED", "Search path order", "Use the order of the search paths"),
+            ("LOOSE", "Loose files", "Loose files override files in VPKs"),
+            ("ARCHIVES", "VPKs", "Files in VPKs override loose files"),
+        ],
+        default="DEFINED",
+    )
+
 
 class GameList(UIList):
     bl_idname = "PLUMBER_UL_game_list"
@@ -297,15 +334,14 @@ class DetectGamesOperator(Operator):
 def detect_games(context: Context):
     preferences: AddonPreferences = context.preferences.addons[__package__].preferences
 
-    filesystems = discover_filesystems()
-
-    for filesystem in filesystems:
-        name = filesystem.name()
+    for detected in discover_games():
+        name = detected.name()
         if any(name == game.name for game in preferences.games):
             continue
-        search_paths = filesystem.search_paths()
+        search_paths = detected.search_paths()
         game: Game = preferences.games.add()
         game.name = name
+        game.workshop_path = detected.workshop_path() or ""
         for kind, path in search_paths:
             search_path: GameSearchPath = game.search_paths.add()
             search_path.path = path
@@ -361,6 +397,57 @@ def detect_gameinfo(path: str, context: Context):
         search_path.kind = kind
 
 
+class MountGameinfoOperator(Operator):
+    """Mount another game from gameinfo.txt into the selected game, below its existing search paths"""
+
+    bl_idname = "plumber.mount_gameinfo"
+    bl_label = "Mount a game from gameinfo.txt"
+    bl_options = {"REGISTER"}
+
+    filepath: StringProperty(
+        name="Path",
+        maxlen=1024,
+        options={"HIDDEN"},
+    )
+
+    filename_ext = ".txt"
+
+    filter_glob: StringProperty(
+        default="*.txt",
+        options={"HIDDEN"},
+        maxlen=255,
+    )
+
+    @classmethod
+    def poll(cls, context: Context) -> bool:
+        return bool(context.preferences.addons[__package__].preferences.games)
+
+    def invoke(self, context: Context, event) -> Set[str]:
+        context.window_manager.fileselect_add(self)
+        return {"RUNNING_MODAL"}
+
+    def execute(self, context: Context) -> Set[str]:
+        try:
+            mount_gameinfo(self.filepath, context)
+        except (ValueError, OSError) as err:
+            self.report({"ERROR"}, f"could not mount gameinfo.txt: {err}")
+
+        return {"FINISHED"}
+
+
+def mount_gameinfo(path: str, context: Context):
+    preferences: AddonPreferences = context.preferences.addons[__package__].preferences
+    game: Game = preferences.games[preferences.game_index]
+
+    filesystem = game.get_file_system().mount(filesystem_from_gameinfo(path))
+
+    game.search_paths.clear()
+    for kind, path in filesystem.search_paths():
+        search_path: GameSearchPath = game.search_paths.add()
+        search_path.path = path
+        search_path.kind = kind
+
+
 class AddonPreferences(AddonPreferences):
     bl_idname = __package__
 
@@ -369,13 +456,29 @@ class AddonPreferences(AddonPreferences):
 
     threads: IntProperty(
         name="Importer threads",
-        description="Total amount of threads to use for importing, the default value is generally the best choice",
+        description="Total amount of threads to use for importing, including Blender's main thread. "
+        "0 uses all available cores. Lower this to limit CPU usage",
         min=0,
         max=64,
-        soft_min=1,
+        soft_min=0,
         soft_max=os.cpu_count(),
     )
 
+    channel_capacity: IntProperty(
+        name="Asset queue size",
+        description="Amount of processed assets that can wait to be imported "
+        "into Blender. The importer threads pause when it's full. "
+        "Lower this if importing large maps runs out of memory",
+        default=256,
+        min=1,
+        soft_max=1024,
+    )
+
+    def get_importer_threads(self) -> int:
+        """Get the amount of asset processing threads, leaving room for Blender's thread."""
+        threads = self.threads or os.cpu_count() or 2
+        return max(1, threads - 1)
+
     def update_enable_file_browser_panel(self, context: Context):
         from .tools import GameFileBrowserPanel
 
@@ -406,6 +509,28 @@ class AddonPreferences(AddonPreferences):
         update=update_enable_benchmarking,
     )
 
+    def update_log_to_python(self, context: Context):
+        set_log_target(logging.getLogger("plumber") if self.log_to_python else None)
+
+    log_to_python: BoolProperty(
+        name="Forward log to Python logging",
+        description="Forward the importer log to the \"plumber\" Python logger, "
+        "so scripts can capture it with logging handlers",
+        default=False,
+        update=update_log_to_python,
+    )
+
+    def update_enable_profiling(self, context: Context):
+        set_profiling(self.enable_profiling)
+
+    enable_profiling: BoolProperty(
+        name="Profile imports",
+        description="Print how long each asset processing stage and the slowest assets "
+        "took to console after importing",
+        default=False,
+        update=update_enable_profiling,
+    )
+
     @staticmethod
     def game_enum_items(
         self: EnumProperty, context: Context
@@ -425,7 +550,13 @@ class AddonPreferences(AddonPreferences):
         layout: UILayout = self.layout
         layout.prop(self, "enable_file_browser_panel")
         layout.prop(self, "enable_benchmarking")
-        layout.prop(self, "threads")
+        layout.prop(self, "log_to_python")
+        layout.prop(self, "enable_profiling")
+        row = layout.row()
+        row.prop(self, "threads")
+        if self.threads == 0:
+            row.label(text=f"Auto ({os.cpu_count() or 2} threads)")
+        layout.prop(self, "channel_capacity")
 
         layout.separator()
         row = layout.row()
@@ -472,6 +603,16 @@ class AddonPreferences(AddonPreferences):
             col.operator(
                 MoveGameSearchPathOperator.bl_idname, text="", icon="TRIA_DOWN"
             ).direction = "DOWN"
+            col.separator()
+            col.operator(MountGameinfoOperator.bl_idname, text="", icon="LINKED")
+
+            row = layout.row()
+            row.prop(game, "mount_workshop", text="")
+            sub = row.row()
+            sub.enabled = game.mount_workshop
+            sub.prop(game, "workshop_path")
+
+            layout.prop(game, "file_priority")
 
 
 class OpenPreferencesOperator(Operator):
@@ -499,6 +640,7 @@ classes = (
     MoveGameOperator,
     DetectGamesOperator,
     DetectGameinfoOperator,
+    MountGameinfoOperator,
     AddonPreferences,
     OpenPreferencesOperator,
 )
@@ -512,13 +654,17 @@ def register():
         __package__
     ].preferences
 
-    if preferences.threads == 0:
-        preferences.threads = max(2, os.cpu_count() or 0)
-
     if not preferences.games:
         detect_games(bpy.context)
 
+    if preferences.log_to_python:
+        preferences.update_log_to_python(bpy.context)
+
+    set_profiling(preferences.enable_profiling)
+
 
 def unregister():
+    set_log_target(None)
+
     for cls in reversed(classes):
         bpy.utils.unregister_class(cls)
@@ -56,7 +56,11 @@ FILE_IMPORTERS = {
     "mdl": "import_scene.plumber_mdl",
     "vmt": "import_scene.plumber_vmt",
     "vmf": "import_scene.plumber_vmf",
+    "map": "import_scene.plumber_vmf",
+    "rmf": "import_scene.plumber_vmf",
     "vtf": "import_scene.plumber_vtf",
+    "nav": "import_scene.plumber_nav",
+    "ain": "import_scene.plumber_ain",
 }
 
 
@@ -11,7 +11,7 @@ rust_extension = RustExtension(
     "plumber.plumber",
     binding=Binding.PyO3,
     py_limited_api=True,
-    features=["trace"],
+    features=["trace", "extension-module"],
     args=["--no-default-features", "--profile=trace"],
 )
 
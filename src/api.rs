@@ -1,4 +1,9 @@
-use std::{path::PathBuf as StdPathBuf, time::Instant};
+use std::{
+    cell::RefCell,
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
 use crossbeam_channel::Receiver;
 use pyo3::{
@@ -6,13 +11,12 @@ use pyo3::{
     prelude::*,
     types::PyDict,
 };
-use tracing::{error, info};
+use tracing::info;
 
 use plumber_core::{
-    asset_core::{AssetConfig, Context, Executor, NoError},
+    asset_core::Executor,
     asset_mdl::MdlConfig,
     asset_vmf::{BrushSetting, VmfConfig},
-    asset_vtf::VtfConfig,
     fs::{GamePathBuf, PathBuf},
     vmf::{
         builder::{GeometrySettings, InvisibleSolids, MergeSolids},
@@ -21,93 +25,72 @@ use plumber_core::{
 };
 
 use crate::{
-    asset::{material::MaterialConfig, BlenderAssetHandler, Message},
+    asset::{
+        anim_event::AnimationEvents,
+        diagnostic::PyDiagnostic,
+        ik_chain::IkChains,
+        jigglebone::JiggleBones,
+        job::{AssetImportJob, UnifiedAssetConfig},
+        material::MaterialConfig,
+        model_bounds::ModelBoundsStore,
+        prop_queue::PropQueue,
+        ragdoll::{physics_path, Ragdolls},
+        solid_repair::SolidRepair,
+        vmf_entities::VmfEntities,
+        vmf_load::{load_vmf_bytes, MapFormat, VmfLoadSettings},
+        vmf_metadata::VmfMetadata,
+        BlenderAssetHandler, Message,
+    },
     filesystem::PyFileSystem,
-    importer::{process_assets_with_callback, PyImporter},
+    importer::{process_assets_with_callback, strict_import_error, PyImporter},
+    vmf_diff::VmfDiff,
 };
 
-/// Unified asset config that can process mixed asset types
-#[derive(Debug, Clone, Copy)]
-pub struct UnifiedAssetConfig {
-    pub material_config: MaterialConfig,
-}
-
-impl AssetConfig<BlenderAssetHandler> for UnifiedAssetConfig {
-    type Input<'a> = AssetImportJob;
-    type Output<'a> = ();
-    type Error<'a> = NoError;
-
-    fn process<'a>(
-        self,
-        input: Self::Input<'a>,
-        context: &mut Context<BlenderAssetHandler>,
-    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
-        match input {
-            AssetImportJob::Vtf { path } => {
-                context.queue(VtfConfig, path);
-            }
-            AssetImportJob::Vmt { path } => {
-                context.queue(self.material_config, path);
-            }
-            AssetImportJob::Mdl { path, config } => {
-                context.queue(config, path);
-            }
-            AssetImportJob::Vmf { path, config } => {
-                // VMF files need special handling - read and parse first
-                if let Ok(bytes) = context.fs().read(&path) {
-                    if let Ok(vmf) = Vmf::from_bytes(&bytes) {
-                        context.queue(config, vmf);
-                    } else {
-                        error!("Failed to parse VMF file: {}", path);
-                    }
-                } else {
-                    error!("Failed to read VMF file: {}", path);
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
-/// Enum representing different types of assets that can be imported
-#[derive(Debug, Clone)]
-pub enum AssetImportJob {
-    Vmf {
-        path: PathBuf,
-        config: VmfConfig<MaterialConfig>,
-    },
-    Mdl {
-        path: PathBuf,
-        config: MdlConfig<MaterialConfig>,
-    },
-    Vmt {
-        path: PathBuf,
-    },
-    Vtf {
-        path: PathBuf,
-    },
-}
-
 /// Python wrapper for parallel import builder
 #[allow(clippy::struct_excessive_bools)]
 #[pyclass(module = "plumber", name = "ApiImporter")]
 pub struct PyApiImporter {
     material_config: MaterialConfig,
+    /// Config the import jobs are processed with.
+    unified_config: UnifiedAssetConfig,
     executor: Option<Executor<BlenderAssetHandler>>,
     receiver: Receiver<Message>,
     jobs: Vec<AssetImportJob>,
     callback_obj: PyObject,
+    file_system_time: Duration,
+    strict: bool,
+    failure: RefCell<Option<PyDiagnostic>>,
+    /// Models are only passed once across the imports of this importer.
+    props: RefCell<PropQueue>,
+    /// Vmf metadata stores of the handler, such as the lightmap scales brushes are built with.
+    vmf_metadata: VmfMetadata,
+    /// Entity data stores of the handler, such as the parents entities are sent with.
+    vmf_entities: VmfEntities,
+    /// Ragdolls of the handler, which models are sent with.
+    ragdolls: Arc<RwLock<Ragdolls>>,
+    /// Jiggle bones of the handler, which models are sent with.
+    jiggle_bones: Arc<RwLock<JiggleBones>>,
+    /// Ik chains of the handler, which models are sent with.
+    ik_chains: Arc<RwLock<IkChains>>,
+    /// Animation events of the handler, which models are sent with.
+    animation_events: Arc<RwLock<AnimationEvents>>,
+    /// Model positions and bounding boxes of the handler, which models are sent with.
+    model_bounds: Arc<RwLock<ModelBoundsStore>>,
     // VMF-specific settings
     vmf_import_brushes: bool,
     vmf_import_overlays: bool,
     vmf_epsilon: f32,
     vmf_cut_threshold: f32,
     vmf_merge_solids: MergeSolids,
+    vmf_separate_entity_solids: bool,
     vmf_invisible_solids: InvisibleSolids,
     vmf_import_props: bool,
     vmf_import_entities: bool,
     vmf_import_sky: bool,
+    vmf_import_radar: bool,
     vmf_scale: f32,
+    /// How the vmfs are changed before they are parsed.
+    vmf_load_settings: VmfLoadSettings,
     // MDL-specific settings
     mdl_import_animations: bool,
 }
@@ -134,9 +117,10 @@ impl PyApiImporter {
             .open()
             .map_err(|e| PyIOError::new_err(e.to_string()))?;
 
+        let file_system_time = start.elapsed();
         info!(
             "file system opened in {:.2} s",
-            start.elapsed().as_secs_f32()
+            file_system_time.as_secs_f32()
         );
 
         let settings = PyImporter::extract_importer_wide_settings(kwargs)?;
@@ -147,9 +131,24 @@ impl PyApiImporter {
         let material_config = MaterialConfig {
             settings: settings.material,
         };
+        let import_lights = settings.import_lights;
+        let strict = settings.strict;
+        let props = PropQueue::with_models(settings.existing_assets.iter().cloned());
 
-        let (sender, receiver) = crossbeam_channel::bounded(256);
-        let handler = BlenderAssetHandler { sender, settings };
+        let (sender, receiver) = crossbeam_channel::bounded(settings.channel_capacity);
+        let handler = BlenderAssetHandler::new(sender, settings);
+        let unified_config = UnifiedAssetConfig {
+            material_config,
+            import_lights,
+            cancelled: handler.cancelled(),
+        };
+        let vmf_metadata = handler.vmf_metadata();
+        let vmf_entities = handler.vmf_entities();
+        let ragdolls = handler.ragdolls();
+        let jiggle_bones = handler.jiggle_bones();
+        let ik_chains = handler.ik_chains();
+        let animation_events = handler.animation_events();
+        let model_bounds = handler.model_bounds();
         let executor = Some(Executor::new_with_threads(
             handler,
             opened,
@@ -158,43 +157,47 @@ impl PyApiImporter {
 
         Ok(Self {
             material_config,
+            unified_config,
             executor,
             receiver,
             jobs: Vec::new(),
             callback_obj,
+            file_system_time,
+            strict,
+            failure: RefCell::new(None),
+            props: RefCell::new(props),
+            vmf_metadata,
+            vmf_entities,
+            ragdolls,
+            jiggle_bones,
+            ik_chains,
+            animation_events,
+            model_bounds,
             vmf_import_brushes: vmf_settings.import_brushes,
             vmf_import_overlays: vmf_settings.import_overlays,
             vmf_epsilon: vmf_settings.epsilon,
             vmf_cut_threshold: vmf_settings.cut_threshold,
             vmf_merge_solids: vmf_settings.merge_solids,
+            vmf_separate_entity_solids: vmf_settings.separate_entity_solids,
             vmf_invisible_solids: vmf_settings.invisible_solids,
             vmf_import_props: vmf_settings.import_props,
             vmf_import_entities: vmf_settings.import_other_entities,
             vmf_import_sky: vmf_settings.import_skybox,
+            vmf_import_radar: vmf_settings.import_radar,
             vmf_scale: vmf_settings.scale,
+            vmf_load_settings: vmf_settings.load,
             mdl_import_animations,
         })
     }
 
     fn add_vmf_job(&mut self, path: &str, from_game: bool) {
-        let mut geometry_settings = GeometrySettings::default();
-        geometry_settings.epsilon(self.vmf_epsilon);
-        geometry_settings.cut_threshold(self.vmf_cut_threshold);
-        geometry_settings.merge_solids(self.vmf_merge_solids);
-        geometry_settings.invisible_solids(self.vmf_invisible_solids);
-
-        let mut settings = VmfConfig::new(self.material_config);
-        settings.import_overlays = self.vmf_import_overlays;
-        settings.import_props = self.vmf_import_props;
-        settings.import_other_entities = self.vmf_import_entities;
-        settings.import_skybox = self.vmf_import_sky;
-        settings.scale = self.vmf_scale;
-
-        settings.brushes = if self.vmf_import_brushes {
-            BrushSetting::Import(geometry_settings)
-        } else {
-            BrushSetting::Skip
-        };
+        if self.vmf_import_radar {
+            if let Some(map_name) = StdPath::new(path).file_stem() {
+                self.jobs.push(AssetImportJob::Radar {
+                    map_name: map_name.to_string_lossy().into_owned(),
+                });
+            }
+        }
 
         let path: PathBuf = if from_game {
             GamePathBuf::from(path).into()
@@ -204,14 +207,93 @@ impl PyApiImporter {
 
         self.jobs.push(AssetImportJob::Vmf {
             path,
-            config: settings,
+            config: self.vmf_config(),
+            load_settings: self.vmf_load_settings,
+            vmf_metadata: self.vmf_metadata.clone(),
+            vmf_entities: self.vmf_entities.clone(),
+            separate_entity_solids: self.vmf_separate_entity_solids,
+        });
+    }
+
+    /// Adds a job importing a vmf from its content instead of a file,
+    /// with `name` identifying it in the log.
+    fn add_vmf_data_job(&mut self, name: &str, data: &[u8]) -> PyResult<()> {
+        info!("reading vmf `{}` from memory...", name);
+        let (vmf, repairs) = self.load_vmf(data)?;
+        self.vmf_entities.add_solid_repairs(repairs);
+
+        self.jobs.push(AssetImportJob::LoadedVmf {
+            vmf,
+            config: self.vmf_config(),
+            separate_entity_solids: self.vmf_separate_entity_solids,
+        });
+
+        Ok(())
+    }
+
+    /// Adds a job importing only the solids and entities of `new_path` which were added or
+    /// changed since `old_path`. The returned diff also lists the removed ids.
+    fn add_vmf_diff_job(
+        &mut self,
+        old_path: &str,
+        new_path: &str,
+        from_game: bool,
+    ) -> PyResult<VmfDiff> {
+        // the ids of vmfs read before would offset the ones of the new vmf,
+        // which need to match the objects imported from the old vmf
+        if self
+            .jobs
+            .iter()
+            .any(|job| matches!(job, AssetImportJob::LoadedVmf { .. }))
+        {
+            return Err(PyRuntimeError::new_err(
+                "vmf diffs can't be imported after other vmfs read into the same importer",
+            ));
+        }
+
+        // the old vmf is only compared against, so it's not read into the stores
+        let (old, _) = self.read_vmf(
+            old_path,
+            from_game,
+            &VmfMetadata::default(),
+            &VmfEntities::default(),
+        )?;
+        let (mut new, repairs) =
+            self.read_vmf(new_path, from_game, &self.vmf_metadata, &self.vmf_entities)?;
+        self.vmf_entities.add_solid_repairs(repairs);
+
+        let mut diff = VmfDiff::new(&old, &new);
+        info!(
+            "vmf `{}` changed {} solids and {} entities, removed {} solids and {} entities",
+            new_path,
+            diff.solids.added.len() + diff.solids.changed.len(),
+            diff.entities.added.len() + diff.entities.changed.len(),
+            diff.solids.removed.len(),
+            diff.entities.removed.len(),
+        );
+
+        let merged_world = matches!(self.vmf_merge_solids, MergeSolids::Merge);
+        diff.retain_changes(&mut new, merged_world);
+
+        let mut config = self.vmf_config();
+        // the sky is only imported again if it could have changed
+        config.import_skybox &= diff.world_changed;
+
+        self.jobs.push(AssetImportJob::LoadedVmf {
+            vmf: new,
+            config,
+            separate_entity_solids: self.vmf_separate_entity_solids,
         });
+
+        Ok(diff)
     }
 
     fn add_mdl_job(&mut self, path: &str, from_game: bool) {
         let mut settings = MdlConfig::new(self.material_config);
         settings.import_animations = self.mdl_import_animations;
 
+        let physics = physics_path(path, from_game);
+
         let path = if from_game {
             GamePathBuf::from(path).into()
         } else {
@@ -220,7 +302,13 @@ impl PyApiImporter {
 
         self.jobs.push(AssetImportJob::Mdl {
             path,
+            physics,
             config: settings,
+            ragdolls: self.ragdolls.clone(),
+            jiggle_bones: self.jiggle_bones.clone(),
+            ik_chains: self.ik_chains.clone(),
+            animation_events: self.animation_events.clone(),
+            model_bounds: self.model_bounds.clone(),
         });
     }
 
@@ -244,6 +332,14 @@ impl PyApiImporter {
         self.jobs.push(AssetImportJob::Vtf { path });
     }
 
+    /// Adds a job importing the skybox named like the `skyname` of a map
+    /// as an equirectangular sky.
+    fn add_sky_job(&mut self, name: &str) {
+        self.jobs.push(AssetImportJob::Sky {
+            name: GamePathBuf::from(name),
+        });
+    }
+
     fn execute_jobs(&mut self, py: Python) -> PyResult<()> {
         if self.jobs.is_empty() {
             return Ok(());
@@ -253,15 +349,17 @@ impl PyApiImporter {
         let start = Instant::now();
         info!("executing {} import jobs in parallel...", self.jobs.len());
 
-        let unified_config = UnifiedAssetConfig {
-            material_config: self.material_config,
-        };
-
         let jobs: Vec<AssetImportJob> = self.jobs.drain(..).collect();
-        executor.process_each(unified_config, jobs, || self.process_assets(py));
+        executor.process_each(self.unified_config.clone(), jobs, || {
+            self.process_assets(py)
+        });
 
         info!("jobs executed in {:.2} s", start.elapsed().as_secs_f32());
-        Ok(())
+
+        match self.failure.take() {
+            Some(diagnostic) => Err(strict_import_error(diagnostic)),
+            None => Ok(()),
+        }
     }
 
     #[getter]
@@ -271,6 +369,73 @@ impl PyApiImporter {
 }
 
 impl PyApiImporter {
+    fn vmf_config(&self) -> VmfConfig<MaterialConfig> {
+        let mut geometry_settings = GeometrySettings::default();
+        geometry_settings.epsilon(self.vmf_epsilon);
+        geometry_settings.cut_threshold(self.vmf_cut_threshold);
+        geometry_settings.merge_solids(self.vmf_merge_solids);
+        geometry_settings.invisible_solids(self.vmf_invisible_solids);
+
+        let mut settings = VmfConfig::new(self.material_config);
+        settings.import_overlays = self.vmf_import_overlays;
+        settings.import_props = self.vmf_import_props;
+        settings.import_other_entities = self.vmf_import_entities;
+        settings.import_skybox = self.vmf_import_sky;
+        settings.scale = self.vmf_scale;
+
+        settings.brushes = if self.vmf_import_brushes {
+            BrushSetting::Import(geometry_settings)
+        } else {
+            BrushSetting::Skip
+        };
+
+        settings
+    }
+
+    /// Reads a vmf into the stores along with the solids which were repaired while reading it.
+    fn read_vmf(
+        &self,
+        path: &str,
+        from_game: bool,
+        vmf_metadata: &VmfMetadata,
+        vmf_entities: &VmfEntities,
+    ) -> PyResult<(Vmf, Vec<SolidRepair>)> {
+        let executor = self
+            .executor
+            .as_ref()
+            .ok_or_else(|| PyRuntimeError::new_err("Importer already consumed"))?;
+
+        let format = MapFormat::from_path(path);
+
+        let path: PathBuf = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let bytes = executor.fs().read(&path)?;
+        load_vmf_bytes(
+            &bytes,
+            format,
+            &self.vmf_load_settings,
+            vmf_metadata,
+            vmf_entities,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    /// Parses a vmf along with the solids which were repaired while parsing it.
+    fn load_vmf(&self, bytes: &[u8]) -> PyResult<(Vmf, Vec<SolidRepair>)> {
+        load_vmf_bytes(
+            bytes,
+            MapFormat::Vmf,
+            &self.vmf_load_settings,
+            &self.vmf_metadata,
+            &self.vmf_entities,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
     fn consume(&mut self) -> PyResult<Executor<BlenderAssetHandler>> {
         self.executor
             .take()
@@ -278,6 +443,16 @@ impl PyApiImporter {
     }
 
     fn process_assets(&self, py: Python) {
-        process_assets_with_callback(py, self.callback_obj.as_ref(py), &self.receiver);
+        let failure = process_assets_with_callback(
+            py,
+            self.callback_obj.as_ref(py),
+            &self.receiver,
+            &mut self.props.borrow_mut(),
+            self.file_system_time,
+            self.strict,
+            &self.unified_config.cancelled,
+        );
+
+        self.failure.replace(failure);
     }
 }
@@ -0,0 +1,357 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io, mem,
+};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::PathBuf,
+};
+
+use super::{
+    binary::{Eof, Reader},
+    up_axis::UpAxis,
+    BlenderAssetHandler,
+};
+
+const AIN_VERSION: i32 = 37;
+const NUM_HULLS: usize = 10;
+
+/// Size of a node: origin, yaw, hull offsets, type, info flags and zone.
+const NODE_SIZE: usize = 16 + NUM_HULLS * 4 + 1 + 2 + 2;
+/// Size of a link: source, destination and allowed moves.
+const LINK_SIZE: usize = 4 + NUM_HULLS;
+
+#[derive(Debug)]
+pub enum AinError {
+    Io(PathBuf, io::Error),
+    UnsupportedVersion(PathBuf, i32),
+    Corrupted(PathBuf),
+}
+
+impl Display for AinError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "node graph `{path}`: {err}"),
+            Self::UnsupportedVersion(path, version) => {
+                write!(f, "node graph `{path}`: unsupported version {version}")
+            }
+            Self::Corrupted(path) => write!(f, "node graph `{path}`: unexpected end of file"),
+        }
+    }
+}
+
+impl Error for AinError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeType {
+    Any,
+    Deleted,
+    Ground,
+    Air,
+    Climb,
+    Water,
+}
+
+impl NodeType {
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            1 => Self::Deleted,
+            2 => Self::Ground,
+            3 => Self::Air,
+            4 => Self::Climb,
+            5 => Self::Water,
+            _ => Self::Any,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Deleted => "deleted",
+            Self::Ground => "ground",
+            Self::Air => "air",
+            Self::Climb => "climb",
+            Self::Water => "water",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiNode {
+    pub origin: [f32; 3],
+    pub yaw: f32,
+    pub node_type: NodeType,
+    pub zone: i16,
+    /// Id of the entity this node was compiled from in Hammer.
+    pub hammer_id: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeGraph {
+    pub name: String,
+    pub nodes: Vec<AiNode>,
+    pub links: Vec<[usize; 2]>,
+}
+
+/// Loads a compiled `.ain` AI node graph.
+#[derive(Debug, Clone, Copy)]
+pub struct AinConfig;
+
+impl AssetConfig<BlenderAssetHandler> for AinConfig {
+    type Input<'a> = PathBuf;
+    type Output<'a> = NodeGraph;
+    type Error<'a> = AinError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let bytes = context
+            .fs()
+            .read(&input)
+            .map_err(|e| AinError::Io(input.clone(), e))?;
+
+        match parse_node_graph(&bytes) {
+            Ok((nodes, links)) => Ok(NodeGraph {
+                name: input.to_string(),
+                nodes,
+                links,
+            }),
+            Err(ParseError::UnsupportedVersion(version)) => {
+                Err(AinError::UnsupportedVersion(input, version))
+            }
+            Err(ParseError::Eof) => Err(AinError::Corrupted(input)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    UnsupportedVersion(i32),
+    Eof,
+}
+
+impl From<Eof> for ParseError {
+    fn from(_: Eof) -> Self {
+        Self::Eof
+    }
+}
+
+type Graph = (Vec<AiNode>, Vec<[usize; 2]>);
+
+fn parse_node_graph(bytes: &[u8]) -> Result<Graph, ParseError> {
+    let mut reader = Reader::new(bytes);
+
+    let version = reader.i32()?;
+
+    if version != AIN_VERSION {
+        return Err(ParseError::UnsupportedVersion(version));
+    }
+
+    // map version
+    reader.i32()?;
+
+    let node_count = usize::try_from(reader.i32()?).unwrap_or_default();
+    let mut nodes = Vec::with_capacity(node_count.min(reader.records_left(NODE_SIZE)));
+
+    for _ in 0..node_count {
+        let origin = [reader.f32()?, reader.f32()?, reader.f32()?];
+        let yaw = reader.f32()?;
+        // per hull offsets
+        reader.skip(NUM_HULLS * 4)?;
+        let node_type = NodeType::from_byte(reader.u8()?);
+        // node info flags
+        reader.skip(2)?;
+        let zone = reader.i16()?;
+
+        nodes.push(AiNode {
+            origin,
+            yaw,
+            node_type,
+            zone,
+            hammer_id: -1,
+        });
+    }
+
+    let link_count = usize::try_from(reader.i32()?).unwrap_or_default();
+    let mut links = Vec::with_capacity(link_count.min(reader.records_left(LINK_SIZE)));
+
+    for _ in 0..link_count {
+        let source = usize::try_from(reader.i16()?);
+        let destination = usize::try_from(reader.i16()?);
+        // allowed moves per hull
+        reader.skip(NUM_HULLS)?;
+
+        if let (Ok(source), Ok(destination)) = (source, destination) {
+            if source < node_count && destination < node_count {
+                links.push([source, destination]);
+            }
+        }
+    }
+
+    // the hammer id lookup table is missing from some older graphs
+    for node in &mut nodes {
+        match reader.i32() {
+            Ok(id) => node.hammer_id = id,
+            Err(_) => break,
+        }
+    }
+
+    Ok((nodes, links))
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "NodeGraph"))]
+pub struct PyNodeGraph {
+    pub name: String,
+    scale: [f32; 3],
+    flat_vertices: Vec<f32>,
+    flat_edge_vertice_indices: Vec<usize>,
+    yaws: Vec<f32>,
+    node_types: Vec<&'static str>,
+    zones: Vec<i16>,
+    hammer_ids: Vec<i32>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyNodeGraph {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scale(&self) -> [f32; 3] {
+        self.scale
+    }
+
+    pub fn vertices(&mut self) -> Vec<f32> {
+        mem::take(&mut self.flat_vertices)
+    }
+
+    pub fn edge_vertices(&mut self) -> Vec<usize> {
+        mem::take(&mut self.flat_edge_vertice_indices)
+    }
+
+    pub fn yaws(&mut self) -> Vec<f32> {
+        mem::take(&mut self.yaws)
+    }
+
+    pub fn node_types(&mut self) -> Vec<&'static str> {
+        mem::take(&mut self.node_types)
+    }
+
+    pub fn zones(&mut self) -> Vec<i16> {
+        mem::take(&mut self.zones)
+    }
+
+    pub fn hammer_ids(&mut self) -> Vec<i32> {
+        mem::take(&mut self.hammer_ids)
+    }
+}
+
+impl PyNodeGraph {
+    pub fn new(graph: NodeGraph, scale: f32) -> Self {
+        Self {
+            name: graph.name,
+            scale: [scale, scale, scale],
+            flat_vertices: graph.nodes.iter().flat_map(|n| n.origin).collect(),
+            flat_edge_vertice_indices: graph.links.into_iter().flatten().collect(),
+            yaws: graph.nodes.iter().map(|n| n.yaw.to_radians()).collect(),
+            node_types: graph.nodes.iter().map(|n| n.node_type.name()).collect(),
+            zones: graph.nodes.iter().map(|n| n.zone).collect(),
+            hammer_ids: graph.nodes.iter().map(|n| n.hammer_id).collect(),
+        }
+    }
+
+    /// Converts the node positions, the yaws stay around the up axis.
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.scale = up_axis.scale(self.scale);
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_node(out: &mut Vec<u8>, origin: [f32; 3], node_type: u8) {
+        for v in origin {
+            out.extend(v.to_le_bytes());
+        }
+        // yaw and hull offsets
+        out.extend([0; 4 + NUM_HULLS * 4]);
+        out.push(node_type);
+        // node info and zone
+        out.extend(0_u16.to_le_bytes());
+        out.extend(3_i16.to_le_bytes());
+    }
+
+    fn write_graph(with_hammer_ids: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend(AIN_VERSION.to_le_bytes());
+        out.extend(1_i32.to_le_bytes());
+
+        out.extend(2_i32.to_le_bytes());
+        write_node(&mut out, [0.0, 0.0, 0.0], 2);
+        write_node(&mut out, [64.0, 0.0, 0.0], 3);
+
+        out.extend(1_i32.to_le_bytes());
+        out.extend(0_i16.to_le_bytes());
+        out.extend(1_i16.to_le_bytes());
+        out.extend([0; NUM_HULLS]);
+
+        if with_hammer_ids {
+            out.extend(10_i32.to_le_bytes());
+            out.extend(11_i32.to_le_bytes());
+        }
+
+        out
+    }
+
+    #[test]
+    fn parse_node_graph_v37() {
+        let (nodes, links) = parse_node_graph(&write_graph(true)).unwrap();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1].origin, [64.0, 0.0, 0.0]);
+        assert_eq!(nodes[1].node_type, NodeType::Air);
+        assert_eq!(nodes[0].zone, 3);
+        assert_eq!(nodes[1].hammer_id, 11);
+        assert_eq!(links, vec![[0, 1]]);
+    }
+
+    #[test]
+    fn parse_node_graph_without_hammer_ids() {
+        let (nodes, _) = parse_node_graph(&write_graph(false)).unwrap();
+
+        assert_eq!(nodes[0].hammer_id, -1);
+    }
+
+    #[test]
+    fn parse_node_graph_unsupported_version() {
+        assert_eq!(
+            parse_node_graph(&36_i32.to_le_bytes()),
+            Err(ParseError::UnsupportedVersion(36))
+        );
+    }
+
+    #[test]
+    fn parse_node_graph_corrupted_count() {
+        let mut node = Vec::new();
+        write_node(&mut node, [0.0, 0.0, 0.0], 2);
+        assert_eq!(node.len(), NODE_SIZE);
+
+        let mut out = Vec::new();
+        out.extend(AIN_VERSION.to_le_bytes());
+        out.extend(1_i32.to_le_bytes());
+        out.extend(i32::MAX.to_le_bytes());
+        out.extend(node);
+
+        assert_eq!(parse_node_graph(&out), Err(ParseError::Eof));
+    }
+}
@@ -0,0 +1,302 @@
+//! Animation events of the sequences of models, read from the mdl.
+//!
+//! Sequences trigger events such as footstep sounds and muzzle flashes at a point of their
+//! cycle. Events are placed on the frames of the animation the sequence plays, so they can
+//! be lined up with the imported animations.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::mdl::{model_key, read_string, record_offsets};
+
+/// Offset of the animation count in the mdl header, followed by the offset of the animations
+/// and the count and offset of the sequences.
+const ANIMATION_COUNT_OFFSET: usize = 180;
+
+const ANIMATION_SIZE: usize = 100;
+
+const SEQUENCE_SIZE: usize = 212;
+
+const EVENT_SIZE: usize = 80;
+
+/// Size of the buffer the options of an event are stored in.
+const OPTIONS_SIZE: usize = 64;
+
+/// Events with this type are named, older events only have a number.
+const NEW_EVENT_STYLE: i32 = 1 << 10;
+
+/// An event of a sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvent {
+    sequence: String,
+    /// Name of the animation the sequence plays.
+    animation: String,
+    /// Frame of the animation, counted from zero at the frame rate of the animation.
+    frame: f32,
+    fps: f32,
+    name: String,
+    options: String,
+}
+
+struct AnimationInfo {
+    name: String,
+    fps: f32,
+    frame_count: i32,
+}
+
+/// Returns the events of the sequences of an mdl.
+pub fn read_animation_events(bytes: &[u8]) -> Vec<AnimationEvent> {
+    let read = |offset: usize| -> Option<[u8; 4]> {
+        bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()
+    };
+    let read_int = |offset| read(offset).map(i32::from_le_bytes);
+    let read_float = |offset| read(offset).map(f32::from_le_bytes);
+    let read_index = |offset| usize::try_from(read_int(offset)?).ok();
+    let relative = |base: usize, offset| read_index(base + offset)?.checked_add(base);
+
+    // older models have differently sized animations and sequences
+    if bytes.get(..4) != Some(b"IDST") || read_int(4).unwrap_or_default() < 44 {
+        return Vec::new();
+    }
+
+    let list = |offset: usize, size: usize| -> Vec<usize> {
+        match (read_index(offset), read_index(offset + 4)) {
+            (Some(count), Some(start)) => record_offsets(bytes, count, start, size).collect(),
+            _ => Vec::new(),
+        }
+    };
+
+    let animations: Vec<Option<AnimationInfo>> = list(ANIMATION_COUNT_OFFSET, ANIMATION_SIZE)
+        .into_iter()
+        .map(|animation| {
+            Some(AnimationInfo {
+                name: read_string(bytes, relative(animation, 4)?)?,
+                fps: read_float(animation + 8)?,
+                frame_count: read_int(animation + 16)?,
+            })
+        })
+        .collect();
+
+    let mut events = Vec::new();
+
+    for sequence in list(ANIMATION_COUNT_OFFSET + 8, SEQUENCE_SIZE) {
+        let (Some(sequence_name), Some(event_count), Some(first_event), Some(animation)) = (
+            relative(sequence, 4).and_then(|offset| read_string(bytes, offset)),
+            read_index(sequence + 24),
+            relative(sequence, 28),
+            // the first animation of the blends of the sequence
+            relative(sequence, 60)
+                .and_then(|offset| bytes.get(offset..offset + 2))
+                .and_then(|b| usize::try_from(i16::from_le_bytes([b[0], b[1]])).ok())
+                .and_then(|index| animations.get(index)?.as_ref()),
+        ) else {
+            continue;
+        };
+
+        for index in 0..event_count {
+            let Some(event) = first_event.checked_add(index * EVENT_SIZE) else {
+                break;
+            };
+
+            let (Some(cycle), Some(number), Some(kind), Some(options)) = (
+                read_float(event),
+                read_int(event + 4),
+                read_int(event + 8),
+                bytes.get(event + 12..event + 12 + OPTIONS_SIZE),
+            ) else {
+                break;
+            };
+
+            let name = if kind & NEW_EVENT_STYLE == 0 {
+                None
+            } else {
+                relative(event, 76).and_then(|offset| read_string(bytes, offset))
+            };
+
+            events.push(AnimationEvent {
+                sequence: sequence_name.clone(),
+                animation: animation.name.clone(),
+                frame: cycle * (animation.frame_count - 1).max(0) as f32,
+                fps: animation.fps,
+                name: name
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| number.to_string()),
+                options: String::from_utf8_lossy(
+                    &options[..options.iter().position(|&b| b == 0).unwrap_or(OPTIONS_SIZE)],
+                )
+                .into_owned(),
+            });
+        }
+    }
+
+    events
+}
+
+/// Animation events of the imported models, by their file name.
+#[derive(Debug, Default)]
+pub struct AnimationEvents {
+    models: HashMap<String, Vec<AnimationEvent>>,
+}
+
+impl AnimationEvents {
+    pub fn insert(&mut self, model: &str, events: Vec<AnimationEvent>) {
+        if !events.is_empty() {
+            self.models.insert(model_key(model), events);
+        }
+    }
+
+    pub fn get(&self, model: &str) -> &[AnimationEvent] {
+        self.models
+            .get(&model_key(model))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "AnimationEvent")
+)]
+#[derive(Debug, Clone)]
+pub struct PyAnimationEvent {
+    sequence: String,
+    animation: String,
+    frame: f32,
+    name: String,
+    options: String,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyAnimationEvent {
+    pub fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    /// Name of the animation the sequence plays.
+    pub fn animation(&self) -> &str {
+        &self.animation
+    }
+
+    /// Frame of the imported animation the event is triggered on.
+    pub fn frame(&self) -> f32 {
+        self.frame
+    }
+
+    /// Name of the event, or its number for old events without a name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn options(&self) -> &str {
+        &self.options
+    }
+}
+
+impl PyAnimationEvent {
+    /// Converts the events to the frames of the imported animations.
+    pub fn from_events(events: &[AnimationEvent], target_fps: f32) -> Vec<Self> {
+        events
+            .iter()
+            .map(|event| {
+                let time_factor = if event.fps > 0.0 {
+                    target_fps / event.fps
+                } else {
+                    1.0
+                };
+
+                Self {
+                    sequence: event.sequence.clone(),
+                    animation: event.animation.clone(),
+                    // imported animations start from frame 1
+                    frame: event.frame * time_factor + 1.0,
+                    name: event.name.clone(),
+                    options: event.options.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_i32(bytes: &mut [u8], offset: usize, value: i32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_f32(bytes: &mut [u8], offset: usize, value: f32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_offset(bytes: &mut [u8], offset: usize, from: usize, to: usize) {
+        put_i32(bytes, offset, i32::try_from(to - from).unwrap());
+    }
+
+    fn put_str(bytes: &mut [u8], offset: usize, text: &str) {
+        bytes[offset..offset + text.len()].copy_from_slice(text.as_bytes());
+    }
+
+    #[test]
+    fn reads_sequence_events() {
+        let animation = 408;
+        let sequence = animation + ANIMATION_SIZE;
+        let events = sequence + SEQUENCE_SIZE;
+        let blends = events + 2 * EVENT_SIZE;
+        let strings = blends + 4;
+
+        let mut mdl = vec![0; strings + 64];
+        put_str(&mut mdl, 0, "IDST");
+        put_i32(&mut mdl, 4, 48);
+        put_i32(&mut mdl, ANIMATION_COUNT_OFFSET, 1);
+        put_offset(&mut mdl, ANIMATION_COUNT_OFFSET + 4, 0, animation);
+        put_i32(&mut mdl, ANIMATION_COUNT_OFFSET + 8, 1);
+        put_offset(&mut mdl, ANIMATION_COUNT_OFFSET + 12, 0, sequence);
+
+        put_str(&mut mdl, strings, "@run");
+        put_offset(&mut mdl, animation + 4, animation, strings);
+        put_f32(&mut mdl, animation + 8, 30.0);
+        put_i32(&mut mdl, animation + 16, 21);
+
+        put_str(&mut mdl, strings + 8, "run");
+        put_offset(&mut mdl, sequence + 4, sequence, strings + 8);
+        put_i32(&mut mdl, sequence + 24, 2);
+        put_offset(&mut mdl, sequence + 28, sequence, events);
+        put_offset(&mut mdl, sequence + 60, sequence, blends);
+
+        // a named footstep and an old numbered sound event
+        put_f32(&mut mdl, events, 0.5);
+        put_i32(&mut mdl, events + 8, NEW_EVENT_STYLE);
+        put_str(&mut mdl, events + 12, "left");
+        put_str(&mut mdl, strings + 16, "AE_NPC_LEFTFOOT");
+        put_offset(&mut mdl, events + 76, events, strings + 16);
+
+        let event = events + EVENT_SIZE;
+        put_f32(&mut mdl, event, 1.0);
+        put_i32(&mut mdl, event + 4, 5004);
+        put_str(&mut mdl, event + 12, "Weapon.Reload");
+
+        let read = read_animation_events(&mdl);
+
+        let event = |frame, name: &str, options: &str| AnimationEvent {
+            sequence: "run".to_owned(),
+            animation: "@run".to_owned(),
+            frame,
+            fps: 30.0,
+            name: name.to_owned(),
+            options: options.to_owned(),
+        };
+
+        assert_eq!(
+            read,
+            [
+                event(10.0, "AE_NPC_LEFTFOOT", "left"),
+                event(20.0, "5004", "Weapon.Reload"),
+            ]
+        );
+
+        let converted = PyAnimationEvent::from_events(&read, 60.0);
+        assert!((converted[0].frame - 21.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,109 @@
+//! Reading of the little endian binary files which `plumber_core` doesn't load,
+//! shared by their parsers.
+
+/// The file ended before a value could be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eof;
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the bytes which haven't been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns how many records of `size` bytes are left, which limits the untrusted counts.
+    pub fn records_left(&self, size: usize) -> usize {
+        self.bytes.len() / size
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Eof> {
+        if self.bytes.len() < len {
+            return Err(Eof);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    pub fn take<const N: usize>(&mut self) -> Result<[u8; N], Eof> {
+        self.bytes(N).map(|taken| {
+            taken
+                .try_into()
+                .expect("slice should have the correct length")
+        })
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), Eof> {
+        self.bytes(len).map(drop)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Eof> {
+        self.take::<1>().map(|b| b[0])
+    }
+
+    pub fn i16(&mut self) -> Result<i16, Eof> {
+        self.take().map(i16::from_le_bytes)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Eof> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    pub fn i32(&mut self) -> Result<i32, Eof> {
+        self.take().map(i32::from_le_bytes)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Eof> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, Eof> {
+        self.take().map(f32::from_le_bytes)
+    }
+
+    pub fn vector(&mut self) -> Result<[f32; 3], Eof> {
+        Ok([self.f32()?, self.f32()?, self.f32()?])
+    }
+
+    /// A string prefixed by its length as a byte, including the terminating null.
+    pub fn nstring(&mut self) -> Result<String, Eof> {
+        let len = self.u8()?;
+        self.fixed_string(usize::from(len))
+    }
+
+    /// A null terminated string in a field of `len` bytes.
+    pub fn fixed_string(&mut self, len: usize) -> Result<String, Eof> {
+        self.bytes(len).map(null_terminated)
+    }
+}
+
+fn null_terminated(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_values_until_the_end() {
+        let mut reader = Reader::new(&[3, b'a', b'b', 0, 1, 0, 0, 0, 7]);
+
+        assert_eq!(reader.nstring(), Ok("ab".to_string()));
+        assert_eq!(reader.i32(), Ok(1));
+        assert_eq!(reader.records_left(1), 1);
+        assert_eq!(reader.u16(), Err(Eof));
+        assert_eq!(reader.u8(), Ok(7));
+    }
+}
@@ -0,0 +1,233 @@
+//! Filtering a vmf down to the solids and entities within a box before it's parsed,
+//! for importing a part of a huge map along with everything inside it.
+
+use std::borrow::Cow;
+
+use glam::Vec3;
+use tracing::info;
+
+use super::{
+    entities::parse_vector,
+    keyvalues::{
+        find_value, indentation, parse_block, parse_plane_points, KeyValue, Token, Tokenizer,
+    },
+};
+
+/// An axis aligned box in Source units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    /// Creates the box between two opposite corners, in any order.
+    pub fn new(a: [f32; 3], b: [f32; 3]) -> Self {
+        let a = Vec3::from(a);
+        let b = Vec3::from(b);
+
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn contains(self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(self, min: Vec3, max: Vec3) -> bool {
+        max.cmpge(self.min).all() && min.cmple(self.max).all()
+    }
+}
+
+/// Returns the vmf without the world solids and entities outside of the bounds.
+/// Solids are kept if they touch the bounds, brush entities if any of their solids do
+/// and point entities if their origin is inside. Entities without either are always kept.
+pub fn filter_bounds(bytes: &[u8], bounds: Bounds) -> Cow<[u8]> {
+    let mut output = Vec::new();
+    let mut copied = 0;
+    let mut removed_solids = 0;
+    let mut removed_entities = 0;
+
+    let mut tokens = Tokenizer::new(bytes);
+    let mut previous: Option<(usize, &[u8])> = None;
+    // names of the blocks the current token is in
+    let mut blocks: Vec<&[u8]> = Vec::new();
+
+    while let Some((start, token)) = tokens.next_token() {
+        match token {
+            Token::Open => {
+                let (key_start, key) = previous.take().unwrap_or((start, b""));
+
+                let is_world_solid = key.eq_ignore_ascii_case(b"solid")
+                    && matches!(blocks.as_slice(), [world] if world.eq_ignore_ascii_case(b"world"));
+                let is_entity = key.eq_ignore_ascii_case(b"entity") && blocks.is_empty();
+
+                if !is_world_solid && !is_entity {
+                    blocks.push(key);
+                    continue;
+                }
+
+                let Some(block) = parse_block(&mut tokens) else {
+                    // unterminated, the vmf parser reports this
+                    return Cow::Borrowed(bytes);
+                };
+
+                if is_world_solid {
+                    if solid_intersects(&block, bounds) {
+                        continue;
+                    }
+
+                    removed_solids += 1;
+                } else {
+                    if entity_is_inside(&block, bounds) {
+                        continue;
+                    }
+
+                    removed_entities += 1;
+                }
+
+                // the whole lines of the block are removed
+                output.extend_from_slice(
+                    &bytes[copied..key_start - indentation(bytes, key_start).len()],
+                );
+                copied = line_end(bytes, tokens.pos());
+            }
+            Token::Text(text) => previous = Some((start, text)),
+            Token::Close => {
+                previous = None;
+                blocks.pop();
+            }
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(bytes);
+    }
+
+    info!(
+        "left out {removed_solids} solids and {removed_entities} entities outside of {:?} to {:?}",
+        bounds.min.to_array(),
+        bounds.max.to_array()
+    );
+
+    output.extend_from_slice(&bytes[copied..]);
+    Cow::Owned(output)
+}
+
+/// Returns the position after the line ending following `pos`, if only whitespace is between.
+fn line_end(bytes: &[u8], pos: usize) -> usize {
+    let rest = &bytes[pos..];
+    let spaces = rest
+        .iter()
+        .take_while(|&&b| b == b' ' || b == b'\t' || b == b'\r')
+        .count();
+
+    if rest.get(spaces) == Some(&b'\n') {
+        pos + spaces + 1
+    } else {
+        pos
+    }
+}
+
+/// The plane points of the sides are corners of the faces, so they span the solid.
+fn solid_intersects(solid: &[KeyValue], bounds: Bounds) -> bool {
+    let points: Vec<Vec3> = solid
+        .iter()
+        .filter_map(|entry| match entry {
+            KeyValue::Block(key, side) if key.eq_ignore_ascii_case("side") => {
+                find_value(side, "plane").and_then(parse_plane_points)
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect();
+
+    if points.is_empty() {
+        return true;
+    }
+
+    let min = points
+        .iter()
+        .fold(Vec3::splat(f32::INFINITY), |min, &p| min.min(p));
+    let max = points
+        .iter()
+        .fold(Vec3::splat(f32::NEG_INFINITY), |max, &p| max.max(p));
+
+    bounds.intersects(min, max)
+}
+
+fn entity_is_inside(entity: &[KeyValue], bounds: Bounds) -> bool {
+    let mut solids = entity
+        .iter()
+        .filter_map(|entry| match entry {
+            KeyValue::Block(key, solid) if key.eq_ignore_ascii_case("solid") => Some(solid),
+            _ => None,
+        })
+        .peekable();
+
+    if solids.peek().is_some() {
+        return solids.any(|solid| solid_intersects(solid, bounds));
+    }
+
+    find_value(entity, "origin")
+        .and_then(parse_vector)
+        .map_or(true, |origin| bounds.contains(Vec3::from(origin)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(id: usize, min: i32, max: i32) -> String {
+        let planes = [
+            format!("({min} {max} {max}) ({max} {max} {max}) ({max} {min} {max})"),
+            format!("({min} {min} {min}) ({max} {min} {min}) ({max} {max} {min})"),
+        ];
+
+        let sides: String = planes
+            .iter()
+            .map(|plane| format!("\t\tside\n\t\t{{\n\t\t\t\"plane\" \"{plane}\"\n\t\t}}\n"))
+            .collect();
+
+        format!("\tsolid\n\t{{\n\t\t\"id\" \"{id}\"\n{sides}\t}}\n")
+    }
+
+    fn point_entity(id: usize, origin: &str) -> String {
+        format!(
+            "entity\n{{\n\t\"id\" \"{id}\"\n\t\"classname\" \"prop_static\"\n\t\"origin\" \"{origin}\"\n}}\n"
+        )
+    }
+
+    #[test]
+    fn removes_outside_solids_and_entities() {
+        let vmf = format!(
+            "world\n{{\n\t\"id\" \"1\"\n{}{}}}\n{}{}",
+            solid(2, 0, 64),
+            solid(3, 1024, 1088),
+            point_entity(4, "32 32 32"),
+            point_entity(5, "2048 0 0"),
+        );
+
+        let filtered = filter_bounds(vmf.as_bytes(), Bounds::new([128.0; 3], [-128.0; 3]));
+
+        assert_eq!(
+            String::from_utf8_lossy(&filtered),
+            format!(
+                "world\n{{\n\t\"id\" \"1\"\n{}}}\n{}",
+                solid(2, 0, 64),
+                point_entity(4, "32 32 32"),
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_everything_inside() {
+        let vmf = format!("world\n{{\n{}}}\n", solid(2, 0, 64));
+
+        let filtered = filter_bounds(vmf.as_bytes(), Bounds::new([32.0; 3], [4096.0; 3]));
+
+        assert!(matches!(filtered, Cow::Borrowed(_)));
+    }
+}
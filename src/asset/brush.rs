@@ -1,13 +1,48 @@
-use std::mem;
+use std::{collections::HashMap, mem};
 
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 use itertools::Either;
-use plumber_core::vmf::builder::{BuiltBrushEntity, BuiltSolid, MergedSolids, SolidFace};
-use pyo3::{prelude::*, types::PyList};
+use plumber_core::vmf::{
+    builder::{BuiltBrushEntity, BuiltSolid, MergedSolids, SolidFace},
+    vmf::{Entity, Vmf},
+};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rgb::RGB8;
 
-use super::utils::linear_to_srgb;
+#[cfg(feature = "python")]
+use super::buffer::{float_buffer, i32_buffer, int_buffer};
+use super::{
+    editor_groups::{EditorGroups, PyEditorGroups},
+    entities::{get_property, parse_color_brightness, parse_property, parse_vector},
+    face_cleanup::{clean_faces, weld_vertices, Cleanup},
+    lightmap::{lightmap_uvs, LightmapScales, LightmapUvs},
+    seam::{sew_seams, BrushPlanes, SeamMesh},
+    source_keyvalues::{FaceTextureAxes, SourceKeyValues},
+    up_axis::UpAxis,
+    utils::{linear_to_srgb, srgb_to_linear},
+    vertices_plus::ExactVertices,
+    vmap::{VmapBrush, VmapMesh},
+};
 
-#[pyclass(module = "plumber", name = "MergedSolids")]
+/// `rendermode` 0 ignores `renderamt`, 10 doesn't draw the entity at all.
+const RENDER_MODE_NORMAL: u8 = 0;
+const RENDER_MODE_NONE: u8 = 10;
+
+/// Tool material of legacy origin brushes, which set the origin of their brush entity.
+const ORIGIN_MATERIAL: &str = "tools/toolsorigin";
+
+/// Geometry of a built solid, for consumers outside of Blender.
+pub struct SolidMesh<'a> {
+    pub no_draw: bool,
+    pub position: [f32; 3],
+    pub scale: [f32; 3],
+    pub vertices: &'a [f32],
+    pub faces: &'a [SolidFace],
+    pub materials: &'a [String],
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "MergedSolids"))]
 pub struct PyMergedSolids {
     no_draw: bool,
     position: [f32; 3],
@@ -18,42 +53,47 @@ pub struct PyMergedSolids {
     flat_polygon_vertice_indices: Vec<usize>,
     flat_loop_uvs: Vec<f32>,
     flat_loop_colors: Vec<f32>,
+    lightmap: Option<LightmapUvs>,
+    texture_axes: Option<FaceTextureAxes>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyMergedSolids {
-    fn no_draw(&self) -> bool {
+    pub fn no_draw(&self) -> bool {
         self.no_draw
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
 
-    fn vertices(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_vertices)
+    #[cfg(feature = "python")]
+    pub fn vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_vertices))
     }
 
-    fn loops_len(&self) -> usize {
+    pub fn loops_len(&self) -> usize {
         self.faces.iter().map(|f| f.vertice_indices.len()).sum()
     }
 
-    fn polygons_len(&self) -> usize {
+    pub fn polygons_len(&self) -> usize {
         self.faces.len()
     }
 
-    fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.vertice_indices.len()))
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.vertice_indices.len()))
     }
 
-    fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> &'p PyList {
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
         let mut acc = 0;
 
-        PyList::new(
+        int_buffer(
             py,
             self.faces.iter().map(|f| {
                 let acc_before = acc;
@@ -63,28 +103,137 @@ impl PyMergedSolids {
         )
     }
 
-    fn polygon_vertices(&mut self) -> Vec<usize> {
-        mem::take(&mut self.flat_polygon_vertice_indices)
+    #[cfg(feature = "python")]
+    pub fn polygon_vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, mem::take(&mut self.flat_polygon_vertice_indices))
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_material_indices<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.material_index))
     }
 
-    fn polygon_material_indices<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.material_index))
+    #[cfg(feature = "python")]
+    pub fn loop_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_uvs))
     }
 
-    fn loop_uvs(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_uvs)
+    #[cfg(feature = "python")]
+    pub fn loop_colors<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_colors))
     }
 
-    fn loop_colors(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_colors)
+    /// Side length in luxels of the lightmap the lightmap uvs are laid out for,
+    /// if they were generated.
+    pub fn lightmap_resolution(&self) -> Option<u32> {
+        self.lightmap.as_ref().map(|l| l.resolution)
     }
 
-    fn materials(&mut self) -> Vec<String> {
+    #[cfg(feature = "python")]
+    pub fn lightmap_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let uvs = self
+            .lightmap
+            .as_mut()
+            .map(|l| mem::take(&mut l.flat_loop_uvs))
+            .unwrap_or_default();
+
+        float_buffer(py, &uvs)
+    }
+
+    /// Lightmap scale of each face in map units per luxel, if the lightmap uvs were generated.
+    #[cfg(feature = "python")]
+    pub fn lightmap_scales<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let scales = self
+            .lightmap
+            .as_mut()
+            .map(|l| mem::take(&mut l.face_scales))
+            .unwrap_or_default();
+
+        float_buffer(py, &scales)
+    }
+
+    pub fn materials(&mut self) -> Vec<String> {
         mem::take(&mut self.materials)
     }
+
+    /// The distinct original `uaxis` and `vaxis` of the faces, if they were read.
+    pub fn texture_axes(&mut self) -> Vec<[String; 2]> {
+        self.texture_axes
+            .as_mut()
+            .map(|a| mem::take(&mut a.axes))
+            .unwrap_or_default()
+    }
+
+    /// Index into the texture axes of each face, or -1 where the side of the face wasn't found.
+    #[cfg(feature = "python")]
+    pub fn face_texture_axes<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let indices = self
+            .texture_axes
+            .as_mut()
+            .map(|a| mem::take(&mut a.face_indices))
+            .unwrap_or_default();
+
+        i32_buffer(py, &indices)
+    }
 }
 
 impl PyMergedSolids {
+    pub fn mesh(&self) -> SolidMesh<'_> {
+        SolidMesh {
+            no_draw: self.no_draw,
+            position: self.position,
+            scale: self.scale,
+            vertices: &self.flat_vertices,
+            faces: &self.faces,
+            materials: &self.materials,
+        }
+    }
+
+    fn seam_mesh(&mut self) -> SeamMesh<'_> {
+        SeamMesh {
+            offset: Vec3::from(self.position) / self.scale[0],
+            vertices: &mut self.flat_vertices,
+            faces: &self.faces,
+        }
+    }
+
+    fn clean_faces(&mut self) -> Cleanup {
+        let cleanup = clean_faces(&mut self.flat_vertices, &mut self.faces);
+        self.update_flat_loops(cleanup);
+        cleanup
+    }
+
+    fn weld_vertices(&mut self, distance: f32) -> Cleanup {
+        let cleanup = weld_vertices(&mut self.flat_vertices, &mut self.faces, distance);
+        self.update_flat_loops(cleanup);
+        cleanup
+    }
+
+    /// Updates the flat face data after `cleanup` changed the faces.
+    fn update_flat_loops(&mut self, cleanup: Cleanup) {
+        if !cleanup.is_empty() {
+            self.flat_polygon_vertice_indices = get_flat_polygon_vertice_indices(&self.faces);
+            self.flat_loop_uvs = get_flat_loop_uvs(&self.faces);
+            self.flat_loop_colors = get_flat_loop_colors(&self.faces);
+        }
+    }
+
+    fn generate_lightmap_uvs(&mut self, scales: &LightmapScales) {
+        let offset = Vec3::from(self.position) / self.scale[0];
+
+        self.lightmap = Some(lightmap_uvs(
+            offset,
+            &self.flat_vertices,
+            &self.faces,
+            scales,
+        ));
+    }
+
+    fn set_texture_axes(&mut self, keyvalues: &SourceKeyValues) {
+        let axes = keyvalues.face_texture_axes(&self.seam_mesh());
+        self.texture_axes = Some(axes);
+    }
+
     fn new(merged: MergedSolids) -> Self {
         let flat_vertices = merged.vertices.iter().flat_map(Vec3::to_array).collect();
 
@@ -106,13 +255,22 @@ impl PyMergedSolids {
             flat_polygon_vertice_indices,
             flat_loop_uvs,
             flat_loop_colors,
+            lightmap: None,
+            texture_axes: None,
         }
     }
+
+    fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.scale = up_axis.scale(self.scale);
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
 }
 
-#[pyclass(module = "plumber", name = "BuiltSolid")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BuiltSolid"))]
 pub struct PyBuiltSolid {
     id: i32,
+    editor_groups: Option<PyEditorGroups>,
     no_draw: bool,
     position: [f32; 3],
     scale: [f32; 3],
@@ -122,46 +280,55 @@ pub struct PyBuiltSolid {
     flat_polygon_vertice_indices: Vec<usize>,
     flat_loop_uvs: Vec<f32>,
     flat_loop_colors: Vec<f32>,
+    lightmap: Option<LightmapUvs>,
+    texture_axes: Option<FaceTextureAxes>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyBuiltSolid {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn no_draw(&self) -> bool {
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn no_draw(&self) -> bool {
         self.no_draw
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
 
-    fn vertices(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_vertices)
+    #[cfg(feature = "python")]
+    pub fn vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_vertices))
     }
 
-    fn loops_len(&self) -> usize {
+    pub fn loops_len(&self) -> usize {
         self.faces.iter().map(|f| f.vertice_indices.len()).sum()
     }
 
-    fn polygons_len(&self) -> usize {
+    pub fn polygons_len(&self) -> usize {
         self.faces.len()
     }
 
-    fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.vertice_indices.len()))
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.vertice_indices.len()))
     }
 
-    fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> &'p PyList {
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
         let mut acc = 0;
 
-        PyList::new(
+        int_buffer(
             py,
             self.faces.iter().map(|f| {
                 let acc_before = acc;
@@ -171,28 +338,137 @@ impl PyBuiltSolid {
         )
     }
 
-    fn polygon_vertices(&mut self) -> Vec<usize> {
-        mem::take(&mut self.flat_polygon_vertice_indices)
+    #[cfg(feature = "python")]
+    pub fn polygon_vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, mem::take(&mut self.flat_polygon_vertice_indices))
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_material_indices<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.material_index))
+    }
+
+    #[cfg(feature = "python")]
+    pub fn loop_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_uvs))
     }
 
-    fn polygon_material_indices<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.material_index))
+    #[cfg(feature = "python")]
+    pub fn loop_colors<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_colors))
     }
 
-    fn loop_uvs(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_uvs)
+    /// Side length in luxels of the lightmap the lightmap uvs are laid out for,
+    /// if they were generated.
+    pub fn lightmap_resolution(&self) -> Option<u32> {
+        self.lightmap.as_ref().map(|l| l.resolution)
     }
 
-    fn loop_colors(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_colors)
+    #[cfg(feature = "python")]
+    pub fn lightmap_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let uvs = self
+            .lightmap
+            .as_mut()
+            .map(|l| mem::take(&mut l.flat_loop_uvs))
+            .unwrap_or_default();
+
+        float_buffer(py, &uvs)
     }
 
-    fn materials(&mut self) -> Vec<String> {
+    /// Lightmap scale of each face in map units per luxel, if the lightmap uvs were generated.
+    #[cfg(feature = "python")]
+    pub fn lightmap_scales<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let scales = self
+            .lightmap
+            .as_mut()
+            .map(|l| mem::take(&mut l.face_scales))
+            .unwrap_or_default();
+
+        float_buffer(py, &scales)
+    }
+
+    pub fn materials(&mut self) -> Vec<String> {
         mem::take(&mut self.materials)
     }
+
+    /// The distinct original `uaxis` and `vaxis` of the faces, if they were read.
+    pub fn texture_axes(&mut self) -> Vec<[String; 2]> {
+        self.texture_axes
+            .as_mut()
+            .map(|a| mem::take(&mut a.axes))
+            .unwrap_or_default()
+    }
+
+    /// Index into the texture axes of each face, or -1 where the side of the face wasn't found.
+    #[cfg(feature = "python")]
+    pub fn face_texture_axes<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let indices = self
+            .texture_axes
+            .as_mut()
+            .map(|a| mem::take(&mut a.face_indices))
+            .unwrap_or_default();
+
+        i32_buffer(py, &indices)
+    }
 }
 
 impl PyBuiltSolid {
+    pub fn mesh(&self) -> SolidMesh<'_> {
+        SolidMesh {
+            no_draw: self.no_draw,
+            position: self.position,
+            scale: self.scale,
+            vertices: &self.flat_vertices,
+            faces: &self.faces,
+            materials: &self.materials,
+        }
+    }
+
+    fn seam_mesh(&mut self) -> SeamMesh<'_> {
+        SeamMesh {
+            offset: Vec3::from(self.position) / self.scale[0],
+            vertices: &mut self.flat_vertices,
+            faces: &self.faces,
+        }
+    }
+
+    fn clean_faces(&mut self) -> Cleanup {
+        let cleanup = clean_faces(&mut self.flat_vertices, &mut self.faces);
+        self.update_flat_loops(cleanup);
+        cleanup
+    }
+
+    fn weld_vertices(&mut self, distance: f32) -> Cleanup {
+        let cleanup = weld_vertices(&mut self.flat_vertices, &mut self.faces, distance);
+        self.update_flat_loops(cleanup);
+        cleanup
+    }
+
+    /// Updates the flat face data after `cleanup` changed the faces.
+    fn update_flat_loops(&mut self, cleanup: Cleanup) {
+        if !cleanup.is_empty() {
+            self.flat_polygon_vertice_indices = get_flat_polygon_vertice_indices(&self.faces);
+            self.flat_loop_uvs = get_flat_loop_uvs(&self.faces);
+            self.flat_loop_colors = get_flat_loop_colors(&self.faces);
+        }
+    }
+
+    fn generate_lightmap_uvs(&mut self, scales: &LightmapScales) {
+        let offset = Vec3::from(self.position) / self.scale[0];
+
+        self.lightmap = Some(lightmap_uvs(
+            offset,
+            &self.flat_vertices,
+            &self.faces,
+            scales,
+        ));
+    }
+
+    fn set_texture_axes(&mut self, keyvalues: &SourceKeyValues) {
+        let axes = keyvalues.face_texture_axes(&self.seam_mesh());
+        self.texture_axes = Some(axes);
+    }
+
     fn new(solid: BuiltSolid) -> Self {
         let flat_vertices = solid.vertices.iter().flat_map(Vec3::to_array).collect();
 
@@ -202,6 +478,7 @@ impl PyBuiltSolid {
 
         Self {
             id: solid.id,
+            editor_groups: None,
             no_draw: solid.materials.iter().all(|m| m.info.no_draw()),
             position: solid.position.to_array(),
             scale: [solid.scale, solid.scale, solid.scale],
@@ -215,48 +492,338 @@ impl PyBuiltSolid {
             flat_polygon_vertice_indices,
             flat_loop_uvs,
             flat_loop_colors,
+            lightmap: None,
+            texture_axes: None,
         }
     }
+
+    fn from_vmap(mesh: VmapMesh, scale: f32) -> Self {
+        let faces: Vec<_> = mesh
+            .faces
+            .into_iter()
+            .map(|face| SolidFace {
+                vertice_alphas: vec![0.0; face.vertice_indices.len()],
+                vertice_uvs: face.vertice_uvs.into_iter().map(Vec2::from).collect(),
+                vertice_indices: face.vertice_indices,
+                vertice_multiblends: None,
+                material_index: face.material_index,
+            })
+            .collect();
+
+        let flat_polygon_vertice_indices = get_flat_polygon_vertice_indices(&faces);
+        let flat_loop_uvs = get_flat_loop_uvs(&faces);
+        let flat_loop_colors = get_flat_loop_colors(&faces);
+
+        Self {
+            id: mesh.id,
+            editor_groups: None,
+            // Source 2 tool materials, such as nodraw and clips, are all in `tools/`
+            no_draw: !mesh.materials.is_empty()
+                && mesh.materials.iter().all(|m| m.starts_with("tools/")),
+            position: mesh.position.map(|c| c * scale),
+            scale: [scale, scale, scale],
+            faces,
+            materials: mesh.materials,
+            flat_vertices: mesh.vertices.into_iter().flatten().collect(),
+            flat_polygon_vertice_indices,
+            flat_loop_uvs,
+            flat_loop_colors,
+            lightmap: None,
+            texture_axes: None,
+        }
+    }
+
+    fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.scale = up_axis.scale(self.scale);
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
 }
 
-#[pyclass(module = "plumber", name = "BuiltBrushEntity")]
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "BuiltBrushEntity")
+)]
 pub struct PyBuiltBrushEntity {
     pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
     class_name: String,
     merged_solids: Option<PyMergedSolids>,
     solids: Vec<PyBuiltSolid>,
+    origin_brush: Option<[f32; 3]>,
+    keyvalues: Option<Vec<(String, String)>>,
+    render: Option<PyBrushRender>,
+    origin: Option<PyBrushOrigin>,
+    motion: Option<PyBrushMotion>,
+    rotation: Option<PyBrushRotation>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyBuiltBrushEntity {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn class_name(&self) -> &str {
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn class_name(&self) -> &str {
         &self.class_name
     }
 
-    fn merged_solids(&mut self) -> Option<PyMergedSolids> {
+    pub fn merged_solids(&mut self) -> Option<PyMergedSolids> {
         self.merged_solids.take()
     }
 
-    fn solids(&mut self) -> Vec<PyBuiltSolid> {
+    pub fn solids(&mut self) -> Vec<PyBuiltSolid> {
         mem::take(&mut self.solids)
     }
+
+    /// Center of the legacy origin brush of the entity, if it has one.
+    pub fn origin_brush(&self) -> Option<[f32; 3]> {
+        self.origin_brush
+    }
+
+    /// The original keyvalues of the entity, if they were read.
+    pub fn keyvalues(&mut self) -> Option<Vec<(String, String)>> {
+        self.keyvalues.take()
+    }
+
+    pub fn render(&mut self) -> Option<PyBrushRender> {
+        self.render.take()
+    }
+
+    /// The `origin` keyvalue of the entity, which its objects are pivoted around.
+    pub fn origin(&mut self) -> Option<PyBrushOrigin> {
+        self.origin.take()
+    }
+
+    pub fn motion(&mut self) -> Option<PyBrushMotion> {
+        self.motion.take()
+    }
+
+    pub fn rotation(&mut self) -> Option<PyBrushRotation> {
+        self.rotation.take()
+    }
 }
 
 impl PyBuiltBrushEntity {
     pub fn new(brush: BuiltBrushEntity) -> Self {
-        Self {
+        let mut brush = Self {
             id: brush.id,
+            parent_id: None,
+            editor_groups: None,
             class_name: brush.class_name.to_owned(),
             merged_solids: brush.merged_solids.map(PyMergedSolids::new),
             solids: brush.solids.into_iter().map(PyBuiltSolid::new).collect(),
+            origin_brush: None,
+            keyvalues: None,
+            render: None,
+            origin: None,
+            motion: None,
+            rotation: None,
+        };
+
+        brush.origin_brush = origin_brush_center(brush.meshes());
+        brush
+    }
+
+    /// Creates a brush entity from the meshes of a Source 2 map.
+    pub fn from_vmap(brush: VmapBrush, scale: f32) -> Self {
+        Self {
+            id: brush.id,
+            parent_id: None,
+            editor_groups: None,
+            class_name: brush.class_name,
+            merged_solids: None,
+            solids: brush
+                .meshes
+                .into_iter()
+                .map(|mesh| PyBuiltSolid::from_vmap(mesh, scale))
+                .collect(),
+            origin_brush: None,
+            keyvalues: None,
+            render: None,
+            origin: None,
+            motion: None,
+            rotation: None,
+        }
+    }
+
+    /// Fills in the render settings, origin and motion of the entity.
+    pub fn set_brush_entity(&mut self, entities: &BrushEntities, scale: f32) {
+        self.render = entities.renders.get(&self.id).map(PyBrushRender::new);
+        self.origin = entities
+            .origins
+            .get(&self.id)
+            .map(|origin| PyBrushOrigin::new(origin, scale));
+        self.motion = entities
+            .motions
+            .get(&self.id)
+            .map(|motion| PyBrushMotion::new(motion, scale));
+        self.rotation = entities.rotations.get(&self.id).map(PyBrushRotation::new);
+    }
+
+    /// Fills in the groups of the entity and of its separately built solids.
+    pub fn set_editor_groups(&mut self, groups: &EditorGroups) {
+        self.editor_groups = groups.get(self.id);
+
+        for solid in &mut self.solids {
+            solid.editor_groups = groups.get(solid.id);
+        }
+    }
+
+    /// Closes the cracks between the displacements of this brush entity by moving their edges
+    /// within `tolerance` map units together. Faces on `brush_planes` aren't displacements and
+    /// are left alone. Returns the amount of moved vertices.
+    pub fn sew_seams(&mut self, tolerance: f32, brush_planes: &BrushPlanes) -> usize {
+        let mut meshes: Vec<_> = self
+            .merged_solids
+            .iter_mut()
+            .map(PyMergedSolids::seam_mesh)
+            .chain(self.solids.iter_mut().map(PyBuiltSolid::seam_mesh))
+            .collect();
+
+        sew_seams(&mut meshes, tolerance, brush_planes)
+    }
+
+    /// Moves the vertices of the meshes onto the exact vertices Hammer++ stored close to them.
+    /// Returns the amount of moved vertices.
+    pub fn snap_vertices(&mut self, exact_vertices: &ExactVertices) -> usize {
+        let mut meshes: Vec<_> = self
+            .merged_solids
+            .iter_mut()
+            .map(PyMergedSolids::seam_mesh)
+            .chain(self.solids.iter_mut().map(PyBuiltSolid::seam_mesh))
+            .collect();
+
+        exact_vertices.snap(&mut meshes)
+    }
+
+    /// Removes degenerate faces and collinear vertices from the meshes.
+    /// Returns the amounts of removed geometry.
+    pub fn clean_faces(&mut self) -> Cleanup {
+        let mut cleanup = Cleanup::default();
+
+        if let Some(merged) = &mut self.merged_solids {
+            cleanup += merged.clean_faces();
+        }
+
+        for solid in &mut self.solids {
+            cleanup += solid.clean_faces();
+        }
+
+        cleanup
+    }
+
+    /// Welds the vertices of each mesh within `distance` map units of each other.
+    /// Returns the amounts of removed geometry.
+    pub fn weld_vertices(&mut self, distance: f32) -> Cleanup {
+        let mut cleanup = Cleanup::default();
+
+        if let Some(merged) = &mut self.merged_solids {
+            cleanup += merged.weld_vertices(distance);
+        }
+
+        for solid in &mut self.solids {
+            cleanup += solid.weld_vertices(distance);
+        }
+
+        cleanup
+    }
+
+    /// Lays out a second uv layer on the meshes for baking lighting,
+    /// at the lightmap scales the map sets for their faces.
+    pub fn generate_lightmap_uvs(&mut self, scales: &LightmapScales) {
+        if let Some(merged) = &mut self.merged_solids {
+            merged.generate_lightmap_uvs(scales);
+        }
+
+        for solid in &mut self.solids {
+            solid.generate_lightmap_uvs(scales);
+        }
+    }
+
+    /// Fills in the original keyvalues of the entity and texture axes of the faces,
+    /// which exporting the brush back into a vmf needs.
+    pub fn set_source_keyvalues(&mut self, keyvalues: &SourceKeyValues) {
+        self.keyvalues = keyvalues.entity(self.id);
+
+        if let Some(merged) = &mut self.merged_solids {
+            merged.set_texture_axes(keyvalues);
+        }
+
+        for solid in &mut self.solids {
+            solid.set_texture_axes(keyvalues);
+        }
+    }
+
+    pub fn meshes(&self) -> impl Iterator<Item = SolidMesh<'_>> {
+        self.merged_solids
+            .iter()
+            .map(PyMergedSolids::mesh)
+            .chain(self.solids.iter().map(PyBuiltSolid::mesh))
+    }
+
+    /// Converts the solids and the origin, movement direction and rotation axis of the entity.
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        if let Some(merged) = &mut self.merged_solids {
+            merged.convert_up_axis(up_axis);
+        }
+
+        for solid in &mut self.solids {
+            solid.convert_up_axis(up_axis);
+        }
+
+        self.origin_brush = self.origin_brush.map(|origin| up_axis.point(origin));
+
+        if let Some(origin) = &mut self.origin {
+            origin.origin = up_axis.point(origin.origin);
+        }
+
+        if let Some(motion) = &mut self.motion {
+            motion.direction = up_axis.point(motion.direction);
+        }
+
+        if let Some(rotation) = &mut self.rotation {
+            rotation.axis = up_axis.point(rotation.axis);
         }
     }
 }
 
+/// Returns the center of the faces using the origin tool material, in scaled units.
+fn origin_brush_center<'a>(meshes: impl Iterator<Item = SolidMesh<'a>>) -> Option<[f32; 3]> {
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+
+    for mesh in meshes {
+        let is_origin: Vec<bool> = mesh
+            .materials
+            .iter()
+            .map(|m| m.eq_ignore_ascii_case(ORIGIN_MATERIAL))
+            .collect();
+
+        let position = Vec3::from(mesh.position);
+        let scale = Vec3::from(mesh.scale);
+
+        for face in mesh.faces.iter().filter(|f| is_origin[f.material_index]) {
+            for &i in &face.vertice_indices {
+                let vertex = position + Vec3::from_slice(&mesh.vertices[i * 3..i * 3 + 3]) * scale;
+                min = min.min(vertex);
+                max = max.max(vertex);
+            }
+        }
+    }
+
+    (min.x <= max.x).then(|| ((min + max) / 2.0).to_array())
+}
+
 fn get_flat_polygon_vertice_indices(faces: &[SolidFace]) -> Vec<usize> {
     faces
         .iter()
@@ -295,3 +862,400 @@ fn get_flat_loop_colors(faces: &[SolidFace]) -> Vec<f32> {
         })
         .collect()
 }
+
+/// Render keyvalues of a brush entity, applied to its objects after they are imported.
+#[derive(Debug, Clone)]
+pub struct BrushRender {
+    id: i32,
+    mode: u8,
+    amount: u8,
+    color: RGB8,
+}
+
+impl BrushRender {
+    fn new(entity: &Entity) -> Option<Self> {
+        let mode = parse_property(entity, "rendermode");
+        let amount = parse_property(entity, "renderamt");
+        let color = get_property(entity, "rendercolor")
+            .and_then(parse_color_brightness)
+            .map(|(color, _)| color);
+
+        if mode.is_none() && amount.is_none() && color.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            id: entity.id,
+            mode: mode.unwrap_or(RENDER_MODE_NORMAL),
+            amount: amount.unwrap_or(255),
+            color: color.unwrap_or(RGB8::new(255, 255, 255)),
+        })
+    }
+
+    fn alpha(&self) -> f32 {
+        match self.mode {
+            RENDER_MODE_NORMAL => 1.0,
+            RENDER_MODE_NONE => 0.0,
+            _ => f32::from(self.amount) / 255.,
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BrushRender"))]
+pub struct PyBrushRender {
+    id: i32,
+    render_mode: u8,
+    color: [f32; 4],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyBrushRender {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn render_mode(&self) -> u8 {
+        self.render_mode
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+}
+
+impl PyBrushRender {
+    pub fn new(render: &BrushRender) -> Self {
+        let RGB8 { r, g, b } = render.color;
+        let [r, g, b] = [r, g, b].map(|c| srgb_to_linear(f32::from(c) / 255.));
+
+        Self {
+            id: render.id,
+            render_mode: render.mode,
+            color: [r, g, b, render.alpha()],
+        }
+    }
+}
+
+/// Origin keyvalue of a brush entity, which its objects are pivoted around after they are imported.
+#[derive(Debug, Clone)]
+pub struct BrushOrigin {
+    id: i32,
+    origin: [f32; 3],
+}
+
+impl BrushOrigin {
+    fn new(entity: &Entity) -> Option<Self> {
+        Some(Self {
+            id: entity.id,
+            origin: get_property(entity, "origin").and_then(parse_vector)?,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BrushOrigin"))]
+pub struct PyBrushOrigin {
+    id: i32,
+    origin: [f32; 3],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyBrushOrigin {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn origin(&self) -> [f32; 3] {
+        self.origin
+    }
+}
+
+impl PyBrushOrigin {
+    pub fn new(origin: &BrushOrigin, scale: f32) -> Self {
+        Self {
+            id: origin.id,
+            origin: origin.origin.map(|c| c * scale),
+        }
+    }
+}
+
+/// Spawnflag of `func_door` which spawns the door open, making the placed position the open one.
+const SF_DOOR_START_OPEN: u32 = 1;
+
+/// Speed doors use when their `speed` keyvalue is 0.
+const DEFAULT_DOOR_SPEED: f32 = 100.0;
+
+/// Linear motion of a `func_door` or `func_movelinear` between its closed and open position.
+#[derive(Debug, Clone)]
+pub struct BrushMotion {
+    id: i32,
+    /// Direction from the closed to the open position.
+    direction: Vec3,
+    /// Travel distance of `func_movelinear`, doors travel their own size along the direction.
+    distance: Option<f32>,
+    /// Subtracted from the size of doors, including the 2 units the engine shrinks it by.
+    lip: f32,
+    /// How far along the travel the brush is placed, from 0 when closed to 1 when open.
+    start_position: f32,
+    speed: f32,
+}
+
+impl BrushMotion {
+    fn new(entity: &Entity) -> Option<Self> {
+        // GoldSrc doors store the direction in their angles, where -1 points up and -2 down
+        let direction =
+            match get_property(entity, "movedir").or_else(|| get_property(entity, "angles")) {
+                Some("0 -1 0") => Vec3::Z,
+                Some("0 -2 0") => -Vec3::Z,
+                angles => move_direction(angles.and_then(parse_vector).unwrap_or_default()),
+            };
+
+        match entity.class_name.as_str() {
+            "func_door" => {
+                let spawn_flags: u32 = parse_property(entity, "spawnflags").unwrap_or_default();
+                let starts_open = spawn_flags & SF_DOOR_START_OPEN != 0;
+                let lip: f32 = parse_property(entity, "lip").unwrap_or_default();
+
+                Some(Self {
+                    id: entity.id,
+                    // the door spawns at the open position, which then becomes the closed one
+                    direction: if starts_open { -direction } else { direction },
+                    distance: None,
+                    lip: lip + 2.0 * direction.abs().dot(Vec3::ONE),
+                    start_position: if starts_open { 1.0 } else { 0.0 },
+                    speed: parse_property(entity, "speed")
+                        .filter(|&speed: &f32| speed > 0.0)
+                        .unwrap_or(DEFAULT_DOOR_SPEED),
+                })
+            }
+            "func_movelinear" | "func_water_analog" => Some(Self {
+                id: entity.id,
+                direction,
+                distance: Some(parse_property(entity, "movedistance").unwrap_or_default()),
+                lip: 0.0,
+                start_position: parse_property(entity, "startposition").unwrap_or_default(),
+                speed: parse_property(entity, "speed").unwrap_or_default(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Converts the `movedir` angles into the forward direction they point in.
+fn move_direction([pitch, yaw, _]: [f32; 3]) -> Vec3 {
+    let (pitch_sin, pitch_cos) = pitch.to_radians().sin_cos();
+    let (yaw_sin, yaw_cos) = yaw.to_radians().sin_cos();
+
+    Vec3::new(pitch_cos * yaw_cos, pitch_cos * yaw_sin, -pitch_sin)
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BrushMotion"))]
+pub struct PyBrushMotion {
+    id: i32,
+    direction: [f32; 3],
+    distance: Option<f32>,
+    lip: f32,
+    start_position: f32,
+    speed: f32,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyBrushMotion {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Direction from the closed to the open position.
+    pub fn direction(&self) -> [f32; 3] {
+        self.direction
+    }
+
+    /// Travel distance, if it doesn't depend on the size of the brush like for doors.
+    pub fn distance(&self) -> Option<f32> {
+        self.distance
+    }
+
+    /// Subtracted from the size of doors along the direction to get their travel distance.
+    pub fn lip(&self) -> f32 {
+        self.lip
+    }
+
+    /// How far along the travel the brush is placed, from 0 when closed to 1 when open.
+    pub fn start_position(&self) -> f32 {
+        self.start_position
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+}
+
+impl PyBrushMotion {
+    pub fn new(motion: &BrushMotion, scale: f32) -> Self {
+        Self {
+            id: motion.id,
+            direction: motion.direction.to_array(),
+            distance: motion.distance.map(|d| d * scale),
+            lip: motion.lip * scale,
+            start_position: motion.start_position,
+            speed: motion.speed * scale,
+        }
+    }
+}
+
+/// Spawnflags of `func_rotating`, which by default spins around the z axis.
+const SF_ROTATING_START_ON: u32 = 1;
+const SF_ROTATING_BACKWARDS: u32 = 2;
+const SF_ROTATING_X_AXIS: u32 = 4;
+const SF_ROTATING_Y_AXIS: u32 = 8;
+
+/// Speed `func_rotating` uses when its `maxspeed` keyvalue is 0, in degrees per second.
+const DEFAULT_ROTATING_SPEED: f32 = 100.0;
+
+/// Constant spin of a `func_rotating` around its origin.
+#[derive(Debug, Clone)]
+pub struct BrushRotation {
+    id: i32,
+    /// Axis the entity spins counterclockwise around, reversed when it spins backwards.
+    axis: Vec3,
+    /// Degrees per second.
+    speed: f32,
+    starts_on: bool,
+}
+
+impl BrushRotation {
+    fn new(entity: &Entity) -> Self {
+        let spawn_flags: u32 = parse_property(entity, "spawnflags").unwrap_or_default();
+
+        let axis = if spawn_flags & SF_ROTATING_X_AXIS != 0 {
+            Vec3::X
+        } else if spawn_flags & SF_ROTATING_Y_AXIS != 0 {
+            Vec3::Y
+        } else {
+            Vec3::Z
+        };
+
+        // GoldSrc stores the speed in `speed`
+        let speed = parse_property(entity, "maxspeed")
+            .or_else(|| parse_property(entity, "speed"))
+            .filter(|&speed: &f32| speed > 0.0)
+            .unwrap_or(DEFAULT_ROTATING_SPEED);
+
+        Self {
+            id: entity.id,
+            axis: if spawn_flags & SF_ROTATING_BACKWARDS == 0 {
+                axis
+            } else {
+                -axis
+            },
+            speed,
+            starts_on: spawn_flags & SF_ROTATING_START_ON != 0,
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "BrushRotation")
+)]
+pub struct PyBrushRotation {
+    id: i32,
+    axis: [f32; 3],
+    speed: f32,
+    starts_on: bool,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyBrushRotation {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Axis the entity spins counterclockwise around, reversed when it spins backwards.
+    pub fn axis(&self) -> [f32; 3] {
+        self.axis
+    }
+
+    /// Speed in radians per second.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Whether the entity spins from the start, instead of when it's triggered.
+    pub fn starts_on(&self) -> bool {
+        self.starts_on
+    }
+}
+
+impl PyBrushRotation {
+    pub fn new(rotation: &BrushRotation) -> Self {
+        Self {
+            id: rotation.id,
+            axis: rotation.axis.to_array(),
+            speed: rotation.speed.to_radians(),
+            starts_on: rotation.starts_on,
+        }
+    }
+}
+
+/// Render settings, origins and motions of the brush entities of the imported vmfs,
+/// which the built brushes are sent with since they only carry their solids.
+#[derive(Debug, Default)]
+pub struct BrushEntities {
+    renders: HashMap<i32, BrushRender>,
+    origins: HashMap<i32, BrushOrigin>,
+    motions: HashMap<i32, BrushMotion>,
+    rotations: HashMap<i32, BrushRotation>,
+}
+
+impl BrushEntities {
+    /// Reads the brush entities of a vmf, replacing what was read for their ids before.
+    pub fn extend_from_vmf(&mut self, vmf: &Vmf) {
+        for entity in vmf.entities.iter().filter(|e| !e.solids.is_empty()) {
+            let id = entity.id;
+
+            set_or_remove(
+                &mut self.renders,
+                id,
+                Some(entity)
+                    .filter(|e| e.class_name.starts_with("func_"))
+                    .and_then(BrushRender::new),
+            );
+            set_or_remove(&mut self.origins, id, BrushOrigin::new(entity));
+            set_or_remove(&mut self.motions, id, BrushMotion::new(entity));
+            set_or_remove(
+                &mut self.rotations,
+                id,
+                (entity.class_name == "func_rotating").then(|| BrushRotation::new(entity)),
+            );
+        }
+    }
+}
+
+fn set_or_remove<T>(map: &mut HashMap<i32, T>, id: i32, value: Option<T>) {
+    match value {
+        Some(value) => map.insert(id, value),
+        None => map.remove(&id),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_move_directions() {
+        let directions = [
+            ([0.0, 90.0, 0.0], Vec3::Y),
+            ([-90.0, 0.0, 0.0], Vec3::Z),
+            ([45.0, 0.0, 0.0], Vec3::new(1.0, 0.0, -1.0).normalize()),
+            ([0.0, 180.0, 0.0], -Vec3::X),
+        ];
+
+        for (angles, expected) in directions {
+            assert!(
+                move_direction(angles).distance(expected) < 1e-6,
+                "{angles:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Mesh arrays passed to Python as typed `memoryview`s.
+//!
+//! Blender's `foreach_set` copies a buffer of the matching type directly,
+//! while lists need every element converted into a Python object and back.
+//! The values are written straight into the `bytes` object the `memoryview` shares,
+//! so they're copied only once.
+
+use image::DynamicImage;
+use pyo3::{exceptions::PyOverflowError, prelude::*, types::PyBytes};
+
+pub fn float_buffer<'p>(py: Python<'p>, values: &[f32]) -> PyResult<&'p PyAny> {
+    typed_buffer(py, values.len(), "f", |bytes| {
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        Ok(())
+    })
+}
+
+/// Blender stores mesh indices as 32-bit integers.
+///
+/// # Errors
+///
+/// Returns `Err` if an index doesn't fit into 32 bits.
+pub fn int_buffer<'p, I>(py: Python<'p>, values: I) -> PyResult<&'p PyAny>
+where
+    I: IntoIterator<Item = usize>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let values = values.into_iter();
+
+    typed_buffer(py, values.len(), "i", |bytes| {
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values) {
+            let value = i32::try_from(value).map_err(|_| {
+                PyOverflowError::new_err(format!("mesh index {value} doesn't fit into 32 bits"))
+            })?;
+
+            chunk.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        Ok(())
+    })
+}
+
+/// Like [`int_buffer`], for values which are 32-bit already.
+pub fn i32_buffer<'p>(py: Python<'p>, values: &[i32]) -> PyResult<&'p PyAny> {
+    typed_buffer(py, values.len(), "i", |bytes| {
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        Ok(())
+    })
+}
+
+/// RGBA pixels of an image as floats, starting from the bottom row like Blender images.
+///
+/// The decoded pixels are converted while they're written, so 8-bit and float images
+/// are copied into the buffer as they are.
+pub fn image_buffer<'p>(py: Python<'p>, image: &DynamicImage) -> PyResult<&'p PyAny> {
+    let row_len = image.width() as usize * 4;
+    let len = row_len * image.height() as usize;
+
+    match image {
+        DynamicImage::ImageRgba8(buffer) => typed_buffer(py, len, "f", |bytes| {
+            write_rows(bytes, buffer.as_raw(), row_len, |v| f32::from(v) / 255.0);
+            Ok(())
+        }),
+        DynamicImage::ImageRgba32F(buffer) => typed_buffer(py, len, "f", |bytes| {
+            write_rows(bytes, buffer.as_raw(), row_len, |v| v);
+            Ok(())
+        }),
+        image => image_buffer(py, &DynamicImage::ImageRgba32F(image.to_rgba32f())),
+    }
+}
+
+/// Writes the rows of the source in reverse order as floats.
+fn write_rows<T: Copy>(
+    bytes: &mut [u8],
+    source: &[T],
+    row_len: usize,
+    to_float: impl Fn(T) -> f32,
+) {
+    if row_len == 0 {
+        return;
+    }
+
+    for (row, source_row) in bytes
+        .chunks_exact_mut(row_len * 4)
+        .zip(source.chunks_exact(row_len).rev())
+    {
+        for (chunk, &value) in row.chunks_exact_mut(4).zip(source_row) {
+            chunk.copy_from_slice(&to_float(value).to_ne_bytes());
+        }
+    }
+}
+
+/// Creates a buffer of `len` 4-byte elements of the given `struct` format, filled by `init`.
+fn typed_buffer<'p>(
+    py: Python<'p>,
+    len: usize,
+    format: &str,
+    init: impl FnOnce(&mut [u8]) -> PyResult<()>,
+) -> PyResult<&'p PyAny> {
+    let bytes = PyBytes::new_with(py, len * 4, init)?;
+
+    py.import("builtins")?
+        .getattr("memoryview")?
+        .call1((bytes,))?
+        .call_method1("cast", (format,))
+}
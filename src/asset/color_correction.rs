@@ -0,0 +1,276 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter, Write as _},
+    fs, io, mem,
+    path::PathBuf as StdPathBuf,
+};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::{GamePathBuf, PathBuf},
+    vmf::vmf::{Entity, Vmf},
+};
+
+use crate::extract_cache::contained_path;
+
+use super::{
+    editor_groups::PyEditorGroups,
+    entities::{entity_properties, get_property, parse_vector},
+    up_axis::UpAxis,
+    BlenderAssetHandler,
+};
+
+/// Color correction lookup tables are 32x32x32 RGB8 volumes.
+const LUT_SIZE: usize = 32;
+const LUT_BYTES: usize = LUT_SIZE * LUT_SIZE * LUT_SIZE * 3;
+
+#[derive(Debug, Clone)]
+pub struct ColorCorrectionEntity {
+    id: i32,
+    origin: [f32; 3],
+    lut_path: GamePathBuf,
+    properties: BTreeMap<String, String>,
+}
+
+impl ColorCorrectionEntity {
+    fn new(entity: &Entity) -> Option<Self> {
+        let file_name = get_property(entity, "filename").filter(|f| !f.is_empty())?;
+
+        Some(Self {
+            id: entity.id,
+            origin: get_property(entity, "origin")
+                .and_then(parse_vector)
+                .unwrap_or_default(),
+            lut_path: GamePathBuf::from(file_name),
+            properties: entity_properties(entity),
+        })
+    }
+}
+
+/// Returns the `color_correction` entities of a vmf which reference a lookup table.
+pub fn color_correction_entities(vmf: &Vmf) -> Vec<ColorCorrectionEntity> {
+    vmf.entities
+        .iter()
+        .filter(|e| e.class_name == "color_correction")
+        .filter_map(ColorCorrectionEntity::new)
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ColorCorrectionError {
+    Read(GamePathBuf, io::Error),
+    InvalidSize(GamePathBuf, usize),
+    InvalidPath(GamePathBuf),
+    Write(StdPathBuf, io::Error),
+}
+
+impl Display for ColorCorrectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(path, err) => write!(f, "color correction `{path}`: {err}"),
+            Self::InvalidSize(path, size) => write!(
+                f,
+                "color correction `{path}`: expected {LUT_BYTES} bytes, got {size}"
+            ),
+            Self::InvalidPath(path) => {
+                write!(
+                    f,
+                    "color correction `{path}`: path leaves the game directory"
+                )
+            }
+            Self::Write(path, err) => write!(
+                f,
+                "error writing color correction `{}`: {err}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for ColorCorrectionError {}
+
+/// Reads the `.raw` lookup table of a `color_correction` entity
+/// and writes it into a temporary `.cube` file.
+#[derive(Debug, Clone, Copy)]
+pub struct ColorCorrectionConfig;
+
+impl AssetConfig<BlenderAssetHandler> for ColorCorrectionConfig {
+    type Input<'a> = ColorCorrectionEntity;
+    type Output<'a> = (ColorCorrectionEntity, StdPathBuf);
+    type Error<'a> = ColorCorrectionError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        // the path is joined onto the temporary directory, so it must stay inside it
+        let relative_path = contained_path(input.lut_path.as_str())
+            .ok_or_else(|| ColorCorrectionError::InvalidPath(input.lut_path.clone()))?;
+
+        let path: PathBuf = input.lut_path.clone().into();
+
+        let raw = context
+            .fs()
+            .read(&path)
+            .map_err(|e| ColorCorrectionError::Read(input.lut_path.clone(), e))?;
+
+        let cube = raw_to_cube(&raw, input.lut_path.as_str())
+            .ok_or_else(|| ColorCorrectionError::InvalidSize(input.lut_path.clone(), raw.len()))?;
+
+        let mut cube_path = std::env::temp_dir().join("plumber");
+        cube_path.push(relative_path);
+        cube_path.set_extension("cube");
+
+        if let Some(parent) = cube_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ColorCorrectionError::Write(cube_path.clone(), e))?;
+        }
+
+        fs::write(&cube_path, cube)
+            .map_err(|e| ColorCorrectionError::Write(cube_path.clone(), e))?;
+
+        Ok((input, cube_path))
+    }
+}
+
+/// Converts a Source `.raw` color correction lookup table into the `.cube` format.
+fn raw_to_cube(raw: &[u8], title: &str) -> Option<String> {
+    if raw.len() != LUT_BYTES {
+        return None;
+    }
+
+    let mut cube = format!("TITLE \"{title}\"\nLUT_3D_SIZE {LUT_SIZE}\n");
+
+    // both formats store red as the fastest changing component, followed by green and blue
+    for rgb in raw.chunks_exact(3) {
+        let [r, g, b] = [rgb[0], rgb[1], rgb[2]].map(|c| f32::from(c) / 255.);
+        writeln!(cube, "{r:.6} {g:.6} {b:.6}").expect("writing to a string cannot fail");
+    }
+
+    Some(cube)
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "ColorCorrection")
+)]
+pub struct PyColorCorrection {
+    pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
+    position: [f32; 3],
+    lut_path: String,
+    cube_path: String,
+    weight: f32,
+    max_weight: f32,
+    min_falloff: f32,
+    max_falloff: f32,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyColorCorrection {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn lut_path(&self) -> &str {
+        &self.lut_path
+    }
+
+    pub fn cube_path(&self) -> &str {
+        &self.cube_path
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn max_weight(&self) -> f32 {
+        self.max_weight
+    }
+
+    pub fn min_falloff(&self) -> f32 {
+        self.min_falloff
+    }
+
+    pub fn max_falloff(&self) -> f32 {
+        self.max_falloff
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PyColorCorrection {
+    pub fn new(entity: ColorCorrectionEntity, cube_path: &StdPathBuf, scale: f32) -> Self {
+        let property = |key: &str, default: f32| {
+            entity
+                .properties
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .and_then(|(_, v)| v.trim().parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            id: entity.id,
+            editor_groups: None,
+            position: entity.origin.map(|c| c * scale),
+            lut_path: entity.lut_path.into_string(),
+            cube_path: cube_path.to_string_lossy().into_owned(),
+            weight: property("weight", 1.0),
+            max_weight: property("maxweight", 1.0),
+            min_falloff: property("minfalloff", 0.0) * scale,
+            max_falloff: property("maxfalloff", 0.0) * scale,
+            properties: entity.properties,
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_to_cube_identity() {
+        let mut raw = Vec::with_capacity(LUT_BYTES);
+
+        for b in 0..LUT_SIZE {
+            for g in 0..LUT_SIZE {
+                for r in 0..LUT_SIZE {
+                    raw.extend([r, g, b].map(|c| u8::try_from(c * 255 / 31).unwrap()));
+                }
+            }
+        }
+
+        let cube = raw_to_cube(&raw, "test").unwrap();
+        let mut lines = cube.lines().skip(2);
+
+        assert_eq!(lines.next(), Some("0.000000 0.000000 0.000000"));
+        assert_eq!(lines.next(), Some("0.031373 0.000000 0.000000"));
+        assert_eq!(cube.lines().last(), Some("1.000000 1.000000 1.000000"));
+    }
+
+    #[test]
+    fn raw_to_cube_invalid_size() {
+        assert!(raw_to_cube(&[0; 12], "test").is_none());
+    }
+}
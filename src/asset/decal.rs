@@ -0,0 +1,209 @@
+//! `infodecal` entities, sized by their material like in game.
+//!
+//! Decals have no size of their own. The engine draws them at the size of their base texture
+//! in pixels multiplied by the `$decalscale` of the material, so both are read here.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    asset_vmt::{VmtError, VmtHelper},
+    fs::{GamePathBuf, OpenFileSystem, PathBuf},
+    vmf::vmf::{Entity, Vmf},
+    vmt::TexturePath,
+};
+
+use super::{
+    editor_groups::PyEditorGroups,
+    entities::{get_property, parse_vector},
+    material::MaterialConfig,
+    up_axis::UpAxis,
+    BlenderAssetHandler,
+};
+
+/// Size of decals whose base texture can't be read, in pixels.
+const DEFAULT_DECAL_SIZE: u16 = 64;
+
+#[derive(Debug, Clone)]
+pub struct InfoDecal {
+    id: i32,
+    origin: [f32; 3],
+    material: GamePathBuf,
+}
+
+impl InfoDecal {
+    fn new(entity: &Entity) -> Option<Self> {
+        let texture = get_property(entity, "texture").filter(|t| !t.is_empty())?;
+
+        Some(Self {
+            id: entity.id,
+            origin: get_property(entity, "origin").and_then(parse_vector)?,
+            material: material_path(texture),
+        })
+    }
+}
+
+/// Returns the `infodecal` entities of a vmf which have a material.
+pub fn info_decals(vmf: &Vmf) -> Vec<InfoDecal> {
+    vmf.entities
+        .iter()
+        .filter(|e| e.class_name == "infodecal")
+        .filter_map(InfoDecal::new)
+        .collect()
+}
+
+/// Returns the path of a material, as it's named by entity keyvalues.
+pub(super) fn material_path(name: &str) -> GamePathBuf {
+    let mut path = GamePathBuf::from(format!("materials/{name}"));
+    path.set_extension("vmt");
+    path
+}
+
+/// Reads the base texture size and `$decalscale` of the material of an `infodecal`,
+/// and queues the material itself.
+#[derive(Debug, Clone, Copy)]
+pub struct DecalConfig {
+    pub material_config: MaterialConfig,
+}
+
+impl AssetConfig<BlenderAssetHandler> for DecalConfig {
+    type Input<'a> = InfoDecal;
+    type Output<'a> = Decal;
+    type Error<'a> = VmtError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let material = PathBuf::from(input.material.clone());
+
+        // the vmt loader resolves the includes of patch materials
+        let (decal_scale, texture_size) = {
+            let vmt = VmtHelper::new(&material, context.fs())?;
+
+            (
+                vmt.extract_param::<f32>("$decalscale").unwrap_or(1.0),
+                base_texture_size(&vmt, context.fs()),
+            )
+        };
+
+        let [width, height] =
+            texture_size.map_or([DEFAULT_DECAL_SIZE; 2], |(width, height)| [width, height]);
+
+        context.queue(self.material_config, material);
+
+        Ok(Decal {
+            id: input.id,
+            origin: input.origin,
+            material: input.material.into_string(),
+            size: [
+                f32::from(width) * decal_scale,
+                f32::from(height) * decal_scale,
+            ],
+        })
+    }
+}
+
+/// Reads the size of the base texture of a material in pixels, from the header of its vtf.
+pub(super) fn base_texture_size(vmt: &VmtHelper, fs: &OpenFileSystem) -> Option<(u16, u16)> {
+    let mut path = vmt
+        .extract_param::<TexturePath>("$basetexture")?
+        .absolute_path();
+    path.set_extension("vtf");
+
+    vtf_size(&fs.read(&PathBuf::from(path)).ok()?)
+}
+
+/// Reads the size of a vtf from its header.
+fn vtf_size(bytes: &[u8]) -> Option<(u16, u16)> {
+    if bytes.get(..4)? != b"VTF\0" {
+        return None;
+    }
+
+    let read_u16 = |offset: usize| {
+        bytes
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+
+    let width = read_u16(16)?;
+    let height = read_u16(18)?;
+
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[derive(Debug, Clone)]
+pub struct Decal {
+    id: i32,
+    origin: [f32; 3],
+    material: String,
+    /// Width and height in map units.
+    size: [f32; 2],
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Decal"))]
+pub struct PyDecal {
+    pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
+    position: [f32; 3],
+    material: String,
+    size: [f32; 2],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyDecal {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn material(&self) -> &str {
+        &self.material
+    }
+
+    /// Width and height of the base texture multiplied by the `$decalscale` of the material.
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+}
+
+impl PyDecal {
+    pub fn new(decal: Decal, scale: f32) -> Self {
+        Self {
+            id: decal.id,
+            editor_groups: None,
+            position: decal.origin.map(|c| c * scale),
+            material: decal.material,
+            size: decal.size.map(|c| c * scale),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_vtf_size() {
+        let mut header = b"VTF\0".to_vec();
+        header.extend([7, 0, 0, 0, 2, 0, 0, 0, 80, 0, 0, 0]);
+        header.extend(128_u16.to_le_bytes());
+        header.extend(32_u16.to_le_bytes());
+
+        assert_eq!(vtf_size(&header), Some((128, 32)));
+        assert_eq!(vtf_size(b"DDS "), None);
+    }
+}
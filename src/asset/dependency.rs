@@ -0,0 +1,90 @@
+use std::{collections::BTreeSet, mem};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::{
+    brush::PyBuiltBrushEntity, entities::PyLoadedProp, material::BuiltMaterialData, model::PyModel,
+    overlay::PyBuiltOverlay,
+};
+
+/// An edge set of the asset dependency graph: the assets of one kind that an asset depends on.
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Dependency"))]
+pub struct PyDependency {
+    pub kind: &'static str,
+    pub name: String,
+    dependency_kind: &'static str,
+    dependencies: Vec<String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyDependency {
+    pub fn kind(&self) -> &str {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dependency_kind(&self) -> &str {
+        self.dependency_kind
+    }
+
+    pub fn dependencies(&mut self) -> Vec<String> {
+        mem::take(&mut self.dependencies)
+    }
+}
+
+impl PyDependency {
+    fn new<'a>(
+        kind: &'static str,
+        name: String,
+        dependency_kind: &'static str,
+        dependencies: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let dependencies: BTreeSet<&str> = dependencies.into_iter().collect();
+
+        Self {
+            kind,
+            name,
+            dependency_kind,
+            dependencies: dependencies.into_iter().map(str::to_owned).collect(),
+        }
+    }
+
+    pub fn material(name: String, data: &BuiltMaterialData) -> Self {
+        Self::new("material", name, "texture", data.texture_paths())
+    }
+
+    pub fn model(model: &PyModel) -> Self {
+        Self::new(
+            "model",
+            model.name.clone(),
+            "material",
+            model.material_names(),
+        )
+    }
+
+    pub fn brush(brush: &PyBuiltBrushEntity) -> Self {
+        Self::new(
+            "brush",
+            brush.id.to_string(),
+            "material",
+            brush.meshes().flat_map(|m| m.materials).map(String::as_str),
+        )
+    }
+
+    pub fn overlay(overlay: &PyBuiltOverlay) -> Self {
+        Self::new(
+            "overlay",
+            overlay.id.to_string(),
+            "material",
+            [overlay.material()],
+        )
+    }
+
+    pub fn prop(prop: &PyLoadedProp) -> Self {
+        Self::new("prop", prop.id.to_string(), "model", [prop.model()])
+    }
+}
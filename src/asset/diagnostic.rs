@@ -0,0 +1,73 @@
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "WARNING",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem with a single asset, sent alongside the assets so it can be shown to the user.
+/// The same problem is also logged.
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Diagnostic"))]
+#[derive(Debug, Clone)]
+pub struct PyDiagnostic {
+    pub severity: Severity,
+    pub asset_kind: &'static str,
+    pub asset_id: Option<String>,
+    pub text: String,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyDiagnostic {
+    /// Either `ERROR` or `WARNING`.
+    pub fn severity(&self) -> &str {
+        self.severity.as_str()
+    }
+
+    pub fn asset_kind(&self) -> &str {
+        self.asset_kind
+    }
+
+    /// The name or entity id of the asset, if known.
+    pub fn asset_id(&self) -> Option<&str> {
+        self.asset_id.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PyDiagnostic {
+    pub fn new(
+        severity: Severity,
+        asset_kind: &'static str,
+        asset_id: Option<String>,
+        text: impl Display,
+    ) -> Self {
+        Self {
+            severity,
+            asset_kind,
+            asset_id,
+            text: text.to_string(),
+        }
+    }
+}
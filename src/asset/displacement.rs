@@ -0,0 +1,410 @@
+//! Simplification of displacements before a vmf is parsed, to keep the vertex counts of maps
+//! with many high power displacements manageable.
+//!
+//! A displacement of power `p` is a grid of `2^p + 1` by `2^p + 1` vertices. Lowering the power
+//! by one keeps every other row and column of that grid, so the kept vertices stay in place.
+//! Neighbors simplified differently no longer share all edge vertices, which sewing the seams
+//! of the brushes closes again.
+
+use std::borrow::Cow;
+
+use glam::Vec3;
+
+use super::keyvalues::{
+    find_block, find_value, indentation, parse_block, write_block, KeyValue, Token, Tokenizer,
+};
+
+/// Hammer doesn't create displacements with a lower power.
+const MIN_POWER: u32 = 2;
+const MAX_POWER: u32 = 4;
+
+/// Blocks of a `dispinfo` with one row of values per grid row, each value per grid vertex.
+const VERTEX_BLOCKS: &[&str] = &[
+    "normals",
+    "distances",
+    "offsets",
+    "offset_normals",
+    "alphas",
+    "multiblend",
+    "alphablend",
+    "multiblend_color_0",
+    "multiblend_color_1",
+    "multiblend_color_2",
+    "multiblend_color_3",
+];
+
+/// `allowed_verts` is a bitfield of the vertices of a power 4 grid in 10 integers.
+const ALLOWED_VERTS_LEN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplacementSettings {
+    /// Displacements with a higher power are downsampled to this power.
+    pub max_power: u32,
+    /// Displacements are downsampled further as long as no vertex moves more than this
+    /// distance in map units. Zero disables this.
+    pub tolerance: f32,
+}
+
+impl Default for DisplacementSettings {
+    fn default() -> Self {
+        Self {
+            max_power: MAX_POWER,
+            tolerance: 0.0,
+        }
+    }
+}
+
+impl DisplacementSettings {
+    fn is_noop(&self) -> bool {
+        self.max_power >= MAX_POWER && self.tolerance <= 0.0
+    }
+
+    fn should_downsample(&self, displacement: &[KeyValue], power: u32) -> bool {
+        if power <= MIN_POWER {
+            return false;
+        }
+
+        if power > self.max_power {
+            return true;
+        }
+
+        if self.tolerance <= 0.0 {
+            return false;
+        }
+
+        let error = downsample_error(displacement, power);
+        matches!(error, Some(error) if error <= self.tolerance)
+    }
+}
+
+/// Returns the vmf with its displacements downsampled according to `settings`.
+/// Displacements which can't be read are left as they are, for the vmf parser to report.
+pub fn simplify_displacements<'a>(
+    bytes: &'a [u8],
+    settings: &DisplacementSettings,
+) -> Cow<'a, [u8]> {
+    if settings.is_noop() {
+        return Cow::Borrowed(bytes);
+    }
+
+    let line_ending: &[u8] = if bytes.windows(2).any(|w| w == b"\r\n") {
+        b"\r\n"
+    } else {
+        b"\n"
+    };
+
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut copied = 0;
+    let mut simplified = 0;
+
+    let mut tokens = Tokenizer::new(bytes);
+    let mut previous: Option<(usize, &[u8])> = None;
+
+    while let Some((start, token)) = tokens.next_token() {
+        match token {
+            Token::Open => {
+                let Some((key_start, key)) = previous.take() else {
+                    continue;
+                };
+
+                if !key.eq_ignore_ascii_case(b"dispinfo") {
+                    continue;
+                }
+
+                let Some(mut displacement) = parse_block(&mut tokens) else {
+                    // unterminated, the vmf parser reports this
+                    return Cow::Borrowed(bytes);
+                };
+
+                let mut changed = false;
+
+                while let Some(power) =
+                    find_value(&displacement, "power").and_then(|p| p.parse().ok())
+                {
+                    if !settings.should_downsample(&displacement, power)
+                        || !downsample(&mut displacement, power)
+                    {
+                        break;
+                    }
+
+                    changed = true;
+                }
+
+                if changed {
+                    let indent = indentation(bytes, key_start);
+                    output.extend_from_slice(&bytes[copied..key_start]);
+                    write_block(&mut output, "dispinfo", &displacement, indent, line_ending);
+                    copied = tokens.pos();
+                    simplified += 1;
+                }
+            }
+            Token::Text(text) => previous = Some((start, text)),
+            Token::Close => previous = None,
+        }
+    }
+
+    if simplified == 0 {
+        return Cow::Borrowed(bytes);
+    }
+
+    output.extend_from_slice(&bytes[copied..]);
+    Cow::Owned(output)
+}
+
+/// Returns the values of `row0` to `row{count - 1}` of a block.
+fn rows<'a>(block: &'a [KeyValue], count: usize) -> Option<Vec<Vec<&'a str>>> {
+    (0..count)
+        .map(|i| find_value(block, &format!("row{i}")).map(|row| row.split_whitespace().collect()))
+        .collect()
+}
+
+fn row_entries(rows: impl IntoIterator<Item = Vec<String>>) -> Vec<KeyValue> {
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| KeyValue::Value(format!("row{i}"), row.join(" ")))
+        .collect()
+}
+
+/// Lowers the power of a displacement by one.
+/// Returns false without changing anything if the displacement can't be read.
+fn downsample(displacement: &mut [KeyValue], power: u32) -> bool {
+    let size = 1 << power;
+    let mut replaced = Vec::new();
+
+    for entry in displacement.iter() {
+        let KeyValue::Block(name, block) = entry else {
+            continue;
+        };
+
+        let lowercase = name.to_ascii_lowercase();
+
+        let new_block = if VERTEX_BLOCKS.contains(&lowercase.as_str()) {
+            downsample_vertex_rows(block, size)
+        } else if lowercase == "triangle_tags" {
+            downsample_triangle_rows(block, size)
+        } else if lowercase == "allowed_verts" {
+            // the bits refer to vertex indices which change, so every vertex is allowed
+            Some(vec![KeyValue::Value(
+                ALLOWED_VERTS_LEN.to_string(),
+                vec!["-1"; ALLOWED_VERTS_LEN].join(" "),
+            )])
+        } else {
+            continue;
+        };
+
+        let Some(new_block) = new_block else {
+            return false;
+        };
+
+        replaced.push((name.clone(), new_block));
+    }
+
+    for entry in displacement.iter_mut() {
+        match entry {
+            KeyValue::Value(key, value) if key.eq_ignore_ascii_case("power") => {
+                *value = (power - 1).to_string();
+            }
+            KeyValue::Block(name, block) => {
+                if let Some(index) = replaced.iter().position(|(replaced, _)| replaced == name) {
+                    *block = replaced.swap_remove(index).1;
+                }
+            }
+            KeyValue::Value(..) => {}
+        }
+    }
+
+    true
+}
+
+/// Keeps every other row and vertex of a grid of `size + 1` rows.
+fn downsample_vertex_rows(block: &[KeyValue], size: usize) -> Option<Vec<KeyValue>> {
+    let rows = rows(block, size + 1)?;
+    let components = rows[0].len() / (size + 1);
+
+    if components == 0 || rows.iter().any(|row| row.len() != components * (size + 1)) {
+        return None;
+    }
+
+    let new_rows = rows.iter().step_by(2).map(|row| {
+        row.chunks(components)
+            .step_by(2)
+            .flatten()
+            .map(|&value| value.to_owned())
+            .collect::<Vec<_>>()
+    });
+
+    Some(row_entries(new_rows))
+}
+
+/// Keeps the two triangles of every other quad of a grid of `size` rows of quads.
+fn downsample_triangle_rows(block: &[KeyValue], size: usize) -> Option<Vec<KeyValue>> {
+    let rows = rows(block, size)?;
+
+    if rows.iter().any(|row| row.len() != size * 2) {
+        return None;
+    }
+
+    let new_rows = rows.iter().step_by(2).map(|row| {
+        row.chunks(4)
+            .flat_map(|quads| &quads[..2])
+            .map(|&value| value.to_owned())
+            .collect::<Vec<_>>()
+    });
+
+    Some(row_entries(new_rows))
+}
+
+/// Returns the largest distance a vertex of the displacement moves when lowering its power.
+/// The base positions of the vertices are interpolated from the face corners,
+/// so only the displacement vectors of the vertices affect this.
+fn downsample_error(displacement: &[KeyValue], power: u32) -> Option<f32> {
+    let size = 1 << power;
+    let vectors = displacement_vectors(displacement, size)?;
+
+    let mut error: f32 = 0.0;
+
+    for i in 0..=size {
+        for j in 0..=size {
+            let vector = vectors[i][j];
+
+            let distance = match (i % 2, j % 2) {
+                (0, 0) => continue,
+                (0, _) => vector.distance((vectors[i][j - 1] + vectors[i][j + 1]) / 2.0),
+                (_, 0) => vector.distance((vectors[i - 1][j] + vectors[i + 1][j]) / 2.0),
+                // either diagonal of the quad can be used by the triangulation
+                _ => vector
+                    .distance((vectors[i - 1][j - 1] + vectors[i + 1][j + 1]) / 2.0)
+                    .max(vector.distance((vectors[i - 1][j + 1] + vectors[i + 1][j - 1]) / 2.0)),
+            };
+
+            error = error.max(distance);
+        }
+    }
+
+    Some(error)
+}
+
+/// Returns the offset of each vertex from its base position, apart from the elevation.
+fn displacement_vectors(displacement: &[KeyValue], size: usize) -> Option<Vec<Vec<Vec3>>> {
+    let normals = parse_rows(find_block(displacement, "normals")?, size, 3)?;
+    let distances = parse_rows(find_block(displacement, "distances")?, size, 1)?;
+    let offsets = match find_block(displacement, "offsets") {
+        Some(block) => Some(parse_rows(block, size, 3)?),
+        None => None,
+    };
+
+    Some(
+        (0..=size)
+            .map(|i| {
+                (0..=size)
+                    .map(|j| {
+                        let normal = Vec3::from_slice(&normals[i][j * 3..j * 3 + 3]);
+                        let offset = offsets.as_ref().map_or(Vec3::ZERO, |offsets| {
+                            Vec3::from_slice(&offsets[i][j * 3..j * 3 + 3])
+                        });
+
+                        normal * distances[i][j] + offset
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+fn parse_rows(block: &[KeyValue], size: usize, components: usize) -> Option<Vec<Vec<f32>>> {
+    rows(block, size + 1)?
+        .into_iter()
+        .map(|row| {
+            if row.len() != (size + 1) * components {
+                return None;
+            }
+
+            row.into_iter().map(|value| value.parse().ok()).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+
+    fn write_rows(
+        vmf: &mut String,
+        name: &str,
+        size: usize,
+        value: impl Fn(usize, usize) -> String,
+    ) {
+        let _ = write!(vmf, "\t\t{name}\n\t\t{{\n");
+
+        for i in 0..=size {
+            let row: Vec<_> = (0..=size).map(|j| value(i, j)).collect();
+            let _ = writeln!(vmf, "\t\t\t\"row{i}\" \"{}\"", row.join(" "));
+        }
+
+        vmf.push_str("\t\t}\n");
+    }
+
+    fn vmf(power: u32, distance: impl Fn(usize, usize) -> f32) -> String {
+        let size = 1 << power;
+        let mut vmf = format!("side\n{{\n\tdispinfo\n\t{{\n\t\t\"power\" \"{power}\"\n");
+
+        write_rows(&mut vmf, "normals", size, |_, _| "0 0 1".to_owned());
+        write_rows(&mut vmf, "distances", size, |i, j| {
+            distance(i, j).to_string()
+        });
+        write_rows(&mut vmf, "alphas", size, |_, _| "0".to_owned());
+
+        vmf.push_str("\t}\n}\n");
+        vmf
+    }
+
+    fn power_of(bytes: &[u8]) -> u32 {
+        let mut tokens = Tokenizer::new(bytes);
+
+        while let Some((_, token)) = tokens.next_token() {
+            if matches!(token, Token::Text(text) if text == b"power") {
+                if let Some((_, Token::Text(power))) = tokens.next_token() {
+                    return String::from_utf8_lossy(power).parse().unwrap();
+                }
+            }
+        }
+
+        panic!("the vmf should have a power");
+    }
+
+    #[test]
+    fn limits_power() {
+        let vmf = vmf(4, |i, j| (i * 17 + j) as f32);
+        let settings = DisplacementSettings {
+            max_power: 2,
+            tolerance: 0.0,
+        };
+
+        let simplified = simplify_displacements(vmf.as_bytes(), &settings);
+        let text = String::from_utf8_lossy(&simplified);
+
+        assert_eq!(power_of(&simplified), 2);
+        // rows 0 and 4 of the power 4 grid, with every fourth vertex
+        assert!(text.contains("\"row0\" \"0 4 8 12 16\""));
+        assert!(text.contains("\"row1\" \"68 72 76 80 84\""));
+        assert!(!text.contains("\"row5\""));
+    }
+
+    #[test]
+    fn decimates_flat_displacements_only() {
+        let settings = DisplacementSettings {
+            max_power: 4,
+            tolerance: 0.5,
+        };
+
+        let flat = vmf(3, |_, _| 8.0);
+        let simplified = simplify_displacements(flat.as_bytes(), &settings);
+        assert_eq!(power_of(&simplified), 2);
+
+        let bumpy = vmf(3, |i, j| if (i + j) % 2 == 0 { 0.0 } else { 16.0 });
+        let simplified = simplify_displacements(bumpy.as_bytes(), &settings);
+        assert!(matches!(simplified, Cow::Borrowed(_)));
+    }
+}
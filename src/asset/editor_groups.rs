@@ -0,0 +1,267 @@
+//! The visgroups and groups Hammer organizes the objects of a vmf in.
+//!
+//! Solids, entities and groups store the ids of their visgroups and of the group they are in
+//! within their `editor` block, while the names of the visgroups are stored at the top of the
+//! vmf. They are sent with the imported objects, so collections can mirror the organization.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::keyvalues::{find_block, find_value, KeyValue};
+
+/// The visgroups and groups of the objects of the imported vmfs.
+#[derive(Debug, Default)]
+pub struct EditorGroups {
+    /// Names of the visgroups along with the names of the visgroups they are nested in,
+    /// from the outermost.
+    visgroups: HashMap<i32, Vec<String>>,
+    /// Groups the groups are in.
+    group_parents: HashMap<i32, i32>,
+    /// Solids and entities by their id.
+    objects: HashMap<i32, ObjectGroups>,
+}
+
+#[derive(Debug)]
+struct ObjectGroups {
+    class_name: Option<String>,
+    world: bool,
+    visgroup_ids: Vec<i32>,
+    group_id: Option<i32>,
+}
+
+impl EditorGroups {
+    /// Reads the visgroups and groups of a vmf.
+    pub fn extend_from_keyvalues(&mut self, vmf: &[KeyValue]) {
+        self.add_blocks(vmf, false);
+    }
+
+    fn add_blocks(&mut self, entries: &[KeyValue], in_world: bool) {
+        for entry in entries {
+            let KeyValue::Block(name, block) = entry else {
+                continue;
+            };
+
+            if name.eq_ignore_ascii_case("visgroups") {
+                self.add_visgroups(block, &[]);
+                continue;
+            }
+
+            let in_world = in_world || name.eq_ignore_ascii_case("world");
+            self.add_object(name, block, in_world);
+            self.add_blocks(block, in_world);
+        }
+    }
+
+    fn add_visgroups(&mut self, entries: &[KeyValue], parents: &[String]) {
+        for entry in entries {
+            let KeyValue::Block(name, visgroup) = entry else {
+                continue;
+            };
+
+            if !name.eq_ignore_ascii_case("visgroup") {
+                continue;
+            }
+
+            let mut path = parents.to_vec();
+            path.push(find_value(visgroup, "name").unwrap_or_default().to_owned());
+
+            if let Some(id) = find_value(visgroup, "visgroupid").and_then(|id| id.parse().ok()) {
+                self.visgroups.insert(id, path.clone());
+            }
+
+            self.add_visgroups(visgroup, &path);
+        }
+    }
+
+    fn add_object(&mut self, name: &str, block: &[KeyValue], world: bool) {
+        let Some(id) = find_value(block, "id").and_then(parse_id) else {
+            return;
+        };
+
+        let editor = find_block(block, "editor").unwrap_or_default();
+        let group_id = find_value(editor, "groupid").and_then(parse_id);
+
+        if name.eq_ignore_ascii_case("group") {
+            if let Some(parent) = group_id {
+                self.group_parents.insert(id, parent);
+            }
+        } else if ["solid", "entity", "world"]
+            .iter()
+            .any(|object| name.eq_ignore_ascii_case(object))
+        {
+            self.objects.insert(
+                id,
+                ObjectGroups {
+                    class_name: find_value(block, "classname").map(str::to_owned),
+                    world,
+                    visgroup_ids: editor
+                        .iter()
+                        .filter_map(|entry| match entry {
+                            KeyValue::Value(key, value)
+                                if key.eq_ignore_ascii_case("visgroupid") =>
+                            {
+                                parse_id(value)
+                            }
+                            _ => None,
+                        })
+                        .collect(),
+                    group_id,
+                },
+            );
+        }
+    }
+
+    /// Returns the groups of the solid or entity with the id, if it was read.
+    pub fn get(&self, id: i32) -> Option<PyEditorGroups> {
+        let object = self.objects.get(&id)?;
+
+        let mut groups = Vec::new();
+        let mut current = object.group_id;
+
+        while let Some(group) = current {
+            // broken vmfs could nest groups in a loop
+            if groups.contains(&group) {
+                break;
+            }
+
+            groups.push(group);
+            current = self.group_parents.get(&group).copied();
+        }
+
+        groups.reverse();
+
+        Some(PyEditorGroups {
+            class_name: object.class_name.clone(),
+            world: object.world,
+            visgroups: object
+                .visgroup_ids
+                .iter()
+                .filter_map(|id| self.visgroups.get(id))
+                .cloned()
+                .collect(),
+            groups,
+        })
+    }
+}
+
+fn parse_id(text: &str) -> Option<i32> {
+    text.trim().parse().ok()
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "EditorGroups"))]
+#[derive(Debug, Clone)]
+pub struct PyEditorGroups {
+    class_name: Option<String>,
+    world: bool,
+    visgroups: Vec<Vec<String>>,
+    groups: Vec<i32>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyEditorGroups {
+    /// Class name of the entity, or `None` for solids.
+    pub fn class_name(&self) -> Option<&str> {
+        self.class_name.as_deref()
+    }
+
+    /// The object is the world or one of its solids.
+    pub fn world(&self) -> bool {
+        self.world
+    }
+
+    /// Each visgroup as the names of the visgroups it's nested in, ending with its own name.
+    pub fn visgroups(&self) -> Vec<Vec<String>> {
+        self.visgroups.clone()
+    }
+
+    /// Ids of the nested groups the object is in, from the outermost.
+    pub fn groups(&self) -> Vec<i32> {
+        self.groups.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::keyvalues::parse_document;
+
+    const VMF: &str = "visgroups
+{
+\tvisgroup
+\t{
+\t\t\"name\" \"Buildings\"
+\t\t\"visgroupid\" \"1\"
+\t\tvisgroup
+\t\t{
+\t\t\t\"name\" \"Roofs\"
+\t\t\t\"visgroupid\" \"2\"
+\t\t}
+\t}
+}
+world
+{
+\t\"id\" \"1\"
+\t\"classname\" \"worldspawn\"
+\tsolid
+\t{
+\t\t\"id\" \"10\"
+\t\tside
+\t\t{
+\t\t\t\"id\" \"11\"
+\t\t}
+\t\teditor
+\t\t{
+\t\t\t\"visgroupid\" \"2\"
+\t\t\t\"groupid\" \"30\"
+\t\t}
+\t}
+\tgroup
+\t{
+\t\t\"id\" \"30\"
+\t\teditor
+\t\t{
+\t\t\t\"groupid\" \"31\"
+\t\t}
+\t}
+}
+entity
+{
+\t\"id\" \"20\"
+\t\"classname\" \"prop_static\"
+\teditor
+\t{
+\t\t\"visgroupid\" \"1\"
+\t\t\"visgroupid\" \"2\"
+\t}
+}
+";
+
+    #[test]
+    fn reads_visgroups_and_groups() {
+        let mut groups = EditorGroups::default();
+        groups.extend_from_keyvalues(&parse_document(VMF.as_bytes()));
+
+        let world = groups.get(1).unwrap();
+        assert_eq!(world.class_name.as_deref(), Some("worldspawn"));
+        assert!(world.world);
+
+        let solid = groups.get(10).unwrap();
+        assert_eq!(solid.class_name, None);
+        assert!(solid.world);
+        assert_eq!(solid.visgroups, [["Buildings", "Roofs"]]);
+        assert_eq!(solid.groups, [31, 30]);
+
+        let prop = groups.get(20).unwrap();
+        assert_eq!(prop.class_name.as_deref(), Some("prop_static"));
+        assert!(!prop.world);
+        assert_eq!(
+            prop.visgroups,
+            [vec!["Buildings"], vec!["Buildings", "Roofs"]]
+        );
+        assert!(prop.groups.is_empty());
+
+        assert!(groups.get(11).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+//! Decoding vmfs which aren't valid UTF-8 before they're parsed.
+//!
+//! Hammer saves vmfs in the system code page, so names and comments written on Windows
+//! are often Windows-1252, and some editors save them as UTF-16. Such files are decoded
+//! lossily instead of failing to parse as a whole.
+
+use std::borrow::Cow;
+
+use tracing::warn;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// The characters of the Windows-1252 bytes `0x80..0xA0`, which differ from Latin-1.
+/// Undefined bytes are mapped to the control character with the same code, like browsers do.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Returns the vmf as UTF-8 without a byte order mark.
+/// UTF-16 files are converted, and bytes which aren't valid UTF-8 are decoded as Windows-1252.
+pub fn decode_vmf(bytes: &[u8]) -> Cow<[u8]> {
+    if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        return decode_vmf(rest);
+    }
+
+    if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+        return Cow::Owned(decode_utf16(rest, u16::from_le_bytes));
+    }
+
+    if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+        return Cow::Owned(decode_utf16(rest, u16::from_be_bytes));
+    }
+
+    // vmfs start with an ascii block name, so without a byte order mark
+    // utf-16 shows up as every other byte being zero
+    match bytes {
+        [first, 0, ..] if *first != 0 => Cow::Owned(decode_utf16(bytes, u16::from_le_bytes)),
+        [0, second, ..] if *second != 0 => Cow::Owned(decode_utf16(bytes, u16::from_be_bytes)),
+        _ if std::str::from_utf8(bytes).is_ok() => Cow::Borrowed(bytes),
+        _ => Cow::Owned(decode_windows_1252(bytes)),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Vec<u8> {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+
+    let mut invalid = 0;
+    let decoded: String = char::decode_utf16(units)
+        .map(|c| {
+            c.unwrap_or_else(|_| {
+                invalid += 1;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+
+    if invalid > 0 {
+        warn!("vmf is utf-16 with {invalid} invalid characters, they were replaced");
+    } else {
+        warn!("vmf is utf-16, it was converted to utf-8");
+    }
+
+    decoded.into_bytes()
+}
+
+/// Keeps valid UTF-8 sequences, so files which are mostly UTF-8 aren't garbled.
+fn decode_windows_1252(mut bytes: &[u8]) -> Vec<u8> {
+    let mut output = String::with_capacity(bytes.len() + bytes.len() / 8);
+    let mut invalid = 0;
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                output.push_str(valid);
+                break;
+            }
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                // the prefix was just validated
+                output.push_str(std::str::from_utf8(valid).unwrap_or_default());
+
+                let invalid_len = error.error_len().unwrap_or(rest.len());
+                output.extend(rest[..invalid_len].iter().map(|&b| windows_1252_char(b)));
+                invalid += invalid_len;
+
+                bytes = &rest[invalid_len..];
+            }
+        }
+    }
+
+    warn!("vmf has {invalid} bytes which aren't valid utf-8, they were decoded as windows-1252");
+
+    output.into_bytes()
+}
+
+fn windows_1252_char(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_valid_utf8() {
+        let vmf = "world\n{\n\t\"comment\" \"käytävä\"\n}\n".as_bytes();
+
+        assert!(matches!(decode_vmf(vmf), Cow::Borrowed(b) if b == vmf));
+    }
+
+    #[test]
+    fn decodes_windows_1252() {
+        let vmf = b"world\n{\n\t\"comment\" \"caf\xE9 \x80 \xE2\x82\xAC\"\n}\n";
+
+        assert_eq!(
+            decode_vmf(vmf).as_ref(),
+            "world\n{\n\t\"comment\" \"café € €\"\n}\n".as_bytes()
+        );
+    }
+
+    #[test]
+    fn decodes_utf16() {
+        let text = "world\n{\n\t\"comment\" \"ä\"\n}\n";
+
+        let mut le = UTF16_LE_BOM.to_vec();
+        le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_vmf(&le).as_ref(), text.as_bytes());
+
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(decode_vmf(&be).as_ref(), text.as_bytes());
+    }
+
+    #[test]
+    fn strips_utf8_bom() {
+        assert_eq!(
+            decode_vmf(b"\xEF\xBB\xBFworld\n{\n}\n").as_ref(),
+            b"world\n{\n}\n"
+        );
+    }
+}
@@ -1,68 +1,126 @@
-use std::{collections::BTreeMap, f32::consts::FRAC_PI_2, mem};
+use std::{collections::BTreeMap, f32::consts::FRAC_PI_2, mem, str::FromStr};
 
 use glam::{EulerRot, Quat};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use rgb::ComponentMap;
+use rgb::{ComponentMap, RGB8};
 
 use plumber_core::{
     asset_vmf::prop::LoadedProp,
-    vmf::entities::{
-        AngledEntity, BaseEntity, EntityParseError, EnvLight, Light, LightEntity, PointEntity,
-        SkyCamera, SpotLight, Unknown,
+    fs::GamePathBuf,
+    vmf::{
+        entities::{
+            AngledEntity, BaseEntity, EntityParseError, EnvLight, Light, LightEntity, PointEntity,
+            SkyCamera, SpotLight, Unknown,
+        },
+        vmf::{Entity, Vmf},
     },
 };
 
-use super::utils::srgb_to_linear;
+use super::{
+    editor_groups::PyEditorGroups, glow::GlowSprite, material::TextureFormat, up_axis::UpAxis,
+    utils::srgb_to_linear, vmap::VmapEntity,
+};
 
-#[pyclass(module = "plumber", name = "LoadedProp")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "LoadedProp"))]
 pub struct PyLoadedProp {
     model: String,
     class_name: String,
     pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
     position: [f32; 3],
     rotation: [f32; 3],
     scale: [f32; 3],
     color: [f32; 4],
+    lighting_origin: Option<String>,
+    skin: i32,
+    body: i32,
+    fade_distances: Option<[f32; 2]>,
+    fade_scale: f32,
+    screen_space_fade: bool,
     properties: BTreeMap<String, String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyLoadedProp {
-    fn model(&self) -> &str {
+    pub fn model(&self) -> &str {
         &self.model
     }
 
-    fn class_name(&self) -> &str {
+    pub fn class_name(&self) -> &str {
         &self.class_name
     }
 
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
 
-    fn color(&self) -> [f32; 4] {
+    pub fn color(&self) -> [f32; 4] {
         self.color
     }
 
-    fn properties(&mut self) -> BTreeMap<String, String> {
+    pub fn lighting_origin(&self) -> Option<&str> {
+        self.lighting_origin.as_deref()
+    }
+
+    pub fn skin(&self) -> i32 {
+        self.skin
+    }
+
+    /// The bodygroup index of the prop.
+    pub fn body(&self) -> i32 {
+        self.body
+    }
+
+    /// Distances at which the prop starts and finishes fading out, if it fades.
+    pub fn fade_distances(&self) -> Option<[f32; 2]> {
+        self.fade_distances
+    }
+
+    /// Multiplier of the fade distances the level's `fadescale` is applied with.
+    pub fn fade_scale(&self) -> f32 {
+        self.fade_scale
+    }
+
+    /// The fade distances are in screen pixels the prop covers instead of map units.
+    pub fn screen_space_fade(&self) -> bool {
+        self.screen_space_fade
+    }
+
+    /// Key shared by the props which look the same apart from their transform,
+    /// so they can be imported as instances of the same objects.
+    pub fn instance_key(&self) -> String {
+        instance_key(&self.model, self.skin, self.body)
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
         mem::take(&mut self.properties)
     }
 }
 
 impl PyLoadedProp {
-    pub fn new(prop: LoadedProp) -> Self {
+    pub fn new(prop: LoadedProp, scale: f32) -> Self {
         let rotation = prop.rotation;
         let properties = prop
             .prop
@@ -71,26 +129,88 @@ impl PyLoadedProp {
             .iter()
             .map(|(k, v)| (k.as_str().to_owned(), v.clone()))
             .collect();
+        let uniform_scale = prop_uniform_scale(prop.prop.entity());
+        let lighting_origin = get_property(prop.prop.entity(), "lightingorigin")
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned);
+        let skin = parse_property(prop.prop.entity(), "skin").unwrap_or(0);
+        // prop_static uses `body` and prop_dynamic `SetBodyGroup`
+        let body = ["body", "setbodygroup"]
+            .into_iter()
+            .find_map(|key| parse_property(prop.prop.entity(), key))
+            .unwrap_or(0);
+        let screen_space_fade =
+            parse_property::<i32>(prop.prop.entity(), "screenspacefade").is_some_and(|f| f != 0);
+        let fade_distances = fade_distances(
+            parse_property(prop.prop.entity(), "fademindist").unwrap_or(-1.0),
+            parse_property(prop.prop.entity(), "fademaxdist").unwrap_or(0.0),
+        )
+        .map(|distances| {
+            if screen_space_fade {
+                distances
+            } else {
+                distances.map(|d| d * scale)
+            }
+        });
+        let fade_scale = parse_property(prop.prop.entity(), "fadescale").unwrap_or(1.0);
 
         Self {
             model: prop.model_path.into_string(),
             class_name: prop.prop.entity().class_name.clone(),
             id: prop.prop.entity().id,
+            parent_id: None,
+            editor_groups: None,
             position: prop.position.into(),
             rotation: [
                 rotation[2].to_radians(),
                 rotation[0].to_radians(),
                 rotation[1].to_radians(),
             ],
-            scale: prop.scale,
+            scale: prop.scale.map(|s| s * uniform_scale),
             color: prop
                 .color
                 .map_alpha(|a| f32::from(a) / 255.)
                 .map_rgb(|c| srgb_to_linear(f32::from(c) / 255.))
                 .into(),
+            lighting_origin,
+            skin,
+            body,
+            fade_distances,
+            fade_scale,
+            screen_space_fade,
             properties,
         }
     }
+
+    /// Converts the placement of the prop, its scale stays in the space of the model.
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+/// Returns the distances a prop starts and finishes fading at, like the engine reads them.
+/// A maximum of 0 disables fading, and a negative minimum makes the prop pop out at the maximum.
+fn fade_distances(min: f32, max: f32) -> Option<[f32; 2]> {
+    if max <= 0.0 {
+        return None;
+    }
+
+    Some([if min < 0.0 { max } else { min.min(max) }, max])
+}
+
+/// Model paths are case insensitive, so the key uses the lowercase path.
+fn instance_key(model: &str, skin: i32, body: i32) -> String {
+    format!("{}#skin{skin}#body{body}", model.to_lowercase())
+}
+
+/// Returns the `modelscale` or Hammer++ `uniformscale` of a prop, defaulting to 1.
+fn prop_uniform_scale(entity: &Entity) -> f32 {
+    ["modelscale", "uniformscale"]
+        .into_iter()
+        .find_map(|key| parse_property::<f32>(entity, key))
+        .filter(|scale| scale.is_finite() && *scale > 0.0)
+        .unwrap_or(1.0)
 }
 
 #[allow(clippy::struct_field_names)]
@@ -111,34 +231,55 @@ impl Default for LightSettings {
     }
 }
 
-#[pyclass(module = "plumber", name = "Light")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Light"))]
 pub struct PyLight {
     color: [f32; 3],
     energy: f32,
+    energy_keyframes: Vec<f32>,
+    cutoff_distance: Option<f32>,
     position: [f32; 3],
     pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
     properties: BTreeMap<String, String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyLight {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn energy_keyframes(&mut self) -> Vec<f32> {
+        mem::take(&mut self.energy_keyframes)
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn color(&self) -> [f32; 3] {
+    pub fn color(&self) -> [f32; 3] {
         self.color
     }
 
-    fn energy(&self) -> f32 {
+    pub fn energy(&self) -> f32 {
         self.energy
     }
 
-    fn properties(&mut self) -> BTreeMap<String, String> {
+    /// Distance past which the light doesn't light anything.
+    pub fn cutoff_distance(&self) -> Option<f32> {
+        self.cutoff_distance
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
         mem::take(&mut self.properties)
     }
 }
@@ -148,6 +289,7 @@ impl PyLight {
         light: Light,
         settings: &LightSettings,
         scale: f32,
+        target_fps: f32,
     ) -> Result<Self, EntityParseError> {
         let (color, brightness) =
             if let Some((hdr_color, hdr_brightness)) = light.hdr_color_brightness()? {
@@ -166,16 +308,135 @@ impl PyLight {
             .map(|(k, v)| (k.as_str().to_owned(), v.clone()))
             .collect();
 
+        let energy = brightness * settings.light_factor;
+
         Ok(Self {
             color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
-            energy: brightness * settings.light_factor,
+            energy,
+            energy_keyframes: light_style_keyframes(light.entity(), energy, target_fps),
+            cutoff_distance: None,
             position,
             id,
+            parent_id: None,
+            editor_groups: None,
             properties,
         })
     }
 }
 
+impl PyLight {
+    pub fn from_light_dynamic(
+        entity: &Unknown,
+        settings: &LightSettings,
+        scale: f32,
+        target_fps: f32,
+    ) -> Result<Self, EntityParseError> {
+        let (color, brightness) = light_dynamic_color_brightness(entity.entity());
+        let energy = brightness * settings.light_factor;
+
+        Ok(Self {
+            color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
+            energy,
+            energy_keyframes: light_style_keyframes(entity.entity(), energy, target_fps),
+            cutoff_distance: light_dynamic_distance(entity.entity(), scale),
+            position: (entity.origin()? * scale).into(),
+            id: entity.entity().id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(entity.entity()),
+        })
+    }
+}
+
+/// Source 2 light brightness multiplies the light color,
+/// so a brightness of 1 is treated like a Source 1 brightness of 255.
+const VMAP_BRIGHTNESS_SCALE: f32 = 255.0;
+
+fn vmap_color_energy(entity: &VmapEntity, settings: &LightSettings) -> ([f32; 3], f32) {
+    let [r, g, b] = entity.vector("color").unwrap_or([255.0; 3]);
+    let brightness = entity.property("brightness").unwrap_or(1.0_f32);
+
+    (
+        [r, g, b].map(|c| srgb_to_linear(c / 255.)),
+        brightness * VMAP_BRIGHTNESS_SCALE * settings.light_factor,
+    )
+}
+
+impl PyLight {
+    /// Creates a light from a Source 2 `light_omni` entity.
+    pub fn from_vmap(entity: &VmapEntity, settings: &LightSettings, scale: f32) -> Self {
+        let (color, energy) = vmap_color_energy(entity, settings);
+
+        Self {
+            color,
+            energy,
+            energy_keyframes: Vec::new(),
+            cutoff_distance: None,
+            position: entity.position.map(|c| c * scale),
+            id: entity.id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity.properties.clone(),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+/// The built-in light style patterns, indexed by the `style` keyvalue.
+const LIGHT_STYLES: &[&str] = &[
+    "m",
+    "mmnmmommommnonmmonqnmmo",
+    "abcdefghijklmnopqrstuvwxyzyxwvutsrqponmlkjihgfedcba",
+    "mmmmmaaaaammmmmaaaaaabcdefgabcdefg",
+    "mamamamamama",
+    "jklmnopqrstuvwxyzyxwvutsrqponmlkj",
+    "nmonqnmomnmomomno",
+    "mmmaaaabcdefgmmmmaaaammmaamm",
+    "mmmaaammmaaammmabcdefaaaammmmabcdefmmmaaaa",
+    "aaaaaaaazzzzzzzz",
+    "mmamammmmammamamaaamammma",
+    "abcdefghijklmnopqrrqponmlkjihgfedcba",
+    "mmnnmmnnnmmnn",
+];
+
+/// Light style patterns advance at 10 characters per second.
+const LIGHT_STYLE_FPS: f32 = 10.0;
+
+/// Returns flat `[frame, energy]` keyframe pairs for an animated light style,
+/// or an empty vec if the light is not animated.
+fn light_style_keyframes(entity: &Entity, energy: f32, target_fps: f32) -> Vec<f32> {
+    let pattern = get_property(entity, "pattern")
+        .filter(|p| !p.is_empty())
+        .or_else(|| {
+            parse_property::<usize>(entity, "style")
+                .and_then(|style| LIGHT_STYLES.get(style).copied())
+        });
+
+    match pattern {
+        Some(pattern) if pattern.len() > 1 => pattern_keyframes(pattern, energy, target_fps),
+        _ => Vec::new(),
+    }
+}
+
+fn pattern_keyframes(pattern: &str, energy: f32, target_fps: f32) -> Vec<f32> {
+    let time_factor = target_fps / LIGHT_STYLE_FPS;
+
+    pattern
+        .bytes()
+        .map(|c| c.to_ascii_lowercase().clamp(b'a', b'z'))
+        .enumerate()
+        .flat_map(|(i, c)| {
+            // `a` is fully dark, `m` is the normal brightness and `z` is double brightness
+            let factor = f32::from(c - b'a') / f32::from(b'm' - b'a');
+
+            [(i as f32 * time_factor) + 1.0, energy * factor]
+        })
+        .collect()
+}
+
 fn get_light_rotation(rotation: [f32; 3]) -> [f32; 3] {
     let rotation_quat = Quat::from_euler(
         EulerRot::ZYX,
@@ -187,49 +448,70 @@ fn get_light_rotation(rotation: [f32; 3]) -> [f32; 3] {
     [x, y, z]
 }
 
-#[pyclass(module = "plumber", name = "SpotLight")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "SpotLight"))]
 pub struct PySpotLight {
     color: [f32; 3],
     energy: f32,
+    energy_keyframes: Vec<f32>,
+    cutoff_distance: Option<f32>,
     spot_size: f32,
     spot_blend: f32,
     position: [f32; 3],
     rotation: [f32; 3],
     pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
     properties: BTreeMap<String, String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PySpotLight {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn energy_keyframes(&mut self) -> Vec<f32> {
+        mem::take(&mut self.energy_keyframes)
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 
-    fn color(&self) -> [f32; 3] {
+    pub fn color(&self) -> [f32; 3] {
         self.color
     }
 
-    fn energy(&self) -> f32 {
+    pub fn energy(&self) -> f32 {
         self.energy
     }
 
-    fn spot_size(&self) -> f32 {
+    /// Distance past which the light doesn't light anything.
+    pub fn cutoff_distance(&self) -> Option<f32> {
+        self.cutoff_distance
+    }
+
+    pub fn spot_size(&self) -> f32 {
         self.spot_size
     }
 
-    fn spot_blend(&self) -> f32 {
+    pub fn spot_blend(&self) -> f32 {
         self.spot_blend
     }
 
-    fn properties(&mut self) -> BTreeMap<String, String> {
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
         mem::take(&mut self.properties)
     }
 }
@@ -239,6 +521,7 @@ impl PySpotLight {
         light: SpotLight,
         settings: &LightSettings,
         scale: f32,
+        target_fps: f32,
     ) -> Result<Self, EntityParseError> {
         let (color, brightness) =
             if let Some((hdr_color, hdr_brightness)) = light.hdr_color_brightness()? {
@@ -265,20 +548,710 @@ impl PySpotLight {
             .map(|(k, v)| (k.as_str().to_owned(), v.clone()))
             .collect();
 
+        let energy = brightness * settings.light_factor;
+
         Ok(Self {
             color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
-            energy: brightness * settings.light_factor,
+            energy,
+            energy_keyframes: light_style_keyframes(light.entity(), energy, target_fps),
+            cutoff_distance: None,
             spot_size,
             spot_blend,
             position,
             rotation,
             id,
+            parent_id: None,
+            editor_groups: None,
             properties,
         })
     }
 }
 
-#[pyclass(module = "plumber", name = "EnvLight")]
+impl PySpotLight {
+    pub fn from_light_dynamic(
+        entity: &Unknown,
+        settings: &LightSettings,
+        scale: f32,
+        target_fps: f32,
+    ) -> Result<Self, EntityParseError> {
+        let (color, brightness) = light_dynamic_color_brightness(entity.entity());
+        let energy = brightness * settings.light_factor;
+
+        let outer_cone = parse_property(entity.entity(), "_cone").unwrap_or(45.0_f32);
+        let inner_cone = parse_property(entity.entity(), "_inner_cone").unwrap_or(30.0_f32);
+
+        let mut angles = entity.angles().unwrap_or_default();
+
+        // the pitch keyvalue overrides the pitch of angles, with the sign flipped
+        if let Some(pitch) = parse_property::<f32>(entity.entity(), "pitch") {
+            angles[0] = -pitch;
+        }
+
+        Ok(Self {
+            color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
+            energy,
+            energy_keyframes: light_style_keyframes(entity.entity(), energy, target_fps),
+            cutoff_distance: light_dynamic_distance(entity.entity(), scale),
+            spot_size: outer_cone.to_radians() * 2.,
+            spot_blend: 1. - inner_cone / outer_cone,
+            position: (entity.origin()? * scale).into(),
+            rotation: get_light_rotation(angles),
+            id: entity.entity().id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(entity.entity()),
+        })
+    }
+}
+
+impl PySpotLight {
+    /// Creates a spot light from a Source 2 `light_spot` entity.
+    pub fn from_vmap(entity: &VmapEntity, settings: &LightSettings, scale: f32) -> Self {
+        let (color, energy) = vmap_color_energy(entity, settings);
+
+        let outer_cone = entity.property("outerconeangle").unwrap_or(45.0_f32);
+        let inner_cone = entity.property("innerconeangle").unwrap_or(30.0_f32);
+
+        Self {
+            color,
+            energy,
+            energy_keyframes: Vec::new(),
+            cutoff_distance: None,
+            spot_size: outer_cone.to_radians() * 2.,
+            spot_blend: 1. - inner_cone / outer_cone,
+            position: entity.position.map(|c| c * scale),
+            rotation: get_light_rotation(entity.angles),
+            id: entity.id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity.properties.clone(),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "PointSpotlight")
+)]
+pub struct PyPointSpotlight {
+    color: [f32; 3],
+    energy: f32,
+    spot_size: f32,
+    beam_length: f32,
+    beam_width: f32,
+    hdr_scale: f32,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyPointSpotlight {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn rotation(&self) -> [f32; 3] {
+        self.rotation
+    }
+
+    pub fn color(&self) -> [f32; 3] {
+        self.color
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    pub fn spot_size(&self) -> f32 {
+        self.spot_size
+    }
+
+    pub fn beam_length(&self) -> f32 {
+        self.beam_length
+    }
+
+    pub fn beam_width(&self) -> f32 {
+        self.beam_width
+    }
+
+    pub fn hdr_scale(&self) -> f32 {
+        self.hdr_scale
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PyPointSpotlight {
+    pub fn new(
+        entity: &Unknown,
+        settings: &LightSettings,
+        scale: f32,
+    ) -> Result<Self, EntityParseError> {
+        let color = get_property(entity.entity(), "rendercolor")
+            .and_then(parse_color_brightness)
+            .map_or(RGB8::new(255, 255, 255), |(color, _)| color);
+
+        // a beam without length would have no cone angle
+        let beam_length = parse_property(entity.entity(), "spotlightlength")
+            .filter(|&length: &f32| length > 0.0)
+            .unwrap_or(500.0);
+        let beam_width = parse_property(entity.entity(), "spotlightwidth").unwrap_or(50.0_f32);
+        let hdr_scale = parse_property(entity.entity(), "HDRColorScale").unwrap_or(1.0);
+
+        // the beam is a cone starting from a point, widening to the full width at its end
+        let spot_size = 2. * (beam_width * 0.5 / beam_length).atan();
+
+        Ok(Self {
+            color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
+            energy: 255. * hdr_scale * settings.light_factor,
+            spot_size,
+            beam_length: beam_length * scale,
+            beam_width: beam_width * scale,
+            hdr_scale,
+            position: (entity.origin()? * scale).into(),
+            rotation: get_light_rotation(entity.angles().unwrap_or_default()),
+            id: entity.entity().id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(entity.entity()),
+        })
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "ProjectedTexture")
+)]
+pub struct PyProjectedTexture {
+    color: [f32; 3],
+    energy: f32,
+    fov: f32,
+    texture: Option<String>,
+    texture_format: TextureFormat,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyProjectedTexture {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn rotation(&self) -> [f32; 3] {
+        self.rotation
+    }
+
+    pub fn color(&self) -> [f32; 3] {
+        self.color
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn texture(&self) -> Option<&str> {
+        self.texture.as_deref()
+    }
+
+    pub fn texture_ext(&self) -> &str {
+        self.texture_format.to_ext_str()
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PyProjectedTexture {
+    pub fn new(
+        entity: &Unknown,
+        settings: &LightSettings,
+        texture_format: TextureFormat,
+        scale: f32,
+    ) -> Result<Self, EntityParseError> {
+        let (color, brightness) = get_property(entity.entity(), "lightcolor")
+            .and_then(parse_color_brightness)
+            .unwrap_or((RGB8::new(255, 255, 255), 200.0));
+
+        let fov = parse_property(entity.entity(), "lightfov").unwrap_or(90.0_f32);
+
+        Ok(Self {
+            color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
+            energy: brightness * settings.light_factor,
+            fov: fov.to_radians(),
+            texture: projected_texture_path(entity.entity()).map(GamePathBuf::into_string),
+            texture_format,
+            position: (entity.origin()? * scale).into(),
+            rotation: get_light_rotation(entity.angles().unwrap_or_default()),
+            id: entity.entity().id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(entity.entity()),
+        })
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+/// Returns the textures projected by the `env_projectedtexture` entities of a vmf.
+pub fn projected_texture_paths(vmf: &Vmf) -> Vec<GamePathBuf> {
+    vmf.entities
+        .iter()
+        .filter(|e| e.class_name == "env_projectedtexture")
+        .filter_map(projected_texture_path)
+        .collect()
+}
+
+fn projected_texture_path(entity: &Entity) -> Option<GamePathBuf> {
+    let texture_name = get_property(entity, "texturename").filter(|t| !t.is_empty())?;
+
+    let mut path = GamePathBuf::from(format!("materials/{texture_name}"));
+    path.set_extension("");
+
+    Some(path)
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Sun"))]
+pub struct PySun {
+    color: [f32; 3],
+    overlay_color: [f32; 3],
+    size: f32,
+    overlay_size: f32,
+    hdr_scale: f32,
+    material: Option<String>,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PySun {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn rotation(&self) -> [f32; 3] {
+        self.rotation
+    }
+
+    pub fn color(&self) -> [f32; 3] {
+        self.color
+    }
+
+    pub fn overlay_color(&self) -> [f32; 3] {
+        self.overlay_color
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn overlay_size(&self) -> f32 {
+        self.overlay_size
+    }
+
+    pub fn hdr_scale(&self) -> f32 {
+        self.hdr_scale
+    }
+
+    pub fn material(&self) -> Option<&str> {
+        self.material.as_deref()
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PySun {
+    pub fn new(entity: &Unknown, scale: f32) -> Result<Self, EntityParseError> {
+        let color = get_property(entity.entity(), "rendercolor")
+            .and_then(parse_color_brightness)
+            .map_or(RGB8::new(100, 80, 80), |(color, _)| color);
+
+        let overlay_color = get_property(entity.entity(), "overlaycolor")
+            .and_then(parse_color_brightness)
+            .map_or(RGB8::new(0, 0, 0), |(color, _)| color);
+
+        let size = parse_property(entity.entity(), "size").unwrap_or(16.0);
+        // a negative overlay size means the same size as the sun itself
+        let overlay_size = parse_property(entity.entity(), "overlaysize")
+            .filter(|&s: &f32| s >= 0.0)
+            .unwrap_or(size);
+
+        let mut angles = entity.angles().unwrap_or_default();
+
+        if let Some(pitch) = parse_property::<f32>(entity.entity(), "pitch") {
+            angles[0] = -pitch;
+        }
+
+        Ok(Self {
+            color: color.map(|c| srgb_to_linear(f32::from(c) / 255.)).into(),
+            overlay_color: overlay_color
+                .map(|c| srgb_to_linear(f32::from(c) / 255.))
+                .into(),
+            size,
+            overlay_size,
+            hdr_scale: parse_property(entity.entity(), "HDRColorScale").unwrap_or(1.0),
+            material: get_property(entity.entity(), "material")
+                .filter(|m| !m.is_empty())
+                .map(str::to_owned),
+            position: (entity.origin().unwrap_or_default() * scale).into(),
+            rotation: get_light_rotation(angles),
+            id: entity.entity().id,
+            editor_groups: None,
+            properties: entity_properties(entity.entity()),
+        })
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "LightGlow"))]
+pub struct PyLightGlow {
+    class_name: String,
+    color: [f32; 4],
+    size: [f32; 2],
+    hdr_scale: f32,
+    material: Option<String>,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyLightGlow {
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn rotation(&self) -> [f32; 3] {
+        self.rotation
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    pub fn hdr_scale(&self) -> f32 {
+        self.hdr_scale
+    }
+
+    pub fn material(&self) -> Option<&str> {
+        self.material.as_deref()
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PyLightGlow {
+    /// Creates a glow from an `env_lightglow`, which is sized by its keyvalues.
+    pub fn new(entity: &Unknown, scale: f32) -> Result<Self, EntityParseError> {
+        let width = parse_property(entity.entity(), "HorizontalGlowSize").unwrap_or(30.0);
+        let height = parse_property(entity.entity(), "VerticalGlowSize").unwrap_or(30.0);
+
+        Ok(Self::from_entity(
+            entity.entity(),
+            entity.origin()?.into(),
+            [width, height],
+            scale,
+        ))
+    }
+
+    /// Creates a glow from an `env_glow`, which is sized by its sprite.
+    pub fn from_sprite(sprite: &GlowSprite, scale: f32) -> Self {
+        let mut glow = Self::from_entity(&sprite.entity, sprite.origin, sprite.size, scale);
+        glow.material = get_property(&sprite.entity, "model")
+            .filter(|m| !m.is_empty())
+            .map(str::to_owned);
+
+        glow
+    }
+
+    fn from_entity(entity: &Entity, origin: [f32; 3], size: [f32; 2], scale: f32) -> Self {
+        let color = get_property(entity, "rendercolor")
+            .and_then(parse_color_brightness)
+            .map_or(RGB8::new(255, 255, 255), |(color, _)| color);
+        let alpha = parse_property(entity, "renderamt").unwrap_or(255.0_f32);
+
+        Self {
+            class_name: entity.class_name.clone(),
+            color: color
+                .map(|c| srgb_to_linear(f32::from(c) / 255.))
+                .alpha(alpha / 255.)
+                .into(),
+            size: size.map(|c| c * scale),
+            hdr_scale: parse_property(entity, "HDRColorScale").unwrap_or(1.0),
+            material: None,
+            position: origin.map(|c| c * scale),
+            rotation: {
+                let rotation = get_property(entity, "angles")
+                    .and_then(parse_vector)
+                    .unwrap_or_default();
+                [
+                    rotation[2].to_radians(),
+                    rotation[0].to_radians(),
+                    rotation[1].to_radians(),
+                ]
+            },
+            id: entity.id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(entity),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+/// Effect entities imported as placeholders of their smoke or steam jet.
+pub const SMOKE_CLASS_NAMES: [&str; 2] = ["env_smokestack", "env_steam"];
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Smoke"))]
+pub struct PySmoke {
+    class_name: String,
+    color: [f32; 4],
+    start_size: f32,
+    end_size: f32,
+    length: f32,
+    speed: f32,
+    rate: f32,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
+    properties: BTreeMap<String, String>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PySmoke {
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    /// Rotation pointing the x axis in the direction of the jet.
+    pub fn rotation(&self) -> [f32; 3] {
+        self.rotation
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    /// Diameter of the particles where they are emitted.
+    pub fn start_size(&self) -> f32 {
+        self.start_size
+    }
+
+    /// Diameter of the particles at the end of the jet.
+    pub fn end_size(&self) -> f32 {
+        self.end_size
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Emitted particles per second.
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
+        mem::take(&mut self.properties)
+    }
+}
+
+impl PySmoke {
+    pub fn new(entity: &Unknown, scale: f32) -> Result<Self, EntityParseError> {
+        let keyvalues = entity.entity();
+
+        let color = get_property(keyvalues, "rendercolor")
+            .and_then(parse_color_brightness)
+            .map_or(RGB8::new(255, 255, 255), |(color, _)| color);
+        let alpha = parse_property(keyvalues, "renderamt").unwrap_or(255.0_f32);
+
+        // the defaults of the fgd, smoke stacks always rise while steam follows the angles
+        let (angles, [start_size, end_size, length, speed, rate]) =
+            if keyvalues.class_name == "env_smokestack" {
+                ([-90.0, 0.0, 0.0], [20.0, 30.0, 180.0, 30.0, 20.0])
+            } else {
+                (
+                    entity.angles().unwrap_or_default(),
+                    [10.0, 25.0, 80.0, 120.0, 26.0],
+                )
+            };
+
+        let property = |key, default| parse_property(keyvalues, key).unwrap_or(default);
+
+        Ok(Self {
+            class_name: keyvalues.class_name.clone(),
+            color: color
+                .map(|c| srgb_to_linear(f32::from(c) / 255.))
+                .alpha(alpha / 255.)
+                .into(),
+            start_size: property("StartSize", start_size) * scale,
+            end_size: property("EndSize", end_size) * scale,
+            length: property("JetLength", length) * scale,
+            speed: property("Speed", speed) * scale,
+            rate: property("Rate", rate),
+            position: (entity.origin()? * scale).into(),
+            rotation: [
+                angles[2].to_radians(),
+                angles[0].to_radians(),
+                angles[1].to_radians(),
+            ],
+            id: keyvalues.id,
+            parent_id: None,
+            editor_groups: None,
+            properties: entity_properties(keyvalues),
+        })
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+/// Returns true if a `light_dynamic` entity should be imported as a spot light.
+pub fn is_light_dynamic_spot(entity: &Unknown) -> bool {
+    parse_property(entity.entity(), "spotlight_radius").is_some_and(|radius: f32| radius > 0.0)
+}
+
+fn light_dynamic_color_brightness(entity: &Entity) -> (RGB8, f32) {
+    let (color, brightness) = get_property(entity, "_light")
+        .and_then(parse_color_brightness)
+        .unwrap_or((RGB8::new(255, 255, 255), 200.0));
+
+    // light_dynamic has an additional integer exponent for the brightness
+    let exponent = parse_property(entity, "brightness").unwrap_or(0_i32);
+
+    (color, brightness * 2.0_f32.powi(exponent))
+}
+
+/// The `distance` of a `light_dynamic` is a hard cutoff, where 0 lights everything.
+fn light_dynamic_distance(entity: &Entity, scale: f32) -> Option<f32> {
+    parse_property(entity, "distance")
+        .filter(|&distance: &f32| distance > 0.0)
+        .map(|distance| distance * scale)
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "EnvLight"))]
 pub struct PyEnvLight {
     sun_color: [f32; 3],
     sun_energy: f32,
@@ -288,43 +1261,48 @@ pub struct PyEnvLight {
     position: [f32; 3],
     rotation: [f32; 3],
     pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
     properties: BTreeMap<String, String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyEnvLight {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 
-    fn sun_color(&self) -> [f32; 3] {
+    pub fn sun_color(&self) -> [f32; 3] {
         self.sun_color
     }
 
-    fn sun_energy(&self) -> f32 {
+    pub fn sun_energy(&self) -> f32 {
         self.sun_energy
     }
 
-    fn ambient_color(&self) -> [f32; 4] {
+    pub fn ambient_color(&self) -> [f32; 4] {
         self.ambient_color
     }
 
-    fn ambient_strength(&self) -> f32 {
+    pub fn ambient_strength(&self) -> f32 {
         self.ambient_strength
     }
 
-    fn angle(&self) -> f32 {
+    pub fn angle(&self) -> f32 {
         self.angle
     }
-    fn properties(&mut self) -> BTreeMap<String, String> {
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
         mem::take(&mut self.properties)
     }
 }
@@ -379,81 +1357,176 @@ impl PyEnvLight {
             position,
             rotation,
             id,
+            editor_groups: None,
             properties,
         })
     }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
 }
 
-#[pyclass(module = "plumber", name = "SkyCamera")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "SkyCamera"))]
 pub struct PySkyCamera {
     pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
     position: [f32; 3],
     scale: [f32; 3],
+    fog_enabled: bool,
+    fog_blend: bool,
+    fog_color: [f32; 3],
+    fog_color2: [f32; 3],
+    fog_direction: [f32; 3],
+    fog_start: f32,
+    fog_end: f32,
+    fog_max_density: f32,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PySkyCamera {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
+
+    pub fn fog_enabled(&self) -> bool {
+        self.fog_enabled
+    }
+
+    pub fn fog_blend(&self) -> bool {
+        self.fog_blend
+    }
+
+    pub fn fog_color(&self) -> [f32; 3] {
+        self.fog_color
+    }
+
+    pub fn fog_color2(&self) -> [f32; 3] {
+        self.fog_color2
+    }
+
+    pub fn fog_direction(&self) -> [f32; 3] {
+        self.fog_direction
+    }
+
+    pub fn fog_start(&self) -> f32 {
+        self.fog_start
+    }
+
+    pub fn fog_end(&self) -> f32 {
+        self.fog_end
+    }
+
+    pub fn fog_max_density(&self) -> f32 {
+        self.fog_max_density
+    }
 }
 
 impl PySkyCamera {
     pub fn new(sky_camera: SkyCamera, scale: f32) -> Result<Self, EntityParseError> {
-        let id = sky_camera.entity().id;
+        let entity = sky_camera.entity();
+
+        let id = entity.id;
         let position = (sky_camera.origin()? * scale).into();
-        let scale = sky_camera.scale()?;
+        let sky_scale = sky_camera.scale()?;
+
+        let fog_color = |key| {
+            get_property(entity, key)
+                .and_then(parse_color_brightness)
+                .map_or(RGB8::new(255, 255, 255), |(color, _)| color)
+                .map(|c| srgb_to_linear(f32::from(c) / 255.))
+                .into()
+        };
+
+        // the fog distances are in the scale of the 3D skybox, not the map
+        let fog_start = parse_property(entity, "fogstart").unwrap_or(500.0);
+        let fog_end = parse_property(entity, "fogend").unwrap_or(2000.0);
 
         Ok(Self {
             id,
+            editor_groups: None,
             position,
-            scale: [scale, scale, scale],
+            scale: [sky_scale, sky_scale, sky_scale],
+            fog_enabled: parse_property(entity, "fogenable").is_some_and(|v: i32| v != 0),
+            fog_blend: parse_property(entity, "fogblend").is_some_and(|v: i32| v != 0),
+            fog_color: fog_color("fogcolor"),
+            fog_color2: fog_color("fogcolor2"),
+            fog_direction: get_property(entity, "fogdir")
+                .and_then(parse_vector)
+                .unwrap_or([1.0, 0.0, 0.0]),
+            fog_start: fog_start * scale,
+            fog_end: fog_end * scale,
+            fog_max_density: parse_property(entity, "fogmaxdensity").unwrap_or(1.0),
         })
     }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.scale = up_axis.scale(self.scale);
+        self.fog_direction = up_axis.point(self.fog_direction);
+    }
 }
 
-#[pyclass(module = "plumber", name = "UnknownEntity")]
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "UnknownEntity")
+)]
 
 pub struct PyUnknownEntity {
     class_name: String,
     pub id: i32,
+    pub parent_id: Option<i32>,
+    pub editor_groups: Option<PyEditorGroups>,
     position: [f32; 3],
     rotation: [f32; 3],
     scale: [f32; 3],
     properties: BTreeMap<String, String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyUnknownEntity {
-    fn class_name(&self) -> &str {
+    pub fn class_name(&self) -> &str {
         &self.class_name
     }
 
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
 
-    fn properties(&mut self) -> BTreeMap<String, String> {
+    pub fn properties(&mut self) -> BTreeMap<String, String> {
         mem::take(&mut self.properties)
     }
 }
@@ -475,6 +1548,8 @@ impl PyUnknownEntity {
         Self {
             class_name,
             id,
+            parent_id: None,
+            editor_groups: None,
             position,
             rotation: [
                 rotation[2].to_radians(),
@@ -485,4 +1560,220 @@ impl PyUnknownEntity {
             properties,
         }
     }
+
+    pub fn from_vmap(entity: &VmapEntity, scale: f32) -> Self {
+        let [pitch, yaw, roll] = entity.angles;
+
+        Self {
+            class_name: entity.class_name.clone(),
+            id: entity.id,
+            parent_id: None,
+            editor_groups: None,
+            position: entity.position.map(|c| c * scale),
+            rotation: [roll.to_radians(), pitch.to_radians(), yaw.to_radians()],
+            scale: [scale, scale, scale],
+            properties: entity.properties.clone(),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.rotation = up_axis.rotation(self.rotation);
+    }
+}
+
+/// An `info_lighting` entity, referenced by the `lightingorigin` keyvalue of props.
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "LightingOrigin")
+)]
+pub struct PyLightingOrigin {
+    name: String,
+    pub id: i32,
+    position: [f32; 3],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyLightingOrigin {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl PyLightingOrigin {
+    /// Returns `None` if the entity has no targetname, since props can't reference it.
+    pub fn new(entity: &Unknown, scale: f32) -> Option<Self> {
+        let name = get_property(entity.entity(), "targetname").filter(|name| !name.is_empty())?;
+
+        Some(Self {
+            name: name.to_owned(),
+            id: entity.entity().id,
+            position: (entity.origin().unwrap_or_default() * scale).into(),
+        })
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+/// Entity classes where players spawn, in the order the map is recentered around them,
+/// with the generic one first.
+pub const PLAYER_SPAWN_CLASS_NAMES: &[&str] = &[
+    "info_player_start",
+    "info_player_terrorist",
+    "info_player_counterterrorist",
+    "info_player_teamspawn",
+    "info_player_deathmatch",
+];
+
+/// A player spawn entity, used for recentering the map around it.
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "PlayerSpawn"))]
+pub struct PyPlayerSpawn {
+    class_name: String,
+    pub id: i32,
+    position: [f32; 3],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyPlayerSpawn {
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    /// Index of the class in [`PLAYER_SPAWN_CLASS_NAMES`], where lower is preferred.
+    pub fn priority(&self) -> usize {
+        PLAYER_SPAWN_CLASS_NAMES
+            .iter()
+            .position(|&name| name == self.class_name)
+            .unwrap_or(PLAYER_SPAWN_CLASS_NAMES.len())
+    }
+}
+
+impl PyPlayerSpawn {
+    pub fn new(entity: &Unknown, scale: f32) -> Self {
+        Self {
+            class_name: entity.entity().class_name.clone(),
+            id: entity.entity().id,
+            position: (entity.origin().unwrap_or_default() * scale).into(),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+pub fn entity_properties(entity: &Entity) -> BTreeMap<String, String> {
+    entity
+        .properties
+        .iter()
+        .map(|(k, v)| (k.as_str().to_owned(), v.clone()))
+        .collect()
+}
+
+pub fn get_property<'a>(entity: &'a Entity, key: &str) -> Option<&'a str> {
+    entity
+        .properties
+        .iter()
+        .find(|(k, _)| k.as_str().eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+pub fn parse_property<T: FromStr>(entity: &Entity, key: &str) -> Option<T> {
+    get_property(entity, key).and_then(|v| v.trim().parse().ok())
+}
+
+/// Parses a `x y z` vector value.
+pub fn parse_vector(value: &str) -> Option<[f32; 3]> {
+    let mut parts = value.split_whitespace().map(str::parse);
+
+    let x = parts.next()?.ok()?;
+    let y = parts.next()?.ok()?;
+    let z = parts.next()?.ok()?;
+
+    Some([x, y, z])
+}
+
+/// Parses a `r g b brightness` color value, where brightness is optional.
+pub fn parse_color_brightness(value: &str) -> Option<(RGB8, f32)> {
+    let mut parts = value.split_whitespace();
+
+    let r = parts.next()?.parse().ok()?;
+    let g = parts.next()?.parse().ok()?;
+    let b = parts.next()?.parse().ok()?;
+    let brightness = parts.next().map_or(Some(255.0), |p| p.parse().ok())?;
+
+    Some((RGB8::new(r, g, b), brightness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_brightness_full() {
+        assert_eq!(
+            parse_color_brightness("255 128 0 200"),
+            Some((RGB8::new(255, 128, 0), 200.0))
+        );
+    }
+
+    #[test]
+    fn parse_color_brightness_no_brightness() {
+        assert_eq!(
+            parse_color_brightness("10 20 30"),
+            Some((RGB8::new(10, 20, 30), 255.0))
+        );
+    }
+
+    #[test]
+    fn pattern_keyframes_brightness() {
+        assert_eq!(
+            pattern_keyframes("amz", 12.0, 30.0),
+            vec![1.0, 0.0, 4.0, 12.0, 7.0, 25.0]
+        );
+    }
+
+    #[test]
+    fn reads_fade_distances() {
+        assert_eq!(fade_distances(-1.0, 0.0), None);
+        assert_eq!(fade_distances(512.0, 2048.0), Some([512.0, 2048.0]));
+        assert_eq!(fade_distances(-1.0, 1024.0), Some([1024.0, 1024.0]));
+        assert_eq!(fade_distances(4096.0, 1024.0), Some([1024.0, 1024.0]));
+    }
+
+    #[test]
+    fn instance_key_ignores_model_case() {
+        assert_eq!(
+            instance_key("models/Props/Crate.mdl", 1, 2),
+            instance_key("models/props/crate.mdl", 1, 2)
+        );
+        assert_ne!(
+            instance_key("models/props/crate.mdl", 0, 0),
+            instance_key("models/props/crate.mdl", 1, 0)
+        );
+    }
+
+    #[test]
+    fn parse_color_brightness_invalid() {
+        assert_eq!(parse_color_brightness("10 20"), None);
+        assert_eq!(parse_color_brightness("a b c"), None);
+    }
 }
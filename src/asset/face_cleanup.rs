@@ -0,0 +1,343 @@
+//! Removal of degenerate geometry from built brush meshes.
+//!
+//! Off-grid brushes and sewn displacements can produce faces with collinear or repeated
+//! vertices, or faces without any area. Blender warns about them and shades them with broken
+//! normals, so they are cleaned up before the meshes are sent.
+//!
+//! Solids are built separately, so the vertices where solids touch are only nearly coincident.
+//! Welding them gives connected meshes for sculpting and retopology.
+
+use std::{collections::HashMap, ops::AddAssign};
+
+use glam::Vec3;
+use plumber_core::vmf::builder::SolidFace;
+
+use super::seam::weld_groups;
+
+/// Distance in map units within which a vertex is considered to be on the line between its
+/// neighbors.
+const EPSILON: f32 = 0.001;
+
+/// Faces with a smaller area in square map units are removed.
+const MIN_AREA: f32 = EPSILON * EPSILON;
+
+/// Amounts of geometry removed by [`clean_faces`] or [`weld_vertices`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cleanup {
+    pub faces: usize,
+    pub loops: usize,
+    pub vertices: usize,
+}
+
+impl Cleanup {
+    pub fn is_empty(self) -> bool {
+        self.faces == 0 && self.loops == 0 && self.vertices == 0
+    }
+}
+
+impl AddAssign for Cleanup {
+    fn add_assign(&mut self, other: Self) {
+        self.faces += other.faces;
+        self.loops += other.loops;
+        self.vertices += other.vertices;
+    }
+}
+
+/// Removes collinear and repeated vertices from the faces, faces without area,
+/// and the vertices no face uses anymore.
+pub fn clean_faces(vertices: &mut Vec<f32>, faces: &mut Vec<SolidFace>) -> Cleanup {
+    let mut cleanup = Cleanup::default();
+
+    for face in faces.iter_mut() {
+        cleanup.loops += remove_collinear_corners(vertices, face);
+    }
+
+    let face_count = faces.len();
+    faces.retain(|face| face.vertice_indices.len() >= 3 && face_area(vertices, face) >= MIN_AREA);
+    cleanup.faces = face_count - faces.len();
+
+    if cleanup.loops > 0 || cleanup.faces > 0 {
+        cleanup.vertices = remove_unused_vertices(vertices, faces);
+    }
+
+    cleanup
+}
+
+/// Welds the vertices within `distance` of each other into one at their average position.
+/// Corners which end up on the same vertex as the previous corner are removed,
+/// along with faces which have less than three corners left.
+pub fn weld_vertices(
+    vertices: &mut Vec<f32>,
+    faces: &mut Vec<SolidFace>,
+    distance: f32,
+) -> Cleanup {
+    let mut cleanup = Cleanup::default();
+
+    if distance <= 0.0 {
+        return cleanup;
+    }
+
+    let positions: Vec<Vec3> = vertices.chunks_exact(3).map(Vec3::from_slice).collect();
+    let groups = weld_groups(&positions, distance);
+
+    let mut new_indices: HashMap<usize, usize> = HashMap::new();
+    let mut sums: Vec<(Vec3, f32)> = Vec::new();
+
+    for (&group, &position) in groups.iter().zip(&positions) {
+        let new = *new_indices.entry(group).or_insert_with(|| {
+            sums.push((Vec3::ZERO, 0.0));
+            sums.len() - 1
+        });
+
+        sums[new].0 += position;
+        sums[new].1 += 1.0;
+    }
+
+    if sums.len() == positions.len() {
+        return cleanup;
+    }
+
+    cleanup.vertices = positions.len() - sums.len();
+    *vertices = sums
+        .into_iter()
+        .flat_map(|(sum, count)| (sum / count).to_array())
+        .collect();
+
+    for face in faces.iter_mut() {
+        for index in &mut face.vertice_indices {
+            *index = new_indices[&groups[*index]];
+        }
+
+        cleanup.loops += remove_repeated_corners(face);
+    }
+
+    let face_count = faces.len();
+    faces.retain(|face| face.vertice_indices.len() >= 3);
+    cleanup.faces = face_count - faces.len();
+
+    if cleanup.faces > 0 {
+        cleanup.vertices += remove_unused_vertices(vertices, faces);
+    }
+
+    cleanup
+}
+
+fn position(vertices: &[f32], vertex: usize) -> Vec3 {
+    Vec3::from_slice(&vertices[vertex * 3..vertex * 3 + 3])
+}
+
+/// Removes corners which lie on the line between their neighbors, until there are none left.
+/// Returns the amount of removed corners.
+fn remove_collinear_corners(vertices: &[f32], face: &mut SolidFace) -> usize {
+    let mut removed = 0;
+    let mut corner = 0;
+    // corners checked since the last removal, every corner is checked once after it
+    let mut checked = 0;
+
+    while face.vertice_indices.len() >= 3 && checked < face.vertice_indices.len() {
+        let len = face.vertice_indices.len();
+        let [previous, current, next] = [corner + len - 1, corner, corner + 1]
+            .map(|i| position(vertices, face.vertice_indices[i % len]));
+
+        if is_collinear(previous, current, next) {
+            remove_corner(face, corner);
+            removed += 1;
+            checked = 0;
+            corner %= face.vertice_indices.len();
+        } else {
+            corner = (corner + 1) % len;
+            checked += 1;
+        }
+    }
+
+    removed
+}
+
+/// Removes corners on the same vertex as the previous corner. Returns the amount of removed corners.
+fn remove_repeated_corners(face: &mut SolidFace) -> usize {
+    let mut removed = 0;
+    let mut corner = 0;
+
+    while face.vertice_indices.len() > 1 && corner < face.vertice_indices.len() {
+        let len = face.vertice_indices.len();
+
+        if face.vertice_indices[corner] == face.vertice_indices[(corner + len - 1) % len] {
+            remove_corner(face, corner);
+            removed += 1;
+        } else {
+            corner += 1;
+        }
+    }
+
+    removed
+}
+
+fn is_collinear(previous: Vec3, current: Vec3, next: Vec3) -> bool {
+    let edge = next - previous;
+    let length = edge.length();
+
+    // the face folds back onto itself at this corner
+    if length < EPSILON {
+        return true;
+    }
+
+    (current - previous).cross(edge).length() / length < EPSILON
+}
+
+fn remove_corner(face: &mut SolidFace, corner: usize) {
+    face.vertice_indices.remove(corner);
+    face.vertice_uvs.remove(corner);
+    face.vertice_alphas.remove(corner);
+
+    if let Some(multiblends) = &mut face.vertice_multiblends {
+        multiblends.remove(corner);
+    }
+}
+
+fn face_area(vertices: &[f32], face: &SolidFace) -> f32 {
+    let corners: Vec<Vec3> = face
+        .vertice_indices
+        .iter()
+        .map(|&i| position(vertices, i))
+        .collect();
+
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .fold(Vec3::ZERO, |sum, (&a, &b)| sum + a.cross(b))
+        .length()
+        / 2.0
+}
+
+/// Removes the vertices no face uses and updates the vertex indices of the faces.
+/// Returns the amount of removed vertices.
+fn remove_unused_vertices(vertices: &mut Vec<f32>, faces: &mut [SolidFace]) -> usize {
+    let vertex_count = vertices.len() / 3;
+    let mut new_indices = vec![None; vertex_count];
+    let mut used = 0;
+
+    for face in faces.iter_mut() {
+        for index in &mut face.vertice_indices {
+            *index = *new_indices[*index].get_or_insert_with(|| {
+                used += 1;
+                used - 1
+            });
+        }
+    }
+
+    let mut compacted = vec![0.0; used * 3];
+
+    for (old, new) in new_indices.into_iter().enumerate() {
+        if let Some(new) = new {
+            compacted[new * 3..new * 3 + 3].copy_from_slice(&vertices[old * 3..old * 3 + 3]);
+        }
+    }
+
+    *vertices = compacted;
+    vertex_count - used
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+
+    fn face(vertice_indices: Vec<usize>) -> SolidFace {
+        SolidFace {
+            vertice_uvs: vec![Vec2::ZERO; vertice_indices.len()],
+            vertice_alphas: vec![0.0; vertice_indices.len()],
+            vertice_indices,
+            vertice_multiblends: None,
+            material_index: 0,
+        }
+    }
+
+    #[test]
+    fn removes_collinear_and_repeated_corners() {
+        let mut vertices = vec![
+            0.0, 0.0, 0.0, //
+            5.0, 0.0, 0.0, //
+            10.0, 0.0, 0.0, //
+            10.0, 10.0, 0.0, //
+            10.0, 10.0, 0.0, //
+            0.0, 10.0, 0.0,
+        ];
+        let mut faces = vec![face(vec![0, 1, 2, 3, 4, 5])];
+
+        let cleanup = clean_faces(&mut vertices, &mut faces);
+
+        assert_eq!(
+            cleanup,
+            Cleanup {
+                faces: 0,
+                loops: 2,
+                vertices: 2,
+            }
+        );
+        assert_eq!(faces[0].vertice_indices, vec![0, 1, 2, 3]);
+        assert_eq!(faces[0].vertice_uvs.len(), 4);
+        assert_eq!(
+            vertices,
+            vec![
+                0.0, 0.0, 0.0, //
+                10.0, 0.0, 0.0, //
+                10.0, 10.0, 0.0, //
+                0.0, 10.0, 0.0,
+            ]
+        );
+    }
+
+    #[test]
+    fn removes_faces_without_area() {
+        let mut vertices = vec![
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, //
+            2.0, 0.0, 0.0,
+        ];
+        let mut faces = vec![face(vec![0, 1, 2]), face(vec![0, 1, 3])];
+
+        let cleanup = clean_faces(&mut vertices, &mut faces);
+
+        assert_eq!(cleanup.faces, 1);
+        assert_eq!(cleanup.vertices, 1);
+        assert_eq!(faces.len(), 1);
+        assert_eq!(vertices.len(), 9);
+    }
+
+    #[test]
+    fn welds_close_vertices() {
+        // two triangles of separate solids, with a shared edge which is slightly apart
+        let mut vertices = vec![
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, //
+            1.0, 0.0, 0.01, //
+            0.0, 1.0, 0.01, //
+            1.0, 1.0, 0.0,
+        ];
+        let mut faces = vec![face(vec![0, 1, 2]), face(vec![4, 3, 5])];
+
+        let cleanup = weld_vertices(&mut vertices, &mut faces, 0.1);
+
+        assert_eq!(cleanup.vertices, 2);
+        assert_eq!(vertices.len(), 12);
+        assert_eq!(faces[0].vertice_indices, vec![0, 1, 2]);
+        assert_eq!(faces[1].vertice_indices, vec![2, 1, 3]);
+        assert!((vertices[5] - 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keeps_clean_faces() {
+        let mut vertices = vec![
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0,
+        ];
+        let mut faces = vec![face(vec![0, 1, 2])];
+
+        assert!(clean_faces(&mut vertices, &mut faces).is_empty());
+        assert_eq!(faces[0].vertice_indices, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,88 @@
+//! `env_glow` sprites, sized by their material like in game.
+//!
+//! The engine draws sprites at the size of their base texture in pixels multiplied by the
+//! `scale` keyvalue of the entity, so the size of the texture is read here.
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context, NoError},
+    asset_vmt::VmtHelper,
+    fs::PathBuf,
+    vmf::vmf::{Entity, Vmf},
+};
+
+use super::{
+    decal::{base_texture_size, material_path},
+    entities::{get_property, parse_property, parse_vector},
+    BlenderAssetHandler,
+};
+
+/// Size of sprites whose base texture can't be read, in pixels.
+const DEFAULT_SPRITE_SIZE: u16 = 32;
+
+#[derive(Debug, Clone)]
+pub struct EnvGlow {
+    entity: Entity,
+    origin: [f32; 3],
+}
+
+/// Returns the `env_glow` entities of a vmf.
+pub fn env_glows(vmf: &Vmf) -> Vec<EnvGlow> {
+    vmf.entities
+        .iter()
+        .filter(|e| e.class_name == "env_glow")
+        .filter_map(|entity| {
+            Some(EnvGlow {
+                origin: get_property(entity, "origin").and_then(parse_vector)?,
+                entity: entity.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Reads the base texture size of the sprite of an `env_glow`.
+#[derive(Debug, Clone, Copy)]
+pub struct GlowConfig;
+
+impl AssetConfig<BlenderAssetHandler> for GlowConfig {
+    type Input<'a> = EnvGlow;
+    type Output<'a> = GlowSprite;
+    type Error<'a> = NoError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        // glows with a missing sprite are still imported, at the default size
+        let texture_size = get_property(&input.entity, "model")
+            .filter(|m| !m.is_empty())
+            .and_then(|model| {
+                let vmt =
+                    VmtHelper::new(&PathBuf::from(material_path(model)), context.fs()).ok()?;
+                base_texture_size(&vmt, context.fs())
+            });
+
+        let [width, height] =
+            texture_size.map_or([DEFAULT_SPRITE_SIZE; 2], |(width, height)| [width, height]);
+        let sprite_scale = parse_property(&input.entity, "scale")
+            .filter(|&scale: &f32| scale > 0.0)
+            .unwrap_or(1.0);
+
+        Ok(GlowSprite {
+            size: [
+                f32::from(width) * sprite_scale,
+                f32::from(height) * sprite_scale,
+            ],
+            entity: input.entity,
+            origin: input.origin,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlowSprite {
+    pub entity: Entity,
+    pub origin: [f32; 3],
+    /// Width and height in map units.
+    pub size: [f32; 2],
+}
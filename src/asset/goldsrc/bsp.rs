@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+
+use super::{f32_at, i32_at, slice, u16_at, u32_at, wad::MipTexture, ParseError};
+
+const BSP_VERSION: u32 = 30;
+
+const LUMP_ENTITIES: usize = 0;
+const LUMP_TEXTURES: usize = 2;
+const LUMP_VERTICES: usize = 3;
+const LUMP_TEXINFO: usize = 6;
+const LUMP_FACES: usize = 7;
+const LUMP_EDGES: usize = 12;
+const LUMP_SURFEDGES: usize = 13;
+const LUMP_MODELS: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TexInfo {
+    /// Texture space s axis and offset.
+    pub s: [f32; 4],
+    /// Texture space t axis and offset.
+    pub t: [f32; 4],
+    pub texture: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Face {
+    pub first_edge: usize,
+    pub edge_count: usize,
+    pub texinfo: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Model {
+    pub first_face: usize,
+    pub face_count: usize,
+}
+
+/// The lumps of a GoldSrc BSP v30 map needed to rebuild its visible geometry.
+#[derive(Debug, Clone)]
+pub struct Bsp {
+    pub entities: String,
+    /// Missing textures are `None`.
+    pub textures: Vec<Option<MipTexture>>,
+    pub vertices: Vec<[f32; 3]>,
+    pub edges: Vec<[u16; 2]>,
+    pub surfedges: Vec<i32>,
+    pub texinfos: Vec<TexInfo>,
+    pub faces: Vec<Face>,
+    pub models: Vec<Model>,
+}
+
+impl Bsp {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let version = u32_at(bytes, 0)?;
+
+        if version != BSP_VERSION {
+            return Err(ParseError::UnsupportedVersion(version));
+        }
+
+        let lump = |index| lump(bytes, index);
+
+        let entities = String::from_utf8_lossy(lump(LUMP_ENTITIES)?)
+            .trim_end_matches('\0')
+            .to_owned();
+
+        let textures = parse_textures(lump(LUMP_TEXTURES)?)?;
+
+        let vertices = lump(LUMP_VERTICES)?
+            .chunks_exact(12)
+            .map(|c| Ok([f32_at(c, 0)?, f32_at(c, 4)?, f32_at(c, 8)?]))
+            .collect::<Result<_, _>>()?;
+
+        let edges = lump(LUMP_EDGES)?
+            .chunks_exact(4)
+            .map(|c| Ok([u16_at(c, 0)?, u16_at(c, 2)?]))
+            .collect::<Result<_, _>>()?;
+
+        let surfedges = lump(LUMP_SURFEDGES)?
+            .chunks_exact(4)
+            .map(|c| i32_at(c, 0))
+            .collect::<Result<_, _>>()?;
+
+        let texinfos = lump(LUMP_TEXINFO)?
+            .chunks_exact(40)
+            .map(|c| {
+                Ok(TexInfo {
+                    s: [f32_at(c, 0)?, f32_at(c, 4)?, f32_at(c, 8)?, f32_at(c, 12)?],
+                    t: [
+                        f32_at(c, 16)?,
+                        f32_at(c, 20)?,
+                        f32_at(c, 24)?,
+                        f32_at(c, 28)?,
+                    ],
+                    texture: u32_at(c, 32)? as usize,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let faces = lump(LUMP_FACES)?
+            .chunks_exact(20)
+            .map(|c| {
+                Ok(Face {
+                    first_edge: u32_at(c, 4)? as usize,
+                    edge_count: usize::from(u16_at(c, 8)?),
+                    texinfo: usize::from(u16_at(c, 10)?),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let models = lump(LUMP_MODELS)?
+            .chunks_exact(64)
+            .map(|c| {
+                Ok(Model {
+                    first_face: u32_at(c, 56)? as usize,
+                    face_count: u32_at(c, 60)? as usize,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            entities,
+            textures,
+            vertices,
+            edges,
+            surfedges,
+            texinfos,
+            faces,
+            models,
+        })
+    }
+
+    /// Returns the vertex indices of a face, or `None` if the face references missing data.
+    pub fn face_vertices(&self, face: &Face) -> Option<Vec<usize>> {
+        self.surfedges
+            .get(face.first_edge..face.first_edge + face.edge_count)?
+            .iter()
+            .map(|&surfedge| {
+                // negative surfedges use the edge in reverse direction
+                let edge = self.edges.get(surfedge.unsigned_abs() as usize)?;
+                let vertex = if surfedge >= 0 { edge[0] } else { edge[1] };
+                Some(usize::from(vertex))
+            })
+            .collect()
+    }
+}
+
+fn lump(bytes: &[u8], index: usize) -> Result<&[u8], ParseError> {
+    let offset = u32_at(bytes, 4 + index * 8)? as usize;
+    let len = u32_at(bytes, 8 + index * 8)? as usize;
+    slice(bytes, offset, len)
+}
+
+fn parse_textures(lump: &[u8]) -> Result<Vec<Option<MipTexture>>, ParseError> {
+    if lump.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let count = u32_at(lump, 0)? as usize;
+
+    (0..count)
+        .map(|i| {
+            let offset = i32_at(lump, 4 + i * 4)?;
+
+            // missing textures have a negative offset
+            match usize::try_from(offset) {
+                Ok(offset) => {
+                    MipTexture::parse(lump.get(offset..).ok_or(ParseError::Eof)?).map(Some)
+                }
+                Err(_) => Ok(None),
+            }
+        })
+        .collect()
+}
+
+/// Parses the entity lump into the key values of each entity.
+pub fn parse_entities(text: &str) -> Vec<BTreeMap<String, String>> {
+    let mut entities = Vec::new();
+    let mut current: Option<BTreeMap<String, String>> = None;
+    let mut key: Option<&str> = None;
+    let mut rest = text;
+
+    loop {
+        rest = rest.trim_start();
+
+        if let Some(after) = rest.strip_prefix('{') {
+            current = Some(BTreeMap::new());
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('}') {
+            entities.extend(current.take());
+            key = None;
+            rest = after;
+        } else if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let token = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+
+            match key.take() {
+                Some(k) => {
+                    if let Some(entity) = &mut current {
+                        entity.insert(k.to_ascii_lowercase(), token.to_owned());
+                    }
+                }
+                None => key = Some(token),
+            }
+        } else if let Some(c) = rest.chars().next() {
+            // skip anything unexpected
+            rest = &rest[c.len_utf8()..];
+        } else {
+            break;
+        }
+    }
+
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entities() {
+        let entities = parse_entities(
+            "{\n\"classname\" \"worldspawn\"\n\"wad\" \"\\half-life\\valve\\halflife.wad\"\n}\n\
+             {\n\"model\" \"*1\"\n\"classname\" \"func_door\"\n}\n",
+        );
+
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0]["classname"], "worldspawn");
+        assert_eq!(entities[0]["wad"], "\\half-life\\valve\\halflife.wad");
+        assert_eq!(entities[1]["model"], "*1");
+    }
+
+    #[test]
+    fn rejects_source_bsp() {
+        let mut bytes = b"VBSP".to_vec();
+        bytes.extend([0; 64]);
+
+        assert!(matches!(
+            Bsp::parse(&bytes),
+            Err(ParseError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn follows_surfedge_direction() {
+        let bsp = Bsp {
+            entities: String::new(),
+            textures: Vec::new(),
+            vertices: Vec::new(),
+            edges: vec![[0, 0], [0, 1], [1, 2], [0, 2]],
+            surfedges: vec![1, 2, -3],
+            texinfos: Vec::new(),
+            faces: Vec::new(),
+            models: Vec::new(),
+        };
+        let face = Face {
+            first_edge: 0,
+            edge_count: 3,
+            texinfo: 0,
+        };
+
+        assert_eq!(bsp.face_vertices(&face), Some(vec![0, 1, 2]));
+    }
+}
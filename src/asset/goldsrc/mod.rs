@@ -0,0 +1,488 @@
+//! Importing GoldSrc (Half-Life 1, Counter-Strike 1.6) maps.
+//!
+//! The geometry is rebuilt from the faces of the compiled BSP v30 map,
+//! and textures are read from the map or from the WAD3 files it references.
+
+mod bsp;
+mod wad;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io, mem,
+};
+
+use image::DynamicImage;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use tracing::{info_span, warn};
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::{GamePathBuf, PathBuf},
+};
+
+use self::{
+    bsp::{parse_entities, Bsp, Model, TexInfo},
+    wad::{MipTexture, Wad},
+};
+
+#[cfg(feature = "python")]
+use super::buffer::{float_buffer, int_buffer};
+use super::{up_axis::UpAxis, BlenderAssetHandler};
+
+/// Tool textures which aren't drawn in game.
+const INVISIBLE_TEXTURES: &[&str] = &[
+    "aaatrigger",
+    "clip",
+    "hint",
+    "null",
+    "origin",
+    "skip",
+    "sky",
+];
+
+#[derive(Debug)]
+pub enum GoldSrcError {
+    Io(PathBuf, io::Error),
+    UnsupportedVersion(PathBuf, u32),
+    Corrupted(PathBuf),
+}
+
+impl Display for GoldSrcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "bsp `{path}`: {err}"),
+            Self::UnsupportedVersion(path, version) => {
+                write!(
+                    f,
+                    "bsp `{path}`: unsupported version {version}, expected 30"
+                )
+            }
+            Self::Corrupted(path) => write!(f, "bsp `{path}`: unexpected end of file"),
+        }
+    }
+}
+
+impl Error for GoldSrcError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidMagic,
+    UnsupportedVersion(u32),
+    Eof,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => f.write_str("invalid magic"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::Eof => f.write_str("unexpected end of file"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+fn slice(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], ParseError> {
+    bytes.get(offset..offset + len).ok_or(ParseError::Eof)
+}
+
+fn array_at<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], ParseError> {
+    Ok(slice(bytes, offset, N)?
+        .try_into()
+        .expect("slice should have the correct length"))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, ParseError> {
+    array_at(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, ParseError> {
+    array_at(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn i32_at(bytes: &[u8], offset: usize) -> Result<i32, ParseError> {
+    array_at(bytes, offset).map(i32::from_le_bytes)
+}
+
+fn f32_at(bytes: &[u8], offset: usize) -> Result<f32, ParseError> {
+    array_at(bytes, offset).map(f32::from_le_bytes)
+}
+
+/// Reads a null-terminated name from a 16 byte field.
+fn name_at(bytes: &[u8], offset: usize) -> Result<String, ParseError> {
+    let name = slice(bytes, offset, 16)?;
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+
+    Ok(String::from_utf8_lossy(&name[..end]).into_owned())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldSrcFace {
+    pub vertice_indices: Vec<usize>,
+    pub vertice_uvs: Vec<[f32; 2]>,
+    pub material_index: usize,
+}
+
+/// The visible faces of the world or a brush entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldSrcModel {
+    pub name: String,
+    pub position: [f32; 3],
+    pub vertices: Vec<[f32; 3]>,
+    pub faces: Vec<GoldSrcFace>,
+    pub materials: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GoldSrcMap {
+    pub name: String,
+    pub textures: Vec<(String, DynamicImage)>,
+    pub models: Vec<GoldSrcModel>,
+}
+
+/// Loads a GoldSrc `.bsp` map with its textures.
+#[derive(Debug, Clone, Copy)]
+pub struct GoldSrcConfig;
+
+impl AssetConfig<BlenderAssetHandler> for GoldSrcConfig {
+    type Input<'a> = PathBuf;
+    type Output<'a> = GoldSrcMap;
+    type Error<'a> = GoldSrcError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let _span = info_span!("goldsrc map parse", id = %input).entered();
+
+        let bytes = context
+            .fs()
+            .read(&input)
+            .map_err(|e| GoldSrcError::Io(input.clone(), e))?;
+
+        let bsp = match Bsp::parse(&bytes) {
+            Ok(bsp) => bsp,
+            Err(ParseError::UnsupportedVersion(version)) => {
+                return Err(GoldSrcError::UnsupportedVersion(input, version))
+            }
+            Err(_) => return Err(GoldSrcError::Corrupted(input)),
+        };
+
+        let entities = parse_entities(&bsp.entities);
+        let worldspawn = entities
+            .iter()
+            .find(|e| e.get("classname").map(String::as_str) == Some("worldspawn"));
+
+        let wads = worldspawn
+            .and_then(|e| e.get("wad"))
+            .map(|paths| load_wads(paths, context))
+            .unwrap_or_default();
+
+        let models: Vec<_> = bsp
+            .models
+            .iter()
+            .enumerate()
+            .filter_map(|(index, model)| {
+                let entity = if index == 0 {
+                    worldspawn
+                } else {
+                    let model_key = format!("*{index}");
+                    entities.iter().find(|e| e.get("model") == Some(&model_key))
+                };
+
+                let name = match entity.and_then(|e| e.get("classname")) {
+                    Some(class_name) if index != 0 => format!("{class_name}_{index}"),
+                    Some(class_name) => class_name.clone(),
+                    None => format!("model_{index}"),
+                };
+
+                // brush entities with an origin brush are built around their origin
+                let position = entity
+                    .and_then(|e| e.get("origin"))
+                    .and_then(|o| parse_vector(o))
+                    .unwrap_or_default();
+
+                build_model(&bsp, model, name, position)
+            })
+            .collect();
+
+        let used_textures: BTreeSet<_> = models.iter().flat_map(|m| &m.materials).collect();
+
+        let textures = used_textures
+            .into_iter()
+            .filter_map(|name| {
+                let image = bsp
+                    .textures
+                    .iter()
+                    .flatten()
+                    .find(|t| t.has_pixels() && t.name.eq_ignore_ascii_case(name))
+                    .or_else(|| wads.iter().find_map(|w| w.get(name)))
+                    .and_then(MipTexture::to_image);
+
+                if image.is_none() {
+                    warn!("bsp `{}`: texture `{}` not found", input, name);
+                }
+
+                image.map(|image| (name.clone(), image))
+            })
+            .collect();
+
+        Ok(GoldSrcMap {
+            name: input.to_string(),
+            textures,
+            models,
+        })
+    }
+}
+
+/// Loads the WAD files listed in the `wad` key of worldspawn.
+/// The paths are absolute paths of the mapper's computer,
+/// so the files are searched from the game root by their names.
+fn load_wads(paths: &str, context: &mut Context<BlenderAssetHandler>) -> Vec<Wad> {
+    paths
+        .split(';')
+        .filter_map(|path| path.rsplit(['\\', '/']).next())
+        .filter(|file_name| !file_name.is_empty())
+        .filter_map(|file_name| {
+            let path = PathBuf::from(GamePathBuf::from(file_name));
+
+            let bytes = match context.fs().read(&path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    warn!("wad `{}`: {}", file_name, err);
+                    return None;
+                }
+            };
+
+            match Wad::parse(&bytes) {
+                Ok(wad) => Some(wad),
+                Err(err) => {
+                    warn!("wad `{}`: {}", file_name, err);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn parse_vector(value: &str) -> Option<[f32; 3]> {
+    let mut parts = value.split_whitespace().map(str::parse);
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(x)), Some(Ok(y)), Some(Ok(z))) => Some([x, y, z]),
+        _ => None,
+    }
+}
+
+fn build_model(bsp: &Bsp, model: &Model, name: String, position: [f32; 3]) -> Option<GoldSrcModel> {
+    let faces = bsp
+        .faces
+        .get(model.first_face..model.first_face + model.face_count)?;
+
+    let mut vertex_map = BTreeMap::new();
+    let mut vertices = Vec::new();
+    let mut material_map = BTreeMap::new();
+    let mut materials = Vec::new();
+    let mut built_faces = Vec::new();
+
+    for face in faces {
+        let Some(texinfo) = bsp.texinfos.get(face.texinfo) else {
+            continue;
+        };
+        let Some(Some(texture)) = bsp.textures.get(texinfo.texture) else {
+            continue;
+        };
+
+        let texture_name = texture.name.to_ascii_lowercase();
+
+        if INVISIBLE_TEXTURES.contains(&texture_name.as_str()) {
+            continue;
+        }
+
+        let Some(face_vertices) = bsp.face_vertices(face) else {
+            continue;
+        };
+
+        let mut vertice_indices = Vec::with_capacity(face_vertices.len());
+        let mut vertice_uvs = Vec::with_capacity(face_vertices.len());
+
+        // GoldSrc faces are wound clockwise, Blender expects counter-clockwise
+        for &vertex in face_vertices.iter().rev() {
+            let Some(&vertex_position) = bsp.vertices.get(vertex) else {
+                continue;
+            };
+
+            let index = *vertex_map.entry(vertex).or_insert_with(|| {
+                vertices.push(vertex_position);
+                vertices.len() - 1
+            });
+
+            vertice_indices.push(index);
+            vertice_uvs.push(texture_uv(texinfo, texture, vertex_position));
+        }
+
+        if vertice_indices.len() < 3 {
+            continue;
+        }
+
+        let material_index = *material_map.entry(texinfo.texture).or_insert_with(|| {
+            materials.push(texture_name);
+            materials.len() - 1
+        });
+
+        built_faces.push(GoldSrcFace {
+            vertice_indices,
+            vertice_uvs,
+            material_index,
+        });
+    }
+
+    if built_faces.is_empty() {
+        return None;
+    }
+
+    Some(GoldSrcModel {
+        name,
+        position,
+        vertices,
+        faces: built_faces,
+        materials,
+    })
+}
+
+fn texture_uv(texinfo: &TexInfo, texture: &MipTexture, position: [f32; 3]) -> [f32; 2] {
+    let project = |axis: [f32; 4]| {
+        position[0] * axis[0] + position[1] * axis[1] + position[2] * axis[2] + axis[3]
+    };
+
+    let width = texture.width.max(1) as f32;
+    let height = texture.height.max(1) as f32;
+
+    // blender has inverted v axis compared to GoldSrc
+    [
+        project(texinfo.s) / width,
+        1.0 - project(texinfo.t) / height,
+    ]
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "GoldSrcModel"))]
+pub struct PyGoldSrcModel {
+    pub name: String,
+    position: [f32; 3],
+    scale: [f32; 3],
+    faces: Vec<GoldSrcFace>,
+    materials: Vec<String>,
+    texture_ext: &'static str,
+    #[cfg(feature = "python")]
+    flat_vertices: Vec<f32>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyGoldSrcModel {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn scale(&self) -> [f32; 3] {
+        self.scale
+    }
+
+    /// Extension of the imported textures, used to find the images of the materials.
+    pub fn texture_ext(&self) -> &'static str {
+        self.texture_ext
+    }
+
+    #[cfg(feature = "python")]
+    pub fn vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_vertices))
+    }
+
+    pub fn loops_len(&self) -> usize {
+        self.faces.iter().map(|f| f.vertice_indices.len()).sum()
+    }
+
+    pub fn polygons_len(&self) -> usize {
+        self.faces.len()
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.vertice_indices.len()))
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let mut acc = 0;
+
+        int_buffer(
+            py,
+            self.faces.iter().map(|f| {
+                let acc_before = acc;
+                acc += f.vertice_indices.len();
+                acc_before
+            }),
+        )
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_vertices<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let vertices: Vec<usize> = self
+            .faces
+            .iter()
+            .flat_map(|f| f.vertice_indices.iter().copied())
+            .collect();
+
+        int_buffer(py, vertices)
+    }
+
+    #[cfg(feature = "python")]
+    pub fn polygon_material_indices<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.material_index))
+    }
+
+    #[cfg(feature = "python")]
+    pub fn loop_uvs<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let uvs: Vec<f32> = self
+            .faces
+            .iter()
+            .flat_map(|f| f.vertice_uvs.iter().flatten().copied())
+            .collect();
+
+        float_buffer(py, &uvs)
+    }
+
+    pub fn materials(&mut self) -> Vec<String> {
+        mem::take(&mut self.materials)
+    }
+}
+
+impl PyGoldSrcModel {
+    pub fn new(model: GoldSrcModel, scale: f32, texture_ext: &'static str) -> Self {
+        Self {
+            name: model.name,
+            position: model.position.map(|c| c * scale),
+            scale: [scale, scale, scale],
+            faces: model.faces,
+            materials: model.materials,
+            texture_ext,
+            #[cfg(feature = "python")]
+            flat_vertices: model.vertices.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.scale = up_axis.scale(self.scale);
+        #[cfg(feature = "python")]
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use super::{name_at, slice, u16_at, u32_at, ParseError};
+
+const WAD_MAGIC: &[u8; 4] = b"WAD3";
+const MIPTEX_TYPE: u8 = 0x43;
+const PALETTE_SIZE: usize = 256;
+
+/// A paletted texture as stored in WAD3 files and GoldSrc BSP files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MipTexture {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Palette indices and the palette, missing if the pixels are stored in a WAD.
+    pixels: Option<(Vec<u8>, Vec<[u8; 3]>)>,
+}
+
+impl MipTexture {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let name = name_at(bytes, 0)?;
+        let width = u32_at(bytes, 16)?;
+        let height = u32_at(bytes, 20)?;
+        let first_mip_offset = u32_at(bytes, 24)? as usize;
+        let last_mip_offset = u32_at(bytes, 36)? as usize;
+
+        if first_mip_offset == 0 {
+            return Ok(Self {
+                name,
+                width,
+                height,
+                pixels: None,
+            });
+        }
+
+        let pixel_count = width as usize * height as usize;
+        let indices = slice(bytes, first_mip_offset, pixel_count)?.to_vec();
+
+        // the palette follows the smallest mip level, which is 1/8 of the size
+        let palette_offset = last_mip_offset + pixel_count / 64;
+        let palette_len = usize::from(u16_at(bytes, palette_offset)?);
+        let palette = slice(bytes, palette_offset + 2, palette_len * 3)?
+            .chunks_exact(3)
+            .map(|c| [c[0], c[1], c[2]])
+            .collect();
+
+        Ok(Self {
+            name,
+            width,
+            height,
+            pixels: Some((indices, palette)),
+        })
+    }
+
+    pub fn has_pixels(&self) -> bool {
+        self.pixels.is_some()
+    }
+
+    /// Converts the texture into an RGBA image.
+    /// Textures starting with `{` use the last palette color as transparency.
+    pub fn to_image(&self) -> Option<DynamicImage> {
+        let (indices, palette) = self.pixels.as_ref()?;
+        let transparent = self.name.starts_with('{');
+
+        let image = RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let index = indices[(y * self.width + x) as usize];
+
+            if transparent && usize::from(index) == PALETTE_SIZE - 1 {
+                return Rgba([0, 0, 0, 0]);
+            }
+
+            let [r, g, b] = palette.get(usize::from(index)).copied().unwrap_or_default();
+            Rgba([r, g, b, 255])
+        });
+
+        Some(DynamicImage::ImageRgba8(image))
+    }
+}
+
+/// Textures of a WAD3 file by their lowercase names.
+#[derive(Debug, Clone, Default)]
+pub struct Wad {
+    pub textures: BTreeMap<String, MipTexture>,
+}
+
+impl Wad {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        if slice(bytes, 0, 4)? != WAD_MAGIC {
+            return Err(ParseError::InvalidMagic);
+        }
+
+        let entry_count = u32_at(bytes, 4)? as usize;
+        let directory_offset = u32_at(bytes, 8)? as usize;
+
+        let mut textures = BTreeMap::new();
+
+        for i in 0..entry_count {
+            let entry = slice(bytes, directory_offset + i * 32, 32)?;
+            let offset = u32_at(entry, 0)? as usize;
+            let disk_size = u32_at(entry, 4)? as usize;
+            let kind = entry[12];
+            let compression = entry[13];
+
+            // other entry types are fonts and palettes, which aren't used by maps
+            if kind != MIPTEX_TYPE || compression != 0 {
+                continue;
+            }
+
+            let texture = MipTexture::parse(slice(bytes, offset, disk_size)?)?;
+            textures.insert(texture.name.to_ascii_lowercase(), texture);
+        }
+
+        Ok(Self { textures })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MipTexture> {
+        self.textures.get(&name.to_ascii_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a 8x8 miptex with every pixel using the palette index `index`.
+    fn write_miptex(name: &str, index: u8) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        let mut name_bytes = [0; 16];
+        name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+        out.extend(name_bytes);
+        out.extend(8_u32.to_le_bytes());
+        out.extend(8_u32.to_le_bytes());
+
+        let header_len = 40;
+        let mut offset = header_len;
+        for size in [64, 16, 4, 1] {
+            out.extend(u32::try_from(offset).unwrap().to_le_bytes());
+            offset += size;
+        }
+
+        out.extend([index; 64 + 16 + 4 + 1]);
+        out.extend(256_u16.to_le_bytes());
+        for i in 0..=255_u8 {
+            out.extend([i, 0, 255 - i]);
+        }
+
+        out
+    }
+
+    #[test]
+    fn converts_miptex_to_image() {
+        let texture = MipTexture::parse(&write_miptex("brick", 10)).unwrap();
+        let image = texture.to_image().unwrap().into_rgba8();
+
+        assert_eq!(image.dimensions(), (8, 8));
+        assert_eq!(image.get_pixel(3, 3), &Rgba([10, 0, 245, 255]));
+    }
+
+    #[test]
+    fn makes_last_color_transparent() {
+        let texture = MipTexture::parse(&write_miptex("{fence", 255)).unwrap();
+        let image = texture.to_image().unwrap().into_rgba8();
+
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn reads_wad_directory() {
+        let miptex = write_miptex("BRICK", 1);
+
+        let mut wad = Vec::new();
+        wad.extend(WAD_MAGIC);
+        wad.extend(1_u32.to_le_bytes());
+        wad.extend((12 + u32::try_from(miptex.len()).unwrap()).to_le_bytes());
+        wad.extend(&miptex);
+
+        wad.extend(12_u32.to_le_bytes());
+        wad.extend(u32::try_from(miptex.len()).unwrap().to_le_bytes());
+        wad.extend(u32::try_from(miptex.len()).unwrap().to_le_bytes());
+        wad.extend([MIPTEX_TYPE, 0, 0, 0]);
+        wad.extend(*b"BRICK\0\0\0\0\0\0\0\0\0\0\0");
+
+        let wad = Wad::parse(&wad).unwrap();
+
+        assert!(wad.get("brick").unwrap().has_pixels());
+    }
+}
@@ -0,0 +1,163 @@
+//! Moving the ids of a vmf past the ids of the vmfs imported before it in the same session.
+//!
+//! Every vmf numbers its solids, sides and entities from 1, while the data read from the vmfs
+//! and the imported objects are keyed by these ids. The ids of each vmf after the first one are
+//! offset, so maps imported together don't overwrite each other's data.
+
+use std::{borrow::Cow, ops::Range};
+
+use tracing::info;
+
+use super::keyvalues::{Token, Tokenizer};
+
+/// Keys whose values are an id, or a list of ids separated by spaces.
+/// Solids and entities share one numbering, while sides, groups and visgroups have their own.
+const ID_KEYS: [&[u8]; 5] = [b"id", b"groupid", b"visgroupid", b"sides", b"sides2"];
+
+/// Returns the highest id of a vmf, or 0 if it has none.
+pub fn max_id(bytes: &[u8]) -> i32 {
+    let mut max = 0;
+
+    for_each_id_value(bytes, |_, value| {
+        if let Some(ids) = parse_ids(value) {
+            max = ids.into_iter().fold(max, i32::max);
+        }
+    });
+
+    max
+}
+
+/// Returns the vmf with all of its ids increased by `offset`.
+pub fn offset_ids(bytes: &[u8], offset: i32) -> Cow<[u8]> {
+    if offset == 0 {
+        return Cow::Borrowed(bytes);
+    }
+
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut copied = 0;
+
+    for_each_id_value(bytes, |range, value| {
+        let Some(ids) = parse_ids(value) else {
+            return;
+        };
+
+        let Some(ids) = ids
+            .into_iter()
+            .map(|id| id.checked_add(offset))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        let ids: Vec<_> = ids.iter().map(i32::to_string).collect();
+
+        output.extend_from_slice(&bytes[copied..range.start]);
+        output.extend_from_slice(ids.join(" ").as_bytes());
+        copied = range.end;
+    });
+
+    info!("offset the ids of the vmf by {offset}, past the ids of the vmfs before it");
+
+    output.extend_from_slice(&bytes[copied..]);
+    Cow::Owned(output)
+}
+
+/// Calls `f` with the range and text of the values of the id keys.
+fn for_each_id_value(bytes: &[u8], mut f: impl FnMut(Range<usize>, &[u8])) {
+    let mut tokens = Tokenizer::new(bytes);
+    let mut key: Option<&[u8]> = None;
+
+    while let Some((start, token)) = tokens.next_token() {
+        match token {
+            Token::Text(text) => {
+                let Some(key) = key.take() else {
+                    key = Some(text);
+                    continue;
+                };
+
+                if ID_KEYS
+                    .iter()
+                    .any(|id_key| key.eq_ignore_ascii_case(id_key))
+                {
+                    // quoted values start after the quote
+                    let text_start = if bytes[start] == b'"' {
+                        start + 1
+                    } else {
+                        start
+                    };
+                    f(text_start..text_start + text.len(), text);
+                }
+            }
+            Token::Open | Token::Close => key = None,
+        }
+    }
+}
+
+/// Parses the ids of a value, or `None` if it isn't a list of ids.
+fn parse_ids(value: &[u8]) -> Option<Vec<i32>> {
+    let value = std::str::from_utf8(value).ok()?;
+    value.split_whitespace().map(|id| id.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VMF: &str = "world
+{
+\t\"id\" \"1\"
+\tsolid
+\t{
+\t\t\"id\" \"2\"
+\t\tside
+\t\t{
+\t\t\t\"id\" \"7\"
+\t\t\t\"material\" \"TOOLS/TOOLSNODRAW\"
+\t\t}
+\t\teditor
+\t\t{
+\t\t\t\"visgroupid\" \"3\"
+\t\t\t\"groupid\" \"4\"
+\t\t}
+\t}
+}
+entity
+{
+\t\"id\" \"5\"
+\t\"classname\" \"info_overlay\"
+\t\"sides\" \"7 8\"
+\t\"targetname\" \"12\"
+}
+";
+
+    #[test]
+    fn finds_highest_id() {
+        assert_eq!(max_id(VMF.as_bytes()), 8);
+        assert_eq!(
+            max_id(b"versioninfo\n{\n\t\"formatversion\" \"100\"\n}\n"),
+            0
+        );
+    }
+
+    #[test]
+    fn offsets_ids_and_id_lists() {
+        let offset = offset_ids(VMF.as_bytes(), 100);
+        let offset = String::from_utf8_lossy(&offset);
+
+        assert_eq!(
+            offset,
+            VMF.replace("\"1\"", "\"101\"")
+                .replace("\"2\"", "\"102\"")
+                .replace("\"7\"", "\"107\"")
+                .replace("\"3\"", "\"103\"")
+                .replace("\"4\"", "\"104\"")
+                .replace("\"5\"", "\"105\"")
+                .replace("\"7 8\"", "\"107 108\"")
+        );
+    }
+
+    #[test]
+    fn keeps_ids_without_offset() {
+        assert!(matches!(offset_ids(VMF.as_bytes(), 0), Cow::Borrowed(_)));
+    }
+}
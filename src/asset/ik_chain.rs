@@ -0,0 +1,193 @@
+//! Inverse kinematics chains of models, read from the mdl.
+//!
+//! The engine uses the chains to place the feet of models on the ground. Each chain is a list
+//! of bones from the root of a limb to its end effector, such as from a thigh to a foot.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::mdl::{model_key, read_string, record_offsets};
+
+/// Offset of the ik chain count in the mdl header, followed by the offset of the chains.
+const IK_CHAIN_COUNT_OFFSET: usize = 284;
+
+const IK_CHAIN_SIZE: usize = 16;
+
+const IK_LINK_SIZE: usize = 28;
+
+/// An ik chain of a model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IkChain {
+    name: String,
+    /// Bone indices of the links of the chain, ending with the end effector.
+    bones: Vec<usize>,
+    /// Direction the knee of the chain bends towards, relative to its first bone.
+    knee_direction: [f32; 3],
+}
+
+/// Returns the ik chains of an mdl.
+pub fn read_ik_chains(bytes: &[u8]) -> Vec<IkChain> {
+    let read = |offset: usize| -> Option<[u8; 4]> {
+        bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()
+    };
+    let read_int = |offset| read(offset).map(i32::from_le_bytes);
+    let read_float = |offset| read(offset).map(f32::from_le_bytes);
+    let read_index = |offset| usize::try_from(read_int(offset)?).ok();
+
+    // older models store the chains elsewhere
+    if bytes.get(..4) != Some(b"IDST") || read_int(4).unwrap_or_default() < 44 {
+        return Vec::new();
+    }
+
+    let (Some(count), Some(chains_offset)) = (
+        read_index(IK_CHAIN_COUNT_OFFSET),
+        read_index(IK_CHAIN_COUNT_OFFSET + 4),
+    ) else {
+        return Vec::new();
+    };
+
+    record_offsets(bytes, count, chains_offset, IK_CHAIN_SIZE)
+        .filter_map(|chain| {
+            let name = read_string(bytes, read_index(chain)?.checked_add(chain)?)?;
+            let link_count = read_index(chain + 8)?;
+            let links = read_index(chain + 12)?.checked_add(chain)?;
+
+            let bones = record_offsets(bytes, link_count, links, IK_LINK_SIZE)
+                .map(read_index)
+                .collect::<Option<Vec<_>>>()?;
+
+            if bones.is_empty() {
+                return None;
+            }
+
+            Some(IkChain {
+                name,
+                bones,
+                knee_direction: [
+                    read_float(links + 4)?,
+                    read_float(links + 8)?,
+                    read_float(links + 12)?,
+                ],
+            })
+        })
+        .collect()
+}
+
+/// Ik chains of the imported models, by their file name.
+#[derive(Debug, Default)]
+pub struct IkChains {
+    models: HashMap<String, Vec<IkChain>>,
+}
+
+impl IkChains {
+    pub fn insert(&mut self, model: &str, chains: Vec<IkChain>) {
+        if !chains.is_empty() {
+            self.models.insert(model_key(model), chains);
+        }
+    }
+
+    pub fn get(&self, model: &str) -> &[IkChain] {
+        self.models
+            .get(&model_key(model))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "IkChain"))]
+#[derive(Debug, Clone)]
+pub struct PyIkChain {
+    name: String,
+    bone_indices: Vec<usize>,
+    knee_direction: [f32; 3],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyIkChain {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Indices of the bones of the chain, from the root of the limb.
+    pub fn bone_indices(&self) -> Vec<usize> {
+        self.bone_indices.clone()
+    }
+
+    /// Index of the last bone of the chain, which is moved to the target.
+    pub fn end_effector_index(&self) -> usize {
+        *self
+            .bone_indices
+            .last()
+            .expect("ik chains should have bones")
+    }
+
+    pub fn knee_direction(&self) -> [f32; 3] {
+        self.knee_direction
+    }
+}
+
+impl PyIkChain {
+    /// Converts the chains of which the model has all bones.
+    pub fn from_chains(chains: &[IkChain], bone_count: usize) -> Vec<Self> {
+        chains
+            .iter()
+            .filter(|chain| chain.bones.iter().all(|&bone| bone < bone_count))
+            .map(|chain| Self {
+                name: chain.name.clone(),
+                bone_indices: chain.bones.clone(),
+                knee_direction: chain.knee_direction,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_i32(bytes: &mut [u8], offset: usize, value: i32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_offset(bytes: &mut [u8], offset: usize, from: usize, to: usize) {
+        put_i32(bytes, offset, i32::try_from(to - from).unwrap());
+    }
+
+    #[test]
+    fn reads_ik_chains() {
+        let chain = 408;
+        let links = chain + IK_CHAIN_SIZE;
+        let name = links + 3 * IK_LINK_SIZE;
+
+        let mut mdl = vec![0; name + 8];
+        mdl[..4].copy_from_slice(b"IDST");
+        put_i32(&mut mdl, 4, 48);
+        put_i32(&mut mdl, IK_CHAIN_COUNT_OFFSET, 1);
+        put_offset(&mut mdl, IK_CHAIN_COUNT_OFFSET + 4, 0, chain);
+
+        mdl[name..name + 7].copy_from_slice(b"rfoot\0\0");
+        put_offset(&mut mdl, chain, chain, name);
+        put_i32(&mut mdl, chain + 8, 3);
+        put_offset(&mut mdl, chain + 12, chain, links);
+
+        for (link, bone) in [4, 5, 6].into_iter().enumerate() {
+            put_i32(&mut mdl, links + link * IK_LINK_SIZE, bone);
+        }
+        mdl[links + 4..links + 8].copy_from_slice(&1.0_f32.to_le_bytes());
+
+        let chains = read_ik_chains(&mdl);
+
+        assert_eq!(
+            chains,
+            [IkChain {
+                name: "rfoot".to_owned(),
+                bones: vec![4, 5, 6],
+                knee_direction: [1.0, 0.0, 0.0],
+            }]
+        );
+
+        assert_eq!(PyIkChain::from_chains(&chains, 7).len(), 1);
+        assert!(PyIkChain::from_chains(&chains, 6).is_empty());
+    }
+}
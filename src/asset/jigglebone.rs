@@ -0,0 +1,239 @@
+//! `$jigglebone` procedural bones of models, read from the bones of the mdl.
+//!
+//! Jiggle bones are simulated by the engine instead of being animated. Their parameters are
+//! stored as the procedural data of the bone, which the converted bones don't carry.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::mdl::{model_key, read_string, record_offsets};
+
+/// Offset of the bone count in the mdl header, followed by the offset of the bones.
+const BONE_COUNT_OFFSET: usize = 156;
+
+/// Size of a bone in mdl versions 44 and later.
+const BONE_SIZE: usize = 216;
+
+/// Offset of the procedural type in a bone, followed by the offset of the procedural data.
+const PROC_TYPE_OFFSET: usize = 164;
+
+const PROC_TYPE_JIGGLE: i32 = 5;
+
+const JIGGLE_IS_RIGID: i32 = 0x02;
+const JIGGLE_HAS_BASE_SPRING: i32 = 0x40;
+
+/// Parameters of a jiggle bone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JiggleBone {
+    bone: String,
+    rigid: bool,
+    length: f32,
+    tip_mass: f32,
+    /// Stiffness and damping of the yaw, pitch and along motion of the tip.
+    tip_springs: [[f32; 2]; 3],
+    /// Mass, stiffness and damping of the base, if it's a spring too.
+    base_spring: Option<[f32; 3]>,
+}
+
+/// Returns the jiggle bones of an mdl.
+pub fn read_jiggle_bones(bytes: &[u8]) -> Vec<JiggleBone> {
+    let read = |offset: usize| -> Option<[u8; 4]> {
+        bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()
+    };
+    let read_int = |offset| read(offset).map(i32::from_le_bytes);
+    let read_float = |offset| read(offset).map(f32::from_le_bytes);
+
+    // older models have a differently sized bone
+    if bytes.get(..4) != Some(b"IDST") || read_int(4).unwrap_or_default() < 44 {
+        return Vec::new();
+    }
+
+    let (Some(count), Some(bones_offset)) = (
+        read_int(BONE_COUNT_OFFSET).and_then(|c| usize::try_from(c).ok()),
+        read_int(BONE_COUNT_OFFSET + 4).and_then(|o| usize::try_from(o).ok()),
+    ) else {
+        return Vec::new();
+    };
+
+    record_offsets(bytes, count, bones_offset, BONE_SIZE)
+        .filter_map(|bone| {
+            if read_int(bone + PROC_TYPE_OFFSET)? != PROC_TYPE_JIGGLE {
+                return None;
+            }
+
+            let relative = |offset| usize::try_from(read_int(offset)?).ok()?.checked_add(bone);
+            let name = read_string(bytes, relative(bone)?)?;
+            let jiggle = relative(bone + PROC_TYPE_OFFSET + 4)?;
+
+            let flags = read_int(jiggle)?;
+            let field = |index: usize| read_float(jiggle + 4 * index);
+
+            Some(JiggleBone {
+                bone: name,
+                rigid: flags & JIGGLE_IS_RIGID != 0,
+                length: field(1)?,
+                tip_mass: field(2)?,
+                tip_springs: [
+                    [field(3)?, field(4)?],
+                    [field(5)?, field(6)?],
+                    [field(7)?, field(8)?],
+                ],
+                base_spring: if flags & JIGGLE_HAS_BASE_SPRING == 0 {
+                    None
+                } else {
+                    Some([field(18)?, field(19)?, field(20)?])
+                },
+            })
+        })
+        .collect()
+}
+
+/// Jiggle bones of the imported models, by their file name.
+#[derive(Debug, Default)]
+pub struct JiggleBones {
+    models: HashMap<String, Vec<JiggleBone>>,
+}
+
+impl JiggleBones {
+    pub fn insert(&mut self, model: &str, bones: Vec<JiggleBone>) {
+        if !bones.is_empty() {
+            self.models.insert(model_key(model), bones);
+        }
+    }
+
+    pub fn get(&self, model: &str) -> &[JiggleBone] {
+        self.models
+            .get(&model_key(model))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "JiggleBone"))]
+#[derive(Debug, Clone)]
+pub struct PyJiggleBone {
+    bone_index: usize,
+    rigid: bool,
+    length: f32,
+    tip_mass: f32,
+    tip_springs: [[f32; 2]; 3],
+    base_spring: Option<[f32; 3]>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyJiggleBone {
+    pub fn bone_index(&self) -> usize {
+        self.bone_index
+    }
+
+    /// The bone keeps its shape, only its base moves if it's a spring.
+    pub fn rigid(&self) -> bool {
+        self.rigid
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    pub fn tip_mass(&self) -> f32 {
+        self.tip_mass
+    }
+
+    /// Stiffness and damping of the yaw, pitch and along motion of the tip.
+    pub fn tip_springs(&self) -> [[f32; 2]; 3] {
+        self.tip_springs
+    }
+
+    /// Mass, stiffness and damping of the base, or `None` if the base doesn't move.
+    pub fn base_spring(&self) -> Option<[f32; 3]> {
+        self.base_spring
+    }
+}
+
+impl PyJiggleBone {
+    /// Converts the jiggle bones which the model has, by the names of its bones.
+    pub fn from_jiggle_bones(jiggle_bones: &[JiggleBone], bone_names: &[&str]) -> Vec<Self> {
+        jiggle_bones
+            .iter()
+            .filter_map(|jiggle| {
+                Some(Self {
+                    bone_index: bone_names
+                        .iter()
+                        .position(|bone| bone.eq_ignore_ascii_case(&jiggle.bone))?,
+                    rigid: jiggle.rigid,
+                    length: jiggle.length,
+                    tip_mass: jiggle.tip_mass,
+                    tip_springs: jiggle.tip_springs,
+                    base_spring: jiggle.base_spring,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_i32(bytes: &mut [u8], offset: usize, value: i32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_offset(bytes: &mut [u8], offset: usize, from: usize, to: usize) {
+        put_i32(bytes, offset, i32::try_from(to - from).unwrap());
+    }
+
+    #[test]
+    fn reads_jiggle_bones() {
+        let bones = 408;
+        let jiggle = bones + 2 * BONE_SIZE;
+        let name = jiggle + 21 * 4;
+
+        let mut mdl = vec![0; name + 8];
+        mdl[..4].copy_from_slice(b"IDST");
+        put_i32(&mut mdl, 4, 48);
+        put_i32(&mut mdl, BONE_COUNT_OFFSET, 2);
+        put_offset(&mut mdl, BONE_COUNT_OFFSET + 4, 0, bones);
+
+        // the first bone is a normal bone, the second one jiggles
+        let bone = bones + BONE_SIZE;
+        mdl[name..name + 5].copy_from_slice(b"tail\0");
+        put_offset(&mut mdl, bone, bone, name);
+        put_i32(&mut mdl, bone + PROC_TYPE_OFFSET, PROC_TYPE_JIGGLE);
+        put_offset(&mut mdl, bone + PROC_TYPE_OFFSET + 4, bone, jiggle);
+
+        put_i32(&mut mdl, jiggle, 0x01 | JIGGLE_HAS_BASE_SPRING);
+        for (index, value) in (1..=20).zip(1_u8..) {
+            let offset = jiggle + 4 * index;
+            mdl[offset..offset + 4].copy_from_slice(&(f32::from(value) * 10.0).to_le_bytes());
+        }
+
+        let jiggle_bones = read_jiggle_bones(&mdl);
+
+        assert_eq!(
+            jiggle_bones,
+            [JiggleBone {
+                bone: "tail".to_owned(),
+                rigid: false,
+                length: 10.0,
+                tip_mass: 20.0,
+                tip_springs: [[30.0, 40.0], [50.0, 60.0], [70.0, 80.0]],
+                base_spring: Some([180.0, 190.0, 200.0]),
+            }]
+        );
+
+        let py_bones = PyJiggleBone::from_jiggle_bones(&jiggle_bones, &["root", "Tail"]);
+        assert_eq!(py_bones.len(), 1);
+        assert_eq!(py_bones[0].bone_index, 1);
+    }
+
+    #[test]
+    fn ignores_old_models() {
+        let mut mdl = vec![0; 400];
+        mdl[..4].copy_from_slice(b"IDST");
+        put_i32(&mut mdl, 4, 37);
+
+        assert!(read_jiggle_bones(&mdl).is_empty());
+    }
+}
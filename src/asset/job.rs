@@ -0,0 +1,261 @@
+//! Import jobs for the asset handler, shared by the Python importers and the cli.
+
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use tracing::error;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context, NoError},
+    asset_mdl::MdlConfig,
+    asset_vmf::{BrushSetting, VmfConfig},
+    asset_vmt::skybox::SkyBoxConfig,
+    asset_vtf::VtfConfig,
+    fs::{GamePathBuf, PathBuf},
+    vmf::{builder::MergeSolids, vmf::Vmf},
+};
+
+use super::{
+    anim_event::AnimationEvents,
+    color_correction::{color_correction_entities, ColorCorrectionConfig},
+    decal::{info_decals, DecalConfig},
+    entities::projected_texture_paths,
+    glow::{env_glows, GlowConfig},
+    ik_chain::IkChains,
+    jigglebone::JiggleBones,
+    material::MaterialConfig,
+    model::{ModelBonesConfig, ModelPaths},
+    model_bounds::ModelBoundsStore,
+    radar::RadarConfig,
+    ragdoll::Ragdolls,
+    vmf_entities::VmfEntities,
+    vmf_load::{load_vmf_bytes, MapFormat, VmfLoadSettings},
+    vmf_metadata::VmfMetadata,
+    BlenderAssetHandler,
+};
+
+/// Unified asset config that can process mixed asset types
+#[derive(Debug, Clone)]
+pub struct UnifiedAssetConfig {
+    pub material_config: MaterialConfig,
+    /// Queue the textures of projected texture lights, which are imported with the lights.
+    pub import_lights: bool,
+    /// Cancellation flag of the handler, jobs are skipped once it's set.
+    pub cancelled: Arc<AtomicBool>,
+}
+
+impl AssetConfig<BlenderAssetHandler> for UnifiedAssetConfig {
+    type Input<'a> = AssetImportJob;
+    type Output<'a> = ();
+    type Error<'a> = NoError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        match input {
+            AssetImportJob::Vtf { path } => {
+                context.queue(VtfConfig, path);
+            }
+            AssetImportJob::Vmt { path } => {
+                context.queue(self.material_config, path);
+            }
+            AssetImportJob::Mdl {
+                path,
+                physics,
+                config,
+                ragdolls,
+                jiggle_bones,
+                ik_chains,
+                animation_events,
+                model_bounds,
+            } => {
+                context.queue(
+                    ModelBonesConfig {
+                        config,
+                        ragdolls,
+                        jiggle_bones,
+                        ik_chains,
+                        animation_events,
+                        model_bounds,
+                    },
+                    ModelPaths {
+                        model: path,
+                        physics,
+                    },
+                );
+            }
+            AssetImportJob::Vmf {
+                path,
+                config,
+                load_settings,
+                vmf_metadata,
+                vmf_entities,
+                separate_entity_solids,
+            } => {
+                // VMF files need special handling - read and parse first
+                let Ok(bytes) = context.fs().read(&path) else {
+                    error!("Failed to read VMF file: {}", path);
+                    return Ok(());
+                };
+
+                let format = MapFormat::from_path(&path.to_string());
+                match load_vmf_bytes(&bytes, format, &load_settings, &vmf_metadata, &vmf_entities) {
+                    Ok((vmf, repairs)) => {
+                        vmf_entities.add_solid_repairs(repairs);
+
+                        queue_vmf(context, vmf, config, &self, separate_entity_solids);
+                    }
+                    Err(err) => error!("Failed to load VMF file {}: {}", path, err),
+                }
+            }
+            AssetImportJob::LoadedVmf {
+                vmf,
+                config,
+                separate_entity_solids,
+            } => {
+                queue_vmf(context, vmf, config, &self, separate_entity_solids);
+            }
+            AssetImportJob::Radar { map_name } => {
+                context.queue(RadarConfig, map_name);
+            }
+            AssetImportJob::Sky { name } => {
+                context.queue(SkyBoxConfig, name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Queues a parsed VMF along with the files its entities reference directly
+fn queue_vmf(
+    context: &mut Context<BlenderAssetHandler>,
+    mut vmf: Vmf,
+    config: VmfConfig<MaterialConfig>,
+    unified_config: &UnifiedAssetConfig,
+    separate_entity_solids: bool,
+) {
+    if separate_entity_solids {
+        if let Some((entity_vmf, entity_config)) = split_brush_entities(&mut vmf, &config) {
+            queue_vmf(context, entity_vmf, entity_config, unified_config, false);
+        }
+    }
+
+    if unified_config.import_lights {
+        for texture in projected_texture_paths(&vmf) {
+            context.queue(VtfConfig, texture.into());
+        }
+
+        for glow in env_glows(&vmf) {
+            context.queue(GlowConfig, glow);
+        }
+    }
+
+    if config.import_other_entities {
+        for color_correction in color_correction_entities(&vmf) {
+            context.queue(ColorCorrectionConfig, color_correction);
+        }
+
+        for decal in info_decals(&vmf) {
+            let material_config = unified_config.material_config;
+            context.queue(DecalConfig { material_config }, decal);
+        }
+    }
+
+    context.queue(config, vmf);
+}
+
+/// Moves the brush entities of the vmf into a separate vmf, along with a config which builds
+/// each of their solids separately. The world stays in the original vmf with the original config.
+/// Overlays are only built with the world, so overlays on brush entities are skipped.
+fn split_brush_entities(
+    vmf: &mut Vmf,
+    config: &VmfConfig<MaterialConfig>,
+) -> Option<(Vmf, VmfConfig<MaterialConfig>)> {
+    let BrushSetting::Import(geometry) = &config.brushes else {
+        return None;
+    };
+
+    let (brush_entities, point_entities): (Vec<_>, Vec<_>) = mem::take(&mut vmf.entities)
+        .into_iter()
+        .partition(|entity| !entity.solids.is_empty());
+    vmf.entities = point_entities;
+
+    // the world solids are the bulk of the vmf, so they aren't cloned
+    let world_solids = mem::take(&mut vmf.world.solids);
+    let mut entity_vmf = vmf.clone();
+    entity_vmf.entities = brush_entities;
+    vmf.world.solids = world_solids;
+
+    let mut geometry = geometry.clone();
+    geometry.merge_solids(MergeSolids::Separate);
+
+    let mut entity_config = config.clone();
+    entity_config.brushes = BrushSetting::Import(geometry);
+    entity_config.import_overlays = false;
+    // the sky is a part of the world
+    entity_config.import_skybox = false;
+
+    Some((entity_vmf, entity_config))
+}
+
+/// Enum representing different types of assets that can be imported
+#[derive(Debug, Clone)]
+pub enum AssetImportJob {
+    Vmf {
+        path: PathBuf,
+        config: VmfConfig<MaterialConfig>,
+        load_settings: VmfLoadSettings,
+        /// Filled with the metadata of the vmf, such as its lightmap scales, before it's built.
+        vmf_metadata: VmfMetadata,
+        /// Filled with the parents and brush entities of the vmf before they are converted.
+        vmf_entities: VmfEntities,
+        /// Build each solid of brush entities separately, even when the world is merged.
+        separate_entity_solids: bool,
+    },
+    LoadedVmf {
+        vmf: Vmf,
+        config: VmfConfig<MaterialConfig>,
+        separate_entity_solids: bool,
+    },
+    Mdl {
+        path: PathBuf,
+        /// Path of the `.phy` file the ragdoll of the model is read from.
+        physics: PathBuf,
+        config: MdlConfig<MaterialConfig>,
+        /// Filled with the ragdoll joints of the model before it's queued.
+        ragdolls: Arc<RwLock<Ragdolls>>,
+        /// Filled with the jiggle bones of the model before it's queued.
+        jiggle_bones: Arc<RwLock<JiggleBones>>,
+        /// Filled with the ik chains of the model before it's queued.
+        ik_chains: Arc<RwLock<IkChains>>,
+        /// Filled with the animation events of the model before it's queued.
+        animation_events: Arc<RwLock<AnimationEvents>>,
+        /// Filled with the positions and bounding boxes of the model before it's queued.
+        model_bounds: Arc<RwLock<ModelBoundsStore>>,
+    },
+    Vmt {
+        path: PathBuf,
+    },
+    Vtf {
+        path: PathBuf,
+    },
+    Radar {
+        map_name: String,
+    },
+    /// A skybox converted to an equirectangular sky without a vmf.
+    Sky {
+        /// Name of the skybox, such as `sky_day01_01`.
+        name: GamePathBuf,
+    },
+}
@@ -0,0 +1,286 @@
+//! Minimal reading and writing of the `KeyValues` text format of vmf files,
+//! for rewriting parts of a vmf before it's parsed and for reading what the parsed vmf lacks.
+
+use glam::Vec3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyValue {
+    Value(String, String),
+    Block(String, Vec<KeyValue>),
+}
+
+pub enum Token<'a> {
+    Text(&'a [u8]),
+    Open,
+    Close,
+}
+
+pub struct Tokenizer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Position after the last read token.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the next token and its start position.
+    pub fn next_token(&mut self) -> Option<(usize, Token<'a>)> {
+        loop {
+            let &byte = self.bytes.get(self.pos)?;
+
+            if byte.is_ascii_whitespace() {
+                self.pos += 1;
+            } else if self.bytes[self.pos..].starts_with(b"//") {
+                while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+
+        let start = self.pos;
+
+        let token = match self.bytes[start] {
+            b'{' => {
+                self.pos += 1;
+                Token::Open
+            }
+            b'}' => {
+                self.pos += 1;
+                Token::Close
+            }
+            b'"' => {
+                let end = self.bytes[start + 1..]
+                    .iter()
+                    .position(|&b| b == b'"')
+                    .map_or(self.bytes.len(), |i| start + 1 + i);
+                self.pos = (end + 1).min(self.bytes.len());
+                Token::Text(&self.bytes[start + 1..end])
+            }
+            _ => {
+                let end = self.bytes[start..]
+                    .iter()
+                    .position(|&b| b.is_ascii_whitespace() || matches!(b, b'{' | b'}' | b'"'))
+                    .map_or(self.bytes.len(), |i| start + i);
+                self.pos = end;
+                Token::Text(&self.bytes[start..end])
+            }
+        };
+
+        Some((start, token))
+    }
+}
+
+/// Parses the entries of a block whose opening brace was already read, up to its closing brace.
+pub fn parse_block(tokens: &mut Tokenizer) -> Option<Vec<KeyValue>> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next_token()?.1 {
+            Token::Close => return Some(entries),
+            Token::Open => return None,
+            Token::Text(key) => String::from_utf8_lossy(key).into_owned(),
+        };
+
+        entries.push(match tokens.next_token()?.1 {
+            Token::Text(value) => KeyValue::Value(key, String::from_utf8_lossy(value).into_owned()),
+            Token::Open => KeyValue::Block(key, parse_block(tokens)?),
+            Token::Close => return None,
+        });
+    }
+}
+
+/// Parses the top level entries of a vmf, up to the first malformed block.
+pub fn parse_document(bytes: &[u8]) -> Vec<KeyValue> {
+    let mut tokens = Tokenizer::new(bytes);
+    let mut entries = Vec::new();
+
+    while let Some((_, Token::Text(key))) = tokens.next_token() {
+        let key = String::from_utf8_lossy(key).into_owned();
+
+        match tokens.next_token() {
+            Some((_, Token::Text(value))) => {
+                entries.push(KeyValue::Value(
+                    key,
+                    String::from_utf8_lossy(value).into_owned(),
+                ));
+            }
+            Some((_, Token::Open)) => match parse_block(&mut tokens) {
+                Some(block) => entries.push(KeyValue::Block(key, block)),
+                None => break,
+            },
+            Some((_, Token::Close)) | None => break,
+        }
+    }
+
+    entries
+}
+
+/// Calls `f` with the entries of every block named `name`, however deeply it's nested.
+pub fn for_each_block<'a>(entries: &'a [KeyValue], name: &str, f: &mut impl FnMut(&'a [KeyValue])) {
+    for entry in entries {
+        if let KeyValue::Block(key, block) = entry {
+            if key.eq_ignore_ascii_case(name) {
+                f(block);
+            }
+
+            for_each_block(block, name, f);
+        }
+    }
+}
+
+/// Writes a block in the format Hammer uses, starting at the current line and without a
+/// trailing line ending.
+pub fn write_block(
+    output: &mut Vec<u8>,
+    name: &str,
+    entries: &[KeyValue],
+    indent: &[u8],
+    line_ending: &[u8],
+) {
+    let mut inner_indent = indent.to_vec();
+    inner_indent.push(b'\t');
+
+    output.extend_from_slice(name.as_bytes());
+    output.extend_from_slice(line_ending);
+    output.extend_from_slice(indent);
+    output.push(b'{');
+    output.extend_from_slice(line_ending);
+
+    for entry in entries {
+        output.extend_from_slice(&inner_indent);
+
+        match entry {
+            KeyValue::Value(key, value) => {
+                output.extend_from_slice(format!("\"{key}\" \"{value}\"").as_bytes());
+                output.extend_from_slice(line_ending);
+            }
+            KeyValue::Block(key, entries) => {
+                write_block(output, key, entries, &inner_indent, line_ending);
+                output.extend_from_slice(line_ending);
+            }
+        }
+    }
+
+    output.extend_from_slice(indent);
+    output.push(b'}');
+}
+
+/// Writes a vmf with the world and entity blocks, along with the blocks Hammer expects
+/// before and after them.
+pub fn write_vmf(world: &[KeyValue], entities: &[Vec<KeyValue>]) -> Vec<u8> {
+    let version_info: Vec<KeyValue> = [
+        ("editorversion", "400"),
+        ("editorbuild", "0"),
+        ("mapversion", "1"),
+        ("formatversion", "100"),
+        ("prefab", "0"),
+    ]
+    .into_iter()
+    .map(|(key, value)| KeyValue::Value(key.into(), value.into()))
+    .collect();
+
+    let mut output = Vec::new();
+    let mut write = |name: &str, entries: &[KeyValue]| {
+        write_block(&mut output, name, entries, b"", b"\n");
+        output.push(b'\n');
+    };
+
+    write("versioninfo", &version_info);
+    write("visgroups", &[]);
+    write("world", world);
+
+    for entity in entities {
+        write("entity", entity);
+    }
+
+    write(
+        "cameras",
+        &[KeyValue::Value("activecamera".into(), "-1".into())],
+    );
+
+    output
+}
+
+/// Returns the entries of a vmf side, without its id.
+/// The texture axes are given as the axis, offset and scale of the u and v coordinates.
+pub fn side_entries(
+    points: [Vec3; 3],
+    material: &str,
+    [u_axis, v_axis]: [(Vec3, f32, f32); 2],
+    rotation: f32,
+) -> Vec<KeyValue> {
+    let plane = points
+        .iter()
+        .map(|point| format!("({} {} {})", point.x, point.y, point.z))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let axis = |(axis, offset, scale): (Vec3, f32, f32)| {
+        format!("[{} {} {} {offset}] {scale}", axis.x, axis.y, axis.z)
+    };
+
+    [
+        ("plane", plane),
+        ("material", material.to_string()),
+        ("uaxis", axis(u_axis)),
+        ("vaxis", axis(v_axis)),
+        ("rotation", rotation.to_string()),
+        ("lightmapscale", "16".to_string()),
+        ("smoothing_groups", "0".to_string()),
+    ]
+    .into_iter()
+    .map(|(key, value)| KeyValue::Value(key.into(), value))
+    .collect()
+}
+
+/// Returns the whitespace before `pos` on its line.
+pub fn indentation(bytes: &[u8], pos: usize) -> &[u8] {
+    let line_start = bytes[..pos]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+
+    let line = &bytes[line_start..pos];
+
+    if line.iter().all(|&b| b == b' ' || b == b'\t') {
+        line
+    } else {
+        &[]
+    }
+}
+
+pub fn find_value<'a>(entries: &'a [KeyValue], name: &str) -> Option<&'a str> {
+    entries.iter().find_map(|entry| match entry {
+        KeyValue::Value(key, value) if key.eq_ignore_ascii_case(name) => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+pub fn find_block<'a>(entries: &'a [KeyValue], name: &str) -> Option<&'a [KeyValue]> {
+    entries.iter().find_map(|entry| match entry {
+        KeyValue::Block(key, entries) if key.eq_ignore_ascii_case(name) => Some(entries.as_slice()),
+        _ => None,
+    })
+}
+
+/// Parses the three points of a plane in the `(x y z) (x y z) (x y z)` format of vmf sides.
+pub fn parse_plane_points(plane: &str) -> Option<[Vec3; 3]> {
+    let values: Vec<f32> = plane
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let values: [f32; 9] = values.try_into().ok()?;
+
+    Some([0, 3, 6].map(|i| Vec3::from_slice(&values[i..i + 3])))
+}
@@ -0,0 +1,464 @@
+//! Lightmap UVs for brushes, laid out at the luxel density the map author set for each face,
+//! so lighting can be baked in Blender at the density it has in game.
+//!
+//! Faces are grouped into charts where their texture coordinates continue across shared edges,
+//! which keeps displacements and aligned coplanar faces in one piece. Each chart is laid out
+//! along its texture axes with one unit per luxel, and the charts are packed into a square.
+
+use std::collections::HashMap;
+
+use glam::{Mat2, Vec2, Vec3};
+use plumber_core::vmf::builder::SolidFace;
+
+use super::keyvalues::{find_value, for_each_block, parse_plane_points, KeyValue};
+
+/// Map units per luxel of faces which don't set `lightmapscale`.
+pub const DEFAULT_LIGHTMAP_SCALE: f32 = 16.0;
+
+/// Luxels left empty around each chart, so baked lighting doesn't bleed between charts.
+const PADDING: f32 = 1.0;
+
+/// Texture coordinates closer than this are considered continuous.
+const UV_EPSILON: f32 = 1e-4;
+
+/// Lightmap scales of the faces of the imported vmfs which don't use the default,
+/// by the plane the face lies on.
+#[derive(Debug, Default)]
+pub struct LightmapScales {
+    planes: HashMap<[i32; 4], f32>,
+}
+
+impl LightmapScales {
+    /// Reads the lightmap scales of the sides of a vmf.
+    pub fn extend_from_keyvalues(&mut self, vmf: &[KeyValue]) {
+        for_each_block(vmf, "side", &mut |side| {
+            let scale = find_value(side, "lightmapscale").and_then(|s| s.parse::<f32>().ok());
+            let plane = find_value(side, "plane").and_then(parse_plane);
+
+            if let (Some(scale), Some((normal, distance))) = (scale, plane) {
+                let is_default = (scale - DEFAULT_LIGHTMAP_SCALE).abs() < f32::EPSILON;
+
+                if scale > 0.0 && !is_default {
+                    self.planes.insert(plane_key(normal, distance), scale);
+                }
+            }
+        });
+    }
+
+    fn get(&self, normal: Vec3, distance: f32) -> f32 {
+        self.planes
+            .get(&plane_key(normal, distance))
+            .copied()
+            .unwrap_or(DEFAULT_LIGHTMAP_SCALE)
+    }
+}
+
+/// Returns the normal and distance of the plane of a vmf side.
+pub fn parse_plane(plane: &str) -> Option<(Vec3, f32)> {
+    let [a, b, c] = parse_plane_points(plane)?;
+    let normal = (a - b).cross(c - b).normalize_or_zero();
+
+    if normal == Vec3::ZERO {
+        return None;
+    }
+
+    Some((normal, normal.dot(a)))
+}
+
+/// Faces of touching brushes share planes facing opposite ways, so the orientation is ignored.
+#[allow(clippy::cast_possible_truncation)]
+pub fn plane_key(normal: Vec3, distance: f32) -> [i32; 4] {
+    let leading = [normal.x, normal.y, normal.z]
+        .into_iter()
+        .find(|c| c.abs() > 1e-3)
+        .unwrap_or(1.0);
+
+    let (normal, distance) = if leading < 0.0 {
+        (-normal, -distance)
+    } else {
+        (normal, distance)
+    };
+
+    [
+        (normal.x * 1000.0).round() as i32,
+        (normal.y * 1000.0).round() as i32,
+        (normal.z * 1000.0).round() as i32,
+        (distance * 10.0).round() as i32,
+    ]
+}
+
+/// Lightmap UVs of a mesh, one per face corner in the order of the faces.
+pub struct LightmapUvs {
+    pub flat_loop_uvs: Vec<f32>,
+    /// Map units per luxel of each face, which the uvs of the face are laid out at.
+    pub face_scales: Vec<f32>,
+    /// Side length of the lightmap in luxels, at which baking matches the density in game.
+    pub resolution: u32,
+}
+
+/// How texture coordinates change along a face, in texture units per map unit.
+struct FaceMapping {
+    gradient: Vec2,
+    area: f32,
+    lightmap_scale: f32,
+}
+
+/// Creates lightmap UVs for a mesh whose vertices are at `offset + vertex`, in map units.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn lightmap_uvs(
+    offset: Vec3,
+    vertices: &[f32],
+    faces: &[SolidFace],
+    scales: &LightmapScales,
+) -> LightmapUvs {
+    let position = |index: usize| offset + Vec3::from_slice(&vertices[index * 3..index * 3 + 3]);
+
+    let mappings: Vec<Option<FaceMapping>> = faces
+        .iter()
+        .map(|face| face_mapping(face, &position, scales))
+        .collect();
+
+    let mut charts = UnionFind::new(faces.len());
+    let mut edges: HashMap<[usize; 2], (usize, Vec2, Vec2)> = HashMap::new();
+
+    for (face_index, face) in faces.iter().enumerate() {
+        let Some(mapping) = &mappings[face_index] else {
+            continue;
+        };
+
+        let len = face.vertice_indices.len();
+
+        for i in 0..len {
+            let j = (i + 1) % len;
+            let (a, b) = (face.vertice_indices[i], face.vertice_indices[j]);
+            let (uv_a, uv_b) = (face.vertice_uvs[i], face.vertice_uvs[j]);
+
+            let (key, uvs) = if a < b {
+                ([a, b], (uv_a, uv_b))
+            } else {
+                ([b, a], (uv_b, uv_a))
+            };
+
+            match edges.get(&key) {
+                Some(&(other, other_a, other_b))
+                    if other_a.abs_diff_eq(uvs.0, UV_EPSILON)
+                        && other_b.abs_diff_eq(uvs.1, UV_EPSILON)
+                        && mappings[other].as_ref().map(|m| m.lightmap_scale)
+                            == Some(mapping.lightmap_scale) =>
+                {
+                    charts.union(face_index, other);
+                }
+                Some(_) => {}
+                None => {
+                    edges.insert(key, (face_index, uvs.0, uvs.1));
+                }
+            }
+        }
+    }
+
+    // the texture scale of a chart is averaged, since it varies along displacements
+    let mut chart_gradients: HashMap<usize, (Vec2, f32)> = HashMap::new();
+
+    for (face_index, mapping) in mappings.iter().enumerate() {
+        if let Some(mapping) = mapping {
+            let sum = chart_gradients
+                .entry(charts.find(face_index))
+                .or_insert((Vec2::ZERO, 0.0));
+            sum.0 += mapping.gradient * mapping.area;
+            sum.1 += mapping.area;
+        }
+    }
+
+    // luxel coordinates of every face corner, and the bounds of each chart
+    let mut luxels: Vec<Vec<Vec2>> = Vec::with_capacity(faces.len());
+    let mut bounds: HashMap<usize, (Vec2, Vec2)> = HashMap::new();
+
+    for (face_index, face) in faces.iter().enumerate() {
+        let chart = charts.find(face_index);
+
+        let face_luxels: Vec<Vec2> = match &mappings[face_index] {
+            Some(mapping) => {
+                let (sum, area) = chart_gradients[&chart];
+                let gradient = if area > 0.0 {
+                    sum / area
+                } else {
+                    mapping.gradient
+                };
+                let luxels_per_texture_unit = Vec2::ONE / (gradient * mapping.lightmap_scale);
+
+                face.vertice_uvs
+                    .iter()
+                    .map(|&uv| uv * luxels_per_texture_unit)
+                    .collect()
+            }
+            None => planar_luxels(face, &position, DEFAULT_LIGHTMAP_SCALE),
+        };
+
+        let chart_bounds = bounds
+            .entry(chart)
+            .or_insert((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)));
+
+        for &luxel in &face_luxels {
+            chart_bounds.0 = chart_bounds.0.min(luxel);
+            chart_bounds.1 = chart_bounds.1.max(luxel);
+        }
+
+        luxels.push(face_luxels);
+    }
+
+    let (positions, resolution) = pack(&bounds);
+
+    let flat_loop_uvs = luxels
+        .iter()
+        .enumerate()
+        .flat_map(|(face_index, face_luxels)| {
+            let chart = charts.find(face_index);
+            let (min, _) = bounds[&chart];
+            let position = positions[&chart];
+
+            face_luxels.iter().flat_map(move |&luxel| {
+                let uv = (luxel - min + position) / resolution;
+                [uv.x, uv.y]
+            })
+        })
+        .collect();
+
+    let face_scales = mappings
+        .iter()
+        .map(|m| {
+            m.as_ref()
+                .map_or(DEFAULT_LIGHTMAP_SCALE, |m| m.lightmap_scale)
+        })
+        .collect();
+
+    LightmapUvs {
+        flat_loop_uvs,
+        face_scales,
+        resolution: resolution.ceil() as u32,
+    }
+}
+
+fn face_mapping(
+    face: &SolidFace,
+    position: &impl Fn(usize) -> Vec3,
+    scales: &LightmapScales,
+) -> Option<FaceMapping> {
+    let points: Vec<Vec3> = face.vertice_indices.iter().map(|&i| position(i)).collect();
+
+    if points.len() < 3 {
+        return None;
+    }
+
+    let newell = newell_normal(&points);
+    let area = newell.length() / 2.0;
+    let normal = newell.normalize_or_zero();
+
+    if normal == Vec3::ZERO {
+        return None;
+    }
+
+    // the corner spanning the largest triangle with the first two is the most stable
+    let first_edge = points[1] - points[0];
+    let third = (2..points.len()).max_by(|&a, &b| {
+        let area_a = first_edge.cross(points[a] - points[0]).length_squared();
+        let area_b = first_edge.cross(points[b] - points[0]).length_squared();
+        area_a.total_cmp(&area_b)
+    })?;
+
+    let second_edge = points[third] - points[0];
+    let axis_x = first_edge.normalize_or_zero();
+    let axis_y = normal.cross(axis_x);
+
+    let edges = Mat2::from_cols(
+        Vec2::new(first_edge.dot(axis_x), second_edge.dot(axis_x)),
+        Vec2::new(first_edge.dot(axis_y), second_edge.dot(axis_y)),
+    );
+
+    if edges.determinant().abs() < f32::EPSILON {
+        return None;
+    }
+
+    let uvs = &face.vertice_uvs;
+    let inverse = edges.inverse();
+    let u_gradient = inverse * Vec2::new(uvs[1].x - uvs[0].x, uvs[third].x - uvs[0].x);
+    let v_gradient = inverse * Vec2::new(uvs[1].y - uvs[0].y, uvs[third].y - uvs[0].y);
+    let gradient = Vec2::new(u_gradient.length(), v_gradient.length());
+
+    // faces without a usable texture mapping are laid out on their plane instead
+    if gradient.min_element() < f32::EPSILON {
+        return None;
+    }
+
+    Some(FaceMapping {
+        gradient,
+        area,
+        lightmap_scale: scales.get(normal, normal.dot(points[0])),
+    })
+}
+
+fn planar_luxels(face: &SolidFace, position: &impl Fn(usize) -> Vec3, scale: f32) -> Vec<Vec2> {
+    let points: Vec<Vec3> = face.vertice_indices.iter().map(|&i| position(i)).collect();
+    let normal = newell_normal(&points).normalize_or_zero();
+    let up = if normal.z.abs() < 0.9 {
+        Vec3::Z
+    } else {
+        Vec3::X
+    };
+    let axis_x = up.cross(normal).normalize_or_zero();
+    let axis_y = normal.cross(axis_x);
+
+    points
+        .iter()
+        .map(|&point| Vec2::new(point.dot(axis_x), point.dot(axis_y)) / scale)
+        .collect()
+}
+
+fn newell_normal(points: &[Vec3]) -> Vec3 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .fold(Vec3::ZERO, |sum, (&a, &b)| sum + a.cross(b))
+}
+
+/// Packs charts by their bounds into rows of a square.
+/// Returns the position of each chart and the side length of the square, in luxels.
+fn pack(bounds: &HashMap<usize, (Vec2, Vec2)>) -> (HashMap<usize, Vec2>, f32) {
+    let mut charts: Vec<(usize, Vec2)> = bounds
+        .iter()
+        .map(|(&chart, &(min, max))| (chart, (max - min).ceil() + 2.0 * PADDING))
+        .collect();
+
+    // tallest first, ties by chart so the layout is the same every time
+    charts.sort_by(|a, b| b.1.y.total_cmp(&a.1.y).then(a.0.cmp(&b.0)));
+
+    let area: f32 = charts.iter().map(|(_, size)| size.x * size.y).sum();
+    let widest = charts.iter().map(|(_, size)| size.x).fold(0.0, f32::max);
+    let row_width = area.sqrt().ceil().max(widest);
+
+    let mut positions = HashMap::with_capacity(charts.len());
+    let mut cursor = Vec2::ZERO;
+    let mut row_height: f32 = 0.0;
+
+    for (chart, size) in charts {
+        if cursor.x + size.x > row_width {
+            cursor = Vec2::new(0.0, cursor.y + row_height);
+            row_height = 0.0;
+        }
+
+        positions.insert(chart, cursor + PADDING);
+        cursor.x += size.x;
+        row_height = row_height.max(size.y);
+    }
+
+    let side = row_width.max(cursor.y + row_height).max(1.0);
+
+    (positions, side)
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.parents[a] = b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::keyvalues::parse_document;
+
+    fn face(vertice_indices: Vec<usize>, vertice_uvs: Vec<Vec2>) -> SolidFace {
+        SolidFace {
+            vertice_alphas: vec![0.0; vertice_indices.len()],
+            vertice_indices,
+            vertice_uvs,
+            vertice_multiblends: None,
+            material_index: 0,
+        }
+    }
+
+    #[test]
+    fn reads_lightmap_scales() {
+        let vmf = br#"solid
+{
+	side
+	{
+		"id" "1"
+		"plane" "(0 0 64) (64 0 64) (64 -64 64)"
+		"lightmapscale" "32"
+	}
+	side
+	{
+		"id" "2"
+		"plane" "(0 0 0) (0 64 0) (64 64 0)"
+		"lightmapscale" "16"
+	}
+}"#;
+
+        let mut scales = LightmapScales::default();
+        scales.extend_from_keyvalues(&parse_document(vmf));
+
+        assert_eq!(scales.planes.len(), 1);
+        assert!((scales.get(Vec3::Z, 64.0) - 32.0).abs() < f32::EPSILON);
+        assert!((scales.get(-Vec3::Z, -64.0) - 32.0).abs() < f32::EPSILON);
+        assert!((scales.get(Vec3::Z, 0.0) - DEFAULT_LIGHTMAP_SCALE).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn joins_continuous_faces_at_luxel_density() {
+        // two 64 by 64 quads next to each other, with a 128 unit wide texture across both
+        let vertices = [
+            0.0, 0.0, 0.0, //
+            64.0, 0.0, 0.0, //
+            128.0, 0.0, 0.0, //
+            0.0, 64.0, 0.0, //
+            64.0, 64.0, 0.0, //
+            128.0, 64.0, 0.0,
+        ];
+        let uv = |x: f32, y: f32| Vec2::new(x / 128.0, y / 128.0);
+        let faces = [
+            face(
+                vec![0, 1, 4, 3],
+                vec![uv(0.0, 0.0), uv(64.0, 0.0), uv(64.0, 64.0), uv(0.0, 64.0)],
+            ),
+            face(
+                vec![1, 2, 5, 4],
+                vec![
+                    uv(64.0, 0.0),
+                    uv(128.0, 0.0),
+                    uv(128.0, 64.0),
+                    uv(64.0, 64.0),
+                ],
+            ),
+        ];
+
+        let uvs = lightmap_uvs(Vec3::ZERO, &vertices, &faces, &LightmapScales::default());
+
+        // one chart of 8 by 4 luxels with padding on both sides
+        assert_eq!(uvs.resolution, 10);
+        assert_eq!(uvs.flat_loop_uvs.len(), 16);
+        // the shared corners have the same lightmap coordinates
+        assert_eq!(&uvs.flat_loop_uvs[2..4], &uvs.flat_loop_uvs[8..10]);
+        assert_eq!(uvs.face_scales, [DEFAULT_LIGHTMAP_SCALE; 2]);
+    }
+}
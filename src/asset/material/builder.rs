@@ -1,10 +1,15 @@
-use std::str::FromStr;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 use glam::{Vec2, Vec3};
 use image::ImageOutputFormat;
+#[cfg(feature = "python")]
 use pyo3::{exceptions::PyValueError, PyErr};
 use rgb::RGB;
-use tracing::warn;
+use tracing::{info_span, warn};
 
 use plumber_core::{
     asset_core::Context,
@@ -23,6 +28,25 @@ use super::{
     BuiltMaterialData,
 };
 
+/// Error for a setting value that isn't one of the known options.
+#[derive(Debug, Clone, Copy)]
+pub struct InvalidSettingError(&'static str);
+
+impl Display for InvalidSettingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}", self.0)
+    }
+}
+
+impl Error for InvalidSettingError {}
+
+#[cfg(feature = "python")]
+impl From<InvalidSettingError> for PyErr {
+    fn from(err: InvalidSettingError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum TextureInterpolation {
     Linear,
@@ -32,7 +56,7 @@ pub enum TextureInterpolation {
 }
 
 impl FromStr for TextureInterpolation {
-    type Err = PyErr;
+    type Err = InvalidSettingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -40,7 +64,7 @@ impl FromStr for TextureInterpolation {
             "Closest" => Ok(Self::Closest),
             "Cubic" => Ok(Self::Cubic),
             "Smart" => Ok(Self::Smart),
-            _ => Err(PyValueError::new_err("invalid texture interpolation")),
+            _ => Err(InvalidSettingError("texture interpolation")),
         }
     }
 }
@@ -66,16 +90,22 @@ impl TextureInterpolation {
 pub enum TextureFormat {
     Tga,
     Png,
+    /// Keeps the values of HDR textures above 1.0.
+    Exr,
+    /// Decoded pixels, without encoding them into an image file.
+    Raw,
 }
 
 impl FromStr for TextureFormat {
-    type Err = PyErr;
+    type Err = InvalidSettingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "Tga" => Ok(Self::Tga),
             "Png" => Ok(Self::Png),
-            _ => Err(PyValueError::new_err("invalid texture format")),
+            "Exr" => Ok(Self::Exr),
+            "Raw" => Ok(Self::Raw),
+            _ => Err(InvalidSettingError("texture format")),
         }
     }
 }
@@ -91,13 +121,17 @@ impl TextureFormat {
         match self {
             TextureFormat::Tga => ".tga",
             TextureFormat::Png => ".png",
+            TextureFormat::Exr => ".exr",
+            TextureFormat::Raw => "",
         }
     }
 
-    pub fn to_output_format(self) -> ImageOutputFormat {
+    pub fn to_output_format(self) -> Option<ImageOutputFormat> {
         match self {
-            TextureFormat::Tga => ImageOutputFormat::Tga,
-            TextureFormat::Png => ImageOutputFormat::Png,
+            TextureFormat::Tga => Some(ImageOutputFormat::Tga),
+            TextureFormat::Png => Some(ImageOutputFormat::Png),
+            TextureFormat::Exr => Some(ImageOutputFormat::OpenExr),
+            TextureFormat::Raw => None,
         }
     }
 }
@@ -107,6 +141,10 @@ impl TextureFormat {
 pub struct Settings {
     pub import_materials: bool,
     pub simple_materials: bool,
+    /// Estimate metallic and roughness values from the shading parameters of materials.
+    pub pbr_estimation: bool,
+    /// Use the inverted envmap mask of materials as their roughness.
+    pub envmap_mask_roughness: bool,
     pub allow_culling: bool,
     pub editor_materials: bool,
     pub texture_interpolation: TextureInterpolation,
@@ -195,7 +233,12 @@ impl MaterialBuilder {
         {
             let texture_path = texture.absolute_path();
 
-            match context.depend_on(VtfConfig, texture_path.clone().into()) {
+            let loaded = {
+                let _span = info_span!("vtf load", id = %texture_path).entered();
+                context.depend_on(VtfConfig, texture_path.clone().into())
+            };
+
+            match loaded {
                 Ok(_) => {
                     self.texture_color_spaces
                         .insert(texture_path.clone().into_string(), ColorSpace::NonColor);
@@ -216,13 +259,15 @@ impl MaterialBuilder {
                     true
                 }
                 Err(err) => {
-                    warn!(
+                    let warning = format!(
                         "material `{}`: parameter `{}`: error loading texture `{}`: {}",
                         vmt.material_path(),
                         parameter,
                         texture_path,
                         err
                     );
+                    warn!("{warning}");
+                    self.warnings.push(warning);
 
                     false
                 }
@@ -249,7 +294,12 @@ impl MaterialBuilder {
             let mut texture_path = texture.absolute_path();
             texture_path.set_extension("");
 
-            match context.depend_on(VtfConfig, texture_path.clone().into()) {
+            let loaded = {
+                let _span = info_span!("vtf load", id = %texture_path).entered();
+                context.depend_on(VtfConfig, texture_path.clone().into())
+            };
+
+            match loaded {
                 Ok(_) => {
                     self.texture_color_spaces
                         .insert(texture_path.clone().into_string(), color_space);
@@ -297,13 +347,15 @@ impl MaterialBuilder {
                     true
                 }
                 Err(err) => {
-                    warn!(
+                    let warning = format!(
                         "material `{}`: parameter `{}`: error loading texture `{}`: {}",
                         vmt.material_path(),
                         parameter,
                         texture_path,
                         err
                     );
+                    warn!("{warning}");
+                    self.warnings.push(warning);
 
                     false
                 }
@@ -422,6 +474,18 @@ fn phong_exponent_to_roughness(exponent: f32) -> f32 {
     0.66 * (150.0 - exponent) / 150.0
 }
 
+/// Converts a Blinn-Phong exponent to the roughness of a GGX distribution with a similar
+/// highlight, using `alpha = sqrt(2 / (exponent + 2))` and `roughness = sqrt(alpha)`.
+fn phong_exponent_to_pbr_roughness(exponent: f32) -> f32 {
+    (2.0 / (exponent.max(0.0) + 2.0)).powf(0.25)
+}
+
+/// Roughness of materials with an `$envmap` but no phong, which reflect like a mirror.
+const ENVMAP_PBR_ROUGHNESS: f32 = 0.15;
+
+/// Specular level of the Principled BSDF which gives the 4% reflectance of dielectrics.
+const DIELECTRIC_SPECULAR: f32 = 0.5;
+
 struct NormalMaterialBuilder<'a, 'b, 'c, 'd> {
     builder: MaterialBuilder,
     context: &'a mut Context<'b, 'd, BlenderAssetHandler>,
@@ -544,23 +608,30 @@ impl<'a, 'b, 'c, 'd> NormalMaterialBuilder<'a, 'b, 'c, 'd> {
                 .output("Specular", base_texture, "alpha")
                 .push(&groups::INVERT_VALUE)
                 .link_input(&groups::INVERT_VALUE, "value");
+
+            // the alpha is already the inverted mask
+            self.handle_envmap_mask_roughness(base_texture, "alpha", false);
         } else if self.builder.has_input("$bumpmap")
             && self
                 .vmt
                 .extract_param_or_default("$normalmapalphaenvmapmask")
         {
             self.builder.output("Specular", "$bumpmap", "alpha");
+            self.handle_envmap_mask_roughness("$bumpmap", "alpha", true);
         } else if self.builder.has_input("$tintmasktexture")
             && self
                 .vmt
                 .extract_param_or_default("$envmapmaskintintmasktexture")
         {
             self.builder.output("Specular", "$tintmasktexture", "r");
+            self.handle_envmap_mask_roughness("$tintmasktexture", "r", true);
         } else if self.handle_texture(
             "$envmapmask",
             Some("$envmapmasktransform"),
             ColorSpace::NonColor,
         ) {
+            self.handle_envmap_mask_roughness("$envmapmask", "color", true);
+
             let output = self.builder.output("Specular", "$envmapmask", "color");
 
             if let Some(tint) = self.vmt.extract_param::<RGB<f32>>("$envmaptint") {
@@ -583,6 +654,26 @@ impl<'a, 'b, 'c, 'd> NormalMaterialBuilder<'a, 'b, 'c, 'd> {
         true
     }
 
+    /// Outputs the envmap mask as the roughness if enabled, so unmasked areas are rough.
+    fn handle_envmap_mask_roughness(
+        &mut self,
+        input: &'static str,
+        source: &'static str,
+        invert: bool,
+    ) {
+        if !self.settings.envmap_mask_roughness {
+            return;
+        }
+
+        let output = self.builder.output("Roughness", input, source);
+
+        if invert {
+            output
+                .push(&groups::INVERT_VALUE)
+                .link_input(&groups::INVERT_VALUE, "value");
+        }
+    }
+
     fn handle_ssbump_detail(&mut self) {
         if self.vmt.extract_param_or_default::<u8>("$detailblendmode") != 10
             || !self.handle_texture("$detail", Some("$detailtexturetransform"), ColorSpace::Srgb)
@@ -1067,7 +1158,11 @@ impl NormalMaterialBuilder<'_, '_, '_, '_> {
 
         self.handle_texture_split("$masks1");
 
-        if !self.handle_phong(blend_input) && !self.handle_envmap("$basetexture") {
+        if self.settings.pbr_estimation {
+            if !self.handle_phong_pbr() && !self.handle_envmap_pbr() {
+                self.handle_unlit();
+            }
+        } else if !self.handle_phong(blend_input) && !self.handle_envmap("$basetexture") {
             self.handle_unlit();
         }
 
@@ -1077,6 +1172,149 @@ impl NormalMaterialBuilder<'_, '_, '_, '_> {
     }
 }
 
+// PBR estimation
+//
+// Source materials scale the strength of their reflections with masks, while PBR
+// materials reflect the same amount with a varying roughness. So the specular level is
+// kept at that of dielectrics, and masks blend the roughness from fully rough where
+// they are black to the roughness of the phong exponent or the envmap where they are white.
+// Reflections tinted by the base texture with `$phongalbedotint` are treated as metal.
+impl NormalMaterialBuilder<'_, '_, '_, '_> {
+    /// Blends the roughness with a mask, or sets it directly without one.
+    fn output_masked_roughness(
+        &mut self,
+        mask: Option<(&'static str, &'static str)>,
+        roughness: f32,
+    ) {
+        if let Some((input, source)) = mask {
+            self.builder
+                .output("Roughness", input, source)
+                .push(&groups::BLEND_VALUES)
+                .link_input(&groups::BLEND_VALUES, "fac")
+                .link(&groups::BLEND_VALUES, "min", Value::Float(1.0))
+                .link(&groups::BLEND_VALUES, "max", Value::Float(roughness));
+        } else {
+            self.builder
+                .socket_value("Roughness", Value::Float(roughness));
+        }
+    }
+
+    fn handle_phong_pbr(&mut self) -> bool {
+        if !self.vmt.extract_param_or_default::<bool>("$phong")
+            && self.vmt.shader().shader.as_uncased_str() != "character".as_uncased()
+        {
+            return false;
+        }
+
+        self.builder
+            .socket_value("Specular", Value::Float(DIELECTRIC_SPECULAR));
+
+        let albedo_tint = self.vmt.extract_param_or_default("$phongalbedotint");
+
+        // the exponent texture stores the exponent itself, which is the roughness inverted
+        if self.vmt.extract_param::<f32>("$phongexponent").is_none()
+            && self.handle_texture_split("$phongexponenttexture")
+        {
+            self.builder
+                .output("Roughness", "$phongexponenttexture", "r")
+                .push(&groups::INVERT_VALUE)
+                .link_input(&groups::INVERT_VALUE, "value");
+
+            // the green channel masks where the albedo tint applies
+            if albedo_tint {
+                self.builder
+                    .output("Metallic", "$phongexponenttexture", "g");
+            }
+
+            return true;
+        }
+
+        if albedo_tint {
+            self.builder.socket_value("Metallic", Value::Float(1.0));
+        }
+
+        let mask = if self
+            .vmt
+            .extract_param_or_default("$basemapluminancephongmask")
+        {
+            Some(("$basetexture", "color"))
+        } else if self.vmt.extract_param_or_default("$basemapalphaphongmask") {
+            Some(("$basetexture", "alpha"))
+        } else if self.builder.has_input("$masks1") {
+            Some(("$masks1", "g"))
+        } else if self.builder.has_input("$bumpmap") {
+            Some(("$bumpmap", "alpha"))
+        } else {
+            None
+        };
+
+        // the default exponent of the shader
+        let exponent = self.vmt.extract_param("$phongexponent").unwrap_or(5.0);
+        self.output_masked_roughness(mask, phong_exponent_to_pbr_roughness(exponent));
+
+        true
+    }
+
+    fn handle_envmap_pbr(&mut self) -> bool {
+        if self.vmt.extract_param::<TexturePath>("$envmap").is_none() {
+            return false;
+        }
+
+        self.builder
+            .socket_value("Specular", Value::Float(DIELECTRIC_SPECULAR));
+
+        if self.builder.has_input("$basetexture")
+            && (self
+                .vmt
+                .extract_param_or_default::<bool>("$basealphaenvmapmask")
+                || self
+                    .vmt
+                    .extract_param_or_default::<bool>("$basealphaenvmask"))
+        {
+            // the alpha masks the reflections where it's black
+            self.builder
+                .output("Roughness", "$basetexture", "alpha")
+                .push(&groups::BLEND_VALUES)
+                .link_input(&groups::BLEND_VALUES, "fac")
+                .link(
+                    &groups::BLEND_VALUES,
+                    "min",
+                    Value::Float(ENVMAP_PBR_ROUGHNESS),
+                )
+                .link(&groups::BLEND_VALUES, "max", Value::Float(1.0));
+
+            return true;
+        }
+
+        let mask = if self.builder.has_input("$bumpmap")
+            && self
+                .vmt
+                .extract_param_or_default("$normalmapalphaenvmapmask")
+        {
+            Some(("$bumpmap", "alpha"))
+        } else if self.handle_texture(
+            "$envmapmask",
+            Some("$envmapmasktransform"),
+            ColorSpace::NonColor,
+        ) {
+            Some(("$envmapmask", "color"))
+        } else {
+            None
+        };
+
+        // dim reflections are rougher, as they are spread over a larger area
+        let tint = self
+            .vmt
+            .extract_param::<RGB<f32>>("$envmaptint")
+            .map_or(1.0, |tint| tint.iter().sum::<f32>() / 3.0);
+        let roughness = ENVMAP_PBR_ROUGHNESS + (1.0 - tint.clamp(0.0, 1.0)) * 0.5;
+
+        self.output_masked_roughness(mask, roughness);
+
+        true
+    }
+}
+
 // Simple material building
 impl NormalMaterialBuilder<'_, '_, '_, '_> {
     fn handle_basetexture_simple(&mut self) -> bool {
@@ -1592,4 +1830,14 @@ mod tests {
     fn build_nodraw_material_no_panic() {
         build_nodraw_material();
     }
+
+    #[test]
+    fn pbr_roughness_decreases_with_exponent() {
+        let rough = phong_exponent_to_pbr_roughness(1.0);
+        let glossy = phong_exponent_to_pbr_roughness(100.0);
+
+        assert!(rough > 0.9 && rough <= 1.0);
+        assert!(glossy > 0.3 && glossy < rough);
+        assert!((phong_exponent_to_pbr_roughness(0.0) - 1.0).abs() < 1e-6);
+    }
 }
@@ -2,6 +2,7 @@ use std::{collections::BTreeMap, iter, mem, ptr};
 
 use float_ord::FloatOrd;
 use itertools::{Either, Itertools};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use tracing::debug;
 
@@ -396,6 +397,7 @@ pub(crate) enum ColorSpace {
     NonColor,
 }
 
+#[cfg(feature = "python")]
 impl IntoPy<PyObject> for ColorSpace {
     fn into_py(self, py: Python) -> PyObject {
         match self {
@@ -412,6 +414,8 @@ pub(crate) struct MaterialBuilder {
     inputs: BTreeMap<&'static str, Input>,
     outputs: Vec<Output>,
     pub(crate) texture_color_spaces: BTreeMap<String, ColorSpace>,
+    /// Problems which didn't prevent building the material, such as missing textures.
+    pub(crate) warnings: Vec<String>,
 }
 
 impl MaterialBuilder {
@@ -423,6 +427,7 @@ impl MaterialBuilder {
             inputs: BTreeMap::new(),
             outputs: Vec::new(),
             texture_color_spaces: BTreeMap::new(),
+            warnings: Vec::new(),
         }
     }
 
@@ -576,6 +581,7 @@ impl MaterialBuilder {
             properties: self.properties,
             nodes,
             texture_color_spaces: self.texture_color_spaces,
+            warnings: self.warnings,
         }
     }
 }
@@ -601,28 +607,47 @@ fn sort_dependencies_recursive<'a>(
     }
 }
 
-#[pyclass(module = "plumber")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct BuiltMaterialData {
     properties: BTreeMap<&'static str, Value>,
     nodes: Vec<BuiltNode>,
     texture_color_spaces: BTreeMap<String, ColorSpace>,
+    warnings: Vec<String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl BuiltMaterialData {
-    fn properties(&mut self) -> BTreeMap<&'static str, Value> {
+    pub fn properties(&mut self) -> BTreeMap<&'static str, Value> {
         mem::take(&mut self.properties)
     }
 
-    fn nodes(&mut self) -> Vec<BuiltNode> {
+    pub fn nodes(&mut self) -> Vec<BuiltNode> {
         mem::take(&mut self.nodes)
     }
 
-    fn texture_color_spaces(&mut self) -> BTreeMap<String, ColorSpace> {
+    pub fn texture_color_spaces(&mut self) -> BTreeMap<String, ColorSpace> {
         mem::take(&mut self.texture_color_spaces)
     }
 }
 
+impl BuiltMaterialData {
+    pub fn texture_paths(&self) -> impl Iterator<Item = &str> {
+        self.texture_color_spaces.keys().map(String::as_str)
+    }
+
+    /// The first color texture of the material, usually `$basetexture`.
+    pub fn base_texture(&self) -> Option<&str> {
+        self.texture_color_spaces
+            .iter()
+            .find(|(_, color_space)| matches!(color_space, ColorSpace::Srgb))
+            .map(|(path, _)| path.as_str())
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        mem::take(&mut self.warnings)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
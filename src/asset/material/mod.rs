@@ -1,9 +1,19 @@
 use std::{
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap,
+    },
     fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
     io::Cursor,
+    mem,
     panic::{catch_unwind, AssertUnwindSafe},
 };
 
+use image::{ColorType, DynamicImage};
+use tracing::info_span;
+
+#[cfg(feature = "python")]
 use pyo3::{exceptions::PyRuntimeError, prelude::*};
 
 use plumber_core::{
@@ -14,10 +24,14 @@ use plumber_core::{
     vmt::MaterialInfo,
 };
 
-pub use builder::{build_material, Settings, TextureFormat, TextureInterpolation};
+pub use builder::{
+    build_material, InvalidSettingError, Settings, TextureFormat, TextureInterpolation,
+};
 pub use builder_base::BuiltMaterialData;
 pub use nodes::{BuiltNode, BuiltNodeSocketRef, TextureRef};
 
+#[cfg(feature = "python")]
+use super::buffer::image_buffer;
 use super::BlenderAssetHandler;
 
 mod builder;
@@ -25,84 +39,288 @@ mod builder_base;
 mod definitions;
 mod nodes;
 
-#[pyclass(module = "plumber")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct Texture {
     pub name: String,
     width: u32,
     height: u32,
     data: Vec<u8>,
+    #[cfg(feature = "python")]
+    raw: Option<DynamicImage>,
+    float_buffer: bool,
     format: TextureFormat,
+    /// Format the data is encoded in, which differs from `format` for 8-bit images
+    /// when `Exr` is used.
+    encoding: TextureFormat,
+    duplicate_of: Option<String>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl Texture {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn width(&self) -> u32 {
+    pub fn width(&self) -> u32 {
         self.width
     }
 
-    fn height(&self) -> u32 {
+    pub fn height(&self) -> u32 {
         self.height
     }
 
-    fn format_ext(&self) -> &'static str {
+    pub fn format_ext(&self) -> &'static str {
         self.format.to_ext_str()
     }
 
-    fn bytes(&self) -> &[u8] {
+    /// Extension of the format the data is encoded in.
+    pub fn encoding_ext(&self) -> &'static str {
+        self.encoding.to_ext_str()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
         &self.data
     }
+
+    /// Whether the pixels have a higher range than 8-bit images.
+    pub fn float_buffer(&self) -> bool {
+        self.float_buffer
+    }
+
+    /// Name of an earlier texture with the same contents, in which case this texture has no data
+    /// and should use the image of that texture.
+    pub fn duplicate_of(&self) -> Option<&str> {
+        self.duplicate_of.as_deref()
+    }
+
+    /// RGBA pixels starting from the bottom row, for `Raw` textures.
+    #[cfg(feature = "python")]
+    pub fn pixels<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let image = self
+            .raw
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("texture pixels already consumed"))?;
+
+        image_buffer(py, &image)
+    }
 }
 
 impl Texture {
     pub fn new(texture: &LoadedVtf, format: TextureFormat) -> Self {
-        let width = texture.data.width();
-        let height = texture.data.height();
+        Self::from_image(texture.name.to_string(), &texture.data, format)
+    }
+
+    /// Encodes a decoded image, for textures which don't come from a vtf.
+    pub fn from_image(name: String, image: &DynamicImage, format: TextureFormat) -> Self {
+        let width = image.width();
+        let height = image.height();
+
+        let high_range = !matches!(
+            image,
+            DynamicImage::ImageLuma8(_)
+                | DynamicImage::ImageLumaA8(_)
+                | DynamicImage::ImageRgb8(_)
+                | DynamicImage::ImageRgba8(_)
+        );
+
+        // 8-bit images have no range to keep, so they don't need to be inflated into floats
+        let encoding = match format {
+            TextureFormat::Exr if !high_range => TextureFormat::Png,
+            format => format,
+        };
 
         let mut data = Vec::new();
-        texture
-            .data
-            .write_to(&mut Cursor::new(&mut data), format.to_output_format())
-            .unwrap();
+
+        if let Some(output_format) = encoding.to_output_format() {
+            let mut cursor = Cursor::new(&mut data);
+
+            if let TextureFormat::Exr = encoding {
+                // the exr encoder only supports float images
+                DynamicImage::ImageRgba32F(image.to_rgba32f())
+                    .write_to(&mut cursor, output_format)
+                    .unwrap();
+            } else {
+                image.write_to(&mut cursor, output_format).unwrap();
+            }
+        }
+
+        // the decoded pixels are converted only once they're written into the Python buffer
+        #[cfg(feature = "python")]
+        let raw = encoding.to_output_format().is_none().then(|| image.clone());
 
         Self {
-            name: texture.name.to_string(),
+            name,
             width,
             height,
-            format,
             data,
+            #[cfg(feature = "python")]
+            raw,
+            float_buffer: high_range,
+            format,
+            encoding,
+            duplicate_of: None,
+        }
+    }
+
+    /// Creates a texture without data referring to an earlier texture with the same contents.
+    pub fn duplicate(name: String, original: String, format: TextureFormat) -> Self {
+        Self {
+            name,
+            width: 0,
+            height: 0,
+            data: Vec::new(),
+            #[cfg(feature = "python")]
+            raw: None,
+            float_buffer: false,
+            format,
+            encoding: format,
+            duplicate_of: Some(original),
+        }
+    }
+
+    /// File name of the encoded texture, including the extension.
+    pub fn file_name(&self) -> String {
+        format!("{}{}", self.name, self.encoding.to_ext_str())
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Identifies the decoded contents of an image, so identical images can be found
+/// without keeping the images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentKey {
+    width: u32,
+    height: u32,
+    color: ColorType,
+    hash: u64,
+    /// A second, independent hash, so a collision of one hash doesn't swap textures.
+    check: u64,
+}
+
+impl ContentKey {
+    pub fn new(image: &DynamicImage) -> Self {
+        let bytes = image.as_bytes();
+
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+
+        Self {
+            width: image.width(),
+            height: image.height(),
+            color: image.color(),
+            hash: hasher.finish(),
+            check: fnv1a(bytes),
         }
     }
 }
 
-#[pyclass(module = "plumber")]
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// The first of the textures with the same contents, which the later ones are sent as
+/// duplicates of.
+struct UniqueTexture {
+    name: String,
+    /// Duplicates found while this texture is encoded, which can only be sent after it.
+    waiting: Vec<String>,
+    sent: bool,
+}
+
+/// What to do with a texture found by [`SentTextures::deduplicate`].
+pub enum Deduplicated {
+    /// The texture has new contents and needs to be sent,
+    /// followed by [`SentTextures::mark_sent`].
+    Original,
+    /// The texture has the contents of an already sent texture.
+    Duplicate(String),
+    /// The texture has the contents of a texture which is still being encoded,
+    /// so it's sent as a duplicate once that texture is marked sent.
+    Waiting,
+}
+
+/// Names of the textures with unique contents, by the key of their contents.
+#[derive(Default)]
+pub struct SentTextures {
+    textures: HashMap<ContentKey, UniqueTexture>,
+}
+
+impl SentTextures {
+    /// Finds an earlier texture with the same contents,
+    /// or remembers this texture as the original of the following ones.
+    pub fn deduplicate(&mut self, key: ContentKey, name: &str) -> Deduplicated {
+        match self.textures.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let original = entry.get_mut();
+
+                if original.sent {
+                    Deduplicated::Duplicate(original.name.clone())
+                } else {
+                    original.waiting.push(name.to_owned());
+                    Deduplicated::Waiting
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(UniqueTexture {
+                    name: name.to_owned(),
+                    waiting: Vec::new(),
+                    sent: false,
+                });
+                Deduplicated::Original
+            }
+        }
+    }
+
+    /// Marks an original texture as sent, returning the names of the duplicates waiting for it.
+    pub fn mark_sent(&mut self, key: ContentKey) -> Vec<String> {
+        self.textures
+            .get_mut(&key)
+            .map(|original| {
+                original.sent = true;
+                mem::take(&mut original.waiting)
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct Material {
     pub name: String,
     data: Option<BuiltMaterialData>,
     texture_format: TextureFormat,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl Material {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn data(&mut self) -> PyResult<BuiltMaterialData> {
+    #[cfg(feature = "python")]
+    pub fn data(&mut self) -> PyResult<BuiltMaterialData> {
         self.data
             .take()
             .ok_or_else(|| PyRuntimeError::new_err("material data already consumed"))
     }
 
-    fn texture_ext(&self) -> &str {
+    pub fn texture_ext(&self) -> &str {
         self.texture_format.to_ext_str()
     }
 }
 
 impl Material {
+    pub fn built_data(&self) -> Option<&BuiltMaterialData> {
+        self.data.as_ref()
+    }
+
     pub fn new(name: &PathBuf, data: BuiltMaterialData, texture_format: TextureFormat) -> Self {
         Self {
             name: name.to_string(),
@@ -145,6 +363,9 @@ impl CachedAssetConfig<BlenderAssetHandler> for MaterialConfig {
     ) -> Result<(Self::Output<'a>, Self::CachedOutput), Self::Error> {
         input.normalize_extension();
 
+        // includes loading the textures the material depends on
+        let _span = info_span!("material build", id = %input).entered();
+
         let vmt_helper = VmtHelper::new(&input, context.fs())?;
         let info = vmt_helper.get_info(context.fs())?;
 
@@ -1,6 +1,7 @@
 use std::{cmp::Ordering, collections::BTreeMap, mem, ptr};
 
 use plumber_core::fs::GamePathBuf;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use tracing::debug;
 
@@ -12,6 +13,7 @@ pub enum NodeSocketId {
     Name(&'static str),
 }
 
+#[cfg(feature = "python")]
 impl IntoPy<PyObject> for NodeSocketId {
     fn into_py(self, py: Python) -> PyObject {
         match self {
@@ -81,16 +83,17 @@ pub enum Value {
     Texture(GamePathBuf),
 }
 
-#[pyclass(module = "plumber")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct TextureRef(String);
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl TextureRef {
-    fn path(&self) -> &str {
+    pub fn path(&self) -> &str {
         &self.0
     }
 }
 
+#[cfg(feature = "python")]
 impl IntoPy<PyObject> for Value {
     fn into_py(self, py: Python) -> PyObject {
         match self {
@@ -298,7 +301,7 @@ impl Node {
 }
 
 #[derive(Debug, Clone, Copy)]
-#[pyclass(module = "plumber")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct BuiltNodeSocketRef {
     node_index: usize,
     socket: NodeSocketId,
@@ -310,13 +313,13 @@ impl BuiltNodeSocketRef {
     }
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl BuiltNodeSocketRef {
-    fn node_index(&self) -> usize {
+    pub fn node_index(&self) -> usize {
         self.node_index
     }
 
-    fn socket(&self) -> NodeSocketId {
+    pub fn socket(&self) -> NodeSocketId {
         self.socket
     }
 }
@@ -327,7 +330,7 @@ pub enum BuiltNodeSocketLink {
     Value(Value),
 }
 
-#[pyclass(module = "plumber")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber"))]
 pub struct BuiltNode {
     kind: &'static NodeType,
     position: [f32; 2],
@@ -358,25 +361,25 @@ impl BuiltNode {
     }
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl BuiltNode {
-    fn blender_id(&self) -> &'static str {
+    pub fn blender_id(&self) -> &'static str {
         self.kind.blender_id
     }
 
-    fn position(&self) -> [f32; 2] {
+    pub fn position(&self) -> [f32; 2] {
         self.position
     }
 
-    fn properties(&mut self) -> BTreeMap<&'static str, Value> {
+    pub fn properties(&mut self) -> BTreeMap<&'static str, Value> {
         mem::take(&mut self.properties)
     }
 
-    fn socket_values(&mut self) -> BTreeMap<NodeSocketId, Value> {
+    pub fn socket_values(&mut self) -> BTreeMap<NodeSocketId, Value> {
         mem::take(&mut self.socket_values)
     }
 
-    fn socket_links(&mut self) -> BTreeMap<NodeSocketId, BuiltNodeSocketRef> {
+    pub fn socket_links(&mut self) -> BTreeMap<NodeSocketId, BuiltNodeSocketRef> {
         mem::take(&mut self.socket_links)
     }
 }
@@ -0,0 +1,49 @@
+//! Reading of the mdl sections the loaded models lack, shared by the readers of each section.
+
+use std::path::Path as StdPath;
+
+/// Returns the offsets of `count` records of `size` bytes, starting at `start`.
+/// The count comes from the mdl, so it's limited to the amount of records the mdl can hold.
+pub fn record_offsets(
+    bytes: &[u8],
+    count: usize,
+    start: usize,
+    size: usize,
+) -> impl Iterator<Item = usize> {
+    (0..count.min(bytes.len() / size))
+        .map_while(move |index| start.checked_add(index.checked_mul(size)?))
+}
+
+/// Reads a null terminated string of an mdl.
+pub fn read_string(bytes: &[u8], offset: usize) -> Option<String> {
+    let bytes = bytes.get(offset..)?;
+    let end = bytes.iter().position(|&b| b == 0)?;
+
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+/// Models are matched by their file name, since models read from outside the game
+/// are named by the path stored in the model.
+pub fn model_key(model: &str) -> String {
+    StdPath::new(model).file_stem().map_or_else(
+        || model.to_lowercase(),
+        |s| s.to_string_lossy().to_lowercase(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_record_counts_to_the_file() {
+        let bytes = [0; 64];
+
+        assert_eq!(
+            record_offsets(&bytes, 3, 8, 16).collect::<Vec<_>>(),
+            [8, 24, 40]
+        );
+        assert_eq!(record_offsets(&bytes, 1 << 31, 0, 16).count(), 4);
+        assert_eq!(record_offsets(&bytes, 2, usize::MAX, 16).count(), 1);
+    }
+}
@@ -1,14 +1,66 @@
+pub mod ain;
+pub mod anim_event;
+pub mod binary;
+pub mod bounds;
 pub mod brush;
+#[cfg(feature = "python")]
+mod buffer;
+pub mod color_correction;
+pub mod decal;
+pub mod dependency;
+pub mod diagnostic;
+pub mod displacement;
+pub mod editor_groups;
+pub mod encoding;
 pub mod entities;
+pub mod face_cleanup;
+pub mod glow;
+pub mod goldsrc;
+pub mod id_offset;
+pub mod ik_chain;
+pub mod jigglebone;
+pub mod job;
+mod keyvalues;
+pub mod lightmap;
 pub mod material;
+pub mod mdl;
 pub mod model;
+pub mod model_bounds;
+pub mod nav;
 pub mod overlay;
+pub mod parents;
+pub mod path_chain;
+pub mod profile;
+pub mod prop_queue;
+pub mod quake_map;
+pub mod radar;
+pub mod ragdoll;
+pub mod rmf;
+pub mod seam;
 pub mod sky;
+pub mod solid_repair;
+pub mod source_keyvalues;
+pub mod summary;
+pub mod up_axis;
 mod utils;
-use std::fmt::{self, Display, Formatter};
+pub mod vertices_plus;
+pub mod vmap;
+pub mod vmf_entities;
+pub mod vmf_load;
+pub mod vmf_metadata;
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    mem,
+    path::PathBuf as StdPathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
 
 use crossbeam_channel::Sender;
-use tracing::{debug_span, error};
+use tracing::{debug, debug_span, error, info_span, warn};
 
 use plumber_core::{
     asset_core::{Asset, Cached, Handler, NoError},
@@ -27,22 +79,51 @@ use plumber_core::{
     fs::PathBuf,
     vmf::{
         builder::{BuiltBrushEntity, BuiltOverlay},
-        entities::{BaseEntity, EntityParseError, TypedEntity},
+        entities::{BaseEntity, EntityParseError, TypedEntity, Unknown},
         vmf::Entity,
     },
 };
 
 use self::{
+    ain::{AinConfig, AinError, NodeGraph, PyNodeGraph},
+    anim_event::AnimationEvents,
     brush::PyBuiltBrushEntity,
+    color_correction::{
+        ColorCorrectionConfig, ColorCorrectionEntity, ColorCorrectionError, PyColorCorrection,
+    },
+    decal::{Decal, DecalConfig, PyDecal},
+    dependency::PyDependency,
+    diagnostic::{PyDiagnostic, Severity},
+    editor_groups::EditorGroups,
     entities::{
-        LightSettings, PyEnvLight, PyLight, PyLoadedProp, PySkyCamera, PySpotLight, PyUnknownEntity,
+        is_light_dynamic_spot, LightSettings, PyEnvLight, PyLight, PyLightGlow, PyLightingOrigin,
+        PyLoadedProp, PyPlayerSpawn, PyPointSpotlight, PyProjectedTexture, PySkyCamera, PySmoke,
+        PySpotLight, PySun, PyUnknownEntity, PLAYER_SPAWN_CLASS_NAMES, SMOKE_CLASS_NAMES,
     },
+    glow::{GlowConfig, GlowSprite},
+    goldsrc::{GoldSrcConfig, GoldSrcError, GoldSrcMap, PyGoldSrcModel},
+    ik_chain::IkChains,
+    jigglebone::JiggleBones,
+    lightmap::LightmapScales,
     material::{
-        BuiltMaterialData, Material, MaterialConfig, Settings as MaterialSettings, Texture,
+        BuiltMaterialData, ContentKey, Deduplicated, Material, MaterialConfig, SentTextures,
+        Settings as MaterialSettings, Texture,
     },
     model::PyModel,
+    model_bounds::ModelBoundsStore,
+    nav::{NavConfig, NavError, NavMesh, PyNavMesh},
     overlay::PyBuiltOverlay,
+    path_chain::PyPathChain,
+    radar::{PyRadar, Radar, RadarConfig, RadarError},
+    ragdoll::Ragdolls,
+    seam::BrushPlanes,
     sky::PySkyEqui,
+    source_keyvalues::SourceKeyValues,
+    up_axis::UpAxis,
+    vertices_plus::ExactVertices,
+    vmap::{Vmap, VmapConfig, VmapEntity, VmapError},
+    vmf_entities::VmfEntities,
+    vmf_metadata::VmfMetadata,
 };
 
 pub enum Message {
@@ -51,13 +132,29 @@ pub enum Message {
     Model(PyModel),
     Brush(PyBuiltBrushEntity),
     Overlay(PyBuiltOverlay),
+    Decal(PyDecal),
     Prop(PyLoadedProp),
     Light(PyLight),
     SpotLight(PySpotLight),
+    PointSpotlight(PyPointSpotlight),
+    ProjectedTexture(PyProjectedTexture),
     EnvLight(PyEnvLight),
+    Sun(PySun),
+    LightGlow(PyLightGlow),
+    Smoke(PySmoke),
+    LightingOrigin(PyLightingOrigin),
+    PlayerSpawn(PyPlayerSpawn),
     SkyCamera(PySkyCamera),
+    ColorCorrection(PyColorCorrection),
+    PathChain(PyPathChain),
     SkyEqui(PySkyEqui),
+    NavMesh(PyNavMesh),
+    GoldSrcModel(PyGoldSrcModel),
+    NodeGraph(PyNodeGraph),
+    Radar(PyRadar),
     UnknownEntity(PyUnknownEntity),
+    Dependency(PyDependency),
+    Diagnostic(PyDiagnostic),
 }
 
 enum MessageId {
@@ -82,13 +179,29 @@ impl Message {
             Message::Model(_) => "model",
             Message::Brush(_) => "brush",
             Message::Overlay(_) => "overlay",
+            Message::Decal(_) => "decal",
             Message::Prop(_) => "prop",
             Message::Light(_) => "light",
             Message::SpotLight(_) => "spot light",
+            Message::PointSpotlight(_) => "point spotlight",
+            Message::ProjectedTexture(_) => "projected texture",
             Message::EnvLight(_) => "env light",
+            Message::Sun(_) => "sun",
+            Message::LightGlow(_) => "light glow",
+            Message::Smoke(_) => "smoke",
+            Message::LightingOrigin(_) => "lighting origin",
+            Message::PlayerSpawn(_) => "player spawn",
             Message::SkyCamera(_) => "sky camera",
+            Message::ColorCorrection(_) => "color correction",
+            Message::PathChain(_) => "path chain",
             Message::SkyEqui(_) => "sky equi",
+            Message::NavMesh(_) => "nav mesh",
+            Message::GoldSrcModel(_) => "goldsrc model",
+            Message::NodeGraph(_) => "node graph",
+            Message::Radar(_) => "radar",
             Message::UnknownEntity(_) => "unknown entity",
+            Message::Dependency(_) => "dependency",
+            Message::Diagnostic(_) => "diagnostic",
         }
     }
 
@@ -99,17 +212,125 @@ impl Message {
             Message::Model(model) => MessageId::String(model.name.clone()),
             Message::Brush(brush) => MessageId::Int(brush.id),
             Message::Overlay(overlay) => MessageId::Int(overlay.id),
+            Message::Decal(decal) => MessageId::Int(decal.id),
             Message::Prop(prop) => MessageId::Int(prop.id),
             Message::Light(light) => MessageId::Int(light.id),
             Message::SpotLight(light) => MessageId::Int(light.id),
+            Message::PointSpotlight(light) => MessageId::Int(light.id),
+            Message::ProjectedTexture(light) => MessageId::Int(light.id),
             Message::EnvLight(light) => MessageId::Int(light.id),
+            Message::Sun(sun) => MessageId::Int(sun.id),
+            Message::LightGlow(glow) => MessageId::Int(glow.id),
+            Message::Smoke(smoke) => MessageId::Int(smoke.id),
+            Message::LightingOrigin(origin) => MessageId::Int(origin.id),
+            Message::PlayerSpawn(spawn) => MessageId::Int(spawn.id),
             Message::SkyCamera(camera) => MessageId::Int(camera.id),
+            Message::ColorCorrection(color_correction) => MessageId::Int(color_correction.id),
+            Message::PathChain(chain) => MessageId::Int(chain.id),
             Message::SkyEqui(equi) => MessageId::String(equi.name.clone()),
+            Message::NavMesh(nav) => MessageId::String(nav.name.clone()),
+            Message::GoldSrcModel(model) => MessageId::String(model.name.clone()),
+            Message::NodeGraph(graph) => MessageId::String(graph.name.clone()),
+            Message::Radar(radar) => MessageId::String(radar.name.clone()),
             Message::UnknownEntity(entity) => MessageId::Int(entity.id),
+            Message::Dependency(dependency) => {
+                MessageId::String(format!("{} {}", dependency.kind, dependency.name))
+            }
+            Message::Diagnostic(diagnostic) => MessageId::String(format!(
+                "{} {}",
+                diagnostic.asset_kind,
+                diagnostic.asset_id.as_deref().unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Returns the id of the entity the message is of and its parent id to fill in,
+    /// if it's of an entity which can be parented.
+    fn entity_parent(&mut self) -> Option<(i32, &mut Option<i32>)> {
+        match self {
+            Message::Brush(brush) => Some((brush.id, &mut brush.parent_id)),
+            Message::Prop(prop) => Some((prop.id, &mut prop.parent_id)),
+            Message::Light(light) => Some((light.id, &mut light.parent_id)),
+            Message::SpotLight(light) => Some((light.id, &mut light.parent_id)),
+            Message::PointSpotlight(light) => Some((light.id, &mut light.parent_id)),
+            Message::ProjectedTexture(light) => Some((light.id, &mut light.parent_id)),
+            Message::LightGlow(glow) => Some((glow.id, &mut glow.parent_id)),
+            Message::Smoke(smoke) => Some((smoke.id, &mut smoke.parent_id)),
+            Message::UnknownEntity(entity) => Some((entity.id, &mut entity.parent_id)),
+            _ => None,
+        }
+    }
+
+    /// Fills in the visgroups and groups of the message, if it's of a solid or entity.
+    fn set_editor_groups(&mut self, groups: &EditorGroups) {
+        let (id, editor_groups) = match self {
+            Message::Brush(brush) => {
+                brush.set_editor_groups(groups);
+                return;
+            }
+            Message::Overlay(overlay) => (overlay.id, &mut overlay.editor_groups),
+            Message::Decal(decal) => (decal.id, &mut decal.editor_groups),
+            Message::Prop(prop) => (prop.id, &mut prop.editor_groups),
+            Message::Light(light) => (light.id, &mut light.editor_groups),
+            Message::SpotLight(light) => (light.id, &mut light.editor_groups),
+            Message::PointSpotlight(light) => (light.id, &mut light.editor_groups),
+            Message::ProjectedTexture(light) => (light.id, &mut light.editor_groups),
+            Message::EnvLight(light) => (light.id, &mut light.editor_groups),
+            Message::Sun(sun) => (sun.id, &mut sun.editor_groups),
+            Message::LightGlow(glow) => (glow.id, &mut glow.editor_groups),
+            Message::Smoke(smoke) => (smoke.id, &mut smoke.editor_groups),
+            Message::SkyCamera(camera) => (camera.id, &mut camera.editor_groups),
+            Message::ColorCorrection(color_correction) => {
+                (color_correction.id, &mut color_correction.editor_groups)
+            }
+            Message::PathChain(chain) => (chain.id, &mut chain.editor_groups),
+            Message::UnknownEntity(entity) => (entity.id, &mut entity.editor_groups),
+            _ => return,
+        };
+
+        *editor_groups = groups.get(id);
+    }
+
+    /// Converts the world space positions, rotations and vertices of the message.
+    fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        if up_axis == UpAxis::Z {
+            return;
+        }
+
+        match self {
+            Message::Brush(brush) => brush.convert_up_axis(up_axis),
+            Message::Overlay(overlay) => overlay.convert_up_axis(up_axis),
+            Message::Decal(decal) => decal.convert_up_axis(up_axis),
+            Message::Prop(prop) => prop.convert_up_axis(up_axis),
+            Message::Light(light) => light.convert_up_axis(up_axis),
+            Message::SpotLight(light) => light.convert_up_axis(up_axis),
+            Message::PointSpotlight(light) => light.convert_up_axis(up_axis),
+            Message::ProjectedTexture(light) => light.convert_up_axis(up_axis),
+            Message::EnvLight(light) => light.convert_up_axis(up_axis),
+            Message::Sun(sun) => sun.convert_up_axis(up_axis),
+            Message::LightGlow(glow) => glow.convert_up_axis(up_axis),
+            Message::Smoke(smoke) => smoke.convert_up_axis(up_axis),
+            Message::LightingOrigin(origin) => origin.convert_up_axis(up_axis),
+            Message::PlayerSpawn(spawn) => spawn.convert_up_axis(up_axis),
+            Message::SkyCamera(camera) => camera.convert_up_axis(up_axis),
+            Message::ColorCorrection(color_correction) => {
+                color_correction.convert_up_axis(up_axis);
+            }
+            Message::PathChain(chain) => chain.convert_up_axis(up_axis),
+            Message::NavMesh(mesh) => mesh.convert_up_axis(up_axis),
+            Message::GoldSrcModel(model) => model.convert_up_axis(up_axis),
+            Message::NodeGraph(graph) => graph.convert_up_axis(up_axis),
+            Message::Radar(radar) => radar.convert_up_axis(up_axis),
+            Message::UnknownEntity(entity) => entity.convert_up_axis(up_axis),
+            // models stay in their own space, which the props place them from
+            _ => {}
         }
     }
 }
 
+/// Default amount of processed assets that can wait for the consumer before the workers block.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct HandlerSettings {
@@ -122,6 +343,37 @@ pub struct HandlerSettings {
     pub remove_animations: bool,
     pub material: MaterialSettings,
     pub import_unknown_entities: bool,
+    pub dependency_graph: bool,
+    /// Abort the import on the first asset error instead of skipping the asset.
+    pub strict: bool,
+    /// Send textures with the same contents as an already sent texture as duplicates without data.
+    pub deduplicate_textures: bool,
+    /// Lowercase names of the materials, textures and models the consumer already has.
+    /// They are not converted or sent again.
+    pub existing_assets: Arc<HashSet<String>>,
+    /// Amount of processed assets that can wait for the consumer.
+    /// When it's full the workers block, so a slow consumer limits the memory used by decoded assets.
+    pub channel_capacity: usize,
+    /// Distance in map units within which the open edges of displacements are sewn together.
+    /// Zero disables sewing.
+    pub seam_tolerance: f32,
+    /// Generate a second uv layer on brushes for baking lighting at the lightmap scale of their faces.
+    pub lightmap_uvs: bool,
+    /// Snap brush vertices to the exact vertices Hammer++ stores in vmfs.
+    pub exact_vertices: bool,
+    /// Send solids and entities with the visgroups and groups they are in,
+    /// and brushes with the keyvalues exporting them back into a vmf needs.
+    pub editor_groups: bool,
+    /// Remove faces without area and collinear vertices from brushes.
+    pub clean_faces: bool,
+    /// Distance in map units within which the vertices of brush meshes are welded together.
+    /// Zero disables welding.
+    pub weld_distance: f32,
+    /// Distance in map units overlays are moved off their surface per step of their render order.
+    /// Zero leaves them on the surface.
+    pub overlay_offset: f32,
+    /// Up axis the positions, rotations and vertices of the sent assets are converted into.
+    pub up_axis: UpAxis,
 }
 
 impl Default for HandlerSettings {
@@ -136,6 +388,19 @@ impl Default for HandlerSettings {
             remove_animations: false,
             material: MaterialSettings::default(),
             import_unknown_entities: false,
+            dependency_graph: false,
+            strict: false,
+            deduplicate_textures: true,
+            existing_assets: Arc::default(),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            seam_tolerance: 0.0,
+            lightmap_uvs: false,
+            exact_vertices: true,
+            editor_groups: true,
+            clean_faces: false,
+            weld_distance: 0.0,
+            overlay_offset: 0.0,
+            up_axis: UpAxis::Z,
         }
     }
 }
@@ -144,23 +409,259 @@ impl Default for HandlerSettings {
 pub struct BlenderAssetHandler {
     pub sender: Sender<Message>,
     pub settings: HandlerSettings,
+    /// Textures with unique contents, which identical textures are sent as duplicates of.
+    sent_textures: Arc<Mutex<SentTextures>>,
+    /// Lightmap scales of the faces of the imported vmfs, filled in as they are read.
+    lightmap_scales: Arc<RwLock<LightmapScales>>,
+    /// Vertices Hammer++ stored in the imported vmfs, filled in as they are read.
+    exact_vertices: Arc<RwLock<ExactVertices>>,
+    /// Planes of the brush sides of the imported vmfs, filled in as they are read.
+    brush_planes: Arc<RwLock<BrushPlanes>>,
+    /// Parents, brush entities and path chains of the imported vmfs, filled in as they are read.
+    vmf_entities: VmfEntities,
+    /// Visgroups and groups of the imported vmfs, filled in as they are read.
+    editor_groups: Arc<RwLock<EditorGroups>>,
+    /// Keyvalues of the brushes of the imported vmfs, filled in as they are read.
+    source_keyvalues: Arc<RwLock<SourceKeyValues>>,
+    /// Ragdoll joints of the imported models, filled in before the models are queued.
+    ragdolls: Arc<RwLock<Ragdolls>>,
+    /// Jiggle bones of the imported models, filled in before the models are queued.
+    jiggle_bones: Arc<RwLock<JiggleBones>>,
+    /// Ik chains of the imported models, filled in before the models are queued.
+    ik_chains: Arc<RwLock<IkChains>>,
+    /// Animation events of the imported models, filled in before the models are queued.
+    animation_events: Arc<RwLock<AnimationEvents>>,
+    /// Positions and bounding boxes of the imported models, filled in before the models are queued.
+    model_bounds: Arc<RwLock<ModelBoundsStore>>,
+    /// Set when the import is aborted, so no more jobs are processed or assets sent.
+    cancelled: Arc<AtomicBool>,
 }
 
 impl BlenderAssetHandler {
-    fn send_asset(&self, asset: Message) {
+    pub fn new(sender: Sender<Message>, settings: HandlerSettings) -> Self {
+        Self {
+            sender,
+            settings,
+            sent_textures: Arc::default(),
+            lightmap_scales: Arc::default(),
+            exact_vertices: Arc::default(),
+            brush_planes: Arc::default(),
+            vmf_entities: VmfEntities::default(),
+            editor_groups: Arc::default(),
+            source_keyvalues: Arc::default(),
+            ragdolls: Arc::default(),
+            jiggle_bones: Arc::default(),
+            ik_chains: Arc::default(),
+            animation_events: Arc::default(),
+            model_bounds: Arc::default(),
+            cancelled: Arc::default(),
+        }
+    }
+
+    /// Returns the stores of the vmf metadata the settings use, such as the lightmap scales
+    /// brush lightmap uvs are generated with.
+    /// The vmfs need to be added to them before their brushes are built.
+    pub fn vmf_metadata(&self) -> VmfMetadata {
+        VmfMetadata {
+            lightmap_scales: self
+                .settings
+                .lightmap_uvs
+                .then(|| self.lightmap_scales.clone()),
+            exact_vertices: self
+                .settings
+                .exact_vertices
+                .then(|| self.exact_vertices.clone()),
+            brush_planes: (self.settings.seam_tolerance > 0.0).then(|| self.brush_planes.clone()),
+            editor_groups: self
+                .settings
+                .editor_groups
+                .then(|| self.editor_groups.clone()),
+            source_keyvalues: self
+                .settings
+                .editor_groups
+                .then(|| self.source_keyvalues.clone()),
+        }
+    }
+
+    /// Returns the stores of the entity data which entities and brushes are sent with,
+    /// such as the ids of their parents.
+    /// The vmfs need to be added to them before their entities are converted.
+    pub fn vmf_entities(&self) -> VmfEntities {
+        self.vmf_entities.clone()
+    }
+
+    /// Returns the ragdoll joints models are sent with.
+    /// The `.phy` files of the models need to be added to them before the models are queued.
+    pub fn ragdolls(&self) -> Arc<RwLock<Ragdolls>> {
+        self.ragdolls.clone()
+    }
+
+    /// Returns the jiggle bones models are sent with.
+    /// The models need to be added to them before they are queued.
+    pub fn jiggle_bones(&self) -> Arc<RwLock<JiggleBones>> {
+        self.jiggle_bones.clone()
+    }
+
+    /// Returns the ik chains models are sent with.
+    /// The models need to be added to them before they are queued.
+    pub fn ik_chains(&self) -> Arc<RwLock<IkChains>> {
+        self.ik_chains.clone()
+    }
+
+    /// Returns the animation events models are sent with.
+    /// The models need to be added to them before they are queued.
+    pub fn animation_events(&self) -> Arc<RwLock<AnimationEvents>> {
+        self.animation_events.clone()
+    }
+
+    /// Returns the model positions and bounding boxes models are sent with.
+    /// The models need to be added to them before they are queued.
+    pub fn model_bounds(&self) -> Arc<RwLock<ModelBoundsStore>> {
+        self.model_bounds.clone()
+    }
+
+    /// Returns the flag which aborts the import when set.
+    /// Jobs which haven't started yet are skipped and no more assets are sent.
+    pub fn cancelled(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    /// Whether the consumer already has the asset, so it doesn't need to be sent.
+    fn is_existing(&self, name: &impl Display) -> bool {
+        let existing = &self.settings.existing_assets;
+
+        !existing.is_empty() && existing.contains(&name.to_string().to_lowercase())
+    }
+
+    fn send_texture(&self, texture: &LoadedVtf) {
+        let texture = {
+            let _span = info_span!("texture encode", id = %texture.name).entered();
+            Texture::new(texture, self.settings.material.texture_format)
+        };
+
+        self.send_asset(Message::Texture(texture));
+    }
+
+    fn send_duplicate_texture(&self, name: String, original: String) {
+        self.send_asset(Message::Texture(Texture::duplicate(
+            name,
+            original,
+            self.settings.material.texture_format,
+        )));
+    }
+
+    /// Sends a texture, or a duplicate of an earlier texture with the same contents.
+    /// Duplicates are only sent after their original, so materials can use their image.
+    fn send_deduplicated_texture(&self, texture: &LoadedVtf) {
+        let key = ContentKey::new(&texture.data);
+
+        let deduplicated = self
+            .sent_textures
+            .lock()
+            .expect("sent texture lock shouldn't be poisoned")
+            .deduplicate(key, &texture.name.to_string());
+
+        match deduplicated {
+            Deduplicated::Original => {
+                self.send_texture(texture);
+
+                let waiting = self
+                    .sent_textures
+                    .lock()
+                    .expect("sent texture lock shouldn't be poisoned")
+                    .mark_sent(key);
+
+                for duplicate in waiting {
+                    self.send_duplicate_texture(duplicate, texture.name.to_string());
+                }
+            }
+            Deduplicated::Duplicate(original) => {
+                self.send_duplicate_texture(texture.name.to_string(), original);
+            }
+            Deduplicated::Waiting => {}
+        }
+    }
+
+    fn send_asset(&self, mut asset: Message) {
         let _span = debug_span!("send_asset").entered();
 
+        if self.cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if let Some((id, parent_id)) = asset.entity_parent() {
+            *parent_id = self
+                .vmf_entities
+                .parents
+                .read()
+                .expect("entity parent lock shouldn't be poisoned")
+                .get(id);
+        }
+
+        asset.set_editor_groups(
+            &self
+                .editor_groups
+                .read()
+                .expect("editor group lock shouldn't be poisoned"),
+        );
+
+        asset.convert_up_axis(self.settings.up_axis);
+
         self.sender
             .send(asset)
             .expect("asset channel should stay connected");
     }
+
+    /// Sends the dependencies of an asset if the dependency graph is enabled.
+    fn send_dependency(&self, dependency: impl FnOnce() -> PyDependency) {
+        if self.settings.dependency_graph {
+            self.send_asset(Message::Dependency(dependency()));
+        }
+    }
+
+    /// Logs an error of an asset and sends it to be shown to the user.
+    fn send_error(&self, asset_kind: &'static str, asset_id: Option<String>, error: impl Display) {
+        error!("{error}");
+        self.send_asset(Message::Diagnostic(PyDiagnostic::new(
+            Severity::Error,
+            asset_kind,
+            asset_id,
+            error,
+        )));
+    }
+
+    fn send_entity_error(&self, entity: &Entity, error: &EntityParseError) {
+        let id = entity.id;
+        let class_name = &entity.class_name;
+
+        self.send_error(
+            "entity",
+            Some(id.to_string()),
+            format_args!("entity {class_name} `{id}`: {error}"),
+        );
+    }
 }
 
 impl Handler<Cached<MaterialConfig>> for BlenderAssetHandler {
     fn handle(&self, output: Result<(PathBuf, Option<BuiltMaterialData>), VmtError>) {
         match output {
             Ok((name, material)) => {
-                if let Some(material) = material {
+                if self.is_existing(&name) {
+                    return;
+                }
+
+                if let Some(mut material) = material {
+                    // texture problems are already logged by the material builder
+                    for warning in material.take_warnings() {
+                        self.send_asset(Message::Diagnostic(PyDiagnostic::new(
+                            Severity::Warning,
+                            "material",
+                            Some(name.to_string()),
+                            warning,
+                        )));
+                    }
+
+                    self.send_dependency(|| PyDependency::material(name.to_string(), &material));
                     self.send_asset(Message::Material(Material::new(
                         &name,
                         material,
@@ -168,7 +669,7 @@ impl Handler<Cached<MaterialConfig>> for BlenderAssetHandler {
                     )));
                 }
             }
-            Err(error) => error!("{error}"),
+            Err(error) => self.send_error("material", Some(error.path.to_string()), error),
         }
     }
 }
@@ -176,11 +677,18 @@ impl Handler<Cached<MaterialConfig>> for BlenderAssetHandler {
 impl Handler<Cached<VtfConfig>> for BlenderAssetHandler {
     fn handle(&self, output: Result<LoadedVtf, VtfError>) {
         match output {
-            Ok(texture) => self.send_asset(Message::Texture(Texture::new(
-                &texture,
-                self.settings.material.texture_format,
-            ))),
-            Err(error) => error!("{error}"),
+            Ok(texture) => {
+                if self.is_existing(&texture.name) {
+                    return;
+                }
+
+                if self.settings.deduplicate_textures {
+                    self.send_deduplicated_texture(&texture);
+                } else {
+                    self.send_texture(&texture);
+                }
+            }
+            Err(error) => self.send_error("texture", None, error),
         }
     }
 }
@@ -188,12 +696,56 @@ impl Handler<Cached<VtfConfig>> for BlenderAssetHandler {
 impl Handler<Cached<MdlConfig<MaterialConfig>>> for BlenderAssetHandler {
     fn handle(&self, output: Result<LoadedMdl, MdlError>) {
         match output {
-            Ok(model) => self.send_asset(Message::Model(PyModel::new(
-                model,
-                self.settings.target_fps,
-                self.settings.remove_animations,
-            ))),
-            Err(error) => error!("{error}"),
+            Ok(model) => {
+                if self.is_existing(&model.name) {
+                    return;
+                }
+
+                let model = {
+                    let _span = info_span!("model conversion", id = %model.name).entered();
+                    let mut model = PyModel::new(
+                        model,
+                        self.settings.target_fps,
+                        self.settings.remove_animations,
+                    );
+
+                    model.set_bone_setup(
+                        self.ragdolls
+                            .read()
+                            .expect("ragdoll lock shouldn't be poisoned")
+                            .get(&model.name),
+                        self.jiggle_bones
+                            .read()
+                            .expect("jiggle bone lock shouldn't be poisoned")
+                            .get(&model.name),
+                        self.ik_chains
+                            .read()
+                            .expect("ik chain lock shouldn't be poisoned")
+                            .get(&model.name),
+                    );
+
+                    model.set_animation_events(
+                        self.animation_events
+                            .read()
+                            .expect("animation event lock shouldn't be poisoned")
+                            .get(&model.name),
+                        self.settings.target_fps,
+                    );
+
+                    model.set_bounds(
+                        self.model_bounds
+                            .read()
+                            .expect("model bounds lock shouldn't be poisoned")
+                            .get(&model.name),
+                    );
+
+                    model
+                };
+
+                self.send_dependency(|| PyDependency::model(&model));
+                self.send_asset(Message::Model(model));
+            }
+            Err(error) => self.send_error("model", None, error),
         }
     }
 }
@@ -204,30 +756,152 @@ impl Handler<Asset<OtherEntityConfig>> for BlenderAssetHandler {
 
         match entity {
             TypedEntity::Light(light) if self.settings.import_lights => {
-                match PyLight::new(light, &self.settings.light, self.settings.scale) {
+                match PyLight::new(
+                    light,
+                    &self.settings.light,
+                    self.settings.scale,
+                    self.settings.target_fps,
+                ) {
                     Ok(light) => self.send_asset(Message::Light(light)),
-                    Err(error) => log_entity_error(light.entity(), &error),
+                    Err(error) => self.send_entity_error(light.entity(), &error),
                 }
             }
             TypedEntity::SpotLight(spot_light) if self.settings.import_lights => {
-                match PySpotLight::new(spot_light, &self.settings.light, self.settings.scale) {
+                match PySpotLight::new(
+                    spot_light,
+                    &self.settings.light,
+                    self.settings.scale,
+                    self.settings.target_fps,
+                ) {
                     Ok(light) => self.send_asset(Message::SpotLight(light)),
-                    Err(error) => log_entity_error(spot_light.entity(), &error),
+                    Err(error) => self.send_entity_error(spot_light.entity(), &error),
                 }
             }
             TypedEntity::EnvLight(env_light) if self.settings.import_lights => {
                 match PyEnvLight::new(env_light, &self.settings.light, self.settings.scale) {
                     Ok(light) => self.send_asset(Message::EnvLight(light)),
-                    Err(error) => log_entity_error(env_light.entity(), &error),
+                    Err(error) => self.send_entity_error(env_light.entity(), &error),
                 }
             }
             TypedEntity::SkyCamera(sky_camera) if self.settings.import_sky_camera => {
                 match PySkyCamera::new(sky_camera, self.settings.scale) {
                     Ok(sky_camera) => self.send_asset(Message::SkyCamera(sky_camera)),
-                    Err(error) => log_entity_error(sky_camera.entity(), &error),
+                    Err(error) => self.send_entity_error(sky_camera.entity(), &error),
+                }
+            }
+            TypedEntity::Unknown(entity) => self.handle_unknown_entity(entity),
+            _ => {}
+        }
+    }
+}
+
+impl BlenderAssetHandler {
+    fn handle_unknown_entity(&self, entity: Unknown) {
+        let chain = self
+            .vmf_entities
+            .path_chains
+            .write()
+            .expect("path chain lock shouldn't be poisoned")
+            .take(entity.entity().id);
+
+        if let Some(chain) = chain {
+            self.send_asset(Message::PathChain(PyPathChain::new(
+                chain,
+                self.settings.scale,
+            )));
+        }
+
+        match entity.entity().class_name.as_str() {
+            "light_dynamic" if self.settings.import_lights => {
+                let result = if is_light_dynamic_spot(&entity) {
+                    PySpotLight::from_light_dynamic(
+                        &entity,
+                        &self.settings.light,
+                        self.settings.scale,
+                        self.settings.target_fps,
+                    )
+                    .map(Message::SpotLight)
+                } else {
+                    PyLight::from_light_dynamic(
+                        &entity,
+                        &self.settings.light,
+                        self.settings.scale,
+                        self.settings.target_fps,
+                    )
+                    .map(Message::Light)
+                };
+
+                match result {
+                    Ok(message) => self.send_asset(message),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
+                }
+            }
+            "point_spotlight" if self.settings.import_lights => {
+                match PyPointSpotlight::new(&entity, &self.settings.light, self.settings.scale) {
+                    Ok(light) => self.send_asset(Message::PointSpotlight(light)),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
                 }
             }
-            TypedEntity::Unknown(entity) if self.settings.import_unknown_entities => {
+            "env_projectedtexture" if self.settings.import_lights => {
+                match PyProjectedTexture::new(
+                    &entity,
+                    &self.settings.light,
+                    self.settings.material.texture_format,
+                    self.settings.scale,
+                ) {
+                    Ok(light) => self.send_asset(Message::ProjectedTexture(light)),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
+                }
+            }
+            "env_sun" if self.settings.import_lights => {
+                match PySun::new(&entity, self.settings.scale) {
+                    Ok(sun) => self.send_asset(Message::Sun(sun)),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
+                }
+            }
+            "env_lightglow" if self.settings.import_lights => {
+                match PyLightGlow::new(&entity, self.settings.scale) {
+                    Ok(glow) => self.send_asset(Message::LightGlow(glow)),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
+                }
+            }
+            // sent once the size of their sprite is read
+            "env_glow" if self.settings.import_lights => {}
+            class_name
+                if SMOKE_CLASS_NAMES.contains(&class_name)
+                    && self.settings.import_unknown_entities =>
+            {
+                match PySmoke::new(&entity, self.settings.scale) {
+                    Ok(smoke) => self.send_asset(Message::Smoke(smoke)),
+                    Err(error) => self.send_entity_error(entity.entity(), &error),
+                }
+            }
+            "info_lighting" => {
+                if let Some(origin) = PyLightingOrigin::new(&entity, self.settings.scale) {
+                    self.send_asset(Message::LightingOrigin(origin));
+                }
+
+                if self.settings.import_unknown_entities {
+                    self.send_asset(Message::UnknownEntity(PyUnknownEntity::new(
+                        entity,
+                        self.settings.scale,
+                    )));
+                }
+            }
+            class_name if PLAYER_SPAWN_CLASS_NAMES.contains(&class_name) => {
+                self.send_asset(Message::PlayerSpawn(PyPlayerSpawn::new(
+                    &entity,
+                    self.settings.scale,
+                )));
+
+                if self.settings.import_unknown_entities {
+                    self.send_asset(Message::UnknownEntity(PyUnknownEntity::new(
+                        entity,
+                        self.settings.scale,
+                    )));
+                }
+            }
+            _ if self.settings.import_unknown_entities => {
                 self.send_asset(Message::UnknownEntity(PyUnknownEntity::new(
                     entity,
                     self.settings.scale,
@@ -236,21 +910,124 @@ impl Handler<Asset<OtherEntityConfig>> for BlenderAssetHandler {
             _ => {}
         }
     }
+
+    /// Reports the solids which were repaired while reading the vmfs since the last report.
+    fn send_solid_repairs(&self) {
+        let repairs = mem::take(
+            &mut *self
+                .vmf_entities
+                .solid_repairs
+                .lock()
+                .expect("solid repair lock shouldn't be poisoned"),
+        );
+
+        for repair in repairs {
+            warn!("{repair}");
+            self.send_asset(Message::Diagnostic(PyDiagnostic::new(
+                Severity::Warning,
+                "solid",
+                Some(repair.id.clone()),
+                repair,
+            )));
+        }
+    }
 }
 
 impl Handler<Asset<BrushConfig<'_, MaterialConfig>>> for BlenderAssetHandler {
     fn handle(&self, output: Result<BuiltBrushEntity<'_>, NoError>) {
         let brush = output.unwrap();
+        let brush = {
+            let _span = info_span!("brush conversion", id = brush.id).entered();
+            let mut brush = PyBuiltBrushEntity::new(brush);
+
+            // before sewing and welding, which move the vertices further
+            {
+                let exact_vertices = self
+                    .exact_vertices
+                    .read()
+                    .expect("exact vertex lock shouldn't be poisoned");
+
+                if !exact_vertices.is_empty() {
+                    let snapped = brush.snap_vertices(&exact_vertices);
+                    debug!("snapped {snapped} vertices of brush `{}`", brush.id);
+                }
+            }
+
+            if self.settings.seam_tolerance > 0.0 {
+                let brush_planes = self
+                    .brush_planes
+                    .read()
+                    .expect("brush plane lock shouldn't be poisoned");
+
+                let sewn = brush.sew_seams(self.settings.seam_tolerance, &brush_planes);
+                debug!("sewed {sewn} seam vertices of brush `{}`", brush.id);
+            }
+
+            if self.settings.weld_distance > 0.0 {
+                let welded = brush.weld_vertices(self.settings.weld_distance);
+                debug!(
+                    "welded {} vertices of brush `{}`",
+                    welded.vertices, brush.id
+                );
+            }
+
+            // after sewing and welding, which can collapse faces
+            if self.settings.clean_faces {
+                let cleanup = brush.clean_faces();
+                debug!(
+                    "removed {} faces, {} loops and {} vertices of brush `{}`",
+                    cleanup.faces, cleanup.loops, cleanup.vertices, brush.id
+                );
+            }
+
+            if self.settings.lightmap_uvs {
+                let scales = self
+                    .lightmap_scales
+                    .read()
+                    .expect("lightmap scale lock shouldn't be poisoned");
+
+                brush.generate_lightmap_uvs(&scales);
+            }
+
+            // after the faces are final, since the axes are looked up for each face
+            if self.settings.editor_groups {
+                let keyvalues = self
+                    .source_keyvalues
+                    .read()
+                    .expect("source keyvalue lock shouldn't be poisoned");
 
-        self.send_asset(Message::Brush(PyBuiltBrushEntity::new(brush)));
+                brush.set_source_keyvalues(&keyvalues);
+            }
+
+            brush.set_brush_entity(
+                &self
+                    .vmf_entities
+                    .brushes
+                    .read()
+                    .expect("brush entity lock shouldn't be poisoned"),
+                self.settings.scale,
+            );
+
+            brush
+        };
+
+        self.send_solid_repairs();
+
+        self.send_dependency(|| PyDependency::brush(&brush));
+        self.send_asset(Message::Brush(brush));
     }
 }
 
 impl Handler<Asset<OverlayConfig<'_, MaterialConfig>>> for BlenderAssetHandler {
     fn handle(&self, output: Result<BuiltOverlay<'_>, OverlayError>) {
         match output {
-            Ok(overlay) => self.send_asset(Message::Overlay(PyBuiltOverlay::new(overlay))),
-            Err(error) => error!("{error}"),
+            Ok(overlay) => {
+                let overlay = PyBuiltOverlay::new(overlay, self.settings.overlay_offset);
+
+                self.send_dependency(|| PyDependency::overlay(&overlay));
+                self.send_asset(Message::Overlay(overlay));
+            }
+            Err(error) => self.send_error("overlay", None, error),
         }
     }
 }
@@ -258,8 +1035,13 @@ impl Handler<Asset<OverlayConfig<'_, MaterialConfig>>> for BlenderAssetHandler {
 impl Handler<Asset<PropConfig<MaterialConfig>>> for BlenderAssetHandler {
     fn handle(&self, output: Result<LoadedProp<'_>, PropError>) {
         match output {
-            Ok(prop) => self.send_asset(Message::Prop(PyLoadedProp::new(prop))),
-            Err(error) => error!("{error}"),
+            Ok(prop) => {
+                let prop = PyLoadedProp::new(prop, self.settings.scale);
+
+                self.send_dependency(|| PyDependency::prop(&prop));
+                self.send_asset(Message::Prop(prop));
+            }
+            Err(error) => self.send_error("prop", None, error),
         }
     }
 }
@@ -271,14 +1053,144 @@ impl Handler<Asset<SkyBoxConfig>> for BlenderAssetHandler {
                 skybox,
                 self.settings.sky_equi_height,
             ))),
-            Err(error) => error!("{error}"),
+            Err(error) => self.send_error("skybox", None, error),
+        }
+    }
+}
+
+impl Handler<Asset<ColorCorrectionConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<(ColorCorrectionEntity, StdPathBuf), ColorCorrectionError>) {
+        match output {
+            Ok((entity, cube_path)) => self.send_asset(Message::ColorCorrection(
+                PyColorCorrection::new(entity, &cube_path, self.settings.scale),
+            )),
+            Err(error) => self.send_error("color correction", None, error),
         }
     }
 }
 
-fn log_entity_error(entity: &Entity, error: &EntityParseError) {
-    let id = entity.id;
-    let class_name = entity.class_name.clone();
+impl Handler<Asset<DecalConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<Decal, VmtError>) {
+        match output {
+            Ok(decal) => self.send_asset(Message::Decal(PyDecal::new(decal, self.settings.scale))),
+            Err(error) => self.send_error("decal", Some(error.path.to_string()), error),
+        }
+    }
+}
 
-    error!("entity {class_name} `{id}`: {error}");
+impl Handler<Asset<GlowConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<GlowSprite, NoError>) {
+        if let Ok(sprite) = output {
+            self.send_asset(Message::LightGlow(PyLightGlow::from_sprite(
+                &sprite,
+                self.settings.scale,
+            )));
+        }
+    }
+}
+
+impl Handler<Asset<NavConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<NavMesh, NavError>) {
+        match output {
+            Ok(nav) => self.send_asset(Message::NavMesh(PyNavMesh::new(nav, self.settings.scale))),
+            Err(error) => self.send_error("nav mesh", None, error),
+        }
+    }
+}
+
+impl Handler<Asset<GoldSrcConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<GoldSrcMap, GoldSrcError>) {
+        match output {
+            Ok(map) => {
+                let texture_format = self.settings.material.texture_format;
+
+                // textures are sent first so the materials can find their images
+                for (name, image) in map.textures {
+                    self.send_asset(Message::Texture(Texture::from_image(
+                        name,
+                        &image,
+                        texture_format,
+                    )));
+                }
+
+                for model in map.models {
+                    self.send_asset(Message::GoldSrcModel(PyGoldSrcModel::new(
+                        model,
+                        self.settings.scale,
+                        texture_format.to_ext_str(),
+                    )));
+                }
+            }
+            Err(error) => self.send_error("goldsrc map", None, error),
+        }
+    }
+}
+
+impl Handler<Asset<VmapConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<Vmap, VmapError>) {
+        match output {
+            Ok(vmap) => {
+                for brush in vmap.brushes {
+                    self.send_asset(Message::Brush(PyBuiltBrushEntity::from_vmap(
+                        brush,
+                        self.settings.scale,
+                    )));
+                }
+
+                for entity in &vmap.entities {
+                    self.handle_vmap_entity(entity);
+                }
+            }
+            Err(error) => self.send_error("vmap", None, error),
+        }
+    }
+}
+
+impl BlenderAssetHandler {
+    fn handle_vmap_entity(&self, entity: &VmapEntity) {
+        match entity.class_name.as_str() {
+            "light_omni" if self.settings.import_lights => {
+                self.send_asset(Message::Light(PyLight::from_vmap(
+                    entity,
+                    &self.settings.light,
+                    self.settings.scale,
+                )));
+            }
+            "light_spot" if self.settings.import_lights => {
+                self.send_asset(Message::SpotLight(PySpotLight::from_vmap(
+                    entity,
+                    &self.settings.light,
+                    self.settings.scale,
+                )));
+            }
+            _ if self.settings.import_unknown_entities => {
+                self.send_asset(Message::UnknownEntity(PyUnknownEntity::from_vmap(
+                    entity,
+                    self.settings.scale,
+                )));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Handler<Asset<AinConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<NodeGraph, AinError>) {
+        match output {
+            Ok(graph) => self.send_asset(Message::NodeGraph(PyNodeGraph::new(
+                graph,
+                self.settings.scale,
+            ))),
+            Err(error) => self.send_error("node graph", None, error),
+        }
+    }
+}
+
+impl Handler<Asset<RadarConfig>> for BlenderAssetHandler {
+    fn handle(&self, output: Result<Radar, RadarError>) {
+        match output {
+            Ok(radar) => self.send_asset(Message::Radar(PyRadar::new(radar, self.settings.scale))),
+            Err(error) => self.send_error("radar", None, error),
+        }
+    }
 }
@@ -1,58 +1,231 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     mem,
+    sync::{Arc, RwLock},
 };
 
 use glam::{Affine3A, EulerRot, Quat, Vec3};
-use pyo3::{prelude::*, types::PyList};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
 use tracing::warn;
 
 use plumber_core::{
-    asset_mdl::{LoadedAnimation, LoadedBone, LoadedMdl, LoadedMesh},
-    fs::GamePathBuf,
+    asset_core::{AssetConfig, Context, NoError},
+    asset_mdl::{LoadedAnimation, LoadedBone, LoadedMdl, LoadedMesh, MdlConfig},
+    fs::{GamePathBuf, PathBuf},
     mdl::{self, AnimationData, AnimationDescFlags, BoneAnimationData},
 };
 
-#[pyclass(module = "plumber", name = "Model")]
+use super::{
+    anim_event::{read_animation_events, AnimationEvent, AnimationEvents, PyAnimationEvent},
+    ik_chain::{read_ik_chains, IkChain, IkChains, PyIkChain},
+    jigglebone::{read_jiggle_bones, JiggleBone, JiggleBones, PyJiggleBone},
+    material::MaterialConfig,
+    model_bounds::{read_model_bounds, ModelBounds, ModelBoundsStore},
+    ragdoll::{read_ragdoll, PyRagdollJoint, RagdollJoint, Ragdolls},
+    BlenderAssetHandler,
+};
+
+/// Paths of a model and its `.phy` file.
+#[derive(Debug, Clone)]
+pub struct ModelPaths {
+    pub model: PathBuf,
+    pub physics: PathBuf,
+}
+
+/// Reads the ragdoll, the jiggle bones, the ik chains, the animation events and the bounds
+/// of a model before queueing the model itself, so they are known when it's converted.
+#[derive(Debug, Clone)]
+pub struct ModelBonesConfig {
+    pub config: MdlConfig<MaterialConfig>,
+    /// Filled with the ragdoll joints of the model.
+    pub ragdolls: Arc<RwLock<Ragdolls>>,
+    /// Filled with the jiggle bones of the model.
+    pub jiggle_bones: Arc<RwLock<JiggleBones>>,
+    /// Filled with the ik chains of the model.
+    pub ik_chains: Arc<RwLock<IkChains>>,
+    /// Filled with the animation events of the model.
+    pub animation_events: Arc<RwLock<AnimationEvents>>,
+    /// Filled with the illumination position and bounding boxes of the model.
+    pub model_bounds: Arc<RwLock<ModelBoundsStore>>,
+}
+
+impl AssetConfig<BlenderAssetHandler> for ModelBonesConfig {
+    type Input<'a> = ModelPaths;
+    type Output<'a> = ();
+    type Error<'a> = NoError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let name = input.model.to_string();
+
+        // most models without bones have no ragdoll, or no physics at all
+        if let Ok(bytes) = context.fs().read(&input.physics) {
+            self.ragdolls
+                .write()
+                .expect("ragdoll lock shouldn't be poisoned")
+                .insert(&name, read_ragdoll(&bytes));
+        }
+
+        // errors reading the model are reported when the model itself is read
+        if let Ok(bytes) = context.fs().read(&input.model) {
+            self.jiggle_bones
+                .write()
+                .expect("jiggle bone lock shouldn't be poisoned")
+                .insert(&name, read_jiggle_bones(&bytes));
+
+            self.ik_chains
+                .write()
+                .expect("ik chain lock shouldn't be poisoned")
+                .insert(&name, read_ik_chains(&bytes));
+
+            self.animation_events
+                .write()
+                .expect("animation event lock shouldn't be poisoned")
+                .insert(&name, read_animation_events(&bytes));
+
+            self.model_bounds
+                .write()
+                .expect("model bounds lock shouldn't be poisoned")
+                .insert(&name, read_model_bounds(&bytes));
+        }
+
+        context.queue(self.config, input.model);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "python")]
+use super::buffer::{float_buffer, int_buffer};
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Model"))]
 pub struct PyModel {
     pub name: String,
+    static_prop: bool,
     meshes: Vec<PyLoadedMesh>,
     materials: Vec<Option<String>>,
     bones: Vec<PyLoadedBone>,
     animations: Vec<PyLoadedAnimation>,
     rest_positions: BTreeMap<usize, PyBoneRestData>,
+    ragdoll: Vec<PyRagdollJoint>,
+    jiggle_bones: Vec<PyJiggleBone>,
+    ik_chains: Vec<PyIkChain>,
+    animation_events: Vec<PyAnimationEvent>,
+    bounds: Option<ModelBounds>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyModel {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn meshes(&mut self) -> Vec<PyLoadedMesh> {
+    /// The model is compiled with `$staticprop`, so its bones are baked into the meshes.
+    pub fn static_prop(&self) -> bool {
+        self.static_prop
+    }
+
+    pub fn meshes(&mut self) -> Vec<PyLoadedMesh> {
         mem::take(&mut self.meshes)
     }
 
-    fn materials(&mut self) -> Vec<Option<String>> {
+    pub fn materials(&mut self) -> Vec<Option<String>> {
         mem::take(&mut self.materials)
     }
 
-    fn bones(&mut self) -> Vec<PyLoadedBone> {
+    pub fn bones(&mut self) -> Vec<PyLoadedBone> {
         mem::take(&mut self.bones)
     }
 
-    fn animations(&mut self) -> Vec<PyLoadedAnimation> {
+    pub fn animations(&mut self) -> Vec<PyLoadedAnimation> {
         mem::take(&mut self.animations)
     }
 
-    fn rest_positions(&mut self) -> BTreeMap<usize, PyBoneRestData> {
+    pub fn rest_positions(&mut self) -> BTreeMap<usize, PyBoneRestData> {
         mem::take(&mut self.rest_positions)
     }
+
+    /// Rotation limits of the bones from the `.phy` file of the model.
+    pub fn ragdoll(&mut self) -> Vec<PyRagdollJoint> {
+        mem::take(&mut self.ragdoll)
+    }
+
+    /// Parameters of the `$jigglebone` bones of the model.
+    pub fn jiggle_bones(&mut self) -> Vec<PyJiggleBone> {
+        mem::take(&mut self.jiggle_bones)
+    }
+
+    pub fn ik_chains(&mut self) -> Vec<PyIkChain> {
+        mem::take(&mut self.ik_chains)
+    }
+
+    /// Events of the sequences of the model, on the frames of the imported animations.
+    pub fn animation_events(&mut self) -> Vec<PyAnimationEvent> {
+        mem::take(&mut self.animation_events)
+    }
+
+    /// Position the lighting of the model is sampled at, set with `$illumposition`.
+    pub fn illum_position(&self) -> Option<[f32; 3]> {
+        self.bounds.map(|b| b.illum_position)
+    }
+
+    pub fn eye_position(&self) -> Option<[f32; 3]> {
+        self.bounds.map(|b| b.eye_position)
+    }
+
+    /// Minimum and maximum corner of the movement hull of the model.
+    pub fn hull_bounds(&self) -> Option<[[f32; 3]; 2]> {
+        self.bounds.map(|b| b.hull)
+    }
+
+    /// Minimum and maximum corner of the box the model is culled with.
+    pub fn view_bounds(&self) -> Option<[[f32; 3]; 2]> {
+        self.bounds.map(|b| b.view)
+    }
 }
 
 impl PyModel {
+    pub fn material_names(&self) -> impl Iterator<Item = &str> {
+        self.materials.iter().flatten().map(String::as_str)
+    }
+
+    pub fn loaded_meshes(&self) -> &[PyLoadedMesh] {
+        &self.meshes
+    }
+
+    pub fn material(&self, index: usize) -> Option<&str> {
+        self.materials.get(index)?.as_deref()
+    }
+
+    /// Sets the ragdoll joints, the jiggle bones and the ik chains of the bones the model has.
+    pub fn set_bone_setup(
+        &mut self,
+        joints: &[RagdollJoint],
+        jiggle_bones: &[JiggleBone],
+        ik_chains: &[IkChain],
+    ) {
+        let bone_names: Vec<&str> = self.bones.iter().map(|b| b.name.as_str()).collect();
+        self.ragdoll = PyRagdollJoint::from_joints(joints, &bone_names);
+        self.jiggle_bones = PyJiggleBone::from_jiggle_bones(jiggle_bones, &bone_names);
+        self.ik_chains = PyIkChain::from_chains(ik_chains, bone_names.len());
+    }
+
+    pub fn set_animation_events(&mut self, events: &[AnimationEvent], target_fps: f32) {
+        self.animation_events = PyAnimationEvent::from_events(events, target_fps);
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<ModelBounds>) {
+        self.bounds = bounds;
+    }
+
     pub fn new(m: LoadedMdl, target_fps: f32, remove_animations: bool) -> Self {
-        let bones = if m.info.static_prop {
+        let static_prop = m.info.static_prop;
+
+        let bones = if static_prop {
             Vec::new()
         } else {
             m.bones.into_iter().map(PyLoadedBone::new).collect()
@@ -109,6 +282,7 @@ impl PyModel {
 
         Self {
             name: m.name.into_string(),
+            static_prop,
             meshes,
             materials: m
                 .materials
@@ -118,6 +292,11 @@ impl PyModel {
             bones,
             animations,
             rest_positions,
+            ragdoll: Vec::new(),
+            jiggle_bones: Vec::new(),
+            ik_chains: Vec::new(),
+            animation_events: Vec::new(),
+            bounds: None,
         }
     }
 }
@@ -154,68 +333,89 @@ fn rot_to_euler(rot: &Quat) -> [f32; 3] {
     [x, y, z]
 }
 
-#[pyclass(module = "plumber", name = "LoadedMesh")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "LoadedMesh"))]
 pub struct PyLoadedMesh {
     name: String,
     vertices: Vec<mdl::Vertex>,
     faces: Vec<mdl::Face>,
+    #[cfg(feature = "python")]
     flat_vertices: Vec<f32>,
+    #[cfg(feature = "python")]
     flat_polygon_vertice_indices: Vec<usize>,
+    #[cfg(feature = "python")]
     flat_loop_uvs: Vec<f32>,
     weight_groups: BTreeMap<u8, BTreeMap<usize, f32>>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyLoadedMesh {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn vertices(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_vertices)
+    #[cfg(feature = "python")]
+    pub fn vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_vertices))
     }
 
-    fn loops_len(&self) -> usize {
+    pub fn loops_len(&self) -> usize {
         self.faces.len() * 3
     }
 
-    fn polygons_len(&self) -> usize {
+    pub fn polygons_len(&self) -> usize {
         self.faces.len()
     }
 
-    fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, itertools::repeat_n(3, self.faces.len()))
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, itertools::repeat_n(3, self.faces.len()))
     }
 
-    fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, (0..self.faces.len()).map(|i| i * 3))
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, (0..self.faces.len()).map(|i| i * 3))
     }
 
-    fn polygon_vertices(&mut self) -> Vec<usize> {
-        mem::take(&mut self.flat_polygon_vertice_indices)
+    #[cfg(feature = "python")]
+    pub fn polygon_vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, mem::take(&mut self.flat_polygon_vertice_indices))
     }
 
-    fn polygon_material_indices<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.material_index))
+    #[cfg(feature = "python")]
+    pub fn polygon_material_indices<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.material_index))
     }
 
-    fn loop_uvs(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_uvs)
+    #[cfg(feature = "python")]
+    pub fn loop_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_uvs))
     }
 
-    fn normals<'p>(&mut self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.vertices.iter().map(|v| v.normal.as_ref()))
+    #[cfg(feature = "python")]
+    pub fn normals<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let normals: Vec<f32> = self.vertices.iter().flat_map(|v| v.normal).collect();
+        float_buffer(py, &normals)
     }
 
-    fn weight_groups(&mut self) -> BTreeMap<u8, BTreeMap<usize, f32>> {
+    pub fn weight_groups(&mut self) -> BTreeMap<u8, BTreeMap<usize, f32>> {
         mem::take(&mut self.weight_groups)
     }
 }
 
 impl PyLoadedMesh {
+    pub fn source_vertices(&self) -> &[mdl::Vertex] {
+        &self.vertices
+    }
+
+    pub fn source_faces(&self) -> &[mdl::Face] {
+        &self.faces
+    }
+
     fn new(mesh: LoadedMesh) -> Self {
+        #[cfg(feature = "python")]
         let flat_vertices = mesh.vertices.iter().flat_map(|v| v.position).collect();
 
+        #[cfg(feature = "python")]
         let flat_polygon_vertice_indices = mesh
             .faces
             .iter()
@@ -224,6 +424,7 @@ impl PyLoadedMesh {
             .copied()
             .collect();
 
+        #[cfg(feature = "python")]
         let flat_loop_uvs = mesh
             .faces
             .iter()
@@ -260,8 +461,11 @@ impl PyLoadedMesh {
             name,
             vertices: mesh.vertices,
             faces: mesh.faces,
+            #[cfg(feature = "python")]
             flat_vertices,
+            #[cfg(feature = "python")]
             flat_polygon_vertice_indices,
+            #[cfg(feature = "python")]
             flat_loop_uvs,
             weight_groups,
         }
@@ -270,7 +474,10 @@ impl PyLoadedMesh {
 
 #[allow(clippy::struct_field_names)]
 #[derive(Default)]
-#[pyclass(module = "plumber", name = "QuaternionData")]
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "QuaternionData")
+)]
 pub struct QuaternionData {
     flat_x_points: Vec<f32>,
     flat_y_points: Vec<f32>,
@@ -278,21 +485,21 @@ pub struct QuaternionData {
     flat_w_points: Vec<f32>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl QuaternionData {
-    fn x_points(&mut self) -> Vec<f32> {
+    pub fn x_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_x_points)
     }
 
-    fn y_points(&mut self) -> Vec<f32> {
+    pub fn y_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_y_points)
     }
 
-    fn z_points(&mut self) -> Vec<f32> {
+    pub fn z_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_z_points)
     }
 
-    fn w_points(&mut self) -> Vec<f32> {
+    pub fn w_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_w_points)
     }
 }
@@ -335,24 +542,24 @@ impl QuaternionData {
 
 #[allow(clippy::struct_field_names)]
 #[derive(Default)]
-#[pyclass(module = "plumber", name = "VectorData")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "VectorData"))]
 pub struct VectorData {
     flat_x_points: Vec<f32>,
     flat_y_points: Vec<f32>,
     flat_z_points: Vec<f32>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl VectorData {
-    fn x_points(&mut self) -> Vec<f32> {
+    pub fn x_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_x_points)
     }
 
-    fn y_points(&mut self) -> Vec<f32> {
+    pub fn y_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_y_points)
     }
 
-    fn z_points(&mut self) -> Vec<f32> {
+    pub fn z_points(&mut self) -> Vec<f32> {
         mem::take(&mut self.flat_z_points)
     }
 }
@@ -398,7 +605,10 @@ enum PyAnimationPositionData {
     None,
 }
 
-#[pyclass(module = "plumber", name = "BoneAnimationData")]
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "BoneAnimationData")
+)]
 pub struct PyBoneAnimationData {
     rotation: PyAnimationRotationData,
     position: PyAnimationPositionData,
@@ -467,9 +677,10 @@ fn position_to_delta(position: &mut Vec3, bone: &PyLoadedBone) {
     *position = bone_matrix.inverse().transform_point3(*position);
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyBoneAnimationData {
-    fn rotation(&mut self, py: Python) -> PyObject {
+    #[cfg(feature = "python")]
+    pub fn rotation(&mut self, py: Python) -> PyObject {
         match &mut self.rotation {
             PyAnimationRotationData::Constant(quat) => (*quat).into_py(py),
             PyAnimationRotationData::Animated(values) => mem::take(values).into_py(py),
@@ -477,7 +688,8 @@ impl PyBoneAnimationData {
         }
     }
 
-    fn position(&mut self, py: Python) -> PyObject {
+    #[cfg(feature = "python")]
+    pub fn position(&mut self, py: Python) -> PyObject {
         match &mut self.position {
             PyAnimationPositionData::Constant(vec) => (*vec).into_py(py),
             PyAnimationPositionData::Animated(values) => mem::take(values).into_py(py),
@@ -486,7 +698,7 @@ impl PyBoneAnimationData {
     }
 }
 
-#[pyclass(module = "plumber", name = "LoadedBone")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "LoadedBone"))]
 pub struct PyLoadedBone {
     name: String,
     parent_bone_index: Option<usize>,
@@ -505,26 +717,29 @@ impl PyLoadedBone {
     }
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyLoadedBone {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn parent_bone_index(&self) -> Option<usize> {
+    pub fn parent_bone_index(&self) -> Option<usize> {
         self.parent_bone_index
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 }
 
-#[pyclass(module = "plumber", name = "LoadedAnimation")]
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "plumber", name = "LoadedAnimation")
+)]
 pub struct PyLoadedAnimation {
     name: String,
     data: BTreeMap<usize, PyBoneAnimationData>,
@@ -547,34 +762,34 @@ impl PyLoadedAnimation {
     }
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyLoadedAnimation {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn data(&mut self) -> BTreeMap<usize, PyBoneAnimationData> {
+    pub fn data(&mut self) -> BTreeMap<usize, PyBoneAnimationData> {
         mem::take(&mut self.data)
     }
 
-    fn looping(&self) -> bool {
+    pub fn looping(&self) -> bool {
         self.looping
     }
 }
 
-#[pyclass(module = "plumber", name = "BoneRestData")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BoneRestData"))]
 pub struct PyBoneRestData {
     rotation: [f32; 3],
     position: [f32; 3],
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyBoneRestData {
-    fn rotation(&self) -> [f32; 3] {
+    pub fn rotation(&self) -> [f32; 3] {
         self.rotation
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 }
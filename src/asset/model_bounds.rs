@@ -0,0 +1,107 @@
+//! The illumination position and bounding boxes of models, read from the mdl header.
+//!
+//! The engine samples the lighting of a model at its `$illumposition`, and uses its hull
+//! and view boxes for movement and culling.
+
+use std::collections::HashMap;
+
+use super::mdl::model_key;
+
+/// Offset of the eye position in the mdl header, followed by the illumination position,
+/// the hull box and the view box.
+const EYE_POSITION_OFFSET: usize = 80;
+
+/// Positions of a model, in model units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelBounds {
+    pub eye_position: [f32; 3],
+    pub illum_position: [f32; 3],
+    /// Minimum and maximum corner of the box the model moves with.
+    pub hull: [[f32; 3]; 2],
+    /// Minimum and maximum corner of the box the model is culled with.
+    pub view: [[f32; 3]; 2],
+}
+
+/// Returns the positions stored in the header of an mdl.
+pub fn read_model_bounds(bytes: &[u8]) -> Option<ModelBounds> {
+    let read_float = |offset: usize| -> Option<f32> {
+        Some(f32::from_le_bytes(
+            bytes.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let read_vector = |index: usize| -> Option<[f32; 3]> {
+        let offset = EYE_POSITION_OFFSET + index * 12;
+        Some([
+            read_float(offset)?,
+            read_float(offset + 4)?,
+            read_float(offset + 8)?,
+        ])
+    };
+
+    if bytes.get(..4) != Some(b"IDST") {
+        return None;
+    }
+
+    let hull = [read_vector(2)?, read_vector(3)?];
+    let view = [read_vector(4)?, read_vector(5)?];
+
+    Some(ModelBounds {
+        eye_position: read_vector(0)?,
+        illum_position: read_vector(1)?,
+        hull,
+        // like in the engine, models without a view box are culled with their hull
+        view: if view == [[0.0; 3]; 2] { hull } else { view },
+    })
+}
+
+/// Positions of the imported models, by their file name.
+#[derive(Debug, Default)]
+pub struct ModelBoundsStore {
+    models: HashMap<String, ModelBounds>,
+}
+
+impl ModelBoundsStore {
+    pub fn insert(&mut self, model: &str, bounds: Option<ModelBounds>) {
+        if let Some(bounds) = bounds {
+            self.models.insert(model_key(model), bounds);
+        }
+    }
+
+    pub fn get(&self, model: &str) -> Option<ModelBounds> {
+        self.models.get(&model_key(model)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_header_positions() {
+        let mut mdl = vec![0; 152];
+        mdl[..4].copy_from_slice(b"IDST");
+
+        // eye and illumination positions followed by the hull box, without a view box
+        let values = [
+            0.0, 0.0, 64.0, 0.0, 0.0, 36.0, -16.0, -16.0, 0.0, 16.0, 16.0, 72.0,
+        ];
+        for (index, value) in values.into_iter().enumerate() {
+            let offset = EYE_POSITION_OFFSET + index * 4;
+            mdl[offset..offset + 4].copy_from_slice(&f32::to_le_bytes(value));
+        }
+
+        let hull = [[-16.0, -16.0, 0.0], [16.0, 16.0, 72.0]];
+
+        assert_eq!(
+            read_model_bounds(&mdl),
+            Some(ModelBounds {
+                eye_position: [0.0, 0.0, 64.0],
+                illum_position: [0.0, 0.0, 36.0],
+                hull,
+                view: hull,
+            })
+        );
+
+        assert_eq!(read_model_bounds(&mdl[..100]), None);
+    }
+}
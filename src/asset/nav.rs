@@ -0,0 +1,471 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io, mem,
+};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::PathBuf,
+};
+
+use super::{
+    binary::{Eof, Reader},
+    up_axis::UpAxis,
+    BlenderAssetHandler,
+};
+
+const NAV_MAGIC: u32 = 0xFEED_FACE;
+const MAX_NAV_VERSION: u32 = 16;
+
+#[derive(Debug)]
+pub enum NavError {
+    Io(PathBuf, io::Error),
+    InvalidMagic(PathBuf),
+    UnsupportedVersion(PathBuf, u32),
+    Corrupted(PathBuf),
+}
+
+impl Display for NavError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "nav `{path}`: {err}"),
+            Self::InvalidMagic(path) => write!(f, "nav `{path}`: not a nav file"),
+            Self::UnsupportedVersion(path, version) => {
+                write!(f, "nav `{path}`: unsupported version {version}")
+            }
+            Self::Corrupted(path) => write!(f, "nav `{path}`: unexpected end of file"),
+        }
+    }
+}
+
+impl Error for NavError {}
+
+/// A single navigation area, a quad between its north-west and south-east corners.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavArea {
+    pub id: u32,
+    pub north_west: [f32; 3],
+    pub south_east: [f32; 3],
+    pub north_east_z: f32,
+    pub south_west_z: f32,
+    pub connections: Vec<u32>,
+}
+
+impl NavArea {
+    /// Returns the corners in north-west, north-east, south-east, south-west order.
+    fn corners(&self) -> [[f32; 3]; 4] {
+        let [nw_x, nw_y, nw_z] = self.north_west;
+        let [se_x, se_y, se_z] = self.south_east;
+
+        [
+            [nw_x, nw_y, nw_z],
+            [se_x, nw_y, self.north_east_z],
+            [se_x, se_y, se_z],
+            [nw_x, se_y, self.south_west_z],
+        ]
+    }
+
+    fn center(&self) -> [f32; 3] {
+        let corners = self.corners();
+        let mut center = [0.0; 3];
+
+        for corner in corners {
+            for (c, v) in center.iter_mut().zip(corner) {
+                *c += v / 4.0;
+            }
+        }
+
+        center
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NavMesh {
+    pub name: String,
+    pub areas: Vec<NavArea>,
+}
+
+/// Loads a `.nav` navigation mesh.
+#[derive(Debug, Clone, Copy)]
+pub struct NavConfig;
+
+impl AssetConfig<BlenderAssetHandler> for NavConfig {
+    type Input<'a> = PathBuf;
+    type Output<'a> = NavMesh;
+    type Error<'a> = NavError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let bytes = context
+            .fs()
+            .read(&input)
+            .map_err(|e| NavError::Io(input.clone(), e))?;
+
+        let areas = match parse_areas(&bytes) {
+            Ok(areas) => areas,
+            Err(ParseError::InvalidMagic) => return Err(NavError::InvalidMagic(input)),
+            Err(ParseError::UnsupportedVersion(version)) => {
+                return Err(NavError::UnsupportedVersion(input, version))
+            }
+            Err(ParseError::Eof) => return Err(NavError::Corrupted(input)),
+        };
+
+        Ok(NavMesh {
+            name: input.to_string(),
+            areas,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    InvalidMagic,
+    UnsupportedVersion(u32),
+    Eof,
+}
+
+impl From<Eof> for ParseError {
+    fn from(_: Eof) -> Self {
+        Self::Eof
+    }
+}
+
+fn parse_areas(bytes: &[u8]) -> Result<Vec<NavArea>, ParseError> {
+    let mut reader = Reader::new(bytes);
+
+    if reader.u32()? != NAV_MAGIC {
+        return Err(ParseError::InvalidMagic);
+    }
+
+    let version = reader.u32()?;
+
+    if version > MAX_NAV_VERSION {
+        return Err(ParseError::UnsupportedVersion(version));
+    }
+
+    if version >= 10 {
+        // sub version
+        reader.u32()?;
+    }
+
+    if version >= 4 {
+        // bsp size, used to check if the nav mesh is up to date
+        reader.u32()?;
+    }
+
+    if version >= 14 {
+        // is analyzed
+        reader.u8()?;
+    }
+
+    if version >= 5 {
+        let place_count = reader.u16()?;
+
+        for _ in 0..place_count {
+            let len = reader.u16()?;
+            reader.skip(len.into())?;
+        }
+
+        if version > 11 {
+            // has unnamed areas
+            reader.u8()?;
+        }
+    }
+
+    let area_count = reader.u32()?;
+    let areas_start = reader.remaining();
+
+    // games can append custom data after each area without any size information,
+    // so try the common sizes and pick the first one which parses all areas
+    let mut last_error = ParseError::Eof;
+
+    for custom_data_size in [0, 4, 8] {
+        let mut reader = Reader::new(areas_start);
+
+        match (0..area_count)
+            .map(|_| parse_area(&mut reader, version, custom_data_size))
+            .collect()
+        {
+            Ok(areas) if plausible_areas(&areas) => return Ok(areas),
+            Ok(_) => {}
+            Err(err) => last_error = err,
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Checks that every connection points to an existing area,
+/// which would be very unlikely if the areas were misaligned.
+fn plausible_areas(areas: &[NavArea]) -> bool {
+    let ids: BTreeSet<_> = areas.iter().map(|a| a.id).collect();
+
+    areas
+        .iter()
+        .flat_map(|a| &a.connections)
+        .all(|id| ids.contains(id))
+}
+
+fn parse_area(
+    reader: &mut Reader,
+    version: u32,
+    custom_data_size: usize,
+) -> Result<NavArea, ParseError> {
+    let id = reader.u32()?;
+
+    // attribute flags
+    match version {
+        0..=8 => reader.skip(1)?,
+        9..=12 => reader.skip(2)?,
+        _ => reader.skip(4)?,
+    }
+
+    let north_west = reader.vector()?;
+    let south_east = reader.vector()?;
+    let north_east_z = reader.f32()?;
+    let south_west_z = reader.f32()?;
+
+    let mut connections = Vec::new();
+
+    for _direction in 0..4 {
+        let count = reader.u32()?;
+
+        for _ in 0..count {
+            connections.push(reader.u32()?);
+        }
+    }
+
+    let hiding_spot_count = reader.u8()?;
+
+    for _ in 0..hiding_spot_count {
+        // id, position and flags
+        reader.skip(4 + 12 + 1)?;
+    }
+
+    if version < 15 {
+        let approach_spot_count = reader.u8()?;
+        reader.skip(usize::from(approach_spot_count) * (4 + 4 + 1 + 4 + 1))?;
+    }
+
+    let encounter_path_count = reader.u32()?;
+
+    for _ in 0..encounter_path_count {
+        // from area, from direction, to area, to direction
+        reader.skip(4 + 1 + 4 + 1)?;
+        let spot_count = reader.u8()?;
+        reader.skip(usize::from(spot_count) * (4 + 1))?;
+    }
+
+    // place id
+    reader.u16()?;
+
+    for _direction in 0..2 {
+        let ladder_count = reader.u32()?;
+        reader.skip(ladder_count as usize * 4)?;
+    }
+
+    // earliest occupy times
+    reader.skip(2 * 4)?;
+
+    if version >= 11 {
+        // light intensity of each corner
+        reader.skip(4 * 4)?;
+    }
+
+    if version >= 16 {
+        let visible_area_count = reader.u32()?;
+        reader.skip(visible_area_count as usize * (4 + 1))?;
+        // inherit visibility from
+        reader.u32()?;
+    }
+
+    reader.skip(custom_data_size)?;
+
+    Ok(NavArea {
+        id,
+        north_west,
+        south_east,
+        north_east_z,
+        south_west_z,
+        connections,
+    })
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "NavMesh"))]
+pub struct PyNavMesh {
+    pub name: String,
+    scale: [f32; 3],
+    area_ids: Vec<u32>,
+    flat_vertices: Vec<f32>,
+    flat_polygon_vertice_indices: Vec<usize>,
+    flat_edge_vertice_indices: Vec<usize>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyNavMesh {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scale(&self) -> [f32; 3] {
+        self.scale
+    }
+
+    pub fn area_ids(&mut self) -> Vec<u32> {
+        mem::take(&mut self.area_ids)
+    }
+
+    pub fn vertices(&mut self) -> Vec<f32> {
+        mem::take(&mut self.flat_vertices)
+    }
+
+    pub fn polygon_vertices(&mut self) -> Vec<usize> {
+        mem::take(&mut self.flat_polygon_vertice_indices)
+    }
+
+    pub fn edge_vertices(&mut self) -> Vec<usize> {
+        mem::take(&mut self.flat_edge_vertice_indices)
+    }
+}
+
+impl PyNavMesh {
+    pub fn new(mesh: NavMesh, scale: f32) -> Self {
+        let area_count = mesh.areas.len();
+
+        // the quad corners of all areas come first, followed by the centers of the areas
+        let flat_vertices = mesh
+            .areas
+            .iter()
+            .flat_map(NavArea::corners)
+            .chain(mesh.areas.iter().map(NavArea::center))
+            .flatten()
+            .collect();
+
+        let flat_polygon_vertice_indices = (0..area_count * 4).collect();
+
+        let center_indices: BTreeMap<_, _> = mesh
+            .areas
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.id, area_count * 4 + i))
+            .collect();
+
+        let flat_edge_vertice_indices = mesh
+            .areas
+            .iter()
+            .flat_map(|a| {
+                let from = center_indices[&a.id];
+                let center_indices = &center_indices;
+
+                a.connections
+                    .iter()
+                    .filter_map(move |id| center_indices.get(id).map(|&to| [from, to]))
+            })
+            .flatten()
+            .collect();
+
+        Self {
+            name: mesh.name,
+            scale: [scale, scale, scale],
+            area_ids: mesh.areas.iter().map(|a| a.id).collect(),
+            flat_vertices,
+            flat_polygon_vertice_indices,
+            flat_edge_vertice_indices,
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.scale = up_axis.scale(self.scale);
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_area(out: &mut Vec<u8>, id: u32, connection: u32) {
+        out.extend(id.to_le_bytes());
+        // flags
+        out.extend(0_u32.to_le_bytes());
+
+        for v in [0.0_f32, 0.0, 0.0, 10.0, 10.0, 0.0, 0.0, 0.0] {
+            out.extend(v.to_le_bytes());
+        }
+
+        // one connection to the north, none to the other directions
+        out.extend(1_u32.to_le_bytes());
+        out.extend(connection.to_le_bytes());
+        for _ in 0..3 {
+            out.extend(0_u32.to_le_bytes());
+        }
+
+        // hiding spots
+        out.push(0);
+        // encounter paths
+        out.extend(0_u32.to_le_bytes());
+        // place
+        out.extend(0_u16.to_le_bytes());
+        // ladders
+        out.extend(0_u32.to_le_bytes());
+        out.extend(0_u32.to_le_bytes());
+        // earliest occupy times and light intensities
+        out.extend([0; 2 * 4 + 4 * 4]);
+        // visible areas and inherit visibility from
+        out.extend(0_u32.to_le_bytes());
+        out.extend(0_u32.to_le_bytes());
+    }
+
+    fn write_nav(custom_data_size: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend(NAV_MAGIC.to_le_bytes());
+        out.extend(16_u32.to_le_bytes());
+        // sub version, bsp size, is analyzed
+        out.extend(0_u32.to_le_bytes());
+        out.extend(0_u32.to_le_bytes());
+        out.push(1);
+        // places, has unnamed areas
+        out.extend(0_u16.to_le_bytes());
+        out.push(0);
+
+        out.extend(2_u32.to_le_bytes());
+        write_area(&mut out, 1, 2);
+        out.extend(vec![0; custom_data_size]);
+        write_area(&mut out, 2, 1);
+        out.extend(vec![0; custom_data_size]);
+
+        out
+    }
+
+    #[test]
+    fn parse_areas_v16() {
+        let areas = parse_areas(&write_nav(0)).unwrap();
+
+        assert_eq!(areas.len(), 2);
+        assert_eq!(areas[0].id, 1);
+        assert_eq!(areas[0].connections, vec![2]);
+        assert_eq!(areas[1].south_east, [10.0, 10.0, 0.0]);
+    }
+
+    #[test]
+    fn parse_areas_custom_data() {
+        let areas = parse_areas(&write_nav(4)).unwrap();
+
+        assert_eq!(areas.len(), 2);
+        assert_eq!(areas[1].connections, vec![1]);
+    }
+
+    #[test]
+    fn parse_areas_invalid_magic() {
+        assert_eq!(parse_areas(&[0; 8]), Err(ParseError::InvalidMagic));
+    }
+}
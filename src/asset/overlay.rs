@@ -5,54 +5,75 @@ use plumber_core::vmf::{
     builder::{BuiltOverlay, BuiltOverlayFace},
     entities::BaseEntity,
 };
-use pyo3::{prelude::*, types::PyList};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
 
-#[pyclass(module = "plumber", name = "BuiltOverlay")]
+#[cfg(feature = "python")]
+use super::buffer::{float_buffer, int_buffer};
+use super::editor_groups::PyEditorGroups;
+use super::entities::{get_property, parse_property, parse_vector};
+use super::up_axis::UpAxis;
+
+/// Highest `RenderOrder` Hammer allows, overlays with a higher order are drawn on top.
+const MAX_RENDER_ORDER: u8 = 3;
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "BuiltOverlay"))]
 pub struct PyBuiltOverlay {
     pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
     position: [f32; 3],
     scale: [f32; 3],
     faces: Vec<BuiltOverlayFace>,
     material: String,
+    #[cfg(feature = "python")]
     flat_vertices: Vec<f32>,
+    #[cfg(feature = "python")]
     flat_polygon_vertice_indices: Vec<usize>,
+    #[cfg(feature = "python")]
     flat_loop_uvs: Vec<f32>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PyBuiltOverlay {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         self.id
     }
 
-    fn position(&self) -> [f32; 3] {
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn position(&self) -> [f32; 3] {
         self.position
     }
 
-    fn scale(&self) -> [f32; 3] {
+    pub fn scale(&self) -> [f32; 3] {
         self.scale
     }
 
-    fn vertices(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_vertices)
+    #[cfg(feature = "python")]
+    pub fn vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_vertices))
     }
 
-    fn loops_len(&self) -> usize {
+    pub fn loops_len(&self) -> usize {
         self.faces.iter().map(|f| f.vertice_indices.len()).sum()
     }
 
-    fn polygons_len(&self) -> usize {
+    pub fn polygons_len(&self) -> usize {
         self.faces.len()
     }
 
-    fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> &'p PyList {
-        PyList::new(py, self.faces.iter().map(|f| f.vertice_indices.len()))
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_totals<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, self.faces.iter().map(|f| f.vertice_indices.len()))
     }
 
-    fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> &'p PyList {
+    #[cfg(feature = "python")]
+    pub fn polygon_loop_starts<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
         let mut acc = 0;
 
-        PyList::new(
+        int_buffer(
             py,
             self.faces.iter().map(|f| {
                 let acc_before = acc;
@@ -62,23 +83,42 @@ impl PyBuiltOverlay {
         )
     }
 
-    fn polygon_vertices(&mut self) -> Vec<usize> {
-        mem::take(&mut self.flat_polygon_vertice_indices)
+    #[cfg(feature = "python")]
+    pub fn polygon_vertices<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        int_buffer(py, mem::take(&mut self.flat_polygon_vertice_indices))
     }
 
-    fn loop_uvs(&mut self) -> Vec<f32> {
-        mem::take(&mut self.flat_loop_uvs)
+    #[cfg(feature = "python")]
+    pub fn loop_uvs<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        float_buffer(py, &mem::take(&mut self.flat_loop_uvs))
     }
 
-    fn material(&self) -> &str {
+    pub fn material(&self) -> &str {
         &self.material
     }
 }
 
 impl PyBuiltOverlay {
-    pub fn new(overlay: BuiltOverlay) -> Self {
-        let flat_vertices = overlay.vertices.iter().flat_map(Vec3::to_array).collect();
+    /// Moves the overlay `offset` map units off its surface for each step of its render order,
+    /// starting at one, so it doesn't z-fight with the surface or the overlays below it.
+    pub fn new(overlay: BuiltOverlay, offset: f32) -> Self {
+        let entity = overlay.overlay.entity();
+        let normal = get_property(entity, "BasisNormal")
+            .and_then(parse_vector)
+            .map_or(Vec3::ZERO, |normal| Vec3::from(normal).normalize_or_zero());
+        let render_order = parse_property(entity, "RenderOrder")
+            .unwrap_or(0_u8)
+            .min(MAX_RENDER_ORDER);
+        let surface_offset = normal * offset * f32::from(render_order + 1);
+
+        #[cfg(feature = "python")]
+        let flat_vertices = overlay
+            .vertices
+            .iter()
+            .flat_map(|&vertex| (vertex + surface_offset).to_array())
+            .collect();
 
+        #[cfg(feature = "python")]
         let flat_polygon_vertice_indices = overlay
             .faces
             .iter()
@@ -86,6 +126,7 @@ impl PyBuiltOverlay {
             .copied()
             .collect();
 
+        #[cfg(feature = "python")]
         let flat_loop_uvs = overlay
             .faces
             .iter()
@@ -99,13 +140,24 @@ impl PyBuiltOverlay {
 
         Self {
             id: overlay.overlay.entity().id,
+            editor_groups: None,
             position: overlay.position.into(),
             scale: [overlay.scale, overlay.scale, overlay.scale],
             faces: overlay.faces,
             material: overlay.material.into_string(),
+            #[cfg(feature = "python")]
             flat_vertices,
+            #[cfg(feature = "python")]
             flat_polygon_vertice_indices,
+            #[cfg(feature = "python")]
             flat_loop_uvs,
         }
     }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+        self.scale = up_axis.scale(self.scale);
+        #[cfg(feature = "python")]
+        up_axis.flat_points(&mut self.flat_vertices);
+    }
 }
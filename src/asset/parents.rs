@@ -0,0 +1,117 @@
+//! Parenting of entities through their `parentname` keyvalue.
+//!
+//! Entities name their parent by its `targetname`, optionally followed by an attachment of
+//! the parent's model after a comma. The names are resolved to the ids of the parents here,
+//! since the entities are converted separately and only know their own keyvalues.
+
+use std::collections::HashMap;
+
+use plumber_core::vmf::vmf::Vmf;
+
+use super::entities::get_property;
+
+/// Parents of the entities of the imported vmfs, by the ids of their children.
+#[derive(Debug, Default)]
+pub struct EntityParents {
+    parents: HashMap<i32, i32>,
+}
+
+impl EntityParents {
+    /// Resolves the parents of the entities of a vmf.
+    pub fn extend_from_vmf(&mut self, vmf: &Vmf) {
+        let entities: Vec<_> = vmf
+            .entities
+            .iter()
+            .map(|entity| {
+                (
+                    entity.id,
+                    get_property(entity, "targetname").unwrap_or_default(),
+                    get_property(entity, "parentname").unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        self.parents.extend(resolve_parents(&entities));
+    }
+
+    pub fn get(&self, id: i32) -> Option<i32> {
+        self.parents.get(&id).copied()
+    }
+}
+
+/// Returns the parent ids of entities given as their id, name and parent name.
+fn resolve_parents(entities: &[(i32, &str, &str)]) -> HashMap<i32, i32> {
+    // names are case insensitive, Hammer only warns about duplicates so the first one is used
+    let mut by_name = HashMap::new();
+    for &(id, name, _) in entities {
+        if !name.is_empty() {
+            by_name.entry(name.to_lowercase()).or_insert(id);
+        }
+    }
+
+    let mut parents: HashMap<i32, i32> = entities
+        .iter()
+        .filter_map(|&(id, _, parent_name)| {
+            let parent_name = parent_name.split(',').next().unwrap_or_default().trim();
+            let parent = *by_name.get(&parent_name.to_lowercase())?;
+
+            (parent != id).then_some((id, parent))
+        })
+        .collect();
+
+    // the engine refuses parenting loops, and Blender can't show them either
+    let looped: Vec<i32> = parents
+        .keys()
+        .copied()
+        .filter(|&id| {
+            let mut current = parents.get(&id).copied();
+            let mut steps = 0;
+
+            while let Some(parent) = current {
+                if parent == id {
+                    return true;
+                }
+
+                // the chain leads into a loop the entity isn't a part of
+                if steps > parents.len() {
+                    break;
+                }
+
+                current = parents.get(&parent).copied();
+                steps += 1;
+            }
+
+            false
+        })
+        .collect();
+
+    for id in looped {
+        parents.remove(&id);
+    }
+
+    parents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_parent_names() {
+        let parents = resolve_parents(&[
+            (1, "Train", ""),
+            (2, "", "train"),
+            (3, "door", "train,wheel_attachment"),
+            (4, "self", "self"),
+            (5, "loop_a", "loop_b"),
+            (6, "loop_b", "loop_a"),
+            (7, "", "missing"),
+            (8, "", "loop_a"),
+        ]);
+
+        let mut parents: Vec<_> = parents.into_iter().collect();
+        parents.sort_unstable();
+
+        assert_eq!(parents, [(2, 1), (3, 1), (8, 5)]);
+    }
+}
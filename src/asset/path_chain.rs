@@ -0,0 +1,236 @@
+//! Chains of `path_track` and `path_corner` entities, which are imported as curves.
+//!
+//! Each path entity names the next one in its `target` keyvalue, so trains and cameras
+//! follow the chain from entity to entity. Without following the targets the entities
+//! would only show up as disconnected empties.
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::vmf::vmf::{Entity, Vmf};
+
+use super::{
+    editor_groups::PyEditorGroups,
+    entities::{get_property, parse_vector},
+    up_axis::UpAxis,
+};
+
+/// Entities which are chained into paths through their `target` keyvalue.
+const PATH_CLASS_NAMES: [&str; 3] = ["path_track", "path_corner", "path_corner_crash"];
+
+struct PathNode<'a> {
+    id: i32,
+    class_name: &'a str,
+    name: &'a str,
+    target: Option<&'a str>,
+    position: [f32; 3],
+}
+
+impl<'a> PathNode<'a> {
+    fn new(entity: &'a Entity) -> Option<Self> {
+        Some(Self {
+            id: entity.id,
+            class_name: &entity.class_name,
+            name: get_property(entity, "targetname").unwrap_or_default(),
+            target: get_property(entity, "target").filter(|t| !t.is_empty()),
+            position: get_property(entity, "origin").and_then(parse_vector)?,
+        })
+    }
+}
+
+/// Positions of a chain of path entities, in the order they are followed.
+#[derive(Debug, Clone)]
+pub struct PathChain {
+    /// Id of the first entity of the chain.
+    id: i32,
+    class_name: String,
+    /// Name of the first entity of the chain.
+    name: String,
+    points: Vec<[f32; 3]>,
+    /// The last entity of the chain targets the first one.
+    closed: bool,
+}
+
+/// Chains of the path entities of the imported vmfs, by the ids of their first entities.
+/// They are sent along with their first entity, since it only knows its own keyvalues.
+#[derive(Debug, Default)]
+pub struct PathChains {
+    chains: HashMap<i32, PathChain>,
+}
+
+impl PathChains {
+    /// Follows the targets of the path entities of a vmf into chains.
+    pub fn extend_from_vmf(&mut self, vmf: &Vmf) {
+        let nodes: Vec<_> = vmf
+            .entities
+            .iter()
+            .filter(|e| PATH_CLASS_NAMES.contains(&e.class_name.as_str()))
+            .filter_map(PathNode::new)
+            .collect();
+
+        self.chains.extend(
+            chain_nodes(&nodes)
+                .into_iter()
+                .map(|chain| (chain.id, chain)),
+        );
+    }
+
+    /// Removes the chain starting at the entity with the id, so it's only sent once.
+    pub fn take(&mut self, id: i32) -> Option<PathChain> {
+        self.chains.remove(&id)
+    }
+}
+
+fn chain_nodes(nodes: &[PathNode]) -> Vec<PathChain> {
+    // names should be unique, Hammer only warns about duplicates so the first one is used
+    let mut by_name = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        if !node.name.is_empty() {
+            by_name.entry(node.name).or_insert(index);
+        }
+    }
+
+    let next = |index: usize| nodes[index].target.and_then(|t| by_name.get(t).copied());
+    let targeted: HashSet<usize> = (0..nodes.len()).filter_map(next).collect();
+
+    // chains start at the entities nothing targets, the entities left after them form loops
+    let starts = (0..nodes.len())
+        .filter(|index| !targeted.contains(index))
+        .chain(0..nodes.len());
+
+    let mut visited = vec![false; nodes.len()];
+    let mut chains = Vec::new();
+
+    for start in starts {
+        if visited[start] {
+            continue;
+        }
+
+        let mut points = Vec::new();
+        let mut closed = false;
+        let mut current = Some(start);
+
+        while let Some(index) = current {
+            if visited[index] {
+                // a branch joining a chain which was already followed ends where it joins
+                if index == start {
+                    closed = true;
+                } else {
+                    points.push(nodes[index].position);
+                }
+
+                break;
+            }
+
+            visited[index] = true;
+            points.push(nodes[index].position);
+            current = next(index);
+        }
+
+        if points.len() >= 2 {
+            let first = &nodes[start];
+
+            chains.push(PathChain {
+                id: first.id,
+                class_name: first.class_name.to_owned(),
+                name: first.name.to_owned(),
+                points,
+                closed,
+            });
+        }
+    }
+
+    chains
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "PathChain"))]
+pub struct PyPathChain {
+    pub id: i32,
+    pub editor_groups: Option<PyEditorGroups>,
+    class_name: String,
+    name: String,
+    points: Vec<f32>,
+    closed: bool,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyPathChain {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn editor_groups(&self) -> Option<PyEditorGroups> {
+        self.editor_groups.clone()
+    }
+
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Flat positions of the path entities.
+    pub fn points(&self) -> Vec<f32> {
+        self.points.clone()
+    }
+
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+}
+
+impl PyPathChain {
+    pub fn new(chain: PathChain, scale: f32) -> Self {
+        Self {
+            id: chain.id,
+            editor_groups: None,
+            class_name: chain.class_name,
+            name: chain.name,
+            points: chain.points.iter().flatten().map(|c| c * scale).collect(),
+            closed: chain.closed,
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        up_axis.flat_points(&mut self.points);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node<'a>(id: i32, name: &'a str, target: Option<&'a str>) -> PathNode<'a> {
+        PathNode {
+            id,
+            class_name: "path_track",
+            name,
+            target,
+            position: [0.0, 0.0, 0.0],
+        }
+    }
+
+    #[test]
+    fn follows_targets() {
+        let nodes = [
+            node(3, "b", Some("c")),
+            node(1, "a", Some("b")),
+            node(5, "c", None),
+            node(7, "branch", Some("b")),
+            node(9, "loop_a", Some("loop_b")),
+            node(11, "loop_b", Some("loop_a")),
+            node(13, "single", Some("missing")),
+        ];
+
+        let chains: Vec<_> = chain_nodes(&nodes)
+            .into_iter()
+            .map(|c| (c.id, c.points.len(), c.closed))
+            .collect();
+
+        assert_eq!(chains, [(1, 3, false), (7, 2, false), (9, 2, true)]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Opt-in profiling of imports, reporting where the time of an import was spent.
+//!
+//! Stages are the names of the spans closed while profiling. Spans with an `id` field
+//! are also reported per asset, so single slow assets can be found.
+//! Besides the spans of this crate, this includes any spans emitted by plumber_core.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Formatter},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    info,
+    span::{Attributes, Id},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// Amount of the slowest assets included in a report.
+const SLOWEST_ASSETS: usize = 20;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static PROFILE: Mutex<Profile> = Mutex::new(Profile::new());
+
+/// Enables or disables profiling of the following imports.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts recording a new profile, discarding the previous one.
+pub fn start() {
+    *lock_profile() = Profile::new();
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording and returns the recorded profile.
+pub fn finish() -> ProfileReport {
+    ACTIVE.store(false, Ordering::Relaxed);
+    let profile = mem::replace(&mut *lock_profile(), Profile::new());
+
+    let mut assets = profile.assets;
+    assets.sort_by(|a, b| b.time.cmp(&a.time));
+    assets.truncate(SLOWEST_ASSETS);
+
+    ProfileReport {
+        stages: profile.stages,
+        slowest_assets: assets,
+    }
+}
+
+fn lock_profile() -> MutexGuard<'static, Profile> {
+    PROFILE.lock().expect("profile lock shouldn't be poisoned")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTime {
+    pub count: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl StageTime {
+    fn record(&mut self, time: Duration) {
+        self.count += 1;
+        self.total += time;
+        self.max = self.max.max(time);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetTime {
+    pub stage: &'static str,
+    pub id: String,
+    pub time: Duration,
+}
+
+struct Profile {
+    stages: BTreeMap<&'static str, StageTime>,
+    assets: Vec<AssetTime>,
+}
+
+impl Profile {
+    const fn new() -> Self {
+        Self {
+            stages: BTreeMap::new(),
+            assets: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, stage: &'static str, id: Option<String>, time: Duration) {
+        self.stages.entry(stage).or_default().record(time);
+
+        if let Some(id) = id {
+            self.assets.push(AssetTime { stage, id, time });
+        }
+    }
+}
+
+/// Recorded durations of an import.
+/// Stage times are summed across threads, so they can exceed the duration of the import.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileReport {
+    pub stages: BTreeMap<&'static str, StageTime>,
+    pub slowest_assets: Vec<AssetTime>,
+}
+
+impl ProfileReport {
+    /// Logs the report one line at a time.
+    pub fn log(&self) {
+        for line in self.to_string().lines() {
+            info!("{line}");
+        }
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "profile: stages by total time")?;
+
+        let mut stages: Vec<_> = self.stages.iter().collect();
+        stages.sort_by(|a, b| b.1.total.cmp(&a.1.total));
+
+        for (name, time) in stages {
+            writeln!(
+                f,
+                "  {name}: {:.3} s total, {} times, {:.3} s max",
+                time.total.as_secs_f32(),
+                time.count,
+                time.max.as_secs_f32()
+            )?;
+        }
+
+        writeln!(f, "profile: slowest assets")?;
+
+        for asset in &self.slowest_assets {
+            writeln!(
+                f,
+                "  {} `{}`: {:.3} s",
+                asset.stage,
+                asset.id,
+                asset.time.as_secs_f32()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tracing layer recording how long spans are entered while a profile is active.
+pub struct ProfileLayer;
+
+struct SpanTiming {
+    id: Option<String>,
+    entered: Option<Instant>,
+    busy: Duration,
+}
+
+impl<S> Layer<S> for ProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !ACTIVE.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = IdVisitor(None);
+        attrs.record(&mut visitor);
+
+        span.extensions_mut().insert(SpanTiming {
+            id: visitor.0,
+            entered: None,
+            busy: Duration::ZERO,
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                timing.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                if let Some(entered) = timing.entered.take() {
+                    timing.busy += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+
+        // spans closing after the profile finished belong to no profile
+        if ACTIVE.load(Ordering::Relaxed) {
+            lock_profile().record(span.name(), timing.id, timing.busy);
+        }
+    }
+}
+
+struct IdVisitor(Option<String>);
+
+impl Visit for IdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "id" {
+            self.0 = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "id" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::info_span;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn records_stages_and_assets() {
+        let subscriber = tracing_subscriber::registry().with(ProfileLayer);
+
+        let report = tracing::subscriber::with_default(subscriber, || {
+            start();
+
+            for id in ["a", "b"] {
+                let _span = info_span!("vtf load", id).entered();
+            }
+            drop(info_span!("brush conversion").entered());
+
+            finish()
+        });
+
+        assert_eq!(report.stages["vtf load"].count, 2);
+        assert_eq!(report.stages["brush conversion"].count, 1);
+        assert_eq!(report.slowest_assets.len(), 2);
+        assert!(report
+            .slowest_assets
+            .iter()
+            .all(|asset| asset.stage == "vtf load"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Ordering of models and the props using them for consumers receiving assets one at a time.
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use super::Message;
+
+/// Passes each model of a session through once and holds back props until their model has passed.
+///
+/// Models are processed in parallel with the props using them, so without this a prop can arrive
+/// before its model. The session can span multiple imports, in which case models received by
+/// earlier imports are still known.
+#[derive(Default)]
+pub struct PropQueue {
+    /// Lowercase names of the models received in this session.
+    models: HashSet<String>,
+    /// Props waiting for their model by its lowercase name, with the order they were received in.
+    waiting: HashMap<String, Vec<(usize, Message)>>,
+    received: usize,
+}
+
+impl PropQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a queue for a session where the consumer already has the given models.
+    pub fn with_models(models: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            models: models.into_iter().map(|name| name.to_lowercase()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the messages which can be handled after receiving `message`, in order.
+    /// A new model is followed by the props which were waiting for it.
+    pub fn push(&mut self, message: Message) -> Vec<Message> {
+        match &message {
+            Message::Model(model) => {
+                let name = model.name.to_lowercase();
+
+                if self.models.contains(&name) {
+                    // the model was already handled, props get copies of that
+                    return Vec::new();
+                }
+
+                let waiting = self.waiting.remove(&name).unwrap_or_default();
+                self.models.insert(name);
+
+                let mut ready = Vec::with_capacity(waiting.len() + 1);
+                ready.push(message);
+                ready.extend(waiting.into_iter().map(|(_, prop)| prop));
+                ready
+            }
+            Message::Prop(prop) => {
+                let name = prop.model().to_lowercase();
+
+                if self.models.contains(&name) {
+                    return vec![message];
+                }
+
+                self.received += 1;
+                self.waiting
+                    .entry(name)
+                    .or_default()
+                    .push((self.received, message));
+
+                Vec::new()
+            }
+            _ => vec![message],
+        }
+    }
+
+    /// Returns the props whose model never arrived, in the order they were received.
+    /// Their model failed to load, so the consumer won't find it.
+    pub fn finish(&mut self) -> Vec<Message> {
+        let mut waiting: Vec<_> = mem::take(&mut self.waiting)
+            .into_values()
+            .flatten()
+            .collect();
+        waiting.sort_unstable_by_key(|&(received, _)| received);
+
+        waiting.into_iter().map(|(_, prop)| prop).collect()
+    }
+
+    /// Drops the props still waiting for their model, when the import they are from stopped.
+    /// The models received so far stay known to the session.
+    pub fn clear(&mut self) {
+        self.waiting.clear();
+    }
+}
@@ -0,0 +1,414 @@
+//! Converting Quake and Half-Life `.map` files into vmfs, so their brushes are built by the
+//! same pipeline as vmf brushes.
+//!
+//! Both the original Quake face format and the Valve 220 format with explicit texture axes,
+//! written by Worldcraft, J.A.C.K. and most newer editors, are read. Quake 3 patches and
+//! brush primitives are skipped.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+use glam::Vec3;
+use tracing::warn;
+
+use super::keyvalues::{parse_plane_points, side_entries, write_vmf, KeyValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuakeMapError {
+    Syntax { line: usize, message: &'static str },
+    UnclosedBlock,
+}
+
+impl Display for QuakeMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, message } => write!(f, "map line {line}: {message}"),
+            Self::UnclosedBlock => f.write_str("map ends before all blocks are closed"),
+        }
+    }
+}
+
+impl Error for QuakeMapError {}
+
+/// The texture axes Quake picks for a face, by the axis its normal is closest to.
+/// Each entry is the normal and the axes of the u and v coordinates.
+const BASE_AXES: [[[f32; 3]; 3]; 6] = [
+    [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]],
+    [[0.0, 0.0, -1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]],
+    [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]],
+    [[-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]],
+    [[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+    [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+];
+
+/// Returns if the path has the `.map` extension.
+pub fn is_quake_map(path: &str) -> bool {
+    matches!(Path::new(path).extension(), Some(extension) if extension.eq_ignore_ascii_case("map"))
+}
+
+/// Converts a `.map` file into a vmf. Textures are used as the names of the materials.
+pub fn quake_map_to_vmf(bytes: &[u8]) -> Result<Vec<u8>, QuakeMapError> {
+    let text = String::from_utf8_lossy(bytes);
+
+    let mut ids = 1_usize..;
+    let mut next_id = || KeyValue::Value("id".into(), ids.next().unwrap_or_default().to_string());
+
+    let mut world = None;
+    let mut entities = Vec::new();
+
+    let mut entity: Option<Vec<KeyValue>> = None;
+    let mut solid: Option<Vec<KeyValue>> = None;
+    // a patch or a brush in an unsupported format is being skipped
+    let mut skipping = false;
+    let mut skipped = 0;
+    let mut depth = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let syntax = |message| QuakeMapError::Syntax {
+            line: i + 1,
+            message,
+        };
+
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        match (line, depth) {
+            ("{", 0) => entity = Some(vec![next_id()]),
+            ("{", 1) => solid = Some(vec![next_id()]),
+            ("}", 0) => return Err(syntax("unexpected `}`")),
+            ("}", 1) => {
+                let entries = entity.take().unwrap_or_default();
+
+                if world.is_none() && is_worldspawn(&entries) {
+                    world = Some(entries);
+                } else {
+                    entities.push(with_angles(entries));
+                }
+            }
+            ("}", 2) => {
+                let entries = solid.take().unwrap_or_default();
+
+                if skipping {
+                    skipped += 1;
+                    skipping = false;
+                } else if let Some(entity) = &mut entity {
+                    entity.push(KeyValue::Block("solid".into(), entries));
+                }
+            }
+            ("{" | "}", _) => {}
+            (_, 1) => {
+                let key_value = parse_key_value(line).ok_or_else(|| syntax("invalid keyvalue"))?;
+
+                if let Some(entity) = &mut entity {
+                    entity.push(key_value);
+                }
+            }
+            (_, 2) if !line.starts_with('(') => skipping = true,
+            (_, 2) if !skipping => {
+                let mut side = vec![next_id()];
+                side.extend(parse_face(line).ok_or_else(|| syntax("invalid face"))?);
+
+                if let Some(solid) = &mut solid {
+                    solid.push(KeyValue::Block("side".into(), side));
+                }
+            }
+            (_, 0) => return Err(syntax("expected `{`")),
+            _ => {}
+        }
+
+        match line {
+            "{" => depth += 1,
+            "}" => depth -= 1,
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(QuakeMapError::UnclosedBlock);
+    }
+
+    if skipped > 0 {
+        warn!("skipped {skipped} patches and brushes in unsupported formats");
+    }
+
+    let mut world = world.unwrap_or_else(|| {
+        vec![
+            next_id(),
+            KeyValue::Value("classname".into(), "worldspawn".into()),
+        ]
+    });
+    // valve 220 maps mark their format with the key, vmfs use it for the revision
+    world.retain(
+        |entry| !matches!(entry, KeyValue::Value(key, _) if key.eq_ignore_ascii_case("mapversion")),
+    );
+
+    Ok(write_vmf(&world, &entities))
+}
+
+fn is_worldspawn(entries: &[KeyValue]) -> bool {
+    entries.iter().any(|entry| {
+        matches!(entry, KeyValue::Value(key, value)
+            if key.eq_ignore_ascii_case("classname") && value.eq_ignore_ascii_case("worldspawn"))
+    })
+}
+
+/// Quake entities have only a yaw in `angle`, where -1 points up and -2 down.
+fn with_angles(mut entries: Vec<KeyValue>) -> Vec<KeyValue> {
+    let has_angles = entries.iter().any(
+        |entry| matches!(entry, KeyValue::Value(key, _) if key.eq_ignore_ascii_case("angles")),
+    );
+
+    let angles = entries.iter().find_map(|entry| match entry {
+        KeyValue::Value(key, value) if key.eq_ignore_ascii_case("angle") => match value.trim() {
+            "-1" => Some("-90 0 0".to_string()),
+            "-2" => Some("90 0 0".to_string()),
+            yaw => Some(format!("0 {yaw} 0")),
+        },
+        _ => None,
+    });
+
+    if let (false, Some(angles)) = (has_angles, angles) {
+        entries.push(KeyValue::Value("angles".into(), angles));
+    }
+
+    entries
+}
+
+fn parse_key_value(line: &str) -> Option<KeyValue> {
+    let mut parts = line.split('"');
+
+    let (Some(""), Some(key), Some(_), Some(value)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+
+    Some(KeyValue::Value(key.into(), value.into()))
+}
+
+/// Parses a face in the `(x y z) (x y z) (x y z) texture` format followed by either
+/// `x_offset y_offset rotation x_scale y_scale` or, in the Valve 220 format,
+/// `[ux uy uz u_offset] [vx vy vz v_offset] rotation u_scale v_scale`.
+fn parse_face(line: &str) -> Option<Vec<KeyValue>> {
+    let points_end = line.match_indices(')').nth(2)?.0 + 1;
+    let (plane, rest) = line.split_at(points_end);
+    let points = parse_plane_points(plane)?;
+
+    let mut parts = rest.split_whitespace();
+    let texture = parts.next()?;
+    let rest = parts.collect::<Vec<_>>().join(" ");
+
+    let is_valve_220 = rest.starts_with('[');
+
+    let values: Vec<f32> = rest
+        .split(|c: char| c.is_whitespace() || c == '[' || c == ']')
+        .filter(|value| !value.is_empty())
+        .take(if is_valve_220 { 11 } else { 5 })
+        .map(|value| value.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let (axes, offsets, rotation, scales) = if is_valve_220 {
+        let &[ux, uy, uz, u_offset, vx, vy, vz, v_offset, rotation, u_scale, v_scale] =
+            values.as_slice()
+        else {
+            return None;
+        };
+
+        (
+            [Vec3::new(ux, uy, uz), Vec3::new(vx, vy, vz)],
+            [u_offset, v_offset],
+            rotation,
+            [u_scale, v_scale],
+        )
+    } else {
+        let &[u_offset, v_offset, rotation, u_scale, v_scale] = values.as_slice() else {
+            return None;
+        };
+
+        (
+            texture_axes(points, rotation),
+            [u_offset, v_offset],
+            rotation,
+            [u_scale, v_scale],
+        )
+    };
+
+    // quake treats a scale of 0 as 1
+    let scales = scales.map(|scale| if scale == 0.0 { 1.0 } else { scale });
+
+    Some(side_entries(
+        points,
+        texture,
+        [0, 1].map(|i| (axes[i], offsets[i], scales[i])),
+        rotation,
+    ))
+}
+
+/// The texture axes of a face in the original Quake format, rotated around the base axes
+/// the same way as the Quake compilers do.
+fn texture_axes(points: [Vec3; 3], rotation: f32) -> [Vec3; 2] {
+    let normal = (points[0] - points[1]).cross(points[2] - points[1]);
+
+    let [_, u_axis, v_axis] = BASE_AXES
+        .iter()
+        .map(|axes| axes.map(Vec3::from))
+        .fold((f32::NEG_INFINITY, [Vec3::ZERO; 3]), |best, axes| {
+            let dot = normal.dot(axes[0]);
+            if dot > best.0 {
+                (dot, axes)
+            } else {
+                best
+            }
+        })
+        .1;
+
+    // snapped so right angles are exact
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let [sin, cos] = [sin, cos].map(|x| if x.abs() < 1e-6 { 0.0 } else { x });
+
+    // the axes are rotated in the plane of the two components they use
+    let u_index = u_axis
+        .to_array()
+        .iter()
+        .position(|&x| x != 0.0)
+        .unwrap_or(2);
+    let v_index = v_axis
+        .to_array()
+        .iter()
+        .position(|&x| x != 0.0)
+        .unwrap_or(2);
+
+    [u_axis, v_axis].map(|axis| {
+        let mut rotated = axis;
+        rotated[u_index] = cos * axis[u_index] - sin * axis[v_index];
+        rotated[v_index] = sin * axis[u_index] + cos * axis[v_index];
+        rotated
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::asset::keyvalues::{find_block, find_value, parse_block, Token, Tokenizer};
+
+    fn parse(vmf: &[u8]) -> Vec<(String, Vec<KeyValue>)> {
+        let mut tokens = Tokenizer::new(vmf);
+        let mut blocks = Vec::new();
+
+        while let Some((_, Token::Text(name))) = tokens.next_token() {
+            assert!(matches!(tokens.next_token(), Some((_, Token::Open))));
+            let block = parse_block(&mut tokens).expect("block should be closed");
+            blocks.push((String::from_utf8_lossy(name).into_owned(), block));
+        }
+
+        blocks
+    }
+
+    const QUAKE_MAP: &str = "// Game: Quake
+{
+\"classname\" \"worldspawn\"
+\"wad\" \"gfx/base.wad\"
+{
+( -64 64 64 ) ( 64 64 64 ) ( 64 -64 64 ) GROUND1_6 16 0 0 1 1
+( -64 -64 -64 ) ( 64 -64 -64 ) ( 64 64 -64 ) GROUND1_6 0 0 90 0 2
+}
+{
+patchDef2
+{
+}
+}
+}
+{
+\"classname\" \"info_player_start\"
+\"origin\" \"0 0 24\"
+\"angle\" \"90\"
+}
+";
+
+    #[test]
+    fn converts_quake_faces() {
+        let vmf = quake_map_to_vmf(QUAKE_MAP.as_bytes()).expect("map should be valid");
+        let blocks = parse(&vmf);
+
+        let names: Vec<_> = blocks.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["versioninfo", "visgroups", "world", "entity", "cameras"]
+        );
+
+        let world = &blocks[2].1;
+        assert_eq!(find_value(world, "wad"), Some("gfx/base.wad"));
+
+        let solids: Vec<_> = world
+            .iter()
+            .filter(|entry| matches!(entry, KeyValue::Block(key, _) if key == "solid"))
+            .collect();
+        assert_eq!(solids.len(), 1);
+
+        let solid = find_block(world, "solid").unwrap();
+        let sides: Vec<_> = solid
+            .iter()
+            .filter_map(|entry| match entry {
+                KeyValue::Block(_, side) => Some(side),
+                KeyValue::Value(..) => None,
+            })
+            .collect();
+
+        assert_eq!(
+            find_value(sides[0], "plane"),
+            Some("(-64 64 64) (64 64 64) (64 -64 64)")
+        );
+        assert_eq!(find_value(sides[0], "material"), Some("GROUND1_6"));
+        assert_eq!(find_value(sides[0], "uaxis"), Some("[1 0 0 16] 1"));
+        assert_eq!(find_value(sides[0], "vaxis"), Some("[0 -1 0 0] 1"));
+
+        // rotated by 90 degrees and with the scale of 0 treated as 1
+        assert_eq!(find_value(sides[1], "uaxis"), Some("[0 1 0 0] 1"));
+        assert_eq!(find_value(sides[1], "vaxis"), Some("[1 0 0 0] 2"));
+
+        let entity = &blocks[3].1;
+        assert_eq!(find_value(entity, "angles"), Some("0 90 0"));
+    }
+
+    #[test]
+    fn converts_valve_220_faces() {
+        let map = "{
+\"classname\" \"worldspawn\"
+{
+( 0 0 0 ) ( 0 1 0 ) ( 1 0 0 ) {BLUE [ 0 1 0 -8 ] [ 0 0 -1 4 ] 30 0.5 0.5
+}
+}
+";
+
+        let vmf = quake_map_to_vmf(map.as_bytes()).expect("map should be valid");
+        let blocks = parse(&vmf);
+
+        let side = find_block(find_block(&blocks[2].1, "solid").unwrap(), "side").unwrap();
+        assert_eq!(find_value(side, "material"), Some("{BLUE"));
+        assert_eq!(find_value(side, "uaxis"), Some("[0 1 0 -8] 0.5"));
+        assert_eq!(find_value(side, "vaxis"), Some("[0 0 -1 4] 0.5"));
+        assert_eq!(find_value(side, "rotation"), Some("30"));
+    }
+
+    #[test]
+    fn reports_invalid_maps() {
+        assert_eq!(
+            quake_map_to_vmf(b"{\n\"classname\" \"worldspawn\"\n"),
+            Err(QuakeMapError::UnclosedBlock)
+        );
+        assert_eq!(
+            quake_map_to_vmf(b"{\n{\n( 0 0 0 ) ( 1 0 0 ) GROUND 0 0 0 1 1\n}\n}\n"),
+            Err(QuakeMapError::Syntax {
+                line: 3,
+                message: "invalid face"
+            })
+        );
+    }
+}
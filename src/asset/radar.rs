@@ -0,0 +1,263 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::PathBuf as StdPathBuf,
+};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::{GamePathBuf, PathBuf},
+};
+
+use super::{
+    keyvalues::{find_value, parse_document, KeyValue},
+    up_axis::UpAxis,
+    BlenderAssetHandler,
+};
+
+/// Radar images are 1024x1024, used when the dds header can't be read.
+const DEFAULT_RADAR_SIZE: u32 = 1024;
+
+#[derive(Debug)]
+pub enum RadarError {
+    Read(GamePathBuf, io::Error),
+    MissingKey(GamePathBuf, &'static str),
+    Write(StdPathBuf, io::Error),
+}
+
+impl Display for RadarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(path, err) => write!(f, "radar `{path}`: {err}"),
+            Self::MissingKey(path, key) => {
+                write!(f, "radar `{path}`: missing or invalid key `{key}`")
+            }
+            Self::Write(path, err) => {
+                write!(f, "error writing radar `{}`: {err}", path.display())
+            }
+        }
+    }
+}
+
+impl Error for RadarError {}
+
+/// Placement of a radar image as defined by a map overview file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overview {
+    /// World position of the top left corner of the image.
+    pub pos_x: f32,
+    pub pos_y: f32,
+    /// World units per image pixel.
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Radar {
+    pub name: String,
+    pub overview: Overview,
+    pub width: u32,
+    pub height: u32,
+    pub image_path: StdPathBuf,
+}
+
+/// Loads the overview of a CS:S or CS:GO map by its name,
+/// copying the radar image into a temporary `.dds` file.
+#[derive(Debug, Clone, Copy)]
+pub struct RadarConfig;
+
+impl AssetConfig<BlenderAssetHandler> for RadarConfig {
+    type Input<'a> = String;
+    type Output<'a> = Radar;
+    type Error<'a> = RadarError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let overview_path = GamePathBuf::from(format!("resource/overviews/{input}.txt").as_str());
+        let image_path =
+            GamePathBuf::from(format!("resource/overviews/{input}_radar.dds").as_str());
+
+        let overview = context
+            .fs()
+            .read(&PathBuf::from(overview_path.clone()))
+            .map_err(|e| RadarError::Read(overview_path.clone(), e))?;
+
+        let overview =
+            parse_overview(&overview).map_err(|key| RadarError::MissingKey(overview_path, key))?;
+
+        let dds = context
+            .fs()
+            .read(&PathBuf::from(image_path.clone()))
+            .map_err(|e| RadarError::Read(image_path.clone(), e))?;
+
+        let (width, height) = dds_size(&dds).unwrap_or((DEFAULT_RADAR_SIZE, DEFAULT_RADAR_SIZE));
+
+        let mut out_path = std::env::temp_dir().join("plumber");
+        out_path.push(image_path.as_str());
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| RadarError::Write(out_path.clone(), e))?;
+        }
+
+        fs::write(&out_path, dds).map_err(|e| RadarError::Write(out_path.clone(), e))?;
+
+        Ok(Radar {
+            name: input,
+            overview,
+            width,
+            height,
+            image_path: out_path,
+        })
+    }
+}
+
+/// Parses the keys of an overview file, ignoring nested blocks such as `verticalsections`.
+fn parse_overview(text: &[u8]) -> Result<Overview, &'static str> {
+    let entries = parse_document(text);
+    let overview = entries
+        .iter()
+        .find_map(|entry| match entry {
+            KeyValue::Block(_, block) => Some(block.as_slice()),
+            KeyValue::Value(..) => None,
+        })
+        .unwrap_or_default();
+
+    let value = |key| find_value(overview, key).and_then(|value| value.trim().parse::<f32>().ok());
+
+    Ok(Overview {
+        pos_x: value("pos_x").ok_or("pos_x")?,
+        pos_y: value("pos_y").ok_or("pos_y")?,
+        scale: value("scale").filter(|s| *s > 0.0).ok_or("scale")?,
+    })
+}
+
+/// Reads the image dimensions from a dds header.
+fn dds_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.get(..4)? != b"DDS " {
+        return None;
+    }
+
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let height = read_u32(12)?;
+    let width = read_u32(16)?;
+
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Radar"))]
+pub struct PyRadar {
+    pub name: String,
+    image_path: String,
+    position: [f32; 3],
+    size: [f32; 2],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyRadar {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn image_path(&self) -> &str {
+        &self.image_path
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+}
+
+impl PyRadar {
+    pub fn new(radar: Radar, scale: f32) -> Self {
+        let Overview {
+            pos_x,
+            pos_y,
+            scale: units_per_pixel,
+        } = radar.overview;
+
+        #[allow(clippy::cast_precision_loss)]
+        let [width, height] = [radar.width, radar.height].map(|s| s as f32 * units_per_pixel);
+
+        // the overview position is the top left corner, the image extends towards +x and -y
+        Self {
+            name: radar.name,
+            image_path: radar.image_path.to_string_lossy().into_owned(),
+            position: [
+                (pos_x + width / 2.0) * scale,
+                (pos_y - height / 2.0) * scale,
+                0.0,
+            ],
+            size: [width * scale, height * scale],
+        }
+    }
+
+    pub fn convert_up_axis(&mut self, up_axis: UpAxis) {
+        self.position = up_axis.point(self.position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overview_file() {
+        let text = r#"
+            // overview of de_example
+            "de_example"
+            {
+                "material"  "overviews/de_example"
+                "pos_x"     "-2476"
+                "pos_y"     "3239"
+                "scale"     "4.4"
+                "verticalsections"
+                {
+                    "default" { "AltitudeMax" "10000" "AltitudeMin" "-10000" }
+                }
+            }
+        "#;
+
+        assert_eq!(
+            parse_overview(text.as_bytes()),
+            Ok(Overview {
+                pos_x: -2476.0,
+                pos_y: 3239.0,
+                scale: 4.4,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_overview_missing_scale() {
+        let text = r#""de_example" { "pos_x" "0" "pos_y" "0" }"#;
+
+        assert_eq!(parse_overview(text.as_bytes()), Err("scale"));
+    }
+
+    #[test]
+    fn read_dds_size() {
+        let mut header = b"DDS ".to_vec();
+        header.extend(124_u32.to_le_bytes());
+        header.extend(0_u32.to_le_bytes());
+        header.extend(512_u32.to_le_bytes());
+        header.extend(1024_u32.to_le_bytes());
+
+        assert_eq!(dds_size(&header), Some((1024, 512)));
+        assert_eq!(dds_size(b"VTF\0"), None);
+    }
+}
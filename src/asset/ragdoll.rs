@@ -0,0 +1,291 @@
+//! Ragdoll joints of models, read from the `.phy` file next to the model.
+//!
+//! After the binary collision meshes of its solids, a `.phy` file has a keyvalues text
+//! section. Its `solid` blocks name the bone each solid is attached to, and its
+//! `ragdollconstraint` blocks limit the rotation of a solid relative to its parent.
+
+use std::{collections::HashMap, path::PathBuf as StdPathBuf};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use plumber_core::fs::{GamePathBuf, PathBuf};
+
+use super::{
+    keyvalues::{find_value, parse_block, KeyValue, Token, Tokenizer},
+    mdl::model_key,
+};
+
+/// Rotation limits of a bone relative to its parent bone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RagdollJoint {
+    bone: String,
+    parent_bone: Option<String>,
+    /// Minimum and maximum rotation around the x, y and z axes, in degrees.
+    limits: [[f32; 2]; 3],
+    /// Friction of the rotation around the x, y and z axes.
+    friction: [f32; 3],
+}
+
+/// Returns the ragdoll joints of a `.phy` file.
+pub fn read_ragdoll(bytes: &[u8]) -> Vec<RagdollJoint> {
+    let Some(text) = text_section(bytes) else {
+        return Vec::new();
+    };
+
+    let mut tokens = Tokenizer::new(text);
+    let mut solid_bones = HashMap::new();
+    let mut constraints = Vec::new();
+
+    while let Some((_, token)) = tokens.next_token() {
+        let Token::Text(name) = token else {
+            continue;
+        };
+
+        if !matches!(tokens.next_token(), Some((_, Token::Open))) {
+            break;
+        }
+
+        let Some(block) = parse_block(&mut tokens) else {
+            break;
+        };
+
+        if name.eq_ignore_ascii_case(b"solid") {
+            let index = find_value(&block, "index").and_then(|i| i.trim().parse::<i32>().ok());
+
+            if let (Some(index), Some(bone)) = (index, find_value(&block, "name")) {
+                solid_bones.insert(index, bone.to_owned());
+            }
+        } else if name.eq_ignore_ascii_case(b"ragdollconstraint") {
+            constraints.push(block);
+        }
+    }
+
+    constraints
+        .iter()
+        .filter_map(|constraint| {
+            let solid = |key| {
+                let index: i32 = find_value(constraint, key)?.trim().parse().ok()?;
+                solid_bones.get(&index).cloned()
+            };
+
+            Some(RagdollJoint {
+                bone: solid("child")?,
+                parent_bone: solid("parent"),
+                limits: ["x", "y", "z"].map(|axis| {
+                    [
+                        float_value(constraint, &format!("{axis}min")),
+                        float_value(constraint, &format!("{axis}max")),
+                    ]
+                }),
+                friction: ["x", "y", "z"]
+                    .map(|axis| float_value(constraint, &format!("{axis}friction"))),
+            })
+        })
+        .collect()
+}
+
+/// Returns the text section after the collision meshes of the solids.
+fn text_section(bytes: &[u8]) -> Option<&[u8]> {
+    let read_i32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let mut offset = usize::try_from(read_i32(0)?).ok()?;
+    let solid_count = read_i32(8)?;
+
+    for _ in 0..solid_count {
+        let size = usize::try_from(read_i32(offset)?).ok()?;
+        offset = offset.checked_add(4 + size)?;
+    }
+
+    let text = bytes.get(offset..)?;
+    let end = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+
+    Some(&text[..end])
+}
+
+fn float_value(entries: &[KeyValue], name: &str) -> f32 {
+    find_value(entries, name)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+/// Returns the path of the `.phy` file of a model.
+pub fn physics_path(model_path: &str, from_game: bool) -> PathBuf {
+    if from_game {
+        let mut path = GamePathBuf::from(model_path);
+        path.set_extension("phy");
+        path.into()
+    } else {
+        StdPathBuf::from(model_path).with_extension("phy").into()
+    }
+}
+
+/// Ragdoll joints of the imported models, by their file name.
+#[derive(Debug, Default)]
+pub struct Ragdolls {
+    models: HashMap<String, Vec<RagdollJoint>>,
+}
+
+impl Ragdolls {
+    pub fn insert(&mut self, model: &str, joints: Vec<RagdollJoint>) {
+        if !joints.is_empty() {
+            self.models.insert(model_key(model), joints);
+        }
+    }
+
+    pub fn get(&self, model: &str) -> &[RagdollJoint] {
+        self.models
+            .get(&model_key(model))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "RagdollJoint"))]
+#[derive(Debug, Clone)]
+pub struct PyRagdollJoint {
+    bone_index: usize,
+    parent_bone_index: Option<usize>,
+    limits: [[f32; 2]; 3],
+    friction: [f32; 3],
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PyRagdollJoint {
+    pub fn bone_index(&self) -> usize {
+        self.bone_index
+    }
+
+    pub fn parent_bone_index(&self) -> Option<usize> {
+        self.parent_bone_index
+    }
+
+    /// Minimum and maximum rotation around the x, y and z axes of the bone, in radians.
+    pub fn limits(&self) -> [[f32; 2]; 3] {
+        self.limits
+    }
+
+    /// Friction of the rotation around the x, y and z axes of the bone.
+    pub fn friction(&self) -> [f32; 3] {
+        self.friction
+    }
+}
+
+impl PyRagdollJoint {
+    /// Converts the joints of bones which the model has, by the names of its bones.
+    pub fn from_joints(joints: &[RagdollJoint], bone_names: &[&str]) -> Vec<Self> {
+        let index = |name: &str| {
+            bone_names
+                .iter()
+                .position(|bone| bone.eq_ignore_ascii_case(name))
+        };
+
+        joints
+            .iter()
+            .filter_map(|joint| {
+                Some(Self {
+                    bone_index: index(&joint.bone)?,
+                    parent_bone_index: joint.parent_bone.as_deref().and_then(index),
+                    limits: joint.limits.map(|axis| axis.map(f32::to_radians)),
+                    friction: joint.friction,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "solid {
+\"index\" \"0\"
+\"name\" \"ValveBiped.Bip01_Pelvis\"
+\"mass\" \"12.0\"
+}
+solid {
+\"index\" \"1\"
+\"name\" \"ValveBiped.Bip01_Spine2\"
+\"parent\" \"ValveBiped.Bip01_Pelvis\"
+}
+ragdollconstraint {
+\"parent\" \"0\"
+\"child\" \"1\"
+\"xmin\" \"-30.000\"
+\"xmax\" \"30.000\"
+\"xfriction\" \"0.500\"
+\"ymin\" \"-10.000\"
+\"ymax\" \"45.000\"
+\"yfriction\" \"0.000\"
+\"zmin\" \"0.000\"
+\"zmax\" \"0.000\"
+\"zfriction\" \"0.000\"
+}
+ragdollconstraint {
+\"parent\" \"0\"
+\"child\" \"5\"
+}
+collisionrules {
+\"selfcollisions\" \"0\"
+}
+";
+
+    #[test]
+    fn reads_ragdoll_constraints() {
+        // header with two solids, followed by the text section
+        let mut phy = Vec::new();
+        phy.extend(16_i32.to_le_bytes());
+        phy.extend(0_i32.to_le_bytes());
+        phy.extend(2_i32.to_le_bytes());
+        phy.extend(0_i32.to_le_bytes());
+
+        for size in [8_usize, 4] {
+            phy.extend(u32::try_from(size).unwrap().to_le_bytes());
+            phy.resize(phy.len() + size, 0xff);
+        }
+
+        phy.extend(TEXT.as_bytes());
+        phy.push(0);
+
+        let joints = read_ragdoll(&phy);
+
+        assert_eq!(
+            joints,
+            [RagdollJoint {
+                bone: "ValveBiped.Bip01_Spine2".to_owned(),
+                parent_bone: Some("ValveBiped.Bip01_Pelvis".to_owned()),
+                limits: [[-30.0, 30.0], [-10.0, 45.0], [0.0, 0.0]],
+                friction: [0.5, 0.0, 0.0],
+            }]
+        );
+
+        let py_joints = PyRagdollJoint::from_joints(
+            &joints,
+            &["valvebiped.bip01_pelvis", "ValveBiped.Bip01_Spine2"],
+        );
+
+        assert_eq!(py_joints.len(), 1);
+        assert_eq!(py_joints[0].bone_index, 1);
+        assert_eq!(py_joints[0].parent_bone_index, Some(0));
+    }
+
+    #[test]
+    fn matches_models_by_file_name() {
+        let mut ragdolls = Ragdolls::default();
+        ragdolls.insert(
+            "models/Humans/Male_01.mdl",
+            vec![RagdollJoint {
+                bone: "a".to_owned(),
+                parent_bone: None,
+                limits: [[0.0; 2]; 3],
+                friction: [0.0; 3],
+            }],
+        );
+
+        assert_eq!(ragdolls.get("humans/male_01.mdl").len(), 1);
+        assert!(ragdolls.get("models/humans/male_02.mdl").is_empty());
+    }
+}
@@ -0,0 +1,403 @@
+//! Converting the binary `.rmf` maps of older Hammer versions into vmfs, so their brushes are
+//! built by the same pipeline as vmf brushes.
+//!
+//! Only the format version 2.2, written by Worldcraft 3.3 and Hammer 3.4 and newer, is read.
+//! Visgroups, colors and paths are left out.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::RangeFrom,
+    path::Path,
+};
+
+use glam::Vec3;
+
+use super::{
+    binary::{Eof, Reader},
+    keyvalues::{find_value, side_entries, write_vmf, KeyValue},
+};
+
+const MAGIC: &[u8] = b"RMF";
+const VERSION: f32 = 2.2;
+
+/// Name, color, index and visibility.
+const VISGROUP_SIZE: usize = 140;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RmfError {
+    NotAnRmf,
+    UnsupportedVersion(f32),
+    Corrupted,
+    UnknownObject(String),
+}
+
+impl Display for RmfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnRmf => f.write_str("the file isn't an rmf"),
+            Self::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "rmf version {version} isn't supported, only {VERSION} is"
+                )
+            }
+            Self::Corrupted => f.write_str("the rmf is truncated or corrupted"),
+            Self::UnknownObject(name) => write!(f, "the rmf contains an unknown object `{name}`"),
+        }
+    }
+}
+
+impl Error for RmfError {}
+
+impl From<Eof> for RmfError {
+    fn from(_: Eof) -> Self {
+        Self::Corrupted
+    }
+}
+
+/// Returns if the path has the `.rmf` extension.
+pub fn is_rmf(path: &str) -> bool {
+    matches!(Path::new(path).extension(), Some(extension) if extension.eq_ignore_ascii_case("rmf"))
+}
+
+/// Converts an `.rmf` file into a vmf. Textures are used as the names of the materials.
+pub fn rmf_to_vmf(bytes: &[u8]) -> Result<Vec<u8>, RmfError> {
+    let mut reader = Reader::new(bytes);
+
+    let version = reader.f32()?;
+
+    if reader.bytes(MAGIC.len())? != MAGIC {
+        return Err(RmfError::NotAnRmf);
+    }
+
+    if (version - VERSION).abs() > 0.01 {
+        return Err(RmfError::UnsupportedVersion(version));
+    }
+
+    let visgroups = read_count(&mut reader)?;
+    reader.skip(
+        visgroups
+            .checked_mul(VISGROUP_SIZE)
+            .ok_or(RmfError::Corrupted)?,
+    )?;
+
+    let mut converter = Converter {
+        ids: 1..,
+        world: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    let world_id = converter.next_id();
+
+    let Object::World(keys) = converter.read_object(&mut reader)? else {
+        return Err(RmfError::Corrupted);
+    };
+
+    let mut world = vec![world_id, KeyValue::Value("mapversion".into(), "1".into())];
+    world.extend(keys);
+    world.append(&mut converter.world);
+
+    Ok(write_vmf(&world, &converter.entities))
+}
+
+enum Object {
+    Solid(Vec<KeyValue>),
+    Entity,
+    Group(Vec<Object>),
+    World(Vec<KeyValue>),
+}
+
+struct Converter {
+    ids: RangeFrom<usize>,
+    /// The solids of the world, which are added after its keyvalues.
+    world: Vec<KeyValue>,
+    entities: Vec<Vec<KeyValue>>,
+}
+
+impl Converter {
+    fn next_id(&mut self) -> KeyValue {
+        KeyValue::Value("id".into(), self.ids.next().unwrap_or_default().to_string())
+    }
+
+    /// Reads an object along with its children. Entities are added to the converter as they
+    /// are read, solids are returned for their parent.
+    fn read_object(&mut self, reader: &mut Reader) -> Result<Object, RmfError> {
+        let kind = reader.nstring()?;
+
+        // visgroup and color
+        reader.skip(7)?;
+
+        let children = (0..read_count(reader)?)
+            .map(|_| self.read_object(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match kind.as_str() {
+            "CMapSolid" => {
+                let mut solid = vec![self.next_id()];
+
+                for _ in 0..read_count(reader)? {
+                    let mut side = vec![self.next_id()];
+                    side.extend(read_face(reader)?);
+                    solid.push(KeyValue::Block("side".into(), side));
+                }
+
+                Ok(Object::Solid(solid))
+            }
+            "CMapEntity" => {
+                let id = self.next_id();
+                let mut entity = vec![id];
+                entity.extend(read_entity_data(reader)?);
+
+                reader.skip(14)?;
+                let origin = read_vector(reader)?;
+                reader.skip(4)?;
+
+                let mut solids = Vec::new();
+                collect_solids(children, &mut solids);
+
+                // brush entities get their origin from their solids
+                if solids.is_empty() && find_value(&entity, "origin").is_none() {
+                    entity.push(KeyValue::Value(
+                        "origin".into(),
+                        format!("{} {} {}", origin.x, origin.y, origin.z),
+                    ));
+                }
+
+                entity.extend(
+                    solids
+                        .into_iter()
+                        .map(|solid| KeyValue::Block("solid".into(), solid)),
+                );
+
+                self.entities.push(entity);
+                Ok(Object::Entity)
+            }
+            "CMapGroup" => Ok(Object::Group(children)),
+            "CMapWorld" => {
+                let mut solids = Vec::new();
+                collect_solids(children, &mut solids);
+
+                self.world.extend(
+                    solids
+                        .into_iter()
+                        .map(|solid| KeyValue::Block("solid".into(), solid)),
+                );
+
+                // the paths after the keyvalues aren't needed
+                Ok(Object::World(read_entity_data(reader)?))
+            }
+            _ => Err(RmfError::UnknownObject(kind)),
+        }
+    }
+}
+
+fn collect_solids(objects: Vec<Object>, solids: &mut Vec<Vec<KeyValue>>) {
+    for object in objects {
+        match object {
+            Object::Solid(solid) => solids.push(solid),
+            Object::Group(children) => collect_solids(children, solids),
+            Object::Entity | Object::World(_) => {}
+        }
+    }
+}
+
+/// Reads the class name, spawnflags and keyvalues of an entity or the world.
+fn read_entity_data(reader: &mut Reader) -> Result<Vec<KeyValue>, RmfError> {
+    let class_name = reader.nstring()?;
+    reader.skip(4)?;
+    let flags = reader.i32()?;
+
+    let mut entries = vec![KeyValue::Value("classname".into(), class_name)];
+
+    for _ in 0..read_count(reader)? {
+        let key = reader.nstring()?;
+        let value = reader.nstring()?;
+        entries.push(KeyValue::Value(key, value));
+    }
+
+    if flags != 0 {
+        entries.push(KeyValue::Value("spawnflags".into(), flags.to_string()));
+    }
+
+    Ok(entries)
+}
+
+fn read_face(reader: &mut Reader) -> Result<Vec<KeyValue>, RmfError> {
+    let texture = reader.fixed_string(256)?;
+    reader.skip(4)?;
+
+    let u_axis = read_vector(reader)?;
+    let u_offset = reader.f32()?;
+    let v_axis = read_vector(reader)?;
+    let v_offset = reader.f32()?;
+    let rotation = reader.f32()?;
+    let u_scale = reader.f32()?;
+    let v_scale = reader.f32()?;
+    reader.skip(16)?;
+
+    // the face is rebuilt from the plane like the faces of vmfs
+    let vertices = read_count(reader)?;
+    reader.skip(vertices.checked_mul(12).ok_or(RmfError::Corrupted)?)?;
+
+    let points = [
+        read_vector(reader)?,
+        read_vector(reader)?,
+        read_vector(reader)?,
+    ];
+
+    Ok(side_entries(
+        points,
+        &texture,
+        [(u_axis, u_offset, u_scale), (v_axis, v_offset, v_scale)],
+        rotation,
+    ))
+}
+
+/// A count, which can't be negative.
+fn read_count(reader: &mut Reader) -> Result<usize, RmfError> {
+    usize::try_from(reader.i32()?).map_err(|_| RmfError::Corrupted)
+}
+
+fn read_vector(reader: &mut Reader) -> Result<Vec3, RmfError> {
+    Ok(Vec3::from(reader.vector()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::asset::keyvalues::{find_block, parse_block, Token, Tokenizer};
+
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn i32(&mut self, value: i32) -> &mut Self {
+            self.0.extend(value.to_le_bytes());
+            self
+        }
+
+        fn floats(&mut self, values: &[f32]) -> &mut Self {
+            for value in values {
+                self.0.extend(value.to_le_bytes());
+            }
+            self
+        }
+
+        fn zeros(&mut self, len: usize) -> &mut Self {
+            self.0.resize(self.0.len() + len, 0);
+            self
+        }
+
+        fn nstring(&mut self, value: &str) -> &mut Self {
+            self.0.push(u8::try_from(value.len() + 1).unwrap());
+            self.0.extend(value.as_bytes());
+            self.0.push(0);
+            self
+        }
+
+        /// The type, visgroup, color and child count of an object.
+        fn object(&mut self, kind: &str, children: i32) -> &mut Self {
+            self.nstring(kind).zeros(7).i32(children)
+        }
+
+        fn face(&mut self, texture: &str, points: [[f32; 3]; 3]) -> &mut Self {
+            let mut name = texture.as_bytes().to_vec();
+            name.resize(256, 0);
+            self.0.extend(name);
+
+            self.zeros(4)
+                .floats(&[1.0, 0.0, 0.0, 8.0])
+                .floats(&[0.0, -1.0, 0.0, 0.0])
+                .floats(&[0.0, 0.5, 0.5])
+                .zeros(16)
+                .i32(1)
+                .floats(&points[0])
+                .floats(&points.concat())
+        }
+    }
+
+    fn parse(vmf: &[u8]) -> Vec<(String, Vec<KeyValue>)> {
+        let mut tokens = Tokenizer::new(vmf);
+        let mut blocks = Vec::new();
+
+        while let Some((_, Token::Text(name))) = tokens.next_token() {
+            assert!(matches!(tokens.next_token(), Some((_, Token::Open))));
+            let block = parse_block(&mut tokens).expect("block should be closed");
+            blocks.push((String::from_utf8_lossy(name).into_owned(), block));
+        }
+
+        blocks
+    }
+
+    #[test]
+    fn converts_solids_and_entities() {
+        let mut rmf = Writer::default();
+
+        rmf.floats(&[2.2]);
+        rmf.0.extend(MAGIC);
+        rmf.i32(1).zeros(VISGROUP_SIZE);
+
+        rmf.object("CMapWorld", 2);
+
+        rmf.object("CMapGroup", 1);
+        rmf.object("CMapSolid", 0).i32(1).face(
+            "CRATE01",
+            [[-64.0, 64.0, 64.0], [64.0, 64.0, 64.0], [64.0, -64.0, 64.0]],
+        );
+
+        rmf.object("CMapEntity", 0)
+            .nstring("info_player_start")
+            .zeros(4)
+            .i32(0)
+            .i32(1)
+            .nstring("angles")
+            .nstring("0 90 0")
+            .zeros(14)
+            .floats(&[16.0, 32.0, 48.0])
+            .zeros(4);
+
+        rmf.nstring("worldspawn")
+            .zeros(4)
+            .i32(0)
+            .i32(1)
+            .nstring("wad")
+            .nstring("halflife.wad")
+            .zeros(12)
+            .i32(0);
+
+        let vmf = rmf_to_vmf(&rmf.0).expect("rmf should be valid");
+        let blocks = parse(&vmf);
+
+        let world = &blocks[2].1;
+        assert_eq!(find_value(world, "classname"), Some("worldspawn"));
+        assert_eq!(find_value(world, "wad"), Some("halflife.wad"));
+
+        let side = find_block(find_block(world, "solid").unwrap(), "side").unwrap();
+        assert_eq!(
+            find_value(side, "plane"),
+            Some("(-64 64 64) (64 64 64) (64 -64 64)")
+        );
+        assert_eq!(find_value(side, "material"), Some("CRATE01"));
+        assert_eq!(find_value(side, "uaxis"), Some("[1 0 0 8] 0.5"));
+        assert_eq!(find_value(side, "vaxis"), Some("[0 -1 0 0] 0.5"));
+
+        let entity = &blocks[3].1;
+        assert_eq!(blocks[3].0, "entity");
+        assert_eq!(find_value(entity, "classname"), Some("info_player_start"));
+        assert_eq!(find_value(entity, "angles"), Some("0 90 0"));
+        assert_eq!(find_value(entity, "origin"), Some("16 32 48"));
+    }
+
+    #[test]
+    fn reports_invalid_files() {
+        let mut rmf = Writer::default();
+        rmf.floats(&[1.8]);
+        rmf.0.extend(MAGIC);
+
+        assert_eq!(rmf_to_vmf(&rmf.0), Err(RmfError::UnsupportedVersion(1.8)));
+        assert_eq!(rmf_to_vmf(b"versioninfo"), Err(RmfError::NotAnRmf));
+        assert_eq!(rmf_to_vmf(&rmf.0[..5]), Err(RmfError::Corrupted));
+    }
+}
@@ -0,0 +1,492 @@
+//! Sewing of the open edges of displacements, so there are no cracks between them.
+//!
+//! Displacements are open surfaces, so their outer edges are only used by a single face.
+//! Neighboring displacements are built separately, which leaves their shared edges slightly
+//! apart, or split differently when their powers differ. Brushes can have open edges too,
+//! where faces were left out, so only the faces of displacements are sewn. Those are the faces
+//! which don't lie on the plane of a side without a displacement.
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec3;
+use plumber_core::vmf::builder::SolidFace;
+
+use super::{
+    keyvalues::{find_block, find_value, for_each_block, KeyValue},
+    lightmap::{parse_plane, plane_key},
+};
+
+/// Size of the grid cells open edges are looked up in, in map units.
+const EDGE_CELL_SIZE: f32 = 64.0;
+
+/// Planes of the sides of the imported vmfs which aren't displacements.
+#[derive(Debug, Default)]
+pub struct BrushPlanes {
+    planes: HashSet<[i32; 4]>,
+}
+
+impl BrushPlanes {
+    /// Reads the planes of the sides of a vmf without a `dispinfo`.
+    pub fn extend_from_keyvalues(&mut self, vmf: &[KeyValue]) {
+        for_each_block(vmf, "side", &mut |side| {
+            if find_block(side, "dispinfo").is_some() {
+                return;
+            }
+
+            if let Some((normal, distance)) = find_value(side, "plane").and_then(parse_plane) {
+                self.planes.insert(plane_key(normal, distance));
+            }
+        });
+    }
+
+    /// Whether the face lies on the plane of a side which isn't a displacement.
+    fn is_brush_face(&self, mesh: &SeamMesh, face: &SolidFace) -> bool {
+        mesh.face_plane_keys(face)
+            .into_iter()
+            .flatten()
+            .any(|key| self.planes.contains(&key))
+    }
+}
+
+/// Vertices and faces of one mesh of a brush entity.
+pub struct SeamMesh<'a> {
+    /// Position of the mesh origin in map units.
+    pub offset: Vec3,
+    /// Flat vertex coordinates relative to the origin, in map units.
+    pub vertices: &'a mut [f32],
+    pub faces: &'a [SolidFace],
+}
+
+impl SeamMesh<'_> {
+    fn position(&self, vertex: usize) -> Vec3 {
+        self.offset + Vec3::from_slice(&self.vertices[vertex * 3..vertex * 3 + 3])
+    }
+
+    /// Returns the keys of the plane the face lies on, as made by [`plane_key`] from the plane
+    /// of a side. The built vertices drift slightly off the plane, which can round the distance
+    /// either way, so the keys of the distances next to it are included.
+    pub fn face_plane_keys(&self, face: &SolidFace) -> Option<[[i32; 4]; 3]> {
+        let positions: Vec<Vec3> = face
+            .vertice_indices
+            .iter()
+            .map(|&vertex| self.position(vertex))
+            .collect();
+
+        // newell's method, which works for the collinear corners faces can have
+        let normal = positions
+            .iter()
+            .zip(positions.iter().cycle().skip(1))
+            .fold(Vec3::ZERO, |normal, (a, b)| {
+                normal
+                    + Vec3::new(
+                        (a.y - b.y) * (a.z + b.z),
+                        (a.z - b.z) * (a.x + b.x),
+                        (a.x - b.x) * (a.y + b.y),
+                    )
+            })
+            .normalize_or_zero();
+
+        let first = match positions.first() {
+            Some(&first) if normal != Vec3::ZERO => first,
+            _ => return None,
+        };
+
+        let key = plane_key(normal, normal.dot(first));
+
+        Some([0, -1, 1].map(|offset| [key[0], key[1], key[2], key[3] + offset]))
+    }
+
+    fn set_position(&mut self, vertex: usize, position: Vec3) {
+        (position - self.offset).write_to_slice(&mut self.vertices[vertex * 3..vertex * 3 + 3]);
+    }
+}
+
+/// A vertex on an open edge, by mesh and vertex index.
+type SeamVertex = (usize, usize);
+
+/// Moves the vertices of open displacement edges which are within `tolerance` of another
+/// open displacement edge onto it. Vertices close to each other are welded to their average
+/// position, vertices close to the middle of an edge are moved onto the edge.
+///
+/// Returns the amount of moved vertices.
+pub fn sew_seams(meshes: &mut [SeamMesh<'_>], tolerance: f32, brush_planes: &BrushPlanes) -> usize {
+    if tolerance <= 0.0 {
+        return 0;
+    }
+
+    let mut vertex_indices: HashMap<SeamVertex, usize> = HashMap::new();
+    let mut vertices: Vec<SeamVertex> = Vec::new();
+    let mut edges: Vec<[usize; 2]> = Vec::new();
+
+    for (mesh_index, mesh) in meshes.iter().enumerate() {
+        let displacement_edges = open_edges(mesh.faces)
+            .into_iter()
+            .filter(|&(face, _)| !brush_planes.is_brush_face(mesh, &mesh.faces[face]))
+            .map(|(_, edge)| edge);
+
+        for [a, b] in displacement_edges {
+            let [a, b] = [a, b].map(|vertex| {
+                *vertex_indices
+                    .entry((mesh_index, vertex))
+                    .or_insert_with(|| {
+                        vertices.push((mesh_index, vertex));
+                        vertices.len() - 1
+                    })
+            });
+
+            edges.push([a, b]);
+        }
+    }
+
+    if vertices.is_empty() {
+        return 0;
+    }
+
+    let original: Vec<Vec3> = vertices
+        .iter()
+        .map(|&(mesh, vertex)| meshes[mesh].position(vertex))
+        .collect();
+
+    let mut positions = weld(&original, tolerance);
+    let projected = project_onto_edges(&positions, &edges, tolerance);
+
+    for (vertex, position) in projected {
+        positions[vertex] = position;
+    }
+
+    let mut moved = 0;
+
+    for (i, &(mesh, vertex)) in vertices.iter().enumerate() {
+        if positions[i] != original[i] {
+            meshes[mesh].set_position(vertex, positions[i]);
+            moved += 1;
+        }
+    }
+
+    moved
+}
+
+/// Returns the edges which are only used by a single face, along with the index of the face.
+fn open_edges(faces: &[SolidFace]) -> Vec<(usize, [usize; 2])> {
+    let mut edge_uses: HashMap<[usize; 2], (usize, usize, [usize; 2])> = HashMap::new();
+
+    for (face_index, face) in faces.iter().enumerate() {
+        let indices = &face.vertice_indices;
+
+        for (i, &a) in indices.iter().enumerate() {
+            let b = indices[(i + 1) % indices.len()];
+
+            if a == b {
+                continue;
+            }
+
+            let key = if a < b { [a, b] } else { [b, a] };
+            edge_uses.entry(key).or_insert((0, face_index, [a, b])).0 += 1;
+        }
+    }
+
+    edge_uses
+        .into_values()
+        .filter(|&(uses, _, _)| uses == 1)
+        .map(|(_, face, edge)| (face, edge))
+        .collect()
+}
+
+/// Returns the group of each position, shared by the positions within `tolerance` of each other
+/// and of other positions in the group.
+pub fn weld_groups(positions: &[Vec3], tolerance: f32) -> Vec<usize> {
+    let mut grid: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+    let mut groups = UnionFind::new(positions.len());
+
+    for (i, &position) in positions.iter().enumerate() {
+        let cell = grid_cell(position, tolerance);
+
+        for neighbor in neighbor_cells(cell) {
+            for &j in grid.get(&neighbor).into_iter().flatten() {
+                if position.distance(positions[j]) <= tolerance {
+                    groups.union(i, j);
+                }
+            }
+        }
+
+        grid.entry(cell).or_default().push(i);
+    }
+
+    (0..positions.len()).map(|i| groups.find(i)).collect()
+}
+
+/// Moves each group of positions within `tolerance` of each other to their average.
+fn weld(positions: &[Vec3], tolerance: f32) -> Vec<Vec3> {
+    let groups = weld_groups(positions, tolerance);
+    let mut sums: HashMap<usize, (Vec3, f32)> = HashMap::new();
+
+    for (&group, &position) in groups.iter().zip(positions) {
+        let sum = sums.entry(group).or_insert((Vec3::ZERO, 0.0));
+        sum.0 += position;
+        sum.1 += 1.0;
+    }
+
+    groups
+        .iter()
+        .zip(positions)
+        .map(|(group, &position)| {
+            let (sum, count) = sums[group];
+
+            if count > 1.0 {
+                sum / count
+            } else {
+                position
+            }
+        })
+        .collect()
+}
+
+/// Returns the positions on the nearest edge for vertices within `tolerance` of an edge's middle.
+#[allow(clippy::cast_precision_loss)]
+fn project_onto_edges(
+    positions: &[Vec3],
+    edges: &[[usize; 2]],
+    tolerance: f32,
+) -> Vec<(usize, Vec3)> {
+    // points within the tolerance of an edge are at most a cell away from the closest of the
+    // points sampled along it, so the edges are only added to the cells of those points
+    let cell_size = EDGE_CELL_SIZE.max(2.0 * tolerance);
+    let mut grid: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+
+    for (edge_index, &[a, b]) in edges.iter().enumerate() {
+        let (start, end) = (positions[a], positions[b]);
+        let steps = edge_steps(start.distance(end), cell_size);
+
+        for step in 0..=steps {
+            let cell = grid_cell(start.lerp(end, step as f32 / steps as f32), cell_size);
+            let cell_edges = grid.entry(cell).or_default();
+
+            if cell_edges.last() != Some(&edge_index) {
+                cell_edges.push(edge_index);
+            }
+        }
+    }
+
+    let mut projected = Vec::new();
+
+    for (vertex, &position) in positions.iter().enumerate() {
+        let mut nearest: Option<(f32, Vec3)> = None;
+
+        let cell_edges = neighbor_cells(grid_cell(position, cell_size))
+            .filter_map(|cell| grid.get(&cell))
+            .flatten();
+
+        for &edge_index in cell_edges {
+            let [a, b] = edges[edge_index];
+            let (start, end) = (positions[a], positions[b]);
+
+            // welded vertices share positions, so this also skips the vertex's own edges
+            if start == position || end == position {
+                continue;
+            }
+
+            let direction = end - start;
+            let t = (position - start).dot(direction) / direction.length_squared();
+
+            // collapsed edges have no direction to project onto
+            if t.is_nan() || t <= 0.0 || t >= 1.0 {
+                continue;
+            }
+
+            let on_edge = start + direction * t;
+            let distance = position.distance(on_edge);
+
+            let is_nearer = !matches!(nearest, Some((nearest, _)) if nearest <= distance);
+
+            if distance <= tolerance && is_nearer {
+                nearest = Some((distance, on_edge));
+            }
+        }
+
+        if let Some((_, on_edge)) = nearest {
+            projected.push((vertex, on_edge));
+        }
+    }
+
+    projected
+}
+
+/// Returns the amount of steps a length is sampled in, so the samples are at most a cell apart.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn edge_steps(length: f32, cell_size: f32) -> u32 {
+    ((length / cell_size).ceil() as u32).max(1)
+}
+
+fn grid_cell(position: Vec3, cell_size: f32) -> [i32; 3] {
+    (position / cell_size).floor().as_ivec3().to_array()
+}
+
+fn neighbor_cells([x, y, z]: [i32; 3]) -> impl Iterator<Item = [i32; 3]> {
+    (-1..=1).flat_map(move |dx| {
+        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz]))
+    })
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.parents[a] = b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+    use crate::asset::keyvalues::parse_document;
+
+    fn face(vertice_indices: Vec<usize>) -> SolidFace {
+        SolidFace {
+            vertice_uvs: vec![Vec2::ZERO; vertice_indices.len()],
+            vertice_alphas: vec![0.0; vertice_indices.len()],
+            vertice_indices,
+            vertice_multiblends: None,
+            material_index: 0,
+        }
+    }
+
+    #[test]
+    fn welds_close_vertices_and_projects_onto_edges() {
+        let mut left = vec![
+            0.0, 0.0, 0.0, //
+            10.0, 0.0, 0.2, //
+            10.0, 10.0, 0.0, //
+            0.0, 10.0, 0.0,
+        ];
+        let left_faces = [face(vec![0, 1, 2, 3])];
+
+        // the right patch is split in the middle of the shared edge
+        let mut right = vec![
+            0.0, 0.0, 0.0, //
+            10.0, 0.0, 0.0, //
+            10.0, 10.0, 0.0, //
+            0.0, 10.0, 0.0, //
+            0.0, 5.0, 0.5,
+        ];
+        let right_faces = [face(vec![0, 1, 2, 3, 4])];
+
+        let moved = sew_seams(
+            &mut [
+                SeamMesh {
+                    offset: Vec3::ZERO,
+                    vertices: &mut left,
+                    faces: &left_faces,
+                },
+                SeamMesh {
+                    offset: Vec3::new(10.0, 0.0, 0.0),
+                    vertices: &mut right,
+                    faces: &right_faces,
+                },
+            ],
+            1.0,
+            &BrushPlanes::default(),
+        );
+
+        assert_eq!(moved, 3);
+        assert_eq!(&left[3..6], &[10.0, 0.0, 0.1]);
+        assert_eq!(&right[0..3], &[0.0, 0.0, 0.1]);
+        // projected onto the welded edge from (10, 0, 0.1) to (10, 10, 0)
+        assert!((right[14] - 0.05).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ignores_closed_meshes() {
+        let mut vertices = vec![
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0,
+        ];
+        let faces = [face(vec![0, 1, 2]), face(vec![2, 1, 0])];
+
+        let moved = sew_seams(
+            &mut [SeamMesh {
+                offset: Vec3::ZERO,
+                vertices: &mut vertices,
+                faces: &faces,
+            }],
+            2.0,
+            &BrushPlanes::default(),
+        );
+
+        assert_eq!(moved, 0);
+    }
+
+    #[test]
+    fn ignores_brush_faces() {
+        let mut brush_planes = BrushPlanes::default();
+        brush_planes.extend_from_keyvalues(&parse_document(
+            br#"world
+{
+	solid
+	{
+		side
+		{
+			"plane" "(0 0 0) (0 10 0) (10 10 0)"
+		}
+	}
+}"#,
+        ));
+
+        let mut brush = vec![
+            0.0, 0.0, 0.0, //
+            10.0, 0.0, 0.0, //
+            10.0, 10.0, 0.0, //
+            0.0, 10.0, 0.0,
+        ];
+        let brush_faces = [face(vec![0, 1, 2, 3])];
+
+        let mut displacement = vec![
+            10.0, 0.0, 0.5, //
+            20.0, 0.0, 0.5, //
+            20.0, 10.0, 0.5, //
+            10.0, 10.0, 0.5,
+        ];
+        let displacement_faces = [face(vec![0, 1, 2, 3])];
+
+        let moved = sew_seams(
+            &mut [
+                SeamMesh {
+                    offset: Vec3::ZERO,
+                    vertices: &mut brush,
+                    faces: &brush_faces,
+                },
+                SeamMesh {
+                    offset: Vec3::ZERO,
+                    vertices: &mut displacement,
+                    faces: &displacement_faces,
+                },
+            ],
+            1.0,
+            &brush_planes,
+        );
+
+        assert_eq!(moved, 0);
+        assert_eq!(&brush[3..6], &[10.0, 0.0, 0.0]);
+    }
+}
@@ -3,11 +3,12 @@ use std::io::Cursor;
 
 use float_ord::FloatOrd;
 use image::{ImageBuffer, ImageOutputFormat, Pixel, Rgba32FImage, RgbaImage};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 use plumber_core::asset_vmt::skybox::{SkyBox, SkyBoxData};
 
-#[pyclass(module = "plumber", name = "SkyEqui")]
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "SkyEqui"))]
 pub struct PySkyEqui {
     pub name: String,
     width: u32,
@@ -16,25 +17,25 @@ pub struct PySkyEqui {
     data: Vec<u8>,
 }
 
-#[pymethods]
+#[cfg_attr(feature = "python", pymethods)]
 impl PySkyEqui {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn width(&self) -> u32 {
+    pub fn width(&self) -> u32 {
         self.width
     }
 
-    fn height(&self) -> u32 {
+    pub fn height(&self) -> u32 {
         self.height
     }
 
-    fn format(&self) -> &str {
+    pub fn format(&self) -> &str {
         self.format
     }
 
-    fn bytes(&self) -> &[u8] {
+    pub fn bytes(&self) -> &[u8] {
         &self.data
     }
 }
@@ -0,0 +1,381 @@
+//! Recovery of invalid solids before a vmf is parsed, so they lose only their broken sides
+//! instead of being skipped entirely.
+//!
+//! A solid is the intersection of the half-spaces behind its side planes. Sides whose three
+//! plane points are collinear have no plane, and sides of non-convex or otherwise broken solids
+//! can lie completely outside the other sides, leaving them without a face. Removing such sides
+//! leaves a valid convex solid made of the faces which could be built.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+
+use glam::{DVec3, Vec3};
+
+use super::keyvalues::{
+    find_value, indentation, parse_block, parse_plane_points, write_block, KeyValue, Token,
+    Tokenizer,
+};
+
+/// Half the size of the polygons sides are cut from, larger than any map.
+const HALF_EXTENT: f64 = 131_072.0;
+
+/// Distance within which points are considered to be on a plane.
+const EPSILON: f64 = 0.01;
+
+/// Faces with a smaller area are considered empty.
+const MIN_AREA: f64 = 0.01;
+
+/// A solid which was recovered by removing its invalid sides.
+#[derive(Debug, Clone)]
+pub struct SolidRepair {
+    pub id: String,
+    pub removed_sides: usize,
+}
+
+impl Display for SolidRepair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "solid `{}` is invalid, recovered it without {} of its sides",
+            self.id, self.removed_sides
+        )
+    }
+}
+
+/// Returns the vmf with the invalid sides of its solids removed, and adds the repaired solids
+/// to `repairs`. Solids which can't be recovered are left as they are, for the builder to report.
+pub fn repair_solids<'a>(bytes: &'a [u8], repairs: &mut Vec<SolidRepair>) -> Cow<'a, [u8]> {
+    let line_ending: &[u8] = if bytes.windows(2).any(|w| w == b"\r\n") {
+        b"\r\n"
+    } else {
+        b"\n"
+    };
+
+    let mut output = Vec::new();
+    let mut copied = 0;
+
+    let mut tokens = Tokenizer::new(bytes);
+    let mut previous: Option<(usize, &[u8])> = None;
+
+    while let Some((start, token)) = tokens.next_token() {
+        match token {
+            Token::Open => {
+                let Some((key_start, key)) = previous.take() else {
+                    continue;
+                };
+
+                if !key.eq_ignore_ascii_case(b"solid") {
+                    continue;
+                }
+
+                let Some(solid) = parse_block(&mut tokens) else {
+                    // unterminated, the vmf parser reports this
+                    return Cow::Borrowed(bytes);
+                };
+
+                let Some((solid, removed_sides)) = repair_solid(solid) else {
+                    continue;
+                };
+
+                let indent = indentation(bytes, key_start);
+                let key = String::from_utf8_lossy(key);
+
+                output.extend_from_slice(&bytes[copied..key_start]);
+                write_block(&mut output, &key, &solid, indent, line_ending);
+                copied = tokens.pos();
+
+                repairs.push(SolidRepair {
+                    id: find_value(&solid, "id").unwrap_or("unknown").to_owned(),
+                    removed_sides,
+                });
+            }
+            Token::Text(text) => previous = Some((start, text)),
+            Token::Close => previous = None,
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(bytes);
+    }
+
+    output.extend_from_slice(&bytes[copied..]);
+    Cow::Owned(output)
+}
+
+/// Returns the solid without its invalid sides and the amount of removed sides,
+/// or `None` if the solid is valid or can't be recovered.
+fn repair_solid(solid: Vec<KeyValue>) -> Option<(Vec<KeyValue>, usize)> {
+    let planes: Vec<Option<[DVec3; 3]>> = solid
+        .iter()
+        .filter_map(|entry| match entry {
+            KeyValue::Block(key, side) if key.eq_ignore_ascii_case("side") => Some(
+                find_value(side, "plane")
+                    .and_then(parse_plane_points)
+                    .map(|points| points.map(to_dvec3)),
+            ),
+            _ => None,
+        })
+        .collect();
+
+    let keep = valid_sides(&planes)?;
+    let removed_sides = keep.iter().filter(|&&keep| !keep).count();
+
+    let mut side_index = 0;
+    let solid = solid
+        .into_iter()
+        .filter(|entry| match entry {
+            KeyValue::Block(key, _) if key.eq_ignore_ascii_case("side") => {
+                side_index += 1;
+                keep[side_index - 1]
+            }
+            _ => true,
+        })
+        .collect();
+
+    Some((solid, removed_sides))
+}
+
+fn to_dvec3(v: Vec3) -> DVec3 {
+    DVec3::new(f64::from(v.x), f64::from(v.y), f64::from(v.z))
+}
+
+/// A side plane, with the inside of the solid behind it.
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: DVec3,
+    distance: f64,
+}
+
+impl Plane {
+    fn signed_distance(self, point: DVec3) -> f64 {
+        self.normal.dot(point) - self.distance
+    }
+}
+
+/// Returns which sides to keep, or `None` if every side is valid or the solid can't be recovered.
+fn valid_sides(points: &[Option<[DVec3; 3]>]) -> Option<Vec<bool>> {
+    // the plane points are corners of the faces, so their average is inside a convex solid
+    let valid_points: Vec<DVec3> = points.iter().flatten().flatten().copied().collect();
+
+    if valid_points.is_empty() {
+        return None;
+    }
+
+    let center = valid_points
+        .iter()
+        .fold(DVec3::ZERO, |sum, &point| sum + point)
+        / valid_points.len() as f64;
+
+    let planes: Vec<Option<Plane>> = points
+        .iter()
+        .map(|points| {
+            let [a, b, c] = (*points)?;
+            let normal = (a - b).cross(c - b).normalize_or_zero();
+
+            if normal == DVec3::ZERO {
+                return None;
+            }
+
+            let plane = Plane {
+                normal,
+                distance: normal.dot(a),
+            };
+
+            Some(if plane.signed_distance(center) > 0.0 {
+                Plane {
+                    normal: -normal,
+                    distance: -plane.distance,
+                }
+            } else {
+                plane
+            })
+        })
+        .collect();
+
+    let mut keep: Vec<bool> = planes.iter().map(Option::is_some).collect();
+
+    // a duplicated plane leaves the face to one of the sides
+    for i in 0..planes.len() {
+        for j in 0..i {
+            if let (Some(a), Some(b)) = (planes[i], planes[j]) {
+                if keep[j]
+                    && a.normal.abs_diff_eq(b.normal, 1e-6)
+                    && (a.distance - b.distance).abs() < EPSILON
+                {
+                    keep[i] = false;
+                }
+            }
+        }
+    }
+
+    // removing a side can give the other sides their faces back, so this repeats until stable
+    loop {
+        let kept: Vec<Plane> = planes
+            .iter()
+            .zip(&keep)
+            .filter_map(|(plane, &keep)| plane.filter(|_| keep))
+            .collect();
+
+        let empty = (0..planes.len()).find(|&i| {
+            let Some(plane) = planes[i].filter(|_| keep[i]) else {
+                return false;
+            };
+
+            let face = face_polygon(plane, kept.iter().copied());
+            polygon_area(&face) < MIN_AREA
+        });
+
+        match empty {
+            Some(i) => keep[i] = false,
+            None => break,
+        }
+    }
+
+    let kept: Vec<Plane> = planes
+        .iter()
+        .zip(&keep)
+        .filter_map(|(plane, &keep)| plane.filter(|_| keep))
+        .collect();
+
+    // a solid needs at least a tetrahedron of sides, and no face may reach the cut polygon edges
+    let is_closed = kept.len() >= 4
+        && kept.iter().all(|&plane| {
+            face_polygon(plane, kept.iter().copied())
+                .iter()
+                .all(|p| p.abs().max_element() < HALF_EXTENT / 2.0)
+        });
+
+    if keep.iter().all(|&keep| keep) || !is_closed {
+        return None;
+    }
+
+    Some(keep)
+}
+
+/// Cuts the face of a plane out of a large polygon on it with the other planes.
+fn face_polygon(plane: Plane, others: impl Iterator<Item = Plane>) -> Vec<DVec3> {
+    let up = if plane.normal.z.abs() < 0.9 {
+        DVec3::Z
+    } else {
+        DVec3::X
+    };
+    let u = up.cross(plane.normal).normalize() * HALF_EXTENT;
+    let v = plane.normal.cross(u);
+    let center = plane.normal * plane.distance;
+
+    let mut polygon = vec![
+        center - u - v,
+        center + u - v,
+        center + u + v,
+        center - u + v,
+    ];
+
+    for other in others {
+        if other.normal.abs_diff_eq(plane.normal, 1e-6)
+            && (other.distance - plane.distance).abs() < EPSILON
+        {
+            continue;
+        }
+
+        polygon = clip_polygon(&polygon, other);
+
+        if polygon.is_empty() {
+            break;
+        }
+    }
+
+    polygon
+}
+
+/// Keeps the part of a convex polygon behind a plane.
+fn clip_polygon(polygon: &[DVec3], plane: Plane) -> Vec<DVec3> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, &start) in polygon.iter().enumerate() {
+        let end = polygon[(i + 1) % polygon.len()];
+        let start_distance = plane.signed_distance(start);
+        let end_distance = plane.signed_distance(end);
+
+        if start_distance <= EPSILON {
+            clipped.push(start);
+        }
+
+        if (start_distance < -EPSILON && end_distance > EPSILON)
+            || (start_distance > EPSILON && end_distance < -EPSILON)
+        {
+            let t = start_distance / (start_distance - end_distance);
+            clipped.push(start + (end - start) * t);
+        }
+    }
+
+    clipped
+}
+
+fn polygon_area(polygon: &[DVec3]) -> f64 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .fold(DVec3::ZERO, |sum, (&a, &b)| sum + a.cross(b))
+        .length()
+        / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(id: usize, plane: &str) -> String {
+        format!(
+            "\t\tside\n\t\t{{\n\t\t\t\"id\" \"{id}\"\n\t\t\t\"plane\" \"{plane}\"\n\
+             \t\t\t\"material\" \"DEV/DEV_MEASUREGENERIC01B\"\n\t\t}}\n"
+        )
+    }
+
+    /// A 64 unit cube with additional sides.
+    fn solid(extra_sides: &[&str]) -> String {
+        let planes = [
+            "(0 64 64) (64 64 64) (64 0 64)",
+            "(0 0 0) (64 0 0) (64 64 0)",
+            "(0 64 64) (0 0 64) (0 0 0)",
+            "(64 64 0) (64 0 0) (64 0 64)",
+            "(64 64 64) (0 64 64) (0 64 0)",
+            "(64 0 0) (0 0 0) (0 0 64)",
+        ];
+
+        let sides: String = planes
+            .iter()
+            .chain(extra_sides)
+            .enumerate()
+            .map(|(i, plane)| side(i + 1, plane))
+            .collect();
+
+        format!("world\n{{\n\tsolid\n\t{{\n\t\t\"id\" \"2\"\n{sides}\t}}\n}}\n")
+    }
+
+    #[test]
+    fn removes_invalid_sides() {
+        // a side without a plane and a side outside of the solid
+        let vmf = solid(&["(0 0 0) (1 1 1) (2 2 2)", "(128 64 0) (128 0 0) (128 0 64)"]);
+
+        let mut repairs = Vec::new();
+        let repaired = repair_solids(vmf.as_bytes(), &mut repairs);
+
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].id, "2");
+        assert_eq!(repairs[0].removed_sides, 2);
+        assert_eq!(repaired.as_ref(), solid(&[]).as_bytes());
+    }
+
+    #[test]
+    fn keeps_valid_solids() {
+        let vmf = solid(&[]);
+
+        let mut repairs = Vec::new();
+        let repaired = repair_solids(vmf.as_bytes(), &mut repairs);
+
+        assert!(repairs.is_empty());
+        assert!(matches!(repaired, Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,184 @@
+//! The original keyvalues of the brushes of the imported vmfs, which the built brushes lack.
+//!
+//! They are sent with the brushes, so the brushes can be exported back into a vmf with the
+//! keyvalues of their entity and the texture axes of their sides.
+
+use std::collections::HashMap;
+
+use super::{
+    keyvalues::{find_block, find_value, for_each_block, KeyValue},
+    lightmap::{parse_plane, plane_key},
+    seam::SeamMesh,
+};
+
+/// Keyvalues of the world and brush entities, and texture axes of the sides,
+/// of the imported vmfs.
+#[derive(Debug, Default)]
+pub struct SourceKeyValues {
+    /// Keyvalues of the world and of the brush entities by their id.
+    entities: HashMap<i32, Vec<(String, String)>>,
+    /// `uaxis` and `vaxis` of the sides by the plane they lie on.
+    sides: HashMap<[i32; 4], [String; 2]>,
+}
+
+impl SourceKeyValues {
+    /// Reads the keyvalues of the world, the brush entities and the sides of a vmf.
+    pub fn extend_from_keyvalues(&mut self, vmf: &[KeyValue]) {
+        for entry in vmf {
+            let KeyValue::Block(name, block) = entry else {
+                continue;
+            };
+
+            // point entities are sent with their own keyvalues
+            let is_brush = name.eq_ignore_ascii_case("world")
+                || (name.eq_ignore_ascii_case("entity") && find_block(block, "solid").is_some());
+
+            if !is_brush {
+                continue;
+            }
+
+            let Some(id) = find_value(block, "id").and_then(|id| id.trim().parse().ok()) else {
+                continue;
+            };
+
+            let keyvalues = block
+                .iter()
+                .filter_map(|entry| match entry {
+                    KeyValue::Value(key, value) => Some((key.clone(), value.clone())),
+                    KeyValue::Block(..) => None,
+                })
+                .collect();
+
+            self.entities.insert(id, keyvalues);
+        }
+
+        for_each_block(vmf, "side", &mut |side| {
+            let plane = find_value(side, "plane").and_then(parse_plane);
+            let axes = find_value(side, "uaxis").zip(find_value(side, "vaxis"));
+
+            if let (Some((normal, distance)), Some((u_axis, v_axis))) = (plane, axes) {
+                self.sides.insert(
+                    plane_key(normal, distance),
+                    [u_axis.to_owned(), v_axis.to_owned()],
+                );
+            }
+        });
+    }
+
+    /// Returns the keyvalues of the world or brush entity with the id, if it was read.
+    pub fn entity(&self, id: i32) -> Option<Vec<(String, String)>> {
+        self.entities.get(&id).cloned()
+    }
+
+    /// Looks up the texture axes of the sides the faces of a mesh were built from.
+    pub fn face_texture_axes(&self, mesh: &SeamMesh) -> FaceTextureAxes {
+        let mut axes: Vec<[String; 2]> = Vec::new();
+        let mut indices: HashMap<[i32; 4], i32> = HashMap::new();
+
+        let face_indices = mesh
+            .faces
+            .iter()
+            .map(|face| {
+                let Some((key, side)) = mesh
+                    .face_plane_keys(face)
+                    .into_iter()
+                    .flatten()
+                    .find_map(|key| self.sides.get_key_value(&key))
+                else {
+                    return -1;
+                };
+
+                *indices.entry(*key).or_insert_with(|| {
+                    axes.push(side.clone());
+                    i32::try_from(axes.len() - 1).unwrap_or(-1)
+                })
+            })
+            .collect();
+
+        FaceTextureAxes { axes, face_indices }
+    }
+}
+
+/// Texture axes of the faces of a mesh.
+#[derive(Debug, Clone, Default)]
+pub struct FaceTextureAxes {
+    /// The distinct `uaxis` and `vaxis` of the faces.
+    pub axes: Vec<[String; 2]>,
+    /// Index into `axes` of each face, or -1 if the side of the face wasn't found.
+    pub face_indices: Vec<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3};
+    use plumber_core::vmf::builder::SolidFace;
+
+    use super::*;
+    use crate::asset::keyvalues::parse_document;
+
+    const VMF: &str = "world
+{
+\t\"id\" \"1\"
+\t\"classname\" \"worldspawn\"
+\t\"skyname\" \"sky_wasteland02\"
+\tsolid
+\t{
+\t\t\"id\" \"2\"
+\t\tside
+\t\t{
+\t\t\t\"id\" \"3\"
+\t\t\t\"plane\" \"(0 0 64) (64 0 64) (64 -64 64)\"
+\t\t\t\"uaxis\" \"[1 0 0 8] 0.5\"
+\t\t\t\"vaxis\" \"[0 -1 0 0] 0.5\"
+\t\t}
+\t}
+}
+entity
+{
+\t\"id\" \"4\"
+\t\"classname\" \"info_player_start\"
+}
+";
+
+    #[test]
+    fn reads_brush_keyvalues_and_texture_axes() {
+        let mut keyvalues = SourceKeyValues::default();
+        keyvalues.extend_from_keyvalues(&parse_document(VMF.as_bytes()));
+
+        let world = keyvalues.entity(1).unwrap();
+        assert!(world.contains(&("skyname".to_owned(), "sky_wasteland02".to_owned())));
+        assert!(keyvalues.entity(4).is_none());
+
+        let mut vertices = [
+            0.0, 0.0, 64.0, 64.0, 0.0, 64.0, 64.0, 64.0, 64.0, 0.0, 64.0, 64.0,
+        ];
+        let faces = [
+            SolidFace {
+                vertice_indices: vec![0, 1, 2, 3],
+                vertice_uvs: vec![Vec2::ZERO; 4],
+                vertice_alphas: vec![0.0; 4],
+                vertice_multiblends: None,
+                material_index: 0,
+            },
+            SolidFace {
+                vertice_indices: vec![0, 1, 2],
+                vertice_uvs: vec![Vec2::ZERO; 3],
+                vertice_alphas: vec![0.0; 3],
+                vertice_multiblends: None,
+                material_index: 0,
+            },
+        ];
+        let mesh = SeamMesh {
+            offset: Vec3::new(0.0, 0.0, 0.0),
+            vertices: &mut vertices,
+            faces: &faces,
+        };
+
+        let axes = keyvalues.face_texture_axes(&mesh);
+        assert_eq!(
+            axes.axes,
+            [["[1 0 0 8] 0.5".to_owned(), "[0 -1 0 0] 0.5".to_owned()]]
+        );
+        assert_eq!(axes.face_indices, [0, 0]);
+    }
+}
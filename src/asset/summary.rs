@@ -0,0 +1,185 @@
+//! Statistics of an import, reported to the callbacks after the asset stream finishes.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{layer::Context, Layer};
+
+use super::Message;
+
+static ERRORS: AtomicUsize = AtomicUsize::new(0);
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Tracing layer counting logged errors and warnings, so imports can report how many they caused.
+pub struct EventCounter;
+
+impl<S: Subscriber> Layer<S> for EventCounter {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let counter = match *event.metadata().level() {
+            Level::ERROR => &ERRORS,
+            Level::WARN => &WARNINGS,
+            _ => return,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Collects the statistics of an import while its assets are received.
+pub struct SummaryBuilder {
+    start: Instant,
+    errors_at_start: usize,
+    warnings_at_start: usize,
+    asset_counts: BTreeMap<&'static str, usize>,
+    loaded_models: HashSet<String>,
+    cache_hits: usize,
+    stage_times: Vec<(&'static str, Duration)>,
+    callback_time: Duration,
+}
+
+impl SummaryBuilder {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            errors_at_start: ERRORS.load(Ordering::Relaxed),
+            warnings_at_start: WARNINGS.load(Ordering::Relaxed),
+            asset_counts: BTreeMap::new(),
+            loaded_models: HashSet::new(),
+            cache_hits: 0,
+            stage_times: Vec::new(),
+            callback_time: Duration::ZERO,
+        }
+    }
+
+    /// Records a stage which happened before the assets were received.
+    pub fn stage(&mut self, name: &'static str, time: Duration) {
+        self.stage_times.push((name, time));
+    }
+
+    pub fn record(&mut self, message: &Message) {
+        // diagnostics are already counted by the logged errors and warnings
+        if !matches!(message, Message::Diagnostic(_)) {
+            *self.asset_counts.entry(message.kind()).or_default() += 1;
+        }
+
+        // only the first prop using a model loads it, the rest get it from the asset cache
+        if let Message::Prop(prop) = message {
+            if !self.loaded_models.insert(prop.model().to_owned()) {
+                self.cache_hits += 1;
+            }
+        }
+    }
+
+    /// Records time spent in the callbacks handling a received asset.
+    pub fn record_callback(&mut self, time: Duration) {
+        self.callback_time += time;
+    }
+
+    pub fn finish(self) -> PySummary {
+        let receive_time = self.start.elapsed();
+
+        let mut stage_times = self.stage_times;
+        stage_times.push((
+            "asset processing",
+            receive_time.saturating_sub(self.callback_time),
+        ));
+        stage_times.push(("callbacks", self.callback_time));
+
+        PySummary {
+            asset_counts: self
+                .asset_counts
+                .into_iter()
+                .map(|(kind, count)| (kind.to_owned(), count))
+                .collect(),
+            errors: ERRORS
+                .load(Ordering::Relaxed)
+                .saturating_sub(self.errors_at_start),
+            warnings: WARNINGS
+                .load(Ordering::Relaxed)
+                .saturating_sub(self.warnings_at_start),
+            cache_hits: self.cache_hits,
+            stage_times: stage_times
+                .into_iter()
+                .map(|(name, time)| (name.to_owned(), time.as_secs_f32()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for SummaryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "Summary"))]
+#[derive(Debug, Clone)]
+pub struct PySummary {
+    asset_counts: BTreeMap<String, usize>,
+    errors: usize,
+    warnings: usize,
+    cache_hits: usize,
+    stage_times: Vec<(String, f32)>,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl PySummary {
+    /// Amount of received assets by their kind.
+    pub fn asset_counts(&self) -> BTreeMap<String, usize> {
+        self.asset_counts.clone()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.warnings
+    }
+
+    /// Amount of props which reused an already loaded model.
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits
+    }
+
+    /// Elapsed seconds of each stage in the order they happened.
+    pub fn stage_times(&self) -> Vec<(String, f32)> {
+        self.stage_times.clone()
+    }
+
+    pub fn total_time(&self) -> f32 {
+        self.stage_times.iter().map(|(_, time)| time).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_processing_and_callback_stages() {
+        let mut builder = SummaryBuilder::new();
+        builder.stage("open file system", Duration::from_secs(1));
+        builder.record_callback(Duration::from_millis(10));
+
+        let summary = builder.finish();
+        let stages: Vec<_> = summary
+            .stage_times()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(
+            stages,
+            ["open file system", "asset processing", "callbacks"]
+        );
+        assert!(summary.total_time() >= 1.01);
+        assert!(summary.asset_counts().is_empty());
+    }
+}
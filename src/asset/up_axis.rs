@@ -0,0 +1,135 @@
+//! Converting the world space of the sent assets from Source's z-up axis into y-up.
+//!
+//! Only the world space is converted: the vertices, bones and animations of models stay in the
+//! z-up space of the model, and the props placing them get rotations in the converted space.
+//! Both conventions are right-handed, so the conversion is a rotation around the x axis.
+
+use std::f32::consts::FRAC_PI_2;
+
+use glam::{EulerRot, Mat3, Quat};
+
+/// Up axis of the positions, rotations and vertices the handler sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpAxis {
+    /// Source's and Blender's convention.
+    Z,
+    /// The convention of `glTF` and of engines like Unity or Unreal.
+    Y,
+}
+
+impl UpAxis {
+    /// Converts a point or direction from Source space.
+    pub fn point(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Z => [x, y, z],
+            Self::Y => [x, z, -y],
+        }
+    }
+
+    /// Converts flat xyz triples of points from Source space in place.
+    pub fn flat_points(self, points: &mut [f32]) {
+        for point in points.chunks_exact_mut(3) {
+            let converted = self.point([point[0], point[1], point[2]]);
+            point.copy_from_slice(&converted);
+        }
+    }
+
+    /// Converts a scale applied to vertices in Source space.
+    pub fn scale(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Z => [x, y, z],
+            Self::Y => [x, z, y],
+        }
+    }
+
+    /// Converts a Blender XYZ euler rotation from Source space.
+    pub fn rotation(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        match self {
+            Self::Z => [x, y, z],
+            Self::Y => {
+                let matrix =
+                    Mat3::from_quat(y_up_rotation()) * Mat3::from_euler(EulerRot::ZYX, z, y, x);
+                matrix_to_euler(&matrix)
+            }
+        }
+    }
+}
+
+/// Rotation from Source's z-up space into y-up space.
+fn y_up_rotation() -> Quat {
+    Quat::from_rotation_x(-FRAC_PI_2)
+}
+
+/// Decomposes a rotation matrix into a Blender XYZ euler rotation.
+///
+/// Unlike [`Quat::to_euler`], this keeps the rotation when the y angle is at a right angle,
+/// which is the case for any y-up rotation converted from a yaw of 90 degrees.
+fn matrix_to_euler(matrix: &Mat3) -> [f32; 3] {
+    let cos_y = matrix.x_axis.x.hypot(matrix.x_axis.y);
+    let y = (-matrix.x_axis.z).atan2(cos_y);
+
+    if cos_y > 16.0 * f32::EPSILON {
+        [
+            matrix.y_axis.z.atan2(matrix.z_axis.z),
+            y,
+            matrix.x_axis.y.atan2(matrix.x_axis.x),
+        ]
+    } else {
+        // the x and z axes line up, so the whole rotation around them is put into x
+        [(-matrix.z_axis.y).atan2(matrix.y_axis.y), y, 0.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use glam::Vec3;
+
+    use super::*;
+
+    fn euler_matrix([x, y, z]: [f32; 3]) -> Mat3 {
+        Mat3::from_euler(EulerRot::ZYX, z, y, x)
+    }
+
+    #[test]
+    fn rotation_into_y_up() {
+        let up = y_up_rotation() * Vec3::Z;
+
+        assert!(up.abs_diff_eq(Vec3::Y, 1e-6));
+        assert_eq!(UpAxis::Y.point([0.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn y_up_rotations_match_points() {
+        for rotation in [
+            [0.3, -0.7, 1.2],
+            [0.0, 0.0, FRAC_PI_2],
+            [FRAC_PI_4, 0.0, -FRAC_PI_2],
+        ] {
+            let point = Vec3::new(1.0, 2.0, 3.0);
+
+            // model points stay in Source space, so the converted rotation has to place them
+            // where rotating them in Source space and converting the result does
+            let expected = Vec3::from(UpAxis::Y.point((euler_matrix(rotation) * point).to_array()));
+            let converted = euler_matrix(UpAxis::Y.rotation(rotation)) * point;
+
+            assert!(
+                converted.abs_diff_eq(expected, 1e-5),
+                "{rotation:?}: {converted} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_z_up_data() {
+        let mut points = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        UpAxis::Z.flat_points(&mut points);
+
+        assert_eq!(points, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(UpAxis::Z.rotation([0.1, 0.2, 0.3]), [0.1, 0.2, 0.3]);
+
+        UpAxis::Y.flat_points(&mut points);
+        assert_eq!(points, [1.0, 3.0, -2.0, 4.0, 6.0, -5.0]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Snapping built brush vertices to the exact vertices Hammer++ stores in vmfs.
+//!
+//! Hammer++ writes the vertices of each side into a `vertices_plus` block. Brushes are still
+//! built by intersecting the side planes, which drifts slightly on off-grid geometry, so the
+//! built vertices are moved onto the stored vertices they were derived from.
+
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+use super::{
+    entities::parse_vector,
+    keyvalues::{for_each_block, KeyValue},
+    seam::SeamMesh,
+};
+
+/// Built vertices within this distance in map units of a stored vertex are moved onto it.
+const SNAP_DISTANCE: f32 = 0.05;
+
+/// Size of the grid cells stored vertices are looked up in, in map units.
+const CELL_SIZE: f32 = 1.0;
+
+/// The vertices stored in the `vertices_plus` blocks of the imported vmfs.
+#[derive(Debug, Default)]
+pub struct ExactVertices {
+    cells: HashMap<[i32; 3], Vec<Vec3>>,
+}
+
+impl ExactVertices {
+    /// Reads the stored vertices of the sides of a vmf.
+    pub fn extend_from_keyvalues(&mut self, vmf: &[KeyValue]) {
+        for_each_block(vmf, "vertices_plus", &mut |vertices| {
+            let stored = vertices.iter().filter_map(|entry| match entry {
+                KeyValue::Value(key, value) if key.eq_ignore_ascii_case("v") => parse_vector(value),
+                _ => None,
+            });
+
+            for vertex in stored {
+                self.insert(Vec3::from(vertex));
+            }
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn insert(&mut self, vertex: Vec3) {
+        let vertices = self.cells.entry(cell(vertex)).or_default();
+
+        if !vertices.contains(&vertex) {
+            vertices.push(vertex);
+        }
+    }
+
+    /// Returns the closest stored vertex within the snapping distance.
+    fn closest(&self, position: Vec3) -> Option<Vec3> {
+        let [x, y, z] = cell(position);
+
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [dx, dy, dz])))
+            .filter_map(|[dx, dy, dz]| self.cells.get(&[x + dx, y + dy, z + dz]))
+            .flatten()
+            .map(|&vertex| (vertex.distance_squared(position), vertex))
+            .filter(|&(distance, _)| distance <= SNAP_DISTANCE * SNAP_DISTANCE)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, vertex)| vertex)
+    }
+
+    /// Moves the vertices of the meshes onto the stored vertices close to them.
+    /// Returns the amount of moved vertices.
+    pub fn snap(&self, meshes: &mut [SeamMesh]) -> usize {
+        let mut snapped = 0;
+
+        for mesh in meshes {
+            for vertex in mesh.vertices.chunks_exact_mut(3) {
+                let position = mesh.offset + Vec3::from_slice(vertex);
+
+                if let Some(exact) = self.closest(position) {
+                    if exact != position {
+                        (exact - mesh.offset).write_to_slice(vertex);
+                        snapped += 1;
+                    }
+                }
+            }
+        }
+
+        snapped
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn cell(position: Vec3) -> [i32; 3] {
+    (position / CELL_SIZE).floor().to_array().map(|c| c as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::keyvalues::parse_document;
+
+    const VMF: &str = "world
+{
+\tsolid
+\t{
+\t\tside
+\t\t{
+\t\t\t\"plane\" \"(0 0 0) (0 1 0) (1 0 0)\"
+\t\t\tvertices_plus
+\t\t\t{
+\t\t\t\t\"v\" \"10.125 -3.5 0.999\"
+\t\t\t\t\"v\" \"-0.001 20 0.999\"
+\t\t\t}
+\t\t}
+\t}
+}
+";
+
+    #[test]
+    fn snaps_close_vertices() {
+        let mut exact = ExactVertices::default();
+        exact.extend_from_keyvalues(&parse_document(VMF.as_bytes()));
+
+        // the first two are snapped onto the stored vertices across cell borders,
+        // the last one is too far from them
+        let mut vertices = [[0.12, -3.502, 0.0], [-10.0, 20.0, 0.0], [0.0, 0.0, 0.0]].concat();
+
+        let mut meshes = [SeamMesh {
+            offset: Vec3::new(10.0, 0.0, 1.0),
+            vertices: &mut vertices,
+            faces: &[],
+        }];
+
+        assert_eq!(exact.snap(&mut meshes), 2);
+
+        let snapped: Vec<Vec3> = vertices
+            .chunks_exact(3)
+            .map(|v| Vec3::from_slice(v) + Vec3::new(10.0, 0.0, 1.0))
+            .collect();
+
+        assert!(snapped[0].distance(Vec3::new(10.125, -3.5, 0.999)) < 1e-5);
+        assert!(snapped[1].distance(Vec3::new(-0.001, 20.0, 0.999)) < 1e-5);
+        assert!(snapped[2].distance(Vec3::new(10.0, 0.0, 1.0)) < 1e-5);
+    }
+
+    #[test]
+    fn ignores_vmfs_without_stored_vertices() {
+        let mut exact = ExactVertices::default();
+        exact.extend_from_keyvalues(&parse_document(b"world\n{\n\tsolid\n\t{\n\t}\n}\n"));
+
+        assert!(exact.is_empty());
+    }
+}
@@ -0,0 +1,509 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+const HEADER_START: &str = "<!-- dmx encoding ";
+const TEXT_ENCODING: &str = "keyvalues2";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DmxError {
+    MissingHeader,
+    UnsupportedEncoding(String),
+    UnexpectedToken { line: usize, expected: &'static str },
+    UnexpectedEof,
+}
+
+impl Display for DmxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => f.write_str("missing dmx header"),
+            Self::UnsupportedEncoding(encoding) => {
+                write!(
+                    f,
+                    "unsupported dmx encoding `{encoding}`, only {TEXT_ENCODING} is supported"
+                )
+            }
+            Self::UnexpectedToken { line, expected } => {
+                write!(f, "line {line}: expected {expected}")
+            }
+            Self::UnexpectedEof => f.write_str("unexpected end of file"),
+        }
+    }
+}
+
+impl Error for DmxError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Reference {
+    Index(usize),
+    Id(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// Scalar values are kept as text and parsed on access.
+    Text(String),
+    Array(Vec<String>),
+    Element(Option<Reference>),
+    ElementArray(Vec<Reference>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub kind: String,
+    value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub class_name: String,
+    pub id: String,
+    attributes: BTreeMap<String, Attribute>,
+}
+
+impl Element {
+    /// Iterates the attributes with a text value, excluding the element id.
+    pub fn text_attributes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes
+            .iter()
+            .filter_map(|(name, attribute)| match &attribute.value {
+                Value::Text(text) => Some((name.as_str(), text.as_str())),
+                _ => None,
+            })
+    }
+
+    pub fn get_text(&self, name: &str) -> Option<&str> {
+        match &self.attributes.get(name)?.value {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get_text(name)?.trim().parse().ok()
+    }
+
+    pub fn get_vector<const N: usize>(&self, name: &str) -> Option<[f32; N]> {
+        parse_vector(self.get_text(name)?)
+    }
+
+    pub fn get_array<T: FromStr>(&self, name: &str) -> Option<Vec<T>> {
+        match &self.attributes.get(name)?.value {
+            Value::Array(items) => items.iter().map(|i| i.trim().parse().ok()).collect(),
+            _ => None,
+        }
+    }
+
+    pub fn get_vector_array<const N: usize>(&self, name: &str) -> Option<Vec<[f32; N]>> {
+        match &self.attributes.get(name)?.value {
+            Value::Array(items) => items.iter().map(|i| parse_vector(i)).collect(),
+            _ => None,
+        }
+    }
+
+    pub fn get_string_array(&self, name: &str) -> Option<&[String]> {
+        match &self.attributes.get(name)?.value {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a whitespace separated vector, such as the value of a `vector3` or `color` attribute.
+pub fn parse_vector<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let mut result = [0.0; N];
+    let mut parts = value.split_whitespace();
+
+    for component in &mut result {
+        *component = parts.next()?.parse().ok()?;
+    }
+
+    Some(result)
+}
+
+/// A parsed DMX document.
+/// Elements reference each other by their index in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dmx {
+    /// The encoding header's format, such as `vmap`.
+    pub format: String,
+    elements: Vec<Element>,
+    roots: Vec<usize>,
+    ids: HashMap<String, usize>,
+}
+
+impl Dmx {
+    /// Parses a text DMX document using the `keyvalues2` encoding.
+    pub fn parse(text: &str) -> Result<Self, DmxError> {
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        let header_end = text.find("-->").ok_or(DmxError::MissingHeader)?;
+        let header = text[..header_end]
+            .strip_prefix(HEADER_START)
+            .ok_or(DmxError::MissingHeader)?;
+
+        let mut header_parts = header.split_whitespace();
+        let encoding = header_parts.next().ok_or(DmxError::MissingHeader)?;
+
+        if encoding != TEXT_ENCODING {
+            return Err(DmxError::UnsupportedEncoding(encoding.to_owned()));
+        }
+
+        // skip the encoding version and the `format` keyword
+        let format = header_parts.nth(2).unwrap_or_default().to_owned();
+
+        let mut parser = Parser {
+            tokens: Tokenizer::new(&text[header_end + 3..]),
+            elements: Vec::new(),
+        };
+
+        let mut roots = Vec::new();
+
+        while let Some(token) = parser.tokens.next()? {
+            let Token::String(class_name) = token else {
+                return Err(parser.unexpected("an element"));
+            };
+
+            roots.push(parser.element(class_name)?);
+        }
+
+        let ids = parser
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !e.id.is_empty())
+            .map(|(i, e)| (e.id.clone(), i))
+            .collect();
+
+        Ok(Self {
+            format,
+            elements: parser.elements,
+            roots,
+            ids,
+        })
+    }
+
+    pub fn root(&self) -> Option<&Element> {
+        self.roots.first().map(|&i| &self.elements[i])
+    }
+
+    fn resolve(&self, reference: &Reference) -> Option<&Element> {
+        match reference {
+            Reference::Index(index) => self.elements.get(*index),
+            Reference::Id(id) => self.ids.get(id).map(|&i| &self.elements[i]),
+        }
+    }
+
+    /// Returns the element an `element` attribute references.
+    pub fn element<'a>(&'a self, element: &Element, name: &str) -> Option<&'a Element> {
+        match &element.attributes.get(name)?.value {
+            Value::Element(reference) => self.resolve(reference.as_ref()?),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements an `element_array` attribute references, skipping missing ones.
+    pub fn elements<'a>(
+        &'a self,
+        element: &'a Element,
+        name: &str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        let references: &[Reference] = match element.attributes.get(name).map(|a| &a.value) {
+            Some(Value::ElementArray(references)) => references,
+            _ => &[],
+        };
+
+        references.iter().filter_map(|r| self.resolve(r))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    String(String),
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+#[derive(Clone)]
+struct Tokenizer<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            rest: text,
+            line: 1,
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            let trimmed = self.rest.trim_start();
+            self.line += self.rest[..self.rest.len() - trimmed.len()]
+                .matches('\n')
+                .count();
+            self.rest = trimmed;
+
+            if let Some(comment) = self.rest.strip_prefix("//") {
+                self.rest = comment.find('\n').map_or("", |end| &comment[end..]);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, DmxError> {
+        self.skip_whitespace_and_comments();
+
+        let Some(c) = self.rest.chars().next() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '{' => Token::OpenBrace,
+            '}' => Token::CloseBrace,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            ',' => Token::Comma,
+            '"' => return self.string().map(Some),
+            _ => {
+                return Err(DmxError::UnexpectedToken {
+                    line: self.line,
+                    expected: "a quoted string",
+                })
+            }
+        };
+
+        self.rest = &self.rest[1..];
+        Ok(Some(token))
+    }
+
+    fn peek(&self) -> Result<Option<Token>, DmxError> {
+        self.clone().next()
+    }
+
+    fn string(&mut self) -> Result<Token, DmxError> {
+        let mut value = String::new();
+        let mut chars = self.rest[1..].char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 2..];
+                    return Ok(Token::String(value));
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                '\n' => {
+                    self.line += 1;
+                    value.push(c);
+                }
+                _ => value.push(c),
+            }
+        }
+
+        Err(DmxError::UnexpectedEof)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Tokenizer<'a>,
+    elements: Vec<Element>,
+}
+
+impl Parser<'_> {
+    fn unexpected(&self, expected: &'static str) -> DmxError {
+        DmxError::UnexpectedToken {
+            line: self.tokens.line,
+            expected,
+        }
+    }
+
+    fn expect_next(&mut self) -> Result<Token, DmxError> {
+        self.tokens.next()?.ok_or(DmxError::UnexpectedEof)
+    }
+
+    fn expect_string(&mut self) -> Result<String, DmxError> {
+        match self.expect_next()? {
+            Token::String(s) => Ok(s),
+            _ => Err(self.unexpected("a quoted string")),
+        }
+    }
+
+    /// Parses the body of an element after its class name, and returns its index.
+    fn element(&mut self, class_name: String) -> Result<usize, DmxError> {
+        if self.expect_next()? != Token::OpenBrace {
+            return Err(self.unexpected("`{`"));
+        }
+
+        let mut id = String::new();
+        let mut attributes = BTreeMap::new();
+
+        loop {
+            let name = match self.expect_next()? {
+                Token::CloseBrace => break,
+                Token::String(name) => name,
+                _ => return Err(self.unexpected("an attribute name or `}`")),
+            };
+
+            let kind = self.expect_string()?;
+
+            if kind == "elementid" {
+                id = self.expect_string()?;
+                continue;
+            }
+
+            let value = if kind == "element" {
+                let id = self.expect_string()?;
+                Value::Element((!id.is_empty()).then_some(Reference::Id(id)))
+            } else if kind == "element_array" {
+                Value::ElementArray(self.element_array()?)
+            } else if kind.ends_with("_array") {
+                Value::Array(self.array()?)
+            } else if self.tokens.peek()? == Some(Token::OpenBrace) {
+                // an inline element, the attribute type is its class name
+                let index = self.element(kind.clone())?;
+                Value::Element(Some(Reference::Index(index)))
+            } else {
+                Value::Text(self.expect_string()?)
+            };
+
+            attributes.insert(name, Attribute { kind, value });
+        }
+
+        self.elements.push(Element {
+            class_name,
+            id,
+            attributes,
+        });
+
+        Ok(self.elements.len() - 1)
+    }
+
+    fn array(&mut self) -> Result<Vec<String>, DmxError> {
+        if self.expect_next()? != Token::OpenBracket {
+            return Err(self.unexpected("`[`"));
+        }
+
+        let mut items = Vec::new();
+
+        loop {
+            match self.expect_next()? {
+                Token::CloseBracket => break,
+                Token::Comma => {}
+                Token::String(item) => items.push(item),
+                _ => return Err(self.unexpected("an array item or `]`")),
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn element_array(&mut self) -> Result<Vec<Reference>, DmxError> {
+        if self.expect_next()? != Token::OpenBracket {
+            return Err(self.unexpected("`[`"));
+        }
+
+        let mut references = Vec::new();
+
+        loop {
+            let class_name = match self.expect_next()? {
+                Token::CloseBracket => break,
+                Token::Comma => continue,
+                Token::String(class_name) => class_name,
+                _ => return Err(self.unexpected("an element or `]`")),
+            };
+
+            // references to elements defined elsewhere are written as `"element" "<id>"`
+            if class_name == "element" && self.tokens.peek()? != Some(Token::OpenBrace) {
+                let id = self.expect_string()?;
+
+                if !id.is_empty() {
+                    references.push(Reference::Id(id));
+                }
+
+                continue;
+            }
+
+            references.push(Reference::Index(self.element(class_name)?));
+        }
+
+        Ok(references)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<!-- dmx encoding keyvalues2 4 format vmap 29 -->
+"CMapRootElement"
+{
+	"id" "elementid" "root"
+	"isprefab" "bool" "0"
+	"world" "CMapWorld"
+	{
+		"id" "elementid" "world"
+		"origin" "vector3" "0 0 16"
+		"children" "element_array"
+		[
+			"CMapEntity"
+			{
+				"id" "elementid" "light"
+				"name" "string" "a \"quoted\" name"
+			},
+			"element" "shared"
+		]
+		"materials" "string_array" [ "materials/dev/a.vmat", "materials/dev/b.vmat" ]
+		"indices" "int_array" [ "1", "2", "3" ]
+	}
+	"shared" "element" "shared"
+}
+// a comment
+"CMapGroup"
+{
+	"id" "elementid" "shared"
+}
+"#;
+
+    #[test]
+    fn parses_elements_and_references() {
+        let dmx = Dmx::parse(DOCUMENT).unwrap();
+        assert_eq!(dmx.format, "vmap");
+
+        let root = dmx.root().unwrap();
+        assert_eq!(root.get::<u8>("isprefab"), Some(0));
+
+        let world = dmx.element(root, "world").unwrap();
+        assert_eq!(world.class_name, "CMapWorld");
+        assert_eq!(world.get_vector("origin"), Some([0.0, 0.0, 16.0]));
+        assert_eq!(world.get_array("indices"), Some(vec![1, 2, 3]));
+        assert_eq!(world.get_string_array("materials").unwrap().len(), 2);
+
+        let children: Vec<_> = dmx.elements(world, "children").collect();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].get_text("name"), Some("a \"quoted\" name"));
+        assert_eq!(children[1].class_name, "CMapGroup");
+
+        assert_eq!(dmx.element(root, "shared").unwrap().id, "shared");
+    }
+
+    #[test]
+    fn rejects_binary_encoding() {
+        assert_eq!(
+            Dmx::parse("<!-- dmx encoding binary 9 format vmap 29 -->\n\0\0"),
+            Err(DmxError::UnsupportedEncoding("binary".to_owned()))
+        );
+    }
+}
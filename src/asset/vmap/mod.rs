@@ -0,0 +1,457 @@
+//! Importing Source 2 `.vmap` maps.
+//!
+//! Only maps saved with the text `keyvalues2` DMX encoding can be read.
+//! Source 2 models and materials can't be loaded, so meshes only keep their material names
+//! and entities other than lights, such as props, are imported as unknown entities.
+
+mod dmx;
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+    str::FromStr,
+};
+
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use tracing::{info_span, warn};
+
+use plumber_core::{
+    asset_core::{AssetConfig, Context},
+    fs::PathBuf,
+};
+
+use self::dmx::{parse_vector, Dmx, DmxError, Element};
+
+use super::BlenderAssetHandler;
+
+#[derive(Debug)]
+pub enum VmapError {
+    Io(PathBuf, io::Error),
+    Dmx(PathBuf, DmxError),
+    NotAMap(PathBuf),
+}
+
+impl Display for VmapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "vmap `{path}`: {err}"),
+            Self::Dmx(path, err) => write!(f, "vmap `{path}`: {err}"),
+            Self::NotAMap(path) => write!(f, "vmap `{path}`: the file doesn't contain a map"),
+        }
+    }
+}
+
+impl Error for VmapError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmapFace {
+    pub vertice_indices: Vec<usize>,
+    pub vertice_uvs: Vec<[f32; 2]>,
+    pub material_index: usize,
+}
+
+/// A mesh with its transform applied.
+/// The vertices are relative to `position`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmapMesh {
+    pub id: i32,
+    pub position: [f32; 3],
+    pub vertices: Vec<[f32; 3]>,
+    pub faces: Vec<VmapFace>,
+    pub materials: Vec<String>,
+}
+
+/// The world or an entity with meshes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmapBrush {
+    pub id: i32,
+    pub class_name: String,
+    pub meshes: Vec<VmapMesh>,
+}
+
+/// An entity without meshes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmapEntity {
+    pub id: i32,
+    pub class_name: String,
+    pub position: [f32; 3],
+    /// Pitch, yaw and roll in degrees.
+    pub angles: [f32; 3],
+    pub properties: BTreeMap<String, String>,
+}
+
+impl VmapEntity {
+    pub fn property<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.properties.get(key)?.trim().parse().ok()
+    }
+
+    pub fn vector<const N: usize>(&self, key: &str) -> Option<[f32; N]> {
+        parse_vector(self.properties.get(key)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vmap {
+    pub name: String,
+    pub brushes: Vec<VmapBrush>,
+    pub entities: Vec<VmapEntity>,
+}
+
+/// Loads the meshes and entities of a Source 2 `.vmap` map.
+#[derive(Debug, Clone, Copy)]
+pub struct VmapConfig;
+
+impl AssetConfig<BlenderAssetHandler> for VmapConfig {
+    type Input<'a> = PathBuf;
+    type Output<'a> = Vmap;
+    type Error<'a> = VmapError;
+
+    fn process<'a>(
+        self,
+        input: Self::Input<'a>,
+        context: &mut Context<BlenderAssetHandler>,
+    ) -> Result<Self::Output<'a>, Self::Error<'a>> {
+        let _span = info_span!("vmap parse", id = %input).entered();
+
+        let bytes = context
+            .fs()
+            .read(&input)
+            .map_err(|e| VmapError::Io(input.clone(), e))?;
+
+        let dmx = Dmx::parse(&String::from_utf8_lossy(&bytes))
+            .map_err(|e| VmapError::Dmx(input.clone(), e))?;
+
+        let (brushes, entities) =
+            build_map(&dmx).ok_or_else(|| VmapError::NotAMap(input.clone()))?;
+
+        Ok(Vmap {
+            name: input.to_string(),
+            brushes,
+            entities,
+        })
+    }
+}
+
+fn build_map(dmx: &Dmx) -> Option<(Vec<VmapBrush>, Vec<VmapEntity>)> {
+    let world = dmx.element(dmx.root()?, "world")?;
+
+    let mut builder = MapBuilder {
+        dmx,
+        brushes: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    let mut meshes = Vec::new();
+    builder.visit_children(world, node_transform(world), &mut meshes);
+
+    let world_brush = VmapBrush {
+        id: world.get("nodeID").unwrap_or_default(),
+        class_name: "worldspawn".to_owned(),
+        meshes,
+    };
+    builder.brushes.insert(0, world_brush);
+
+    Some((builder.brushes, builder.entities))
+}
+
+struct MapBuilder<'a> {
+    dmx: &'a Dmx,
+    brushes: Vec<VmapBrush>,
+    entities: Vec<VmapEntity>,
+}
+
+impl MapBuilder<'_> {
+    /// Collects the meshes of `node` into `meshes`, and entities into the builder.
+    fn visit_children(&mut self, node: &Element, transform: Mat4, meshes: &mut Vec<VmapMesh>) {
+        let dmx = self.dmx;
+
+        for child in dmx.elements(node, "children") {
+            let child_transform = transform * node_transform(child);
+
+            match child.class_name.as_str() {
+                "CMapMesh" => meshes.extend(build_mesh(dmx, child, child_transform)),
+                "CMapEntity" => self.visit_entity(child, child_transform),
+                "CMapPrefab" => {
+                    warn!(
+                        "vmap: prefab `{}` is not imported",
+                        child.get_text("targetMapPath").unwrap_or_default()
+                    );
+                }
+                // groups and other containers only affect the transform
+                _ => self.visit_children(child, child_transform, meshes),
+            }
+        }
+    }
+
+    fn visit_entity(&mut self, element: &Element, transform: Mat4) {
+        let properties: BTreeMap<_, _> = self
+            .dmx
+            .element(element, "entity_properties")
+            .map(|p| {
+                p.text_attributes()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let id = element.get("nodeID").unwrap_or_default();
+        let class_name = properties.get("classname").cloned().unwrap_or_default();
+
+        let mut meshes = Vec::new();
+        self.visit_children(element, transform, &mut meshes);
+
+        if !meshes.is_empty() {
+            self.brushes.push(VmapBrush {
+                id,
+                class_name,
+                meshes,
+            });
+            return;
+        }
+
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        let (yaw, pitch, roll) = rotation.to_euler(EulerRot::ZYX);
+
+        self.entities.push(VmapEntity {
+            id,
+            class_name,
+            position: translation.to_array(),
+            angles: [pitch, yaw, roll].map(f32::to_degrees),
+            properties,
+        });
+    }
+}
+
+fn node_transform(element: &Element) -> Mat4 {
+    let origin = element.get_vector("origin").unwrap_or_default();
+    let [pitch, yaw, roll] = element.get_vector("angles").unwrap_or_default();
+    let scales = element.get_vector("scales").unwrap_or([1.0; 3]);
+
+    Mat4::from_scale_rotation_translation(
+        scales.into(),
+        Quat::from_euler(
+            EulerRot::ZYX,
+            yaw.to_radians(),
+            pitch.to_radians(),
+            roll.to_radians(),
+        ),
+        origin.into(),
+    )
+}
+
+/// Returns the stream of a mesh data array, such as the `texcoord` stream of `faceVertexData`.
+fn data_stream<'a>(
+    dmx: &'a Dmx,
+    mesh_data: &'a Element,
+    array: &str,
+    attribute: &str,
+) -> Option<&'a Element> {
+    let array = dmx.element(mesh_data, array)?;
+
+    dmx.elements(array, "streams").find(|stream| {
+        // stream names have a semantic index suffix, such as `texcoord$0`
+        stream
+            .get_text("standardAttributeName")
+            .or_else(|| stream.get_text("name"))
+            .and_then(|name| name.split(['$', ':']).next())
+            == Some(attribute)
+    })
+}
+
+/// Returns an index from an index array, where deleted elements are `-1`.
+fn index_at(indices: &[i32], i: usize) -> Option<usize> {
+    usize::try_from(*indices.get(i)?).ok()
+}
+
+/// Builds a mesh from the half-edge data of a `CMapMesh`.
+fn build_mesh(dmx: &Dmx, element: &Element, transform: Mat4) -> Option<VmapMesh> {
+    let data = dmx.element(element, "meshData")?;
+
+    let vertex_data_indices: Vec<i32> = data.get_array("vertexDataIndices")?;
+    let edge_vertices: Vec<i32> = data.get_array("edgeVertexIndices")?;
+    let edge_next: Vec<i32> = data.get_array("edgeNextIndices")?;
+    let edge_vertex_data: Vec<i32> = data.get_array("edgeVertexDataIndices")?;
+    let face_edges: Vec<i32> = data.get_array("faceEdgeIndices")?;
+    let face_data_indices: Vec<i32> = data.get_array("faceDataIndices").unwrap_or_default();
+
+    let positions =
+        data_stream(dmx, data, "vertexData", "position")?.get_vector_array::<3>("data")?;
+    let uvs = data_stream(dmx, data, "faceVertexData", "texcoord")
+        .and_then(|s| s.get_vector_array::<2>("data"))
+        .unwrap_or_default();
+    let material_indices: Vec<usize> = data_stream(dmx, data, "faceData", "materialindex")
+        .and_then(|s| s.get_array("data"))
+        .unwrap_or_default();
+
+    let materials = data
+        .get_string_array("materials")
+        .unwrap_or_default()
+        .iter()
+        .map(|m| material_name(m))
+        .collect();
+
+    let position = transform.transform_point3(Vec3::ZERO);
+
+    let vertices = (0..vertex_data_indices.len())
+        .map(|vertex| {
+            let local = index_at(&vertex_data_indices, vertex)
+                .and_then(|i| positions.get(i))
+                .copied()
+                .unwrap_or_default();
+
+            (transform.transform_point3(local.into()) - position).to_array()
+        })
+        .collect();
+
+    let faces = (0..face_edges.len())
+        .filter_map(|face| {
+            let first_edge = index_at(&face_edges, face)?;
+
+            let mut vertice_indices = Vec::new();
+            let mut vertice_uvs = Vec::new();
+            let mut edge = first_edge;
+
+            loop {
+                vertice_indices.push(index_at(&edge_vertices, edge)?);
+                vertice_uvs.push(
+                    index_at(&edge_vertex_data, edge)
+                        .and_then(|i| uvs.get(i))
+                        .copied()
+                        .unwrap_or_default(),
+                );
+
+                edge = index_at(&edge_next, edge)?;
+
+                // a corrupted edge loop would never return to the first edge
+                if edge == first_edge || vertice_indices.len() > edge_vertices.len() {
+                    break;
+                }
+            }
+
+            if vertice_indices.len() < 3 {
+                return None;
+            }
+
+            let material_index = index_at(&face_data_indices, face)
+                .and_then(|i| material_indices.get(i))
+                .copied()
+                .unwrap_or_default();
+
+            Some(VmapFace {
+                vertice_indices,
+                vertice_uvs,
+                material_index,
+            })
+        })
+        .collect();
+
+    Some(VmapMesh {
+        id: element.get("nodeID").unwrap_or_default(),
+        position: position.to_array(),
+        vertices,
+        faces,
+        materials,
+    })
+}
+
+/// Converts a `.vmat` path into a material name in the format Source 1 materials use,
+/// so that maps ported from Source 1 can use the same materials.
+fn material_name(path: &str) -> String {
+    let path = path.replace('\\', "/").to_ascii_lowercase();
+    let path = path.strip_prefix("materials/").unwrap_or(&path);
+
+    path.strip_suffix(".vmat").unwrap_or(path).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"<!-- dmx encoding keyvalues2 4 format vmap 29 -->
+"CMapRootElement"
+{
+	"id" "elementid" "root"
+	"world" "CMapWorld"
+	{
+		"id" "elementid" "world"
+		"nodeID" "int" "1"
+		"children" "element_array"
+		[
+			"CMapGroup"
+			{
+				"id" "elementid" "group"
+				"origin" "vector3" "0 0 64"
+				"children" "element_array"
+				[
+					"CMapMesh"
+					{
+						"id" "elementid" "mesh"
+						"nodeID" "int" "2"
+						"origin" "vector3" "16 0 0"
+						"meshData" "CDmePolygonMesh"
+						{
+							"id" "elementid" "data"
+							"vertexDataIndices" "int_array" [ "0", "1", "2" ]
+							"edgeVertexIndices" "int_array" [ "1", "2", "0" ]
+							"edgeNextIndices" "int_array" [ "1", "2", "0" ]
+							"edgeVertexDataIndices" "int_array" [ "0", "1", "2" ]
+							"faceEdgeIndices" "int_array" [ "0" ]
+							"faceDataIndices" "int_array" [ "0" ]
+							"materials" "string_array" [ "materials/dev/reflectivity_30.vmat" ]
+							"vertexData" "CDmePolygonMeshDataArray"
+							{
+								"id" "elementid" "vertex data"
+								"streams" "element_array"
+								[
+									"CDmePolygonMeshDataStream"
+									{
+										"id" "elementid" "positions"
+										"name" "string" "position$0"
+										"data" "vector3_array" [ "0 0 0", "8 0 0", "0 8 0" ]
+									}
+								]
+							}
+						}
+					}
+				]
+			},
+			"CMapEntity"
+			{
+				"id" "elementid" "light"
+				"nodeID" "int" "3"
+				"origin" "vector3" "1 2 3"
+				"entity_properties" "EditGameClass"
+				{
+					"id" "elementid" "light properties"
+					"classname" "string" "light_omni"
+					"brightness" "float" "2"
+				}
+			}
+		]
+	}
+}
+"#;
+
+    #[test]
+    fn builds_meshes_and_entities() {
+        let dmx = Dmx::parse(MAP).unwrap();
+        let (brushes, entities) = build_map(&dmx).unwrap();
+
+        assert_eq!(brushes.len(), 1);
+        assert_eq!(brushes[0].class_name, "worldspawn");
+
+        let mesh = &brushes[0].meshes[0];
+        assert_eq!(mesh.id, 2);
+        assert_eq!(mesh.position, [16.0, 0.0, 64.0]);
+        assert_eq!(mesh.vertices[1], [8.0, 0.0, 0.0]);
+        assert_eq!(mesh.faces[0].vertice_indices, vec![1, 2, 0]);
+        assert_eq!(mesh.materials, vec!["dev/reflectivity_30"]);
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].class_name, "light_omni");
+        assert_eq!(entities[0].position, [1.0, 2.0, 3.0]);
+        assert_eq!(entities[0].property::<f32>("brightness"), Some(2.0));
+    }
+}
@@ -0,0 +1,69 @@
+//! Data of the entities of vmfs which spans several entities or solids, read from the parsed
+//! vmf before it's converted.
+//!
+//! Each entity is converted separately and only knows its own keyvalues, so the data is
+//! resolved here first and sent along with the entities or brushes it belongs to.
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use plumber_core::vmf::vmf::Vmf;
+
+use super::{
+    brush::BrushEntities, parents::EntityParents, path_chain::PathChains, solid_repair::SolidRepair,
+};
+
+/// The stores the entity data of the imported vmfs is read into.
+#[derive(Debug, Clone, Default)]
+pub struct VmfEntities {
+    /// Parents of the entities, which the entities are sent with.
+    pub parents: Arc<RwLock<EntityParents>>,
+    /// Render settings, origins and motions of brush entities, which brushes are sent with.
+    pub brushes: Arc<RwLock<BrushEntities>>,
+    /// Chains of path entities, which are sent with the first entity of each chain.
+    pub path_chains: Arc<RwLock<PathChains>>,
+    /// Solids which were repaired while reading the vmfs, reported with the next built brush.
+    pub solid_repairs: Arc<Mutex<Vec<SolidRepair>>>,
+    /// Offset of the ids of the next vmf, past the ids of the vmfs read before it.
+    next_id_offset: Arc<Mutex<i32>>,
+}
+
+impl VmfEntities {
+    /// Reads the entity data of a vmf. This needs to be done before it's queued.
+    pub fn extend_from_vmf(&self, vmf: &Vmf) {
+        self.parents
+            .write()
+            .expect("entity parent lock shouldn't be poisoned")
+            .extend_from_vmf(vmf);
+
+        self.brushes
+            .write()
+            .expect("brush entity lock shouldn't be poisoned")
+            .extend_from_vmf(vmf);
+
+        self.path_chains
+            .write()
+            .expect("path chain lock shouldn't be poisoned")
+            .extend_from_vmf(vmf);
+    }
+
+    /// Reserves the ids of a vmf whose highest id is `max_id`, returning the offset its ids
+    /// need to be moved by to not overlap the ids of the vmfs read before it.
+    pub fn reserve_ids(&self, max_id: i32) -> i32 {
+        let mut next_id_offset = self
+            .next_id_offset
+            .lock()
+            .expect("id offset lock shouldn't be poisoned");
+
+        let offset = *next_id_offset;
+        *next_id_offset = offset.saturating_add(max_id);
+        offset
+    }
+
+    /// Adds the solids which were repaired while reading a vmf, to be reported.
+    pub fn add_solid_repairs(&self, repairs: Vec<SolidRepair>) {
+        self.solid_repairs
+            .lock()
+            .expect("solid repair lock shouldn't be poisoned")
+            .extend(repairs);
+    }
+}
@@ -0,0 +1,220 @@
+//! Reading vmfs and the map formats converted to them, shared by the importers and the cli.
+//!
+//! Each step works on the bytes of the vmf, so the order matters: rmfs are binary and converted
+//! before decoding, Quake maps are text and converted after it, and the metadata is read from
+//! the final bytes which the vmf is parsed from.
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use plumber_core::vmf::vmf::Vmf;
+
+use super::{
+    bounds::{filter_bounds, Bounds},
+    displacement::{simplify_displacements, DisplacementSettings},
+    encoding::decode_vmf,
+    id_offset::{max_id, offset_ids},
+    quake_map::{is_quake_map, quake_map_to_vmf, QuakeMapError},
+    rmf::{is_rmf, rmf_to_vmf, RmfError},
+    solid_repair::{repair_solids, SolidRepair},
+    vmf_entities::VmfEntities,
+    vmf_metadata::VmfMetadata,
+};
+
+/// Format of a map file, which is converted to a vmf when it's read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFormat {
+    Vmf,
+    /// The binary `.rmf` maps of older Hammer versions.
+    Rmf,
+    /// The `.map` files of Quake based games.
+    QuakeMap,
+}
+
+impl MapFormat {
+    /// Returns the format of a map by its extension, vmf if it's not known.
+    pub fn from_path(path: &str) -> Self {
+        if is_rmf(path) {
+            Self::Rmf
+        } else if is_quake_map(path) {
+            Self::QuakeMap
+        } else {
+            Self::Vmf
+        }
+    }
+}
+
+/// How the bytes of a vmf are changed before it's parsed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VmfLoadSettings {
+    pub displacements: DisplacementSettings,
+    /// Remove the sides of invalid solids which have no face, instead of skipping the solids.
+    pub repair_solids: bool,
+    /// Only import the solids and entities within these bounds, if set.
+    pub bounds: Option<Bounds>,
+}
+
+#[derive(Debug)]
+pub enum VmfLoadError {
+    Rmf(RmfError),
+    QuakeMap(QuakeMapError),
+    /// The vmf parser doesn't name its error type, so only its message is kept.
+    Parse(String),
+}
+
+impl Display for VmfLoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rmf(error) => write!(f, "failed to convert rmf: {error}"),
+            Self::QuakeMap(error) => write!(f, "failed to convert map: {error}"),
+            Self::Parse(error) => write!(f, "failed to parse vmf: {error}"),
+        }
+    }
+}
+
+impl Error for VmfLoadError {}
+
+impl From<RmfError> for VmfLoadError {
+    fn from(error: RmfError) -> Self {
+        Self::Rmf(error)
+    }
+}
+
+impl From<QuakeMapError> for VmfLoadError {
+    fn from(error: QuakeMapError) -> Self {
+        Self::QuakeMap(error)
+    }
+}
+
+/// Converts and parses the bytes of a map file, reading its metadata and entity data into the
+/// stores on the way. Returns the vmf along with the solids which were repaired.
+///
+/// The ids of the vmf are offset past the ids of the vmfs read into the stores before it.
+///
+/// # Errors
+///
+/// Returns an error if the map can't be converted or the vmf can't be parsed.
+pub fn load_vmf_bytes(
+    bytes: &[u8],
+    format: MapFormat,
+    settings: &VmfLoadSettings,
+    vmf_metadata: &VmfMetadata,
+    vmf_entities: &VmfEntities,
+) -> Result<(Vmf, Vec<SolidRepair>), VmfLoadError> {
+    // rmfs are binary, so they are converted before decoding
+    let bytes = if format == MapFormat::Rmf {
+        Cow::Owned(rmf_to_vmf(bytes)?)
+    } else {
+        Cow::Borrowed(bytes)
+    };
+
+    let bytes = decode_vmf(&bytes);
+
+    let bytes = if format == MapFormat::QuakeMap {
+        Cow::Owned(quake_map_to_vmf(&bytes)?)
+    } else {
+        bytes
+    };
+
+    let bytes = simplify_displacements(&bytes, &settings.displacements);
+
+    let mut repairs = Vec::new();
+    let bytes = if settings.repair_solids {
+        repair_solids(&bytes, &mut repairs)
+    } else {
+        Cow::Borrowed(&*bytes)
+    };
+
+    let bytes = match settings.bounds {
+        Some(bounds) => filter_bounds(&bytes, bounds),
+        None => Cow::Borrowed(&*bytes),
+    };
+
+    // the stores and the imported objects are keyed by id, which every vmf starts from 1
+    let id_offset = vmf_entities.reserve_ids(max_id(&bytes));
+    let bytes = offset_ids(&bytes, id_offset);
+
+    vmf_metadata.extend_from_vmf(&bytes);
+
+    let vmf = Vmf::from_bytes(&bytes).map_err(|e| VmfLoadError::Parse(e.to_string()))?;
+
+    vmf_entities.extend_from_vmf(&vmf);
+
+    Ok((vmf, repairs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formats_by_extension() {
+        assert_eq!(MapFormat::from_path("maps/e1m1.MAP"), MapFormat::QuakeMap);
+        assert_eq!(MapFormat::from_path("maps/old.rmf"), MapFormat::Rmf);
+        assert_eq!(MapFormat::from_path("maps/new.vmf"), MapFormat::Vmf);
+        assert_eq!(MapFormat::from_path("maps/map"), MapFormat::Vmf);
+    }
+
+    fn load(bytes: &[u8], format: MapFormat) -> Result<(Vmf, Vec<SolidRepair>), VmfLoadError> {
+        load_vmf_bytes(
+            bytes,
+            format,
+            &VmfLoadSettings::default(),
+            &VmfMetadata::default(),
+            &VmfEntities::default(),
+        )
+    }
+
+    #[test]
+    fn converts_decoded_quake_maps() {
+        let mut map = b"\xEF\xBB\xBF".to_vec();
+        map.extend_from_slice(
+            b"{\n\"classname\" \"worldspawn\"\n}\n{\n\"classname\" \"info_player_start\"\n}\n",
+        );
+
+        let (vmf, repairs) = load(&map, MapFormat::QuakeMap).expect("map should load");
+
+        assert_eq!(vmf.entities.len(), 1);
+        assert_eq!(vmf.entities[0].class_name, "info_player_start");
+        assert!(repairs.is_empty());
+    }
+
+    #[test]
+    fn offsets_overlapping_ids_of_later_maps() {
+        let map = b"world\n{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n}\n\
+            entity\n{\n\t\"id\" \"2\"\n\t\"classname\" \"func_door\"\n\t\"targetname\" \"door\"\n}\n\
+            entity\n{\n\t\"id\" \"3\"\n\t\"classname\" \"prop_dynamic\"\n\t\"parentname\" \"door\"\n}\n";
+
+        let vmf_metadata = VmfMetadata::default();
+        let vmf_entities = VmfEntities::default();
+        let settings = VmfLoadSettings::default();
+
+        let (first, _) =
+            load_vmf_bytes(map, MapFormat::Vmf, &settings, &vmf_metadata, &vmf_entities)
+                .expect("first map should load");
+        let (second, _) =
+            load_vmf_bytes(map, MapFormat::Vmf, &settings, &vmf_metadata, &vmf_entities)
+                .expect("second map should load");
+
+        let ids = |vmf: &Vmf| vmf.entities.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), [2, 3]);
+        assert_eq!(ids(&second), [5, 6]);
+
+        let parents = vmf_entities
+            .parents
+            .read()
+            .expect("entity parent lock shouldn't be poisoned");
+        assert_eq!(parents.get(3), Some(2));
+        assert_eq!(parents.get(6), Some(5));
+    }
+
+    #[test]
+    fn rejects_invalid_rmfs() {
+        let error = load(b"not an rmf", MapFormat::Rmf).expect_err("rmf should be rejected");
+
+        assert!(matches!(error, VmfLoadError::Rmf(_)));
+    }
+}
@@ -0,0 +1,76 @@
+//! Metadata of vmfs which the parsed vmf lacks, read from the keyvalues of the vmf.
+//!
+//! The vmf is parsed into keyvalues once for all the metadata, and not at all if none of it
+//! is used by the import.
+
+use std::sync::{Arc, RwLock};
+
+use super::{
+    editor_groups::EditorGroups, keyvalues::parse_document, lightmap::LightmapScales,
+    seam::BrushPlanes, source_keyvalues::SourceKeyValues, vertices_plus::ExactVertices,
+};
+
+/// The stores the metadata of the imported vmfs is read into, which are `None` if the import
+/// doesn't use their metadata.
+#[derive(Debug, Clone, Default)]
+pub struct VmfMetadata {
+    pub lightmap_scales: Option<Arc<RwLock<LightmapScales>>>,
+    pub exact_vertices: Option<Arc<RwLock<ExactVertices>>>,
+    pub brush_planes: Option<Arc<RwLock<BrushPlanes>>>,
+    pub editor_groups: Option<Arc<RwLock<EditorGroups>>>,
+    pub source_keyvalues: Option<Arc<RwLock<SourceKeyValues>>>,
+}
+
+impl VmfMetadata {
+    fn is_empty(&self) -> bool {
+        self.lightmap_scales.is_none()
+            && self.exact_vertices.is_none()
+            && self.brush_planes.is_none()
+            && self.editor_groups.is_none()
+            && self.source_keyvalues.is_none()
+    }
+
+    /// Reads the used metadata of a vmf. This needs to be done before its brushes are built.
+    pub fn extend_from_vmf(&self, bytes: &[u8]) {
+        if self.is_empty() {
+            return;
+        }
+
+        let vmf = parse_document(bytes);
+
+        if let Some(scales) = &self.lightmap_scales {
+            scales
+                .write()
+                .expect("lightmap scale lock shouldn't be poisoned")
+                .extend_from_keyvalues(&vmf);
+        }
+
+        if let Some(exact_vertices) = &self.exact_vertices {
+            exact_vertices
+                .write()
+                .expect("exact vertex lock shouldn't be poisoned")
+                .extend_from_keyvalues(&vmf);
+        }
+
+        if let Some(brush_planes) = &self.brush_planes {
+            brush_planes
+                .write()
+                .expect("brush plane lock shouldn't be poisoned")
+                .extend_from_keyvalues(&vmf);
+        }
+
+        if let Some(editor_groups) = &self.editor_groups {
+            editor_groups
+                .write()
+                .expect("editor group lock shouldn't be poisoned")
+                .extend_from_keyvalues(&vmf);
+        }
+
+        if let Some(source_keyvalues) = &self.source_keyvalues {
+            source_keyvalues
+                .write()
+                .expect("source keyvalue lock shouldn't be poisoned")
+                .extend_from_keyvalues(&vmf);
+        }
+    }
+}
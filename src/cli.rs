@@ -0,0 +1,509 @@
+//! Headless map conversion without Blender, used by the `plumber-cli` binary.
+
+use std::{
+    fs, io,
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+    time::Instant,
+};
+
+use tracing::info;
+use tracing_subscriber::prelude::*;
+
+use plumber_core::{
+    asset_core::Executor,
+    asset_vmf::{BrushSetting, VmfConfig},
+    fs::{FileSystem, GamePathBuf, PathBuf},
+};
+
+use crate::{
+    asset::{
+        bounds::Bounds,
+        displacement::DisplacementSettings,
+        job::{AssetImportJob, UnifiedAssetConfig},
+        material::{MaterialConfig, TextureFormat},
+        profile::{self, ProfileLayer},
+        up_axis::UpAxis,
+        vmf_entities::VmfEntities,
+        vmf_load::{load_vmf_bytes, MapFormat, VmfLoadSettings},
+        vmf_metadata::VmfMetadata,
+        BlenderAssetHandler, HandlerSettings, DEFAULT_CHANNEL_CAPACITY,
+    },
+    export::{self, GltfOutput, ObjOutput, Output, UsdOutput},
+    manifest::Manifest,
+};
+
+const USAGE: &str = "\
+usage: plumber-cli [options] <gameinfo.txt> <map.vmf> <output directory>
+
+Converts a map into the output directory without Blender.
+
+formats:
+    obj                brushes into map.obj and their materials into map.mtl,
+                       with the textures they use in textures/
+    gltf, glb          brushes, props, materials and lights into map.gltf or map.glb
+    usd, usda          brushes, instanced props, materials and lights into map.usda,
+                       with the textures they use in textures/
+
+options:
+    --bounds <min x,y,z,max x,y,z>
+                       only convert the solids and entities within this box in Source units
+    --channel-capacity <n>
+                       number of converted assets that can wait to be written before
+                       the workers block, defaults to 256
+    --clean-faces      remove faces without area and collinear vertices from brushes
+    --cut-threshold <distance>
+                       minimum distance a face must extend past a plane to be cut by it,
+                       raise it if off-grid brushes have sliver polygons
+    --displacement-tolerance <distance>
+                       lower the power of displacements while no vertex moves further
+                       than this distance in Source units, defaults to 0 (disabled)
+    --epsilon <distance>
+                       distance within which points are considered to be on a plane,
+                       raise it if faces of off-grid brushes are missing
+    --format <format>  output format, defaults to obj
+    --from-game        read the vmf from the game file system instead of the os file system
+    --manifest         only write a json manifest of the referenced files, without converting
+    --max-displacement-power <2-4>
+                       lower the power of displacements above this, defaults to 4
+    --profile          log how long each stage and the slowest assets took after converting
+    --repair-solids    remove the sides of invalid solids which have no face,
+                       instead of skipping the whole solid
+    --scale <scale|real-world|character>
+                       scale of the output geometry, defaults to 1.0 (Source units).
+                       real-world converts 1 unit = 1 inch to meters,
+                       character converts 1 unit = 0.75 inch to meters
+    --seam-tolerance <distance>
+                       sew displacement edges within this distance in Source units together,
+                       defaults to 1.0, 0 disables
+    --texture-format <png|tga|exr>
+                       format of the written textures, defaults to png.
+                       gltf and glb only support png
+    --threads <n|auto> number of worker threads, auto or 0 uses the number of cpus,
+                       defaults to auto
+    --weld-distance <distance>
+                       weld brush vertices within this distance in Source units together,
+                       defaults to 0 (disabled)
+    --y-up             write usd geometry and transforms y-up instead of z-up,
+                       obj, gltf and glb are always y-up";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Obj,
+    Gltf,
+    Glb,
+    Usd,
+}
+
+impl Format {
+    fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "obj" => Some(Self::Obj),
+            "gltf" => Some(Self::Gltf),
+            "glb" => Some(Self::Glb),
+            "usd" | "usda" => Some(Self::Usd),
+            _ => None,
+        }
+    }
+
+    /// Returns the up axis the assets are sent in for the format,
+    /// obj and gltf are always y-up while usd is only with `--y-up`.
+    fn up_axis(self, y_up: bool) -> UpAxis {
+        if y_up || self != Self::Usd {
+            UpAxis::Y
+        } else {
+            UpAxis::Z
+        }
+    }
+
+    fn create_output(
+        self,
+        directory: &StdPath,
+        scale: f32,
+        up_axis: UpAxis,
+    ) -> io::Result<Box<dyn Output>> {
+        Ok(match self {
+            Self::Obj => Box::new(ObjOutput::create(directory)?),
+            Self::Gltf => Box::new(GltfOutput::create(&directory.join("map.gltf"))?),
+            Self::Glb => Box::new(GltfOutput::create(&directory.join("map.glb"))?),
+            Self::Usd => Box::new(UsdOutput::create(
+                &directory.join("map.usda"),
+                scale,
+                up_axis,
+            )?),
+        })
+    }
+}
+
+struct Args {
+    game_info: StdPathBuf,
+    vmf: String,
+    output: StdPathBuf,
+    format: Format,
+    channel_capacity: usize,
+    clean_faces: bool,
+    bounds: Option<Bounds>,
+    cut_threshold: Option<f32>,
+    displacements: DisplacementSettings,
+    epsilon: Option<f32>,
+    from_game: bool,
+    manifest: bool,
+    profile: bool,
+    repair_solids: bool,
+    scale: f32,
+    seam_tolerance: f32,
+    texture_format: TextureFormat,
+    threads: usize,
+    weld_distance: f32,
+    y_up: bool,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut format = Format::Obj;
+        let mut channel_capacity = DEFAULT_CHANNEL_CAPACITY;
+        let mut clean_faces = false;
+        let mut bounds = None;
+        let mut cut_threshold = None;
+        let mut displacements = DisplacementSettings::default();
+        let mut epsilon = None;
+        let mut from_game = false;
+        let mut manifest = false;
+        let mut profile = false;
+        let mut repair_solids = false;
+        let mut scale = 1.0;
+        let mut seam_tolerance = 1.0;
+        let mut texture_format = TextureFormat::Png;
+        let mut threads = available_threads();
+        let mut weld_distance = 0.0;
+        let mut y_up = false;
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bounds" => {
+                    bounds = Some(
+                        args.next()
+                            .and_then(|s| parse_bounds(s))
+                            .ok_or("--bounds expects six comma separated numbers")?,
+                    );
+                }
+                "--channel-capacity" => {
+                    channel_capacity = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .filter(|&n| n > 0)
+                        .ok_or("--channel-capacity expects a positive number")?;
+                }
+                "--clean-faces" => clean_faces = true,
+                "--cut-threshold" => {
+                    cut_threshold = Some(
+                        args.next()
+                            .and_then(|s| s.parse().ok())
+                            .ok_or("--cut-threshold expects a number")?,
+                    );
+                }
+                "--displacement-tolerance" => {
+                    displacements.tolerance = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .ok_or("--displacement-tolerance expects a number")?;
+                }
+                "--epsilon" => {
+                    epsilon = Some(
+                        args.next()
+                            .and_then(|s| s.parse().ok())
+                            .ok_or("--epsilon expects a number")?,
+                    );
+                }
+                "--format" => {
+                    format = args
+                        .next()
+                        .and_then(|s| Format::parse(s))
+                        .ok_or("--format expects obj, gltf, glb or usd")?;
+                }
+                "--from-game" => from_game = true,
+                "--manifest" => manifest = true,
+                "--max-displacement-power" => {
+                    displacements.max_power = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .ok_or("--max-displacement-power expects a number")?;
+                }
+                "--profile" => profile = true,
+                "--repair-solids" => repair_solids = true,
+                "--scale" => {
+                    scale = args
+                        .next()
+                        .and_then(|s| parse_scale(s))
+                        .ok_or("--scale expects a number, real-world or character")?;
+                }
+                "--seam-tolerance" => {
+                    seam_tolerance = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .ok_or("--seam-tolerance expects a number")?;
+                }
+                "--texture-format" => {
+                    texture_format = args
+                        .next()
+                        .and_then(|s| parse_texture_format(s))
+                        .ok_or("--texture-format expects png, tga or exr")?;
+                }
+                "--threads" => {
+                    threads = args
+                        .next()
+                        .and_then(|s| parse_threads(s))
+                        .ok_or("--threads expects a number or auto")?;
+                }
+                "--weld-distance" => {
+                    weld_distance = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .ok_or("--weld-distance expects a number")?;
+                }
+                "--y-up" => y_up = true,
+                "-h" | "--help" => return Err(USAGE.to_owned()),
+                _ => positional.push(arg.as_str()),
+            }
+        }
+
+        let [game_info, vmf, output] = positional[..] else {
+            return Err(USAGE.to_owned());
+        };
+
+        if matches!(format, Format::Gltf | Format::Glb)
+            && !matches!(texture_format, TextureFormat::Png)
+        {
+            return Err("gltf and glb only support png textures".to_owned());
+        }
+
+        Ok(Self {
+            game_info: game_info.into(),
+            vmf: vmf.to_owned(),
+            output: output.into(),
+            format,
+            channel_capacity,
+            bounds,
+            clean_faces,
+            cut_threshold,
+            displacements,
+            epsilon,
+            from_game,
+            manifest,
+            profile,
+            repair_solids,
+            scale,
+            seam_tolerance,
+            texture_format,
+            threads,
+            weld_distance,
+            y_up,
+        })
+    }
+}
+
+fn parse_texture_format(format: &str) -> Option<TextureFormat> {
+    match format.to_ascii_lowercase().as_str() {
+        "png" => Some(TextureFormat::Png),
+        "tga" => Some(TextureFormat::Tga),
+        "exr" => Some(TextureFormat::Exr),
+        _ => None,
+    }
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Parses the bounds to convert as `min_x,min_y,min_z,max_x,max_y,max_z` in Source units.
+fn parse_bounds(bounds: &str) -> Option<Bounds> {
+    let values: Vec<f32> = bounds
+        .split(',')
+        .map(|value| value.trim().parse().ok())
+        .collect::<Option<_>>()?;
+
+    match values.as_slice() {
+        &[min_x, min_y, min_z, max_x, max_y, max_z] => {
+            Some(Bounds::new([min_x, min_y, min_z], [max_x, max_y, max_z]))
+        }
+        _ => None,
+    }
+}
+
+/// Meters per unit of Valve's scale for world geometry, 1 unit = 1 inch.
+const REAL_WORLD_SCALE: f32 = 0.0254;
+
+/// Meters per unit of Valve's scale for characters, 1 unit = 0.75 inch.
+const CHARACTER_SCALE: f32 = 0.019_05;
+
+/// Parses an output scale, either `real-world`, `character` or a factor of Source units.
+fn parse_scale(scale: &str) -> Option<f32> {
+    match scale.to_ascii_lowercase().as_str() {
+        "real-world" => Some(REAL_WORLD_SCALE),
+        "character" => Some(CHARACTER_SCALE),
+        scale => scale.parse().ok(),
+    }
+}
+
+/// Parses a worker thread count, where `auto` and 0 use all available cpus.
+fn parse_threads(threads: &str) -> Option<usize> {
+    if threads.eq_ignore_ascii_case("auto") {
+        return Some(available_threads());
+    }
+
+    match threads.parse().ok()? {
+        0 => Some(available_threads()),
+        n => Some(n),
+    }
+}
+
+/// Runs the cli with the given arguments, excluding the program name.
+///
+/// # Errors
+///
+/// Returns an error message if the arguments are invalid or the import fails.
+pub fn run(args: &[String]) -> Result<(), String> {
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .finish()
+        .with(ProfileLayer)
+        .init();
+
+    let args = Args::parse(args)?;
+
+    let root_path = args
+        .game_info
+        .parent()
+        .and_then(StdPath::parent)
+        .ok_or("gameinfo.txt directory doesn't have a parent")?;
+
+    let file_system =
+        FileSystem::from_paths(root_path, &args.game_info).map_err(|e| e.to_string())?;
+    let opened = file_system.open().map_err(|e| e.to_string())?;
+
+    let path: PathBuf = if args.from_game {
+        GamePathBuf::from(args.vmf.as_str()).into()
+    } else {
+        StdPathBuf::from(&args.vmf).into()
+    };
+
+    let load_settings = VmfLoadSettings {
+        displacements: args.displacements,
+        repair_solids: args.repair_solids,
+        bounds: args.bounds,
+    };
+
+    if args.manifest {
+        let bytes = opened.read(&path).map_err(|e| e.to_string())?;
+        let (vmf, _) = load_vmf_bytes(
+            &bytes,
+            MapFormat::from_path(&args.vmf),
+            &load_settings,
+            &VmfMetadata::default(),
+            &VmfEntities::default(),
+        )
+        .map_err(|e| e.to_string())?;
+        let manifest = Manifest::new(&vmf, &opened);
+
+        fs::create_dir_all(&args.output).map_err(|e| e.to_string())?;
+        fs::write(args.output.join("manifest.json"), manifest.to_json())
+            .map_err(|e| e.to_string())?;
+
+        info!(
+            "manifest written, {} referenced files are missing",
+            manifest.missing_count()
+        );
+
+        return Ok(());
+    }
+
+    // obj only contains brush geometry, so entities are not imported for it
+    let import_entities = args.format != Format::Obj;
+    let up_axis = args.format.up_axis(args.y_up);
+
+    let mut settings = HandlerSettings {
+        import_lights: import_entities,
+        import_sky_camera: false,
+        scale: args.scale,
+        channel_capacity: args.channel_capacity,
+        seam_tolerance: args.seam_tolerance,
+        clean_faces: args.clean_faces,
+        weld_distance: args.weld_distance,
+        // the outputs write every texture they receive into its own file
+        deduplicate_textures: false,
+        // and don't organize the objects they write
+        editor_groups: false,
+        up_axis,
+        ..HandlerSettings::default()
+    };
+    settings.material.texture_format = args.texture_format;
+
+    let material_config = MaterialConfig {
+        settings: settings.material,
+    };
+    let import_lights = settings.import_lights;
+
+    let mut config = VmfConfig::new(material_config);
+    config.import_props = import_entities;
+    config.import_other_entities = import_entities;
+    config.import_skybox = false;
+    config.scale = args.scale;
+
+    if let BrushSetting::Import(geometry_settings) = &mut config.brushes {
+        if let Some(epsilon) = args.epsilon {
+            geometry_settings.epsilon(epsilon);
+        }
+
+        if let Some(cut_threshold) = args.cut_threshold {
+            geometry_settings.cut_threshold(cut_threshold);
+        }
+    }
+
+    let mut output = args
+        .format
+        .create_output(&args.output, args.scale, up_axis)
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    info!("converting vmf `{}`...", path);
+
+    let (sender, receiver) = crossbeam_channel::bounded(settings.channel_capacity);
+    let handler = BlenderAssetHandler::new(sender, settings);
+    let unified_config = UnifiedAssetConfig {
+        material_config,
+        import_lights,
+        cancelled: handler.cancelled(),
+    };
+    let vmf_metadata = handler.vmf_metadata();
+    let vmf_entities = handler.vmf_entities();
+    let executor = Executor::new_with_threads(handler, opened, args.threads);
+
+    if args.profile {
+        profile::start();
+    }
+
+    executor.process(
+        unified_config,
+        AssetImportJob::Vmf {
+            path,
+            config,
+            load_settings,
+            vmf_metadata,
+            vmf_entities,
+            separate_entity_solids: false,
+        },
+        || export::write_messages(output.as_mut(), &receiver),
+    );
+
+    if args.profile {
+        profile::finish().log();
+    }
+
+    output.finish().map_err(|e| e.to_string())?;
+
+    info!("vmf converted in {:.2} s", start.elapsed().as_secs_f32());
+
+    Ok(())
+}
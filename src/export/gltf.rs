@@ -0,0 +1,584 @@
+use std::{
+    collections::BTreeMap,
+    f32::consts::PI,
+    fs,
+    io::{self, Write},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+};
+
+use glam::Quat;
+
+use crate::{
+    asset::{
+        brush::PyBuiltBrushEntity,
+        entities::{PyLight, PyLoadedProp, PySpotLight},
+        material::{Material, Texture},
+        model::PyModel,
+        Message,
+    },
+    manifest::escape_json,
+};
+
+use super::{euler_to_quat, Output};
+
+/// Luminous efficacy used to convert Blender watts into candela.
+const LUMENS_PER_WATT: f32 = 683.0;
+
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const GLB_CHUNK_JSON: &[u8; 4] = b"JSON";
+const GLB_CHUNK_BIN: &[u8; 4] = b"BIN\0";
+
+fn json_array(items: &[String]) -> String {
+    format!("[{}]", items.join(","))
+}
+
+fn json_floats(values: &[f32]) -> String {
+    let values: Vec<_> = values.iter().map(f32::to_string).collect();
+    json_array(&values)
+}
+
+/// Triangles of a single material, with the vertices split per face corner.
+#[derive(Default)]
+struct Primitive {
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl Primitive {
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_polygon(&mut self, corners: impl IntoIterator<Item = ([f32; 3], [f32; 2])>) {
+        let start = self.positions.len() as u32;
+
+        for (position, uv) in corners {
+            self.positions.push(position);
+            self.uvs.push(uv);
+        }
+
+        let count = self.positions.len() as u32 - start;
+
+        // polygons are convex, so a triangle fan is enough
+        for i in 1..count.saturating_sub(1) {
+            self.indices.extend([start, start + i, start + i + 1]);
+        }
+    }
+}
+
+/// Writes brushes, props, materials and lights into a `.gltf` or `.glb` file.
+/// The handler has to send y-up assets, which `glTF` requires.
+pub struct GltfOutput {
+    path: StdPathBuf,
+    binary: bool,
+    buffer: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    meshes: Vec<String>,
+    nodes: Vec<String>,
+    lights: Vec<String>,
+    material_names: Vec<String>,
+    material_indices: BTreeMap<String, usize>,
+    base_textures: BTreeMap<String, String>,
+    images: BTreeMap<String, usize>,
+    model_meshes: BTreeMap<String, usize>,
+    props: Vec<PyLoadedProp>,
+}
+
+impl GltfOutput {
+    pub fn create(path: &StdPath) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let binary = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("glb"));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            binary,
+            buffer: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            lights: Vec::new(),
+            material_names: Vec::new(),
+            material_indices: BTreeMap::new(),
+            base_textures: BTreeMap::new(),
+            images: BTreeMap::new(),
+            model_meshes: BTreeMap::new(),
+            props: Vec::new(),
+        })
+    }
+
+    fn material_index(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.material_indices.get(name) {
+            return index;
+        }
+
+        let index = self.material_names.len();
+        self.material_names.push(name.to_owned());
+        self.material_indices.insert(name.to_owned(), index);
+        index
+    }
+
+    fn push_buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+
+        let target = target.map_or_else(String::new, |t| format!(",\"target\":{t}"));
+        self.buffer_views.push(format!(
+            "{{\"buffer\":0,\"byteOffset\":{offset},\"byteLength\":{}{target}}}",
+            data.len()
+        ));
+
+        self.buffer_views.len() - 1
+    }
+
+    fn push_accessor(
+        &mut self,
+        data: &[u8],
+        target: u32,
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        bounds: Option<([f32; 3], [f32; 3])>,
+    ) -> usize {
+        let view = self.push_buffer_view(data, Some(target));
+
+        let bounds = bounds.map_or_else(String::new, |(min, max)| {
+            format!(
+                ",\"min\":{},\"max\":{}",
+                json_floats(&min),
+                json_floats(&max)
+            )
+        });
+        self.accessors.push(format!(
+            "{{\"bufferView\":{view},\"componentType\":{component_type},\"count\":{count},\"type\":\"{kind}\"{bounds}}}"
+        ));
+
+        self.accessors.len() - 1
+    }
+
+    fn push_mesh(
+        &mut self,
+        name: &str,
+        primitives: BTreeMap<Option<usize>, Primitive>,
+    ) -> Option<usize> {
+        let mut primitives_json = Vec::new();
+
+        for (material, primitive) in primitives {
+            if primitive.indices.is_empty() {
+                continue;
+            }
+
+            let mut min = [f32::INFINITY; 3];
+            let mut max = [f32::NEG_INFINITY; 3];
+
+            for position in &primitive.positions {
+                for (i, &value) in position.iter().enumerate() {
+                    min[i] = min[i].min(value);
+                    max[i] = max[i].max(value);
+                }
+            }
+
+            let positions: Vec<u8> = primitive
+                .positions
+                .iter()
+                .flatten()
+                .flat_map(|f| f.to_le_bytes())
+                .collect();
+            let uvs: Vec<u8> = primitive
+                .uvs
+                .iter()
+                .flatten()
+                .flat_map(|f| f.to_le_bytes())
+                .collect();
+            let indices: Vec<u8> = primitive
+                .indices
+                .iter()
+                .flat_map(|i| i.to_le_bytes())
+                .collect();
+
+            let position_accessor = self.push_accessor(
+                &positions,
+                TARGET_ARRAY_BUFFER,
+                COMPONENT_FLOAT,
+                primitive.positions.len(),
+                "VEC3",
+                Some((min, max)),
+            );
+            let uv_accessor = self.push_accessor(
+                &uvs,
+                TARGET_ARRAY_BUFFER,
+                COMPONENT_FLOAT,
+                primitive.uvs.len(),
+                "VEC2",
+                None,
+            );
+            let index_accessor = self.push_accessor(
+                &indices,
+                TARGET_ELEMENT_ARRAY_BUFFER,
+                COMPONENT_UNSIGNED_INT,
+                primitive.indices.len(),
+                "SCALAR",
+                None,
+            );
+
+            let material = material.map_or_else(String::new, |m| format!(",\"material\":{m}"));
+            primitives_json.push(format!(
+                "{{\"attributes\":{{\"POSITION\":{position_accessor},\"TEXCOORD_0\":{uv_accessor}}},\"indices\":{index_accessor}{material}}}"
+            ));
+        }
+
+        if primitives_json.is_empty() {
+            return None;
+        }
+
+        self.meshes.push(format!(
+            "{{\"name\":\"{}\",\"primitives\":{}}}",
+            escape_json(name),
+            json_array(&primitives_json)
+        ));
+
+        Some(self.meshes.len() - 1)
+    }
+
+    fn write_brush(&mut self, brush: &PyBuiltBrushEntity) {
+        let mut primitives: BTreeMap<Option<usize>, Primitive> = BTreeMap::new();
+
+        for mesh in brush.meshes().filter(|m| !m.no_draw) {
+            for face in mesh.faces {
+                let material = mesh
+                    .materials
+                    .get(face.material_index)
+                    .map(|m| self.material_index(m));
+
+                let corners = face
+                    .vertice_indices
+                    .iter()
+                    .zip(&face.vertice_uvs)
+                    .map(|(&i, uv)| {
+                        let vertex = &mesh.vertices[i * 3..i * 3 + 3];
+                        let position =
+                            [0, 1, 2].map(|j| mesh.position[j] + vertex[j] * mesh.scale[j]);
+
+                        // glTF has the same uv origin as Source
+                        (position, [uv.x, uv.y])
+                    });
+
+                primitives
+                    .entry(material)
+                    .or_default()
+                    .push_polygon(corners);
+            }
+        }
+
+        let name = format!("{}_{}", brush.class_name(), brush.id);
+
+        if let Some(mesh) = self.push_mesh(&name, primitives) {
+            self.nodes.push(format!(
+                "{{\"name\":\"{}\",\"mesh\":{mesh}}}",
+                escape_json(&name)
+            ));
+        }
+    }
+
+    fn write_model(&mut self, model: &PyModel) {
+        let mut primitives: BTreeMap<Option<usize>, Primitive> = BTreeMap::new();
+
+        for mesh in model.loaded_meshes() {
+            let vertices = mesh.source_vertices();
+
+            for face in mesh.source_faces() {
+                let material = model
+                    .material(face.material_index)
+                    .map(|m| self.material_index(m));
+
+                // face vertices are in opposite winding order compared to glTF
+                let corners = face.vertice_indices.iter().rev().map(|&i| {
+                    let vertex = &vertices[i];
+                    let position = [0, 1, 2].map(|j| vertex.position[j]);
+
+                    (position, [vertex.tex_coord[0], vertex.tex_coord[1]])
+                });
+
+                primitives
+                    .entry(material)
+                    .or_default()
+                    .push_polygon(corners);
+            }
+        }
+
+        if let Some(mesh) = self.push_mesh(&model.name, primitives) {
+            self.model_meshes.insert(model.name.clone(), mesh);
+        }
+    }
+
+    fn write_prop(&mut self, prop: &PyLoadedProp) {
+        let Some(&mesh) = self.model_meshes.get(prop.model()) else {
+            return;
+        };
+
+        let rotation = euler_to_quat(prop.rotation());
+
+        self.nodes.push(format!(
+            "{{\"name\":\"{}_{}\",\"mesh\":{mesh},\"translation\":{},\"rotation\":{},\"scale\":{}}}",
+            escape_json(prop.class_name()),
+            prop.id,
+            json_floats(&prop.position()),
+            json_floats(&rotation.to_array()),
+            json_floats(&prop.scale()),
+        ));
+    }
+
+    fn push_light(
+        &mut self,
+        light: String,
+        name: String,
+        position: [f32; 3],
+        rotation: Option<Quat>,
+    ) {
+        self.lights.push(light);
+
+        let rotation = rotation.map_or_else(String::new, |r| {
+            format!(",\"rotation\":{}", json_floats(&r.to_array()))
+        });
+        self.nodes.push(format!(
+            "{{\"name\":\"{name}\",\"translation\":{}{rotation},\"extensions\":{{\"KHR_lights_punctual\":{{\"light\":{}}}}}}}",
+            json_floats(&position),
+            self.lights.len() - 1
+        ));
+    }
+
+    fn write_light(&mut self, light: &PyLight) {
+        let intensity = light.energy() * LUMENS_PER_WATT / (4.0 * PI);
+
+        self.push_light(
+            format!(
+                "{{\"type\":\"point\",\"color\":{},\"intensity\":{intensity}}}",
+                json_floats(&light.color())
+            ),
+            format!("light_{}", light.id),
+            light.position(),
+            None,
+        );
+    }
+
+    fn write_spot_light(&mut self, light: &PySpotLight) {
+        let intensity = light.energy() * LUMENS_PER_WATT / (4.0 * PI);
+        let outer = light.spot_size() / 2.0;
+        let inner = outer * (1.0 - light.spot_blend());
+
+        // spot lights point towards -z in both Blender and glTF
+        let rotation = euler_to_quat(light.rotation());
+
+        self.push_light(
+            format!(
+                "{{\"type\":\"spot\",\"color\":{},\"intensity\":{intensity},\"spot\":{{\"innerConeAngle\":{inner},\"outerConeAngle\":{outer}}}}}",
+                json_floats(&light.color())
+            ),
+            format!("spot_light_{}", light.id),
+            light.position(),
+            Some(rotation),
+        );
+    }
+
+    fn write_material(&mut self, material: &Material) {
+        if let Some(texture) = material.built_data().and_then(|d| d.base_texture()) {
+            self.base_textures
+                .insert(material.name.clone(), texture.to_owned());
+        }
+    }
+
+    fn write_texture(&mut self, texture: &Texture) {
+        // glTF only supports png and jpeg images
+        if !texture.file_name().ends_with(".png") {
+            return;
+        }
+
+        let view = self.push_buffer_view(texture.data(), None);
+        self.images.insert(texture.name.clone(), view);
+    }
+
+    fn build_json(&mut self, buffer_uri: Option<&str>) -> String {
+        let props = std::mem::take(&mut self.props);
+        for prop in &props {
+            self.write_prop(prop);
+        }
+
+        let mut images = Vec::new();
+        let mut textures = Vec::new();
+        let mut texture_indices = BTreeMap::new();
+        let mut materials = Vec::new();
+
+        for name in &self.material_names {
+            let mut pbr = String::from("\"metallicFactor\":0");
+
+            let image = self
+                .base_textures
+                .get(name)
+                .and_then(|t| Some((t, *self.images.get(t)?)));
+
+            if let Some((texture, view)) = image {
+                let index = *texture_indices.entry(texture.clone()).or_insert_with(|| {
+                    images.push(format!(
+                        "{{\"name\":\"{}\",\"bufferView\":{view},\"mimeType\":\"image/png\"}}",
+                        escape_json(texture)
+                    ));
+                    textures.push(format!("{{\"source\":{}}}", images.len() - 1));
+                    textures.len() - 1
+                });
+
+                pbr.push_str(&format!(",\"baseColorTexture\":{{\"index\":{index}}}"));
+            }
+
+            materials.push(format!(
+                "{{\"name\":\"{}\",\"pbrMetallicRoughness\":{{{pbr}}}}}",
+                escape_json(name)
+            ));
+        }
+
+        let node_indices: Vec<_> = (0..self.nodes.len()).map(|i| i.to_string()).collect();
+
+        let mut fields = vec![
+            "\"asset\":{\"version\":\"2.0\",\"generator\":\"plumber-cli\"}".to_owned(),
+            "\"scene\":0".to_owned(),
+            format!("\"scenes\":[{{\"nodes\":{}}}]", json_array(&node_indices)),
+        ];
+
+        for (name, items) in [
+            ("nodes", &self.nodes),
+            ("meshes", &self.meshes),
+            ("materials", &materials),
+            ("textures", &textures),
+            ("images", &images),
+            ("accessors", &self.accessors),
+            ("bufferViews", &self.buffer_views),
+        ] {
+            if !items.is_empty() {
+                fields.push(format!("\"{name}\":{}", json_array(items)));
+            }
+        }
+
+        if !self.buffer.is_empty() {
+            let uri =
+                buffer_uri.map_or_else(String::new, |u| format!(",\"uri\":\"{}\"", escape_json(u)));
+            fields.push(format!(
+                "\"buffers\":[{{\"byteLength\":{}{uri}}}]",
+                self.buffer.len()
+            ));
+        }
+
+        if !self.lights.is_empty() {
+            fields.push("\"extensionsUsed\":[\"KHR_lights_punctual\"]".to_owned());
+            fields.push(format!(
+                "\"extensions\":{{\"KHR_lights_punctual\":{{\"lights\":{}}}}}",
+                json_array(&self.lights)
+            ));
+        }
+
+        format!("{{{}}}", fields.join(","))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_glb(&mut self) -> io::Result<()> {
+        let mut json = self.build_json(None).into_bytes();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+
+        let mut length = 12 + 8 + json.len();
+        if !self.buffer.is_empty() {
+            length += 8 + self.buffer.len();
+        }
+
+        let mut file = io::BufWriter::new(fs::File::create(&self.path)?);
+
+        file.write_all(GLB_MAGIC)?;
+        file.write_all(&GLB_VERSION.to_le_bytes())?;
+        file.write_all(&(length as u32).to_le_bytes())?;
+
+        file.write_all(&(json.len() as u32).to_le_bytes())?;
+        file.write_all(GLB_CHUNK_JSON)?;
+        file.write_all(&json)?;
+
+        if !self.buffer.is_empty() {
+            file.write_all(&(self.buffer.len() as u32).to_le_bytes())?;
+            file.write_all(GLB_CHUNK_BIN)?;
+            file.write_all(&self.buffer)?;
+        }
+
+        file.flush()
+    }
+
+    fn write_gltf(&mut self) -> io::Result<()> {
+        let bin_path = self.path.with_extension("bin");
+        let bin_name = bin_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let json = self.build_json(Some(&bin_name));
+        fs::write(&self.path, json)?;
+
+        if !self.buffer.is_empty() {
+            fs::write(bin_path, &self.buffer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Output for GltfOutput {
+    fn write_message(&mut self, message: Message) -> io::Result<()> {
+        match message {
+            Message::Brush(brush) => self.write_brush(&brush),
+            Message::Model(model) => self.write_model(&model),
+            // models may arrive after the props using them
+            Message::Prop(prop) => self.props.push(prop),
+            Message::Light(light) => self.write_light(&light),
+            Message::SpotLight(light) => self.write_spot_light(&light),
+            Message::Material(material) => self.write_material(&material),
+            Message::Texture(texture) => self.write_texture(&texture),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        if self.binary {
+            self.write_glb()
+        } else {
+            self.write_gltf()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangulate_polygon() {
+        let mut primitive = Primitive::default();
+        primitive.push_polygon((0..4).map(|i| ([i as f32, 0.0, 0.0], [0.0, 0.0])));
+        primitive.push_polygon((0..3).map(|i| ([i as f32, 1.0, 0.0], [0.0, 0.0])));
+
+        assert_eq!(primitive.indices, [0, 1, 2, 0, 2, 3, 4, 5, 6]);
+    }
+}
@@ -0,0 +1,37 @@
+//! Output formats of the `plumber-cli` binary,
+//! written from the messages of [`BlenderAssetHandler`](crate::asset::BlenderAssetHandler).
+//!
+//! The messages are written as they are sent, in the up axis of the handler settings.
+
+mod gltf;
+mod obj;
+mod usd;
+
+use std::io;
+
+use crossbeam_channel::Receiver;
+use glam::{EulerRot, Quat};
+use tracing::error;
+
+use crate::asset::Message;
+
+pub use self::{gltf::GltfOutput, obj::ObjOutput, usd::UsdOutput};
+
+/// Converts a Blender XYZ euler rotation into a quaternion.
+fn euler_to_quat([x, y, z]: [f32; 3]) -> Quat {
+    Quat::from_euler(EulerRot::ZYX, z, y, x)
+}
+
+pub trait Output {
+    fn write_message(&mut self, message: Message) -> io::Result<()>;
+
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+pub fn write_messages(output: &mut dyn Output, receiver: &Receiver<Message>) {
+    for message in receiver {
+        if let Err(err) = output.write_message(message) {
+            error!("error writing output: {err}");
+        }
+    }
+}
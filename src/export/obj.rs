@@ -0,0 +1,145 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+};
+
+use crate::asset::{
+    brush::PyBuiltBrushEntity,
+    material::{Material, Texture},
+    Message,
+};
+
+use super::Output;
+
+/// Writes brushes into a single OBJ file with an MTL file for their materials,
+/// and textures next to them. The handler has to send y-up assets, which OBJ assumes.
+pub struct ObjOutput {
+    directory: StdPathBuf,
+    obj: BufWriter<File>,
+    vertex_count: usize,
+    uv_count: usize,
+    used_materials: BTreeSet<String>,
+    base_textures: BTreeMap<String, String>,
+    textures: BTreeMap<String, String>,
+}
+
+impl ObjOutput {
+    pub fn create(directory: &StdPath) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let mut obj = BufWriter::new(File::create(directory.join("map.obj"))?);
+        writeln!(obj, "mtllib map.mtl")?;
+
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            obj,
+            vertex_count: 0,
+            uv_count: 0,
+            used_materials: BTreeSet::new(),
+            base_textures: BTreeMap::new(),
+            textures: BTreeMap::new(),
+        })
+    }
+
+    fn write_brush(&mut self, brush: &PyBuiltBrushEntity) -> io::Result<()> {
+        writeln!(self.obj, "o {}_{}", brush.class_name(), brush.id)?;
+
+        for mesh in brush.meshes().filter(|m| !m.no_draw) {
+            for vertex in mesh.vertices.chunks_exact(3) {
+                let [x, y, z] = [0, 1, 2].map(|i| mesh.position[i] + vertex[i] * mesh.scale[i]);
+                writeln!(self.obj, "v {x} {y} {z}")?;
+            }
+
+            for face in mesh.faces {
+                for uv in &face.vertice_uvs {
+                    // obj has inverted v axis compared to Source
+                    writeln!(self.obj, "vt {} {}", uv.x, 1.0 - uv.y)?;
+                }
+
+                if let Some(material) = mesh.materials.get(face.material_index) {
+                    writeln!(self.obj, "usemtl {material}")?;
+
+                    if !self.used_materials.contains(material) {
+                        self.used_materials.insert(material.clone());
+                    }
+                }
+
+                write!(self.obj, "f")?;
+                for (i, vertex_index) in face.vertice_indices.iter().enumerate() {
+                    let v = self.vertex_count + vertex_index + 1;
+                    let vt = self.uv_count + i + 1;
+                    write!(self.obj, " {v}/{vt}")?;
+                }
+                writeln!(self.obj)?;
+
+                self.uv_count += face.vertice_uvs.len();
+            }
+
+            self.vertex_count += mesh.vertices.len() / 3;
+        }
+
+        Ok(())
+    }
+
+    fn write_material(&mut self, material: &Material) {
+        if let Some(texture) = material.built_data().and_then(|d| d.base_texture()) {
+            self.base_textures
+                .insert(material.name.clone(), texture.to_owned());
+        }
+    }
+
+    fn write_texture(&mut self, texture: &Texture) -> io::Result<()> {
+        let file_name = format!("textures/{}", texture.file_name());
+        let path = self.directory.join(&file_name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, texture.data())?;
+        self.textures.insert(texture.name.clone(), file_name);
+
+        Ok(())
+    }
+
+    fn write_mtl(&self) -> io::Result<()> {
+        let mut mtl = BufWriter::new(File::create(self.directory.join("map.mtl"))?);
+
+        for material in &self.used_materials {
+            writeln!(mtl, "newmtl {material}")?;
+            writeln!(mtl, "Kd 1 1 1")?;
+
+            if let Some(texture) = self
+                .base_textures
+                .get(material)
+                .and_then(|t| self.textures.get(t))
+            {
+                writeln!(mtl, "map_Kd {texture}")?;
+            }
+
+            writeln!(mtl)?;
+        }
+
+        mtl.flush()
+    }
+}
+
+impl Output for ObjOutput {
+    fn write_message(&mut self, message: Message) -> io::Result<()> {
+        match message {
+            Message::Brush(brush) => self.write_brush(&brush),
+            Message::Material(material) => {
+                self.write_material(&material);
+                Ok(())
+            }
+            Message::Texture(texture) => self.write_texture(&texture),
+            _ => Ok(()),
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.obj.flush()?;
+        self.write_mtl()
+    }
+}
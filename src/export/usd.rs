@@ -0,0 +1,622 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f32::consts::PI,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+};
+
+use glam::Quat;
+
+use crate::asset::{
+    brush::PyBuiltBrushEntity,
+    entities::{PyLight, PyLoadedProp, PySpotLight},
+    material::{Material, Texture},
+    model::PyModel,
+    up_axis::UpAxis,
+    Message,
+};
+
+use super::{euler_to_quat, Output};
+
+/// Source units are inches.
+const METERS_PER_SOURCE_UNIT: f32 = 0.0254;
+
+/// Converts arbitrary names into valid, unique prim names.
+#[derive(Default)]
+struct PrimNames {
+    names: BTreeMap<String, String>,
+    used: BTreeSet<String>,
+}
+
+impl PrimNames {
+    fn get(&mut self, name: &str) -> String {
+        if let Some(prim_name) = self.names.get(name) {
+            return prim_name.clone();
+        }
+
+        let mut base: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            base.insert(0, '_');
+        }
+
+        let mut prim_name = base.clone();
+        let mut counter = 1;
+
+        while self.used.contains(&prim_name) {
+            prim_name = format!("{base}_{counter}");
+            counter += 1;
+        }
+
+        self.used.insert(prim_name.clone());
+        self.names.insert(name.to_owned(), prim_name.clone());
+        prim_name
+    }
+}
+
+/// Polygons of a mesh prim, with the faces grouped by material.
+#[derive(Default)]
+struct MeshData {
+    points: Vec<[f32; 3]>,
+    face_vertex_counts: Vec<usize>,
+    face_vertex_indices: Vec<usize>,
+    uvs: Vec<[f32; 2]>,
+    subsets: BTreeMap<String, Vec<usize>>,
+}
+
+impl MeshData {
+    fn push_face(
+        &mut self,
+        material: Option<&str>,
+        corners: impl IntoIterator<Item = (usize, [f32; 2])>,
+    ) {
+        let face_index = self.face_vertex_counts.len();
+        let start = self.face_vertex_indices.len();
+
+        for (index, uv) in corners {
+            self.face_vertex_indices.push(index);
+            self.uvs.push(uv);
+        }
+
+        self.face_vertex_counts
+            .push(self.face_vertex_indices.len() - start);
+
+        if let Some(material) = material {
+            self.subsets
+                .entry(material.to_owned())
+                .or_default()
+                .push(face_index);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.face_vertex_counts.is_empty()
+    }
+}
+
+fn usd_array<T>(values: &[T], format: impl Fn(&T) -> String) -> String {
+    let values: Vec<_> = values.iter().map(format).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn usd_vec3([x, y, z]: [f32; 3]) -> String {
+    format!("({x}, {y}, {z})")
+}
+
+fn usd_quat(quat: Quat) -> String {
+    let [x, y, z, w] = quat.to_array();
+    format!("({w}, {x}, {y}, {z})")
+}
+
+fn write_transform(
+    out: &mut String,
+    position: [f32; 3],
+    rotation: Option<Quat>,
+    scale: Option<[f32; 3]>,
+) {
+    let mut order = vec!["\"xformOp:translate\""];
+
+    let _ = writeln!(
+        out,
+        "            double3 xformOp:translate = {}",
+        usd_vec3(position)
+    );
+
+    if let Some(rotation) = rotation {
+        let _ = writeln!(
+            out,
+            "            quatf xformOp:orient = {}",
+            usd_quat(rotation)
+        );
+        order.push("\"xformOp:orient\"");
+    }
+
+    if let Some(scale) = scale {
+        let _ = writeln!(
+            out,
+            "            float3 xformOp:scale = {}",
+            usd_vec3(scale)
+        );
+        order.push("\"xformOp:scale\"");
+    }
+
+    let _ = writeln!(
+        out,
+        "            uniform token[] xformOpOrder = [{}]",
+        order.join(", ")
+    );
+}
+
+/// Writes brushes, instanced props, lights and materials into a `.usda` file,
+/// with the textures in a `textures` directory next to it.
+pub struct UsdOutput {
+    path: StdPathBuf,
+    directory: StdPathBuf,
+    scale: f32,
+    /// Up axis the handler sends the assets in, which the stage is written with.
+    up_axis: UpAxis,
+    names: PrimNames,
+    brushes: String,
+    prototypes: String,
+    lights: String,
+    materials: BTreeSet<String>,
+    base_textures: BTreeMap<String, String>,
+    textures: BTreeMap<String, String>,
+    models: BTreeSet<String>,
+    props: Vec<PyLoadedProp>,
+}
+
+impl UsdOutput {
+    pub fn create(path: &StdPath, scale: f32, up_axis: UpAxis) -> io::Result<Self> {
+        let directory = path.parent().unwrap_or(StdPath::new("")).to_path_buf();
+        fs::create_dir_all(&directory)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            directory,
+            scale,
+            up_axis,
+            names: PrimNames::default(),
+            brushes: String::new(),
+            prototypes: String::new(),
+            lights: String::new(),
+            materials: BTreeSet::new(),
+            base_textures: BTreeMap::new(),
+            textures: BTreeMap::new(),
+            models: BTreeSet::new(),
+            props: Vec::new(),
+        })
+    }
+
+    /// Writes a mesh prim, binding the face subsets to materials under `material_root`.
+    fn write_mesh(&mut self, out: &mut String, name: &str, mesh: &MeshData, material_root: &str) {
+        let indent = "        ";
+
+        let _ = writeln!(out, "{indent}def Mesh \"{name}\"");
+        let _ = writeln!(out, "{indent}{{");
+        let _ = writeln!(
+            out,
+            "{indent}    point3f[] points = {}",
+            usd_array(&mesh.points, |p| usd_vec3(*p))
+        );
+        let _ = writeln!(
+            out,
+            "{indent}    int[] faceVertexCounts = {}",
+            usd_array(&mesh.face_vertex_counts, ToString::to_string)
+        );
+        let _ = writeln!(
+            out,
+            "{indent}    int[] faceVertexIndices = {}",
+            usd_array(&mesh.face_vertex_indices, ToString::to_string)
+        );
+        let _ = writeln!(
+            out,
+            "{indent}    texCoord2f[] primvars:st = {} (",
+            usd_array(&mesh.uvs, |[u, v]| format!("({u}, {v})"))
+        );
+        let _ = writeln!(out, "{indent}        interpolation = \"faceVarying\"");
+        let _ = writeln!(out, "{indent}    )");
+        let _ = writeln!(
+            out,
+            "{indent}    uniform token subdivisionScheme = \"none\""
+        );
+
+        for (material, faces) in &mesh.subsets {
+            let material = self.material_prim(material);
+
+            let _ = writeln!(out);
+            let _ = writeln!(
+                out,
+                "{indent}    def GeomSubset \"{material}\" (\n{indent}        prepend apiSchemas = [\"MaterialBindingAPI\"]\n{indent}    )"
+            );
+            let _ = writeln!(out, "{indent}    {{");
+            let _ = writeln!(out, "{indent}        uniform token elementType = \"face\"");
+            let _ = writeln!(
+                out,
+                "{indent}        uniform token familyName = \"materialBind\""
+            );
+            let _ = writeln!(
+                out,
+                "{indent}        int[] indices = {}",
+                usd_array(faces, ToString::to_string)
+            );
+            let _ = writeln!(
+                out,
+                "{indent}        rel material:binding = <{material_root}/{material}>"
+            );
+            let _ = writeln!(out, "{indent}    }}");
+        }
+
+        let _ = writeln!(out, "{indent}}}");
+    }
+
+    fn material_prim(&mut self, material: &str) -> String {
+        let prim = self.names.get(material);
+        self.materials.insert(material.to_owned());
+        prim
+    }
+
+    fn write_brush(&mut self, brush: &PyBuiltBrushEntity) {
+        let mut data = MeshData::default();
+
+        for mesh in brush.meshes().filter(|m| !m.no_draw) {
+            let offset = data.points.len();
+
+            data.points.extend(
+                mesh.vertices
+                    .chunks_exact(3)
+                    .map(|v| [0, 1, 2].map(|i| mesh.position[i] + v[i] * mesh.scale[i])),
+            );
+
+            for face in mesh.faces {
+                data.push_face(
+                    mesh.materials.get(face.material_index).map(String::as_str),
+                    face.vertice_indices
+                        .iter()
+                        .zip(&face.vertice_uvs)
+                        // usd has inverted v axis compared to Source
+                        .map(|(&i, uv)| (offset + i, [uv.x, 1.0 - uv.y])),
+                );
+            }
+        }
+
+        if data.is_empty() {
+            return;
+        }
+
+        let name = self
+            .names
+            .get(&format!("{}_{}", brush.class_name(), brush.id));
+
+        let mut out = String::new();
+        self.write_mesh(&mut out, &name, &data, "/Map/Materials");
+        self.brushes.push_str(&out);
+    }
+
+    fn write_model(&mut self, model: &PyModel) {
+        let prototype = self.names.get(&model.name);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "    def Xform \"{prototype}\"");
+        let _ = writeln!(out, "    {{");
+
+        let mut used_materials = BTreeSet::new();
+
+        for mesh in model.loaded_meshes() {
+            let mut data = MeshData::default();
+
+            data.points.extend(
+                mesh.source_vertices()
+                    .iter()
+                    .map(|v| [0, 1, 2].map(|i| v.position[i])),
+            );
+
+            for face in mesh.source_faces() {
+                let material = model.material(face.material_index);
+                used_materials.extend(material);
+
+                // face vertices are in opposite winding order compared to usd
+                data.push_face(
+                    material,
+                    face.vertice_indices.iter().rev().map(|&i| {
+                        let uv = mesh.source_vertices()[i].tex_coord;
+                        (i, [uv[0], 1.0 - uv[1]])
+                    }),
+                );
+            }
+
+            if !data.is_empty() {
+                let name = self.names.get(&format!("{}_{}", model.name, mesh.name()));
+                self.write_mesh(
+                    &mut out,
+                    &name,
+                    &data,
+                    &format!("/Prototypes/{prototype}/Materials"),
+                );
+            }
+        }
+
+        // bindings can't point outside of a referenced prim,
+        // so the materials are referenced into the prototype
+        let _ = writeln!(out, "        def Scope \"Materials\"");
+        let _ = writeln!(out, "        {{");
+        for material in used_materials {
+            let prim = self.material_prim(material);
+            let _ = writeln!(
+                out,
+                "            def Material \"{prim}\" (\n                prepend references = </Map/Materials/{prim}>\n            )\n            {{\n            }}"
+            );
+        }
+        let _ = writeln!(out, "        }}");
+        let _ = writeln!(out, "    }}");
+
+        self.prototypes.push_str(&out);
+        self.models.insert(model.name.clone());
+    }
+
+    fn write_prop(&mut self, out: &mut String, prop: &PyLoadedProp) {
+        if !self.models.contains(prop.model()) {
+            return;
+        }
+
+        let prototype = self.names.get(prop.model());
+        let name = self
+            .names
+            .get(&format!("{}_{}", prop.class_name(), prop.id));
+
+        let _ = writeln!(
+            out,
+            "        def Xform \"{name}\" (\n            instanceable = true\n            prepend references = </Prototypes/{prototype}>\n        )"
+        );
+        let _ = writeln!(out, "        {{");
+        write_transform(
+            out,
+            prop.position(),
+            Some(euler_to_quat(prop.rotation())),
+            Some(prop.scale()),
+        );
+        let _ = writeln!(out, "        }}");
+    }
+
+    fn write_light(&mut self, light: &PyLight) {
+        let name = self.names.get(&format!("light_{}", light.id));
+        let mut out = String::new();
+
+        let _ = writeln!(out, "        def SphereLight \"{name}\"");
+        let _ = writeln!(out, "        {{");
+        let _ = writeln!(
+            out,
+            "            color3f inputs:color = {}",
+            usd_vec3(light.color())
+        );
+        // Blender watts spread over the sphere
+        let _ = writeln!(
+            out,
+            "            float inputs:intensity = {}",
+            light.energy() / (4.0 * PI)
+        );
+        let _ = writeln!(out, "            bool treatAsPoint = true");
+        write_transform(&mut out, light.position(), None, None);
+        let _ = writeln!(out, "        }}");
+
+        self.lights.push_str(&out);
+    }
+
+    fn write_spot_light(&mut self, light: &PySpotLight) {
+        let name = self.names.get(&format!("spot_light_{}", light.id));
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "        def SphereLight \"{name}\" (\n            prepend apiSchemas = [\"ShapingAPI\"]\n        )"
+        );
+        let _ = writeln!(out, "        {{");
+        let _ = writeln!(
+            out,
+            "            color3f inputs:color = {}",
+            usd_vec3(light.color())
+        );
+        let _ = writeln!(
+            out,
+            "            float inputs:intensity = {}",
+            light.energy() / (4.0 * PI)
+        );
+        let _ = writeln!(
+            out,
+            "            float inputs:shaping:cone:angle = {}",
+            (light.spot_size() / 2.0).to_degrees()
+        );
+        let _ = writeln!(
+            out,
+            "            float inputs:shaping:cone:softness = {}",
+            light.spot_blend()
+        );
+        let _ = writeln!(out, "            bool treatAsPoint = true");
+        // lights point towards -z in both Blender and usd
+        let rotation = euler_to_quat(light.rotation());
+        write_transform(&mut out, light.position(), Some(rotation), None);
+        let _ = writeln!(out, "        }}");
+
+        self.lights.push_str(&out);
+    }
+
+    fn write_material(&mut self, material: &Material) {
+        if let Some(texture) = material.built_data().and_then(|d| d.base_texture()) {
+            self.base_textures
+                .insert(material.name.clone(), texture.to_owned());
+        }
+    }
+
+    fn write_texture(&mut self, texture: &Texture) -> io::Result<()> {
+        let file_name = format!("textures/{}", texture.file_name());
+        let path = self.directory.join(&file_name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, texture.data())?;
+        self.textures.insert(texture.name.clone(), file_name);
+
+        Ok(())
+    }
+
+    fn write_materials(&self, out: &mut String) {
+        for material in &self.materials {
+            let prim = self.names.names[material].clone();
+            let root = format!("/Map/Materials/{prim}");
+
+            let texture = self
+                .base_textures
+                .get(material)
+                .and_then(|t| self.textures.get(t));
+
+            let _ = writeln!(out, "        def Material \"{prim}\"");
+            let _ = writeln!(out, "        {{");
+            let _ = writeln!(
+                out,
+                "            token outputs:surface.connect = <{root}/Surface.outputs:surface>"
+            );
+            let _ = writeln!(out);
+            let _ = writeln!(out, "            def Shader \"Surface\"");
+            let _ = writeln!(out, "            {{");
+            let _ = writeln!(
+                out,
+                "                uniform token info:id = \"UsdPreviewSurface\""
+            );
+            let _ = writeln!(out, "                float inputs:metallic = 0");
+            if texture.is_some() {
+                let _ = writeln!(
+                    out,
+                    "                color3f inputs:diffuseColor.connect = <{root}/Texture.outputs:rgb>"
+                );
+            }
+            let _ = writeln!(out, "                token outputs:surface");
+            let _ = writeln!(out, "            }}");
+
+            if let Some(texture) = texture {
+                let _ = writeln!(out);
+                let _ = writeln!(out, "            def Shader \"Texture\"");
+                let _ = writeln!(out, "            {{");
+                let _ = writeln!(
+                    out,
+                    "                uniform token info:id = \"UsdUVTexture\""
+                );
+                let _ = writeln!(out, "                asset inputs:file = @./{texture}@");
+                let _ = writeln!(
+                    out,
+                    "                float2 inputs:st.connect = <{root}/UvReader.outputs:result>"
+                );
+                let _ = writeln!(out, "                float3 outputs:rgb");
+                let _ = writeln!(out, "            }}");
+                let _ = writeln!(out);
+                let _ = writeln!(out, "            def Shader \"UvReader\"");
+                let _ = writeln!(out, "            {{");
+                let _ = writeln!(
+                    out,
+                    "                uniform token info:id = \"UsdPrimvarReader_float2\""
+                );
+                let _ = writeln!(out, "                string inputs:varname = \"st\"");
+                let _ = writeln!(out, "                float2 outputs:result");
+                let _ = writeln!(out, "            }}");
+            }
+
+            let _ = writeln!(out, "        }}");
+        }
+    }
+
+    fn build_usda(&mut self) -> String {
+        let mut props = String::new();
+        for prop in std::mem::take(&mut self.props) {
+            self.write_prop(&mut props, &prop);
+        }
+
+        let mut materials = String::new();
+        self.write_materials(&mut materials);
+
+        let mut out = String::from("#usda 1.0\n(\n    defaultPrim = \"Map\"\n");
+        let _ = writeln!(
+            out,
+            "    metersPerUnit = {}",
+            METERS_PER_SOURCE_UNIT / self.scale
+        );
+        let up_axis = match self.up_axis {
+            UpAxis::Z => "Z",
+            UpAxis::Y => "Y",
+        };
+        let _ = write!(out, "    upAxis = \"{up_axis}\"\n)\n\n");
+
+        out.push_str("def Xform \"Map\"\n{\n");
+
+        for (name, kind, content) in [
+            ("Materials", "Scope", &materials),
+            ("Brushes", "Xform", &self.brushes),
+            ("Props", "Xform", &props),
+            ("Lights", "Xform", &self.lights),
+        ] {
+            let _ = writeln!(out, "    def {kind} \"{name}\"\n    {{\n{content}    }}\n");
+        }
+
+        out.push_str("}\n\n");
+
+        // prototypes are only referenced by the props, so they aren't drawn themselves
+        let _ = writeln!(out, "class \"Prototypes\"\n{{\n{}}}", self.prototypes);
+
+        out
+    }
+}
+
+impl Output for UsdOutput {
+    fn write_message(&mut self, message: Message) -> io::Result<()> {
+        match message {
+            Message::Brush(brush) => self.write_brush(&brush),
+            Message::Model(model) => self.write_model(&model),
+            // models may arrive after the props using them
+            Message::Prop(prop) => self.props.push(prop),
+            Message::Light(light) => self.write_light(&light),
+            Message::SpotLight(light) => self.write_spot_light(&light),
+            Message::Material(material) => self.write_material(&material),
+            Message::Texture(texture) => return self.write_texture(&texture),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        let usda = self.build_usda();
+
+        let mut file = io::BufWriter::new(fs::File::create(&self.path)?);
+        file.write_all(usda.as_bytes())?;
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_prim_names() {
+        let mut names = PrimNames::default();
+
+        assert_eq!(
+            names.get("materials/concrete/wall-01"),
+            "materials_concrete_wall_01"
+        );
+        assert_eq!(
+            names.get("materials/concrete/wall_01"),
+            "materials_concrete_wall_01_1"
+        );
+        assert_eq!(
+            names.get("materials/concrete/wall-01"),
+            "materials_concrete_wall_01"
+        );
+        assert_eq!(names.get("12"), "_12");
+    }
+}
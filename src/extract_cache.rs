@@ -0,0 +1,124 @@
+//! Extraction of archives which the file system can't mount, so they are mounted as plain
+//! directories instead, and caching of the extracted files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, Read, Write},
+    path::{Component, Path as StdPath, PathBuf as StdPathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Written into an extraction directory after all files are extracted.
+const COMPLETE_MARKER: &str = ".plumber_complete";
+
+/// Extracts the archive at `path` into a subdirectory of `cache_directory` using `extract`,
+/// and returns the subdirectory.
+/// Extraction is skipped if the archive was already extracted and hasn't changed since.
+pub fn extract_cached<E>(
+    path: &StdPath,
+    cache_directory: &StdPath,
+    io_error: impl Fn(io::Error) -> E,
+    extract: impl FnOnce(&StdPath) -> Result<(), E>,
+) -> Result<StdPathBuf, E> {
+    let metadata = fs::metadata(path).map_err(&io_error)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "archive".into(), |s| s.to_string_lossy());
+
+    let target = cache_directory.join(format!("{}-{}-{}", stem, metadata.len(), modified));
+
+    if target.join(COMPLETE_MARKER).is_file() {
+        return Ok(target);
+    }
+
+    // remove files from a possible interrupted extraction
+    if target.exists() {
+        fs::remove_dir_all(&target).map_err(&io_error)?;
+    }
+
+    extract(&target)?;
+    File::create(target.join(COMPLETE_MARKER)).map_err(&io_error)?;
+
+    Ok(target)
+}
+
+/// An archive whose files are extracted into a directory.
+pub trait Archive {
+    type Entry;
+    type Error;
+
+    fn entries(&self) -> &[Self::Entry];
+
+    /// Path of the entry inside the archive, which is kept when it's extracted.
+    fn entry_path(entry: &Self::Entry) -> &str;
+
+    fn copy_entry(&self, entry: &Self::Entry, writer: &mut impl Write) -> Result<(), Self::Error>;
+
+    fn io_error(&self, err: io::Error) -> Self::Error;
+
+    fn invalid_entry_path(&self, entry: &Self::Entry) -> Self::Error;
+
+    /// Extracts all files of the archive into `directory`, keeping their paths.
+    fn extract(&self, directory: &StdPath) -> Result<(), Self::Error> {
+        for entry in self.entries() {
+            let path = contained_path(Self::entry_path(entry))
+                .ok_or_else(|| self.invalid_entry_path(entry))?;
+            let target = directory.join(path);
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| self.io_error(e))?;
+            }
+
+            let mut file = File::create(&target).map_err(|e| self.io_error(e))?;
+            self.copy_entry(entry, &mut file)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the path if it stays inside the directory it's joined onto,
+/// so it's neither absolute nor has `..` components.
+pub fn contained_path(path: &str) -> Option<&StdPath> {
+    let path = StdPath::new(path);
+
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        .then_some(path)
+}
+
+pub fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads a null terminated string.
+pub fn read_string(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    reader.read_until(0, &mut bytes)?;
+
+    if bytes.pop() != Some(0) {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_escaping_paths() {
+        assert!(contained_path("materials/./test.vmt").is_some());
+        assert!(contained_path("../outside.txt").is_none());
+        assert!(contained_path("materials/../../outside.txt").is_none());
+        assert!(contained_path("/outside.txt").is_none());
+    }
+}
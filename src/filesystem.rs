@@ -1,7 +1,7 @@
 use std::{
     cmp::Ordering,
     fs::{self, File},
-    io::{BufRead, BufReader, Read, Write},
+    io::{self, BufRead, BufReader, Read, Write},
     path::{Path as StdPath, PathBuf as StdPathBuf},
     time::Instant,
 };
@@ -14,12 +14,23 @@ use tracing::{error, info, warn};
 
 use plumber_core::{
     fs::{
-        DirEntryType, FileSystem, GameFile, GamePathBuf, OpenFileSystem, ReadDir, SearchPath,
-        SourceAppsExt,
+        DirEntryType, FileSystem, GameFile, GamePathBuf, OpenFileSystem, PathBuf, ReadDir,
+        SearchPath, SourceAppsExt,
     },
     steam::Libraries,
 };
 
+use crate::{
+    asset::{
+        vmf_entities::VmfEntities,
+        vmf_load::{load_vmf_bytes, MapFormat, VmfLoadSettings},
+        vmf_metadata::VmfMetadata,
+    },
+    gma,
+    manifest::Manifest,
+    vpk,
+};
+
 #[pyclass(module = "plumber", name = "FileSystem")]
 pub struct PyFileSystem {
     pub file_system: FileSystem,
@@ -74,6 +85,43 @@ impl PyFileSystem {
             .collect()
     }
 
+    /// Returns the search paths in priority order with wildcards expanded
+    /// into the directories and vpk archives they match.
+    fn resolved_search_paths(&self) -> PyResult<Vec<(String, String)>> {
+        resolve_search_paths(&self.file_system.search_paths)
+            .iter()
+            .map(|path| {
+                from_search_path(path).map(|(kind, path)| (kind.to_owned(), path.to_owned()))
+            })
+            .collect()
+    }
+
+    /// Returns every resolved search path containing the file at `path`, in priority order.
+    /// The first one is the file that imports use, the rest are overridden by it.
+    fn find_file(&self, path: &str) -> PyResult<Vec<(String, String)>> {
+        let path = GamePathBuf::from(path);
+        let mut found = Vec::new();
+
+        for search_path in resolve_search_paths(&self.file_system.search_paths) {
+            let single = FileSystem {
+                name: self.file_system.name.clone(),
+                search_paths: vec![search_path],
+            };
+
+            // search paths which fail to open are reported by the file system when importing
+            let Ok(opened) = single.open() else {
+                continue;
+            };
+
+            if opened.open_file(&path).is_ok() {
+                let (kind, path) = from_search_path(&single.search_paths[0])?;
+                found.push((kind.to_owned(), path.to_owned()));
+            }
+        }
+
+        Ok(found)
+    }
+
     fn with_search_path(&self, search_path: (&str, &str)) -> PyResult<Self> {
         let path = to_search_path(search_path)?;
 
@@ -82,6 +130,45 @@ impl PyFileSystem {
         })
     }
 
+    /// Reorders the search paths so that loose files override files in vpk archives
+    /// (`LOOSE`) or the other way around (`ARCHIVES`). `DEFINED` keeps the order as is.
+    fn with_file_priority(&self, priority: &str) -> PyResult<Self> {
+        let mut file_system = self.file_system.with_search_paths(Vec::new());
+
+        let archives_first = match priority {
+            "DEFINED" => return Ok(file_system.into()),
+            "LOOSE" => false,
+            "ARCHIVES" => true,
+            _ => return Err(PyValueError::new_err("invalid file priority enum value")),
+        };
+
+        // the sort is stable, so the order is kept within loose files and archives
+        file_system
+            .search_paths
+            .sort_by_key(|path| matches!(path, SearchPath::Vpk(_)) != archives_first);
+
+        Ok(file_system.into())
+    }
+
+    /// Mounts the subscribed items in a workshop content directory
+    /// below the search paths of this file system.
+    fn with_workshop(&self, directory: &str) -> PyResult<Self> {
+        let search_paths = workshop_search_paths(StdPath::new(directory))
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        Ok(Self {
+            file_system: self.file_system.with_search_paths(search_paths),
+        })
+    }
+
+    /// Mounts the search paths of `other` below the ones of this file system,
+    /// so files in this file system take priority.
+    fn mount(&self, other: &PyFileSystem) -> Self {
+        let mut file_system = self.file_system.with_search_paths(Vec::new());
+        mount(&mut file_system, &other.file_system);
+        file_system.into()
+    }
+
     fn browse(&self) -> PyResult<PyFileBrowser> {
         let opened = self
             .file_system
@@ -99,19 +186,7 @@ impl PyFileSystem {
             .open()
             .map_err(|e| PyIOError::new_err(e.to_string()))?;
 
-        let path = GamePathBuf::from(path);
-        let file = opened
-            .open_file(&path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
-
-        let mut reader = BufReader::new(file);
-        let mut content = String::new();
-
-        reader
-            .read_to_string(&mut content)
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
-
-        Ok(content)
+        read_file_text(&opened, path)
     }
 
     fn read_file_bytes(&self, path: &str) -> PyResult<Vec<u8>> {
@@ -120,19 +195,7 @@ impl PyFileSystem {
             .open()
             .map_err(|e| PyIOError::new_err(e.to_string()))?;
 
-        let path = GamePathBuf::from(path);
-        let file = opened
-            .open_file(&path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
-
-        let mut reader = BufReader::new(file);
-        let mut content = Vec::new();
-
-        reader
-            .read_to_end(&mut content)
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
-
-        Ok(content)
+        read_file_bytes(&opened, path)
     }
 
     fn file_exists(&self, path: &str) -> PyResult<bool> {
@@ -183,6 +246,41 @@ impl PyFileSystem {
 
         Ok(())
     }
+
+    /// Returns a json manifest of the files referenced by a vmf, without importing it.
+    fn vmf_manifest(&self, path: &str, from_game: bool) -> PyResult<String> {
+        let opened = self
+            .file_system
+            .open()
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        let format = MapFormat::from_path(path);
+
+        let path: PathBuf = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let bytes = opened.read(&path)?;
+        let (vmf, _) = load_vmf_bytes(
+            &bytes,
+            format,
+            &VmfLoadSettings::default(),
+            &VmfMetadata::default(),
+            &VmfEntities::default(),
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        let manifest = Manifest::new(&vmf, &opened);
+        info!(
+            "vmf `{}` references {} missing files",
+            path,
+            manifest.missing_count()
+        );
+
+        Ok(manifest.to_json())
+    }
 }
 
 fn extract_file(file: GameFile, file_path: &str, target_path: &StdPath) -> PyResult<()> {
@@ -259,8 +357,29 @@ fn to_search_path(search_path: (&str, &str)) -> PyResult<SearchPath> {
 
     match kind {
         "DIR" => Ok(SearchPath::Directory(StdPathBuf::from(path))),
-        "VPK" => Ok(SearchPath::Vpk(StdPathBuf::from(path))),
+        "VPK" => {
+            let path = StdPath::new(path);
+
+            if !vpk::needs_extraction(path) {
+                return Ok(SearchPath::Vpk(path.to_path_buf()));
+            }
+
+            // archives the file system can't read are mounted from a cached extraction
+            let cache_directory = std::env::temp_dir().join("plumber_vpk");
+
+            vpk::extract_cached(path, &cache_directory)
+                .map(SearchPath::Directory)
+                .map_err(|e| PyIOError::new_err(e.to_string()))
+        }
         "WILDCARD" => Ok(SearchPath::Wildcard(StdPathBuf::from(path))),
+        "GMA" => {
+            // addons are mounted from a cached extraction
+            let cache_directory = std::env::temp_dir().join("plumber_gma");
+
+            gma::extract_cached(StdPath::new(path), &cache_directory)
+                .map(SearchPath::Directory)
+                .map_err(|e| PyIOError::new_err(e.to_string()))
+        }
         _ => Err(PyTypeError::new_err("invalid search path enum value")),
     }
 }
@@ -274,6 +393,102 @@ fn from_search_path(search_path: &SearchPath) -> PyResult<(&str, &str)> {
     .ok_or_else(|| PyUnicodeDecodeError::new_err("search path is not valid utf8"))
 }
 
+/// Expands wildcard search paths into the directories and vpk archives they match,
+/// sorted by name like the game does. Numbered vpk archive parts are skipped.
+fn resolve_search_paths(search_paths: &[SearchPath]) -> Vec<SearchPath> {
+    let mut resolved = Vec::new();
+
+    for search_path in search_paths {
+        let SearchPath::Wildcard(directory) = search_path else {
+            resolved.push(search_path.clone());
+            continue;
+        };
+
+        let Ok(entries) = fs::read_dir(directory) else {
+            continue;
+        };
+
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        paths.sort();
+
+        for path in paths {
+            if path.is_dir() {
+                resolved.push(SearchPath::Directory(path));
+            } else if is_mountable_vpk(&path) {
+                resolved.push(SearchPath::Vpk(path));
+            }
+        }
+    }
+
+    resolved
+}
+
+/// Returns if `path` is a vpk that can be mounted by itself,
+/// meaning a `_dir.vpk` or a single file vpk, but not a numbered part such as `pak01_003.vpk`.
+fn is_mountable_vpk(path: &StdPath) -> bool {
+    let is_vpk = path
+        .extension()
+        .map_or(false, |e| e.eq_ignore_ascii_case("vpk"));
+
+    let is_part = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.rsplit_once('_'))
+        .map_or(false, |(_, suffix)| {
+            suffix.len() == 3 && suffix.bytes().all(|b| b.is_ascii_digit())
+        });
+
+    is_vpk && !is_part
+}
+
+/// Lists the search paths of the items in a workshop content directory.
+/// Each item directory is mounted as is, and `.gma` addons inside them are extracted and mounted.
+fn workshop_search_paths(directory: &StdPath) -> io::Result<Vec<SearchPath>> {
+    let mut items = fs::read_dir(directory)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    items.retain(|path| path.is_dir());
+    items.sort();
+
+    let cache_directory = std::env::temp_dir().join("plumber_gma");
+    let mut search_paths = Vec::new();
+
+    for item in items {
+        for entry in fs::read_dir(&item)? {
+            let path = entry?.path();
+
+            if path
+                .extension()
+                .map_or(false, |e| e.eq_ignore_ascii_case("gma"))
+            {
+                match gma::extract_cached(&path, &cache_directory) {
+                    Ok(extracted) => search_paths.push(SearchPath::Directory(extracted)),
+                    Err(e) => warn!("could not mount workshop addon: {}", e),
+                }
+            }
+        }
+
+        search_paths.push(SearchPath::Directory(item));
+    }
+
+    info!(
+        "found {} workshop search paths in `{}`",
+        search_paths.len(),
+        directory.display()
+    );
+
+    Ok(search_paths)
+}
+
+/// Appends the search paths of `other` which `file_system` doesn't already have.
+fn mount(file_system: &mut FileSystem, other: &FileSystem) {
+    for search_path in &other.search_paths {
+        if !file_system.search_paths.contains(search_path) {
+            file_system.search_paths.push(search_path.clone());
+        }
+    }
+}
+
 pub fn discover() -> Vec<PyFileSystem> {
     let libraries = match Libraries::discover() {
         Ok(libraries) => libraries,
@@ -297,17 +512,130 @@ pub fn discover() -> Vec<PyFileSystem> {
         .collect()
 }
 
+/// Lists the installed Source games along with their Steam app ids.
+pub fn discover_games() -> Vec<PyGame> {
+    let libraries = match Libraries::discover() {
+        Ok(libraries) => libraries,
+        Err(err) => {
+            error!("could not discover games: {}", err);
+            return Vec::new();
+        }
+    };
+
+    libraries
+        .apps()
+        .source()
+        .filter_map(|r| match r {
+            Ok(app) => match FileSystem::from_app(&app) {
+                Ok(file_system) => Some(PyGame::new(app.app_id, file_system)),
+                Err(e) => {
+                    warn!("could not discover game `{}`: {}", app.name, e);
+                    None
+                }
+            },
+            Err(e) => {
+                warn!("could not discover a game: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
 pub fn from_gameinfo(path: &str) -> PyResult<PyFileSystem> {
+    file_system_from_gameinfo(path).map(PyFileSystem::from)
+}
+
+/// Builds a single file system from multiple gameinfo.txt files,
+/// the earlier ones taking priority over later ones.
+/// The file system is named after the first game.
+pub fn from_gameinfos(paths: Vec<&str>) -> PyResult<PyFileSystem> {
+    let mut paths = paths.into_iter();
+
+    let first = paths
+        .next()
+        .ok_or_else(|| PyValueError::new_err("no gameinfo.txt paths given"))?;
+    let mut file_system = file_system_from_gameinfo(first)?;
+
+    for path in paths {
+        mount(&mut file_system, &file_system_from_gameinfo(path)?);
+    }
+
+    Ok(file_system.into())
+}
+
+fn file_system_from_gameinfo(path: &str) -> PyResult<FileSystem> {
     let game_info_path = StdPath::new(path);
     let root_path = game_info_path
         .parent()
         .and_then(StdPath::parent)
         .ok_or_else(|| PyValueError::new_err("gameinfo.txt directory doesn't have a parent"))?;
 
-    let file_system = FileSystem::from_paths(root_path, game_info_path)
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+    FileSystem::from_paths(root_path, game_info_path).map_err(|e| PyIOError::new_err(e.to_string()))
+}
 
-    Ok(file_system.into())
+#[pyclass(module = "plumber", name = "Game")]
+pub struct PyGame {
+    app_id: u32,
+    game_info_path: Option<StdPathBuf>,
+    workshop_path: Option<StdPathBuf>,
+    file_system: FileSystem,
+}
+
+#[pymethods]
+impl PyGame {
+    fn app_id(&self) -> u32 {
+        self.app_id
+    }
+
+    fn name(&self) -> &str {
+        &self.file_system.name
+    }
+
+    fn game_info_path(&self) -> Option<&str> {
+        self.game_info_path.as_deref().and_then(StdPath::to_str)
+    }
+
+    fn workshop_path(&self) -> Option<&str> {
+        self.workshop_path.as_deref().and_then(StdPath::to_str)
+    }
+
+    fn search_paths(&self) -> PyResult<Vec<(&str, &str)>> {
+        self.file_system
+            .search_paths
+            .iter()
+            .map(from_search_path)
+            .collect()
+    }
+}
+
+impl PyGame {
+    fn new(app_id: u32, file_system: FileSystem) -> Self {
+        // the game directory containing gameinfo.txt is one of the search paths
+        let game_info_path = file_system.search_paths.iter().find_map(|p| match p {
+            SearchPath::Directory(path) => Some(path.join("gameinfo.txt")).filter(|p| p.is_file()),
+            _ => None,
+        });
+
+        // games are installed in steamapps/common/<game>/<game directory>,
+        // workshop items in steamapps/workshop/content/<app id>/<item id>
+        let workshop_path = game_info_path
+            .as_deref()
+            .and_then(|p| p.ancestors().nth(4))
+            .map(|steamapps| {
+                steamapps
+                    .join("workshop")
+                    .join("content")
+                    .join(app_id.to_string())
+            })
+            .filter(|p| p.is_dir());
+
+        Self {
+            app_id,
+            game_info_path,
+            workshop_path,
+            file_system,
+        }
+    }
 }
 
 #[pyclass(module = "plumber", name = "FileBrowser")]
@@ -344,6 +672,99 @@ impl PyFileBrowser {
 
         Ok(entries)
     }
+
+    fn read_file_text(&self, path: &str) -> PyResult<String> {
+        read_file_text(&self.file_system, path)
+    }
+
+    fn read_file_bytes(&self, path: &str) -> PyResult<Vec<u8>> {
+        read_file_bytes(&self.file_system, path)
+    }
+
+    fn file_exists(&self, path: &str) -> bool {
+        self.file_system.open_file(&GamePathBuf::from(path)).is_ok()
+    }
+
+    fn dir_exists(&self, path: &str) -> bool {
+        matches!(
+            self.file_system.read_dir(&GamePathBuf::from(path)).next(),
+            Some(Ok(_))
+        )
+    }
+
+    /// Lists the game paths of the files in `dir` with one of `extensions`, sorted.
+    /// All files are listed if `extensions` is empty.
+    fn list_files(
+        &self,
+        dir: &str,
+        extensions: Vec<&str>,
+        recursive: bool,
+    ) -> PyResult<Vec<String>> {
+        let mut files = Vec::new();
+        let mut directories = vec![GamePathBuf::from(dir)];
+
+        while let Some(directory) = directories.pop() {
+            for res in self.file_system.read_dir(&directory) {
+                let entry = res?;
+
+                match entry.entry_type() {
+                    DirEntryType::Directory if recursive => {
+                        directories.push(entry.path().to_path_buf());
+                    }
+                    DirEntryType::Directory => {}
+                    DirEntryType::File => {
+                        let name = entry.name().as_str();
+                        let matches = extensions.is_empty()
+                            || name.rsplit_once('.').map_or(false, |(_, extension)| {
+                                extensions.iter().any(|e| e.eq_ignore_ascii_case(extension))
+                            });
+
+                        if matches {
+                            files.push(entry.path().as_str().to_owned());
+                        }
+                    }
+                }
+            }
+        }
+
+        // the same file can be in multiple search paths
+        files.sort_unstable();
+        files.dedup();
+
+        Ok(files)
+    }
+}
+
+fn read_file_text(opened: &OpenFileSystem, path: &str) -> PyResult<String> {
+    let path = GamePathBuf::from(path);
+    let file = opened
+        .open_file(&path)
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+    let mut reader = BufReader::new(file);
+    let mut content = String::new();
+
+    reader
+        .read_to_string(&mut content)
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+    Ok(content)
+}
+
+fn read_file_bytes(opened: &OpenFileSystem, path: &str) -> PyResult<Vec<u8>> {
+    let path = GamePathBuf::from(path);
+    let file = opened
+        .open_file(&path)
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+    let mut reader = BufReader::new(file);
+    let mut content = Vec::new();
+
+    reader
+        .read_to_end(&mut content)
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+    Ok(content)
 }
 
 #[pyclass(module = "plumber", name = "FileBrowserEntry")]
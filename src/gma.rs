@@ -0,0 +1,284 @@
+//! Reading Garry's Mod `.gma` addon archives.
+//!
+//! The file system can't read from archives other than vpk, so addons are extracted
+//! into a cache directory once and mounted as a plain directory after that.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+};
+
+use tracing::info;
+
+use crate::extract_cache::{self, read_array, read_string, Archive};
+
+const GMA_MAGIC: &[u8; 4] = b"GMAD";
+const MAX_GMA_VERSION: u8 = 3;
+
+#[derive(Debug)]
+pub enum GmaError {
+    Io(StdPathBuf, io::Error),
+    InvalidMagic(StdPathBuf),
+    UnsupportedVersion(StdPathBuf, u8),
+    Corrupted(StdPathBuf),
+    InvalidEntryPath(StdPathBuf, String),
+}
+
+impl Display for GmaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "gma `{}`: {}", path.display(), err),
+            Self::InvalidMagic(path) => write!(f, "gma `{}`: not a gma file", path.display()),
+            Self::UnsupportedVersion(path, version) => {
+                write!(
+                    f,
+                    "gma `{}`: unsupported version {}",
+                    path.display(),
+                    version
+                )
+            }
+            Self::Corrupted(path) => {
+                write!(f, "gma `{}`: unexpected end of file", path.display())
+            }
+            Self::InvalidEntryPath(path, entry) => {
+                write!(f, "gma `{}`: invalid file path `{}`", path.display(), entry)
+            }
+        }
+    }
+}
+
+impl Error for GmaError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GmaEntry {
+    pub path: String,
+    pub size: u64,
+    pub crc: u32,
+    offset: u64,
+}
+
+/// An opened `.gma` archive with its file index read.
+#[derive(Debug)]
+pub struct GmaArchive {
+    path: StdPathBuf,
+    pub name: String,
+    pub entries: Vec<GmaEntry>,
+}
+
+impl GmaArchive {
+    /// Opens the archive at `path` and reads its file index.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read or isn't a valid `.gma` archive.
+    pub fn open(path: &StdPath) -> Result<Self, GmaError> {
+        let file = File::open(path).map_err(|e| GmaError::Io(path.to_path_buf(), e))?;
+        let mut reader = BufReader::new(file);
+
+        let (name, entries) = read_index(&mut reader).map_err(|e| match e {
+            ParseError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                GmaError::Corrupted(path.to_path_buf())
+            }
+            ParseError::Io(e) => GmaError::Io(path.to_path_buf(), e),
+            ParseError::InvalidMagic => GmaError::InvalidMagic(path.to_path_buf()),
+            ParseError::UnsupportedVersion(version) => {
+                GmaError::UnsupportedVersion(path.to_path_buf(), version)
+            }
+        })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            name,
+            entries,
+        })
+    }
+}
+
+impl Archive for GmaArchive {
+    type Entry = GmaEntry;
+    type Error = GmaError;
+
+    fn entries(&self) -> &[GmaEntry] {
+        &self.entries
+    }
+
+    fn entry_path(entry: &GmaEntry) -> &str {
+        &entry.path
+    }
+
+    fn copy_entry(&self, entry: &GmaEntry, writer: &mut impl Write) -> Result<(), GmaError> {
+        let mut file = File::open(&self.path).map_err(|e| self.io_error(e))?;
+        file.seek(SeekFrom::Start(entry.offset))
+            .map_err(|e| self.io_error(e))?;
+
+        let copied = io::copy(&mut file.take(entry.size), writer).map_err(|e| self.io_error(e))?;
+
+        if copied == entry.size {
+            Ok(())
+        } else {
+            Err(GmaError::Corrupted(self.path.clone()))
+        }
+    }
+
+    fn io_error(&self, err: io::Error) -> GmaError {
+        GmaError::Io(self.path.clone(), err)
+    }
+
+    fn invalid_entry_path(&self, entry: &GmaEntry) -> GmaError {
+        GmaError::InvalidEntryPath(self.path.clone(), entry.path.clone())
+    }
+}
+
+/// Extracts the addon at `path` into a subdirectory of `cache_directory`,
+/// and returns the subdirectory.
+/// Extraction is skipped if the addon was already extracted and hasn't changed since.
+///
+/// # Errors
+///
+/// Returns `Err` if the addon can't be read or extracted.
+pub fn extract_cached(path: &StdPath, cache_directory: &StdPath) -> Result<StdPathBuf, GmaError> {
+    extract_cache::extract_cached(
+        path,
+        cache_directory,
+        |e| GmaError::Io(path.to_path_buf(), e),
+        |target| {
+            let archive = GmaArchive::open(path)?;
+            info!(
+                "extracting addon `{}` into `{}`...",
+                archive.name,
+                target.display()
+            );
+
+            archive.extract(target)
+        },
+    )
+}
+
+#[derive(Debug)]
+enum ParseError {
+    Io(io::Error),
+    InvalidMagic,
+    UnsupportedVersion(u8),
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn read_index<R: BufRead + Seek>(reader: &mut R) -> Result<(String, Vec<GmaEntry>), ParseError> {
+    if &read_array::<4>(reader)? != GMA_MAGIC {
+        return Err(ParseError::InvalidMagic);
+    }
+
+    let [version] = read_array(reader)?;
+
+    if version > MAX_GMA_VERSION {
+        return Err(ParseError::UnsupportedVersion(version));
+    }
+
+    // steam id and timestamp
+    read_array::<16>(reader)?;
+
+    if version > 1 {
+        // required content, a list of strings terminated by an empty string
+        while !read_string(reader)?.is_empty() {}
+    }
+
+    let name = read_string(reader)?;
+    // description and author
+    read_string(reader)?;
+    read_string(reader)?;
+    // addon version
+    read_array::<4>(reader)?;
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    // entries are numbered starting from 1, 0 terminates the list
+    while u32::from_le_bytes(read_array(reader)?) != 0 {
+        let path = read_string(reader)?.to_ascii_lowercase();
+        let size = u64::from_le_bytes(read_array(reader)?);
+        let crc = u32::from_le_bytes(read_array(reader)?);
+
+        entries.push(GmaEntry {
+            path,
+            size,
+            crc,
+            offset,
+        });
+
+        offset += size;
+    }
+
+    // file data follows the index in the same order as the entries
+    let data_start = reader.stream_position()?;
+
+    for entry in &mut entries {
+        entry.offset += data_start;
+    }
+
+    Ok((name, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn build_gma(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(GMA_MAGIC);
+        bytes.push(3);
+        bytes.extend_from_slice(&[0; 16]);
+        bytes.extend_from_slice(b"\0");
+        bytes.extend_from_slice(b"test addon\0description\0author\0");
+        bytes.extend_from_slice(&1_i32.to_le_bytes());
+
+        for (i, (path, data)) in (1_u32..).zip(files) {
+            bytes.extend_from_slice(&i.to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&0_u32.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&0_u32.to_le_bytes());
+
+        for (_, data) in files {
+            bytes.extend_from_slice(data);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn reads_index() {
+        let bytes = build_gma(&[
+            ("materials/Test.vmt", b"LightmappedGeneric {}"),
+            ("models/test.mdl", b"IDST"),
+        ]);
+        let (name, entries) = read_index(&mut Cursor::new(&bytes)).unwrap();
+
+        assert_eq!(name, "test addon");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "materials/test.vmt");
+
+        let second = &entries[1];
+        let start = usize::try_from(second.offset).unwrap();
+        assert_eq!(&bytes[start..start + 4], b"IDST");
+    }
+
+    #[test]
+    fn rejects_invalid_magic() {
+        assert!(matches!(
+            read_index(&mut Cursor::new(b"VPK\0")),
+            Err(ParseError::InvalidMagic)
+        ));
+    }
+}
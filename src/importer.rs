@@ -1,16 +1,22 @@
 use std::{
+    cell::RefCell,
     path::{Path as StdPath, PathBuf as StdPathBuf},
     str::FromStr,
-    time::Instant,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
 };
 
 use crossbeam_channel::Receiver;
 use pyo3::{
+    create_exception,
     exceptions::{PyIOError, PyRuntimeError, PyTypeError},
     prelude::*,
     types::PyDict,
 };
-use tracing::{debug, debug_span, error, info};
+use tracing::{debug, error, info, info_span};
 
 use plumber_core::{
     asset_core::Executor,
@@ -18,20 +24,41 @@ use plumber_core::{
     asset_vmf::{BrushSetting, VmfConfig},
     asset_vtf::VtfConfig,
     fs::{GamePathBuf, OpenFileSystem, OpenSearchPath, PathBuf},
-    vmf::{
-        builder::{GeometrySettings, InvisibleSolids, MergeSolids},
-        vmf::Vmf,
-    },
+    vmf::builder::{GeometrySettings, InvisibleSolids, MergeSolids},
 };
 
 use crate::{
     asset::{
+        ain::AinConfig,
+        anim_event::AnimationEvents,
+        bounds::Bounds,
+        diagnostic::{PyDiagnostic, Severity},
+        displacement::DisplacementSettings,
+        goldsrc::GoldSrcConfig,
+        ik_chain::IkChains,
+        jigglebone::JiggleBones,
+        job::{AssetImportJob, UnifiedAssetConfig},
         material::{MaterialConfig, TextureFormat, TextureInterpolation},
+        model::{ModelBonesConfig, ModelPaths},
+        model_bounds::ModelBoundsStore,
+        nav::NavConfig,
+        profile,
+        prop_queue::PropQueue,
+        ragdoll::{physics_path, Ragdolls},
+        summary::SummaryBuilder,
+        vmap::VmapConfig,
+        vmf_entities::VmfEntities,
+        vmf_load::{load_vmf_bytes, MapFormat, VmfLoadSettings},
+        vmf_metadata::VmfMetadata,
         BlenderAssetHandler, HandlerSettings, Message,
     },
     filesystem::PyFileSystem,
+    log_bridge,
 };
 
+// raised when an asset fails to import in strict mode, the second argument is the asset's `Diagnostic`
+create_exception!(plumber, StrictImportError, PyRuntimeError);
+
 /// Helper struct for VMF-specific settings
 #[allow(clippy::struct_excessive_bools)]
 pub struct VmfSettings {
@@ -44,15 +71,42 @@ pub struct VmfSettings {
     pub import_props: bool,
     pub import_other_entities: bool,
     pub import_skybox: bool,
+    pub import_radar: bool,
     pub scale: f32,
+    /// Build each solid of brush entities separately, while merging the world.
+    pub separate_entity_solids: bool,
+    /// How the vmf is changed before it's parsed.
+    pub load: VmfLoadSettings,
 }
 
 #[pyclass(module = "plumber", name = "Importer")]
 pub struct PyImporter {
     material_config: MaterialConfig,
+    /// Config the import jobs are processed with.
+    unified_config: UnifiedAssetConfig,
     executor: Option<Executor<BlenderAssetHandler>>,
     receiver: Receiver<Message>,
     callback_obj: PyObject,
+    file_system_time: Duration,
+    strict: bool,
+    /// The asset error which aborted the import in strict mode.
+    failure: RefCell<Option<PyDiagnostic>>,
+    /// Models are only passed once across the imports of this importer.
+    props: RefCell<PropQueue>,
+    /// Vmf metadata stores of the handler, such as the lightmap scales brushes are built with.
+    vmf_metadata: VmfMetadata,
+    /// Entity data stores of the handler, such as the parents entities are sent with.
+    vmf_entities: VmfEntities,
+    /// Ragdolls of the handler, which models are sent with.
+    ragdolls: Arc<RwLock<Ragdolls>>,
+    /// Jiggle bones of the handler, which models are sent with.
+    jiggle_bones: Arc<RwLock<JiggleBones>>,
+    /// Ik chains of the handler, which models are sent with.
+    ik_chains: Arc<RwLock<IkChains>>,
+    /// Animation events of the handler, which models are sent with.
+    animation_events: Arc<RwLock<AnimationEvents>>,
+    /// Model positions and bounding boxes of the handler, which models are sent with.
+    model_bounds: Arc<RwLock<ModelBoundsStore>>,
 }
 
 #[pymethods]
@@ -76,9 +130,10 @@ impl PyImporter {
             .open()
             .map_err(|e| PyIOError::new_err(e.to_string()))?;
 
+        let file_system_time = start.elapsed();
         info!(
             "file system opened in {:.2} s",
-            start.elapsed().as_secs_f32()
+            file_system_time.as_secs_f32()
         );
 
         let settings = Self::extract_importer_wide_settings(kwargs)?;
@@ -87,9 +142,24 @@ impl PyImporter {
         let material_config = MaterialConfig {
             settings: settings.material,
         };
+        let import_lights = settings.import_lights;
+        let strict = settings.strict;
+        let props = PropQueue::with_models(settings.existing_assets.iter().cloned());
 
-        let (sender, receiver) = crossbeam_channel::bounded(256);
-        let handler = BlenderAssetHandler { sender, settings };
+        let (sender, receiver) = crossbeam_channel::bounded(settings.channel_capacity);
+        let handler = BlenderAssetHandler::new(sender, settings);
+        let unified_config = UnifiedAssetConfig {
+            material_config,
+            import_lights,
+            cancelled: handler.cancelled(),
+        };
+        let vmf_metadata = handler.vmf_metadata();
+        let vmf_entities = handler.vmf_entities();
+        let ragdolls = handler.ragdolls();
+        let jiggle_bones = handler.jiggle_bones();
+        let ik_chains = handler.ik_chains();
+        let animation_events = handler.animation_events();
+        let model_bounds = handler.model_bounds();
         let executor = Some(Executor::new_with_threads(
             handler,
             opened,
@@ -98,9 +168,21 @@ impl PyImporter {
 
         Ok(Self {
             material_config,
+            unified_config,
             executor,
             receiver,
             callback_obj,
+            file_system_time,
+            strict,
+            failure: RefCell::new(None),
+            props: RefCell::new(props),
+            vmf_metadata,
+            vmf_entities,
+            ragdolls,
+            jiggle_bones,
+            ik_chains,
+            animation_events,
+            model_bounds,
         })
     }
 
@@ -116,42 +198,53 @@ impl PyImporter {
 
         let vmf_settings = Self::extract_vmf_settings(kwargs)?;
 
-        let mut settings = VmfConfig::new(self.material_config);
-        settings.import_overlays = vmf_settings.import_overlays;
-        settings.import_props = vmf_settings.import_props;
-        settings.import_other_entities = vmf_settings.import_other_entities;
-        settings.import_skybox = vmf_settings.import_skybox;
-        settings.scale = vmf_settings.scale;
+        let start = Instant::now();
+        info!("importing vmf `{}`...", path);
 
-        let mut geometry_settings = GeometrySettings::default();
-        geometry_settings.epsilon(vmf_settings.epsilon);
-        geometry_settings.cut_threshold(vmf_settings.cut_threshold);
-        geometry_settings.merge_solids(vmf_settings.merge_solids);
-        geometry_settings.invisible_solids(vmf_settings.invisible_solids);
+        let jobs = self.vmf_jobs(&executor, path, from_game, &vmf_settings)?;
 
-        settings.brushes = if vmf_settings.import_brushes {
-            BrushSetting::Import(geometry_settings)
-        } else {
-            BrushSetting::Skip
-        };
+        executor.process_each(self.unified_config.clone(), jobs, || {
+            self.process_assets(py)
+        });
+
+        info!("vmf imported in {:.2} s", start.elapsed().as_secs_f32());
+
+        self.check_failure()
+    }
+
+    /// Imports several vmfs in one session, so they share the file system
+    /// and the materials, textures and models are only imported once.
+    #[args(paths, from_game, kwargs = "**")]
+    fn import_vmf_batch(
+        &mut self,
+        py: Python,
+        paths: Vec<&str>,
+        from_game: bool,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<()> {
+        let executor = self.consume()?;
+
+        let vmf_settings = Self::extract_vmf_settings(kwargs)?;
 
         let start = Instant::now();
-        info!("importing vmf `{}`...", path);
+        info!("importing {} vmf files...", paths.len());
 
-        let path: PathBuf = if from_game {
-            GamePathBuf::from(path).into()
-        } else {
-            StdPathBuf::from(path).into()
-        };
+        let mut jobs = Vec::new();
 
-        let bytes = executor.fs().read(&path)?;
-        let vmf = Vmf::from_bytes(&bytes).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        for path in paths {
+            jobs.extend(self.vmf_jobs(&executor, path, from_game, &vmf_settings)?);
+        }
 
-        executor.process(settings, vmf, || self.process_assets(py));
+        executor.process_each(self.unified_config.clone(), jobs, || {
+            self.process_assets(py)
+        });
 
-        info!("vmf imported in {:.2} s", start.elapsed().as_secs_f32());
+        info!(
+            "vmf batch imported in {:.2} s",
+            start.elapsed().as_secs_f32()
+        );
 
-        Ok(())
+        self.check_failure()
     }
 
     #[args(path, from_game, kwargs = "**")]
@@ -164,24 +257,19 @@ impl PyImporter {
     ) -> PyResult<()> {
         let executor = self.consume()?;
 
-        let path = if from_game {
-            GamePathBuf::from(path).into()
-        } else {
-            StdPathBuf::from(path).into()
-        };
-
+        let paths = Self::model_paths(path, from_game);
         let settings = self.mdl_settings(kwargs)?;
 
         let start = Instant::now();
-        info!("importing mdl `{}`...", path);
+        info!("importing mdl `{}`...", paths.model);
 
         executor
-            .depend_on(settings, path, || self.process_assets(py))
+            .depend_on(settings, paths, || self.process_assets(py))
             .map_err(|e| PyIOError::new_err(e.to_string()))?;
 
         info!("mdl imported in {:.2} s", start.elapsed().as_secs_f32());
 
-        Ok(())
+        self.check_failure()
     }
 
     fn import_vmt(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
@@ -202,7 +290,7 @@ impl PyImporter {
 
         info!("vmt imported in {:.2} s", start.elapsed().as_secs_f32());
 
-        Ok(())
+        self.check_failure()
     }
 
     fn import_vtf(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
@@ -221,7 +309,7 @@ impl PyImporter {
 
         info!("vtf imported in {:.2} s", start.elapsed().as_secs_f32());
 
-        Ok(())
+        self.check_failure()
     }
 
     #[args(paths, from_game, kwargs = "**")]
@@ -234,15 +322,9 @@ impl PyImporter {
     ) -> PyResult<()> {
         let executor = self.consume()?;
 
-        let paths: Vec<PathBuf> = paths
+        let paths: Vec<ModelPaths> = paths
             .into_iter()
-            .map(|path| {
-                if from_game {
-                    GamePathBuf::from(path).into()
-                } else {
-                    StdPathBuf::from(path).into()
-                }
-            })
+            .map(|path| Self::model_paths(path, from_game))
             .collect();
 
         let settings = self.mdl_settings(kwargs)?;
@@ -257,7 +339,7 @@ impl PyImporter {
             start.elapsed().as_secs_f32()
         );
 
-        Ok(())
+        self.check_failure()
     }
 
     fn import_vmt_batch(&mut self, py: Python, paths: Vec<&str>, from_game: bool) -> PyResult<()> {
@@ -284,7 +366,7 @@ impl PyImporter {
             start.elapsed().as_secs_f32()
         );
 
-        Ok(())
+        self.check_failure()
     }
 
     fn import_vtf_batch(&mut self, py: Python, paths: Vec<&str>, from_game: bool) -> PyResult<()> {
@@ -311,15 +393,98 @@ impl PyImporter {
             start.elapsed().as_secs_f32()
         );
 
-        Ok(())
+        self.check_failure()
+    }
+
+    fn import_nav(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
+        let executor = self.consume()?;
+
+        let path = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let start = Instant::now();
+        info!("importing nav `{}`...", path);
+
+        executor.process(NavConfig, path, || self.process_assets(py));
+
+        info!("nav imported in {:.2} s", start.elapsed().as_secs_f32());
+
+        self.check_failure()
+    }
+
+    fn import_ain(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
+        let executor = self.consume()?;
+
+        let path = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let start = Instant::now();
+        info!("importing node graph `{}`...", path);
+
+        executor.process(AinConfig, path, || self.process_assets(py));
+
+        info!(
+            "node graph imported in {:.2} s",
+            start.elapsed().as_secs_f32()
+        );
+
+        self.check_failure()
     }
 
-    fn import_assets(&mut self, py: Python) {
+    fn import_goldsrc_bsp(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
+        let executor = self.consume()?;
+
+        let path = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let start = Instant::now();
+        info!("importing goldsrc bsp `{}`...", path);
+
+        executor.process(GoldSrcConfig, path, || self.process_assets(py));
+
+        info!(
+            "goldsrc bsp imported in {:.2} s",
+            start.elapsed().as_secs_f32()
+        );
+
+        self.check_failure()
+    }
+
+    fn import_vmap(&mut self, py: Python, path: &str, from_game: bool) -> PyResult<()> {
+        let executor = self.consume()?;
+
+        let path = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let start = Instant::now();
+        info!("importing vmap `{}`...", path);
+
+        executor.process(VmapConfig, path, || self.process_assets(py));
+
+        info!("vmap imported in {:.2} s", start.elapsed().as_secs_f32());
+
+        self.check_failure()
+    }
+
+    fn import_assets(&mut self, py: Python) -> PyResult<()> {
         // drop the importer, causing the asset channel to disconnect
         // if we don't do this, process_assets will hang forever waiting for new assets to be sent
         self.executor = None;
 
         self.process_assets(py);
+        self.check_failure()
     }
 }
 
@@ -330,6 +495,72 @@ impl PyImporter {
             .ok_or_else(|| PyRuntimeError::new_err("Importer already consumed"))
     }
 
+    /// Reads and parses a vmf, returning the jobs for importing it and its overview.
+    fn vmf_jobs(
+        &self,
+        executor: &Executor<BlenderAssetHandler>,
+        path: &str,
+        from_game: bool,
+        vmf_settings: &VmfSettings,
+    ) -> PyResult<Vec<AssetImportJob>> {
+        let mut settings = VmfConfig::new(self.material_config);
+        settings.import_overlays = vmf_settings.import_overlays;
+        settings.import_props = vmf_settings.import_props;
+        settings.import_other_entities = vmf_settings.import_other_entities;
+        settings.import_skybox = vmf_settings.import_skybox;
+        settings.scale = vmf_settings.scale;
+
+        let mut geometry_settings = GeometrySettings::default();
+        geometry_settings.epsilon(vmf_settings.epsilon);
+        geometry_settings.cut_threshold(vmf_settings.cut_threshold);
+        geometry_settings.merge_solids(vmf_settings.merge_solids);
+        geometry_settings.invisible_solids(vmf_settings.invisible_solids);
+
+        settings.brushes = if vmf_settings.import_brushes {
+            BrushSetting::Import(geometry_settings)
+        } else {
+            BrushSetting::Skip
+        };
+
+        // overview files are looked up by the map name
+        let radar_map_name = StdPath::new(path)
+            .file_stem()
+            .filter(|_| vmf_settings.import_radar)
+            .map(|stem| stem.to_string_lossy().into_owned());
+
+        let format = MapFormat::from_path(path);
+
+        let path: PathBuf = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        let bytes = executor.fs().read(&path)?;
+        let (vmf, repairs) = load_vmf_bytes(
+            &bytes,
+            format,
+            &vmf_settings.load,
+            &self.vmf_metadata,
+            &self.vmf_entities,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        self.vmf_entities.add_solid_repairs(repairs);
+
+        let mut jobs = vec![AssetImportJob::LoadedVmf {
+            vmf,
+            config: settings,
+            separate_entity_solids: vmf_settings.separate_entity_solids,
+        }];
+
+        if let Some(map_name) = radar_map_name {
+            jobs.push(AssetImportJob::Radar { map_name });
+        }
+
+        Ok(jobs)
+    }
+
     /// Extract importer-wide settings (material + general settings)
     pub fn extract_importer_wide_settings(kwargs: Option<&PyDict>) -> PyResult<HandlerSettings> {
         let mut settings = HandlerSettings::default();
@@ -345,6 +576,10 @@ impl PyImporter {
                     // Material settings
                     "import_materials" => settings.material.import_materials = value.extract()?,
                     "simple_materials" => settings.material.simple_materials = value.extract()?,
+                    "pbr_estimation" => settings.material.pbr_estimation = value.extract()?,
+                    "envmap_mask_roughness" => {
+                        settings.material.envmap_mask_roughness = value.extract()?;
+                    }
                     "allow_culling" => settings.material.allow_culling = value.extract()?,
                     "editor_materials" => settings.material.editor_materials = value.extract()?,
                     "texture_format" => {
@@ -368,6 +603,25 @@ impl PyImporter {
                     "import_unknown_entities" => {
                         settings.import_unknown_entities = value.extract()?;
                     }
+                    "dependency_graph" => settings.dependency_graph = value.extract()?,
+                    "strict" => settings.strict = value.extract()?,
+                    "deduplicate_textures" => settings.deduplicate_textures = value.extract()?,
+                    "existing_assets" => {
+                        let names: Vec<String> = value.extract()?;
+                        settings.existing_assets =
+                            Arc::new(names.iter().map(|name| name.to_lowercase()).collect());
+                    }
+                    // an empty channel would make every asset wait for the consumer
+                    "channel_capacity" => {
+                        settings.channel_capacity = value.extract::<usize>()?.max(1);
+                    }
+                    "seam_tolerance" => settings.seam_tolerance = value.extract()?,
+                    "lightmap_uvs" => settings.lightmap_uvs = value.extract()?,
+                    "exact_vertices" => settings.exact_vertices = value.extract()?,
+                    "editor_groups" => settings.editor_groups = value.extract()?,
+                    "clean_faces" => settings.clean_faces = value.extract()?,
+                    "weld_distance" => settings.weld_distance = value.extract()?,
+                    "overlay_offset" => settings.overlay_offset = value.extract()?,
                     _ => {
                         check_unknown_keys(key_str)?;
                     }
@@ -451,7 +705,12 @@ impl PyImporter {
         let mut import_props = true;
         let mut import_other_entities = true;
         let mut import_skybox = true;
+        let mut import_radar = false;
         let mut scale = 1.0;
+        let mut displacements = DisplacementSettings::default();
+        let mut separate_entity_solids = false;
+        let mut repair_solids = false;
+        let mut bounds = None;
 
         if let Some(kwargs) = kwargs {
             for (key, value) in kwargs {
@@ -476,6 +735,10 @@ impl PyImporter {
                     "merge_solids" => match value.extract()? {
                         "MERGE" => merge_solids = MergeSolids::Merge,
                         "SEPARATE" => merge_solids = MergeSolids::Separate,
+                        "MERGE_WORLD" => {
+                            merge_solids = MergeSolids::Merge;
+                            separate_entity_solids = true;
+                        }
                         _ => return Err(PyTypeError::new_err("unexpected kwarg value")),
                     },
                     "invisible_solids" => match value.extract()? {
@@ -492,9 +755,25 @@ impl PyImporter {
                     "import_sky" => {
                         import_skybox = value.extract()?;
                     }
+                    "import_radar" => {
+                        import_radar = value.extract()?;
+                    }
                     "scale" => {
                         scale = value.extract()?;
                     }
+                    "max_displacement_power" => {
+                        displacements.max_power = value.extract()?;
+                    }
+                    "displacement_tolerance" => {
+                        displacements.tolerance = value.extract()?;
+                    }
+                    "repair_solids" => {
+                        repair_solids = value.extract()?;
+                    }
+                    "bounds" => {
+                        let (min, max): ([f32; 3], [f32; 3]) = value.extract()?;
+                        bounds = Some(Bounds::new(min, max));
+                    }
                     _ => {
                         check_unknown_keys(key_str)?;
                     }
@@ -512,7 +791,14 @@ impl PyImporter {
             import_props,
             import_other_entities,
             import_skybox,
+            import_radar,
             scale,
+            separate_entity_solids,
+            load: VmfLoadSettings {
+                displacements,
+                repair_solids,
+                bounds,
+            },
         })
     }
 
@@ -540,17 +826,56 @@ impl PyImporter {
     }
 
     fn process_assets(&self, py: Python) {
-        process_assets_with_callback(py, self.callback_obj.as_ref(py), &self.receiver);
+        let failure = process_assets_with_callback(
+            py,
+            self.callback_obj.as_ref(py),
+            &self.receiver,
+            &mut self.props.borrow_mut(),
+            self.file_system_time,
+            self.strict,
+            &self.unified_config.cancelled,
+        );
+
+        self.failure.replace(failure);
+    }
+
+    fn check_failure(&self) -> PyResult<()> {
+        match self.failure.take() {
+            Some(diagnostic) => Err(strict_import_error(diagnostic)),
+            None => Ok(()),
+        }
     }
 
-    fn mdl_settings(&self, kwargs: Option<&PyDict>) -> PyResult<MdlConfig<MaterialConfig>> {
+    /// Models are read along with their ragdoll, jiggle bones, ik chains, animation events
+    /// and bounds.
+    fn mdl_settings(&self, kwargs: Option<&PyDict>) -> PyResult<ModelBonesConfig> {
         let mut settings = MdlConfig::new(self.material_config);
 
         // Extract MDL settings
         let import_animations = Self::extract_mdl_settings(kwargs)?;
         settings.import_animations = import_animations;
 
-        Ok(settings)
+        Ok(ModelBonesConfig {
+            config: settings,
+            ragdolls: self.ragdolls.clone(),
+            jiggle_bones: self.jiggle_bones.clone(),
+            ik_chains: self.ik_chains.clone(),
+            animation_events: self.animation_events.clone(),
+            model_bounds: self.model_bounds.clone(),
+        })
+    }
+
+    fn model_paths(path: &str, from_game: bool) -> ModelPaths {
+        let model = if from_game {
+            GamePathBuf::from(path).into()
+        } else {
+            StdPathBuf::from(path).into()
+        };
+
+        ModelPaths {
+            model,
+            physics: physics_path(path, from_game),
+        }
     }
 }
 
@@ -597,42 +922,175 @@ fn detect_embedded_files_path(file_path_string: &str, opened: &mut OpenFileSyste
     }
 }
 
-/// Shared function to process assets with a callback
+/// Creates the error raised when an asset fails to import in strict mode.
+pub fn strict_import_error(diagnostic: PyDiagnostic) -> PyErr {
+    StrictImportError::new_err((format!("import aborted: {}", diagnostic.text), diagnostic))
+}
+
+/// Shared function to process assets with a callback.
+/// After all assets are received, a summary of the import is passed to the optional `summary` callback.
+///
+/// In strict mode, the first asset error stops passing assets to the callbacks and is returned.
+///
+/// If profiling is enabled, the profile of the import is logged after the summary.
+///
+/// Each model is only passed once per `props` session,
+/// and props are held back until their model has been passed.
 pub fn process_assets_with_callback(
     py: Python,
     callback_ref: &PyAny,
     receiver: &Receiver<Message>,
-) {
+    props: &mut PropQueue,
+    file_system_time: Duration,
+    strict: bool,
+    cancelled: &AtomicBool,
+) -> Option<PyDiagnostic> {
+    let mut summary = SummaryBuilder::new();
+    summary.stage("open file system", file_system_time);
+
+    let profiling = profile::is_enabled();
+    if profiling {
+        profile::start();
+    }
+
+    let mut failure = None;
+
     for asset in receiver {
-        let kind = asset.kind();
-        let id = asset.id();
-
-        let _asset_span = debug_span!("asset", kind, %id).entered();
-
-        let result = match asset {
-            Message::Material(material) => callback_ref.call_method1("material", (material,)),
-            Message::Texture(texture) => callback_ref.call_method1("texture", (texture,)),
-            Message::Model(model) => callback_ref.call_method1("model", (model,)),
-            Message::Brush(brush) => callback_ref.call_method1("brush", (brush,)),
-            Message::Overlay(overlay) => callback_ref.call_method1("overlay", (overlay,)),
-            Message::Prop(prop) => callback_ref.call_method1("prop", (prop,)),
-            Message::Light(light) => callback_ref.call_method1("light", (light,)),
-            Message::SpotLight(light) => callback_ref.call_method1("spot_light", (light,)),
-            Message::EnvLight(light) => callback_ref.call_method1("env_light", (light,)),
-            Message::SkyCamera(sky_camera) => {
-                callback_ref.call_method1("sky_camera", (sky_camera,))
+        log_bridge::flush(py);
+
+        // the assets sent before the import was cancelled still need to be received
+        // so the workers can finish
+        if failure.is_some() {
+            continue;
+        }
+
+        for asset in props.push(asset) {
+            failure = handle_asset(py, callback_ref, asset, strict, &mut summary);
+
+            if failure.is_some() {
+                // stops the workers from processing and sending the remaining assets
+                cancelled.store(true, Ordering::Relaxed);
+                break;
             }
-            Message::SkyEqui(sky_equi) => callback_ref.call_method1("sky_equi", (sky_equi,)),
-            Message::UnknownEntity(entity) => {
-                callback_ref.call_method1("unknown_entity", (entity,))
+        }
+    }
+
+    if failure.is_none() {
+        for prop in props.finish() {
+            failure = handle_asset(py, callback_ref, prop, strict, &mut summary);
+
+            if failure.is_some() {
+                break;
             }
-        };
+        }
+    } else {
+        // otherwise the props of the failed import would be passed with the next one
+        props.clear();
+    }
+
+    log_bridge::flush(py);
+
+    let summary = summary.finish();
+    info!(
+        "import summary: {} errors, {} warnings, {} cache hits",
+        summary.errors(),
+        summary.warnings(),
+        summary.cache_hits()
+    );
+
+    if profiling {
+        profile::finish().log();
+    }
+
+    // custom callback objects may not handle summaries
+    if !callback_ref.hasattr("summary").unwrap_or(false) {
+        return failure;
+    }
+
+    if let Err(err) = callback_ref.call_method1("summary", (summary,)) {
+        err.print(py);
+        error!("Import summary errored: {}", err);
+    }
 
-        if let Err(err) = result {
-            err.print(py);
-            error!("Asset importing errored: {}", err);
+    failure
+}
+
+/// Passes a received asset to its callback, returning the failure of the import in strict mode.
+fn handle_asset(
+    py: Python,
+    callback_ref: &PyAny,
+    asset: Message,
+    strict: bool,
+    summary: &mut SummaryBuilder,
+) -> Option<PyDiagnostic> {
+    let mut failure = None;
+
+    if let Message::Diagnostic(diagnostic) = &asset {
+        if strict && diagnostic.severity == Severity::Error {
+            failure = Some(diagnostic.clone());
+        }
+    }
+
+    let kind = asset.kind();
+    let id = asset.id();
+
+    let _asset_span = info_span!("blender callback", kind, %id).entered();
+
+    summary.record(&asset);
+    let callback_start = Instant::now();
+
+    let result = match asset {
+        Message::Material(material) => callback_ref.call_method1("material", (material,)),
+        Message::Texture(texture) => callback_ref.call_method1("texture", (texture,)),
+        Message::Model(model) => callback_ref.call_method1("model", (model,)),
+        Message::Brush(brush) => callback_ref.call_method1("brush", (brush,)),
+        Message::Overlay(overlay) => callback_ref.call_method1("overlay", (overlay,)),
+        Message::Decal(decal) => callback_ref.call_method1("decal", (decal,)),
+        Message::Prop(prop) => callback_ref.call_method1("prop", (prop,)),
+        Message::Light(light) => callback_ref.call_method1("light", (light,)),
+        Message::SpotLight(light) => callback_ref.call_method1("spot_light", (light,)),
+        Message::PointSpotlight(light) => callback_ref.call_method1("point_spotlight", (light,)),
+        Message::ProjectedTexture(light) => {
+            callback_ref.call_method1("projected_texture", (light,))
+        }
+        Message::EnvLight(light) => callback_ref.call_method1("env_light", (light,)),
+        Message::Sun(sun) => callback_ref.call_method1("sun", (sun,)),
+        Message::LightGlow(glow) => callback_ref.call_method1("light_glow", (glow,)),
+        Message::Smoke(smoke) => callback_ref.call_method1("smoke", (smoke,)),
+        Message::LightingOrigin(origin) => callback_ref.call_method1("lighting_origin", (origin,)),
+        Message::PlayerSpawn(spawn) => callback_ref.call_method1("player_spawn", (spawn,)),
+        Message::SkyCamera(sky_camera) => callback_ref.call_method1("sky_camera", (sky_camera,)),
+        Message::ColorCorrection(color_correction) => {
+            callback_ref.call_method1("color_correction", (color_correction,))
+        }
+        Message::PathChain(chain) => callback_ref.call_method1("path_chain", (chain,)),
+        Message::SkyEqui(sky_equi) => callback_ref.call_method1("sky_equi", (sky_equi,)),
+        Message::NavMesh(nav) => callback_ref.call_method1("nav_mesh", (nav,)),
+        Message::GoldSrcModel(model) => callback_ref.call_method1("goldsrc_model", (model,)),
+        Message::NodeGraph(graph) => callback_ref.call_method1("node_graph", (graph,)),
+        Message::Radar(radar) => callback_ref.call_method1("radar", (radar,)),
+        Message::UnknownEntity(entity) => callback_ref.call_method1("unknown_entity", (entity,)),
+        Message::Dependency(dependency) => callback_ref.call_method1("dependency", (dependency,)),
+        Message::Diagnostic(diagnostic) => callback_ref.call_method1("diagnostic", (diagnostic,)),
+    };
+
+    summary.record_callback(callback_start.elapsed());
+
+    if let Err(err) = result {
+        err.print(py);
+        error!("Asset importing errored: {}", err);
+
+        if strict {
+            failure = Some(PyDiagnostic::new(
+                Severity::Error,
+                kind,
+                Some(id.to_string()),
+                err,
+            ));
         }
     }
+
+    failure
 }
 
 /// Helper function to check if a key is unknown and return an error if it is
@@ -642,6 +1100,8 @@ pub fn check_unknown_keys(key: &str) -> PyResult<()> {
         // Material settings
         "import_materials",
         "simple_materials",
+        "pbr_estimation",
+        "envmap_mask_roughness",
         "allow_culling",
         "editor_materials",
         "texture_format",
@@ -656,6 +1116,11 @@ pub fn check_unknown_keys(key: &str) -> PyResult<()> {
         "import_props",
         "import_entities",
         "import_sky",
+        "import_radar",
+        "max_displacement_power",
+        "displacement_tolerance",
+        "repair_solids",
+        "bounds",
         "import_lights",
         "light_factor",
         "sun_factor",
@@ -664,6 +1129,18 @@ pub fn check_unknown_keys(key: &str) -> PyResult<()> {
         "sky_equi_height",
         "scale",
         "import_unknown_entities",
+        "dependency_graph",
+        "strict",
+        "deduplicate_textures",
+        "existing_assets",
+        "channel_capacity",
+        "seam_tolerance",
+        "lightmap_uvs",
+        "exact_vertices",
+        "editor_groups",
+        "clean_faces",
+        "weld_distance",
+        "overlay_offset",
         // MDL settings
         "import_animations",
         "remove_animations",
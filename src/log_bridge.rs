@@ -0,0 +1,139 @@
+//! Forwards tracing events into Python's `logging` module or a Python callback.
+//!
+//! Events can only be forwarded immediately on the Python thread, which holds the GIL
+//! whenever it runs Rust code. The asset processing threads can't wait for the GIL,
+//! since the Python thread holds it while waiting for their assets,
+//! so their events are queued until the Python thread flushes them.
+
+use std::{
+    fmt::{self, Write},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    thread::{self, ThreadId},
+};
+
+use pyo3::prelude::*;
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TARGET: Mutex<Option<PyObject>> = Mutex::new(None);
+static PENDING: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+static PYTHON_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Sets the object events are forwarded to, or disables forwarding with `None`.
+/// Objects with a `log` method, such as `logging.Logger`, are called with `log(level, message)`,
+/// other objects are called directly with `(level, message)`. Levels use the `logging` values.
+pub fn set_target(target: Option<PyObject>) {
+    PYTHON_THREAD.get_or_init(|| thread::current().id());
+
+    ENABLED.store(target.is_some(), Ordering::Relaxed);
+    *TARGET
+        .lock()
+        .expect("log target lock shouldn't be poisoned") = target;
+
+    if !ENABLED.load(Ordering::Relaxed) {
+        PENDING
+            .lock()
+            .expect("pending log lock shouldn't be poisoned")
+            .clear();
+    }
+}
+
+/// Forwards the events queued by other threads.
+pub fn flush(py: Python) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let pending = mem::take(
+        &mut *PENDING
+            .lock()
+            .expect("pending log lock shouldn't be poisoned"),
+    );
+
+    for (level, message) in pending {
+        forward(py, level, &message);
+    }
+}
+
+fn forward(py: Python, level: Level, message: &str) {
+    // the lock is released before calling into Python, which may log again
+    let target = match TARGET
+        .lock()
+        .expect("log target lock shouldn't be poisoned")
+        .as_ref()
+    {
+        Some(target) => target.clone_ref(py),
+        None => return,
+    };
+
+    let level = python_level(level);
+    let target = target.as_ref(py);
+
+    let result = if target.hasattr("log").unwrap_or(false) {
+        target.call_method1("log", (level, message))
+    } else {
+        target.call1((level, message))
+    };
+
+    // logging the error would forward it again
+    if let Err(err) = result {
+        err.print(py);
+    }
+}
+
+fn python_level(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 40,
+        Level::WARN => 30,
+        Level::INFO => 20,
+        Level::DEBUG => 10,
+        Level::TRACE => 5,
+    }
+}
+
+/// Tracing layer forwarding events to the Python log target, if one is set.
+pub struct PythonLogLayer;
+
+impl<S: Subscriber> Layer<S> for PythonLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        let level = *event.metadata().level();
+
+        if PYTHON_THREAD.get() == Some(&thread::current().id()) {
+            Python::with_gil(|py| {
+                flush(py);
+                forward(py, level, &visitor.0);
+            });
+        } else {
+            PENDING
+                .lock()
+                .expect("pending log lock shouldn't be poisoned")
+                .push((level, visitor.0));
+        }
+    }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Lists the files a map references without importing anything,
+//! so missing content can be found before a long import.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use plumber_core::{
+    asset_vmt::VmtHelper,
+    fs::{GamePathBuf, OpenFileSystem, PathBuf},
+    vmf::vmf::{Entity, Solid, Vmf},
+    vmt::TexturePath,
+};
+
+use crate::asset::entities::get_property;
+
+/// Vmt parameters that reference textures.
+const TEXTURE_PARAMETERS: &[&str] = &[
+    "$basetexture",
+    "$basetexture2",
+    "$bumpmap",
+    "$bumpmap2",
+    "$normalmap",
+    "$detail",
+    "$envmapmask",
+    "$blendmodulatetexture",
+    "$selfillummask",
+    "$phongexponenttexture",
+    "$lightwarptexture",
+    "$texture2",
+];
+
+const SKYBOX_SIDES: [&str; 6] = ["bk", "dn", "ft", "lf", "rt", "up"];
+
+const SOUND_EXTENSIONS: [&str; 2] = [".wav", ".mp3"];
+
+/// Characters that may prefix a sound path to control playback.
+const SOUND_CHARS: &[char] = &[
+    '*', '#', '@', '>', '<', '^', ')', '}', '$', '!', '?', '&', '~', '`',
+];
+
+/// Referenced files of a map, keyed by path with their found status.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    pub materials: BTreeMap<String, bool>,
+    pub textures: BTreeMap<String, bool>,
+    pub models: BTreeMap<String, bool>,
+    pub sounds: BTreeMap<String, bool>,
+}
+
+impl Manifest {
+    #[must_use]
+    pub fn new(vmf: &Vmf, file_system: &OpenFileSystem) -> Self {
+        let mut materials = BTreeSet::new();
+        let mut textures = BTreeSet::new();
+        let mut models = BTreeSet::new();
+        let mut sounds = BTreeSet::new();
+
+        add_solid_materials(&vmf.world.solids, &mut materials);
+
+        if let Some((_, sky_name)) = vmf
+            .world
+            .properties
+            .iter()
+            .find(|(k, _)| k.as_str().eq_ignore_ascii_case("skyname"))
+        {
+            for side in SKYBOX_SIDES {
+                materials.insert(material_path(&format!("skybox/{sky_name}{side}")));
+            }
+        }
+
+        for entity in &vmf.entities {
+            add_solid_materials(&entity.solids, &mut materials);
+            add_entity_files(
+                entity,
+                &mut materials,
+                &mut textures,
+                &mut models,
+                &mut sounds,
+            );
+        }
+
+        let materials = check_files(materials, file_system);
+
+        for (material, _) in materials.iter().filter(|(_, found)| **found) {
+            textures.extend(material_textures(material, file_system));
+        }
+
+        Self {
+            materials,
+            textures: check_files(textures, file_system),
+            models: check_files(models, file_system),
+            sounds: check_files(sounds, file_system),
+        }
+    }
+
+    /// Number of referenced files that weren't found.
+    #[must_use]
+    pub fn missing_count(&self) -> usize {
+        [&self.materials, &self.textures, &self.models, &self.sounds]
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .filter(|found| !**found)
+            .count()
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n");
+
+        let sections = [
+            ("materials", &self.materials),
+            ("textures", &self.textures),
+            ("models", &self.models),
+            ("sounds", &self.sounds),
+        ];
+
+        for (i, (name, files)) in sections.into_iter().enumerate() {
+            let _ = write!(json, "  \"{name}\": [");
+
+            for (j, (path, found)) in files.iter().enumerate() {
+                let separator = if j == 0 { "" } else { "," };
+                let _ = write!(
+                    json,
+                    "{separator}\n    {{\"path\": \"{}\", \"found\": {found}}}",
+                    escape_json(path)
+                );
+            }
+
+            if !files.is_empty() {
+                json.push_str("\n  ");
+            }
+
+            json.push_str(if i + 1 == sections.len() {
+                "]\n"
+            } else {
+                "],\n"
+            });
+        }
+
+        json.push('}');
+        json
+    }
+}
+
+fn add_solid_materials(solids: &[Solid], materials: &mut BTreeSet<String>) {
+    for side in solids.iter().flat_map(|s| &s.sides) {
+        materials.insert(material_path(side.material.as_str()));
+    }
+}
+
+fn add_entity_files(
+    entity: &Entity,
+    materials: &mut BTreeSet<String>,
+    textures: &mut BTreeSet<String>,
+    models: &mut BTreeSet<String>,
+    sounds: &mut BTreeSet<String>,
+) {
+    if let Some(model) = get_property(entity, "model").filter(|m| !m.is_empty()) {
+        let model = normalize_path(model);
+
+        // sprites reference materials instead of models
+        if model.ends_with(".vmt") || model.ends_with(".spr") {
+            materials.insert(material_path(&model));
+        } else if model.ends_with(".mdl") {
+            models.insert(model);
+        }
+    }
+
+    if entity.class_name == "info_overlay" {
+        if let Some(material) = get_property(entity, "material").filter(|m| !m.is_empty()) {
+            materials.insert(material_path(material));
+        }
+    }
+
+    if entity.class_name == "env_projectedtexture" {
+        if let Some(texture) = get_property(entity, "texturename").filter(|t| !t.is_empty()) {
+            textures.insert(texture_path(&format!("materials/{texture}")));
+        }
+    }
+
+    sounds.extend(
+        entity
+            .properties
+            .iter()
+            .filter_map(|(_, value)| sound_path(value)),
+    );
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim().replace('\\', "/").to_ascii_lowercase()
+}
+
+fn material_path(name: &str) -> String {
+    let mut path = normalize_path(name);
+
+    for extension in [".vmt", ".spr"] {
+        if let Some(len) = path.strip_suffix(extension).map(str::len) {
+            path.truncate(len);
+        }
+    }
+
+    if !path.starts_with("materials/") {
+        path.insert_str(0, "materials/");
+    }
+
+    path.push_str(".vmt");
+    path
+}
+
+fn texture_path(name: &str) -> String {
+    let mut path = normalize_path(name);
+
+    if let Some(len) = path.strip_suffix(".vtf").map(str::len) {
+        path.truncate(len);
+    }
+
+    path.push_str(".vtf");
+    path
+}
+
+fn sound_path(value: &str) -> Option<String> {
+    let path = normalize_path(value);
+
+    if !SOUND_EXTENSIONS.iter().any(|e| path.ends_with(e)) {
+        return None;
+    }
+
+    let path = path.trim_start_matches(SOUND_CHARS);
+
+    Some(if path.starts_with("sound/") {
+        path.to_owned()
+    } else {
+        format!("sound/{path}")
+    })
+}
+
+fn material_textures(material: &str, file_system: &OpenFileSystem) -> Vec<String> {
+    let Ok(vmt) = VmtHelper::new(&PathBuf::from(GamePathBuf::from(material)), file_system) else {
+        return Vec::new();
+    };
+
+    TEXTURE_PARAMETERS
+        .iter()
+        .filter_map(|parameter| vmt.extract_param::<TexturePath>(parameter))
+        .map(|texture| texture_path(texture.absolute_path().as_str()))
+        .collect()
+}
+
+fn check_files(paths: BTreeSet<String>, file_system: &OpenFileSystem) -> BTreeMap<String, bool> {
+    paths
+        .into_iter()
+        .map(|path| {
+            let found = file_system
+                .open_file(&GamePathBuf::from(path.as_str()))
+                .is_ok();
+            (path, found)
+        })
+        .collect()
+}
+
+pub(crate) fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_paths() {
+        assert_eq!(
+            material_path("TOOLS\\ToolsNodraw"),
+            "materials/tools/toolsnodraw.vmt"
+        );
+        assert_eq!(
+            material_path("sprites/glow01.spr"),
+            "materials/sprites/glow01.vmt"
+        );
+        assert_eq!(
+            material_path("materials/concrete/wall.vmt"),
+            "materials/concrete/wall.vmt"
+        );
+    }
+
+    #[test]
+    fn sound_paths() {
+        assert_eq!(
+            sound_path(")ambient/Machines/fan.wav"),
+            Some("sound/ambient/machines/fan.wav".to_owned())
+        );
+        assert_eq!(sound_path("Ambient.Fan"), None);
+    }
+
+    #[test]
+    fn manifest_json() {
+        let mut manifest = Manifest::default();
+        manifest
+            .materials
+            .insert("materials/a \"b\".vmt".to_owned(), true);
+        manifest.models.insert("models/c.mdl".to_owned(), false);
+
+        assert_eq!(
+            manifest.to_json(),
+            "{\n  \"materials\": [\n    {\"path\": \"materials/a \\\"b\\\".vmt\", \"found\": true}\n  ],\n  \"textures\": [],\n  \"models\": [\n    {\"path\": \"models/c.mdl\", \"found\": false}\n  ],\n  \"sounds\": []\n}"
+        );
+        assert_eq!(manifest.missing_count(), 1);
+    }
+}
@@ -0,0 +1,222 @@
+//! The `plumber` Python extension module used by the Blender addon.
+
+use std::fmt;
+
+use pyo3::prelude::*;
+use tracing::{error, info, Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{format, FmtContext, FormatEvent, FormatFields},
+    prelude::*,
+    registry::LookupSpan,
+};
+
+use crate::{
+    api::PyApiImporter,
+    asset::{
+        ain::PyNodeGraph,
+        anim_event::PyAnimationEvent,
+        brush::{
+            PyBrushMotion, PyBrushOrigin, PyBrushRender, PyBrushRotation, PyBuiltBrushEntity,
+            PyBuiltSolid, PyMergedSolids,
+        },
+        color_correction::PyColorCorrection,
+        decal::PyDecal,
+        dependency::PyDependency,
+        diagnostic::PyDiagnostic,
+        editor_groups::PyEditorGroups,
+        entities::{
+            PyEnvLight, PyLight, PyLightGlow, PyLightingOrigin, PyLoadedProp, PyPlayerSpawn,
+            PyPointSpotlight, PyProjectedTexture, PySkyCamera, PySmoke, PySpotLight, PySun,
+            PyUnknownEntity,
+        },
+        goldsrc::PyGoldSrcModel,
+        ik_chain::PyIkChain,
+        jigglebone::PyJiggleBone,
+        material::{
+            BuiltMaterialData, BuiltNode, BuiltNodeSocketRef, Material, Texture, TextureRef,
+        },
+        model::{
+            PyBoneAnimationData, PyBoneRestData, PyLoadedAnimation, PyLoadedBone, PyLoadedMesh,
+            PyModel, QuaternionData, VectorData,
+        },
+        nav::PyNavMesh,
+        overlay::PyBuiltOverlay,
+        path_chain::PyPathChain,
+        profile::{self, ProfileLayer},
+        radar::PyRadar,
+        ragdoll::PyRagdollJoint,
+        sky::PySkyEqui,
+        summary::{EventCounter, PySummary},
+    },
+    filesystem::{self, PyFileBrowser, PyFileBrowserEntry, PyFileSystem, PyGame},
+    importer::{PyImporter, StrictImportError},
+    log_bridge::{self, PythonLogLayer},
+    vmf_diff::VmfDiff,
+};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[pymodule]
+fn plumber(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyFileSystem>()?;
+    m.add_class::<PyFileBrowser>()?;
+    m.add_class::<PyFileBrowserEntry>()?;
+    m.add_class::<PyGame>()?;
+    m.add_class::<PyApiImporter>()?;
+    m.add_class::<PySkyEqui>()?;
+    m.add_class::<PyNavMesh>()?;
+    m.add_class::<PyGoldSrcModel>()?;
+    m.add_class::<PyNodeGraph>()?;
+    m.add_class::<PyRadar>()?;
+    m.add_class::<Texture>()?;
+    m.add_class::<Material>()?;
+    m.add_class::<BuiltMaterialData>()?;
+    m.add_class::<BuiltNode>()?;
+    m.add_class::<BuiltNodeSocketRef>()?;
+    m.add_class::<TextureRef>()?;
+    m.add_class::<QuaternionData>()?;
+    m.add_class::<VectorData>()?;
+    m.add_class::<PyBoneAnimationData>()?;
+    m.add_class::<PyBoneRestData>()?;
+    m.add_class::<PyLoadedAnimation>()?;
+    m.add_class::<PyLoadedBone>()?;
+    m.add_class::<PyLoadedMesh>()?;
+    m.add_class::<PyModel>()?;
+    m.add_class::<PyRagdollJoint>()?;
+    m.add_class::<PyJiggleBone>()?;
+    m.add_class::<PyIkChain>()?;
+    m.add_class::<PyAnimationEvent>()?;
+    m.add_class::<PyMergedSolids>()?;
+    m.add_class::<PyBuiltSolid>()?;
+    m.add_class::<PyBuiltBrushEntity>()?;
+    m.add_class::<PyBrushRender>()?;
+    m.add_class::<PyBrushOrigin>()?;
+    m.add_class::<PyBrushMotion>()?;
+    m.add_class::<PyBrushRotation>()?;
+    m.add_class::<PyBuiltOverlay>()?;
+    m.add_class::<PyDecal>()?;
+    m.add_class::<PyLoadedProp>()?;
+    m.add_class::<PyLight>()?;
+    m.add_class::<PySpotLight>()?;
+    m.add_class::<PyPointSpotlight>()?;
+    m.add_class::<PyProjectedTexture>()?;
+    m.add_class::<PyEnvLight>()?;
+    m.add_class::<PySun>()?;
+    m.add_class::<PyLightGlow>()?;
+    m.add_class::<PySmoke>()?;
+    m.add_class::<PyLightingOrigin>()?;
+    m.add_class::<PyPlayerSpawn>()?;
+    m.add_class::<PySkyCamera>()?;
+    m.add_class::<PyColorCorrection>()?;
+    m.add_class::<PyPathChain>()?;
+    m.add_class::<PyUnknownEntity>()?;
+    m.add_class::<PyDependency>()?;
+    m.add_class::<PyDiagnostic>()?;
+    m.add_class::<PyEditorGroups>()?;
+    m.add_class::<PySummary>()?;
+    m.add_class::<VmfDiff>()?;
+    m.add_class::<PyImporter>()?;
+    m.add("StrictImportError", py.get_type::<StrictImportError>())?;
+
+    #[pyfn(m)]
+    fn discover_filesystems() -> Vec<PyFileSystem> {
+        filesystem::discover()
+    }
+
+    #[pyfn(m)]
+    fn discover_games() -> Vec<PyGame> {
+        filesystem::discover_games()
+    }
+
+    #[pyfn(m)]
+    fn filesystem_from_gameinfo(path: &str) -> PyResult<PyFileSystem> {
+        filesystem::from_gameinfo(path)
+    }
+
+    #[pyfn(m)]
+    fn filesystem_from_gameinfos(paths: Vec<&str>) -> PyResult<PyFileSystem> {
+        filesystem::from_gameinfos(paths)
+    }
+
+    #[pyfn(m)]
+    fn log_error(error: &str) {
+        error!("{}", error);
+    }
+
+    #[pyfn(m)]
+    fn log_info(info: &str) {
+        info!("{}", info);
+    }
+
+    /// Forwards the log to `target`, either a `logging.Logger` or a callable taking the level
+    /// and message, or stops forwarding it with `None`.
+    #[pyfn(m)]
+    fn set_log_target(target: Option<PyObject>) {
+        log_bridge::set_target(target);
+    }
+
+    /// Enables logging a profile of the asset processing durations after each import.
+    #[pyfn(m)]
+    fn set_profiling(enabled: bool) {
+        profile::set_enabled(enabled);
+    }
+
+    #[pyfn(m)]
+    fn version() -> &'static str {
+        VERSION
+    }
+
+    initialize_logger();
+
+    Ok(())
+}
+
+struct PlumberLogFormatter;
+
+impl<S, N> FormatEvent<S, N> for PlumberLogFormatter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        // Format values from the event's's metadata:
+        let metadata = event.metadata();
+        write!(&mut writer, "[Plumber] [{}] ", metadata.level())?;
+
+        // Write fields on the event
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+
+        writeln!(writer)
+    }
+}
+
+fn initialize_logger() {
+    let layer = tracing_subscriber::fmt::layer().event_format(PlumberLogFormatter);
+
+    #[cfg(feature = "trace")]
+    {
+        let registry = tracing_subscriber::registry()
+            .with(tracing_tracy::TracyLayer::new())
+            .with(EventCounter)
+            .with(PythonLogLayer)
+            .with(ProfileLayer)
+            .with(layer);
+
+        let _ = tracing::subscriber::set_global_default(registry);
+    }
+
+    #[cfg(feature = "normal_logging")]
+    {
+        let registry = tracing_subscriber::registry()
+            .with(EventCounter)
+            .with(PythonLogLayer)
+            .with(ProfileLayer)
+            .with(layer);
+        let _ = tracing::subscriber::set_global_default(registry);
+    }
+}
@@ -0,0 +1,150 @@
+//! Compares two versions of a map, so only what changed in between has to be imported again.
+
+use std::collections::{HashMap, HashSet};
+
+use plumber_core::vmf::vmf::Vmf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Ids of the elements added, changed and removed between two versions of a list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdDiff {
+    pub added: Vec<i32>,
+    pub changed: Vec<i32>,
+    pub removed: Vec<i32>,
+}
+
+impl IdDiff {
+    fn new<T: PartialEq>(old: &[T], new: &[T], id: impl Fn(&T) -> i32) -> Self {
+        let mut old: HashMap<i32, &T> = old.iter().map(|element| (id(element), element)).collect();
+
+        let mut diff = Self::default();
+
+        for element in new {
+            let element_id = id(element);
+
+            match old.remove(&element_id) {
+                None => diff.added.push(element_id),
+                Some(old) if old != element => diff.changed.push(element_id),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = old.into_keys().collect();
+        diff.removed.sort_unstable();
+
+        diff
+    }
+
+    /// Ids of the elements which have to be imported.
+    fn imported(&self) -> HashSet<i32> {
+        self.added.iter().chain(&self.changed).copied().collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Differences between an old and a new version of a vmf, by solid and entity id.
+/// World solids and entities are compared separately, brush entities include their solids.
+#[cfg_attr(feature = "python", pyclass(module = "plumber", name = "VmfDiff"))]
+#[derive(Debug, Clone, Default)]
+pub struct VmfDiff {
+    pub solids: IdDiff,
+    pub entities: IdDiff,
+    /// Whether the world properties, such as the sky name, changed.
+    pub world_changed: bool,
+    /// Whether all world solids are imported again, since they are merged into one object.
+    pub world_solids_replaced: bool,
+}
+
+#[cfg_attr(feature = "python", pymethods)]
+impl VmfDiff {
+    pub fn added_solids(&self) -> Vec<i32> {
+        self.solids.added.clone()
+    }
+
+    pub fn changed_solids(&self) -> Vec<i32> {
+        self.solids.changed.clone()
+    }
+
+    pub fn removed_solids(&self) -> Vec<i32> {
+        self.solids.removed.clone()
+    }
+
+    pub fn added_entities(&self) -> Vec<i32> {
+        self.entities.added.clone()
+    }
+
+    pub fn changed_entities(&self) -> Vec<i32> {
+        self.entities.changed.clone()
+    }
+
+    pub fn removed_entities(&self) -> Vec<i32> {
+        self.entities.removed.clone()
+    }
+
+    pub fn world_changed(&self) -> bool {
+        self.world_changed
+    }
+
+    pub fn world_solids_replaced(&self) -> bool {
+        self.world_solids_replaced
+    }
+
+    /// Whether the two versions contain the same solids and entities.
+    pub fn is_empty(&self) -> bool {
+        self.solids.is_empty() && self.entities.is_empty() && !self.world_changed
+    }
+}
+
+impl VmfDiff {
+    pub fn new(old: &Vmf, new: &Vmf) -> Self {
+        Self {
+            solids: IdDiff::new(&old.world.solids, &new.world.solids, |solid| solid.id),
+            entities: IdDiff::new(&old.entities, &new.entities, |entity| entity.id),
+            world_changed: old.world.properties != new.world.properties,
+            world_solids_replaced: false,
+        }
+    }
+
+    /// Removes the solids and entities which didn't change from the new version of the vmf.
+    /// If the world solids are merged, a change to any of them keeps all of them, so the
+    /// merged object can replace the previous one.
+    pub fn retain_changes(&mut self, vmf: &mut Vmf, merged_world: bool) {
+        self.world_solids_replaced = merged_world && !self.solids.is_empty();
+
+        if !self.world_solids_replaced {
+            let solids = self.solids.imported();
+            vmf.world.solids.retain(|solid| solids.contains(&solid.id));
+        }
+
+        let entities = self.entities.imported();
+        vmf.entities.retain(|entity| entities.contains(&entity.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_by_id() {
+        let old = [(1, "a"), (2, "b"), (3, "c")];
+        let new = [(1, "a"), (2, "changed"), (4, "d")];
+
+        let diff = IdDiff::new(&old, &new, |&(id, _)| id);
+
+        assert_eq!(
+            diff,
+            IdDiff {
+                added: vec![4],
+                changed: vec![2],
+                removed: vec![3],
+            }
+        );
+        assert_eq!(diff.imported(), HashSet::from([2, 4]));
+    }
+}
@@ -0,0 +1,416 @@
+//! Reading `.vpk` archives the file system can't mount.
+//!
+//! The file system only mounts version 2 directory archives (`*_dir.vpk`).
+//! Version 1 archives and single file archives without a `_dir` suffix
+//! are extracted into a cache directory once and mounted as a plain directory after that.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
+};
+
+use tracing::info;
+
+use crate::extract_cache::{self, read_array, read_string, Archive};
+
+const VPK_SIGNATURE: u32 = 0x55aa_1234;
+const HEADER_SIZE_V1: u64 = 12;
+const HEADER_SIZE_V2: u64 = 28;
+/// The archive index of files stored in the directory file itself, after the tree.
+const INLINE_ARCHIVE: u16 = 0x7fff;
+const ENTRY_TERMINATOR: u16 = 0xffff;
+/// Used in the tree for files without an extension and files in the root directory.
+const EMPTY_NAME: &str = " ";
+
+#[derive(Debug)]
+pub enum VpkError {
+    Io(StdPathBuf, io::Error),
+    InvalidSignature(StdPathBuf),
+    UnsupportedVersion(StdPathBuf, u32),
+    Corrupted(StdPathBuf),
+    InvalidEntryPath(StdPathBuf, String),
+}
+
+impl Display for VpkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "vpk `{}`: {}", path.display(), err),
+            Self::InvalidSignature(path) => write!(f, "vpk `{}`: not a vpk file", path.display()),
+            Self::UnsupportedVersion(path, version) => {
+                write!(
+                    f,
+                    "vpk `{}`: unsupported version {}",
+                    path.display(),
+                    version
+                )
+            }
+            Self::Corrupted(path) => {
+                write!(f, "vpk `{}`: unexpected end of file", path.display())
+            }
+            Self::InvalidEntryPath(path, entry) => {
+                write!(f, "vpk `{}`: invalid file path `{}`", path.display(), entry)
+            }
+        }
+    }
+}
+
+impl Error for VpkError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VpkEntry {
+    pub path: String,
+    pub crc: u32,
+    preload: Vec<u8>,
+    archive_index: u16,
+    offset: u64,
+    length: u64,
+}
+
+/// An opened `.vpk` archive with its directory tree read.
+#[derive(Debug)]
+pub struct VpkArchive {
+    path: StdPathBuf,
+    pub version: u32,
+    /// Offset of the data of inline files in the directory file.
+    data_start: u64,
+    pub entries: Vec<VpkEntry>,
+}
+
+impl VpkArchive {
+    /// Opens the directory file at `path` and reads its file tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read or isn't a valid `.vpk` archive.
+    pub fn open(path: &StdPath) -> Result<Self, VpkError> {
+        let file = File::open(path).map_err(|e| VpkError::Io(path.to_path_buf(), e))?;
+        let mut reader = BufReader::new(file);
+
+        let (version, data_start, entries) = read_tree(&mut reader).map_err(|e| match e {
+            ParseError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                VpkError::Corrupted(path.to_path_buf())
+            }
+            ParseError::Io(e) => VpkError::Io(path.to_path_buf(), e),
+            ParseError::InvalidSignature => VpkError::InvalidSignature(path.to_path_buf()),
+            ParseError::UnsupportedVersion(version) => {
+                VpkError::UnsupportedVersion(path.to_path_buf(), version)
+            }
+        })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            version,
+            data_start,
+            entries,
+        })
+    }
+
+    /// Returns the path of the archive file containing the data of numbered archives,
+    /// such as `pak01_003.vpk` for `pak01_dir.vpk`.
+    fn archive_path(&self, index: u16) -> StdPathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map_or_else(Default::default, |s| s.to_string_lossy());
+        let prefix = stem.strip_suffix("_dir").unwrap_or(&stem);
+
+        self.path.with_file_name(format!("{prefix}_{index:03}.vpk"))
+    }
+}
+
+impl Archive for VpkArchive {
+    type Entry = VpkEntry;
+    type Error = VpkError;
+
+    fn entries(&self) -> &[VpkEntry] {
+        &self.entries
+    }
+
+    fn entry_path(entry: &VpkEntry) -> &str {
+        &entry.path
+    }
+
+    fn copy_entry(&self, entry: &VpkEntry, writer: &mut impl Write) -> Result<(), VpkError> {
+        writer
+            .write_all(&entry.preload)
+            .map_err(|e| io_error(&self.path, e))?;
+
+        if entry.length == 0 {
+            return Ok(());
+        }
+
+        let (archive_path, offset) = if entry.archive_index == INLINE_ARCHIVE {
+            (self.path.clone(), self.data_start + entry.offset)
+        } else {
+            (self.archive_path(entry.archive_index), entry.offset)
+        };
+
+        let mut file = File::open(&archive_path).map_err(|e| io_error(&archive_path, e))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| io_error(&archive_path, e))?;
+
+        let copied = io::copy(&mut file.take(entry.length), writer)
+            .map_err(|e| io_error(&archive_path, e))?;
+
+        if copied == entry.length {
+            Ok(())
+        } else {
+            Err(VpkError::Corrupted(archive_path))
+        }
+    }
+
+    fn io_error(&self, err: io::Error) -> VpkError {
+        io_error(&self.path, err)
+    }
+
+    fn invalid_entry_path(&self, entry: &VpkEntry) -> VpkError {
+        VpkError::InvalidEntryPath(self.path.clone(), entry.path.clone())
+    }
+}
+
+fn io_error(path: &StdPath, err: io::Error) -> VpkError {
+    VpkError::Io(path.to_path_buf(), err)
+}
+
+/// Returns if the archive at `path` has to be extracted before mounting,
+/// because it's a version 1 archive or a single file archive without a `_dir` suffix.
+/// Archives that can't be read are left for the file system to report.
+#[must_use]
+pub fn needs_extraction(path: &StdPath) -> bool {
+    let is_dir_archive = path
+        .file_stem()
+        .map_or(false, |s| s.to_string_lossy().ends_with("_dir"));
+
+    let version = File::open(path).and_then(|mut file| {
+        let signature = u32::from_le_bytes(read_array(&mut file)?);
+        let version = u32::from_le_bytes(read_array(&mut file)?);
+        Ok((signature == VPK_SIGNATURE).then_some(version))
+    });
+
+    match version {
+        Ok(Some(1)) => true,
+        Ok(Some(_)) => !is_dir_archive,
+        _ => false,
+    }
+}
+
+/// Extracts the archive at `path` into a subdirectory of `cache_directory`,
+/// and returns the subdirectory.
+/// Extraction is skipped if the archive was already extracted and hasn't changed since.
+///
+/// # Errors
+///
+/// Returns `Err` if the archive can't be read or extracted.
+pub fn extract_cached(path: &StdPath, cache_directory: &StdPath) -> Result<StdPathBuf, VpkError> {
+    extract_cache::extract_cached(
+        path,
+        cache_directory,
+        |e| io_error(path, e),
+        |target| {
+            let archive = VpkArchive::open(path)?;
+            info!(
+                "extracting version {} vpk `{}` into `{}`...",
+                archive.version,
+                path.display(),
+                target.display()
+            );
+
+            archive.extract(target)
+        },
+    )
+}
+
+#[derive(Debug)]
+enum ParseError {
+    Io(io::Error),
+    InvalidSignature,
+    UnsupportedVersion(u32),
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn read_tree<R: BufRead>(reader: &mut R) -> Result<(u32, u64, Vec<VpkEntry>), ParseError> {
+    if u32::from_le_bytes(read_array(reader)?) != VPK_SIGNATURE {
+        return Err(ParseError::InvalidSignature);
+    }
+
+    let version = u32::from_le_bytes(read_array(reader)?);
+    let tree_size = u64::from(u32::from_le_bytes(read_array(reader)?));
+
+    let header_size = match version {
+        1 => HEADER_SIZE_V1,
+        2 => {
+            // section sizes of the file data and the checksums
+            read_array::<16>(reader)?;
+            HEADER_SIZE_V2
+        }
+        _ => return Err(ParseError::UnsupportedVersion(version)),
+    };
+
+    let mut entries = Vec::new();
+
+    // the tree is grouped by extension, then directory, each list terminated by an empty string
+    loop {
+        let extension = read_string(reader)?;
+        if extension.is_empty() {
+            break;
+        }
+
+        loop {
+            let directory = read_string(reader)?;
+            if directory.is_empty() {
+                break;
+            }
+
+            loop {
+                let name = read_string(reader)?;
+                if name.is_empty() {
+                    break;
+                }
+
+                let crc = u32::from_le_bytes(read_array(reader)?);
+                let preload_len = u16::from_le_bytes(read_array(reader)?);
+                let archive_index = u16::from_le_bytes(read_array(reader)?);
+                let offset = u64::from(u32::from_le_bytes(read_array(reader)?));
+                let length = u64::from(u32::from_le_bytes(read_array(reader)?));
+
+                if u16::from_le_bytes(read_array(reader)?) != ENTRY_TERMINATOR {
+                    return Err(io::Error::from(io::ErrorKind::InvalidData).into());
+                }
+
+                let mut preload = vec![0; usize::from(preload_len)];
+                reader.read_exact(&mut preload)?;
+
+                entries.push(VpkEntry {
+                    path: entry_path(&directory, &name, &extension),
+                    crc,
+                    preload,
+                    archive_index,
+                    offset,
+                    length,
+                });
+            }
+        }
+    }
+
+    Ok((version, header_size + tree_size, entries))
+}
+
+fn entry_path(directory: &str, name: &str, extension: &str) -> String {
+    let mut path = String::new();
+
+    if directory != EMPTY_NAME {
+        path.push_str(directory);
+        path.push('/');
+    }
+
+    path.push_str(name);
+
+    if extension != EMPTY_NAME {
+        path.push('.');
+        path.push_str(extension);
+    }
+
+    path.to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Builds a version 1 archive with the files stored inline after the tree.
+    fn build_vpk(files: &[(&str, &str, &str, &[u8])]) -> Vec<u8> {
+        let mut tree = Vec::new();
+        let mut offset = 0_u32;
+
+        for (extension, directory, name, data) in files {
+            for s in [extension, directory, name] {
+                tree.extend_from_slice(s.as_bytes());
+                tree.push(0);
+            }
+
+            tree.extend_from_slice(&0_u32.to_le_bytes());
+            tree.extend_from_slice(&0_u16.to_le_bytes());
+            tree.extend_from_slice(&INLINE_ARCHIVE.to_le_bytes());
+            tree.extend_from_slice(&offset.to_le_bytes());
+            tree.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+            tree.extend_from_slice(&ENTRY_TERMINATOR.to_le_bytes());
+
+            // end of the name and directory lists
+            tree.extend_from_slice(&[0, 0]);
+            offset += u32::try_from(data.len()).unwrap();
+        }
+
+        tree.push(0);
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&VPK_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&1_u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::try_from(tree.len()).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&tree);
+
+        for (_, _, _, data) in files {
+            bytes.extend_from_slice(data);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn reads_v1_tree() {
+        let bytes = build_vpk(&[
+            (
+                "vmt",
+                "materials/dev",
+                "Dev_Floor",
+                b"LightmappedGeneric {}",
+            ),
+            (" ", " ", "readme", b"hi"),
+        ]);
+        let (version, data_start, entries) = read_tree(&mut Cursor::new(&bytes)).unwrap();
+
+        assert_eq!(version, 1);
+        assert_eq!(entries[0].path, "materials/dev/dev_floor.vmt");
+        assert_eq!(entries[1].path, "readme");
+
+        let second = &entries[1];
+        let start = usize::try_from(data_start + second.offset).unwrap();
+        assert_eq!(&bytes[start..start + 2], b"hi");
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let mut bytes = VPK_SIGNATURE.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&3_u32.to_le_bytes());
+        bytes.extend_from_slice(&0_u32.to_le_bytes());
+
+        assert!(matches!(
+            read_tree(&mut Cursor::new(&bytes)),
+            Err(ParseError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn names_numbered_archives() {
+        let archive = VpkArchive {
+            path: StdPathBuf::from("game/pak01_dir.vpk"),
+            version: 1,
+            data_start: 0,
+            entries: Vec::new(),
+        };
+
+        assert_eq!(
+            archive.archive_path(3),
+            StdPathBuf::from("game/pak01_003.vpk")
+        );
+    }
+}